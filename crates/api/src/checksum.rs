@@ -1,20 +1,28 @@
 //! File checksum utilities for validation
 
-use crate::error::DownloadError;
+use crate::error::{file_name, DownloadError, FileContext, FileOperation};
+use crate::manifest::DataFile;
+use sha1::Sha1;
 use sha2::{Digest, Sha256};
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Upper bound on hashing threads used by `verify_directory`
+const MAX_VERIFY_THREADS: usize = 4;
 
 /// Calculate SHA256 checksum of a file
 pub fn calculate_sha256(path: &Path) -> Result<String, DownloadError> {
-    let mut file = File::open(path).map_err(|e| DownloadError::IoError(e))?;
+    let mut file = File::open(path).file_context(FileOperation::Read, path)?;
 
     let mut hasher = Sha256::new();
     let mut buffer = vec![0; 8192]; // 8KB buffer
 
     loop {
-        let bytes_read = file.read(&mut buffer).map_err(|e| DownloadError::IoError(e))?;
+        let bytes_read = file.read(&mut buffer).file_context(FileOperation::Read, path)?;
         if bytes_read == 0 {
             break;
         }
@@ -33,6 +41,173 @@ pub fn calculate_sha256_bytes(data: &[u8]) -> String {
     format!("{:x}", result)
 }
 
+/// Calculate the git blob SHA-1 of a file
+///
+/// This is the `sha` GitHub reports for a file (and what the manifest keeps
+/// in `github_sha`): SHA-1 over `"blob <len>\0"` followed by the content.
+pub fn calculate_git_blob_sha(path: &Path) -> Result<String, DownloadError> {
+    let mut file = File::open(path).file_context(FileOperation::Read, path)?;
+    let len = file.metadata().file_context(FileOperation::Read, path)?.len();
+
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", len));
+    let mut buffer = vec![0; 8192];
+
+    loop {
+        let bytes_read = file.read(&mut buffer).file_context(FileOperation::Read, path)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Calculate the git blob SHA-1 of byte data
+pub fn calculate_git_blob_sha_bytes(data: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", data.len()));
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Async version of `calculate_sha256`
+///
+/// Hashing runs on tokio's blocking thread pool so large files don't stall
+/// the runtime. Output and errors are identical to the sync version.
+pub async fn calculate_sha256_async(path: &Path) -> Result<String, DownloadError> {
+    let path = path.to_path_buf();
+    run_blocking(move || calculate_sha256(&path)).await
+}
+
+/// Async version of `calculate_git_blob_sha`
+pub async fn calculate_git_blob_sha_async(path: &Path) -> Result<String, DownloadError> {
+    let path = path.to_path_buf();
+    run_blocking(move || calculate_git_blob_sha(&path)).await
+}
+
+/// Async version of `validate_checksum`
+pub async fn validate_checksum_async(path: &Path, expected: &str) -> Result<bool, DownloadError> {
+    let actual = calculate_sha256_async(path).await?;
+    Ok(actual.eq_ignore_ascii_case(expected))
+}
+
+/// Async version of `verify_checksum`
+pub async fn verify_checksum_async(path: &Path, expected: &str) -> Result<(), DownloadError> {
+    let actual = calculate_sha256_async(path).await?;
+
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(DownloadError::ChecksumMismatch {
+            file: file_name(path),
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+
+    Ok(())
+}
+
+/// Run blocking file work on tokio's blocking pool
+pub(crate) async fn run_blocking<T, F>(work: F) -> Result<T, DownloadError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, DownloadError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| DownloadError::DownloadFailed(format!("File task failed: {}", e)))?
+}
+
+/// Digests of everything written through a `HashingWriter`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digests {
+    /// Number of bytes written
+    pub bytes: u64,
+
+    /// SHA256 (hex)
+    pub sha256: String,
+
+    /// Git blob SHA-1 (hex), if requested and the length matched
+    pub git_blob_sha: Option<String>,
+}
+
+/// `Write` wrapper that hashes data as it passes through
+///
+/// Used to checksum downloads while they are written, instead of reading
+/// the file back afterwards.
+pub struct HashingWriter<W> {
+    inner: W,
+    sha256: Sha256,
+    git_blob: Option<(Sha1, u64)>,
+    bytes: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    /// Wrap `inner`, computing the SHA256 of everything written
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            sha256: Sha256::new(),
+            git_blob: None,
+            bytes: 0,
+        }
+    }
+
+    /// Also compute the git blob SHA of content that will be `len` bytes long
+    ///
+    /// The blob header includes the length, so it must be known up front.
+    /// If a different number of bytes ends up written, no blob SHA is
+    /// reported.
+    pub fn with_git_blob(mut self, len: u64) -> Self {
+        let mut hasher = Sha1::new();
+        hasher.update(format!("blob {}\0", len));
+        self.git_blob = Some((hasher, len));
+        self
+    }
+
+    /// Bytes written so far
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Stop hashing, returning the inner writer and the digests
+    pub fn finish(self) -> (W, Digests) {
+        let bytes = self.bytes;
+        let git_blob_sha = self
+            .git_blob
+            .filter(|(_, len)| *len == bytes)
+            .map(|(hasher, _)| format!("{:x}", hasher.finalize()));
+
+        let digests = Digests {
+            bytes,
+            sha256: format!("{:x}", self.sha256.finalize()),
+            git_blob_sha,
+        };
+
+        (self.inner, digests)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        let data = &buf[..written];
+
+        self.sha256.update(data);
+        if let Some((hasher, _)) = &mut self.git_blob {
+            hasher.update(data);
+        }
+        self.bytes += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 /// Validate file checksum against expected value
 pub fn validate_checksum(path: &Path, expected: &str) -> Result<bool, DownloadError> {
     let actual = calculate_sha256(path)?;
@@ -45,6 +220,7 @@ pub fn verify_checksum(path: &Path, expected: &str) -> Result<(), DownloadError>
 
     if !actual.eq_ignore_ascii_case(expected) {
         return Err(DownloadError::ChecksumMismatch {
+            file: file_name(path),
             expected: expected.to_string(),
             actual,
         });
@@ -53,6 +229,188 @@ pub fn verify_checksum(path: &Path, expected: &str) -> Result<(), DownloadError>
     Ok(())
 }
 
+/// Outcome of verifying a downloaded file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// Checksum matched the expected value
+    Verified,
+
+    /// No expected checksum was available
+    Unverified,
+}
+
+/// Verify a downloaded file, deleting it if the checksum doesn't match
+///
+/// An empty `expected` checksum means "unknown" and the file is accepted as
+/// `ChecksumStatus::Unverified`.
+pub fn verify_download(path: &Path, expected: &str) -> Result<ChecksumStatus, DownloadError> {
+    if expected.is_empty() {
+        return Ok(ChecksumStatus::Unverified);
+    }
+
+    verify_digest(path, expected, &calculate_sha256(path)?)
+}
+
+/// Like `verify_download`, for a file whose SHA256 was computed while writing it
+pub fn verify_digest(
+    path: &Path,
+    expected: &str,
+    actual: &str,
+) -> Result<ChecksumStatus, DownloadError> {
+    if expected.is_empty() {
+        return Ok(ChecksumStatus::Unverified);
+    }
+
+    if !actual.eq_ignore_ascii_case(expected) {
+        // Never leave a bad file behind for the parser to pick up
+        let _ = std::fs::remove_file(path);
+        return Err(DownloadError::ChecksumMismatch {
+            file: file_name(path),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        });
+    }
+
+    Ok(ChecksumStatus::Verified)
+}
+
+/// Result of checking one file in `verify_directory`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileVerification {
+    /// Present and matching the manifest (size and SHA256, when known)
+    Ok,
+
+    /// Present but different from the manifest
+    Mismatched {
+        /// Expected SHA256 (or size, if that already differed)
+        expected: String,
+        /// Actual value found on disk
+        actual: String,
+    },
+
+    /// Not present in the directory
+    Missing,
+}
+
+/// Outcome of verifying a data directory against the manifest
+#[derive(Debug, Clone)]
+pub struct DirectoryVerification {
+    /// Per-file results, in the order the files were given
+    pub files: Vec<(String, FileVerification)>,
+
+    /// Time spent hashing
+    pub elapsed: Duration,
+}
+
+impl DirectoryVerification {
+    /// Whether every file is present and matches
+    pub fn is_ok(&self) -> bool {
+        self.files.iter().all(|(_, result)| *result == FileVerification::Ok)
+    }
+
+    /// Names of files that differ from the manifest
+    pub fn mismatched(&self) -> Vec<&str> {
+        self.names_where(|result| matches!(result, FileVerification::Mismatched { .. }))
+    }
+
+    /// Names of files that are missing
+    pub fn missing(&self) -> Vec<&str> {
+        self.names_where(|result| *result == FileVerification::Missing)
+    }
+
+    fn names_where(&self, predicate: impl Fn(&FileVerification) -> bool) -> Vec<&str> {
+        self.files
+            .iter()
+            .filter(|(_, result)| predicate(result))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+/// Verify the files in `data_dir` against their manifest entries
+///
+/// Files are hashed in parallel on a small bounded set of threads, since
+/// each can be tens of megabytes. Files without a recorded SHA256 only need
+/// to exist (with the right size, if known).
+pub fn verify_directory(
+    data_dir: &Path,
+    files: &[DataFile],
+) -> Result<DirectoryVerification, DownloadError> {
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_VERIFY_THREADS);
+
+    verify_directory_with_threads(data_dir, files, threads)
+}
+
+fn verify_directory_with_threads(
+    data_dir: &Path,
+    files: &[DataFile],
+    threads: usize,
+) -> Result<DirectoryVerification, DownloadError> {
+    let started = Instant::now();
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<FileVerification, DownloadError>>>> =
+        Mutex::new((0..files.len()).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, files.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(index) else {
+                    break;
+                };
+
+                let result = verify_file(data_dir, file);
+                results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+            });
+        }
+    });
+
+    let results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    let mut verified = Vec::with_capacity(files.len());
+
+    for (file, result) in files.iter().zip(results) {
+        let result = result.expect("every file is verified")?;
+        verified.push((file.name.clone(), result));
+    }
+
+    Ok(DirectoryVerification {
+        files: verified,
+        elapsed: started.elapsed(),
+    })
+}
+
+fn verify_file(data_dir: &Path, file: &DataFile) -> Result<FileVerification, DownloadError> {
+    let path = data_dir.join(&file.name);
+
+    let metadata = match std::fs::metadata(&path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return Ok(FileVerification::Missing),
+    };
+
+    if file.size > 0 && metadata.len() != file.size {
+        return Ok(FileVerification::Mismatched {
+            expected: format!("{} bytes", file.size),
+            actual: format!("{} bytes", metadata.len()),
+        });
+    }
+
+    if !file.has_checksum() {
+        return Ok(FileVerification::Ok);
+    }
+
+    let actual = calculate_sha256(&path)?;
+    if actual.eq_ignore_ascii_case(&file.sha256) {
+        Ok(FileVerification::Ok)
+    } else {
+        Ok(FileVerification::Mismatched {
+            expected: file.sha256.clone(),
+            actual,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,6 +456,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_git_blob_sha_bytes() {
+        // Values from `git hash-object`
+        assert_eq!(
+            calculate_git_blob_sha_bytes(b""),
+            "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+        );
+        assert_eq!(
+            calculate_git_blob_sha_bytes(b"hello\n"),
+            "ce013625030ba8dba906f756967f9e9ca394464a"
+        );
+    }
+
+    #[test]
+    fn test_git_blob_sha_file() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"hello\n").unwrap();
+        temp_file.flush().unwrap();
+
+        assert_eq!(
+            calculate_git_blob_sha(temp_file.path()).unwrap(),
+            "ce013625030ba8dba906f756967f9e9ca394464a"
+        );
+
+        // Larger than the read buffer
+        let data = vec![0x42; 20_000];
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&data).unwrap();
+        temp_file.flush().unwrap();
+
+        assert_eq!(
+            calculate_git_blob_sha(temp_file.path()).unwrap(),
+            calculate_git_blob_sha_bytes(&data)
+        );
+    }
+
     #[test]
     fn test_validate_checksum_match() {
         let mut temp_file = NamedTempFile::new().unwrap();
@@ -158,7 +552,7 @@ mod tests {
 
         assert!(result.is_err());
 
-        if let Err(DownloadError::ChecksumMismatch { expected, actual }) = result {
+        if let Err(DownloadError::ChecksumMismatch { expected, actual, .. }) = result {
             assert_eq!(expected, wrong_checksum);
             assert_eq!(
                 actual,
@@ -185,4 +579,212 @@ mod tests {
         assert!(checksum.is_ok());
         assert_eq!(checksum.unwrap().len(), 64); // SHA256 is 64 hex chars
     }
+
+    #[test]
+    fn test_verify_download_match() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"Test data").unwrap();
+        temp_file.flush().unwrap();
+
+        let expected = "e27c8214be8b7cf5bccc7c08247e3cb0c1514a48ee1f63197fe4ef3ef51d7e6f";
+        let status = verify_download(temp_file.path(), expected).unwrap();
+
+        assert_eq!(status, ChecksumStatus::Verified);
+        assert!(temp_file.path().exists());
+    }
+
+    #[test]
+    fn test_verify_download_without_checksum() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"Test data").unwrap();
+        temp_file.flush().unwrap();
+
+        let status = verify_download(temp_file.path(), "").unwrap();
+
+        assert_eq!(status, ChecksumStatus::Unverified);
+        assert!(temp_file.path().exists());
+    }
+
+    #[test]
+    fn test_hashing_writer_matches_file_checksum() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("streamed.zip");
+
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut writer = HashingWriter::new(File::create(&path).unwrap())
+            .with_git_blob(data.len() as u64);
+
+        // Odd-sized chunks, like a network body
+        for chunk in data.chunks(4093) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.flush().unwrap();
+        let (_, digests) = writer.finish();
+
+        assert_eq!(digests.bytes, data.len() as u64);
+        assert_eq!(digests.sha256, calculate_sha256(&path).unwrap());
+        assert_eq!(
+            digests.git_blob_sha.as_deref(),
+            Some(calculate_git_blob_sha(&path).unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn test_hashing_writer_git_blob_length_mismatch() {
+        let mut writer = HashingWriter::new(Vec::new()).with_git_blob(10);
+        writer.write_all(b"hello\n").unwrap();
+
+        let (written, digests) = writer.finish();
+
+        assert_eq!(written, b"hello\n");
+        assert_eq!(digests.sha256, calculate_sha256_bytes(b"hello\n"));
+        assert_eq!(digests.git_blob_sha, None);
+    }
+
+    #[test]
+    fn test_verify_digest_mismatch_deletes_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("bad.zip");
+        std::fs::write(&path, b"Test data").unwrap();
+
+        let actual = calculate_sha256_bytes(b"Test data");
+        assert_eq!(
+            verify_digest(&path, &actual, &actual).unwrap(),
+            ChecksumStatus::Verified
+        );
+
+        let result = verify_digest(&path, &"0".repeat(64), &actual);
+        assert!(matches!(result, Err(DownloadError::ChecksumMismatch { .. })));
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_async_checksums_match_sync() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let small = temp_dir.path().join("small.lua");
+        std::fs::write(&small, b"Test data").unwrap();
+
+        let large = temp_dir.path().join("large.zip");
+        let data: Vec<u8> = (0..5_000_000u32).map(|i| (i % 241) as u8).collect();
+        std::fs::write(&large, &data).unwrap();
+
+        for path in [&small, &large] {
+            let sha256 = calculate_sha256(path).unwrap();
+
+            assert_eq!(calculate_sha256_async(path).await.unwrap(), sha256);
+            assert_eq!(
+                calculate_git_blob_sha_async(path).await.unwrap(),
+                calculate_git_blob_sha(path).unwrap()
+            );
+            assert!(validate_checksum_async(path, &sha256).await.unwrap());
+            assert!(verify_checksum_async(path, &sha256.to_uppercase()).await.is_ok());
+        }
+
+        let wrong = "0".repeat(64);
+        match verify_checksum_async(&small, &wrong).await {
+            Err(DownloadError::ChecksumMismatch { file, expected, actual }) => {
+                assert_eq!(file, small.file_name().unwrap().to_str().unwrap());
+                assert_eq!(expected, wrong);
+                assert_eq!(actual, calculate_sha256(&small).unwrap());
+            }
+            other => panic!("Expected ChecksumMismatch, got {:?}", other),
+        }
+
+        let missing = temp_dir.path().join("missing.zip");
+        assert!(matches!(
+            calculate_sha256_async(&missing).await,
+            Err(DownloadError::IoError { operation: FileOperation::Read, .. })
+        ));
+        let error = calculate_sha256(&missing).unwrap_err();
+        assert!(error.to_string().contains("missing.zip"), "{}", error);
+    }
+
+    fn data_file(name: &str, data: &[u8]) -> DataFile {
+        DataFile {
+            name: name.to_string(),
+            url: String::new(),
+            sha256: calculate_sha256_bytes(data),
+            github_sha: String::new(),
+            size: data.len() as u64,
+            required: true,
+            description: String::new(),
+            parts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_verify_directory_outcomes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+
+        std::fs::write(dir.join("good.zip"), b"good data").unwrap();
+        std::fs::write(dir.join("bad.zip"), b"tampered!").unwrap();
+        std::fs::write(dir.join("short.zip"), b"short").unwrap();
+
+        let mut unverified = data_file("unverified.lua", b"anything");
+        unverified.sha256.clear();
+        unverified.size = 0;
+        std::fs::write(dir.join("unverified.lua"), b"whatever").unwrap();
+
+        let files = vec![
+            data_file("good.zip", b"good data"),
+            data_file("bad.zip", b"bad data!"),
+            data_file("short.zip", b"longer data"),
+            data_file("missing.zip", b"missing"),
+            unverified,
+        ];
+
+        let verification = verify_directory(dir, &files).unwrap();
+
+        assert!(!verification.is_ok());
+        assert_eq!(verification.mismatched(), vec!["bad.zip", "short.zip"]);
+        assert_eq!(verification.missing(), vec!["missing.zip"]);
+        assert_eq!(verification.files[0].1, FileVerification::Ok);
+        assert_eq!(verification.files[4].1, FileVerification::Ok);
+        assert_eq!(
+            verification.files[2].1,
+            FileVerification::Mismatched {
+                expected: "11 bytes".to_string(),
+                actual: "5 bytes".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_directory_parallel_matches_sequential() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+
+        let files: Vec<DataFile> = (0..12)
+            .map(|i| {
+                let data = vec![i as u8; 100_000 + i * 1000];
+                std::fs::write(dir.join(format!("file{}.zip", i)), &data).unwrap();
+
+                // Every third file is corrupted on disk
+                let expected = if i % 3 == 0 { vec![0xff; data.len()] } else { data };
+                data_file(&format!("file{}.zip", i), &expected)
+            })
+            .collect();
+
+        let sequential = verify_directory_with_threads(dir, &files, 1).unwrap();
+        let parallel = verify_directory_with_threads(dir, &files, 4).unwrap();
+
+        assert_eq!(sequential.files, parallel.files);
+        assert_eq!(parallel.mismatched(), vec!["file0.zip", "file3.zip", "file6.zip", "file9.zip"]);
+        assert!(verify_directory(dir, &[]).unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_verify_download_mismatch_deletes_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("bad.zip");
+        std::fs::write(&path, b"Test data").unwrap();
+
+        let wrong_checksum = "0000000000000000000000000000000000000000000000000000000000000000";
+        let result = verify_download(&path, wrong_checksum);
+
+        assert!(matches!(result, Err(DownloadError::ChecksumMismatch { .. })));
+        assert!(!path.exists());
+    }
 }
@@ -0,0 +1,107 @@
+//! Free space checks before downloading and parsing
+//!
+//! The data files and the LUT parsed from them take several hundred MB.
+//! Running out of space half way through fails with an I/O error about
+//! whichever file happened to be written, so the space needed is estimated
+//! and compared with what the volume has left before anything is written.
+
+use std::io;
+use std::path::Path;
+
+use tracing::warn;
+
+use crate::error::DownloadError;
+
+/// Headroom on top of the download size, for files that grew upstream
+/// since their sizes were recorded and for filesystem overhead
+pub const SAFETY_FACTOR: f64 = 1.25;
+
+/// Parsed LUT JSON size relative to the compressed data it comes from
+pub const PARSED_SIZE_FACTOR: u64 = 4;
+
+/// Looks up the space available to the current user on `path`'s volume
+///
+/// `available_space` in normal use; tests substitute a fixed answer.
+pub type SpaceProbe = fn(&Path) -> io::Result<u64>;
+
+/// Bytes needed to download `download_bytes` and write a `parsed_bytes` artifact
+pub fn required_space(download_bytes: u64, parsed_bytes: u64) -> u64 {
+    let download = (download_bytes as f64 * SAFETY_FACTOR).ceil() as u64;
+    download.saturating_add(parsed_bytes)
+}
+
+/// Expected size of the LUT parsed from `data_bytes` of data files
+pub fn estimated_artifact_size(data_bytes: u64) -> u64 {
+    data_bytes.saturating_mul(PARSED_SIZE_FACTOR)
+}
+
+/// Space available on the volume `path` is (or would be created) on
+///
+/// Missing directories are looked up through their nearest existing
+/// ancestor, so a data directory can be checked before it is created.
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    let existing = path.ancestors().find(|dir| dir.exists()).unwrap_or(path);
+    fs4::available_space(existing)
+}
+
+/// Fail with `DownloadError::InsufficientSpace` if `path`'s volume has less
+/// than `needed` bytes free
+///
+/// A volume whose free space can't be determined passes, with a warning: the
+/// download may still fit, and failing would leave no way to proceed.
+pub(crate) fn ensure_space(
+    probe: SpaceProbe,
+    path: &Path,
+    needed: u64,
+) -> Result<(), DownloadError> {
+    match probe(path) {
+        Ok(available) if available < needed => {
+            Err(DownloadError::InsufficientSpace { needed, available })
+        }
+        Ok(_) => Ok(()),
+        Err(e) => {
+            warn!("Could not check free space for {}: {}", path.display(), e);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_required_space_adds_headroom_and_artifact() {
+        assert_eq!(required_space(0, 0), 0);
+        assert_eq!(required_space(100, 0), 125);
+        assert_eq!(required_space(100, estimated_artifact_size(100)), 525);
+        assert_eq!(required_space(u64::MAX, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_ensure_space_compares_with_probe() {
+        let path = Path::new("data");
+
+        assert!(ensure_space(|_| Ok(1000), path, 1000).is_ok());
+        match ensure_space(|_| Ok(999), path, 1000) {
+            Err(DownloadError::InsufficientSpace { needed, available }) => {
+                assert_eq!((needed, available), (1000, 999));
+            }
+            other => panic!("Expected insufficient space, got {:?}", other),
+        }
+
+        // Unknown free space doesn't block the download
+        let unknown: SpaceProbe = |_| Err(io::Error::other("unsupported"));
+        assert!(ensure_space(unknown, path, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_available_space_of_missing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("not").join("created");
+
+        assert!(available_space(&missing).unwrap() > 0);
+        assert!(!missing.exists());
+    }
+}
@@ -1,104 +1,1351 @@
 //! Data downloader for LUT files
 
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
 use reqwest;
+use tracing::{debug, info, warn};
 
-use crate::error::DownloadError;
+use crate::checksum::{self, ChecksumStatus, Digests, HashingWriter};
+use crate::disk_space::{self, SpaceProbe};
+use crate::error::{DownloadError, FileContext, FileOperation};
+use crate::http_cache::{CacheValidators, HttpCache};
+use crate::http_config::HttpConfig;
+use crate::http_util;
+use crate::layout::{DataLayout, MANIFEST_FILE};
+use crate::manifest::{DataFile, DataManifest, ManifestDiff};
+use crate::parser::LuaParser;
+use crate::sources::{DownloadSource, SourceLocation};
+use crate::update_checker::ChangedFile;
+use crate::validation::{self, ValidationResult};
+
+pub use crate::http_util::RetryPolicy;
+pub use tokio_util::sync::CancellationToken;
+
+/// Progress events emitted while downloading
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadEvent {
+    /// A file download has started
+    FileStarted {
+        current: usize,
+        total: usize,
+        file_name: String,
+    },
+
+    /// Bytes received for the file currently downloading
+    Progress(ProgressEvent),
+
+    /// A file download failed transiently and will be retried
+    Retrying {
+        file_name: String,
+        attempt: u32,
+        max_attempts: u32,
+        reason: String,
+    },
+
+    /// A file was downloaded and verified
+    FileCompleted { file_name: String, bytes: u64 },
+
+    /// The server reported the existing file is unchanged (HTTP 304)
+    NotModified { file_name: String },
+
+    /// Non-fatal problem (e.g., a file without a known checksum)
+    Warning(String),
+}
+
+/// Byte-level progress for a single file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressEvent {
+    /// File being downloaded
+    pub file: String,
+
+    /// Bytes written so far
+    pub bytes_downloaded: u64,
+
+    /// Total size from Content-Length, if the server sent it
+    pub total_bytes: Option<u64>,
+}
+
+impl ProgressEvent {
+    /// Fraction complete (0.0 - 1.0), if the total size is known
+    pub fn fraction(&self) -> Option<f32> {
+        match self.total_bytes {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.bytes_downloaded as f64 / total as f64) as f32),
+            None => None,
+        }
+    }
+}
+
+/// Result of fetching a single file
+enum Fetched {
+    /// New contents were downloaded and saved
+    Downloaded {
+        path: PathBuf,
+        validators: CacheValidators,
+        digests: Digests,
+    },
+
+    /// The server confirmed the existing file is current
+    NotModified,
+}
+
+/// A successful download attempt
+enum Attempt {
+    Downloaded {
+        digests: Digests,
+        validators: CacheValidators,
+    },
+    NotModified,
+}
+
+/// A failed download attempt
+struct AttemptFailure {
+    error: DownloadError,
+    retryable: bool,
+}
+
+impl AttemptFailure {
+    fn retryable(error: DownloadError) -> Self {
+        Self { error, retryable: true }
+    }
+
+    fn fatal(error: DownloadError) -> Self {
+        Self { error, retryable: false }
+    }
+
+    /// Classify a reqwest error: connection problems and timeouts are transient
+    fn from_reqwest(error: reqwest::Error, file_name: &str) -> Self {
+        Self {
+            retryable: http_util::is_transient(&error),
+            error: DownloadError::file_failed(FileOperation::Download, file_name, error),
+        }
+    }
+
+    /// The server sent nothing for `timeout`; worth retrying like a timeout
+    fn stalled(timeout: Duration, file_name: &str) -> Self {
+        let reason = format!("no data received for {:.1}s", timeout.as_secs_f64());
+        Self::retryable(DownloadError::file_failed(FileOperation::Download, file_name, reason))
+    }
+}
+
+/// Outcome of `DataDownloader::sync`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncReport {
+    /// Files already present and matching the manifest
+    pub skipped: Vec<String>,
+
+    /// Files that were missing or outdated and have been downloaded
+    pub downloaded: Vec<String>,
+
+    /// Files that could not be downloaded, with the error message
+    pub failed: Vec<(String, String)>,
+
+    /// Files served by a mirror after their primary URL failed, with the mirror
+    pub fallbacks: Vec<(String, String)>,
+
+    /// Bytes received for each file that was fetched, failed attempts included
+    pub transferred: Vec<(String, u64)>,
+
+    /// Bytes received in total
+    pub bytes_downloaded: u64,
+
+    /// Size of the files the server answered "not modified" (HTTP 304) for,
+    /// which didn't have to be downloaded again
+    pub bytes_saved: u64,
+}
+
+impl SyncReport {
+    /// Whether every file is now present and valid
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Progress callback forwarding every event to a channel
+///
+/// Lets a UI thread receive events as its own message type. Events sent
+/// after the receiver is dropped are discarded.
+pub fn progress_channel<T>(tx: Sender<T>) -> impl Fn(DownloadEvent)
+where
+    T: From<DownloadEvent>,
+{
+    move |event| {
+        let _ = tx.send(T::from(event));
+    }
+}
+
+/// Concatenate the downloaded parts of a split file, in order, into `dir/<name>`
+///
+/// Fails without writing the joined file if any part is missing.
+pub fn join_parts(dir: &Path, file: &DataFile) -> Result<PathBuf, DownloadError> {
+    join_parts_hashed(dir, file).map(|(path, _)| path)
+}
+
+/// `join_parts`, also returning the digests of the joined file
+fn join_parts_hashed(dir: &Path, file: &DataFile) -> Result<(PathBuf, Digests), DownloadError> {
+    let part_paths: Vec<PathBuf> = (0..file.parts.len())
+        .map(|index| dir.join(file.part_name(index)))
+        .collect();
+
+    for (index, part_path) in part_paths.iter().enumerate() {
+        if !part_path.exists() {
+            return Err(DownloadError::file_failed(
+                FileOperation::Write,
+                &file.name,
+                format!("part {} ({}) is missing", index + 1, file.part_name(index)),
+            ));
+        }
+    }
+
+    let joined_path = dir.join(&file.name);
+    let mut joined = HashingWriter::new(
+        File::create(&joined_path).file_context(FileOperation::Write, &joined_path)?,
+    );
+
+    for part_path in &part_paths {
+        let mut part = File::open(part_path).file_context(FileOperation::Read, part_path)?;
+        std::io::copy(&mut part, &mut joined).file_context(FileOperation::Write, &joined_path)?;
+    }
+
+    joined.flush().file_context(FileOperation::Write, &joined_path)?;
+    let (_, digests) = joined.finish();
+
+    debug!("Joined {} parts into {}", part_paths.len(), file.name);
+    Ok((joined_path, digests))
+}
 
 /// Data downloader for managing LUT files
 pub struct DataDownloader {
     target_dir: PathBuf,
+    manifest: Option<DataManifest>,
+    base_url: Option<String>,
+    include_optional: bool,
+    retry_policy: RetryPolicy,
+    cancel: CancellationToken,
+    client: reqwest::Client,
+    read_timeout: Duration,
+    space_probe: SpaceProbe,
+
+    /// Response body bytes received so far
+    transferred: AtomicU64,
 }
 
 impl DataDownloader {
     /// Create a new data downloader
     pub fn new(target_dir: PathBuf) -> Self {
-        Self { target_dir }
+        Self {
+            target_dir,
+            manifest: None,
+            base_url: None,
+            include_optional: false,
+            retry_policy: RetryPolicy::default(),
+            cancel: CancellationToken::new(),
+            client: HttpConfig::default()
+                .build_client()
+                .expect("Failed to build HTTP client"),
+            read_timeout: HttpConfig::default().read_timeout,
+            space_probe: disk_space::available_space,
+            transferred: AtomicU64::new(0),
+        }
+    }
+
+    /// Set the retry policy for transient failures
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Use a manifest describing the files to download
+    pub fn with_manifest(mut self, manifest: DataManifest) -> Self {
+        self.manifest = Some(manifest);
+        self
+    }
+
+    /// Fetch manifest files from `<base_url>/<name>` instead of their own URLs
+    ///
+    /// Useful for self-hosted copies of the PoB data and for tests against a
+    /// local server. Split files are fetched as `<base_url>/<name>.partN`.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
     }
 
-    /// Download all required PoB data files
-    pub async fn download_pob_data(&self) -> Result<(), DownloadError> {
+    /// Also download files the manifest marks as optional
+    pub fn include_optional(mut self, include: bool) -> Self {
+        self.include_optional = include;
+        self
+    }
+
+    /// Use a cancellation token to abort downloads from another task or thread
+    ///
+    /// Cancelling stops the current request, removes partially written files
+    /// and makes the download return `DownloadError::Cancelled`.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Token that cancels this downloader's in-flight downloads
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Use a preconfigured HTTP client (e.g., with custom timeouts)
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Use the timeouts, pooling and proxy of `config`
+    ///
+    /// A response that sends no data for `config.read_timeout` fails (and is
+    /// retried), however long the whole file takes. Fails if the proxy URL
+    /// is invalid.
+    pub fn with_http_config(mut self, config: &HttpConfig) -> Result<Self, DownloadError> {
+        self.client = config.build_client()?;
+        self.read_timeout = config.read_timeout;
+        Ok(self)
+    }
+
+    /// Look up free disk space with `probe` instead of asking the filesystem
+    pub fn with_space_probe(mut self, probe: SpaceProbe) -> Self {
+        self.space_probe = probe;
+        self
+    }
+
+    /// Download all files from the configured manifest
+    ///
+    /// Files are fetched from the configured base URL if one was set, and
+    /// from each file's own URL otherwise. Returns the manifest updated with
+    /// the actual size and SHA256 of each downloaded file.
+    pub async fn download_pob_data(&self) -> Result<DataManifest, DownloadError> {
+        self.download_pob_data_with_progress(|event| {
+            if let DownloadEvent::Warning(message) = event {
+                warn!("{}", message);
+            }
+        })
+        .await
+    }
+
+    /// Download all files from the configured manifest, reporting progress
+    pub async fn download_pob_data_with_progress<F>(
+        &self,
+        progress: F,
+    ) -> Result<DataManifest, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        let manifest = self.manifest.as_ref().ok_or_else(|| {
+            DownloadError::InvalidManifest("No manifest configured for download".to_string())
+        })?;
+
+        self.download_manifest(manifest, progress).await
+    }
+
+    /// Download every file listed in a manifest
+    ///
+    /// Optional files are skipped unless `include_optional(true)` was set.
+    /// Returns a copy of the manifest with the actual size and SHA256 of each
+    /// downloaded file recorded.
+    pub async fn download_manifest<F>(
+        &self,
+        manifest: &DataManifest,
+        progress: F,
+    ) -> Result<DataManifest, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
         // Create target directory if it doesn't exist
         std::fs::create_dir_all(&self.target_dir)
-            .map_err(|e| DownloadError::IoError(e))?;
+            .file_context(FileOperation::Write, &self.target_dir)?;
 
-        eprintln!("Downloading PoB data to: {}", self.target_dir.display());
+        info!("Downloading PoB data to: {}", self.target_dir.display());
 
-        // Base URL for PoB timeless jewel data
-        let base_url = "https://raw.githubusercontent.com/PathOfBuildingCommunity/PathOfBuilding/master/src/Data/TimelessJewelData";
+        let files: Vec<&DataFile> = manifest
+            .files
+            .iter()
+            .filter(|f| f.required || self.include_optional)
+            .collect();
 
-        // List of files to download
-        let files = vec![
-            "NodeIndexMapping.lua",
-            "LegionPassives.lua",
-            "LethalPride.zip",
-            "BrutalRestraint.zip",
-            "ElegantHubris.zip",
-            "MilitantFaith.zip",
-        ];
+        let total = files.len();
+        let mut updated = manifest.clone();
+        let mut cache = HttpCache::load(&self.target_dir);
 
-        let client = reqwest::Client::new();
+        for (index, file) in files.iter().enumerate() {
+            progress(DownloadEvent::FileStarted {
+                current: index + 1,
+                total,
+                file_name: file.name.clone(),
+            });
 
-        for file_name in files {
-            let url = format!("{}/{}", base_url, file_name);
-            eprintln!("Downloading: {}", file_name);
+            let (fetched, _mirror) = self
+                .fetch_data_file(file, None, &manifest.source.mirrors, &progress)
+                .await?;
 
-            let response = client
-                .get(&url)
-                .send()
-                .await
-                .map_err(|e| DownloadError::DownloadFailed(format!("Failed to download {}: {}", file_name, e)))?;
+            let (size, sha256) = match fetched {
+                Fetched::Downloaded {
+                    validators,
+                    digests,
+                    ..
+                } => {
+                    cache.insert(&file.name, validators);
+                    (digests.bytes, digests.sha256)
+                }
+                Fetched::NotModified => {
+                    let file_path = self.target_dir.join(&file.name);
+                    let size = std::fs::metadata(&file_path)
+                        .file_context(FileOperation::Read, &file_path)?
+                        .len();
+                    (size, checksum::calculate_sha256_async(&file_path).await?)
+                }
+            };
+
+            if let Some(entry) = updated.files.iter_mut().find(|f| f.name == file.name) {
+                entry.size = size;
+                entry.sha256 = sha256;
+            }
+        }
+
+        updated.last_updated = chrono::Utc::now().to_rfc3339();
+        cache.save(&self.target_dir)?;
+
+        info!("Download complete!");
+        Ok(updated)
+    }
+
+    /// Download a manifest into a staging directory and swap it into place
+    ///
+    /// Files are downloaded next to the target directory (e.g.
+    /// `.data.staging-<timestamp>`), verified, and checked to parse before the
+    /// target is replaced. The previous directory is kept as `<name>.bak` until
+    /// the swap succeeds, so an interrupted or failed download never leaves
+    /// the live data half updated. The updated manifest is saved as
+    /// `manifest.json` inside the new directory.
+    ///
+    /// Fails with `DownloadError::InsufficientSpace` before downloading
+    /// anything if the files and the LUT parsed from them won't fit.
+    pub async fn download_and_swap<F>(
+        &self,
+        manifest: &DataManifest,
+        progress: F,
+    ) -> Result<DataManifest, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        self.check_space(manifest).await?;
+
+        let staging_dir = self.staging_path();
+        let staging = self.for_dir(staging_dir.clone());
+
+        let prepared = async {
+            let updated = staging.download_manifest(manifest, &progress).await?;
+            Self::check_parseable(&staging_dir)?;
+
+            let manifest_path = staging_dir.join("manifest.json");
+            updated
+                .save_to_file(&manifest_path)
+                .file_context(FileOperation::Write, &manifest_path)?;
+
+            Ok(updated)
+        }
+        .await;
+
+        let updated = match prepared {
+            Ok(updated) => updated,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&staging_dir);
+                return Err(e);
+            }
+        };
+
+        self.swap_into_place(&staging_dir)?;
+
+        Ok(updated)
+    }
+
+    /// Download a manifest as a version of `layout`
+    ///
+    /// Like `download_and_swap`, into the `raw` directory of the manifest's
+    /// `data_version`; the updated manifest is saved at the version's
+    /// manifest path rather than inside `raw`. The downloader's own target
+    /// directory isn't used.
+    pub async fn download_version<F>(
+        &self,
+        layout: &DataLayout,
+        manifest: &DataManifest,
+        progress: F,
+    ) -> Result<DataManifest, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        let version = &manifest.data_version;
+        std::fs::create_dir_all(layout.parsed_dir(version))
+            .file_context(FileOperation::Write, &layout.parsed_dir(version))?;
+
+        let raw_dir = layout.raw_dir(version);
+        let updated = self.for_dir(raw_dir.clone()).download_and_swap(manifest, progress).await?;
+
+        let manifest_path = layout.manifest_path(version);
+        std::fs::rename(raw_dir.join(MANIFEST_FILE), &manifest_path)
+            .file_context(FileOperation::Write, &manifest_path)?;
+
+        Ok(updated)
+    }
+
+    /// Check the target volume has room for `manifest`'s files and their LUT
+    ///
+    /// Sizes the manifest doesn't record are asked from the server.
+    async fn check_space(&self, manifest: &DataManifest) -> Result<(), DownloadError> {
+        let mut diff = ManifestDiff {
+            added: manifest
+                .files
+                .iter()
+                .filter(|f| f.required || self.include_optional)
+                .cloned()
+                .collect(),
+            ..ManifestDiff::default()
+        };
+        self.resolve_sizes(&mut diff).await;
+
+        let download_bytes = diff.download_size();
+        let needed = disk_space::required_space(
+            download_bytes,
+            disk_space::estimated_artifact_size(download_bytes),
+        );
+        disk_space::ensure_space(self.space_probe, &self.target_dir, needed)
+    }
+
+    /// Download only the files in `data_dir` that are missing or outdated
+    ///
+    /// Each file is checked for existence, size and SHA256 against the
+    /// manifest. Files the manifest has no checksum for are revalidated with
+    /// a conditional GET using the ETag/Last-Modified recorded in the
+    /// directory's `HttpCache`; a 304 keeps the existing file. A failed file
+    /// doesn't stop the others; it is recorded in the returned report instead.
+    pub async fn sync(
+        &self,
+        manifest: &DataManifest,
+        data_dir: &Path,
+    ) -> Result<SyncReport, DownloadError> {
+        self.sync_with_progress(manifest, data_dir, |event| {
+            if let DownloadEvent::Warning(message) = event {
+                warn!("{}", message);
+            }
+        })
+        .await
+    }
+
+    /// Download only missing or outdated files, reporting progress
+    pub async fn sync_with_progress<F>(
+        &self,
+        manifest: &DataManifest,
+        data_dir: &Path,
+        progress: F,
+    ) -> Result<SyncReport, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        self.sync_files(manifest, data_dir, &[], &progress).await
+    }
+
+    /// Sync after `UpdateChecker::check_file_updates`
+    ///
+    /// Files reported as changed upstream are re-downloaded even if the
+    /// local copy still matches the manifest; everything else is synced as
+    /// usual, so unchanged files aren't fetched again.
+    pub async fn sync_changed<F>(
+        &self,
+        manifest: &DataManifest,
+        data_dir: &Path,
+        changed: &[ChangedFile],
+        progress: F,
+    ) -> Result<SyncReport, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        self.sync_files(manifest, data_dir, changed, &progress).await
+    }
+
+    async fn sync_files<F>(
+        &self,
+        manifest: &DataManifest,
+        data_dir: &Path,
+        changed: &[ChangedFile],
+        progress: &F,
+    ) -> Result<SyncReport, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        std::fs::create_dir_all(data_dir).file_context(FileOperation::Write, data_dir)?;
+
+        let mut report = SyncReport::default();
+        let mut cache = HttpCache::load(data_dir);
+        let mut to_fetch = Vec::new();
+
+        for file in manifest
+            .files
+            .iter()
+            .filter(|f| f.required || self.include_optional)
+        {
+            let is_changed = changed.iter().any(|c| c.name == file.name);
+
+            if is_changed || !file.is_up_to_date_async(data_dir).await? {
+                // Local copy is missing, wrong or stale, so don't revalidate it
+                to_fetch.push((file, None));
+            } else if file.has_checksum() || file.is_split() {
+                report.skipped.push(file.name.clone());
+            } else if let Some(validators) = cache.get(&file.name) {
+                to_fetch.push((file, Some(validators.clone())));
+            } else {
+                report.skipped.push(file.name.clone());
+            }
+        }
+
+        let downloader = self.for_dir(data_dir.to_path_buf());
+        let total = to_fetch.len();
+
+        for (index, (file, validators)) in to_fetch.into_iter().enumerate() {
+            progress(DownloadEvent::FileStarted {
+                current: index + 1,
+                total,
+                file_name: file.name.clone(),
+            });
+
+            let before = downloader.transferred.load(Ordering::Relaxed);
+            let result = downloader
+                .fetch_data_file(file, validators.as_ref(), &manifest.source.mirrors, progress)
+                .await;
+            let transferred = downloader.transferred.load(Ordering::Relaxed) - before;
+            report.transferred.push((file.name.clone(), transferred));
+            report.bytes_downloaded += transferred;
+
+            if let Ok((_, Some(mirror))) = &result {
+                report.fallbacks.push((file.name.clone(), mirror.clone()));
+            }
+
+            match result {
+                Ok((Fetched::Downloaded { validators, .. }, _)) => {
+                    cache.insert(&file.name, validators);
+                    report.downloaded.push(file.name.clone());
+                }
+                Ok((Fetched::NotModified, _)) => {
+                    let existing = std::fs::metadata(data_dir.join(&file.name));
+                    report.bytes_saved += existing.map_or(0, |metadata| metadata.len());
+                    report.skipped.push(file.name.clone());
+                }
+                Err(DownloadError::Cancelled) => {
+                    cache.save(data_dir)?;
+                    return Err(DownloadError::Cancelled);
+                }
+                Err(e) => report.failed.push((file.name.clone(), e.to_string())),
+            }
+        }
+
+        cache.save(data_dir)?;
+
+        info!(
+            "Sync complete: {} up to date, {} downloaded ({} bytes), {} failed",
+            report.skipped.len(),
+            report.downloaded.len(),
+            report.bytes_downloaded,
+            report.failed.len()
+        );
 
-            if !response.status().is_success() {
-                return Err(DownloadError::DownloadFailed(format!(
-                    "Failed to download {}: HTTP {}",
-                    file_name,
-                    response.status()
-                )));
+        Ok(report)
+    }
+
+    /// Copy of this downloader's settings targeting another directory
+    fn for_dir(&self, target_dir: PathBuf) -> DataDownloader {
+        DataDownloader {
+            target_dir,
+            manifest: None,
+            base_url: self.base_url.clone(),
+            include_optional: self.include_optional,
+            retry_policy: self.retry_policy.clone(),
+            cancel: self.cancel.clone(),
+            client: self.client.clone(),
+            read_timeout: self.read_timeout,
+            space_probe: self.space_probe,
+            transferred: AtomicU64::new(0),
+        }
+    }
+
+    /// Fresh staging directory next to the target (e.g. `.data.staging-<timestamp>`)
+    pub(crate) fn staging_path(&self) -> PathBuf {
+        self.sibling_path(&format!(
+            ".{}.staging-{}",
+            self.target_dir_name(),
+            chrono::Utc::now().timestamp_millis()
+        ))
+    }
+
+    /// Replace the target directory with `staging_dir`, keeping a backup until done
+    pub(crate) fn swap_into_place(&self, staging_dir: &Path) -> Result<(), DownloadError> {
+        let backup_dir = self.sibling_path(&format!("{}.bak", self.target_dir_name()));
+
+        if backup_dir.exists() {
+            std::fs::remove_dir_all(&backup_dir).file_context(FileOperation::Write, &backup_dir)?;
+        }
+
+        let had_previous = self.target_dir.exists();
+        if had_previous {
+            std::fs::rename(&self.target_dir, &backup_dir)
+                .file_context(FileOperation::Write, &backup_dir)?;
+        }
+
+        if let Err(e) = std::fs::rename(staging_dir, &self.target_dir) {
+            // Put the previous data back so the live directory stays usable
+            if had_previous {
+                let _ = std::fs::rename(&backup_dir, &self.target_dir);
             }
+            let _ = std::fs::remove_dir_all(staging_dir);
+            return Err(DownloadError::io(FileOperation::Write, &self.target_dir, e));
+        }
+
+        if had_previous {
+            std::fs::remove_dir_all(&backup_dir).file_context(FileOperation::Write, &backup_dir)?;
+        }
+
+        info!("Swapped new data into: {}", self.target_dir.display());
+        Ok(())
+    }
+
+    /// Make sure the downloaded Lua metadata files actually parse
+    fn check_parseable(dir: &Path) -> Result<(), DownloadError> {
+        let node_mapping = dir.join("NodeIndexMapping.lua");
+        if node_mapping.exists() {
+            LuaParser::parse_node_index_mapping(&node_mapping)?;
+        }
+
+        let legion_passives = dir.join("LegionPassives.lua");
+        if legion_passives.exists() {
+            LuaParser::parse_legion_passives(&legion_passives)?;
+        }
 
-            let bytes = response
-                .bytes()
+        Ok(())
+    }
+
+    /// Name of the target directory (e.g., "data")
+    fn target_dir_name(&self) -> String {
+        self.target_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "data".to_string())
+    }
+
+    /// Path next to the target directory with the given file name
+    fn sibling_path(&self, name: &str) -> PathBuf {
+        match self.target_dir.parent() {
+            Some(parent) => parent.join(name),
+            None => PathBuf::from(name),
+        }
+    }
+
+    /// Download a single file into the target directory and verify its checksum
+    ///
+    /// The response body is streamed to disk chunk by chunk, emitting
+    /// `DownloadEvent::Progress` as bytes arrive. If `expected_sha256` is empty
+    /// the file is accepted with a warning. On a checksum mismatch the file is
+    /// deleted and `ChecksumMismatch` is returned.
+    pub async fn download_file<F>(
+        &self,
+        url: &str,
+        file_name: &str,
+        expected_sha256: &str,
+        progress: &F,
+    ) -> Result<PathBuf, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        self.download_file_with_digests(url, file_name, expected_sha256, progress)
+            .await
+            .map(|(path, _)| path)
+    }
+
+    /// `download_file`, also returning the digests computed while streaming
+    ///
+    /// The file is hashed as it is written, so the returned SHA256 (and git
+    /// blob SHA, when the server sent a length) cost no extra read.
+    pub async fn download_file_with_digests<F>(
+        &self,
+        url: &str,
+        file_name: &str,
+        expected_sha256: &str,
+        progress: &F,
+    ) -> Result<(PathBuf, Digests), DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        match self
+            .fetch_file(url, file_name, expected_sha256, None, progress)
+            .await
+            .map_err(|failure| failure.error)?
+        {
+            Fetched::Downloaded { path, digests, .. } => Ok((path, digests)),
+            // Without validators the server can't answer "not modified"
+            Fetched::NotModified => unreachable!("no validators were sent"),
+        }
+    }
+
+    /// Download a file, optionally revalidating an existing copy
+    ///
+    /// The body is written to `<name>.download` and only renamed over the
+    /// existing file once it is complete and verified, so a failed refresh
+    /// never destroys a good copy.
+    async fn fetch_file<F>(
+        &self,
+        url: &str,
+        file_name: &str,
+        expected_sha256: &str,
+        validators: Option<&CacheValidators>,
+        progress: &F,
+    ) -> Result<Fetched, AttemptFailure>
+    where
+        F: Fn(DownloadEvent),
+    {
+        debug!("Downloading: {}", file_name);
+
+        let file_path = self.target_dir.join(file_name);
+        let temp_path = self.target_dir.join(format!("{}.download", file_name));
+        let max_attempts = self.retry_policy.max_attempts.max(1);
+        let mut attempt = 1;
+
+        let (digests, new_validators) = loop {
+            match self
+                .try_download(url, &temp_path, file_name, validators, progress)
                 .await
-                .map_err(|e| DownloadError::DownloadFailed(format!("Failed to read {}: {}", file_name, e)))?;
+            {
+                Ok(Attempt::Downloaded { digests, validators }) => break (digests, validators),
+                Ok(Attempt::NotModified) => {
+                    debug!("{} not modified", file_name);
+                    progress(DownloadEvent::NotModified {
+                        file_name: file_name.to_string(),
+                    });
+                    return Ok(Fetched::NotModified);
+                }
+                Err(failure) => {
+                    // Don't leave a partial file behind
+                    let _ = std::fs::remove_file(&temp_path);
+
+                    if !failure.retryable || attempt >= max_attempts {
+                        return Err(failure);
+                    }
+
+                    let delay = self.retry_policy.delay_for(attempt);
+                    attempt += 1;
 
-            let file_path = self.target_dir.join(file_name);
-            std::fs::write(&file_path, &bytes)
-                .map_err(|e| DownloadError::IoError(e))?;
+                    progress(DownloadEvent::Retrying {
+                        file_name: file_name.to_string(),
+                        attempt,
+                        max_attempts,
+                        reason: failure.error.to_string(),
+                    });
 
-            eprintln!("  ✓ Saved {} ({} bytes)", file_name, bytes.len());
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = self.cancel.cancelled() => {
+                            return Err(AttemptFailure::fatal(DownloadError::Cancelled));
+                        }
+                    }
+                }
+            }
+        };
+
+        Self::finish_download(&temp_path, &file_path, file_name, expected_sha256, &digests, progress)
+            .map_err(AttemptFailure::fatal)?;
+
+        Ok(Fetched::Downloaded {
+            path: file_path,
+            validators: new_validators,
+            digests,
+        })
+    }
+
+    /// Verify a completed temp file and move it into place
+    ///
+    /// `digests` were computed while writing the file, so it isn't re-read.
+    fn finish_download<F>(
+        temp_path: &Path,
+        file_path: &Path,
+        file_name: &str,
+        expected_sha256: &str,
+        digests: &Digests,
+        progress: &F,
+    ) -> Result<(), DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        // Report the file by its name rather than the temp file's
+        let status = checksum::verify_digest(temp_path, expected_sha256, &digests.sha256)
+            .map_err(|e| match e {
+                DownloadError::ChecksumMismatch { expected, actual, .. } => {
+                    DownloadError::ChecksumMismatch {
+                        file: file_name.to_string(),
+                        expected,
+                        actual,
+                    }
+                }
+                e => e,
+            })?;
+        if status == ChecksumStatus::Unverified {
+            progress(DownloadEvent::Warning(format!(
+                "No checksum available for {}, accepting without verification",
+                file_name
+            )));
         }
 
-        eprintln!("Download complete!");
+        std::fs::rename(temp_path, file_path).map_err(|e| {
+            let _ = std::fs::remove_file(temp_path);
+            DownloadError::io(FileOperation::Write, file_path, e)
+        })?;
+
+        debug!("Saved {} ({} bytes)", file_name, digests.bytes);
+
+        progress(DownloadEvent::FileCompleted {
+            file_name: file_name.to_string(),
+            bytes: digests.bytes,
+        });
+
         Ok(())
     }
 
+    /// Fetch a file from its URL, falling back to mirrors on transient failures
+    ///
+    /// Mirrors are only tried when the primary URL fails with a network error
+    /// or 5xx response. Returns the mirror that served the file, if any.
+    async fn fetch_with_mirrors<F>(
+        &self,
+        url: &str,
+        file_name: &str,
+        expected_sha256: &str,
+        validators: Option<&CacheValidators>,
+        mirrors: &[DownloadSource],
+        progress: &F,
+    ) -> Result<(Fetched, Option<String>), DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        let mut last_error = match self
+            .fetch_file(url, file_name, expected_sha256, validators, progress)
+            .await
+        {
+            Ok(fetched) => return Ok((fetched, None)),
+            Err(failure) if failure.retryable && !mirrors.is_empty() => failure.error,
+            Err(failure) => return Err(failure.error),
+        };
+
+        for mirror in mirrors {
+            progress(DownloadEvent::Warning(format!(
+                "{} failed ({}), trying mirror {}",
+                file_name, last_error, mirror
+            )));
+
+            let result = match mirror.location(file_name) {
+                SourceLocation::Remote(mirror_url) => self
+                    .fetch_file(&mirror_url, file_name, expected_sha256, None, progress)
+                    .await
+                    .map_err(|failure| failure.error),
+                SourceLocation::Local(path) => {
+                    self.copy_local(&path, file_name, expected_sha256, progress)
+                }
+            };
+
+            match result {
+                Ok(fetched) => return Ok((fetched, Some(mirror.to_string()))),
+                Err(DownloadError::Cancelled) => return Err(DownloadError::Cancelled),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Copy a file from a local mirror directory into the target directory
+    fn copy_local<F>(
+        &self,
+        source: &Path,
+        file_name: &str,
+        expected_sha256: &str,
+        progress: &F,
+    ) -> Result<Fetched, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        if self.cancel.is_cancelled() {
+            return Err(DownloadError::Cancelled);
+        }
+
+        let file_path = self.target_dir.join(file_name);
+        let temp_path = self.target_dir.join(format!("{}.download", file_name));
+
+        let copy = || -> std::io::Result<Digests> {
+            let mut reader = File::open(source)?;
+            let mut writer = HashingWriter::new(File::create(&temp_path)?);
+            std::io::copy(&mut reader, &mut writer)?;
+            writer.flush()?;
+            Ok(writer.finish().1)
+        };
+
+        let digests = copy().map_err(|e| {
+            let _ = std::fs::remove_file(&temp_path);
+            DownloadError::file_failed(
+                FileOperation::Download,
+                file_name,
+                format!("copying from {} failed: {}", source.display(), e),
+            )
+        })?;
+
+        Self::finish_download(&temp_path, &file_path, file_name, expected_sha256, &digests, progress)?;
+
+        Ok(Fetched::Downloaded {
+            path: file_path,
+            validators: CacheValidators::default(),
+            digests,
+        })
+    }
+
+    /// Download every part of a split file and join them into the final file
+    ///
+    /// Each part is verified against its own checksum, then the parts are
+    /// concatenated in order, the joined file is verified against the
+    /// file's `sha256`, and the parts are deleted.
+    pub async fn download_split_file<F>(
+        &self,
+        file: &DataFile,
+        progress: &F,
+    ) -> Result<PathBuf, DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        self.download_split_file_from(file, &[], progress)
+            .await
+            .map(|(path, _, _)| path)
+    }
+
+    /// Download a manifest entry (single or split), with mirror fallback
+    async fn fetch_data_file<F>(
+        &self,
+        file: &DataFile,
+        validators: Option<&CacheValidators>,
+        mirrors: &[DownloadSource],
+        progress: &F,
+    ) -> Result<(Fetched, Option<String>), DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        if file.is_split() {
+            let (path, digests, mirror) =
+                self.download_split_file_from(file, mirrors, progress).await?;
+            let fetched = Fetched::Downloaded {
+                path,
+                validators: CacheValidators::default(),
+                digests,
+            };
+            return Ok((fetched, mirror));
+        }
+
+        let url = self.resolve_url(&file.url, &file.name);
+
+        self.fetch_with_mirrors(&url, &file.name, &file.sha256, validators, mirrors, progress)
+            .await
+    }
+
+    /// Download and join a split file, trying mirrors for each failing part
+    async fn download_split_file_from<F>(
+        &self,
+        file: &DataFile,
+        mirrors: &[DownloadSource],
+        progress: &F,
+    ) -> Result<(PathBuf, Digests, Option<String>), DownloadError>
+    where
+        F: Fn(DownloadEvent),
+    {
+        let remove_parts = || {
+            for index in 0..file.parts.len() {
+                let _ = std::fs::remove_file(self.target_dir.join(file.part_name(index)));
+            }
+        };
+
+        let mut mirror_used = None;
+
+        for (index, part) in file.parts.iter().enumerate() {
+            let part_name = file.part_name(index);
+            let url = self.resolve_url(&part.url, &part_name);
+
+            match self
+                .fetch_with_mirrors(&url, &part_name, &part.sha256, None, mirrors, progress)
+                .await
+            {
+                Ok((_, mirror)) => mirror_used = mirror_used.or(mirror),
+                Err(e) => {
+                    remove_parts();
+                    return Err(e);
+                }
+            }
+        }
+
+        let joined = join_parts_hashed(&self.target_dir, file);
+        remove_parts();
+        let (joined, digests) = joined?;
+
+        let status = checksum::verify_digest(&joined, &file.sha256, &digests.sha256)?;
+        if status == ChecksumStatus::Unverified {
+            progress(DownloadEvent::Warning(format!(
+                "No checksum available for joined {}, accepting without verification",
+                file.name
+            )));
+        }
+
+        Ok((joined, digests, mirror_used))
+    }
+
+    /// Make a single download attempt, streaming the body to `file_path`
+    async fn try_download<F>(
+        &self,
+        url: &str,
+        file_path: &Path,
+        file_name: &str,
+        validators: Option<&CacheValidators>,
+        progress: &F,
+    ) -> Result<Attempt, AttemptFailure>
+    where
+        F: Fn(DownloadEvent),
+    {
+        if self.cancel.is_cancelled() {
+            return Err(AttemptFailure::fatal(DownloadError::Cancelled));
+        }
+
+        let mut request = self.client.get(url);
+        if let Some(validators) = validators {
+            if let Some(etag) = &validators.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let request = tokio::time::timeout(self.read_timeout, request.send());
+        let mut response = tokio::select! {
+            result = request => result
+                .map_err(|_| AttemptFailure::stalled(self.read_timeout, file_name))?
+                .map_err(|e| AttemptFailure::from_reqwest(e, file_name))?,
+            _ = self.cancel.cancelled() => {
+                return Err(AttemptFailure::fatal(DownloadError::Cancelled));
+            }
+        };
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED && validators.is_some() {
+            return Ok(Attempt::NotModified);
+        }
+
+        if !status.is_success() {
+            let error =
+                DownloadError::file_failed(FileOperation::Download, file_name, format!("HTTP {}", status));
+
+            return Err(if status.is_server_error() {
+                AttemptFailure::retryable(error)
+            } else {
+                AttemptFailure::fatal(error)
+            });
+        }
+
+        let total_bytes = response.content_length();
+        let validators = CacheValidators::from_headers(response.headers());
+
+        let digests = self
+            .stream_to_file(&mut response, file_path, file_name, total_bytes, progress)
+            .await?;
+
+        Ok(Attempt::Downloaded { digests, validators })
+    }
+
+    /// Write a response body to disk as it arrives, hashing it on the way
+    ///
+    /// The git blob SHA is only computed when the response has a length.
+    async fn stream_to_file<F>(
+        &self,
+        response: &mut reqwest::Response,
+        file_path: &Path,
+        file_name: &str,
+        total_bytes: Option<u64>,
+        progress: &F,
+    ) -> Result<Digests, AttemptFailure>
+    where
+        F: Fn(DownloadEvent),
+    {
+        let io_error = |e| AttemptFailure::fatal(DownloadError::io(FileOperation::Write, file_path, e));
+
+        let mut file = HashingWriter::new(File::create(file_path).map_err(io_error)?);
+        if let Some(total_bytes) = total_bytes {
+            file = file.with_git_blob(total_bytes);
+        }
+
+        loop {
+            // Checked between chunks so a cancel stops the transfer promptly
+            // Each chunk gets its own timeout: a slow but steady body is fine
+            let chunk = tokio::time::timeout(self.read_timeout, response.chunk());
+            let chunk = tokio::select! {
+                chunk = chunk => chunk
+                    .map_err(|_| AttemptFailure::stalled(self.read_timeout, file_name))?
+                    .map_err(|e| AttemptFailure::from_reqwest(e, file_name))?,
+                _ = self.cancel.cancelled() => {
+                    return Err(AttemptFailure::fatal(DownloadError::Cancelled));
+                }
+            };
+
+            let Some(chunk) = chunk else {
+                break;
+            };
+
+            file.write_all(&chunk).map_err(io_error)?;
+            self.transferred.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+
+            progress(DownloadEvent::Progress(ProgressEvent {
+                file: file_name.to_string(),
+                bytes_downloaded: file.bytes_written(),
+                total_bytes,
+            }));
+        }
+
+        file.flush().map_err(io_error)?;
+
+        Ok(file.finish().1)
+    }
+
+    /// Fill in the sizes of the files `diff` would download that have none
+    ///
+    /// Single files get the Content-Length of a HEAD request to the URL they
+    /// would be downloaded from; split files the sum of their parts' sizes,
+    /// asking the server for parts without one. Sizes the server doesn't
+    /// report stay 0.
+    pub async fn resolve_sizes(&self, diff: &mut ManifestDiff) {
+        for file in diff.added.iter_mut().chain(diff.changed.iter_mut()) {
+            if file.size > 0 {
+                continue;
+            }
+
+            if !file.is_split() {
+                let url = self.resolve_url(&file.url, &file.name);
+                file.size = self.content_length(&url).await.unwrap_or(0);
+                continue;
+            }
+
+            let mut total = 0;
+            for part in &file.parts {
+                let size = match part.size {
+                    0 => self.content_length(&part.url).await,
+                    size => Some(size),
+                };
+                match size {
+                    Some(size) => total += size,
+                    // A partial sum would understate the download
+                    None => {
+                        total = 0;
+                        break;
+                    }
+                }
+            }
+            file.size = total;
+        }
+    }
+
+    /// Content-Length of `url` from a HEAD request, if the server sends one
+    async fn content_length(&self, url: &str) -> Option<u64> {
+        let response = match self.client.head(url).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                debug!("HEAD {} returned {}", url, response.status());
+                return None;
+            }
+            Err(e) => {
+                debug!("HEAD {} failed: {}", url, e);
+                return None;
+            }
+        };
+
+        // `Response::content_length` is the body's length, which a HEAD
+        // response doesn't have
+        response
+            .headers()
+            .get(reqwest::header::CONTENT_LENGTH)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    }
+
     /// Get the target directory path
     pub fn target_dir(&self) -> &PathBuf {
         &self.target_dir
     }
 
+    /// Get the configured manifest, if any
+    pub fn manifest(&self) -> Option<&DataManifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Get the configured base URL, if any
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    /// URL to fetch a file from, honouring the configured base URL
+    fn resolve_url(&self, url: &str, file_name: &str) -> String {
+        match &self.base_url {
+            Some(base_url) => format!("{}/{}", base_url.trim_end_matches('/'), file_name),
+            None => url.to_string(),
+        }
+    }
+
     /// Check for updates
     pub async fn check_updates(&self) -> Result<Option<String>, DownloadError> {
         // TODO: Implement update checking
         Ok(None)
     }
 
-    /// Validate downloaded files
-    pub async fn validate_files(&self) -> Result<bool, DownloadError> {
-        // Check if required files exist
-        let required_files = vec![
-            "NodeIndexMapping.lua",
-            "LegionPassives.lua",
-        ];
+    /// Validate the contents of downloaded files
+    ///
+    /// Checks every file the configured manifest would download, or the Lua
+    /// metadata files plus any jewel data present when there is no manifest.
+    /// See `validation::validate_file` for the per-file checks.
+    pub async fn validate_files(&self) -> Result<Vec<ValidationResult>, DownloadError> {
+        let file_names: Vec<String> = match &self.manifest {
+            Some(manifest) => manifest
+                .files
+                .iter()
+                .filter(|f| f.required || self.include_optional)
+                .map(|f| f.name.clone())
+                .collect(),
+            None => {
+                let mut names = vec![
+                    "NodeIndexMapping.lua".to_string(),
+                    "LegionPassives.lua".to_string(),
+                ];
+
+                if let Ok(entries) = std::fs::read_dir(&self.target_dir) {
+                    let mut zips: Vec<String> = entries
+                        .flatten()
+                        .map(|e| e.file_name().to_string_lossy().into_owned())
+                        .filter(|name| name.ends_with(".zip"))
+                        .collect();
+                    zips.sort();
+                    names.extend(zips);
+                }
 
-        for file_name in required_files {
-            let file_path = self.target_dir.join(file_name);
-            if !file_path.exists() {
-                return Ok(false);
+                names
             }
-        }
+        };
 
-        Ok(true)
+        Ok(file_names
+            .iter()
+            .map(|name| validation::validate_file(&self.target_dir.join(name)))
+            .collect())
     }
 }
+
@@ -1,5 +1,10 @@
 //! Error types for the API crate
 
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use poe_item_analyzer_core::{AnalysisError, DataError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,29 +15,146 @@ pub enum ApiError {
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
 
-    #[error("Rate limited: {0}")]
-    RateLimited(String),
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        /// When the limit resets, if the server said
+        reset_at: Option<DateTime<Utc>>,
+    },
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
 
     #[error("API error: {0}")]
     ApiError(String),
 }
 
+/// What was being done with a file when it failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOperation {
+    Download,
+    Read,
+    Write,
+    Decompress,
+    Parse,
+}
+
+impl fmt::Display for FileOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FileOperation::Download => "download",
+            FileOperation::Read => "read",
+            FileOperation::Write => "write",
+            FileOperation::Decompress => "decompress",
+            FileOperation::Parse => "parse",
+        })
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum DownloadError {
     #[error("Download failed: {0}")]
     DownloadFailed(String),
 
-    #[error("Checksum mismatch: expected {expected}, got {actual}")]
-    ChecksumMismatch { expected: String, actual: String },
+    /// A file couldn't be downloaded or processed, for a reason other than I/O
+    #[error("Could not {operation} {file}: {reason}")]
+    FileFailed {
+        operation: FileOperation,
+        file: String,
+        reason: String,
+    },
 
-    #[error("IO error: {0}")]
-    IoError(#[from] std::io::Error),
+    /// A jewel data file decompressed, but its layout is inconsistent
+    #[error(
+        "{file} is truncated or malformed: {reason} \
+         ({salvaged_seeds} seeds, {salvaged_entries} entries readable)"
+    )]
+    MalformedData {
+        file: String,
+        reason: String,
+        salvaged_seeds: usize,
+        salvaged_entries: usize,
+    },
+
+    #[error("Checksum mismatch for {file}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Could not {operation} {}: {source}", path.display())]
+    IoError {
+        operation: FileOperation,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
 
     #[error("HTTP error: {0}")]
     HttpError(#[from] reqwest::Error),
 
     #[error("Invalid manifest: {0}")]
     InvalidManifest(String),
+
+    #[error("Download cancelled")]
+    Cancelled,
+
+    /// The data volume has less free space than downloading and parsing need
+    #[error("Not enough disk space: {needed} bytes needed, {available} bytes available")]
+    InsufficientSpace { needed: u64, available: u64 },
+
+    #[error("{message}")]
+    RateLimited {
+        message: String,
+        /// When the limit resets, if the server said
+        reset_at: Option<DateTime<Utc>>,
+    },
+}
+
+impl DownloadError {
+    /// I/O error while doing `operation` with `path`
+    pub fn io(operation: FileOperation, path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        DownloadError::IoError {
+            operation,
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Non-I/O failure while doing `operation` with `file`
+    pub fn file_failed(
+        operation: FileOperation,
+        file: impl Into<String>,
+        reason: impl fmt::Display,
+    ) -> Self {
+        DownloadError::FileFailed {
+            operation,
+            file: file.into(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// File name as a display string
+pub(crate) fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Attach the file and operation to I/O errors
+pub trait FileContext<T> {
+    fn file_context(self, operation: FileOperation, path: &Path) -> Result<T, DownloadError>;
+}
+
+impl<T> FileContext<T> for std::io::Result<T> {
+    fn file_context(self, operation: FileOperation, path: &Path) -> Result<T, DownloadError> {
+        self.map_err(|e| DownloadError::io(operation, path, e))
+    }
 }
 
 #[derive(Error, Debug)]
@@ -49,3 +171,107 @@ pub enum SourceError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
+
+/// A Path of Building build code that can't be read
+#[derive(Error, Debug)]
+pub enum BuildCodeError {
+    #[error("Build code is not valid base64: {0}")]
+    InvalidEncoding(String),
+
+    #[error("Build code could not be decompressed: {0}")]
+    Decompress(#[source] std::io::Error),
+
+    #[error("Build XML is invalid: {0}")]
+    InvalidXml(String),
+
+    #[error("Build has no {0}")]
+    Missing(&'static str),
+}
+
+impl From<DownloadError> for DataError {
+    /// Files that fail checksums or parsing are corrupt; missing ones can be downloaded again
+    fn from(error: DownloadError) -> Self {
+        let message = error.to_string();
+        match error {
+            DownloadError::ChecksumMismatch { .. } | DownloadError::MalformedData { .. } => {
+                DataError::CorruptedData(message)
+            }
+            DownloadError::FileFailed {
+                operation: FileOperation::Parse | FileOperation::Decompress,
+                ..
+            } => DataError::CorruptedData(message),
+            DownloadError::IoError { source, .. }
+                if source.kind() == std::io::ErrorKind::NotFound =>
+            {
+                DataError::MissingFile(message)
+            }
+            DownloadError::InvalidManifest(_) => DataError::InvalidFormat(message),
+            _ => DataError::LoadError(message),
+        }
+    }
+}
+
+impl From<DownloadError> for AnalysisError {
+    fn from(error: DownloadError) -> Self {
+        AnalysisError::DataError(error.into())
+    }
+}
+
+impl From<SourceError> for AnalysisError {
+    fn from(error: SourceError) -> Self {
+        AnalysisError::SourceUnavailable(Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+    use crate::parser::PobDataParser;
+    use std::error::Error as _;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_checksum_mismatch_is_corrupted_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("LethalPride.zip");
+        std::fs::write(&path, b"not the expected bytes").unwrap();
+
+        let error = checksum::verify_checksum(&path, &"0".repeat(64)).unwrap_err();
+        let error = DataError::from(error);
+
+        assert!(matches!(&error, DataError::CorruptedData(m) if m.contains("LethalPride.zip")));
+        assert!(error.needs_redownload());
+    }
+
+    #[test]
+    fn test_corrupt_lut_is_corrupted_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("lut_data.json");
+        std::fs::write(&path, "{ \"nodes\": [").unwrap();
+
+        let error = PobDataParser::load_from_json(&path).unwrap_err();
+
+        match AnalysisError::from(error) {
+            AnalysisError::DataError(DataError::CorruptedData(message)) => {
+                assert!(message.starts_with("Could not parse lut_data.json"), "{}", message);
+            }
+            other => panic!("Expected corrupted data, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_file_and_source_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let error = PobDataParser::load_from_json(&temp_dir.path().join("missing.json"));
+        assert!(matches!(DataError::from(error.unwrap_err()), DataError::MissingFile(_)));
+
+        let error = AnalysisError::from(SourceError::FetchFailed("timeout".to_string()));
+        assert_eq!(
+            error.to_string(),
+            "Item source unavailable: Failed to fetch items: timeout"
+        );
+        let source = error.source().unwrap().downcast_ref::<SourceError>();
+        assert!(matches!(source, Some(SourceError::FetchFailed(_))));
+    }
+}
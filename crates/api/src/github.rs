@@ -1,8 +1,22 @@
 //! GitHub API client for checking data updates
 
 use crate::error::ApiError;
-use crate::manifest::DataSource;
+use crate::http_config::HttpConfig;
+use crate::http_util::{send_with_retry, RetryPolicy};
+use crate::manifest::{DataFile, DataSource};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Default GitHub REST API endpoint
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// User-Agent GitHub requires on every request
+const USER_AGENT: &str = "poe-item-analyzer/0.1.0";
 
 /// GitHub commit information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +38,29 @@ pub struct Author {
     pub date: String,
 }
 
+/// Short description of a commit for changelogs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitSummary {
+    pub sha: String,
+
+    /// First line of the commit message
+    pub message: String,
+
+    pub date: String,
+    pub author: String,
+}
+
+impl From<&GitHubCommit> for CommitSummary {
+    fn from(commit: &GitHubCommit) -> Self {
+        Self {
+            sha: commit.sha.clone(),
+            message: commit.commit.message.lines().next().unwrap_or("").to_string(),
+            date: commit.commit.author.date.clone(),
+            author: commit.commit.author.name.clone(),
+        }
+    }
+}
+
 /// GitHub file content information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubFile {
@@ -33,24 +70,290 @@ pub struct GitHubFile {
     pub size: u64,
     pub url: String,
     pub download_url: Option<String>,
+
+    /// Entry type ("file", "dir", "symlink" or "submodule")
+    #[serde(rename = "type", default = "default_entry_type")]
+    pub entry_type: String,
+}
+
+fn default_entry_type() -> String {
+    "file".to_string()
+}
+
+impl GitHubFile {
+    /// Whether this entry is a regular file
+    pub fn is_file(&self) -> bool {
+        self.entry_type == "file"
+    }
+
+    /// Convert to a manifest entry for `source`
+    ///
+    /// Entries without a `download_url` fall back to the source's raw URL
+    /// for its repository and branch.
+    pub fn to_data_file(&self, source: &DataSource) -> DataFile {
+        let url = self
+            .download_url
+            .clone()
+            .unwrap_or_else(|| source.raw_repo_url(&self.path));
+
+        DataFile {
+            name: self.name.clone(),
+            url,
+            sha256: String::new(),
+            github_sha: self.sha.clone(),
+            size: self.size,
+            required: true,
+            description: String::new(),
+            parts: Vec::new(),
+        }
+    }
+}
+
+/// Convert a directory listing into manifest entries, skipping non-files
+pub fn data_files_from_listing(listing: &[GitHubFile], source: &DataSource) -> Vec<DataFile> {
+    listing
+        .iter()
+        .filter(|entry| entry.is_file())
+        .map(|entry| entry.to_data_file(source))
+        .collect()
+}
+
+/// Source of commit and file information for a repository
+///
+/// Implemented by `GitHubClient`; `test_support::FakeCommitProvider` serves
+/// the same data from memory for tests and offline use.
+#[async_trait]
+pub trait CommitProvider: Send + Sync {
+    /// Latest commit touching `path`
+    async fn get_latest_commit(&self, repo: &str, path: &str) -> Result<GitHubCommit, ApiError>;
+
+    /// Commits touching `path` newer than `since_sha`, newest first, at most `limit`
+    async fn get_commits_since(
+        &self,
+        repo: &str,
+        path: &str,
+        since_sha: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubCommit>, ApiError>;
+
+    /// Information about a single file
+    async fn get_file_info(
+        &self,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<GitHubFile, ApiError>;
+
+    /// Entries directly under a directory
+    async fn list_directory(
+        &self,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<Vec<GitHubFile>, ApiError>;
+}
+
+/// Rate limit information from the most recent GitHub response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitStatus {
+    /// Requests allowed per window (X-RateLimit-Limit)
+    pub limit: Option<u32>,
+
+    /// Requests left in the current window (X-RateLimit-Remaining)
+    pub remaining: Option<u32>,
+
+    /// When the window resets (X-RateLimit-Reset)
+    pub reset_at: Option<DateTime<Utc>>,
+
+    /// How long to wait before retrying (Retry-After)
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitStatus {
+    /// Parse rate limit headers from a response
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let number = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+
+        Self {
+            limit: number("x-ratelimit-limit").map(|v| v as u32),
+            remaining: number("x-ratelimit-remaining").map(|v| v as u32),
+            reset_at: number("x-ratelimit-reset")
+                .and_then(|secs| Utc.timestamp_opt(secs as i64, 0).single()),
+            retry_after: number("retry-after").map(Duration::from_secs),
+        }
+    }
+
+    /// Whether the limit is used up
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    /// Earliest time a new request is expected to succeed
+    pub fn retry_at(&self) -> Option<DateTime<Utc>> {
+        match self.retry_after {
+            Some(wait) => chrono::Duration::from_std(wait).ok().map(|wait| Utc::now() + wait),
+            None => self.reset_at,
+        }
+    }
+}
+
+/// GitHub settings supplied by the application (e.g., the settings screen)
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitHubConfig {
+    /// Personal access token for authenticated requests (5000 req/hour)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Alternative API endpoint (e.g., GitHub Enterprise)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+}
+
+impl fmt::Debug for GitHubConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the token itself
+        f.debug_struct("GitHubConfig")
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("api_url", &self.api_url)
+            .finish()
+    }
+}
+
+/// Client for the GitHub API with `config`'s settings
+fn build_client(config: &HttpConfig) -> Result<reqwest::Client, reqwest::Error> {
+    config
+        .client_builder()?
+        .user_agent(USER_AGENT)
+        .timeout(config.read_timeout)
+        .build()
 }
 
 /// GitHub API client
 pub struct GitHubClient {
     client: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+    rate_limit: Mutex<Option<RateLimitStatus>>,
+    retry_policy: RetryPolicy,
 }
 
 impl GitHubClient {
     /// Create a new GitHub API client
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::builder()
-                .user_agent("poe-item-analyzer/0.1.0")
-                .build()
-                .expect("Failed to build HTTP client"),
+            client: build_client(&HttpConfig::default()).expect("Failed to build HTTP client"),
+            api_url: GITHUB_API_URL.to_string(),
+            token: None,
+            rate_limit: Mutex::new(None),
+            retry_policy: RetryPolicy::default(),
         }
     }
 
+    /// Create a client from application settings
+    pub fn from_config(config: &GitHubConfig) -> Self {
+        let mut client = Self::new();
+
+        if let Some(token) = config.token.as_deref().filter(|t| !t.trim().is_empty()) {
+            client = client.with_token(token.trim());
+        }
+        if let Some(api_url) = &config.api_url {
+            client = client.with_api_url(api_url.as_str());
+        }
+
+        client
+    }
+
+    /// Use the timeouts, pooling and proxy of `config`
+    ///
+    /// Fails if the proxy URL is invalid.
+    pub fn with_http_config(mut self, config: &HttpConfig) -> Result<Self, ApiError> {
+        self.client = build_client(config)?;
+        Ok(self)
+    }
+
+    /// Authenticate requests with a personal access token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Whether requests are authenticated
+    pub fn is_authenticated(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Use a different API endpoint (e.g., GitHub Enterprise or a test server)
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the retry policy for transient failures
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Rate limit values from the most recent response, if any
+    pub fn rate_limit_status(&self) -> Option<RateLimitStatus> {
+        self.rate_limit.lock().ok().and_then(|status| status.clone())
+    }
+
+    /// Send a GET request, tracking rate limits and mapping error statuses
+    async fn get(&self, url: &str) -> Result<reqwest::Response, ApiError> {
+        let mut request = self.client.get(url);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = send_with_retry(&self.client, request, &self.retry_policy)
+            .await
+            .map_err(ApiError::RequestFailed)?;
+
+        let status = RateLimitStatus::from_headers(response.headers());
+        if let Ok(mut latest) = self.rate_limit.lock() {
+            *latest = Some(status.clone());
+        }
+
+        let code = response.status();
+        if code.is_success() {
+            return Ok(response);
+        }
+
+        let limited = code == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || (code == reqwest::StatusCode::FORBIDDEN
+                && (status.is_exhausted() || status.retry_after.is_some()));
+
+        if limited {
+            let reset_at = status.retry_at();
+            let message = match reset_at {
+                Some(reset_at) => format!(
+                    "GitHub API rate limit exceeded, try again at {}",
+                    reset_at.with_timezone(&chrono::Local).format("%H:%M")
+                ),
+                None => "GitHub API rate limit exceeded".to_string(),
+            };
+
+            return Err(ApiError::RateLimited { message, reset_at });
+        }
+
+        if code == reqwest::StatusCode::UNAUTHORIZED {
+            let message = if self.token.is_some() {
+                "GitHub rejected the configured token (invalid or expired)"
+            } else {
+                "GitHub requires authentication for this request"
+            };
+            return Err(ApiError::Unauthorized(message.to_string()));
+        }
+
+        Err(ApiError::ApiError(format!("GitHub API error: {}", code)))
+    }
+
     /// Get the latest commit for a specific path
     pub async fn get_latest_commit(
         &self,
@@ -58,23 +361,11 @@ impl GitHubClient {
         path: &str,
     ) -> Result<GitHubCommit, ApiError> {
         let url = format!(
-            "https://api.github.com/repos/{}/commits?path={}&per_page=1",
-            repo, path
+            "{}/repos/{}/commits?path={}&per_page=1",
+            self.api_url, repo, path
         );
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ApiError::RequestFailed(e))?;
-
-        if !response.status().is_success() {
-            return Err(ApiError::ApiError(format!(
-                "GitHub API error: {}",
-                response.status()
-            )));
-        }
+        let response = self.get(&url).await?;
 
         let commits: Vec<GitHubCommit> = response
             .json()
@@ -87,6 +378,44 @@ impl GitHubClient {
             .ok_or_else(|| ApiError::InvalidResponse("No commits found".to_string()))
     }
 
+    /// Get the commits touching `path` after `since_sha`, newest first
+    ///
+    /// Pages through the commits API until `since_sha` is reached or `limit`
+    /// commits have been collected.
+    pub async fn get_commits_since(
+        &self,
+        repo: &str,
+        path: &str,
+        since_sha: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubCommit>, ApiError> {
+        let per_page = limit.clamp(1, 100);
+        let mut url = Some(format!(
+            "{}/repos/{}/commits?path={}&per_page={}",
+            self.api_url, repo, path, per_page
+        ));
+        let mut commits = Vec::new();
+
+        while let Some(page_url) = url.take() {
+            let response = self.get(&page_url).await?;
+            url = next_page_url(response.headers());
+
+            let page: Vec<GitHubCommit> = response
+                .json()
+                .await
+                .map_err(|e| ApiError::InvalidResponse(e.to_string()))?;
+
+            for commit in page {
+                if commit.sha == since_sha || commits.len() == limit {
+                    return Ok(commits);
+                }
+                commits.push(commit);
+            }
+        }
+
+        Ok(commits)
+    }
+
     /// Get file information from GitHub
     pub async fn get_file_info(
         &self,
@@ -95,23 +424,11 @@ impl GitHubClient {
         branch: &str,
     ) -> Result<GitHubFile, ApiError> {
         let url = format!(
-            "https://api.github.com/repos/{}/contents/{}?ref={}",
-            repo, path, branch
+            "{}/repos/{}/contents/{}?ref={}",
+            self.api_url, repo, path, branch
         );
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ApiError::RequestFailed(e))?;
-
-        if !response.status().is_success() {
-            return Err(ApiError::ApiError(format!(
-                "GitHub API error: {}",
-                response.status()
-            )));
-        }
+        let response = self.get(&url).await?;
 
         response
             .json()
@@ -119,6 +436,47 @@ impl GitHubClient {
             .map_err(|e| ApiError::InvalidResponse(e.to_string()))
     }
 
+    /// List the entries of a directory, following `Link: rel="next"` pages
+    pub async fn list_directory(
+        &self,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<Vec<GitHubFile>, ApiError> {
+        let mut url = Some(format!(
+            "{}/repos/{}/contents/{}?ref={}",
+            self.api_url,
+            repo,
+            path.trim_matches('/'),
+            branch
+        ));
+        let mut entries = Vec::new();
+
+        while let Some(page_url) = url.take() {
+            let response = self.get(&page_url).await?;
+            url = next_page_url(response.headers());
+
+            let body: serde_json::Value = response
+                .json()
+                .await
+                .map_err(|e| ApiError::InvalidResponse(e.to_string()))?;
+
+            // A file path returns a single object instead of an array
+            if !body.is_array() {
+                return Err(ApiError::InvalidResponse(format!(
+                    "{} is not a directory",
+                    path
+                )));
+            }
+
+            let page: Vec<GitHubFile> = serde_json::from_value(body)
+                .map_err(|e| ApiError::InvalidResponse(e.to_string()))?;
+            entries.extend(page);
+        }
+
+        Ok(entries)
+    }
+
     /// Check if data source has updates available
     pub async fn check_for_updates(
         &self,
@@ -136,19 +494,153 @@ impl GitHubClient {
     }
 }
 
+/// Extract the `rel="next"` URL from a Link header
+fn next_page_url(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(reqwest::header::LINK)?.to_str().ok()?;
+
+    link.split(',').find_map(|part| {
+        let (target, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"next\"")
+            .then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
 impl Default for GitHubClient {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[async_trait]
+impl CommitProvider for GitHubClient {
+    async fn get_latest_commit(&self, repo: &str, path: &str) -> Result<GitHubCommit, ApiError> {
+        GitHubClient::get_latest_commit(self, repo, path).await
+    }
+
+    async fn get_commits_since(
+        &self,
+        repo: &str,
+        path: &str,
+        since_sha: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubCommit>, ApiError> {
+        GitHubClient::get_commits_since(self, repo, path, since_sha, limit).await
+    }
+
+    async fn get_file_info(
+        &self,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<GitHubFile, ApiError> {
+        GitHubClient::get_file_info(self, repo, path, branch).await
+    }
+
+    async fn list_directory(
+        &self,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<Vec<GitHubFile>, ApiError> {
+        GitHubClient::list_directory(self, repo, path, branch).await
+    }
+}
+
+/// Share one provider between an `UpdateChecker` and its owner (e.g., tests)
+#[async_trait]
+impl<T: CommitProvider + ?Sized> CommitProvider for Arc<T> {
+    async fn get_latest_commit(&self, repo: &str, path: &str) -> Result<GitHubCommit, ApiError> {
+        (**self).get_latest_commit(repo, path).await
+    }
+
+    async fn get_commits_since(
+        &self,
+        repo: &str,
+        path: &str,
+        since_sha: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubCommit>, ApiError> {
+        (**self).get_commits_since(repo, path, since_sha, limit).await
+    }
+
+    async fn get_file_info(
+        &self,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<GitHubFile, ApiError> {
+        (**self).get_file_info(repo, path, branch).await
+    }
+
+    async fn list_directory(
+        &self,
+        repo: &str,
+        path: &str,
+        branch: &str,
+    ) -> Result<Vec<GitHubFile>, ApiError> {
+        (**self).list_directory(repo, path, branch).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_github_client_creation() {
-        let _client = GitHubClient::new();
+        let client = GitHubClient::new();
+
+        assert!(client.rate_limit_status().is_none());
+    }
+
+    #[test]
+    fn test_config_debug_redacts_token() {
+        let config = GitHubConfig {
+            token: Some("ghp_secret".to_string()),
+            api_url: None,
+        };
+
+        let printed = format!("{:?}", config);
+        assert!(!printed.contains("ghp_secret"));
+        assert!(GitHubClient::from_config(&config).is_authenticated());
+        assert!(!GitHubClient::from_config(&GitHubConfig::default()).is_authenticated());
+    }
+
+    #[test]
+    fn test_next_page_url() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            reqwest::header::LINK,
+            "<https://api.github.com/x?page=2>; rel=\"next\", <https://api.github.com/x?page=5>; rel=\"last\""
+                .parse()
+                .unwrap(),
+        );
+
+        assert_eq!(
+            next_page_url(&headers).as_deref(),
+            Some("https://api.github.com/x?page=2")
+        );
+        assert!(next_page_url(&HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", "60".parse().unwrap());
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        headers.insert("x-ratelimit-reset", "1735689600".parse().unwrap());
+
+        let status = RateLimitStatus::from_headers(&headers);
+
+        assert_eq!(status.limit, Some(60));
+        assert!(status.is_exhausted());
+        assert_eq!(
+            status.reset_at,
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(status.retry_at(), status.reset_at);
     }
 
     // Note: These integration tests require network access
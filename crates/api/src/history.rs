@@ -0,0 +1,322 @@
+//! Analysis history kept in the data directory
+//!
+//! Each seed search or batch ranking can be recorded as one JSON line in
+//! `history.jsonl`, with the weights' hash and the LUT version it ran
+//! against, so a later run can be compared with it after the weights
+//! changed.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use poe_item_analyzer_core::analyzers::{RunDiff, RunEntry, TimelessJewelConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::error::{file_name, DownloadError, FileContext, FileOperation};
+
+/// Name of the history file inside a data directory
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// Results kept per run
+pub const HISTORY_TOP: usize = 100;
+
+/// What kind of run was recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunKind {
+    SeedSearch,
+    Batch,
+}
+
+/// One recorded run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRun {
+    /// Id assigned when recorded ("1" for the first run of a history)
+    #[serde(default)]
+    pub id: String,
+
+    pub timestamp: DateTime<Utc>,
+
+    pub kind: RunKind,
+
+    /// What was run (e.g., "Lethal Pride (Kaom)")
+    pub label: String,
+
+    /// Hash of the weights config (see `config_hash`)
+    pub config_hash: String,
+
+    /// Version of the LUT the run used, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lut_version: Option<String>,
+
+    /// Best results, best first (at most `HISTORY_TOP`)
+    pub results: Vec<RunEntry>,
+}
+
+impl HistoryRun {
+    /// A run made now, keeping the best `HISTORY_TOP` of `results`
+    pub fn new(
+        kind: RunKind,
+        label: impl Into<String>,
+        config: &TimelessJewelConfig,
+        lut_version: Option<String>,
+        mut results: Vec<RunEntry>,
+    ) -> Self {
+        results.truncate(HISTORY_TOP);
+        Self {
+            id: String::new(),
+            timestamp: Utc::now(),
+            kind,
+            label: label.into(),
+            config_hash: config_hash(config),
+            lut_version,
+            results,
+        }
+    }
+
+    /// Whether `other` ran the same kind of search on the same jewel
+    /// (e.g., two seed searches of "Lethal Pride (Kaom)"), so their results
+    /// can be compared
+    pub fn is_rerun_of(&self, other: &HistoryRun) -> bool {
+        self.kind == other.kind && self.label == other.label
+    }
+}
+
+/// Short hash identifying a weights config
+///
+/// Independent of the order of map keys and set elements, so loading the
+/// same config again gives the same hash.
+pub fn config_hash(config: &TimelessJewelConfig) -> String {
+    let value = serde_json::to_value(config).unwrap_or(Value::Null);
+    let digest = Sha256::digest(canonical_json(&value).as_bytes());
+    digest[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// `value` as JSON with object keys and array elements sorted
+///
+/// The config's arrays are all sets (e.g., allocated nodes).
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Array(items) => {
+            let mut items: Vec<String> = items.iter().map(canonical_json).collect();
+            items.sort();
+            format!("[{}]", items.join(","))
+        }
+        Value::Object(map) => {
+            let entry = |(key, value): (&String, &Value)| {
+                format!("{}:{}", Value::from(key.as_str()), canonical_json(value))
+            };
+            let mut entries: Vec<String> = map.iter().map(entry).collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(","))
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// The history file of a data directory
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// Store the history in the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// History of the data directory `data_dir`
+    pub fn in_data_dir(data_dir: &Path) -> Self {
+        Self::new(data_dir.join(HISTORY_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `run`, returning it with its new id
+    pub fn record(&self, mut run: HistoryRun) -> Result<HistoryRun, DownloadError> {
+        let next_id = self
+            .list_history()?
+            .iter()
+            .filter_map(|run| run.id.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        run.id = next_id.to_string();
+
+        let mut line = serde_json::to_string(&run).map_err(|e| {
+            DownloadError::file_failed(FileOperation::Write, file_name(&self.path), e)
+        })?;
+        line.push('\n');
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).file_context(FileOperation::Write, parent)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .file_context(FileOperation::Write, &self.path)?;
+
+        Ok(run)
+    }
+
+    /// Every recorded run, oldest first
+    ///
+    /// A missing file is an empty history; unreadable lines are skipped.
+    pub fn list_history(&self) -> Result<Vec<HistoryRun>, DownloadError> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(DownloadError::io(FileOperation::Read, &self.path, e)),
+        };
+
+        Ok(text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(index, line)| match serde_json::from_str(line) {
+                Ok(run) => Some(run),
+                Err(e) => {
+                    warn!("Skipping line {} of {}: {}", index + 1, self.path.display(), e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// The run with `id`, if recorded
+    pub fn get(&self, id: &str) -> Result<Option<HistoryRun>, DownloadError> {
+        Ok(self.list_history()?.into_iter().find(|run| run.id == id))
+    }
+
+    /// Score changes from the run `previous_run_id` to the latest rerun of
+    /// it (see `HistoryRun::is_rerun_of`)
+    ///
+    /// None if the run isn't recorded or wasn't rerun since.
+    pub fn diff_against(&self, previous_run_id: &str) -> Result<Option<RunDiff>, DownloadError> {
+        let runs = self.list_history()?;
+        let Some(position) = runs.iter().position(|run| run.id == previous_run_id) else {
+            return Ok(None);
+        };
+        let previous = &runs[position];
+        let latest = runs[position + 1..].iter().rev().find(|run| run.is_rerun_of(previous));
+
+        Ok(latest.map(|latest| RunDiff::new(&previous.results, &latest.results)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poe_item_analyzer_core::analyzers::ScoreChange;
+    use tempfile::TempDir;
+
+    fn entry(seed: u32, score: f64) -> RunEntry {
+        RunEntry {
+            jewel_id: format!("Lethal Pride:{}:Kaom", seed),
+            seed,
+            score,
+        }
+    }
+
+    fn config(weight: f64) -> TimelessJewelConfig {
+        let mut config = TimelessJewelConfig::new();
+        config.add_mod("Double Damage".to_string(), weight);
+        config.add_mod("Onslaught".to_string(), 1.0);
+        config.add_mod_per_value("+10 to Strength".to_string(), 0.5);
+        config.add_mod_per_value("+10 to Dexterity".to_string(), 0.5);
+        config
+    }
+
+    fn run(weight: f64, results: Vec<RunEntry>) -> HistoryRun {
+        let label = "Lethal Pride (Kaom)";
+        HistoryRun::new(RunKind::SeedSearch, label, &config(weight), None, results)
+    }
+
+    #[test]
+    fn test_config_hash_is_stable() {
+        assert_eq!(config_hash(&config(5.0)), config_hash(&config(5.0)));
+        assert_ne!(config_hash(&config(5.0)), config_hash(&config(6.0)));
+        assert_eq!(config_hash(&config(5.0)).len(), 16);
+    }
+
+    #[test]
+    fn test_record_and_diff_runs() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HistoryStore::in_data_dir(&temp_dir.path().join("data"));
+        assert!(store.list_history().unwrap().is_empty());
+
+        let first = store
+            .record(run(5.0, vec![entry(10000, 10.0), entry(11000, 8.0), entry(12000, 6.0)]))
+            .unwrap();
+        let second = store
+            .record(run(6.0, vec![entry(11000, 9.5), entry(10000, 7.0), entry(13000, 5.0)]))
+            .unwrap();
+        assert_eq!((first.id.as_str(), second.id.as_str()), ("1", "2"));
+        assert_ne!(first.config_hash, second.config_hash);
+
+        let history = store.list_history().unwrap();
+        assert_eq!(history, vec![first, second]);
+
+        let diff = store.diff_against("1").unwrap().unwrap();
+        let seeds = |change| diff.with_change(change).map(|d| d.seed).collect::<Vec<_>>();
+        assert_eq!(seeds(ScoreChange::Improved), vec![11000]);
+        assert_eq!(seeds(ScoreChange::Worsened), vec![10000]);
+        assert_eq!(seeds(ScoreChange::New), vec![13000]);
+        assert_eq!(seeds(ScoreChange::Dropped), vec![12000]);
+        assert_eq!(diff.seeds[0].score_delta(), 1.5);
+
+        assert_eq!(store.diff_against("7").unwrap(), None);
+    }
+
+    #[test]
+    fn test_only_reruns_are_diffed() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HistoryStore::in_data_dir(temp_dir.path());
+        let label = "Lethal Pride (Kaom)";
+        let batch = |score| {
+            HistoryRun::new(RunKind::Batch, label, &config(5.0), None, vec![entry(10000, score)])
+        };
+
+        store.record(run(5.0, vec![entry(10000, 10.0)])).unwrap();
+        store.record(batch(3.0)).unwrap();
+        let mut other = run(5.0, vec![entry(10000, 1.0)]);
+        other.label = "Lethal Pride (Akoya)".to_string();
+        store.record(other).unwrap();
+
+        // Neither the batch nor the other jewel's search is a rerun of run 1
+        assert_eq!(store.diff_against("1").unwrap(), None);
+        assert_eq!(store.diff_against("2").unwrap(), None);
+
+        // The latest rerun is compared, whatever was recorded after it
+        store.record(run(5.0, vec![entry(10000, 12.0)])).unwrap();
+        store.record(batch(4.0)).unwrap();
+        let diff = store.diff_against("1").unwrap().unwrap();
+        assert_eq!((diff.seeds[0].score_a, diff.seeds[0].score_b), (Some(10.0), Some(12.0)));
+        let diff = store.diff_against("2").unwrap().unwrap();
+        assert_eq!((diff.seeds[0].score_a, diff.seeds[0].score_b), (Some(3.0), Some(4.0)));
+    }
+
+    #[test]
+    fn test_unreadable_lines_are_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HistoryStore::in_data_dir(temp_dir.path());
+
+        store.record(run(5.0, vec![entry(10000, 10.0)])).unwrap();
+        let mut text = std::fs::read_to_string(store.path()).unwrap();
+        text.push_str("{not json\n");
+        std::fs::write(store.path(), text).unwrap();
+
+        let recorded = store.record(run(5.0, Vec::new())).unwrap();
+        assert_eq!(recorded.id, "2");
+        assert_eq!(store.list_history().unwrap().len(), 2);
+    }
+}
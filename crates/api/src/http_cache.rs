@@ -0,0 +1,132 @@
+//! HTTP validator cache for conditional downloads
+//!
+//! Stores the ETag and Last-Modified headers returned for each downloaded
+//! file in a sidecar file next to the data, so later syncs can send
+//! If-None-Match / If-Modified-Since and skip unchanged files on a 304.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{file_name, DownloadError, FileContext, FileOperation};
+
+/// Validators returned by the server for one file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheValidators {
+    /// ETag header value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+
+    /// Last-Modified header value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    /// Read validators from response headers
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+
+        Self {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        }
+    }
+
+    /// Check if there is nothing to revalidate with
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Validators for every file in a data directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HttpCache {
+    /// Validators keyed by file name
+    pub files: BTreeMap<String, CacheValidators>,
+}
+
+impl HttpCache {
+    /// Name of the cache file inside a data directory
+    pub const FILE_NAME: &'static str = ".http-cache.json";
+
+    /// Path of the cache file for a data directory
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(Self::FILE_NAME)
+    }
+
+    /// Load the cache for a data directory
+    ///
+    /// A missing or unreadable cache is treated as empty, which only means
+    /// the next download is unconditional.
+    pub fn load(data_dir: &Path) -> Self {
+        std::fs::read_to_string(Self::path(data_dir))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the cache into a data directory
+    pub fn save(&self, data_dir: &Path) -> Result<(), DownloadError> {
+        let path = Self::path(data_dir);
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| DownloadError::file_failed(FileOperation::Write, file_name(&path), e))?;
+
+        std::fs::write(&path, json).file_context(FileOperation::Write, &path)
+    }
+
+    /// Validators for a file, if any were recorded
+    pub fn get(&self, file_name: &str) -> Option<&CacheValidators> {
+        self.files.get(file_name).filter(|v| !v.is_empty())
+    }
+
+    /// Record validators for a file (empty validators remove the entry)
+    pub fn insert(&mut self, file_name: &str, validators: CacheValidators) {
+        if validators.is_empty() {
+            self.files.remove(file_name);
+        } else {
+            self.files.insert(file_name.to_string(), validators);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut cache = HttpCache::default();
+        cache.insert(
+            "LethalPride.zip",
+            CacheValidators {
+                etag: Some("\"abc\"".to_string()),
+                last_modified: None,
+            },
+        );
+        cache.insert("Empty.zip", CacheValidators::default());
+        cache.save(temp_dir.path()).unwrap();
+
+        let loaded = HttpCache::load(temp_dir.path());
+        assert_eq!(
+            loaded.get("LethalPride.zip").unwrap().etag.as_deref(),
+            Some("\"abc\"")
+        );
+        assert!(loaded.get("Empty.zip").is_none());
+    }
+
+    #[test]
+    fn test_missing_cache_is_empty() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(HttpCache::load(temp_dir.path()).files.is_empty());
+    }
+}
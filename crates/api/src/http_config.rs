@@ -0,0 +1,61 @@
+//! Connection settings shared by the HTTP clients
+//!
+//! `GitHubClient`, `PoeApiClient` and `DataDownloader` build their
+//! `reqwest::Client` from an `HttpConfig`, so a proxy or slower network only
+//! has to be configured once.
+
+use std::time::Duration;
+
+/// Timeouts, pooling and proxy for an HTTP client
+///
+/// `read_timeout` is an inactivity timeout, not a limit on the whole
+/// request: downloads fail once no data arrived for that long, however long
+/// the file takes in total. The API clients, whose responses are small
+/// documents, apply it to each request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpConfig {
+    /// Time allowed to establish a connection
+    pub connect_timeout: Duration,
+
+    /// Time allowed without receiving any data
+    pub read_timeout: Duration,
+
+    /// Idle connections kept open per host for reuse
+    pub max_idle_connections: usize,
+
+    /// Proxy for all requests (e.g., "http://proxy.local:8080"), if any
+    pub proxy: Option<String>,
+}
+
+impl HttpConfig {
+    /// Builder for a client with these settings
+    ///
+    /// Fails if the proxy URL is invalid.
+    pub(crate) fn client_builder(&self) -> Result<reqwest::ClientBuilder, reqwest::Error> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .pool_max_idle_per_host(self.max_idle_connections);
+
+        if let Some(proxy) = self.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            builder = builder.proxy(reqwest::Proxy::all(proxy.trim())?);
+        }
+
+        Ok(builder)
+    }
+
+    /// Build a client with these settings
+    pub fn build_client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        self.client_builder()?.build()
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            max_idle_connections: 8,
+            proxy: None,
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Shared retry handling for HTTP clients
+//!
+//! `send_with_retry` retries connection errors, timeouts, 5xx responses and
+//! 429 responses whose `Retry-After` is short enough to wait out. Everything
+//! else is returned as-is so each client can map statuses to its own errors.
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+/// Longest `Retry-After` that is waited out before retrying
+///
+/// Longer waits are left to the caller, which usually reports them as a
+/// rate limit error instead of blocking.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Retry policy for transient HTTP failures
+///
+/// Connection errors, timeouts and 5xx responses are retried; other 4xx
+/// responses (and, for downloads, checksum mismatches) are not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts per request, including the first one
+    pub max_attempts: u32,
+
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+
+    /// Factor the delay grows by after each retry
+    pub multiplier: f64,
+
+    /// Random variation applied to each delay (0.1 = ±10%)
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay to wait after the given (1-based) failed attempt
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let base = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 + jitter * (2.0 * random_unit() - 1.0);
+
+        Duration::from_secs_f64((base * factor).max(0.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.1,
+        }
+    }
+}
+
+/// Random value in [0, 1) for retry jitter
+fn random_unit() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Whether a request error is worth retrying (connection problems and timeouts)
+pub fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request() || error.is_body()
+}
+
+/// Seconds from a `Retry-After` header
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Send `request` with `client`, retrying transient failures per `policy`
+///
+/// Returns the last response or error once attempts run out. Requests with
+/// a streaming body can't be cloned and are sent only once.
+pub async fn send_with_retry(
+    client: &reqwest::Client,
+    request: reqwest::RequestBuilder,
+    policy: &RetryPolicy,
+) -> Result<reqwest::Response, reqwest::Error> {
+    send_with_retry_hooked(client, request, policy, || async {}, |_| {}).await
+}
+
+/// `send_with_retry`, awaiting `before_attempt` ahead of every attempt and
+/// passing every response to `after_attempt`
+///
+/// Lets a rate limiter count and learn from retries as well.
+pub async fn send_with_retry_hooked<B, Fut, A>(
+    client: &reqwest::Client,
+    request: reqwest::RequestBuilder,
+    policy: &RetryPolicy,
+    before_attempt: B,
+    after_attempt: A,
+) -> Result<reqwest::Response, reqwest::Error>
+where
+    B: Fn() -> Fut,
+    Fut: Future<Output = ()>,
+    A: Fn(&reqwest::Response),
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt = 1;
+
+    loop {
+        let next = if attempt < max_attempts {
+            request.try_clone()
+        } else {
+            None
+        };
+        let Some(next) = next else {
+            before_attempt().await;
+            let response = client.execute(request.build()?).await?;
+            after_attempt(&response);
+            return Ok(response);
+        };
+
+        before_attempt().await;
+        let result = client.execute(next.build()?).await;
+        if let Ok(response) = &result {
+            after_attempt(response);
+        }
+        let delay = match result {
+            Ok(response) => match retry_delay(policy, attempt, &response) {
+                Some(delay) => delay,
+                None => return Ok(response),
+            },
+            Err(error) if is_transient(&error) => policy.delay_for(attempt),
+            Err(error) => return Err(error),
+        };
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// How long to wait before retrying after `response`, or None to return it
+fn retry_delay(policy: &RetryPolicy, attempt: u32, response: &reqwest::Response) -> Option<Duration> {
+    let status = response.status();
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return retry_after(response.headers()).filter(|wait| *wait <= MAX_RETRY_AFTER);
+    }
+
+    status.is_server_error().then(|| policy.delay_for(attempt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static(" 12 "));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(12)));
+
+        // HTTP dates are not supported
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_retry_policy_none() {
+        assert_eq!(RetryPolicy::none().max_attempts, 1);
+    }
+}
@@ -0,0 +1,316 @@
+//! Per-version layout of the data directory
+//!
+//! Data of several PoB versions (e.g., for 3.24 and 3.25 leagues running at
+//! the same time) is kept side by side under one root, so switching between
+//! them doesn't need a download:
+//!
+//! ```text
+//! <root>/<data_version>/manifest.json
+//! <root>/<data_version>/raw/      downloaded data files
+//! <root>/<data_version>/parsed/   parsed LUTs and their reports
+//! ```
+//!
+//! Directories from before the layout, with everything directly in the root,
+//! are moved into it by `DataLayout::migrate_flat`.
+
+use std::path::{Path, PathBuf};
+
+use tracing::info;
+
+use crate::error::{DownloadError, FileContext, FileOperation};
+use crate::http_cache::HttpCache;
+use crate::manifest::{is_data_file_name, DataManifest};
+
+/// Manifest of a version, next to its `raw` and `parsed` directories
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Downloaded data files of a version
+const RAW_DIR: &str = "raw";
+
+/// Parsed LUTs and reports of a version
+const PARSED_DIR: &str = "parsed";
+
+/// Paths of the data versions under a root directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataLayout {
+    root: PathBuf,
+}
+
+impl DataLayout {
+    /// Layout under `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Directory holding every version
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory of `version` (a manifest `data_version`)
+    pub fn version_dir(&self, version: &str) -> PathBuf {
+        self.root.join(version)
+    }
+
+    /// Downloaded data files of `version`
+    pub fn raw_dir(&self, version: &str) -> PathBuf {
+        self.version_dir(version).join(RAW_DIR)
+    }
+
+    /// Parsed LUTs of `version`
+    pub fn parsed_dir(&self, version: &str) -> PathBuf {
+        self.version_dir(version).join(PARSED_DIR)
+    }
+
+    /// Manifest of `version`
+    pub fn manifest_path(&self, version: &str) -> PathBuf {
+        self.version_dir(version).join(MANIFEST_FILE)
+    }
+
+    /// Create the directories of `version`
+    pub fn create(&self, version: &str) -> Result<(), DownloadError> {
+        for dir in [self.raw_dir(version), self.parsed_dir(version)] {
+            std::fs::create_dir_all(&dir).file_context(FileOperation::Write, &dir)?;
+        }
+        Ok(())
+    }
+
+    /// Installed versions, most recently updated first
+    ///
+    /// A version is installed once its manifest has been saved. A missing
+    /// root has none.
+    pub fn versions(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+
+        let mut versions: Vec<(String, String)> = entries
+            .filter_map(|entry| {
+                let version = entry.ok()?.file_name().into_string().ok()?;
+                let manifest = DataManifest::load_from_file(&self.manifest_path(&version)).ok()?;
+                Some((manifest.last_updated, version))
+            })
+            .collect();
+        versions.sort_by(|a, b| b.cmp(a));
+
+        versions.into_iter().map(|(_, version)| version).collect()
+    }
+
+    /// The most recently updated installed version
+    pub fn latest_version(&self) -> Option<String> {
+        self.versions().into_iter().next()
+    }
+
+    /// Whether the root holds data directly, as directories did before the layout
+    pub fn is_flat(&self) -> bool {
+        if self.root.join(MANIFEST_FILE).is_file() {
+            return true;
+        }
+
+        std::fs::read_dir(&self.root).is_ok_and(|entries| {
+            entries.filter_map(Result::ok).any(|entry| {
+                entry.file_type().is_ok_and(|kind| kind.is_file())
+                    && is_data_file_name(&entry.file_name().to_string_lossy())
+            })
+        })
+    }
+
+    /// Move the files of a flat root into the version its manifest names
+    ///
+    /// Data files, their parts and the HTTP cache of their downloads go to
+    /// `raw`, the manifest to the version directory and everything else
+    /// (parsed LUTs, reports) to `parsed`; the recorded parsed artifact is
+    /// pointed at its new place. Without a manifest the files become the
+    /// built-in manifest's version. Subdirectories and the manifest's lock
+    /// are left alone.
+    ///
+    /// Returns the version the files were moved to, or None if the root
+    /// wasn't flat.
+    pub fn migrate_flat(&self) -> Result<Option<String>, DownloadError> {
+        if !self.is_flat() {
+            return Ok(None);
+        }
+
+        let flat_manifest_path = self.root.join(MANIFEST_FILE);
+        let mut manifest = DataManifest::load_from_file(&flat_manifest_path)
+            .unwrap_or_else(|_| DataManifest::default_pob());
+        let version = manifest.data_version.clone();
+        self.create(&version)?;
+
+        let raw_dir = self.raw_dir(&version);
+        let parsed_dir = self.parsed_dir(&version);
+        let entries = std::fs::read_dir(&self.root).file_context(FileOperation::Read, &self.root)?;
+        for entry in entries {
+            let entry = entry.file_context(FileOperation::Read, &self.root)?;
+            let path = entry.path();
+            if !entry.file_type().file_context(FileOperation::Read, &path)?.is_file() {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let target_dir = if name == MANIFEST_FILE || name.starts_with(".manifest.json.") {
+                continue;
+            } else if is_raw_file_name(&name) {
+                &raw_dir
+            } else {
+                &parsed_dir
+            };
+            let target = target_dir.join(&name);
+            std::fs::rename(&path, &target).file_context(FileOperation::Write, &target)?;
+        }
+
+        // The artifact was recorded relative to the flat root
+        if let Some(artifact) = &mut manifest.parsed_artifact {
+            let moved = parsed_dir.join(&artifact.path);
+            if moved.is_file() {
+                artifact.path = moved.to_string_lossy().into_owned();
+            }
+        }
+
+        let manifest_path = self.manifest_path(&version);
+        manifest
+            .save_atomic(&manifest_path)
+            .file_context(FileOperation::Write, &manifest_path)?;
+        if flat_manifest_path.exists() {
+            std::fs::remove_file(&flat_manifest_path)
+                .file_context(FileOperation::Write, &flat_manifest_path)?;
+        }
+
+        info!("Moved the data in {} to version {}", self.root.display(), version);
+        Ok(Some(version))
+    }
+}
+
+/// Whether a file of a flat directory belongs in `raw`
+fn is_raw_file_name(name: &str) -> bool {
+    let part_of = name.rsplit_once(".part").map(|(file, _)| file);
+    is_data_file_name(name)
+        || part_of.is_some_and(is_data_file_name)
+        || name == HttpCache::FILE_NAME
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{LutData, PobDataParser};
+    use tempfile::TempDir;
+
+    /// Save a small LUT to `path`
+    fn save_lut(path: &Path) -> LutData {
+        let data = LutData {
+            version: "fixture".to_string(),
+            node_indices: Default::default(),
+            modifiers: Default::default(),
+            modifier_indices: Default::default(),
+            jewels: Default::default(),
+            trade_stat_ids: Default::default(),
+            tree_version: None,
+        };
+        PobDataParser::save_to_binary(&data, path).unwrap();
+        data
+    }
+
+    fn manifest(version: &str, last_updated: &str) -> DataManifest {
+        let mut manifest = DataManifest::default_pob();
+        manifest.data_version = version.to_string();
+        manifest.last_updated = last_updated.to_string();
+        manifest
+    }
+
+    #[test]
+    fn test_layout_paths_and_creation() {
+        let temp_dir = TempDir::new().unwrap();
+        let layout = DataLayout::new(temp_dir.path().join("pob-data"));
+
+        assert_eq!(layout.raw_dir("abc"), temp_dir.path().join("pob-data/abc/raw"));
+        assert_eq!(layout.parsed_dir("abc"), temp_dir.path().join("pob-data/abc/parsed"));
+        assert_eq!(
+            layout.manifest_path("abc"),
+            temp_dir.path().join("pob-data/abc/manifest.json")
+        );
+
+        assert!(layout.versions().is_empty());
+        layout.create("abc").unwrap();
+        assert!(layout.raw_dir("abc").is_dir() && layout.parsed_dir("abc").is_dir());
+
+        // Not installed until its manifest is saved
+        assert!(layout.versions().is_empty());
+        assert!(!layout.is_flat());
+    }
+
+    #[test]
+    fn test_versions_newest_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let layout = DataLayout::new(temp_dir.path());
+
+        for (version, last_updated) in [
+            ("3-24", "2024-08-01T00:00:00Z"),
+            ("3-25", "2025-01-01T00:00:00Z"),
+            ("3-23", "2024-01-01T00:00:00Z"),
+        ] {
+            layout.create(version).unwrap();
+            manifest(version, last_updated)
+                .save_to_file(&layout.manifest_path(version))
+                .unwrap();
+        }
+        std::fs::create_dir(temp_dir.path().join("unrelated")).unwrap();
+
+        assert_eq!(layout.versions(), vec!["3-25", "3-24", "3-23"]);
+        assert_eq!(layout.latest_version().as_deref(), Some("3-25"));
+    }
+
+    #[test]
+    fn test_migrate_flat_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let layout = DataLayout::new(root);
+
+        std::fs::write(root.join("LethalPride.zip"), b"zip").unwrap();
+        std::fs::write(root.join("GloriousVanity.zip.part0"), b"part").unwrap();
+        std::fs::write(root.join("LegionPassives.lua"), b"lua").unwrap();
+        std::fs::write(root.join(HttpCache::FILE_NAME), b"{}").unwrap();
+        std::fs::create_dir(root.join("old")).unwrap();
+        let data = save_lut(&root.join("lut_data.bin"));
+
+        let mut flat = manifest("abc123", "2025-01-01T00:00:00Z");
+        flat.record_parsed_artifact(root, &root.join("lut_data.bin"), "bincode").unwrap();
+        flat.save_to_file(&root.join(MANIFEST_FILE)).unwrap();
+        assert!(layout.is_flat());
+
+        assert_eq!(layout.migrate_flat().unwrap().as_deref(), Some("abc123"));
+
+        let raw = layout.raw_dir("abc123");
+        for name in ["LethalPride.zip", "GloriousVanity.zip.part0", "LegionPassives.lua"] {
+            assert!(raw.join(name).is_file(), "{}", name);
+        }
+        assert!(raw.join(HttpCache::FILE_NAME).is_file());
+        assert!(layout.parsed_dir("abc123").join("lut_data.bin").is_file());
+        assert!(root.join("old").is_dir());
+        assert!(!root.join(MANIFEST_FILE).exists());
+        assert!(!layout.is_flat());
+
+        // The parsed LUT is still found, and current
+        let migrated = DataManifest::load_from_file(&layout.manifest_path("abc123")).unwrap();
+        let artifact = migrated.parsed_artifact.as_ref().unwrap();
+        assert!(!migrated.needs_reparse(&raw).unwrap());
+        let loaded = PobDataParser::load_artifact(&artifact.resolve(&raw), "bincode").unwrap();
+        assert_eq!(loaded.version, data.version);
+        assert_eq!(layout.versions(), vec!["abc123"]);
+
+        // Nothing left to migrate
+        assert_eq!(layout.migrate_flat().unwrap(), None);
+    }
+
+    #[test]
+    fn test_migrate_flat_without_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let layout = DataLayout::new(temp_dir.path());
+        std::fs::write(temp_dir.path().join("NodeIndexMapping.lua"), b"lua").unwrap();
+
+        let version = layout.migrate_flat().unwrap().unwrap();
+
+        assert_eq!(version, DataManifest::default_pob().data_version);
+        assert!(layout.raw_dir(&version).join("NodeIndexMapping.lua").is_file());
+        assert_eq!(layout.versions(), vec![version]);
+    }
+}
@@ -12,16 +12,56 @@ pub mod downloader;
 pub mod manifest;
 pub mod github;
 pub mod update_checker;
+pub mod update_watcher;
 pub mod checksum;
+pub mod disk_space;
+pub mod http_cache;
+pub mod history;
+pub mod layout;
+pub mod http_util;
+pub mod http_config;
+pub mod validation;
+pub mod test_support;
 pub mod parser;
+pub mod lut_service;
+pub mod pob_build;
+pub mod repair;
 pub mod error;
 
 #[cfg(test)]
 mod tests;
 
-pub use error::{ApiError, DownloadError, SourceError};
-pub use manifest::{DataFile, DataManifest, DataSource};
-pub use github::GitHubClient;
-pub use update_checker::{UpdateChecker, UpdateInfo};
-pub use parser::{LutData, NodeModifier, PobDataParser};
-pub use downloader::DataDownloader;
+pub use error::{ApiError, BuildCodeError, DownloadError, FileContext, FileOperation, SourceError};
+pub use manifest::{
+    DataFile, DataManifest, DataSource, FilePart, ManifestDiff, ManifestLock, ParsedArtifact,
+};
+pub use github::{
+    data_files_from_listing, CommitProvider, CommitSummary, GitHubClient, GitHubConfig,
+    GitHubFile, RateLimitStatus,
+};
+pub use http_cache::HttpCache;
+pub use history::{HistoryRun, HistoryStore, RunKind};
+pub use layout::DataLayout;
+pub use http_util::send_with_retry;
+pub use http_config::HttpConfig;
+pub use poe_api::{
+    current_challenge_league, CharacterPassives, League, PoeApiClient, RateLimitState, RateLimiter, StashItem,
+    StashTab, TradeListing, TradeSearch,
+};
+pub use sources::{
+    ClipboardTextSource, CompositeFetch, CompositeSource, ItemSource, LocalFileSource,
+    SourceReport, StashTabSource,
+};
+pub use validation::{ValidationResult, ValidationStatus};
+pub use update_checker::{
+    ChangedFile, UpdateChecker, UpdateEvent, UpdateInfo, UpdateOutcome, UpdateStage,
+};
+pub use update_watcher::UpdateWatcher;
+pub use lut_service::{LutHandle, LutService};
+pub use pob_build::{decode_build_code, Attribute, PobBuild};
+pub use repair::{repair, RepairReport};
+pub use parser::{LutData, NodeModifier, ParseEvent, ParseMode, ParseReport, PobDataParser};
+pub use downloader::{
+    join_parts, progress_channel, CancellationToken, DataDownloader, DownloadEvent, ProgressEvent,
+    RetryPolicy, SyncReport,
+};
@@ -0,0 +1,302 @@
+//! Shared parsed LUT for concurrent analyses
+//!
+//! `LutService` holds the current `LutData` behind an `Arc`, so the seed
+//! search, batch analyses and the mod browser share one copy instead of
+//! cloning it. `swap` replaces the data after an update; consumers keep the
+//! snapshot they started with and can watch their `LutHandle` to learn that
+//! newer data is available.
+
+use std::sync::Arc;
+
+use poe_item_analyzer_core::data::TimelessLookup;
+use poe_item_analyzer_core::items::JewelType;
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::parser::LutData;
+
+/// Data as of one swap
+#[derive(Clone)]
+struct Snapshot {
+    /// Number of swaps before this data was installed
+    generation: u64,
+    data: Arc<LutData>,
+}
+
+/// Current LUT, shared between threads
+///
+/// Clones share the same data.
+#[derive(Clone)]
+pub struct LutService {
+    current: Arc<watch::Sender<Snapshot>>,
+}
+
+impl LutService {
+    /// Share `data`
+    pub fn new(data: impl Into<Arc<LutData>>) -> Self {
+        let (current, _) = watch::channel(Snapshot {
+            generation: 0,
+            data: data.into(),
+        });
+
+        Self {
+            current: Arc::new(current),
+        }
+    }
+
+    /// Handle to the current data
+    pub fn handle(&self) -> LutHandle {
+        let mut changes = self.current.subscribe();
+        let snapshot = changes.borrow_and_update().clone();
+        LutHandle { snapshot, changes }
+    }
+
+    /// The current data
+    pub fn data(&self) -> Arc<LutData> {
+        Arc::clone(&self.current.borrow().data)
+    }
+
+    /// Install `data`, returning the data it replaces
+    ///
+    /// Handles taken earlier keep their data and report themselves stale.
+    pub fn swap(&self, data: impl Into<Arc<LutData>>) -> Arc<LutData> {
+        let mut data = data.into();
+        self.current.send_modify(|snapshot| {
+            snapshot.generation += 1;
+            std::mem::swap(&mut snapshot.data, &mut data);
+        });
+        info!("Swapped LUT data {} for {}", data.version, self.data().version);
+        data
+    }
+}
+
+/// A consumer's snapshot of the service's data
+///
+/// Cheap to clone; the data stays alive for as long as a handle holds it,
+/// even after it was swapped out.
+#[derive(Clone)]
+pub struct LutHandle {
+    snapshot: Snapshot,
+    changes: watch::Receiver<Snapshot>,
+}
+
+impl LutHandle {
+    /// The data this handle was taken with
+    pub fn data(&self) -> &Arc<LutData> {
+        &self.snapshot.data
+    }
+
+    /// Whether newer data was swapped in since the handle was taken
+    pub fn is_stale(&self) -> bool {
+        self.changes.borrow().generation != self.snapshot.generation
+    }
+
+    /// Wait until newer data is swapped in (or the service is dropped)
+    pub async fn invalidated(&mut self) {
+        while !self.is_stale() {
+            if self.changes.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Handle to the service's current data
+    pub fn refresh(&self) -> LutHandle {
+        let mut changes = self.changes.clone();
+        let snapshot = changes.borrow_and_update().clone();
+        LutHandle { snapshot, changes }
+    }
+}
+
+impl TimelessLookup for LutHandle {
+    fn node_mods(&self, jewel_type: JewelType, seed: u32, node_id: u32) -> Option<Vec<String>> {
+        self.snapshot.data.node_mods(jewel_type, seed, node_id)
+    }
+
+    fn variant_node_mods(
+        &self,
+        jewel_type: JewelType,
+        seed: u32,
+        node_id: u32,
+        variant: &str,
+    ) -> Option<Vec<String>> {
+        self.snapshot.data.variant_node_mods(jewel_type, seed, node_id, variant)
+    }
+
+    fn has_variants(&self, jewel_type: JewelType, seed: u32) -> bool {
+        self.snapshot.data.has_variants(jewel_type, seed)
+    }
+
+    fn has_seed(&self, jewel_type: JewelType, seed: u32) -> Option<bool> {
+        self.snapshot.data.has_seed(jewel_type, seed)
+    }
+
+    fn nodes(&self) -> Vec<u32> {
+        self.snapshot.data.nodes()
+    }
+
+    fn trade_stat_id(&self, mod_text: &str) -> Option<&str> {
+        self.snapshot.data.trade_stat_id(mod_text)
+    }
+
+    fn tree_version(&self) -> Option<&str> {
+        self.snapshot.data.tree_version()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{JewelLutData, LutEntry, NodeInfo, NodeModifier};
+    use crate::poe_api::CharacterPassives;
+    use poe_item_analyzer_core::analyzers::{
+        AnalysisWarning, Analyzer, TimelessJewelAnalyzer, TimelessJewelConfig,
+    };
+    use poe_item_analyzer_core::items::TimelessJewel;
+    use std::collections::HashMap;
+    use std::sync::Barrier;
+
+    /// LUT where seed 14032 puts `mod_name` on 50 nodes
+    fn lut(version: &str, mod_name: &str) -> LutData {
+        let node_indices = (0..50)
+            .map(|index| {
+                let info = NodeInfo { index, size: 1, name: None, is_notable: true };
+                (index as u32 + 100, info)
+            })
+            .collect();
+        let modifier = NodeModifier {
+            id: "mod".to_string(),
+            display_name: mod_name.to_string(),
+            stat_descriptions: Vec::new(),
+            search_text: String::new(),
+            trade_stat_id: None,
+        };
+        let seed = (0..50).map(|index| (index, LutEntry::from("mod"))).collect();
+
+        LutData {
+            version: version.to_string(),
+            node_indices,
+            modifiers: HashMap::from([("mod".to_string(), modifier)]),
+            modifier_indices: HashMap::new(),
+            jewels: HashMap::from([(
+                "LethalPride".to_string(),
+                JewelLutData {
+                    jewel_type: "LethalPride".to_string(),
+                    seed_range: (10000, 18000),
+                    lookup_table: HashMap::from([(14032, seed)]),
+                    variants: HashMap::new(),
+                    truncated: false,
+                },
+            )]),
+            trade_stat_ids: HashMap::new(),
+            tree_version: None,
+        }
+    }
+
+    fn score(handle: LutHandle) -> f64 {
+        let jewel = TimelessJewel::new(
+            "Lethal Pride:14032:Kaom".to_string(),
+            JewelType::LethalPride,
+            14032,
+            "Kaom".to_string(),
+            serde_json::Value::Null,
+        );
+        let mut config = TimelessJewelConfig::new();
+        config.add_mod("Old".to_string(), 1.0);
+        config.add_mod("New".to_string(), 2.0);
+
+        let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(handle));
+        analyzer.analyze(&jewel, &config).unwrap().best_score
+    }
+
+    #[test]
+    fn test_swap_mid_flight_keeps_each_analysis_consistent() {
+        let service = LutService::new(lut("old", "Old"));
+        let swapped = Arc::new(Barrier::new(3));
+
+        let workers: Vec<_> = (0..2)
+            .map(|_| {
+                let handle = service.handle();
+                let swapped = Arc::clone(&swapped);
+                std::thread::spawn(move || {
+                    let before = score(handle.clone());
+                    swapped.wait();
+                    let after = score(handle.clone());
+                    (before, after, handle.is_stale())
+                })
+            })
+            .collect();
+
+        let previous = service.swap(lut("new", "New"));
+        swapped.wait();
+
+        for worker in workers {
+            // Both analyses ran against the data the handle was taken with
+            assert_eq!(worker.join().unwrap(), (50.0, 50.0, true));
+        }
+        assert_eq!(previous.version, "old");
+        assert_eq!(service.data().version, "new");
+        assert_eq!(score(service.handle()), 100.0);
+    }
+
+    #[test]
+    fn test_handles_share_the_data() {
+        let service = LutService::new(lut("old", "Old"));
+        let handle = service.handle();
+
+        assert!(Arc::ptr_eq(handle.data(), &service.clone().data()));
+        assert!(!handle.is_stale());
+
+        service.swap(lut("new", "New"));
+        assert!(handle.is_stale());
+        assert_eq!(handle.data().version, "old");
+
+        let refreshed = handle.refresh();
+        assert!(!refreshed.is_stale());
+        assert_eq!(refreshed.data().version, "new");
+    }
+
+    #[tokio::test]
+    async fn test_invalidated_wakes_on_swap() {
+        let service = LutService::new(lut("old", "Old"));
+        let mut handle = service.handle();
+
+        let waiter = tokio::spawn(async move {
+            handle.invalidated().await;
+            handle.refresh().data().version.clone()
+        });
+        tokio::task::yield_now().await;
+        service.swap(lut("new", "New"));
+
+        assert_eq!(waiter.await.unwrap(), "new");
+    }
+    #[test]
+    fn test_tree_version_mismatch_with_character_warns() {
+        let mut data = lut("new", "New");
+        data.tree_version = Some("3_25".to_string());
+        let service = LutService::new(data);
+
+        let jewel = TimelessJewel::new(
+            "Lethal Pride:14032:Kaom".to_string(),
+            JewelType::LethalPride,
+            14032,
+            "Kaom".to_string(),
+            serde_json::Value::Null,
+        );
+        let passives: CharacterPassives =
+            serde_json::from_str(r#"{"hashes": [100, 101], "tree_version": "3.24.2"}"#).unwrap();
+        let mut config = passives.to_config();
+        config.add_mod("New".to_string(), 1.0);
+
+        let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(service.handle()));
+        let result = analyzer.analyze(&jewel, &config).unwrap();
+
+        assert_eq!(
+            result.warnings.iter().collect::<Vec<_>>(),
+            vec![&AnalysisWarning::TreeVersionMismatch {
+                data: "3_25".to_string(),
+                character: "3.24.2".to_string(),
+            }]
+        );
+    }
+}
@@ -1,7 +1,36 @@
 //! Data manifest models for tracking and updating game data
 
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::checksum;
+use crate::error::{DownloadError, FileContext, FileOperation};
+use crate::github::{GitHubFile, GITHUB_API_URL};
+use crate::poe_api::{current_challenge_league, League};
+use crate::sources::DownloadSource;
+
+/// Manifest for the PoB TimelessJewelData files, with unknown version and checksums
+const DEFAULT_POB_MANIFEST: &str = include_str!("../../../data/manifest.json");
+
+/// Default host serving raw repository files
+pub const GITHUB_RAW_URL: &str = "https://raw.githubusercontent.com";
+
+/// Numbers the temporary files of `DataManifest::save_atomic` within a process
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Files the parser can't work without
+pub const CORE_FILES: &[&str] = &[
+    "NodeIndexMapping.lua",
+    "LegionPassives.lua",
+    "LethalPride.zip",
+    "BrutalRestraint.zip",
+    "ElegantHubris.zip",
+    "MilitantFaith.zip",
+    "GloriousVanity.zip",
+];
 
 /// Main data manifest
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,9 +49,166 @@ pub struct DataManifest {
 
     /// List of data files
     pub files: Vec<DataFile>,
+
+    /// Upstream versions the user chose not to update to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignored_versions: Vec<String>,
+
+    /// Parsed LUT generated from the data files (None until the first parse)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parsed_artifact: Option<ParsedArtifact>,
+}
+
+/// Parsed LUT file derived from the data files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedArtifact {
+    /// Artifact path, relative to the data directory unless absolute
+    pub path: String,
+
+    /// Serialization format (e.g., "json")
+    pub format: String,
+
+    /// SHA256 of the artifact file
+    pub sha256: String,
+
+    /// `data_version` of the data the artifact was parsed from
+    pub generated_from_version: String,
+
+    /// When the artifact was written (RFC 3339)
+    pub generated_at: String,
+}
+
+impl ParsedArtifact {
+    /// Location of the artifact for data stored in `data_dir`
+    pub fn resolve(&self, data_dir: &Path) -> PathBuf {
+        data_dir.join(&self.path)
+    }
 }
 
 impl DataManifest {
+    /// Built-in manifest for the Path of Building data files
+    ///
+    /// Used on first run, before any manifest has been saved to disk.
+    pub fn default_pob() -> Self {
+        serde_json::from_str(DEFAULT_POB_MANIFEST).expect("embedded manifest is valid")
+    }
+
+    /// Check the manifest for structural problems
+    ///
+    /// Returns every problem found (duplicate or empty names, files with
+    /// neither a URL nor parts, malformed checksums) in one error.
+    pub fn validate(&self) -> Result<(), DownloadError> {
+        let mut problems = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        let is_sha256 = |sha: &str| {
+            sha.is_empty() || (sha.len() == 64 && sha.chars().all(|c| c.is_ascii_hexdigit()))
+        };
+
+        for file in &self.files {
+            if file.name.trim().is_empty() {
+                problems.push("file with an empty name".to_string());
+            } else if !seen.insert(file.name.as_str()) {
+                problems.push(format!("{} is listed twice", file.name));
+            }
+
+            if file.url.is_empty() && !file.is_split() {
+                problems.push(format!("{} has no URL", file.name));
+            }
+            if !is_sha256(&file.sha256) {
+                problems.push(format!("{} has a malformed sha256", file.name));
+            }
+            for (index, part) in file.parts.iter().enumerate() {
+                if part.url.is_empty() {
+                    problems.push(format!("part {} of {} has no URL", index + 1, file.name));
+                }
+                if !is_sha256(&part.sha256) {
+                    problems.push(format!(
+                        "part {} of {} has a malformed sha256",
+                        index + 1,
+                        file.name
+                    ));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(DownloadError::InvalidManifest(problems.join("; ")))
+        }
+    }
+
+    /// Build a manifest describing the data files already in `data_dir`
+    ///
+    /// Every `.lua` and `.zip` file gets an entry with its size, SHA256 and
+    /// git blob SHA;
+    /// the files in `CORE_FILES` are marked required. Entries are sorted by
+    /// name and their URLs point at `source`. The version is unknown until
+    /// an update check fills it in.
+    pub fn generate(
+        data_dir: &Path,
+        source: DataSource,
+        league: &str,
+    ) -> Result<Self, DownloadError> {
+        let mut names = Vec::new();
+
+        let read_error = |e| DownloadError::io(FileOperation::Read, data_dir, e);
+        for entry in std::fs::read_dir(data_dir).map_err(read_error)? {
+            let entry = entry.map_err(read_error)?;
+            if !entry.file_type().map_err(read_error)?.is_file() {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_data_file_name(&name) {
+                names.push(name);
+            }
+        }
+
+        names.sort();
+
+        let mut files = Vec::with_capacity(names.len());
+        for name in names {
+            let path = data_dir.join(&name);
+            let size = std::fs::metadata(&path).file_context(FileOperation::Read, &path)?.len();
+
+            files.push(DataFile {
+                url: source.raw_file_url(&name),
+                sha256: checksum::calculate_sha256(&path)?,
+                github_sha: checksum::calculate_git_blob_sha(&path)?,
+                size,
+                required: CORE_FILES.contains(&name.as_str()),
+                description: String::new(),
+                parts: Vec::new(),
+                name,
+            });
+        }
+
+        Ok(Self {
+            data_version: "pob-unknown".to_string(),
+            poe_league: league.to_string(),
+            last_updated: chrono::Utc::now().to_rfc3339(),
+            source,
+            files,
+            ignored_versions: Vec::new(),
+            parsed_artifact: None,
+        })
+    }
+
+    /// Fill in `github_sha` and `url` from a GitHub directory listing
+    ///
+    /// Files missing from the listing are left unchanged.
+    pub fn apply_github_listing(&mut self, listing: &[GitHubFile]) {
+        for file in &mut self.files {
+            if let Some(entry) = listing.iter().find(|e| e.is_file() && e.name == file.name) {
+                let remote = entry.to_data_file(&self.source);
+                file.github_sha = remote.github_sha;
+                file.url = remote.url;
+            }
+        }
+    }
+
     /// Load manifest from JSON file
     pub fn load_from_file(path: &Path) -> Result<Self, std::io::Error> {
         let content = std::fs::read_to_string(path)?;
@@ -37,6 +223,125 @@ impl DataManifest {
         std::fs::write(path, content)
     }
 
+    /// Save to `path` without ever leaving a partly written manifest
+    ///
+    /// The manifest is written to a temporary file next to `path` and renamed
+    /// over it, so readers see either the old or the new manifest.
+    pub fn save_atomic(&self, path: &Path) -> Result<(), std::io::Error> {
+        let content = serde_json::to_string_pretty(self)?;
+        let temp_path = sibling_path(
+            path,
+            &format!(
+                "{}.{}.tmp",
+                std::process::id(),
+                TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ),
+        );
+
+        let written = File::create(&temp_path).and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        });
+        let result = written.and_then(|()| std::fs::rename(&temp_path, path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    /// Lock the manifest at `path` until the returned guard is dropped
+    ///
+    /// Blocks while another process or thread holds the lock. Hold it from
+    /// loading the manifest to saving it, so concurrent changes are applied
+    /// one after another instead of overwriting each other. The lock is
+    /// advisory and kept on a `.lock` file next to the manifest, since
+    /// `save_atomic` replaces the manifest file itself.
+    pub fn lock(path: &Path) -> Result<ManifestLock, std::io::Error> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(sibling_path(path, "lock"))?;
+        file.lock()?;
+        Ok(ManifestLock { _file: file })
+    }
+
+    /// Record a newly installed version
+    ///
+    /// Clears ignored versions: each was the latest upstream version when it
+    /// was ignored, so none is newer than a version installed afterwards.
+    pub fn set_installed_version(&mut self, version: String) {
+        self.data_version = version;
+        self.last_updated = chrono::Utc::now().to_rfc3339();
+        self.ignored_versions.clear();
+    }
+
+    /// Set `poe_league` to the current challenge league from `leagues`
+    ///
+    /// Returns whether the league changed. Keeps the old name when no
+    /// challenge league is running (e.g., between leagues).
+    pub fn refresh_league(&mut self, leagues: &[League]) -> bool {
+        match current_challenge_league(leagues, chrono::Utc::now()) {
+            Some(league) if league.id != self.poe_league => {
+                self.poe_league = league.id.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Record the parsed LUT written to `artifact_path` for the current version
+    ///
+    /// The path is stored relative to `data_dir` when it lies inside it.
+    pub fn record_parsed_artifact(
+        &mut self,
+        data_dir: &Path,
+        artifact_path: &Path,
+        format: &str,
+    ) -> Result<(), DownloadError> {
+        let path = artifact_path.strip_prefix(data_dir).unwrap_or(artifact_path);
+
+        self.parsed_artifact = Some(ParsedArtifact {
+            path: path.to_string_lossy().into_owned(),
+            format: format.to_string(),
+            sha256: checksum::calculate_sha256(artifact_path)?,
+            generated_from_version: self.data_version.clone(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+        });
+
+        Ok(())
+    }
+
+    /// Check whether the data in `data_dir` has to be parsed again
+    ///
+    /// True when no artifact is recorded, it was generated from another
+    /// `data_version`, or the file is missing or fails its checksum.
+    pub fn needs_reparse(&self, data_dir: &Path) -> Result<bool, DownloadError> {
+        let Some(artifact) = &self.parsed_artifact else {
+            return Ok(true);
+        };
+
+        if artifact.generated_from_version != self.data_version {
+            return Ok(true);
+        }
+
+        let path = artifact.resolve(data_dir);
+        if !path.is_file() {
+            return Ok(true);
+        }
+
+        Ok(!checksum::validate_checksum(&path, &artifact.sha256)?)
+    }
+
+    /// Whether the user chose to skip `version`
+    pub fn is_ignored(&self, version: &str) -> bool {
+        self.ignored_versions.iter().any(|v| v == version)
+    }
+
     /// Get all required files
     pub fn required_files(&self) -> Vec<&DataFile> {
         self.files.iter().filter(|f| f.required).collect()
@@ -46,6 +351,86 @@ impl DataManifest {
     pub fn find_file(&self, name: &str) -> Option<&DataFile> {
         self.files.iter().find(|f| f.name == name)
     }
+
+    /// Compare this manifest with `other` (usually a newer one)
+    ///
+    /// A file counts as changed when its sha256, github_sha or size differ.
+    pub fn diff(&self, other: &DataManifest) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+
+        for new in &other.files {
+            match self.find_file(&new.name) {
+                None => diff.added.push(new.clone()),
+                Some(old)
+                    if old.sha256 != new.sha256
+                        || old.github_sha != new.github_sha
+                        || old.size != new.size =>
+                {
+                    diff.changed.push(new.clone())
+                }
+                Some(_) => {}
+            }
+        }
+
+        diff.removed = self
+            .files
+            .iter()
+            .filter(|old| other.find_file(&old.name).is_none())
+            .cloned()
+            .collect();
+
+        diff
+    }
+}
+
+/// Files that differ between two manifests
+#[derive(Debug, Clone, Default)]
+pub struct ManifestDiff {
+    /// Files only in the newer manifest
+    pub added: Vec<DataFile>,
+
+    /// Files only in the older manifest
+    pub removed: Vec<DataFile>,
+
+    /// Files in both whose content changed (entries from the newer manifest)
+    pub changed: Vec<DataFile>,
+}
+
+impl ManifestDiff {
+    /// Check whether the manifests describe the same files
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Number of files that need downloading (added + changed)
+    pub fn download_count(&self) -> usize {
+        self.added.len() + self.changed.len()
+    }
+
+    /// Total size in bytes of the files that need downloading
+    ///
+    /// Files of unknown size count as 0; see `DataDownloader::resolve_sizes`.
+    pub fn download_size(&self) -> u64 {
+        self.added.iter().chain(&self.changed).map(|f| f.size).sum()
+    }
+
+    /// Number of files to download whose size is unknown
+    pub fn unknown_sizes(&self) -> usize {
+        self.added.iter().chain(&self.changed).filter(|f| f.size == 0).count()
+    }
+}
+
+impl std::fmt::Display for ManifestDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} changed, {} removed ({} bytes to download)",
+            self.added.len(),
+            self.changed.len(),
+            self.removed.len(),
+            self.download_size()
+        )
+    }
 }
 
 /// Data source configuration
@@ -66,24 +451,58 @@ pub struct DataSource {
 
     /// Full URL to repository
     pub url: String,
+
+    /// Fallback sources tried in order when a file's own URL fails
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<DownloadSource>,
+
+    /// Host serving raw files instead of raw.githubusercontent.com
+    /// (e.g., a mirror or a test server)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_url: Option<String>,
+
+    /// API endpoint instead of api.github.com
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
 }
 
 impl DataSource {
+    /// Raw URL of a file in the data directory
+    pub fn raw_file_url(&self, filename: &str) -> String {
+        self.raw_repo_url(&format!("{}/{}", self.path.trim_matches('/'), filename))
+    }
+
+    /// Raw URL of `path` within the repository, on the source's branch
+    pub fn raw_repo_url(&self, path: &str) -> String {
+        let raw_url = self.raw_url.as_deref().unwrap_or(GITHUB_RAW_URL);
+        format!("{}/{}/{}/{}", raw_url.trim_end_matches('/'), self.repo, self.branch, path)
+    }
+
     /// Get API URL for checking commits
     pub fn commits_api_url(&self) -> String {
         format!(
-            "https://api.github.com/repos/{}/commits?path={}&per_page=1",
-            self.repo, self.path
+            "{}/repos/{}/commits?path={}&per_page=1",
+            self.api_base_url(),
+            self.repo,
+            self.path
         )
     }
 
     /// Get API URL for a specific file
     pub fn file_api_url(&self, filename: &str) -> String {
         format!(
-            "https://api.github.com/repos/{}/contents/{}/{}?ref={}",
-            self.repo, self.path, filename, self.branch
+            "{}/repos/{}/contents/{}/{}?ref={}",
+            self.api_base_url(),
+            self.repo,
+            self.path,
+            filename,
+            self.branch
         )
     }
+
+    fn api_base_url(&self) -> &str {
+        self.api_url.as_deref().unwrap_or(GITHUB_API_URL).trim_end_matches('/')
+    }
 }
 
 /// Individual data file metadata
@@ -109,6 +528,29 @@ pub struct DataFile {
 
     /// Human-readable description
     pub description: String,
+
+    /// Parts that are concatenated in order to produce this file
+    ///
+    /// Used for files too large to host as a single download (e.g.,
+    /// GloriousVanity.zip). When set, `url` is unused and `sha256`/`size`
+    /// describe the joined file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<FilePart>,
+}
+
+/// One part of a file split across several downloads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilePart {
+    /// Download URL
+    pub url: String,
+
+    /// SHA256 checksum of this part (empty if unknown)
+    #[serde(default)]
+    pub sha256: String,
+
+    /// Part size in bytes
+    #[serde(default)]
+    pub size: u64,
 }
 
 impl DataFile {
@@ -121,11 +563,289 @@ impl DataFile {
     pub fn has_github_sha(&self) -> bool {
         !self.github_sha.is_empty()
     }
+
+    /// Check if file is downloaded in parts
+    pub fn is_split(&self) -> bool {
+        !self.parts.is_empty()
+    }
+
+    /// Check whether the copy of this file in `data_dir` matches the manifest
+    ///
+    /// The file must exist, and its size and SHA256 must match whenever the
+    /// manifest records them.
+    pub fn is_up_to_date(&self, data_dir: &Path) -> Result<bool, DownloadError> {
+        let path = data_dir.join(&self.name);
+
+        if !self.size_matches(&path) {
+            return Ok(false);
+        }
+
+        if self.has_checksum() {
+            return checksum::validate_checksum(&path, &self.sha256);
+        }
+
+        Ok(true)
+    }
+
+    /// Async version of `is_up_to_date`, hashing off the runtime thread
+    pub async fn is_up_to_date_async(&self, data_dir: &Path) -> Result<bool, DownloadError> {
+        let path = data_dir.join(&self.name);
+
+        if !self.size_matches(&path) {
+            return Ok(false);
+        }
+
+        if self.has_checksum() {
+            return checksum::validate_checksum_async(&path, &self.sha256).await;
+        }
+
+        Ok(true)
+    }
+
+    /// The file exists and has the recorded size (if any)
+    fn size_matches(&self, path: &Path) -> bool {
+        match std::fs::metadata(path) {
+            Ok(metadata) => self.size == 0 || metadata.len() == self.size,
+            Err(_) => false,
+        }
+    }
+
+    /// File name used for a downloaded part (e.g., "GloriousVanity.zip.part0")
+    pub fn part_name(&self, index: usize) -> String {
+        format!("{}.part{}", self.name, index)
+    }
+}
+
+/// Whether a file in a data directory is PoB data (not a part, cache or temp file)
+pub(crate) fn is_data_file_name(name: &str) -> bool {
+    !name.starts_with('.') && (name.ends_with(".lua") || name.ends_with(".zip"))
+}
+
+/// Exclusive lock on a manifest, released when dropped
+#[derive(Debug)]
+pub struct ManifestLock {
+    // Closing the file releases the lock
+    _file: File,
+}
+
+/// `path` with `.suffix` added to its file name, hidden on Unix
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "manifest.json".to_string());
+
+    path.with_file_name(format!(".{}.{}", name, suffix))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
+
+    fn pob_source() -> DataSource {
+        DataSource {
+            source_type: "github".to_string(),
+            repo: "PathOfBuildingCommunity/PathOfBuilding".to_string(),
+            branch: "master".to_string(),
+            path: "src/Data/TimelessJewelData".to_string(),
+            url: "https://github.com/PathOfBuildingCommunity/PathOfBuilding".to_string(),
+            mirrors: Vec::new(),
+            raw_url: None,
+            api_url: None,
+        }
+    }
+
+    #[test]
+    fn test_default_pob_manifest() {
+        let manifest = DataManifest::default_pob();
+
+        assert_eq!(manifest.data_version, "pob-unknown");
+        assert!(manifest.validate().is_ok());
+
+        for name in CORE_FILES {
+            assert!(manifest.find_file(name).unwrap().required, "{} not required", name);
+        }
+        assert!(manifest.find_file("GloriousVanity.zip").unwrap().is_split());
+    }
+
+    #[test]
+    fn test_validate_reports_problems() {
+        let mut manifest = DataManifest::default_pob();
+        let mut duplicate = manifest.files[0].clone();
+        duplicate.url = String::new();
+        duplicate.sha256 = "not-a-hash".to_string();
+        manifest.files.push(duplicate);
+
+        let error = manifest.validate().unwrap_err().to_string();
+
+        assert!(error.contains("listed twice"));
+        assert!(error.contains("has no URL"));
+        assert!(error.contains("malformed sha256"));
+    }
+
+    #[test]
+    fn test_diff_categorizes_files() {
+        let old = DataManifest::default_pob();
+        let mut new = old.clone();
+
+        let modified = new.files.iter_mut().find(|f| f.name == "LethalPride.zip").unwrap();
+        modified.github_sha = "new-blob".to_string();
+        modified.size = 3_000_000;
+
+        let mut added = new.files[0].clone();
+        added.name = "Heroic Tragedy.zip".to_string();
+        added.size = 1_500_000;
+        new.files.push(added);
+
+        let diff = old.diff(&new);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "Heroic Tragedy.zip");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, "LethalPride.zip");
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.download_count(), 2);
+        assert_eq!(diff.download_size(), 4_500_000);
+
+        // Reversed, the added file is removed and nothing needs downloading
+        // beyond the changed one
+        let reverse = new.diff(&old);
+        assert_eq!(reverse.removed.len(), 1);
+        assert_eq!(reverse.download_count(), 1);
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    fn manifest_with_artifact(temp_dir: &TempDir) -> DataManifest {
+        std::fs::write(temp_dir.path().join("lut_data.json"), b"{\"modifiers\":[]}").unwrap();
+
+        let mut manifest = DataManifest::default_pob();
+        manifest.data_version = "abc123".to_string();
+        manifest
+            .record_parsed_artifact(temp_dir.path(), &temp_dir.path().join("lut_data.json"), "json")
+            .unwrap();
+        manifest
+    }
+
+    #[test]
+    fn test_needs_reparse_matching_artifact() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = manifest_with_artifact(&temp_dir);
+
+        let artifact = manifest.parsed_artifact.as_ref().unwrap();
+        assert_eq!(artifact.path, "lut_data.json");
+        assert_eq!(artifact.generated_from_version, "abc123");
+        assert!(!manifest.needs_reparse(temp_dir.path()).unwrap());
+
+        // Survives a save/load round trip
+        let manifest_path = temp_dir.path().join("manifest.json");
+        manifest.save_to_file(&manifest_path).unwrap();
+        let loaded = DataManifest::load_from_file(&manifest_path).unwrap();
+        assert_eq!(loaded.parsed_artifact, manifest.parsed_artifact);
+        assert!(!loaded.needs_reparse(temp_dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_needs_reparse_stale_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manifest = manifest_with_artifact(&temp_dir);
+
+        manifest.set_installed_version("def456".to_string());
+
+        assert!(manifest.needs_reparse(temp_dir.path()).unwrap());
+        assert!(DataManifest::default_pob().needs_reparse(temp_dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_needs_reparse_corrupted_artifact() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = manifest_with_artifact(&temp_dir);
+
+        std::fs::write(temp_dir.path().join("lut_data.json"), b"{\"modifiers\":[").unwrap();
+        assert!(manifest.needs_reparse(temp_dir.path()).unwrap());
+
+        std::fs::remove_file(temp_dir.path().join("lut_data.json")).unwrap();
+        assert!(manifest.needs_reparse(temp_dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_generate_from_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+
+        std::fs::write(dir.join("LethalPride.zip"), b"lethal pride data").unwrap();
+        std::fs::write(dir.join("LegionTradeIds.lua"), b"trade ids").unwrap();
+        std::fs::write(dir.join("LegionPassives.lua"), b"passives").unwrap();
+
+        // Not data files
+        std::fs::write(dir.join("manifest.json"), b"{}").unwrap();
+        std::fs::write(dir.join(".http-cache.json"), b"{}").unwrap();
+        std::fs::write(dir.join("GloriousVanity.zip.part0"), b"part").unwrap();
+        std::fs::write(dir.join("LethalPride.zip.download"), b"partial").unwrap();
+
+        let manifest = DataManifest::generate(dir, pob_source(), "Settlers").unwrap();
+
+        let names: Vec<_> = manifest.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["LegionPassives.lua", "LegionTradeIds.lua", "LethalPride.zip"]
+        );
+
+        let passives = &manifest.files[0];
+        assert_eq!(passives.size, 8);
+        assert_eq!(
+            passives.sha256,
+            checksum::calculate_sha256_bytes(b"passives")
+        );
+        assert_eq!(
+            passives.github_sha,
+            checksum::calculate_git_blob_sha_bytes(b"passives")
+        );
+        assert!(passives.required);
+
+        assert!(!manifest.files[1].required);
+        assert!(manifest.files[2].required);
+        assert_eq!(manifest.files[2].size, 17);
+        assert_eq!(
+            manifest.files[2].url,
+            "https://raw.githubusercontent.com/PathOfBuildingCommunity/PathOfBuilding/master/src/Data/TimelessJewelData/LethalPride.zip"
+        );
+
+        assert_eq!(manifest.poe_league, "Settlers");
+        assert_eq!(manifest.data_version, "pob-unknown");
+        assert!(manifest.files.iter().all(|f| f.is_up_to_date(dir).unwrap()));
+    }
+
+    #[test]
+    fn test_apply_github_listing() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("LethalPride.zip"), b"lethal pride data").unwrap();
+        std::fs::write(temp_dir.path().join("LegionPassives.lua"), b"passives").unwrap();
+
+        let mut manifest =
+            DataManifest::generate(temp_dir.path(), pob_source(), "Standard").unwrap();
+
+        manifest.apply_github_listing(&[GitHubFile {
+            name: "LethalPride.zip".to_string(),
+            path: "src/Data/TimelessJewelData/LethalPride.zip".to_string(),
+            sha: "blob-lp".to_string(),
+            size: 17,
+            url: "https://api.github.com/unused".to_string(),
+            download_url: Some("https://example.com/LethalPride.zip".to_string()),
+            entry_type: "file".to_string(),
+        }]);
+
+        let lethal_pride = manifest.find_file("LethalPride.zip").unwrap();
+        assert_eq!(lethal_pride.github_sha, "blob-lp");
+        assert_eq!(lethal_pride.url, "https://example.com/LethalPride.zip");
+
+        // Not in the listing: keeps the locally computed blob SHA
+        assert_eq!(
+            manifest.find_file("LegionPassives.lua").unwrap().github_sha,
+            checksum::calculate_git_blob_sha_bytes(b"passives")
+        );
+    }
 
     #[test]
     fn test_data_source_api_urls() {
@@ -135,6 +855,9 @@ mod tests {
             branch: "master".to_string(),
             path: "src/Data/TimelessJewelData".to_string(),
             url: "https://github.com/PathOfBuildingCommunity/PathOfBuilding".to_string(),
+            mirrors: Vec::new(),
+            raw_url: None,
+            api_url: None,
         };
 
         let commits_url = source.commits_api_url();
@@ -146,6 +869,18 @@ mod tests {
         assert!(file_url.contains("api.github.com"));
         assert!(file_url.contains("contents"));
         assert!(file_url.contains("test.zip"));
+
+        let local = DataSource {
+            raw_url: Some("http://127.0.0.1:8080/".to_string()),
+            api_url: Some("http://127.0.0.1:9090".to_string()),
+            ..source
+        };
+        assert_eq!(
+            local.raw_file_url("test.zip"),
+            format!("http://127.0.0.1:8080/{}/master/{}/test.zip", local.repo, local.path)
+        );
+        assert!(local.commits_api_url().starts_with("http://127.0.0.1:9090/repos/"));
+        assert!(local.file_api_url("test.zip").starts_with("http://127.0.0.1:9090/repos/"));
     }
 
     #[test]
@@ -160,6 +895,9 @@ mod tests {
                 branch: "master".to_string(),
                 path: "data".to_string(),
                 url: "https://github.com/test/test".to_string(),
+                mirrors: Vec::new(),
+                raw_url: None,
+                api_url: None,
             },
             files: vec![
                 DataFile {
@@ -170,6 +908,7 @@ mod tests {
                     size: 0,
                     required: true,
                     description: "Required file".to_string(),
+                    parts: Vec::new(),
                 },
                 DataFile {
                     name: "optional.zip".to_string(),
@@ -179,8 +918,11 @@ mod tests {
                     size: 0,
                     required: false,
                     description: "Optional file".to_string(),
+                    parts: Vec::new(),
                 },
             ],
+            ignored_versions: Vec::new(),
+            parsed_artifact: None,
         };
 
         let required = manifest.required_files();
@@ -198,6 +940,7 @@ mod tests {
             size: 1000,
             required: true,
             description: "Test".to_string(),
+            parts: Vec::new(),
         };
 
         assert!(file_with_checksum.has_checksum());
@@ -211,9 +954,52 @@ mod tests {
             size: 0,
             required: true,
             description: "Test".to_string(),
+            parts: Vec::new(),
         };
 
         assert!(!file_without.has_checksum());
         assert!(!file_without.has_github_sha());
     }
+
+    #[test]
+    fn test_refresh_league() {
+        let leagues: Vec<League> = serde_json::from_str(
+            r#"[
+                {"id": "Standard", "startAt": "2013-01-23T21:00:00Z", "endAt": null},
+                {"id": "Hardcore Keepers", "startAt": "2025-10-31T20:00:00Z", "endAt": null,
+                 "rules": [{"id": "Hardcore", "name": "Hardcore"}]},
+                {"id": "Keepers", "startAt": "2025-10-31T20:00:00Z", "endAt": null}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut manifest = DataManifest::default_pob();
+        manifest.poe_league = "Settlers".to_string();
+
+        assert!(manifest.refresh_league(&leagues));
+        assert_eq!(manifest.poe_league, "Keepers");
+        assert!(!manifest.refresh_league(&leagues));
+
+        // No challenge league running: keep the known name
+        assert!(!manifest.refresh_league(&leagues[..1]));
+        assert_eq!(manifest.poe_league, "Keepers");
+    }
+
+    #[test]
+    fn test_save_atomic_leaves_no_temp_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("manifest.json");
+
+        let mut manifest = DataManifest::default_pob();
+        manifest.save_atomic(&path).unwrap();
+        manifest.data_version = "abc".to_string();
+        manifest.save_atomic(&path).unwrap();
+
+        assert_eq!(DataManifest::load_from_file(&path).unwrap().data_version, "abc");
+        let names: Vec<_> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(names, vec!["manifest.json"]);
+    }
 }
@@ -0,0 +1,124 @@
+//! Signed header of binary LUT artifacts
+//!
+//! Parsed LUTs get shared (e.g., on a guild's Google Drive) so players can
+//! skip the parse. The header records which version wrote the payload and
+//! the payload's SHA256, so a corrupted or edited file is rejected instead
+//! of loaded.
+//!
+//! ```text
+//! magic (8 bytes) | payload SHA256 (32 bytes) | version length (1 byte)
+//! | generator version (UTF-8) | bincode LutData
+//! ```
+
+use std::io::Write;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use super::lut::LutData;
+use crate::error::{file_name, DownloadError, FileContext, FileOperation};
+
+/// First bytes of a binary artifact (format 1)
+const MAGIC: &[u8; 8] = b"POELUT\0\x01";
+
+/// Length of the payload digest
+const DIGEST_LEN: usize = 32;
+
+/// What the header of a binary artifact records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactHeader {
+    /// Version of the crate that wrote the artifact
+    pub generator_version: String,
+
+    /// SHA256 of the payload, in hex
+    pub payload_sha256: String,
+}
+
+/// Serialize `lut_data` with a header to `output_path`
+pub(crate) fn write(lut_data: &LutData, output_path: &Path) -> Result<(), DownloadError> {
+    let payload = bincode::serialize(lut_data).map_err(|e| {
+        DownloadError::file_failed(FileOperation::Write, file_name(output_path), e)
+    })?;
+    let version = env!("CARGO_PKG_VERSION").as_bytes();
+
+    let file = std::fs::File::create(output_path)
+        .file_context(FileOperation::Write, output_path)?;
+    let mut out = std::io::BufWriter::new(file);
+    out.write_all(MAGIC)
+        .and_then(|()| out.write_all(&Sha256::digest(&payload)))
+        .and_then(|()| out.write_all(&[version.len() as u8]))
+        .and_then(|()| out.write_all(version))
+        .and_then(|()| out.write_all(&payload))
+        .and_then(|()| out.flush())
+        .file_context(FileOperation::Write, output_path)
+}
+
+/// Read the artifact at `input_path`, verifying its header
+pub(crate) fn read(input_path: &Path) -> Result<(ArtifactHeader, LutData), DownloadError> {
+    let bytes = std::fs::read(input_path).file_context(FileOperation::Read, input_path)?;
+    let (header, payload) = verify_bytes(input_path, &bytes)?;
+
+    let data = bincode::deserialize(payload)
+        .map_err(|e| DownloadError::file_failed(FileOperation::Parse, file_name(input_path), e))?;
+    Ok((header, data))
+}
+
+/// Check the header and payload digest of the artifact at `input_path`
+/// without deserializing it
+pub(crate) fn verify(input_path: &Path) -> Result<ArtifactHeader, DownloadError> {
+    let bytes = std::fs::read(input_path).file_context(FileOperation::Read, input_path)?;
+    verify_bytes(input_path, &bytes).map(|(header, _)| header)
+}
+
+/// The header and payload of `bytes`, read from `path`
+///
+/// Fails if the header is missing, was written by another version (the
+/// payload format is private to a version) or doesn't match the payload.
+fn verify_bytes<'a>(
+    path: &Path,
+    bytes: &'a [u8],
+) -> Result<(ArtifactHeader, &'a [u8]), DownloadError> {
+    let invalid = |reason: &str| {
+        DownloadError::file_failed(FileOperation::Parse, file_name(path), reason)
+    };
+
+    let rest = bytes
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| invalid("not a signed LUT artifact"))?;
+    if rest.len() <= DIGEST_LEN {
+        return Err(invalid("header is truncated"));
+    }
+    let (digest, rest) = rest.split_at(DIGEST_LEN);
+    let (&version_len, rest) = rest.split_first().ok_or_else(|| invalid("header is truncated"))?;
+    if rest.len() < version_len as usize {
+        return Err(invalid("header is truncated"));
+    }
+    let (version, payload) = rest.split_at(version_len as usize);
+
+    let header = ArtifactHeader {
+        generator_version: String::from_utf8_lossy(version).into_owned(),
+        payload_sha256: hex(digest),
+    };
+    if header.generator_version != env!("CARGO_PKG_VERSION") {
+        return Err(invalid(&format!(
+            "written by version {}, but this is {}; parse the data again",
+            header.generator_version,
+            env!("CARGO_PKG_VERSION")
+        )));
+    }
+
+    let actual = hex(&Sha256::digest(payload));
+    if actual != header.payload_sha256 {
+        return Err(DownloadError::ChecksumMismatch {
+            file: file_name(path),
+            expected: header.payload_sha256,
+            actual,
+        });
+    }
+
+    Ok((header, payload))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
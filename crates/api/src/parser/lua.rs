@@ -1,6 +1,6 @@
 //! Lua file parser for PoB data files
 
-use crate::error::DownloadError;
+use crate::error::{file_name, DownloadError, FileContext, FileOperation};
 use mlua::{Lua, Table, Value};
 use std::collections::HashMap;
 use std::path::Path;
@@ -27,6 +27,8 @@ pub struct LegionPassives {
 
 #[derive(Debug, Clone)]
 pub struct PassiveAddition {
+    /// Index in the additions table, which binary LUTs refer to
+    pub index: u32,
     pub display_name: String,
     pub stat_descriptions: Vec<String>,
 }
@@ -34,34 +36,53 @@ pub struct PassiveAddition {
 /// Lua file parser
 pub struct LuaParser;
 
+/// Error parsing the Lua file at `path`
+fn parse_error(path: &Path, reason: String) -> DownloadError {
+    DownloadError::file_failed(FileOperation::Parse, file_name(path), reason)
+}
+
 impl LuaParser {
+    /// Check that a Lua file compiles, without executing it
+    pub fn check_syntax(path: &Path) -> Result<(), DownloadError> {
+        let lua_code = std::fs::read_to_string(path)
+            .file_context(FileOperation::Read, path)?;
+
+        let lua = Lua::new();
+
+        lua.load(&lua_code)
+            .into_function()
+            .map_err(|e| parse_error(path, format!("Lua error: {}", e)))?;
+
+        Ok(())
+    }
+
     /// Parse NodeIndexMapping.lua
     pub fn parse_node_index_mapping(path: &Path) -> Result<NodeIndexMapping, DownloadError> {
         let lua_code = std::fs::read_to_string(path)
-            .map_err(|e| DownloadError::IoError(e))?;
+            .file_context(FileOperation::Read, path)?;
 
         let lua = Lua::new();
 
         // Execute Lua code
         lua.load(&lua_code)
             .exec()
-            .map_err(|e| DownloadError::InvalidManifest(format!("Lua error: {}", e)))?;
+            .map_err(|e| parse_error(path, format!("Lua error: {}", e)))?;
 
         // Get the nodeIDList table
         let globals = lua.globals();
         let node_list: Table = globals
             .get("nodeIDList")
-            .map_err(|e| DownloadError::InvalidManifest(format!("Missing nodeIDList: {}", e)))?;
+            .map_err(|e| parse_error(path, format!("Missing nodeIDList: {}", e)))?;
 
         // Extract size values
         let size: usize = node_list
             .get("size")
-            .map_err(|e| DownloadError::InvalidManifest(format!("Missing size: {}", e)))?;
+            .map_err(|e| parse_error(path, format!("Missing size: {}", e)))?;
 
         let size_notable: usize = node_list
             .get("sizeNotable")
             .map_err(|e| {
-                DownloadError::InvalidManifest(format!("Missing sizeNotable: {}", e))
+                parse_error(path, format!("Missing sizeNotable: {}", e))
             })?;
 
         // Extract node mappings
@@ -69,18 +90,18 @@ impl LuaParser {
 
         for pair in node_list.pairs::<Value, Value>() {
             let (key, value) = pair.map_err(|e| {
-                DownloadError::InvalidManifest(format!("Error iterating table: {}", e))
+                parse_error(path, format!("Error iterating table: {}", e))
             })?;
 
             // Skip string keys (size, sizeNotable)
             if let Value::Integer(node_id) = key {
                 if let Value::Table(info_table) = value {
                     let index: usize = info_table.get("index").map_err(|e| {
-                        DownloadError::InvalidManifest(format!("Missing index: {}", e))
+                        parse_error(path, format!("Missing index: {}", e))
                     })?;
 
                     let size_val: u32 = info_table.get("size").map_err(|e| {
-                        DownloadError::InvalidManifest(format!("Missing size: {}", e))
+                        parse_error(path, format!("Missing size: {}", e))
                     })?;
 
                     nodes.insert(
@@ -101,10 +122,24 @@ impl LuaParser {
         })
     }
 
+    /// Latest passive tree version in PoB's GameVersions.lua (e.g., "3_25")
+    ///
+    /// Reads the `latestTreeVersion = "..."` assignment as text rather than
+    /// running the file.
+    pub fn parse_tree_version(lua_code: &str) -> Option<String> {
+        lua_code.lines().find_map(|line| {
+            let value = line.trim().strip_prefix("latestTreeVersion")?;
+            let value = value.trim_start().strip_prefix('=')?.trim();
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let version = value[1..].split(quote).next()?;
+            (!version.is_empty()).then(|| version.to_string())
+        })
+    }
+
     /// Parse LegionPassives.lua
     pub fn parse_legion_passives(path: &Path) -> Result<LegionPassives, DownloadError> {
         let lua_code = std::fs::read_to_string(path)
-            .map_err(|e| DownloadError::IoError(e))?;
+            .file_context(FileOperation::Read, path)?;
 
         let lua = Lua::new();
 
@@ -112,28 +147,33 @@ impl LuaParser {
         let data: Table = lua
             .load(&lua_code)
             .eval()
-            .map_err(|e| DownloadError::InvalidManifest(format!("Lua error: {}", e)))?;
+            .map_err(|e| parse_error(path, format!("Lua error: {}", e)))?;
 
         // Get additions table
         let additions_table: Table = data.get("additions").map_err(|e| {
-            DownloadError::InvalidManifest(format!("Missing additions: {}", e))
+            parse_error(path, format!("Missing additions: {}", e))
         })?;
 
         // Parse additions
         let mut additions = HashMap::new();
 
         for pair in additions_table.pairs::<Value, Table>() {
-            let (_index, addition_table) = pair.map_err(|e| {
-                DownloadError::InvalidManifest(format!("Error iterating additions: {}", e))
+            let (key, addition_table) = pair.map_err(|e| {
+                parse_error(path, format!("Error iterating additions: {}", e))
             })?;
+            let index = match key {
+                Value::Integer(index) => u32::try_from(index).ok(),
+                _ => None,
+            }
+            .ok_or_else(|| parse_error(path, format!("Addition key {:?} is not an index", key)))?;
 
             // Get required fields
             let id: String = addition_table.get("id").map_err(|e| {
-                DownloadError::InvalidManifest(format!("Missing id: {}", e))
+                parse_error(path, format!("Missing id: {}", e))
             })?;
 
             let display_name: String = addition_table.get("dn").map_err(|e| {
-                DownloadError::InvalidManifest(format!("Missing dn: {}", e))
+                parse_error(path, format!("Missing dn: {}", e))
             })?;
 
             // Get stat descriptions array
@@ -143,15 +183,14 @@ impl LuaParser {
             });
 
             let mut stat_descriptions = Vec::new();
-            for pair in sd_table.pairs::<usize, String>() {
-                if let Ok((_idx, desc)) = pair {
-                    stat_descriptions.push(desc);
-                }
+            for (_idx, desc) in sd_table.pairs::<usize, String>().flatten() {
+                stat_descriptions.push(desc);
             }
 
             additions.insert(
                 id,
                 PassiveAddition {
+                    index,
                     display_name,
                     stat_descriptions,
                 },
@@ -2,8 +2,13 @@
 //!
 //! Our optimized JSON format for timeless jewel data
 
-use serde::{Deserialize, Serialize};
+use poe_item_analyzer_core::data::TimelessLookup;
+use poe_item_analyzer_core::items::JewelType;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use smallvec::SmallVec;
 use std::collections::HashMap;
+use std::fmt;
 
 use super::lua::{LegionPassives, NodeIndexMapping};
 use crate::error::DownloadError;
@@ -20,8 +25,21 @@ pub struct LutData {
     /// Available modifiers by ID
     pub modifiers: HashMap<String, NodeModifier>,
 
+    /// ID of the modifier at each index of LegionPassives.lua's additions,
+    /// which binary LUTs store instead of IDs
+    #[serde(default)]
+    pub modifier_indices: HashMap<u32, String>,
+
     /// Jewel-specific data
     pub jewels: HashMap<String, JewelLutData>,
+
+    /// Trade stat id of each mapped mod text (modifier names and stats)
+    #[serde(default)]
+    pub trade_stat_ids: HashMap<String, String>,
+
+    /// Passive tree version the node ids belong to (e.g., "3_25"), if known
+    #[serde(default)]
+    pub tree_version: Option<String>,
 }
 
 /// Node information from passive tree
@@ -54,6 +72,10 @@ pub struct NodeModifier {
 
     /// Searchable text (lowercase, for searching)
     pub search_text: String,
+
+    /// Trade API stat id (e.g., "explicit.stat_4080418644"), if known
+    #[serde(default)]
+    pub trade_stat_id: Option<String>,
 }
 
 /// LUT data for a specific jewel type
@@ -65,9 +87,263 @@ pub struct JewelLutData {
     /// Seed range (min, max)
     pub seed_range: (u32, u32),
 
-    /// Raw LUT data: seed -> node_index -> modifier_id
-    /// Format: HashMap<seed, HashMap<node_index, modifier_id>>
-    pub lookup_table: HashMap<u32, HashMap<usize, String>>,
+    /// Raw LUT data: seed -> node_index -> entry
+    /// Format: HashMap<seed, HashMap<node_index, LutEntry>>
+    pub lookup_table: HashMap<u32, HashMap<usize, LutEntry>>,
+
+    /// Tables of conqueror variants (see `JewelType::variant`) with their
+    /// own effects, by variant; a seed listed here replaces `lookup_table`'s
+    #[serde(default)]
+    pub variants: HashMap<String, HashMap<u32, HashMap<usize, LutEntry>>>,
+
+    /// The data file was malformed and parsed leniently, so seeds past the
+    /// problem may be missing
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Modifier ID as listed in `LutData::modifiers`
+///
+/// Binary LUTs give the modifier's index in LegionPassives.lua instead;
+/// parsing replaces it with the ID (see `LutData::resolve_modifier_indices`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ModId(pub String);
+
+impl ModId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for ModId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl From<String> for ModId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for ModId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Glorious Vanity stat: an index into LegionPassives.lua's additions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StatId(pub u8);
+
+/// Glorious Vanity stats with their rolls
+///
+/// Nodes get at most four, which are stored inline.
+pub type GvStats = SmallVec<[(StatId, u8); 4]>;
+
+/// What a seed does to one node
+///
+/// In JSON a modifier reference is its ID and Glorious Vanity stats are
+/// `[stat, roll]` pairs. Older JSON stored both as strings, Glorious Vanity
+/// as "s<stat>|...|r<roll>|..."; those still load (see `From<&str>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LutEntry {
+    /// The node gets the modifier with this ID
+    ModifierRef(ModId),
+
+    /// Glorious Vanity: the node gets these stats with their rolls
+    ///
+    /// A stat with two rolls is listed twice.
+    GloriousVanity { stats: GvStats },
+}
+
+impl LutEntry {
+    /// Glorious Vanity entry from stat indices and rolls in the order the
+    /// LUT lists them
+    ///
+    /// Each stat is paired with the roll at the same position; extra rolls
+    /// belong to the last stat, and a stat without a roll gets 0.
+    pub fn glorious_vanity(stats: &[u8], rolls: &[u8]) -> Self {
+        let Some(last) = stats.len().checked_sub(1) else {
+            return LutEntry::GloriousVanity { stats: GvStats::new() };
+        };
+
+        let stats = (0..stats.len().max(rolls.len()))
+            .map(|index| {
+                let roll = rolls.get(index).copied().unwrap_or(0);
+                (StatId(stats[index.min(last)]), roll)
+            })
+            .collect();
+        LutEntry::GloriousVanity { stats }
+    }
+
+    /// Modifier ID, if this entry references one
+    pub fn modifier_id(&self) -> Option<&ModId> {
+        match self {
+            LutEntry::ModifierRef(id) => Some(id),
+            LutEntry::GloriousVanity { .. } => None,
+        }
+    }
+}
+
+impl From<&str> for LutEntry {
+    /// Read the string form of older JSON
+    fn from(value: &str) -> Self {
+        let mut stats = Vec::new();
+        let mut rolls = Vec::new();
+
+        for part in value.split('|') {
+            let number = part.get(1..).and_then(|number| number.parse::<u8>().ok());
+            match (part.get(..1), number) {
+                (Some("s"), Some(stat)) => stats.push(stat),
+                (Some("r"), Some(roll)) => rolls.push(roll),
+                _ => return LutEntry::ModifierRef(ModId::from(value)),
+            }
+        }
+
+        if stats.is_empty() {
+            return LutEntry::ModifierRef(ModId::from(value));
+        }
+        LutEntry::glorious_vanity(&stats, &rolls)
+    }
+}
+
+impl Serialize for LutEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return match self {
+                LutEntry::ModifierRef(id) => serializer.serialize_str(id.as_str()),
+                LutEntry::GloriousVanity { stats } => stats.serialize(serializer),
+            };
+        }
+
+        // Same layout as `BinaryLutEntry`
+        match self {
+            LutEntry::ModifierRef(id) => {
+                serializer.serialize_newtype_variant("LutEntry", 0, "ModifierRef", id)
+            }
+            LutEntry::GloriousVanity { stats } => {
+                serializer.serialize_newtype_variant("LutEntry", 1, "GloriousVanity", stats)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LutEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            return deserializer.deserialize_any(LutEntryVisitor);
+        }
+
+        Ok(match BinaryLutEntry::deserialize(deserializer)? {
+            BinaryLutEntry::ModifierRef(id) => LutEntry::ModifierRef(id),
+            BinaryLutEntry::GloriousVanity(stats) => LutEntry::GloriousVanity { stats },
+        })
+    }
+}
+
+/// `LutEntry` in formats that can't tell a string from a list (bincode)
+#[derive(Deserialize)]
+#[serde(rename = "LutEntry")]
+enum BinaryLutEntry {
+    ModifierRef(ModId),
+    GloriousVanity(GvStats),
+}
+
+struct LutEntryVisitor;
+
+impl<'de> Visitor<'de> for LutEntryVisitor {
+    type Value = LutEntry;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a modifier ID or a list of [stat, roll] pairs")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<LutEntry, E> {
+        Ok(LutEntry::from(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<LutEntry, A::Error> {
+        let mut stats = GvStats::new();
+        while let Some(stat) = seq.next_element::<(StatId, u8)>()? {
+            stats.push(stat);
+        }
+        Ok(LutEntry::GloriousVanity { stats })
+    }
+}
+
+/// Reverse index from modifier to the seeds it appears on
+///
+/// Built once from the lookup tables with `LutData::modifier_index`.
+#[derive(Debug, Clone, Default)]
+pub struct ModifierIndex {
+    /// Modifier ID -> jewel key (e.g., "LethalPride") -> sorted seeds
+    seeds: HashMap<String, HashMap<String, Vec<u32>>>,
+}
+
+impl ModifierIndex {
+    /// Seeds of `jewel_type` with `modifier_id` on at least one node
+    pub fn seeds(&self, modifier_id: &str, jewel_type: JewelType) -> &[u32] {
+        self.seeds
+            .get(modifier_id)
+            .and_then(|by_jewel| by_jewel.get(&jewel_key(jewel_type)))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Number of seeds of `jewel_type` with `modifier_id`
+    pub fn seed_count(&self, modifier_id: &str, jewel_type: JewelType) -> usize {
+        self.seeds(modifier_id, jewel_type).len()
+    }
+}
+
+/// How often a modifier rolls with one jewel type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModFrequency {
+    /// Modifier ID
+    pub modifier_id: String,
+
+    /// Modifier display name
+    pub display_name: String,
+
+    /// Seeds with the modifier on at least one node
+    pub seed_count: usize,
+
+    /// Nodes with the modifier, summed over all seeds
+    pub node_count: usize,
+
+    /// Share of the jewel type's seeds with the modifier (0-100)
+    pub percentage: f64,
+}
+
+/// Modifier frequencies of one jewel type
+///
+/// Built with `LutData::build_statistics`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JewelStatistics {
+    /// Jewel type the statistics are for
+    pub jewel_type: JewelType,
+
+    /// Seeds in the lookup table
+    pub seed_count: usize,
+
+    /// Every modifier the jewel type rolls, rarest first
+    pub frequencies: Vec<ModFrequency>,
+}
+
+impl JewelStatistics {
+    /// Frequency of `modifier_id`, if the jewel type rolls it
+    pub fn get(&self, modifier_id: &str) -> Option<&ModFrequency> {
+        self.frequencies.iter().find(|f| f.modifier_id == modifier_id)
+    }
+
+    /// Frequency of the modifier named `display_name`
+    pub fn by_name(&self, display_name: &str) -> Option<&ModFrequency> {
+        self.frequencies.iter().find(|f| f.display_name == display_name)
+    }
 }
 
 /// Passive skill node on the tree
@@ -100,7 +376,9 @@ impl LutData {
 
         // Convert modifiers from additions
         let mut modifiers = HashMap::new();
+        let mut modifier_indices = HashMap::new();
         for (id, addition) in legion_passives.additions {
+            modifier_indices.insert(addition.index, id.clone());
             let search_text = format!(
                 "{} {}",
                 addition.display_name,
@@ -115,6 +393,7 @@ impl LutData {
                     display_name: addition.display_name,
                     stat_descriptions: addition.stat_descriptions,
                     search_text,
+                    trade_stat_id: None,
                 },
             );
         }
@@ -123,10 +402,140 @@ impl LutData {
             version: "1.0.0".to_string(),
             node_indices,
             modifiers,
+            modifier_indices,
             jewels: HashMap::new(), // Will be populated from ZIP files
+            trade_stat_ids: HashMap::new(),
+            tree_version: None,
         })
     }
 
+    /// Replace the LegionPassives.lua indices in `jewel_data`'s modifier
+    /// references with the modifiers' IDs
+    ///
+    /// Returns how many references name no known modifier; they are kept
+    /// as they are.
+    pub fn resolve_modifier_indices(&self, jewel_data: &mut JewelLutData) -> usize {
+        let mut unresolved = 0;
+        let tables = std::iter::once(&mut jewel_data.lookup_table)
+            .chain(jewel_data.variants.values_mut());
+        let entries = tables
+            .flat_map(|table| table.values_mut())
+            .flat_map(|nodes| nodes.values_mut());
+
+        for entry in entries {
+            let LutEntry::ModifierRef(modifier_id) = entry else {
+                continue;
+            };
+            if self.modifiers.contains_key(modifier_id.as_str()) {
+                continue;
+            }
+            let id = modifier_id.as_str().parse::<u32>().ok();
+            match id.and_then(|index| self.modifier_indices.get(&index)) {
+                Some(id) => *modifier_id = ModId(id.clone()),
+                None => unresolved += 1,
+            }
+        }
+
+        unresolved
+    }
+
+    /// Modifiers whose name or stats contain every word of `query`
+    ///
+    /// Matching is case-insensitive. Results are sorted by display name and
+    /// capped at `limit`; an empty query matches every modifier.
+    pub fn search_modifiers(&self, query: &str, limit: usize) -> Vec<&NodeModifier> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+
+        let mut matches: Vec<&NodeModifier> = self
+            .modifiers
+            .values()
+            .filter(|modifier| words.iter().all(|word| modifier.search_text.contains(word)))
+            .collect();
+
+        matches.sort_by(|a, b| a.display_name.cmp(&b.display_name).then_with(|| a.id.cmp(&b.id)));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Build the reverse index from modifier to seeds
+    ///
+    /// Walks every lookup table, so call it once and keep the result.
+    /// Glorious Vanity stats aren't modifiers and are left out.
+    pub fn modifier_index(&self) -> ModifierIndex {
+        let mut seeds: HashMap<String, HashMap<String, Vec<u32>>> = HashMap::new();
+
+        for (jewel_type, jewel_data) in &self.jewels {
+            for (seed, nodes) in &jewel_data.lookup_table {
+                for modifier_id in nodes.values().filter_map(LutEntry::modifier_id) {
+                    let list = seeds
+                        .entry(modifier_id.to_string())
+                        .or_default()
+                        .entry(jewel_type.clone())
+                        .or_default();
+
+                    // A seed's nodes are visited together, so duplicates are adjacent
+                    if list.last() != Some(seed) {
+                        list.push(*seed);
+                    }
+                }
+            }
+        }
+
+        for list in seeds.values_mut().flat_map(HashMap::values_mut) {
+            list.sort_unstable();
+        }
+
+        ModifierIndex { seeds }
+    }
+
+    /// Count how often each modifier rolls with `jewel_type`
+    ///
+    /// Scans the lookup table once without collecting per-seed data, so it
+    /// stays cheap for the largest tables. Keep the result rather than
+    /// calling it repeatedly. Glorious Vanity stats are not counted.
+    pub fn build_statistics(&self, jewel_type: JewelType) -> JewelStatistics {
+        let Some(jewel_data) = self.jewels.get(&jewel_key(jewel_type)) else {
+            return JewelStatistics { jewel_type, seed_count: 0, frequencies: Vec::new() };
+        };
+
+        // Modifier ID -> (seeds, nodes, last seed counted)
+        let mut counts: HashMap<&str, (usize, usize, u32)> = HashMap::new();
+        for (&seed, nodes) in &jewel_data.lookup_table {
+            for modifier_id in nodes.values().filter_map(LutEntry::modifier_id) {
+                let (seeds, node_count, last_seed) =
+                    counts.entry(modifier_id.as_str()).or_insert((0, 0, seed));
+
+                // A seed's nodes are visited together
+                if *seeds == 0 || *last_seed != seed {
+                    *seeds += 1;
+                    *last_seed = seed;
+                }
+                *node_count += 1;
+            }
+        }
+
+        let seed_count = jewel_data.lookup_table.len();
+        let mut frequencies: Vec<ModFrequency> = counts
+            .into_iter()
+            .map(|(modifier_id, (seeds, node_count, _))| ModFrequency {
+                modifier_id: modifier_id.to_string(),
+                display_name: self
+                    .modifiers
+                    .get(modifier_id)
+                    .map(|m| m.display_name.clone())
+                    .unwrap_or_else(|| modifier_id.to_string()),
+                seed_count: seeds,
+                node_count,
+                percentage: seeds as f64 * 100.0 / seed_count as f64,
+            })
+            .collect();
+        frequencies.sort_by(|a, b| {
+            a.seed_count.cmp(&b.seed_count).then_with(|| a.modifier_id.cmp(&b.modifier_id))
+        });
+
+        JewelStatistics { jewel_type, seed_count, frequencies }
+    }
+
     /// Get modifier for a specific jewel, seed, and node
     pub fn get_modifier(
         &self,
@@ -134,17 +543,153 @@ impl LutData {
         seed: u32,
         node_id: u32,
     ) -> Option<&NodeModifier> {
-        // Get jewel data
-        let jewel_data = self.jewels.get(jewel_type)?;
+        self.entry_modifier(self.node_entry(jewel_type, seed, node_id, None)?)
+    }
+
+    /// Like `get_modifier`, for the jewel's conqueror `variant`
+    ///
+    /// Falls back to the shared table when the variant has no data for `seed`.
+    pub fn get_variant_modifier(
+        &self,
+        jewel_type: &str,
+        seed: u32,
+        node_id: u32,
+        variant: &str,
+    ) -> Option<&NodeModifier> {
+        self.entry_modifier(self.node_entry(jewel_type, seed, node_id, Some(variant))?)
+    }
 
-        // Get node index
+    /// What `seed` does to `node_id`, from the `variant` table if it has data
+    /// for the seed and the shared table otherwise
+    fn node_entry(
+        &self,
+        jewel_type: &str,
+        seed: u32,
+        node_id: u32,
+        variant: Option<&str>,
+    ) -> Option<&LutEntry> {
+        let jewel_data = self.jewels.get(jewel_type)?;
         let node_info = self.node_indices.get(&node_id)?;
 
-        // Lookup modifier ID
-        let seed_data = jewel_data.lookup_table.get(&seed)?;
-        let modifier_id = seed_data.get(&node_info.index)?;
+        let variant_data = variant
+            .and_then(|variant| jewel_data.variants.get(variant))
+            .and_then(|table| table.get(&seed));
+        let seed_data = match variant_data {
+            Some(seed_data) => seed_data,
+            None => jewel_data.lookup_table.get(&seed)?,
+        };
+        seed_data.get(&node_info.index)
+    }
+
+    /// Modifier an entry refers to
+    fn entry_modifier(&self, entry: &LutEntry) -> Option<&NodeModifier> {
+        match entry {
+            LutEntry::ModifierRef(modifier_id) => self.modifiers.get(modifier_id.as_str()),
+            // Glorious Vanity stats have no entry in `modifiers`
+            LutEntry::GloriousVanity { .. } => None,
+        }
+    }
+
+    /// Mod text of an entry
+    fn entry_mods(&self, entry: &LutEntry) -> Option<Vec<String>> {
+        match entry {
+            LutEntry::ModifierRef(_) => self.entry_modifier(entry).map(modifier_mods),
+            LutEntry::GloriousVanity { stats } => self.glorious_vanity_mods(stats),
+        }
+    }
+
+    /// Display name and stat lines of each Glorious Vanity stat, with its
+    /// rolls in place of the "#"s
+    ///
+    /// Stat indices refer to LegionPassives.lua's additions like binary
+    /// modifier indices do. None if no stat refers to a known modifier.
+    fn glorious_vanity_mods(&self, stats: &GvStats) -> Option<Vec<String>> {
+        let mut mods = Vec::new();
+
+        // A stat with several rolls is listed once per roll
+        for stat_rolls in stats.chunk_by(|a, b| a.0 == b.0) {
+            let StatId(stat) = stat_rolls[0].0;
+            let Some(modifier) = self
+                .modifier_indices
+                .get(&u32::from(stat))
+                .and_then(|id| self.modifiers.get(id))
+            else {
+                continue;
+            };
+
+            let mut rolls = stat_rolls.iter().map(|(_, roll)| *roll);
+            mods.push(modifier.display_name.clone());
+            mods.extend(modifier.stat_descriptions.iter().map(|line| fill_rolls(line, &mut rolls)));
+        }
+
+        (!mods.is_empty()).then_some(mods)
+    }
+}
+
+/// `line` with each "#" replaced by the next roll, while there are rolls left
+fn fill_rolls(line: &str, rolls: &mut impl Iterator<Item = u8>) -> String {
+    let mut parts = line.split('#');
+    let mut text = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        match rolls.next() {
+            Some(roll) => text.push_str(&roll.to_string()),
+            None => text.push('#'),
+        }
+        text.push_str(part);
+    }
+    text
+}
+
+/// Display name and stat lines of a modifier
+fn modifier_mods(modifier: &NodeModifier) -> Vec<String> {
+    let mut mods = vec![modifier.display_name.clone()];
+    mods.extend(modifier.stat_descriptions.iter().cloned());
+    mods
+}
+
+/// Key of a jewel type in `LutData::jewels` (e.g., "LethalPride")
+pub(crate) fn jewel_key(jewel_type: JewelType) -> String {
+    jewel_type.as_str().replace(' ', "")
+}
+
+impl TimelessLookup for LutData {
+    fn node_mods(&self, jewel_type: JewelType, seed: u32, node_id: u32) -> Option<Vec<String>> {
+        self.entry_mods(self.node_entry(&jewel_key(jewel_type), seed, node_id, None)?)
+    }
+
+    fn variant_node_mods(
+        &self,
+        jewel_type: JewelType,
+        seed: u32,
+        node_id: u32,
+        variant: &str,
+    ) -> Option<Vec<String>> {
+        let jewel_type = jewel_key(jewel_type);
+        self.entry_mods(self.node_entry(&jewel_type, seed, node_id, Some(variant))?)
+    }
+
+    fn has_variants(&self, jewel_type: JewelType, seed: u32) -> bool {
+        self.jewels
+            .get(&jewel_key(jewel_type))
+            .is_some_and(|jewel_data| jewel_data.variants.values().any(|t| t.contains_key(&seed)))
+    }
+
+    fn has_seed(&self, jewel_type: JewelType, seed: u32) -> Option<bool> {
+        let jewel_data = self.jewels.get(&jewel_key(jewel_type));
+        Some(jewel_data.is_some_and(|jewel_data| jewel_data.lookup_table.contains_key(&seed)))
+    }
+
+    fn nodes(&self) -> Vec<u32> {
+        let mut nodes: Vec<u32> = self.node_indices.keys().copied().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    fn trade_stat_id(&self, mod_text: &str) -> Option<&str> {
+        self.trade_stat_ids.get(mod_text).map(String::as_str)
+    }
 
-        // Get modifier
-        self.modifiers.get(modifier_id)
+    fn tree_version(&self) -> Option<&str> {
+        self.tree_version.as_deref()
     }
 }
@@ -1,67 +1,254 @@
 //! Parser module for converting PoB data to our optimized format
 
+mod artifact;
 mod lua;
 mod lut;
+mod report;
+mod trade_stats;
 mod zip_parser;
 
 #[cfg(test)]
 mod tests;
 
-pub use lut::{LutData, NodeModifier, PassiveNode, NodeInfo, JewelLutData};
+pub use lut::{
+    GvStats, JewelLutData, JewelStatistics, LutData, LutEntry, ModFrequency, ModId, ModifierIndex,
+    NodeInfo, NodeModifier, PassiveNode, StatId,
+};
+pub use artifact::ArtifactHeader;
 pub use lua::{LuaParser, NodeIndexMapping, LegionPassives};
-pub use zip_parser::ZipParser;
+pub use report::{FileReport, JewelReport, ParseReport};
+pub use trade_stats::{stat_template, TradeStatTable};
+pub use zip_parser::{ParseMode, ZipParser};
 
-use crate::error::DownloadError;
+use crate::error::{file_name, DownloadError, FileContext, FileOperation};
+use lut::jewel_key;
+use poe_item_analyzer_core::items::JewelType;
 use std::path::Path;
+use std::time::Instant;
+
+/// PoB file naming the passive tree version the data is for
+pub const TREE_VERSION_FILE: &str = "GameVersions.lua";
+
+/// Jewel data files parsed after the Lua metadata, in order
+const JEWEL_FILES: [&str; 5] = [
+    "LethalPride",
+    "BrutalRestraint",
+    "GloriousVanity",
+    "ElegantHubris",
+    "MilitantFaith",
+];
+
+/// Progress events emitted while parsing
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseEvent {
+    /// A file has started parsing
+    FileStarted {
+        current: usize,
+        total: usize,
+        file_name: String,
+    },
+
+    /// A file was parsed, or skipped because it is missing
+    FileCompleted {
+        current: usize,
+        total: usize,
+        file_name: String,
+    },
+}
 
 /// Main parser for converting PoB data to our format
 pub struct PobDataParser;
 
 impl PobDataParser {
     /// Parse PoB data directory and convert to optimized format
-    pub fn parse_directory(data_dir: &Path) -> Result<LutData, DownloadError> {
+    ///
+    /// Returns the parsed data together with a report of file sizes, seed
+    /// counts, timings and warnings collected along the way.
+    pub fn parse_directory(data_dir: &Path) -> Result<(LutData, ParseReport), DownloadError> {
+        Self::parse_directory_with_progress(data_dir, |_| {})
+    }
+
+    /// Parse PoB data directory, reporting each file to `progress`
+    pub fn parse_directory_with_progress(
+        data_dir: &Path,
+        progress: impl FnMut(ParseEvent),
+    ) -> Result<(LutData, ParseReport), DownloadError> {
+        Self::parse_directory_with_mode(data_dir, ParseMode::Strict, progress)
+    }
+
+    /// Parse PoB data directory, handling malformed jewel files per `mode`
+    ///
+    /// In `ParseMode::Lenient` a malformed file adds warnings and its
+    /// `JewelLutData::truncated` is set, instead of failing the parse.
+    pub fn parse_directory_with_mode(
+        data_dir: &Path,
+        mode: ParseMode,
+        mut progress: impl FnMut(ParseEvent),
+    ) -> Result<(LutData, ParseReport), DownloadError> {
+        let started = Instant::now();
+        let mut report = ParseReport::new();
+
+        let total = 2 + JEWEL_FILES.len();
+        let mut current = 0;
+        let mut step = |file_name: &str, done: bool| {
+            if !done {
+                current += 1;
+            }
+            let file_name = file_name.to_string();
+            progress(if done {
+                ParseEvent::FileCompleted { current, total, file_name }
+            } else {
+                ParseEvent::FileStarted { current, total, file_name }
+            });
+        };
+
         // Parse Lua metadata files
-        let node_mapping = LuaParser::parse_node_index_mapping(
-            &data_dir.join("NodeIndexMapping.lua")
-        )?;
+        let node_mapping_path = data_dir.join("NodeIndexMapping.lua");
+        step("NodeIndexMapping.lua", false);
+        let file_started = Instant::now();
+        let node_mapping = LuaParser::parse_node_index_mapping(&node_mapping_path)?;
+        report.files.push(FileReport {
+            name: file_name(&node_mapping_path),
+            bytes: report::file_size(&node_mapping_path),
+            decompressed_bytes: None,
+            duration: file_started.elapsed(),
+        });
+        step("NodeIndexMapping.lua", true);
 
-        let legion_passives = LuaParser::parse_legion_passives(
-            &data_dir.join("LegionPassives.lua")
-        )?;
+        let legion_passives_path = data_dir.join("LegionPassives.lua");
+        step("LegionPassives.lua", false);
+        let file_started = Instant::now();
+        let legion_passives = LuaParser::parse_legion_passives(&legion_passives_path)?;
+        report.files.push(FileReport {
+            name: file_name(&legion_passives_path),
+            bytes: report::file_size(&legion_passives_path),
+            decompressed_bytes: None,
+            duration: file_started.elapsed(),
+        });
+        step("LegionPassives.lua", true);
 
         // Convert to our LUT format (without jewel data yet)
         let mut lut_data = LutData::from_pob_data(node_mapping, legion_passives)?;
+        report.node_index_count = lut_data.node_indices.len();
+        report.modifier_count = lut_data.modifiers.len();
+
+        // Data downloaded before the file was in the manifest has no version
+        let versions_path = data_dir.join(TREE_VERSION_FILE);
+        if let Ok(lua_code) = std::fs::read_to_string(&versions_path) {
+            lut_data.tree_version = LuaParser::parse_tree_version(&lua_code);
+            if lut_data.tree_version.is_none() {
+                report.warn(format!("{} names no tree version", TREE_VERSION_FILE));
+            }
+        }
+
+        // Unmapped modifiers are kept, only listed in the report
+        let unmapped = TradeStatTable::embedded().apply(&mut lut_data);
+        if !unmapped.is_empty() {
+            report.warn(format!(
+                "{} of {} modifiers have no trade stat id",
+                unmapped.len(),
+                report.modifier_count
+            ));
+        }
+        report.unmapped_trade_stats = unmapped;
 
         // Extract and parse ZIP files for each jewel type
-        let jewel_types = vec![
-            "LethalPride",
-            "BrutalRestraint",
-            "GloriousVanity",
-            "ElegantHubris",
-            "MilitantFaith",
-        ];
-
-        for jewel_type in jewel_types {
-            let zip_path = data_dir.join(format!("{}.zip", jewel_type));
-
-            if zip_path.exists() {
-                let jewel_data = ZipParser::parse_jewel_zip(&zip_path, jewel_type)?;
-                lut_data.jewels.insert(jewel_type.to_string(), jewel_data);
-            } else {
-                eprintln!("Warning: {} not found, skipping", zip_path.display());
+        for jewel_type in JEWEL_FILES {
+            let file_name = format!("{}.zip", jewel_type);
+            step(&file_name, false);
+            Self::parse_jewel_file(data_dir, jewel_type, mode, &mut lut_data, &mut report)?;
+            step(&file_name, true);
+        }
+
+        report.total_duration = started.elapsed();
+
+        Ok((lut_data, report))
+    }
+
+    /// Parse `<jewel_type>.zip` into `lut_data`, replacing its previous data
+    ///
+    /// A missing file only adds a warning, and removes the jewel's old data.
+    /// Tables of conqueror variants, `<jewel_type>_<variant>.zip`, are
+    /// optional and kept alongside.
+    pub(crate) fn parse_jewel_file(
+        data_dir: &Path,
+        jewel_type: &str,
+        mode: ParseMode,
+        lut_data: &mut LutData,
+        report: &mut ParseReport,
+    ) -> Result<(), DownloadError> {
+        let file_name = format!("{}.zip", jewel_type);
+        let zip_path = data_dir.join(&file_name);
+        let variant_prefix = format!("{}_", jewel_type);
+        report
+            .files
+            .retain(|file| file.name != file_name && !file.name.starts_with(&variant_prefix));
+
+        if zip_path.exists() {
+            let mut jewel_data = ZipParser::parse_jewel_zip(&zip_path, jewel_type, mode, report)?;
+            Self::parse_variant_files(data_dir, jewel_type, mode, &mut jewel_data, report)?;
+            let unresolved = lut_data.resolve_modifier_indices(&mut jewel_data);
+            if unresolved > 0 {
+                report.warn(format!(
+                    "{}: {} entries refer to no modifier in LegionPassives.lua",
+                    file_name, unresolved
+                ));
             }
+            lut_data.jewels.insert(jewel_type.to_string(), jewel_data);
+        } else {
+            lut_data.jewels.remove(jewel_type);
+            report.jewels.remove(jewel_type);
+            report.warn(format!("{} not found, skipping", zip_path.display()));
         }
 
-        Ok(lut_data)
+        Ok(())
+    }
+
+    /// Add the tables of the jewel's conqueror variants present in `data_dir`
+    fn parse_variant_files(
+        data_dir: &Path,
+        jewel_type: &str,
+        mode: ParseMode,
+        jewel_data: &mut JewelLutData,
+        report: &mut ParseReport,
+    ) -> Result<(), DownloadError> {
+        let Some(kind) = JewelType::ALL.into_iter().find(|kind| jewel_key(*kind) == jewel_type)
+        else {
+            return Ok(());
+        };
+
+        for variant in kind.variants() {
+            let zip_path = data_dir.join(format!("{}_{}.zip", jewel_type, variant));
+            if !zip_path.exists() {
+                continue;
+            }
+
+            // Its seed counts would replace the jewel's in `report.jewels`
+            let mut variant_report = ParseReport::new();
+            let variant_data =
+                ZipParser::parse_jewel_zip(&zip_path, jewel_type, mode, &mut variant_report)?;
+            report.files.extend(variant_report.files);
+            report.warnings.extend(variant_report.warnings);
+            jewel_data.truncated |= variant_data.truncated;
+            jewel_data.variants.insert(variant.to_string(), variant_data.lookup_table);
+        }
+
+        Ok(())
+    }
+
+    /// Jewel type parsed from `file_name` (e.g., "LethalPride" for "LethalPride.zip")
+    pub(crate) fn jewel_file_type(file_name: &str) -> Option<&'static str> {
+        let stem = file_name.strip_suffix(".zip")?;
+        JEWEL_FILES.into_iter().find(|jewel_type| *jewel_type == stem)
     }
 
     /// Save parsed data to JSON file
     pub fn save_to_json(lut_data: &LutData, output_path: &Path) -> Result<(), DownloadError> {
-        let json = serde_json::to_string_pretty(lut_data)
-            .map_err(|e| DownloadError::DownloadFailed(e.to_string()))?;
+        let json = serde_json::to_string_pretty(lut_data).map_err(|e| {
+            DownloadError::file_failed(FileOperation::Write, file_name(output_path), e)
+        })?;
 
-        std::fs::write(output_path, json)
-            .map_err(|e| DownloadError::IoError(e))?;
+        std::fs::write(output_path, json).file_context(FileOperation::Write, output_path)?;
 
         Ok(())
     }
@@ -69,9 +256,43 @@ impl PobDataParser {
     /// Load parsed data from JSON file
     pub fn load_from_json(input_path: &Path) -> Result<LutData, DownloadError> {
         let json = std::fs::read_to_string(input_path)
-            .map_err(|e| DownloadError::IoError(e))?;
+            .file_context(FileOperation::Read, input_path)?;
 
         serde_json::from_str(&json)
-            .map_err(|e| DownloadError::InvalidManifest(e.to_string()))
+            .map_err(|e| DownloadError::file_failed(FileOperation::Parse, file_name(input_path), e))
+    }
+
+    /// Save parsed data to a binary cache file
+    ///
+    /// Much faster to load than JSON. The payload format is private to this
+    /// crate version; a header records the version and the payload's SHA256.
+    pub fn save_to_binary(lut_data: &LutData, output_path: &Path) -> Result<(), DownloadError> {
+        artifact::write(lut_data, output_path)
+    }
+
+    /// Load parsed data from a binary cache file
+    ///
+    /// Files without a valid header, from another version or whose payload
+    /// doesn't match the header's SHA256 are rejected; the errors convert to
+    /// `DataError::CorruptedData`.
+    pub fn load_from_binary(input_path: &Path) -> Result<LutData, DownloadError> {
+        artifact::read(input_path).map(|(_, data)| data)
+    }
+
+    /// Check the header of a binary cache file without loading it
+    pub fn verify_binary(input_path: &Path) -> Result<ArtifactHeader, DownloadError> {
+        artifact::verify(input_path)
+    }
+
+    /// Load parsed data saved in `format` ("bincode" or "json")
+    pub fn load_artifact(input_path: &Path, format: &str) -> Result<LutData, DownloadError> {
+        match format {
+            "bincode" => Self::load_from_binary(input_path),
+            "json" => Self::load_from_json(input_path),
+            other => Err(DownloadError::InvalidManifest(format!(
+                "unknown parsed data format: {}",
+                other
+            ))),
+        }
     }
 }
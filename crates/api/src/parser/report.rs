@@ -0,0 +1,125 @@
+//! Parse statistics collected while converting PoB data
+//!
+//! A `ParseReport` is produced alongside the `LutData` so callers (the desktop
+//! UI, support/debug tooling) don't have to reconstruct statistics from the
+//! parsed tables themselves.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::{file_name, DownloadError, FileContext, FileOperation};
+
+/// Statistics and warnings collected while parsing a PoB data directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParseReport {
+    /// Per-file statistics, in the order the files were parsed
+    pub files: Vec<FileReport>,
+
+    /// Per-jewel statistics, keyed by jewel type (e.g., "LethalPride")
+    pub jewels: BTreeMap<String, JewelReport>,
+
+    /// Number of node index mappings parsed
+    pub node_index_count: usize,
+
+    /// Number of modifiers parsed
+    pub modifier_count: usize,
+
+    /// Ids of the modifiers without a trade stat id
+    #[serde(default)]
+    pub unmapped_trade_stats: Vec<String>,
+
+    /// Total time spent parsing the directory
+    pub total_duration: Duration,
+
+    /// Warnings collected during parsing
+    pub warnings: Vec<String>,
+}
+
+/// Statistics for a single parsed file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileReport {
+    /// File name (e.g., "LethalPride.zip")
+    pub name: String,
+
+    /// Size of the file on disk in bytes
+    pub bytes: u64,
+
+    /// Size after decompression (compressed files only)
+    pub decompressed_bytes: Option<u64>,
+
+    /// Time spent parsing this file
+    pub duration: Duration,
+}
+
+/// Statistics for a single jewel type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JewelReport {
+    /// Number of seeds with at least one modifier
+    pub seed_count: usize,
+
+    /// Number of nodes detected in the binary data
+    pub node_count: usize,
+}
+
+impl ParseReport {
+    /// Create an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a warning
+    pub fn warn(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        tracing::warn!("{}", message);
+        self.warnings.push(message);
+    }
+
+    /// Find the report for a file by name
+    pub fn find_file(&self, name: &str) -> Option<&FileReport> {
+        self.files.iter().find(|f| f.name == name)
+    }
+
+    /// Total number of seeds with data across all jewel types
+    pub fn total_seed_count(&self) -> usize {
+        self.jewels.values().map(|j| j.seed_count).sum()
+    }
+
+    /// Path of the report written next to a LUT JSON file
+    ///
+    /// `lut_data.json` becomes `lut_data.report.json`.
+    pub fn sidecar_path(lut_path: &Path) -> PathBuf {
+        let stem = lut_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "lut_data".to_string());
+
+        lut_path.with_file_name(format!("{}.report.json", stem))
+    }
+
+    /// Save report to JSON file
+    pub fn save_to_json(&self, output_path: &Path) -> Result<(), DownloadError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            DownloadError::file_failed(FileOperation::Write, file_name(output_path), e)
+        })?;
+
+        std::fs::write(output_path, json).file_context(FileOperation::Write, output_path)?;
+
+        Ok(())
+    }
+
+    /// Load report from JSON file
+    pub fn load_from_json(input_path: &Path) -> Result<Self, DownloadError> {
+        let json =
+            std::fs::read_to_string(input_path).file_context(FileOperation::Read, input_path)?;
+
+        serde_json::from_str(&json)
+            .map_err(|e| DownloadError::file_failed(FileOperation::Parse, file_name(input_path), e))
+    }
+}
+
+/// Size of a file on disk, or 0 if it can't be read
+pub(crate) fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
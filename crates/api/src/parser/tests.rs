@@ -42,12 +42,52 @@ fn test_node_modifier_search_text() {
         display_name: "Fire Damage".to_string(),
         stat_descriptions: vec!["10% increased Fire Damage".to_string()],
         search_text: "fire damage 10% increased fire damage".to_string(),
+        trade_stat_id: None,
     };
 
     assert!(modifier.search_text.contains("fire"));
     assert!(modifier.search_text.contains("damage"));
 }
 
+#[test]
+fn test_search_modifiers() {
+    use super::lua::{LegionPassives, NodeIndexMapping, PassiveAddition};
+    use std::collections::HashMap;
+
+    let addition = |name: &str, stat: &str| PassiveAddition {
+        index: 1,
+        display_name: name.to_string(),
+        stat_descriptions: vec![stat.to_string()],
+    };
+    let legion_passives = LegionPassives {
+        additions: HashMap::from([
+            ("fire".to_string(), addition("Fire Damage", "10% increased Fire Damage")),
+            ("cold".to_string(), addition("Cold Damage", "10% increased Cold Damage")),
+            ("str".to_string(), addition("Strength", "+10 to Strength")),
+        ]),
+    };
+    let node_mapping = NodeIndexMapping {
+        size: 0,
+        size_notable: 0,
+        nodes: HashMap::new(),
+    };
+    let lut_data = LutData::from_pob_data(node_mapping, legion_passives).unwrap();
+
+    let names = |query: &str, limit: usize| -> Vec<String> {
+        lut_data
+            .search_modifiers(query, limit)
+            .iter()
+            .map(|m| m.display_name.clone())
+            .collect()
+    };
+
+    assert_eq!(names("damage", 10), vec!["Cold Damage", "Fire Damage"]);
+    assert_eq!(names("INCREASED fire", 10), vec!["Fire Damage"]);
+    assert_eq!(names("+10", 10), vec!["Strength"]);
+    assert_eq!(names("", 2), vec!["Cold Damage", "Fire Damage"]);
+    assert!(names("lightning", 10).is_empty());
+}
+
 #[test]
 fn test_save_and_load_json() {
     use super::lua::{NodeIndexMapping, LegionPassives};
@@ -104,32 +144,992 @@ fn test_get_modifier_not_found() {
     assert!(result.is_none());
 }
 
+#[test]
+fn test_lut_data_timeless_lookup() {
+    use super::lut::{JewelLutData, LutEntry, NodeModifier};
+    use poe_item_analyzer_core::data::TimelessLookup;
+    use poe_item_analyzer_core::items::JewelType;
+    use std::collections::HashMap;
+
+    let mut lut_data = LutData {
+        version: "1.0.0".to_string(),
+        node_indices: HashMap::new(),
+        modifiers: HashMap::new(),
+        modifier_indices: HashMap::new(),
+        jewels: HashMap::new(),
+        trade_stat_ids: HashMap::new(),
+        tree_version: None,
+    };
+    lut_data.node_indices.insert(
+        36634,
+        NodeInfo { index: 0, size: 1, name: None, is_notable: true },
+    );
+    lut_data.modifiers.insert(
+        "karui_str".to_string(),
+        NodeModifier {
+            id: "karui_str".to_string(),
+            display_name: "Strength of Blood".to_string(),
+            stat_descriptions: vec!["+20 to Strength".to_string()],
+            search_text: String::new(),
+            trade_stat_id: None,
+        },
+    );
+    lut_data.jewels.insert(
+        "LethalPride".to_string(),
+        JewelLutData {
+            jewel_type: "LethalPride".to_string(),
+            seed_range: (10000, 18000),
+            lookup_table: HashMap::from([(
+                14032,
+                HashMap::from([(0, LutEntry::from("karui_str"))]),
+            )]),
+            variants: HashMap::new(),
+            truncated: false,
+        },
+    );
+
+    assert_eq!(
+        lut_data.node_mods(JewelType::LethalPride, 14032, 36634),
+        Some(vec!["Strength of Blood".to_string(), "+20 to Strength".to_string()])
+    );
+    assert_eq!(lut_data.node_mods(JewelType::LethalPride, 14033, 36634), None);
+    assert_eq!(lut_data.node_mods(JewelType::BrutalRestraint, 14032, 36634), None);
+    assert_eq!(lut_data.nodes(), vec![36634]);
+}
+
+#[test]
+fn test_modifier_index() {
+    use super::lut::{JewelLutData, LutEntry};
+    use poe_item_analyzer_core::items::JewelType;
+    use std::collections::HashMap;
+
+    let mut lut_data = LutData {
+        version: "1.0.0".to_string(),
+        node_indices: HashMap::new(),
+        modifiers: HashMap::new(),
+        modifier_indices: HashMap::new(),
+        jewels: HashMap::new(),
+        trade_stat_ids: HashMap::new(),
+        tree_version: None,
+    };
+    lut_data.jewels.insert(
+        "LethalPride".to_string(),
+        JewelLutData {
+            jewel_type: "LethalPride".to_string(),
+            seed_range: (10000, 18000),
+            lookup_table: HashMap::from([
+                (
+                    10042,
+                    HashMap::from([(0, LutEntry::from("karui_str")), (1, LutEntry::from("karui_str"))]),
+                ),
+                (10001, HashMap::from([(0, LutEntry::from("karui_str")), (1, LutEntry::from("karui_life"))])),
+            ]),
+            variants: HashMap::new(),
+            truncated: false,
+        },
+    );
+
+    let index = lut_data.modifier_index();
+
+    // Seeds are listed once even when several nodes roll the modifier
+    assert_eq!(index.seeds("karui_str", JewelType::LethalPride), &[10001, 10042]);
+    assert_eq!(index.seed_count("karui_life", JewelType::LethalPride), 1);
+    assert_eq!(index.seed_count("karui_str", JewelType::BrutalRestraint), 0);
+    assert!(index.seeds("unknown", JewelType::LethalPride).is_empty());
+}
+
 #[test]
 fn test_zip_parser_seed_ranges() {
     use super::zip_parser::ZipParser;
-    use std::fs::File;
-    use std::io::Write;
-    use zip::write::FileOptions;
-    use zip::ZipWriter;
 
     let temp_dir = TempDir::new().unwrap();
     let zip_path = temp_dir.path().join("LethalPride.zip");
 
-    // Create a dummy ZIP file
-    {
-        let file = File::create(&zip_path).unwrap();
-        let mut zip = ZipWriter::new(file);
-
-        zip.start_file("data.bin", FileOptions::default()).unwrap();
-        zip.write_all(&[0u8; 100]).unwrap();
-        zip.finish().unwrap();
-    }
+    // Create a dummy zlib-compressed data file
+    write_zlib(&zip_path, &[0u8; 100]);
 
     // Parse it
-    let result = ZipParser::parse_jewel_zip(&zip_path, "LethalPride");
+    let mut report = ParseReport::new();
+    let result =
+        ZipParser::parse_jewel_zip(&zip_path, "LethalPride", ParseMode::Lenient, &mut report);
     assert!(result.is_ok());
 
     let jewel_data = result.unwrap();
     assert_eq!(jewel_data.jewel_type, "LethalPride");
     assert_eq!(jewel_data.seed_range, (10000, 18000));
+
+    // 100 bytes isn't a whole number of nodes, which should be reported
+    assert!(jewel_data.truncated);
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.warnings[0].contains("not evenly divisible"));
+}
+
+/// Parse `buffer` as a `jewel_type` file in `mode`
+fn parse_buffer(
+    jewel_type: &str,
+    buffer: &[u8],
+    mode: ParseMode,
+) -> (Result<JewelLutData, DownloadError>, ParseReport) {
+    use super::zip_parser::ZipParser;
+
+    let temp_dir = TempDir::new().unwrap();
+    let zip_path = temp_dir.path().join(format!("{}.zip", jewel_type));
+    write_zlib(&zip_path, buffer);
+
+    let mut report = ParseReport::new();
+    let result = ZipParser::parse_jewel_zip(&zip_path, jewel_type, mode, &mut report);
+    (result, report)
+}
+
+#[test]
+fn test_truncated_binary_data_fails_in_strict_mode() {
+    // Two whole nodes, seed 10000 set on both, then half of a third node
+    let mut buffer = vec![0u8; 2 * LETHAL_PRIDE_SEEDS + LETHAL_PRIDE_SEEDS / 2];
+    buffer[0] = 1;
+    buffer[LETHAL_PRIDE_SEEDS] = 2;
+    buffer[2 * LETHAL_PRIDE_SEEDS + 1] = 3;
+
+    let (result, report) = parse_buffer("LethalPride", &buffer, ParseMode::Strict);
+    match result {
+        Err(DownloadError::MalformedData {
+            file,
+            reason,
+            salvaged_seeds,
+            salvaged_entries,
+        }) => {
+            assert_eq!(file, "LethalPride.zip");
+            assert!(reason.contains("not evenly divisible"), "{}", reason);
+            assert_eq!((salvaged_seeds, salvaged_entries), (1, 2));
+        }
+        other => panic!("Expected malformed data, got {:?}", other),
+    }
+    assert!(report.jewels.is_empty());
+
+    // Lenient mode keeps the two whole nodes
+    let (result, report) = parse_buffer("LethalPride", &buffer, ParseMode::Lenient);
+    let jewel_data = result.unwrap();
+    assert!(jewel_data.truncated);
+    assert_eq!(jewel_data.lookup_table.len(), 1);
+    assert_eq!(jewel_data.lookup_table[&10000].len(), 2);
+    assert!(report.warnings[0].starts_with("LethalPride.zip: "));
+
+    // Whole nodes are not truncated
+    let whole_nodes = &buffer[..2 * LETHAL_PRIDE_SEEDS];
+    let (result, report) = parse_buffer("LethalPride", whole_nodes, ParseMode::Strict);
+    assert!(!result.unwrap().truncated);
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn test_empty_and_garbage_jewel_files() {
+    use super::zip_parser::ZipParser;
+
+    let (result, _) = parse_buffer("LethalPride", &[], ParseMode::Strict);
+    assert!(matches!(result, Err(DownloadError::MalformedData { salvaged_seeds: 0, .. })));
+    let (result, _) = parse_buffer("GloriousVanity", &[], ParseMode::Lenient);
+    let jewel_data = result.unwrap();
+    assert!(jewel_data.truncated && jewel_data.lookup_table.is_empty());
+
+    // Not zlib at all
+    let temp_dir = TempDir::new().unwrap();
+    let zip_path = temp_dir.path().join("LethalPride.zip");
+    std::fs::write(&zip_path, b"<html>404: Not Found</html>").unwrap();
+    for mode in [ParseMode::Strict, ParseMode::Lenient] {
+        let result =
+            ZipParser::parse_jewel_zip(&zip_path, "LethalPride", mode, &mut ParseReport::new());
+        assert!(matches!(
+            result,
+            Err(DownloadError::IoError { operation: FileOperation::Decompress, .. })
+        ));
+    }
+
+    // A zlib stream cut off mid-way
+    let data: Vec<u8> = (0..=255).cycle().take(4 * LETHAL_PRIDE_SEEDS).collect();
+    write_zlib(&zip_path, &data);
+    let compressed = std::fs::read(&zip_path).unwrap();
+    std::fs::write(&zip_path, &compressed[..compressed.len() / 2]).unwrap();
+    let mut report = ParseReport::new();
+    let result =
+        ZipParser::parse_jewel_zip(&zip_path, "LethalPride", ParseMode::Strict, &mut report);
+    assert!(result.is_err());
+
+    // Decompressed garbage is reported, not panicked on
+    let garbage: Vec<u8> =
+        (0..200_000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+    let (result, report) = parse_buffer("GloriousVanity", &garbage, ParseMode::Lenient);
+    assert!(result.unwrap().truncated);
+    assert!(!report.warnings.is_empty());
+}
+
+/// Lethal Pride seed range size (10000..=18000)
+const LETHAL_PRIDE_SEEDS: usize = 8001;
+
+/// Write `data` zlib-compressed, matching the PoB "ZIP" file format
+fn write_zlib(path: &std::path::Path, data: &[u8]) {
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    std::fs::write(path, encoder.finish().unwrap()).unwrap();
+}
+
+/// Create a minimal PoB data directory:
+/// - 3 nodes in NodeIndexMapping.lua
+/// - 2 modifiers in LegionPassives.lua
+/// - LethalPride.zip with 2 nodes and modifiers on 3 distinct seeds
+fn create_fixture_directory() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+
+    std::fs::write(
+        dir.join("NodeIndexMapping.lua"),
+        r#"nodeIDList = {}
+nodeIDList["size"] = 3
+nodeIDList["sizeNotable"] = 1
+nodeIDList[100] = { index = 0, size = 1 }
+nodeIDList[200] = { index = 1, size = 1 }
+nodeIDList[300] = { index = 2, size = 0 }
+"#,
+    )
+    .unwrap();
+
+    std::fs::write(
+        dir.join("LegionPassives.lua"),
+        r#"return {
+    additions = {
+        [1] = { id = "karui_notable_add_strength", dn = "Strength", sd = { "+20 to Strength" } },
+        [2] = { id = "karui_notable_add_life", dn = "Life", sd = { "+10 to maximum Life" } },
+    },
+}
+"#,
+    )
+    .unwrap();
+
+    let mut buffer = vec![0u8; 2 * LETHAL_PRIDE_SEEDS];
+    buffer[0] = 1; // node 0, seed 10000
+    buffer[5] = 2; // node 0, seed 10005
+    buffer[LETHAL_PRIDE_SEEDS] = 1; // node 1, seed 10000
+    buffer[LETHAL_PRIDE_SEEDS + 42] = 2; // node 1, seed 10042
+    write_zlib(&dir.join("LethalPride.zip"), &buffer);
+
+    temp_dir
+}
+
+#[test]
+fn test_parse_report_counts_match_fixture() {
+    let temp_dir = create_fixture_directory();
+
+    let (lut_data, report) = PobDataParser::parse_directory(temp_dir.path()).unwrap();
+
+    assert_eq!(report.node_index_count, 3);
+    assert_eq!(report.modifier_count, 2);
+    assert_eq!(report.node_index_count, lut_data.node_indices.len());
+    assert_eq!(report.modifier_count, lut_data.modifiers.len());
+
+    // Only Lethal Pride is present
+    assert_eq!(report.jewels.len(), 1);
+    let lethal_pride = &report.jewels["LethalPride"];
+    assert_eq!(lethal_pride.seed_count, 3);
+    assert_eq!(lethal_pride.node_count, 2);
+    assert_eq!(report.total_seed_count(), 3);
+
+    // Lua files plus one jewel file
+    assert_eq!(report.files.len(), 3);
+
+    let zip_path = temp_dir.path().join("LethalPride.zip");
+    let zip_report = report.find_file("LethalPride.zip").unwrap();
+    assert_eq!(zip_report.bytes, std::fs::metadata(&zip_path).unwrap().len());
+    assert_eq!(
+        zip_report.decompressed_bytes,
+        Some((2 * LETHAL_PRIDE_SEEDS) as u64)
+    );
+
+    let lua_report = report.find_file("NodeIndexMapping.lua").unwrap();
+    assert!(lua_report.bytes > 0);
+    assert_eq!(lua_report.decompressed_bytes, None);
+
+    // The four other jewel files are missing
+    assert_eq!(report.warnings.len(), 4);
+    assert!(report.warnings.iter().any(|w| w.contains("GloriousVanity.zip")));
+}
+
+#[test]
+fn test_parse_directory_resolves_modifier_indices() {
+    use poe_item_analyzer_core::data::TimelessLookup;
+    use poe_item_analyzer_core::items::JewelType;
+
+    let temp_dir = create_fixture_directory();
+    let dir = temp_dir.path();
+
+    let (lut_data, report) = PobDataParser::parse_directory(dir).unwrap();
+    assert_eq!(lut_data.modifier_indices[&1], "karui_notable_add_strength");
+
+    // Binary entries hold additions indices; lookups get the modifiers
+    let modifier = lut_data.get_modifier("LethalPride", 10000, 100).unwrap();
+    assert_eq!(modifier.id, "karui_notable_add_strength");
+    assert_eq!(
+        lut_data.node_mods(JewelType::LethalPride, 10005, 100),
+        Some(vec!["Life".to_string(), "+10 to maximum Life".to_string()])
+    );
+    assert_eq!(
+        lut_data.node_mods(JewelType::LethalPride, 10042, 200),
+        Some(vec!["Life".to_string(), "+10 to maximum Life".to_string()])
+    );
+    assert_eq!(lut_data.node_mods(JewelType::LethalPride, 10001, 100), None);
+    assert!(!report.warnings.iter().any(|w| w.contains("refer to no modifier")));
+
+    // Indices past the additions are kept and reported
+    let mut buffer = vec![0u8; LETHAL_PRIDE_SEEDS];
+    buffer[0] = 9;
+    write_zlib(&dir.join("LethalPride.zip"), &buffer);
+    let (lut_data, report) = PobDataParser::parse_directory(dir).unwrap();
+    assert_eq!(lut_data.jewels["LethalPride"].lookup_table[&10000][&0], LutEntry::from("9"));
+    assert!(report.warnings.iter().any(|w| w.contains("1 entries refer to no modifier")));
+}
+
+#[test]
+fn test_parse_directory_scores_glorious_vanity() {
+    use super::zip_parser::GV_NODE_COUNT;
+    use poe_item_analyzer_core::analyzers::{Analyzer, TimelessJewelAnalyzer, TimelessJewelConfig};
+    use poe_item_analyzer_core::data::TimelessLookup;
+    use poe_item_analyzer_core::items::{JewelType, TimelessJewel};
+    use std::sync::Arc;
+
+    let temp_dir = create_fixture_directory();
+    let dir = temp_dir.path();
+    std::fs::write(
+        dir.join("LegionPassives.lua"),
+        r##"return {
+    additions = {
+        [1] = { id = "karui_notable_add_strength", dn = "Strength", sd = { "+20 to Strength" } },
+        [3] = {
+            id = "vaal_small_fire",
+            dn = "Fire",
+            sd = { "#% increased Fire Damage", "+# to Dexterity" },
+        },
+    },
+}
+"##,
+    )
+    .unwrap();
+
+    // Seed 100: node 0 gets stat 3 with two rolls, node 1 gets stat 1
+    let seed_size = 7901;
+    let mut buffer = vec![0u8; GV_NODE_COUNT * seed_size];
+    buffer[0] = 3;
+    buffer[seed_size] = 2;
+    buffer.extend([3, 12, 15, 1, 0]);
+    write_zlib(&dir.join("GloriousVanity.zip"), &buffer);
+
+    let (lut_data, _) = PobDataParser::parse_directory(dir).unwrap();
+    assert_eq!(
+        lut_data.node_mods(JewelType::GloriousVanity, 100, 100),
+        Some(vec![
+            "Fire".to_string(),
+            "12% increased Fire Damage".to_string(),
+            "+15 to Dexterity".to_string(),
+        ])
+    );
+
+    let mut config = TimelessJewelConfig::new();
+    config.add_mod("12% increased Fire Damage".to_string(), 2.0);
+    config.add_mod("+20 to Strength".to_string(), 1.0);
+    let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(lut_data));
+    let jewel = TimelessJewel::new(
+        "Glorious Vanity:100:Doryani".to_string(),
+        JewelType::GloriousVanity,
+        100,
+        "Doryani".to_string(),
+        serde_json::Value::Null,
+    );
+
+    let result = analyzer.analyze(&jewel, &config).unwrap();
+    assert_eq!(result.metrics.socket_results[0].score, 3.0);
+}
+
+#[test]
+fn test_conqueror_variant_picks_effect_set() {
+    use super::lut::{JewelLutData, LutEntry, NodeModifier};
+    use poe_item_analyzer_core::analyzers::{
+        AnalysisWarning, Analyzer, TimelessJewelAnalyzer, TimelessJewelConfig,
+    };
+    use poe_item_analyzer_core::items::{JewelType, TimelessJewel};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    let modifier = |id: &str, name: &str| {
+        let modifier = NodeModifier {
+            id: id.to_string(),
+            display_name: name.to_string(),
+            stat_descriptions: Vec::new(),
+            search_text: String::new(),
+            trade_stat_id: None,
+        };
+        (id.to_string(), modifier)
+    };
+    let table = |id: &str| HashMap::from([(2000, HashMap::from([(0, LutEntry::from(id))]))]);
+
+    let lut_data = LutData {
+        version: "1.0.0".to_string(),
+        node_indices: HashMap::from([(
+            36634,
+            NodeInfo { index: 0, size: 1, name: None, is_notable: true },
+        )]),
+        modifiers: HashMap::from([
+            modifier("eternal", "Eternal Shared"),
+            modifier("cadiro", "Cadiro's Era"),
+            modifier("victario", "Victario's Era"),
+        ]),
+        modifier_indices: HashMap::new(),
+        jewels: HashMap::from([(
+            "ElegantHubris".to_string(),
+            JewelLutData {
+                jewel_type: "ElegantHubris".to_string(),
+                seed_range: (2000, 160000),
+                lookup_table: table("eternal"),
+                variants: HashMap::from([
+                    ("Cadiro".to_string(), table("cadiro")),
+                    ("Victario".to_string(), table("victario")),
+                ]),
+                truncated: false,
+            },
+        )]),
+        trade_stat_ids: HashMap::new(),
+        tree_version: None,
+    };
+
+    let mut config = TimelessJewelConfig::new();
+    for name in ["Eternal Shared", "Cadiro's Era", "Victario's Era"] {
+        config.add_mod(name.to_string(), 1.0);
+    }
+    let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(lut_data));
+    let analyze = |conqueror: &str| {
+        let jewel = TimelessJewel::new(
+            format!("Elegant Hubris:2000:{}", conqueror),
+            JewelType::ElegantHubris,
+            2000,
+            conqueror.to_string(),
+            serde_json::Value::Null,
+        );
+        let result = analyzer.analyze(&jewel, &config).unwrap();
+        let matched: Vec<String> = result.metrics.socket_results[0]
+            .matched_mods
+            .iter()
+            .map(|matched| matched.mod_text.clone())
+            .collect();
+        (matched, result.warnings)
+    };
+
+    let (cadiro, warnings) = analyze("Cadiro");
+    assert_eq!(cadiro, vec!["Cadiro's Era"]);
+    assert!(warnings.is_empty());
+    assert_eq!(analyze("Victario").0, vec!["Victario's Era"]);
+
+    // Caspiro has no table of its own, and Chitus shares Caspiro's
+    assert_eq!(analyze("Caspiro").0, vec!["Eternal Shared"]);
+    assert_eq!(analyze("Chitus").0, vec!["Eternal Shared"]);
+
+    let (matched, warnings) = analyze("");
+    assert_eq!(matched, vec!["Eternal Shared"]);
+    assert!(warnings.contains(&AnalysisWarning::AmbiguousConqueror {
+        conqueror: String::new()
+    }));
+}
+
+#[test]
+fn test_parse_directory_keeps_conqueror_variant_tables() {
+    const ELEGANT_HUBRIS_SEEDS: usize = 158001;
+
+    let temp_dir = create_fixture_directory();
+    let dir = temp_dir.path();
+
+    // One node; seed 2000 gets modifier 1 from the shared table, 2 from Cadiro's
+    let mut shared = vec![0u8; ELEGANT_HUBRIS_SEEDS];
+    shared[0] = 1;
+    write_zlib(&dir.join("ElegantHubris.zip"), &shared);
+    let mut cadiro = vec![0u8; ELEGANT_HUBRIS_SEEDS];
+    cadiro[0] = 2;
+    write_zlib(&dir.join("ElegantHubris_Cadiro.zip"), &cadiro);
+
+    let (lut_data, report) = PobDataParser::parse_directory(dir).unwrap();
+
+    let jewel_data = &lut_data.jewels["ElegantHubris"];
+    let strength = LutEntry::from("karui_notable_add_strength");
+    assert_eq!(jewel_data.lookup_table[&2000][&0], strength);
+    assert_eq!(jewel_data.variants.len(), 1);
+    let life = LutEntry::from("karui_notable_add_life");
+    assert_eq!(jewel_data.variants["Cadiro"][&2000][&0], life);
+
+    // The variant file is reported, without replacing the jewel's seed count
+    assert!(report.find_file("ElegantHubris_Cadiro.zip").is_some());
+    assert_eq!(report.jewels["ElegantHubris"].seed_count, 1);
+
+    let json = serde_json::to_string(&lut_data).unwrap();
+    let loaded: LutData = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.jewels["ElegantHubris"].variants["Cadiro"].len(), 1);
+}
+
+#[test]
+fn test_parse_tree_version() {
+    let game_versions = r#"-- This file is automatically generated, do not edit!
+legacyTargetVersion = "2_6"
+latestTreeVersion = "3_25"
+treeVersionList = { "2_6", "3_24", "3_25" }
+"#;
+    assert_eq!(LuaParser::parse_tree_version(game_versions).as_deref(), Some("3_25"));
+    let single_quoted = LuaParser::parse_tree_version("latestTreeVersion = '3_24'");
+    assert_eq!(single_quoted.as_deref(), Some("3_24"));
+    assert_eq!(LuaParser::parse_tree_version("latestTreeVersion = nil"), None);
+    assert_eq!(LuaParser::parse_tree_version(""), None);
+}
+
+#[test]
+fn test_parse_directory_records_tree_version() {
+    use poe_item_analyzer_core::data::TimelessLookup;
+
+    let temp_dir = create_fixture_directory();
+
+    let (lut_data, _) = PobDataParser::parse_directory(temp_dir.path()).unwrap();
+    assert_eq!(lut_data.tree_version, None);
+
+    std::fs::write(
+        temp_dir.path().join(TREE_VERSION_FILE),
+        "latestTreeVersion = \"3_25\"\n",
+    )
+    .unwrap();
+    let (lut_data, report) = PobDataParser::parse_directory(temp_dir.path()).unwrap();
+    assert_eq!(lut_data.tree_version.as_deref(), Some("3_25"));
+    assert_eq!(lut_data.tree_version(), Some("3_25"));
+    assert_eq!(report.warnings.len(), 4);
+}
+
+#[test]
+fn test_parse_directory_reports_progress() {
+    let temp_dir = create_fixture_directory();
+    let mut events = Vec::new();
+
+    PobDataParser::parse_directory_with_progress(temp_dir.path(), |event| events.push(event))
+        .unwrap();
+
+    // Start and completion for both Lua files and all five jewel files
+    assert_eq!(events.len(), 14);
+    assert_eq!(
+        events[0],
+        ParseEvent::FileStarted {
+            current: 1,
+            total: 7,
+            file_name: "NodeIndexMapping.lua".to_string(),
+        }
+    );
+    assert_eq!(
+        events.last(),
+        Some(&ParseEvent::FileCompleted {
+            current: 7,
+            total: 7,
+            file_name: "MilitantFaith.zip".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_parse_report_durations_non_zero() {
+    let temp_dir = create_fixture_directory();
+
+    let (_, report) = PobDataParser::parse_directory(temp_dir.path()).unwrap();
+
+    assert!(!report.total_duration.is_zero());
+    for file in &report.files {
+        assert!(!file.duration.is_zero(), "{} has zero duration", file.name);
+    }
+
+    let file_total: std::time::Duration = report.files.iter().map(|f| f.duration).sum();
+    assert!(report.total_duration >= file_total);
+}
+
+#[test]
+fn test_parse_report_save_next_to_lut_json() {
+    let temp_dir = create_fixture_directory();
+    let (lut_data, report) = PobDataParser::parse_directory(temp_dir.path()).unwrap();
+
+    let json_path = temp_dir.path().join("lut_data.json");
+    PobDataParser::save_to_json(&lut_data, &json_path).unwrap();
+
+    let report_path = ParseReport::sidecar_path(&json_path);
+    assert_eq!(report_path, temp_dir.path().join("lut_data.report.json"));
+
+    report.save_to_json(&report_path).unwrap();
+    let loaded = ParseReport::load_from_json(&report_path).unwrap();
+
+    assert_eq!(loaded.files.len(), report.files.len());
+    assert_eq!(loaded.jewels["LethalPride"].seed_count, 3);
+    assert_eq!(loaded.total_duration, report.total_duration);
+    assert_eq!(loaded.warnings, report.warnings);
+}
+
+#[test]
+fn test_binary_cache_round_trip() {
+    let temp_dir = create_fixture_directory();
+    let (lut_data, _) = PobDataParser::parse_directory(temp_dir.path()).unwrap();
+
+    let cache_path = temp_dir.path().join("lut_data.bin");
+    PobDataParser::save_to_binary(&lut_data, &cache_path).unwrap();
+    let loaded = PobDataParser::load_artifact(&cache_path, "bincode").unwrap();
+
+    assert_eq!(loaded.version, lut_data.version);
+    assert_eq!(loaded.node_indices.len(), lut_data.node_indices.len());
+    assert_eq!(loaded.modifiers.len(), lut_data.modifiers.len());
+    assert_eq!(
+        loaded.jewels["LethalPride"].lookup_table,
+        lut_data.jewels["LethalPride"].lookup_table
+    );
+
+    // A truncated cache is an error, not a panic
+    std::fs::write(&cache_path, b"\x01\x02").unwrap();
+    assert!(PobDataParser::load_from_binary(&cache_path).is_err());
+    assert!(PobDataParser::load_artifact(&cache_path, "yaml").is_err());
+}
+
+#[test]
+fn test_binary_cache_header_verified() {
+    let temp_dir = create_fixture_directory();
+    let (lut_data, _) = PobDataParser::parse_directory(temp_dir.path()).unwrap();
+    let cache_path = temp_dir.path().join("lut_data.bin");
+    PobDataParser::save_to_binary(&lut_data, &cache_path).unwrap();
+
+    let header = PobDataParser::verify_binary(&cache_path).unwrap();
+    assert_eq!(header.generator_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(header.payload_sha256.len(), 64);
+
+    // Flip a byte of the payload
+    let mut bytes = std::fs::read(&cache_path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    std::fs::write(&cache_path, &bytes).unwrap();
+
+    let error = PobDataParser::load_from_binary(&cache_path).unwrap_err();
+    assert!(matches!(error, DownloadError::ChecksumMismatch { .. }));
+    match poe_item_analyzer_core::DataError::from(error) {
+        poe_item_analyzer_core::DataError::CorruptedData(message) => {
+            assert!(message.contains("lut_data.bin"), "{}", message)
+        }
+        other => panic!("Expected corrupted data, got {:?}", other),
+    }
+    assert!(PobDataParser::verify_binary(&cache_path).is_err());
+
+    // Caches from before the header, and from other versions
+    std::fs::write(&cache_path, bincode::serialize(&lut_data).unwrap()).unwrap();
+    let error = PobDataParser::load_from_binary(&cache_path).unwrap_err();
+    assert!(error.to_string().contains("not a signed LUT artifact"), "{}", error);
+
+    bytes[last] ^= 0xff;
+    bytes[8 + 32 + 1] = b'9';
+    std::fs::write(&cache_path, &bytes).unwrap();
+    let error = PobDataParser::load_from_binary(&cache_path).unwrap_err();
+    assert!(error.to_string().contains("written by version 9"), "{}", error);
+}
+
+#[test]
+fn test_save_error_names_the_file() {
+    let temp_dir = TempDir::new().unwrap();
+    // A path under a plain file can't be written, even by root
+    let blocker = temp_dir.path().join("read-only");
+    std::fs::write(&blocker, "").unwrap();
+    let report_path = blocker.join("lut_data.report.json");
+
+    let error = ParseReport::default().save_to_json(&report_path).unwrap_err();
+
+    match &error {
+        DownloadError::IoError { operation, path, .. } => {
+            assert_eq!(*operation, FileOperation::Write);
+            assert_eq!(path, &report_path);
+        }
+        other => panic!("Expected an I/O error, got {:?}", other),
+    }
+    let message = error.to_string();
+    assert!(message.starts_with("Could not write "), "{}", message);
+    assert!(message.contains(&report_path.display().to_string()), "{}", message);
+}
+
+#[test]
+fn test_trade_stat_table() {
+    assert_eq!(stat_template("+20 to Strength"), "+# to Strength");
+    assert_eq!(stat_template("0.4% of Life Regenerated"), "#% of Life Regenerated");
+    assert_eq!(stat_template("Onslaught"), "Onslaught");
+
+    let table = TradeStatTable::embedded();
+    assert_eq!(table.stat_id("+35 to maximum Life"), Some("explicit.stat_3299347043"));
+    assert_eq!(table.stat_id("5% chance to deal Double Damage"), Some("explicit.stat_1172810729"));
+    assert_eq!(table.stat_id("Gain Onslaught on Kill"), None);
+}
+
+#[test]
+fn test_build_statistics() {
+    use super::lut::{JewelLutData, LutEntry, NodeModifier};
+    use poe_item_analyzer_core::items::JewelType;
+    use std::collections::HashMap;
+
+    let mut lut_data = LutData {
+        version: "1.0.0".to_string(),
+        node_indices: HashMap::new(),
+        modifiers: HashMap::new(),
+        modifier_indices: HashMap::new(),
+        jewels: HashMap::new(),
+        trade_stat_ids: HashMap::new(),
+        tree_version: None,
+    };
+    lut_data.modifiers.insert(
+        "karui_str".to_string(),
+        NodeModifier {
+            id: "karui_str".to_string(),
+            display_name: "Strength of Blood".to_string(),
+            stat_descriptions: Vec::new(),
+            search_text: String::new(),
+            trade_stat_id: None,
+        },
+    );
+    lut_data.jewels.insert(
+        "LethalPride".to_string(),
+        JewelLutData {
+            jewel_type: "LethalPride".to_string(),
+            seed_range: (10000, 18000),
+            lookup_table: HashMap::from([
+                (10000, HashMap::from([(0, LutEntry::from("karui_str")), (1, LutEntry::from("karui_str"))])),
+                (10001, HashMap::from([(0, LutEntry::from("karui_str")), (1, LutEntry::from("karui_life"))])),
+                (10002, HashMap::from([(0, LutEntry::from("karui_life"))])),
+                (10003, HashMap::from([(0, LutEntry::from("karui_life"))])),
+            ]),
+            variants: HashMap::new(),
+            truncated: false,
+        },
+    );
+
+    let statistics = lut_data.build_statistics(JewelType::LethalPride);
+
+    assert_eq!(statistics.seed_count, 4);
+    assert_eq!(statistics.frequencies.len(), 2);
+    // Rarest first
+    let strength = &statistics.frequencies[0];
+    assert_eq!(strength.modifier_id, "karui_str");
+    assert_eq!(strength.display_name, "Strength of Blood");
+    assert_eq!((strength.seed_count, strength.node_count), (2, 3));
+    assert_eq!(strength.percentage, 50.0);
+    let life = statistics.get("karui_life").unwrap();
+    assert_eq!((life.seed_count, life.node_count), (3, 3));
+    assert_eq!(life.percentage, 75.0);
+    assert_eq!(statistics.by_name("Strength of Blood"), Some(strength));
+
+    let json = serde_json::to_string(&statistics).unwrap();
+    assert_eq!(serde_json::from_str::<JewelStatistics>(&json).unwrap(), statistics);
+
+    let empty = lut_data.build_statistics(JewelType::ElegantHubris);
+    assert_eq!(empty.seed_count, 0);
+    assert!(empty.frequencies.is_empty());
+}
+
+#[test]
+fn test_lut_entry_round_trip() {
+    use super::lut::{LutEntry, ModId, StatId};
+
+    let entries = vec![
+        LutEntry::ModifierRef(ModId::from("karui_str")),
+        LutEntry::GloriousVanity { stats: vec![(StatId(5), 10), (StatId(7), 3)].into() },
+    ];
+
+    let json = serde_json::to_string(&entries).unwrap();
+    assert_eq!(json, r#"["karui_str",[[5,10],[7,3]]]"#);
+    assert_eq!(serde_json::from_str::<Vec<LutEntry>>(&json).unwrap(), entries);
+
+    let binary = bincode::serialize(&entries).unwrap();
+    assert_eq!(bincode::deserialize::<Vec<LutEntry>>(&binary).unwrap(), entries);
+}
+
+#[test]
+fn test_lut_entry_legacy_strings() {
+    use super::lut::{JewelLutData, LutEntry, ModId, StatId};
+
+    // Lookup tables saved before entries were typed
+    let json = r#"{
+        "jewel_type": "GloriousVanity",
+        "seed_range": [100, 8000],
+        "lookup_table": {"100": {"0": "s5|s7|s9|r1|r2|r3", "1": "s4|r1|r2", "2": "12"}}
+    }"#;
+    let jewel_data: JewelLutData = serde_json::from_str(json).unwrap();
+    let nodes = &jewel_data.lookup_table[&100];
+
+    assert_eq!(
+        nodes[&0],
+        LutEntry::GloriousVanity {
+            stats: vec![(StatId(5), 1), (StatId(7), 2), (StatId(9), 3)].into()
+        }
+    );
+    // A stat with two rolls is listed once per roll
+    assert_eq!(
+        nodes[&1],
+        LutEntry::GloriousVanity { stats: vec![(StatId(4), 1), (StatId(4), 2)].into() }
+    );
+    assert_eq!(nodes[&2], LutEntry::ModifierRef(ModId::from("12")));
+
+    assert_eq!(LutEntry::from("karui_str"), LutEntry::ModifierRef(ModId::from("karui_str")));
+    assert_eq!(LutEntry::from("s5|x1"), LutEntry::ModifierRef(ModId::from("s5|x1")));
+}
+
+/// Glorious Vanity buffer where every `every`th cell has data, cycling
+/// through the 1+1, 1+2, 3+3 and 4+4 patterns
+fn glorious_vanity_buffer(seed_size: usize, every: usize) -> Vec<u8> {
+    use super::zip_parser::GV_NODE_COUNT;
+
+    let length = |node: usize, seed: usize| match (node + seed) % every {
+        0 => [2u8, 3, 6, 8][(node * 7 + seed) % 4],
+        _ => 0,
+    };
+
+    let mut buffer = Vec::with_capacity(GV_NODE_COUNT * seed_size * 2);
+    for node in 0..GV_NODE_COUNT {
+        buffer.extend((0..seed_size).map(|seed| length(node, seed)));
+    }
+    for seed in 0..seed_size {
+        for node in 0..GV_NODE_COUNT {
+            let start = buffer.len();
+            buffer.extend((0..length(node, seed)).map(|i| ((start + i as usize) % 251) as u8));
+        }
+    }
+    buffer
+}
+
+/// Glorious Vanity decoding as it was before entries were typed, for
+/// comparison
+fn parse_glorious_vanity_strings(
+    buffer: &[u8],
+    seed_size: usize,
+) -> std::collections::HashMap<u32, std::collections::HashMap<usize, String>> {
+    use super::zip_parser::GV_NODE_COUNT;
+    use std::collections::HashMap;
+
+    let (header, data) = buffer.split_at(GV_NODE_COUNT * seed_size);
+    let mut lookup_table = HashMap::new();
+    let mut data_offset = 0;
+
+    for seed_offset in 0..seed_size {
+        let mut node_modifiers = HashMap::new();
+        for node_index in 0..GV_NODE_COUNT {
+            let length = header[node_index * seed_size + seed_offset] as usize;
+            if length == 0 {
+                continue;
+            }
+
+            let node_data = &data[data_offset..data_offset + length];
+            let (num_stats, num_rolls) = match length {
+                2 => (1, 1),
+                3 => (1, 2),
+                6 => (3, 3),
+                _ => (4, 4),
+            };
+            let mut parts = Vec::new();
+            for stat in &node_data[..num_stats] {
+                parts.push(format!("s{}", stat));
+            }
+            for roll in &node_data[num_stats..num_stats + num_rolls] {
+                parts.push(format!("r{}", roll));
+            }
+            node_modifiers.insert(node_index, parts.join("|"));
+            data_offset += length;
+        }
+        if !node_modifiers.is_empty() {
+            lookup_table.insert(100 + seed_offset as u32, node_modifiers);
+        }
+    }
+    lookup_table
+}
+
+#[test]
+fn test_glorious_vanity_matches_string_decoding() {
+    use super::zip_parser::ZipParser;
+    use std::collections::HashMap;
+
+    let buffer = glorious_vanity_buffer(7, 3);
+    let mut warnings = Vec::new();
+    let table = ZipParser::parse_glorious_vanity(&buffer, (100, 106), &mut warnings);
+
+    let strings = parse_glorious_vanity_strings(&buffer, 7);
+    let expected: HashMap<u32, HashMap<usize, LutEntry>> = strings
+        .into_iter()
+        .map(|(seed, nodes)| {
+            let nodes = nodes
+                .iter()
+                .map(|(&node, text)| (node, LutEntry::from(text.as_str())))
+                .collect();
+            (seed, nodes)
+        })
+        .collect();
+
+    assert!(warnings.is_empty());
+    assert_eq!(table.len(), 7);
+    assert_eq!(table, expected);
+}
+
+#[test]
+fn test_glorious_vanity_truncated_and_oversized_data() {
+    use super::zip_parser::{ZipParser, GV_NODE_COUNT};
+
+    let buffer = glorious_vanity_buffer(7, 3);
+    let full = ZipParser::parse_glorious_vanity(&buffer, (100, 106), &mut Vec::new());
+
+    // The last seed's entries run past the end, so only the seeds before it are kept
+    let mut problems = Vec::new();
+    let truncated = &buffer[..buffer.len() - 1];
+    let table = ZipParser::parse_glorious_vanity(truncated, (100, 106), &mut problems);
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("exceeds buffer size") && problems[0].ends_with("seed 106"));
+    assert_eq!(table.len(), 6);
+    assert!(table.iter().all(|(seed, nodes)| full[seed] == *nodes));
+
+    // Data the header doesn't account for
+    let mut oversized = buffer.clone();
+    oversized.extend([1, 2, 3]);
+    let mut problems = Vec::new();
+    let table = ZipParser::parse_glorious_vanity(&oversized, (100, 106), &mut problems);
+    assert_eq!(problems, vec!["3 bytes of data left after the last entry"]);
+    assert_eq!(table, full);
+
+    // Not even the header is complete
+    let mut problems = Vec::new();
+    let short_header = &buffer[..GV_NODE_COUNT * 7 - 1];
+    let table = ZipParser::parse_glorious_vanity(short_header, (100, 106), &mut problems);
+    assert!(table.is_empty());
+    assert!(problems[0].contains("too small for Glorious Vanity header"));
+
+    // One seed, node 0 with 5 bytes of data and node 1 with 7
+    let mut odd = vec![0u8; GV_NODE_COUNT];
+    odd[0] = 5;
+    odd[1] = 7;
+    odd.extend(1..=12);
+    let mut problems = Vec::new();
+    let table = ZipParser::parse_glorious_vanity(&odd, (100, 100), &mut problems);
+    assert_eq!(problems, vec!["2 entries have a length matching no stat pattern (first: 5)"]);
+    assert_eq!(table[&100].len(), 2);
+}
+
+/// Run with `cargo test --release -p poe-item-analyzer-api -- --ignored`
+#[test]
+#[ignore = "timing test on a full-size table"]
+fn test_glorious_vanity_decoding_speed() {
+    use super::zip_parser::ZipParser;
+    use std::time::Instant;
+
+    let seed_size = 7901;
+    let buffer = glorious_vanity_buffer(seed_size, 4);
+
+    let started = Instant::now();
+    let strings = parse_glorious_vanity_strings(&buffer, seed_size);
+    let string_time = started.elapsed();
+
+    let started = Instant::now();
+    let table = ZipParser::parse_glorious_vanity(&buffer, (100, 8000), &mut Vec::new());
+    let typed_time = started.elapsed();
+
+    println!("strings: {:?}, typed: {:?}", string_time, typed_time);
+    assert_eq!(table.len(), strings.len());
+    assert!(typed_time * 3 <= string_time);
 }
@@ -0,0 +1,75 @@
+//! Trade stat ids of modifiers
+//!
+//! The trade API names stats by id (e.g., "explicit.stat_3299347043" for
+//! "+# to maximum Life"). The table shipped in `data/trade_stats.json` maps
+//! stat texts, with their numbers replaced by "#", to those ids.
+
+use std::collections::HashMap;
+
+use super::lut::LutData;
+
+pub use poe_item_analyzer_core::scoring::stat_template;
+
+/// Trade stat table built into the binary
+const EMBEDDED_TABLE: &str = include_str!("../../../../data/trade_stats.json");
+
+/// Stat text templates mapped to trade stat ids
+#[derive(Debug, Clone, Default)]
+pub struct TradeStatTable {
+    ids: HashMap<String, String>,
+}
+
+impl TradeStatTable {
+    /// The table shipped with the app
+    pub fn embedded() -> Self {
+        Self::from_json(EMBEDDED_TABLE).expect("embedded trade stat table is valid JSON")
+    }
+
+    /// Load a table of `{"+# to Strength": "explicit.stat_4080418644", ...}`
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            ids: serde_json::from_str(json)?,
+        })
+    }
+
+    /// Trade stat id of a stat text (e.g., "+20 to Strength")
+    pub fn stat_id(&self, text: &str) -> Option<&str> {
+        self.ids.get(&stat_template(text)).map(String::as_str)
+    }
+
+    /// Set the trade stat id of every modifier in `lut_data`
+    ///
+    /// A modifier takes the id of its first mapped stat, or of its name.
+    /// Returns the ids of the modifiers left without one, sorted; they stay
+    /// in the data but can't be searched for.
+    pub fn apply(&self, lut_data: &mut LutData) -> Vec<String> {
+        let mut unmapped = Vec::new();
+
+        for modifier in lut_data.modifiers.values_mut() {
+            for stat in &modifier.stat_descriptions {
+                if let Some(id) = self.stat_id(stat) {
+                    lut_data.trade_stat_ids.insert(stat.clone(), id.to_string());
+                }
+            }
+
+            modifier.trade_stat_id = modifier
+                .stat_descriptions
+                .iter()
+                .chain(std::iter::once(&modifier.display_name))
+                .find_map(|text| self.stat_id(text))
+                .map(str::to_string);
+
+            match &modifier.trade_stat_id {
+                Some(id) => {
+                    lut_data
+                        .trade_stat_ids
+                        .insert(modifier.display_name.clone(), id.clone());
+                }
+                None => unmapped.push(modifier.id.clone()),
+            }
+        }
+
+        unmapped.sort();
+        unmapped
+    }
+}
@@ -8,7 +8,7 @@
 //! - Data is a flat array of u8 values
 //! - Layout: `data[node_index * seed_range_size + (seed - min_seed)] = modifier_index`
 //! - modifier_index 0 = no change
-//! - modifier_index > 0 = maps to a modifier in LegionPassives.lua
+//! - modifier_index > 0 = index of a modifier in LegionPassives.lua's additions
 //!
 //! # Glorious Vanity Special Case
 //!
@@ -17,15 +17,39 @@
 //! - Variable-length data section with stat IDs and roll values
 //! - Format: All stats first, then all rolls (not interleaved)
 //! - Valid patterns: 1+1, 1+2, 3+3, or 4+4 (stats+rolls)
+//!
+//! # Malformed Data
+//!
+//! A truncated download still decompresses, so the layout is checked: the
+//! buffer must hold whole nodes, the Glorious Vanity data section must be
+//! exactly as long as its header says, and every entry must match a stat
+//! pattern. What happens otherwise depends on the [`ParseMode`].
 
-use crate::error::DownloadError;
+use crate::error::{file_name, DownloadError, FileContext, FileOperation};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
+use std::time::Instant;
 use flate2::read::ZlibDecoder;
+use tracing::debug;
+
+use super::lut::{JewelLutData, LutEntry, ModId};
+use super::report::{self, FileReport, JewelReport, ParseReport};
 
-use super::lut::JewelLutData;
+/// Glorious Vanity has fixed node count (1678 nodes)
+pub(super) const GV_NODE_COUNT: usize = 1678;
+
+/// How inconsistent jewel data is handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail with `DownloadError::MalformedData`
+    #[default]
+    Strict,
+
+    /// Keep the complete seeds, warn, and mark the table `truncated`
+    Lenient,
+}
 
 /// ZIP file parser for jewel LUT data
 pub struct ZipParser;
@@ -33,24 +57,29 @@ pub struct ZipParser;
 impl ZipParser {
     /// Extract and parse a jewel ZIP file
     ///
-    /// The "ZIP" files are actually zlib-compressed binary data, not ZIP archives
+    /// The "ZIP" files are actually zlib-compressed binary data, not ZIP archives.
+    /// File sizes, seed counts, timings and warnings are recorded in `report`.
     pub fn parse_jewel_zip(
         zip_path: &Path,
         jewel_type: &str,
+        mode: ParseMode,
+        report: &mut ParseReport,
     ) -> Result<JewelLutData, DownloadError> {
-        eprintln!("Parsing jewel file: {}", zip_path.display());
+        debug!("Parsing jewel file: {}", zip_path.display());
+
+        let started = Instant::now();
 
         // Open the file
-        let file = File::open(zip_path).map_err(|e| DownloadError::IoError(e))?;
+        let file = File::open(zip_path).file_context(FileOperation::Read, zip_path)?;
 
         // Decompress with zlib
         let mut decoder = ZlibDecoder::new(file);
         let mut decompressed_data = Vec::new();
         decoder
             .read_to_end(&mut decompressed_data)
-            .map_err(|e| DownloadError::DownloadFailed(format!("Failed to decompress: {}", e)))?;
+            .file_context(FileOperation::Decompress, zip_path)?;
 
-        eprintln!(
+        debug!(
             "Decompressed {} bytes from {}",
             decompressed_data.len(),
             zip_path.file_name().unwrap().to_string_lossy()
@@ -60,16 +89,56 @@ impl ZipParser {
         let seed_range = Self::get_seed_range(jewel_type);
 
         // Parse the binary LUT data based on jewel type
-        let lookup_table = if jewel_type == "GloriousVanity" {
-            Self::parse_glorious_vanity(&decompressed_data, seed_range)?
+        let mut problems = Vec::new();
+        let (lookup_table, node_count) = if jewel_type == "GloriousVanity" {
+            let table = Self::parse_glorious_vanity(&decompressed_data, seed_range, &mut problems);
+            (table, GV_NODE_COUNT)
         } else {
-            Self::parse_binary_data(&decompressed_data, seed_range)?
+            let table = Self::parse_binary_data(&decompressed_data, seed_range, &mut problems);
+            let seed_size = (seed_range.1 - seed_range.0 + 1) as usize;
+            (table, decompressed_data.len() / seed_size)
         };
 
+        let truncated = !problems.is_empty();
+        if truncated {
+            match mode {
+                ParseMode::Strict => {
+                    return Err(DownloadError::MalformedData {
+                        file: file_name(zip_path),
+                        reason: problems.join("; "),
+                        salvaged_seeds: lookup_table.len(),
+                        salvaged_entries: lookup_table.values().map(HashMap::len).sum(),
+                    });
+                }
+                ParseMode::Lenient => {
+                    for problem in problems {
+                        report.warn(format!("{}: {}", file_name(zip_path), problem));
+                    }
+                }
+            }
+        }
+
+        report.files.push(FileReport {
+            name: file_name(zip_path),
+            bytes: report::file_size(zip_path),
+            decompressed_bytes: Some(decompressed_data.len() as u64),
+            duration: started.elapsed(),
+        });
+
+        report.jewels.insert(
+            jewel_type.to_string(),
+            JewelReport {
+                seed_count: lookup_table.len(),
+                node_count,
+            },
+        );
+
         Ok(JewelLutData {
             jewel_type: jewel_type.to_string(),
             seed_range,
             lookup_table,
+            variants: HashMap::new(),
+            truncated,
         })
     }
 
@@ -91,18 +160,22 @@ impl ZipParser {
     /// - Array of bytes representing modifier indices
     /// - Formula: array[node_index * seed_range_size + (seed - min_seed)] = modifier_index
     /// - Where modifier_index 0 means "no change"
-    /// - Non-zero modifier_index maps to a modifier ID (string representation)
-    fn parse_binary_data(
+    /// - Non-zero modifier_index becomes a `LutEntry::ModifierRef`
+    ///
+    /// A trailing partial node is left out and recorded in `problems`.
+    pub(super) fn parse_binary_data(
         buffer: &[u8],
         seed_range: (u32, u32),
-    ) -> Result<HashMap<u32, HashMap<usize, String>>, DownloadError> {
-        let mut lookup_table: HashMap<u32, HashMap<usize, String>> = HashMap::new();
+        problems: &mut Vec<String>,
+    ) -> HashMap<u32, HashMap<usize, LutEntry>> {
+        let mut lookup_table: HashMap<u32, HashMap<usize, LutEntry>> = HashMap::new();
 
         if buffer.is_empty() {
-            return Ok(lookup_table);
+            problems.push("no data".to_string());
+            return lookup_table;
         }
 
-        eprintln!(
+        debug!(
             "Parsing {} bytes (seed range: {:?})",
             buffer.len(),
             seed_range
@@ -119,17 +192,17 @@ impl ZipParser {
         //     modifier_index: u8
         //
         // To determine number of nodes: buffer.len() / seed_size
-        if buffer.len() % seed_size != 0 {
-            eprintln!(
-                "Warning: Buffer size {} is not evenly divisible by seed_size {}",
+        if !buffer.len().is_multiple_of(seed_size) {
+            problems.push(format!(
+                "Buffer size {} is not evenly divisible by seed_size {}",
                 buffer.len(),
                 seed_size
-            );
+            ));
         }
 
         let num_nodes = buffer.len() / seed_size;
 
-        eprintln!(
+        debug!(
             "Detected {} nodes with {} seeds each",
             num_nodes, seed_size
         );
@@ -138,25 +211,18 @@ impl ZipParser {
         // We iterate by seed to build the lookup table structure: seed -> node_index -> modifier
         for seed_offset in 0..seed_size {
             let seed = min_seed + seed_offset as u32;
-            let mut node_modifiers: HashMap<usize, String> = HashMap::new();
+            let mut node_modifiers: HashMap<usize, LutEntry> = HashMap::new();
 
             for node_index in 0..num_nodes {
                 let byte_offset = node_index * seed_size + seed_offset;
-
-                if byte_offset >= buffer.len() {
-                    break;
-                }
-
                 let modifier_index = buffer[byte_offset];
 
                 // modifier_index 0 typically means "no change" - we skip these
                 if modifier_index != 0 {
-                    // Convert modifier index to string ID
-                    // The modifier index maps to entries in LegionPassives.lua
-                    // For now, use the index as a string; this will be resolved
-                    // against the actual modifier data later
-                    let modifier_id = modifier_index.to_string();
-                    node_modifiers.insert(node_index, modifier_id);
+                    // The index into LegionPassives.lua's additions, replaced
+                    // with the modifier's ID by `LutData::resolve_modifier_indices`
+                    let modifier_id = ModId(modifier_index.to_string());
+                    node_modifiers.insert(node_index, LutEntry::ModifierRef(modifier_id));
                 }
             }
 
@@ -166,12 +232,12 @@ impl ZipParser {
             }
         }
 
-        eprintln!(
+        debug!(
             "Parsed {} seeds with modifier data",
             lookup_table.len()
         );
 
-        Ok(lookup_table)
+        lookup_table
     }
 
     /// Parse Glorious Vanity binary data (special format with header)
@@ -182,17 +248,26 @@ impl ZipParser {
     ///
     /// Format: All stats first, then all rolls (not interleaved)
     /// Valid patterns: 1+1, 1+2, 3+3, or 4+4 (stats+rolls)
-    fn parse_glorious_vanity(
+    ///
+    /// A full table has tens of millions of cells, so cells are decoded
+    /// straight into `LutEntry` values without per-cell allocations.
+    ///
+    /// A short header or data section, leftover data and entries matching no
+    /// pattern are recorded in `problems`. Only the seeds read completely
+    /// before the data ran out are kept.
+    pub(super) fn parse_glorious_vanity(
         buffer: &[u8],
         seed_range: (u32, u32),
-    ) -> Result<HashMap<u32, HashMap<usize, String>>, DownloadError> {
-        let mut lookup_table: HashMap<u32, HashMap<usize, String>> = HashMap::new();
+        problems: &mut Vec<String>,
+    ) -> HashMap<u32, HashMap<usize, LutEntry>> {
+        let mut lookup_table: HashMap<u32, HashMap<usize, LutEntry>> = HashMap::new();
 
         if buffer.is_empty() {
-            return Ok(lookup_table);
+            problems.push("no data".to_string());
+            return lookup_table;
         }
 
-        eprintln!(
+        debug!(
             "Parsing Glorious Vanity: {} bytes (seed range: {:?})",
             buffer.len(),
             seed_range
@@ -202,25 +277,23 @@ impl ZipParser {
         let max_seed = seed_range.1;
         let seed_size = (max_seed - min_seed + 1) as usize;
 
-        // Glorious Vanity has fixed node count (1678 nodes)
-        const GV_NODE_COUNT: usize = 1678;
-
         // Header size: nodeCount × seedRange
         let header_size = GV_NODE_COUNT * seed_size;
 
         if buffer.len() < header_size {
-            return Err(DownloadError::DownloadFailed(format!(
+            problems.push(format!(
                 "Buffer too small for Glorious Vanity header: {} < {}",
                 buffer.len(),
                 header_size
-            )));
+            ));
+            return lookup_table;
         }
 
         // Split buffer into header and data sections
         let header = &buffer[0..header_size];
         let data = &buffer[header_size..];
 
-        eprintln!(
+        debug!(
             "Header: {} bytes ({} nodes × {} seeds), Data: {} bytes",
             header_size,
             GV_NODE_COUNT,
@@ -228,105 +301,135 @@ impl ZipParser {
             data.len()
         );
 
+        // The header is laid out node by node but the data seed by seed, so
+        // transpose it once to read each seed's lengths as one slice
+        let lengths = transpose(header, GV_NODE_COUNT, seed_size);
+
         // Parse data section using header as index
         let mut data_offset = 0;
+        let mut ran_out = false;
+        let mut unknown_lengths = UnknownLengths::default();
+        let mut node_modifiers: Vec<(usize, LutEntry)> = Vec::with_capacity(GV_NODE_COUNT);
+        lookup_table.reserve(seed_size);
 
-        for seed_offset in 0..seed_size {
+        'seeds: for (seed_offset, seed_lengths) in lengths.chunks_exact(GV_NODE_COUNT).enumerate()
+        {
             let seed = min_seed + seed_offset as u32;
-            let mut node_modifiers: HashMap<usize, String> = HashMap::new();
-
-            for node_index in 0..GV_NODE_COUNT {
-                // Get data length from header
-                let header_index = node_index * seed_size + seed_offset;
-                let data_length = header[header_index] as usize;
-
-                if data_length > 0 {
-                    // Extract the data bytes for this node/seed
-                    if data_offset + data_length > data.len() {
-                        eprintln!(
-                            "Warning: Data offset {} + length {} exceeds buffer size {}",
-                            data_offset,
-                            data_length,
-                            data.len()
-                        );
-                        break;
-                    }
 
-                    let node_data = &data[data_offset..data_offset + data_length];
-
-                    // Parse the variable-length data
-                    // Format: [stat1, stat2, ...] [roll1, roll2, ...]
-                    // Valid patterns: 1+1, 1+2, 3+3, or 4+4
-                    let modifier_str = Self::parse_gv_node_data(node_data, data_length);
-
-                    if !modifier_str.is_empty() {
-                        node_modifiers.insert(node_index, modifier_str);
-                    }
-
-                    data_offset += data_length;
+            for (node_index, &data_length) in seed_lengths.iter().enumerate() {
+                if data_length == 0 {
+                    continue;
                 }
+
+                // Extract the data bytes for this node/seed
+                let data_length = data_length as usize;
+                let Some(node_data) = data.get(data_offset..data_offset + data_length) else {
+                    // The seed is incomplete, and every later entry would be
+                    // read from the wrong offset
+                    problems.push(format!(
+                        "Data offset {} + length {} exceeds buffer size {} at seed {}",
+                        data_offset,
+                        data_length,
+                        data.len(),
+                        seed
+                    ));
+                    ran_out = true;
+                    break 'seeds;
+                };
+
+                let entry = Self::parse_gv_node_data(node_data, &mut unknown_lengths);
+                node_modifiers.push((node_index, entry));
+
+                data_offset += data_length;
             }
 
             // Only store seeds that have modifiers
             if !node_modifiers.is_empty() {
-                lookup_table.insert(seed, node_modifiers);
+                lookup_table.insert(seed, node_modifiers.drain(..).collect());
             }
         }
 
-        eprintln!(
+        if !ran_out && data_offset < data.len() {
+            problems.push(format!(
+                "{} bytes of data left after the last entry",
+                data.len() - data_offset
+            ));
+        }
+        if let Some(length) = unknown_lengths.first {
+            problems.push(format!(
+                "{} entries have a length matching no stat pattern (first: {})",
+                unknown_lengths.count, length
+            ));
+        }
+
+        debug!(
             "Parsed {} Glorious Vanity seeds with data",
             lookup_table.len()
         );
 
-        Ok(lookup_table)
+        lookup_table
     }
 
     /// Parse Glorious Vanity node data (variable-length byte array)
     ///
-    /// Returns a string representation of the stats and rolls
-    fn parse_gv_node_data(data: &[u8], length: usize) -> String {
-        if length == 0 {
-            return String::new();
-        }
+    /// Returns the stats paired with their rolls. Lengths matching no
+    /// pattern are counted in `unknown_lengths`.
+    fn parse_gv_node_data(data: &[u8], unknown_lengths: &mut UnknownLengths) -> LutEntry {
+        let length = data.len();
 
         // Determine pattern based on length
         // 1+1 = 2 bytes, 1+2 = 3 bytes, 3+3 = 6 bytes, 4+4 = 8 bytes
-        let (num_stats, num_rolls) = match length {
-            2 => (1, 1),
-            3 => (1, 2),
-            6 => (3, 3),
-            8 => (4, 4),
+        let num_stats = match length {
+            2 | 3 => 1,
+            6 => 3,
+            8 => 4,
             _ => {
-                eprintln!("Warning: Unexpected GV data length: {}", length);
-                // Try to infer from length (assume equal stats and rolls)
-                if length % 2 == 0 {
-                    let half = length / 2;
-                    (half, half)
+                unknown_lengths.count += 1;
+                unknown_lengths.first.get_or_insert(length);
+                // Try to infer from length (assume equal stats and rolls);
+                // odd lengths might be 1 stat with multiple rolls
+                if length.is_multiple_of(2) {
+                    length / 2
                 } else {
-                    // Odd length, might be 1 stat with multiple rolls
-                    (1, length - 1)
+                    1
                 }
             }
         };
 
-        // Extract stats and rolls
-        let mut parts = Vec::new();
+        // Stats come first, rolls after them
+        let (stats, rolls) = data.split_at(num_stats);
+        LutEntry::glorious_vanity(stats, rolls)
+    }
+}
 
-        // Stats come first
-        for i in 0..num_stats {
-            if i < data.len() {
-                parts.push(format!("s{}", data[i]));
-            }
-        }
+/// Glorious Vanity entries whose length matches no stat pattern
+///
+/// Garbage data has one in most cells, so they are counted, not listed.
+#[derive(Default)]
+struct UnknownLengths {
+    count: usize,
+    first: Option<usize>,
+}
 
-        // Rolls come after stats
-        for i in 0..num_rolls {
-            let idx = num_stats + i;
-            if idx < data.len() {
-                parts.push(format!("r{}", data[idx]));
+/// Transpose a `rows` × `columns` byte matrix
+///
+/// Works in square tiles so both sides stay in cache.
+fn transpose(matrix: &[u8], rows: usize, columns: usize) -> Vec<u8> {
+    const TILE: usize = 64;
+    let mut transposed = vec![0u8; matrix.len()];
+
+    for row_start in (0..rows).step_by(TILE) {
+        let row_end = (row_start + TILE).min(rows);
+        for column_start in (0..columns).step_by(TILE) {
+            let column_end = (column_start + TILE).min(columns);
+            for row in row_start..row_end {
+                let source = &matrix[row * columns..(row + 1) * columns];
+                for column in column_start..column_end {
+                    transposed[column * rows + row] = source[column];
+                }
             }
         }
-
-        parts.join("|")
     }
+
+    transposed
 }
@@ -0,0 +1,305 @@
+//! Path of Building build codes
+//!
+//! A build code is the build's XML, zlib-compressed and base64-encoded.
+//! Path of Building and pobb.in use the URL-safe alphabet ("-" and "_")
+//! without padding; other tools pass the standard alphabet through, so
+//! both are accepted. The build gives a starter `TimelessJewelConfig`:
+//! its allocated passives, plus weights for its main attributes.
+
+use std::collections::HashSet;
+use std::io::Read;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::ZlibDecoder;
+use poe_item_analyzer_core::analyzers::TimelessJewelConfig;
+
+use crate::error::BuildCodeError;
+
+/// Classes by the tree's `classId`
+const CLASSES: [&str; 7] = [
+    "Scion", "Marauder", "Ranger", "Witch", "Duelist", "Templar", "Shadow",
+];
+
+/// Attribute lines timeless jewels grant, weighted by their size
+const ATTRIBUTE_MODS: [(u32, f64); 4] = [(2, 0.2), (4, 0.4), (10, 1.0), (20, 2.0)];
+
+/// Share of the highest attribute another attribute needs to count as main
+const MAIN_ATTRIBUTE_SHARE: f64 = 0.75;
+
+/// A character attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    Strength,
+    Dexterity,
+    Intelligence,
+}
+
+impl Attribute {
+    /// Name as used in mod texts
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Attribute::Strength => "Strength",
+            Attribute::Dexterity => "Dexterity",
+            Attribute::Intelligence => "Intelligence",
+        }
+    }
+
+    /// Attributes a class starts next to on the tree
+    pub fn of_class(class_name: &str) -> &'static [Attribute] {
+        use Attribute::*;
+        match class_name {
+            "Marauder" => &[Strength],
+            "Ranger" => &[Dexterity],
+            "Witch" => &[Intelligence],
+            "Duelist" => &[Strength, Dexterity],
+            "Templar" => &[Strength, Intelligence],
+            "Shadow" => &[Dexterity, Intelligence],
+            _ => &[Strength, Dexterity, Intelligence],
+        }
+    }
+}
+
+/// What a build code tells about the character
+#[derive(Debug, Clone, PartialEq)]
+pub struct PobBuild {
+    /// Class (e.g., "Duelist")
+    pub class_name: String,
+
+    /// Ascendancy, if one was chosen
+    pub ascendancy: Option<String>,
+
+    /// Character level
+    pub level: Option<u32>,
+
+    /// Passive nodes allocated in the active tree
+    pub allocated_nodes: HashSet<u32>,
+
+    /// Passive tree version of the active tree (e.g., "3_25")
+    pub tree_version: Option<String>,
+
+    /// Strength, Dexterity and Intelligence as Path of Building computed
+    /// them, when the build was saved with its stats
+    pub attributes: Option<[f64; 3]>,
+}
+
+impl PobBuild {
+    /// Read a pasted build code
+    pub fn from_code(code: &str) -> Result<Self, BuildCodeError> {
+        Self::from_xml(&decode_build_code(code)?)
+    }
+
+    /// Read a build's XML
+    pub fn from_xml(xml: &str) -> Result<Self, BuildCodeError> {
+        let document = roxmltree::Document::parse(xml)
+            .map_err(|e| BuildCodeError::InvalidXml(e.to_string()))?;
+        let root = document.root_element();
+
+        let build = child(root, "Build").ok_or(BuildCodeError::Missing("Build element"))?;
+        let spec = active_spec(root).ok_or(BuildCodeError::Missing("passive tree"))?;
+
+        let class_name = build
+            .attribute("className")
+            .map(str::to_string)
+            .or_else(|| {
+                let class_id: usize = spec.attribute("classId")?.parse().ok()?;
+                CLASSES.get(class_id).map(|name| name.to_string())
+            })
+            .ok_or(BuildCodeError::Missing("class"))?;
+        let ascendancy = build
+            .attribute("ascendClassName")
+            .filter(|name| !name.is_empty() && *name != "None")
+            .map(str::to_string);
+        let level = build.attribute("level").and_then(|level| level.parse().ok());
+        let tree_version = spec.attribute("treeVersion").map(str::to_string);
+
+        let allocated_nodes = spec
+            .attribute("nodes")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|node| !node.trim().is_empty())
+            .map(|node| {
+                node.trim()
+                    .parse()
+                    .map_err(|_| BuildCodeError::InvalidXml(format!("invalid node id '{}'", node)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let stat = |name: &str| {
+            build
+                .children()
+                .filter(|node| node.has_tag_name("PlayerStat"))
+                .find(|node| node.attribute("stat") == Some(name))
+                .and_then(|node| node.attribute("value")?.parse::<f64>().ok())
+        };
+        let attributes = match (stat("Str"), stat("Dex"), stat("Int")) {
+            (Some(str), Some(dex), Some(int)) => Some([str, dex, int]),
+            _ => None,
+        };
+
+        Ok(Self {
+            class_name,
+            ascendancy,
+            level,
+            allocated_nodes,
+            tree_version,
+            attributes,
+        })
+    }
+
+    /// Attributes the build stacks
+    ///
+    /// Those within 75% of the highest, when the build has its stats;
+    /// otherwise the attributes of its class.
+    pub fn main_attributes(&self) -> Vec<Attribute> {
+        let Some(values) = self.attributes else {
+            return Attribute::of_class(&self.class_name).to_vec();
+        };
+
+        let highest = values.iter().copied().fold(0.0, f64::max);
+        [Attribute::Strength, Attribute::Dexterity, Attribute::Intelligence]
+            .into_iter()
+            .zip(values)
+            .filter(|(_, value)| highest > 0.0 && *value >= highest * MAIN_ATTRIBUTE_SHARE)
+            .map(|(attribute, _)| attribute)
+            .collect()
+    }
+
+    /// Starter config: the allocated passives (with their tree version) and
+    /// weights for the main attributes' mods
+    pub fn to_config(&self) -> TimelessJewelConfig {
+        let mut config =
+            TimelessJewelConfig::new().with_allocated_nodes(self.allocated_nodes.clone());
+        if let Some(version) = &self.tree_version {
+            config = config.with_tree_version(version.clone());
+        }
+
+        for attribute in self.main_attributes() {
+            for (value, weight) in ATTRIBUTE_MODS {
+                config.add_mod(format!("+{} to {}", value, attribute.as_str()), weight);
+            }
+        }
+
+        config
+    }
+}
+
+/// The XML inside a build code
+pub fn decode_build_code(code: &str) -> Result<String, BuildCodeError> {
+    // Fold the standard alphabet into the URL-safe one and drop the padding
+    let normalized: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            c => c,
+        })
+        .collect();
+    if normalized.starts_with("http") {
+        return Err(BuildCodeError::InvalidEncoding(
+            "this is a link; paste the build code it shows instead".to_string(),
+        ));
+    }
+
+    let compressed = URL_SAFE_NO_PAD
+        .decode(normalized)
+        .map_err(|e| BuildCodeError::InvalidEncoding(e.to_string()))?;
+
+    let mut xml = String::new();
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_string(&mut xml)
+        .map_err(BuildCodeError::Decompress)?;
+    Ok(xml)
+}
+
+/// First child element of `node` named `name`
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+/// The tree spec the build has selected (`activeSpec` counts from 1)
+fn active_spec<'a, 'input>(
+    root: roxmltree::Node<'a, 'input>,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    let tree = child(root, "Tree")?;
+    let mut specs = tree.children().filter(|node| node.has_tag_name("Spec"));
+    let active = tree
+        .attribute("activeSpec")
+        .and_then(|index| index.parse::<usize>().ok())
+        .unwrap_or(1);
+
+    specs.clone().nth(active.saturating_sub(1)).or_else(|| specs.next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const XML: &str = r#"<PathOfBuilding>
+        <Build level="90" className="Witch" ascendClassName="None"/>
+        <Tree><Spec classId="3" nodes="1,2"/></Tree>
+    </PathOfBuilding>"#;
+
+    fn compress(xml: &str) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(xml.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_decodes_both_alphabets() {
+        let compressed = compress(XML);
+        let url_safe = URL_SAFE_NO_PAD.encode(&compressed);
+        let standard = STANDARD.encode(&compressed);
+
+        assert_eq!(decode_build_code(&url_safe).unwrap(), XML);
+        assert_eq!(decode_build_code(&format!("  {}\n", standard)).unwrap(), XML);
+    }
+
+    #[test]
+    fn test_rejects_links_and_garbage() {
+        assert!(matches!(
+            decode_build_code("https://pobb.in/abc123"),
+            Err(BuildCodeError::InvalidEncoding(_))
+        ));
+        assert!(matches!(decode_build_code("not*base64"), Err(BuildCodeError::InvalidEncoding(_))));
+        assert!(matches!(decode_build_code("AAAA"), Err(BuildCodeError::Decompress(_))));
+    }
+
+    #[test]
+    fn test_class_fallback_without_stats() {
+        let build = PobBuild::from_xml(XML).unwrap();
+
+        assert_eq!(build.ascendancy, None);
+        assert_eq!(build.attributes, None);
+        assert_eq!(build.main_attributes(), vec![Attribute::Intelligence]);
+        assert_eq!(
+            build.to_config().valuable_mods().get("+20 to Intelligence"),
+            Some(&2.0)
+        );
+    }
+
+    #[test]
+    fn test_tree_version_reaches_config() {
+        assert_eq!(PobBuild::from_xml(XML).unwrap().to_config().tree_version, None);
+
+        let xml = XML.replace("<Spec ", "<Spec treeVersion=\"3_25\" ");
+        let build = PobBuild::from_xml(&xml).unwrap();
+        assert_eq!(build.tree_version.as_deref(), Some("3_25"));
+        assert_eq!(build.to_config().tree_version.as_deref(), Some("3_25"));
+    }
+
+    #[test]
+    fn test_missing_tree() {
+        let error = PobBuild::from_xml("<PathOfBuilding><Build/></PathOfBuilding>").unwrap_err();
+        assert_eq!(error.to_string(), "Build has no passive tree");
+    }
+}
@@ -0,0 +1,83 @@
+//! Character API endpoints
+
+use super::client::PoeApiClient;
+use super::models::CharacterPassives;
+use crate::error::ApiError;
+
+/// Path of the character-window passive skills endpoint
+const PASSIVE_SKILLS_PATH: &str = "/character-window/get-passive-skills";
+
+impl PoeApiClient {
+    /// Get the allocated passive tree and socketed jewels of `character`
+    ///
+    /// Only works for public profiles; a private one returns
+    /// `ApiError::Unauthorized` explaining how to make it public.
+    pub async fn get_character_passives(
+        &self,
+        account: &str,
+        character: &str,
+    ) -> Result<CharacterPassives, ApiError> {
+        let query = [
+            ("accountName", account),
+            ("character", character),
+            ("realm", "pc"),
+        ];
+
+        let response = match self.get(PASSIVE_SKILLS_PATH, &query, None).await {
+            Err(ApiError::Unauthorized(_)) => {
+                return Err(ApiError::Unauthorized(format!(
+                    "The characters of {} are private; untick \"Hide Characters\" in the \
+                     pathofexile.com privacy settings to load passives",
+                    account
+                )))
+            }
+            result => result?,
+        };
+
+        response
+            .json()
+            .await
+            .map_err(|e| ApiError::InvalidResponse(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passives() -> CharacterPassives {
+        serde_json::from_str(include_str!("../../tests/fixtures/passive_skills.json")).unwrap()
+    }
+
+    #[test]
+    fn test_allocated_nodes() {
+        let nodes = passives().allocated_nodes();
+
+        assert_eq!(nodes.len(), 8);
+        assert!(nodes.contains(&26725));
+        assert!(nodes.contains(&61834));
+    }
+
+    #[test]
+    fn test_timeless_jewel_socket() {
+        let passives = passives();
+
+        let sockets: Vec<_> = passives
+            .jewel_sockets()
+            .into_iter()
+            .map(|(slot, jewel)| (slot, jewel.name.as_str()))
+            .collect();
+        assert_eq!(sockets, vec![(3, "Watcher's Eye"), (12, "Lethal Pride")]);
+
+        assert_eq!(passives.timeless_jewel_socket(), Some(12));
+    }
+
+    #[test]
+    fn test_no_timeless_jewel() {
+        let passives: CharacterPassives =
+            serde_json::from_str(r#"{"hashes": [1, 2], "items": []}"#).unwrap();
+
+        assert!(passives.timeless_jewel_socket().is_none());
+        assert!(passives.hashes_ex.is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+//! HTTP client for the pathofexile.com API
+
+use reqwest::header::{HeaderValue, COOKIE, RETRY_AFTER};
+
+use super::rate_limit::{RateLimitState, RateLimiter};
+use crate::error::ApiError;
+use crate::http_config::HttpConfig;
+use crate::http_util::{send_with_retry_hooked, RetryPolicy};
+
+/// Default base URL of the Path of Exile website
+pub const POE_API_URL: &str = "https://www.pathofexile.com";
+
+/// User-Agent sent with every request, as GGG asks API users to identify themselves
+const USER_AGENT: &str = "poe-item-analyzer/0.1.0";
+
+/// Path of Exile API client
+///
+/// Session ids are passed per request and only ever sent as a sensitive
+/// cookie header, so they never end up in `Debug` output or error messages.
+///
+/// Requests go through a [`RateLimiter`] fed from the API's rate limit
+/// headers, so a burst of calls is spaced out instead of getting restricted.
+#[derive(Debug)]
+pub struct PoeApiClient {
+    client: reqwest::Client,
+    base_url: String,
+    limiter: RateLimiter,
+    retry_policy: RetryPolicy,
+}
+
+/// Client for the PoE API with `config`'s settings
+fn build_client(config: &HttpConfig) -> Result<reqwest::Client, reqwest::Error> {
+    config
+        .client_builder()?
+        .user_agent(USER_AGENT)
+        .timeout(config.read_timeout)
+        .build()
+}
+
+impl PoeApiClient {
+    /// Create a new client for pathofexile.com
+    pub fn new() -> Self {
+        Self {
+            client: build_client(&HttpConfig::default()).expect("Failed to build HTTP client"),
+            base_url: POE_API_URL.to_string(),
+            limiter: RateLimiter::new(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Use a different base URL (e.g., a test server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Use the timeouts, pooling and proxy of `config`
+    ///
+    /// Fails if the proxy URL is invalid.
+    pub fn with_http_config(mut self, config: &HttpConfig) -> Result<Self, ApiError> {
+        self.client = build_client(config)?;
+        Ok(self)
+    }
+
+    /// Set the retry policy for transient failures
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Base URL requests are sent to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Current rate limit usage, e.g. to show why a request is waiting
+    pub fn rate_limit_state(&self) -> RateLimitState {
+        self.limiter.state()
+    }
+
+    /// Send a GET request to `path`, mapping error statuses
+    ///
+    /// With a `session_id` the request is authenticated with the `POESESSID`
+    /// cookie. Every attempt, retries included, waits for the rate limiter
+    /// first; after a 429 the next one waits until `Retry-After` has passed.
+    pub(crate) async fn get(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        session_id: Option<&str>,
+    ) -> Result<reqwest::Response, ApiError> {
+        let request = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .query(query);
+
+        self.send(request, session_id).await
+    }
+
+    /// Send a POST request with a JSON `body` to `path`, like [`Self::get`]
+    pub(crate) async fn post_json(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+        session_id: Option<&str>,
+    ) -> Result<reqwest::Response, ApiError> {
+        let request = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(body);
+
+        self.send(request, session_id).await
+    }
+
+    async fn send(
+        &self,
+        mut request: reqwest::RequestBuilder,
+        session_id: Option<&str>,
+    ) -> Result<reqwest::Response, ApiError> {
+        if let Some(session_id) = session_id {
+            let mut cookie = HeaderValue::from_str(&format!("POESESSID={}", session_id))
+                .map_err(|_| ApiError::Unauthorized("Malformed session id".to_string()))?;
+            cookie.set_sensitive(true);
+            request = request.header(COOKIE, cookie);
+        }
+
+        let response = send_with_retry_hooked(
+            &self.client,
+            request,
+            &self.retry_policy,
+            || self.limiter.acquire(),
+            |response| self.limiter.update_from_headers(response.headers()),
+        )
+        .await
+        .map_err(ApiError::RequestFailed)?;
+
+        let code = response.status();
+        if code.is_success() {
+            return Ok(response);
+        }
+
+        match code {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                let message = if session_id.is_some() {
+                    "Path of Exile rejected the session id (expired, or the stash is private)"
+                } else {
+                    "Path of Exile requires a session id for this request"
+                };
+                Err(ApiError::Unauthorized(message.to_string()))
+            }
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse::<i64>().ok());
+
+                let reset_at =
+                    retry_after.map(|secs| chrono::Utc::now() + chrono::Duration::seconds(secs));
+                let message = match retry_after {
+                    Some(secs) => format!(
+                        "Path of Exile API rate limit exceeded, retry in {}s",
+                        secs
+                    ),
+                    None => "Path of Exile API rate limit exceeded".to_string(),
+                };
+
+                Err(ApiError::RateLimited { message, reset_at })
+            }
+            _ => Err(ApiError::ApiError(format!(
+                "Path of Exile API error: {}",
+                code
+            ))),
+        }
+    }
+}
+
+impl Default for PoeApiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
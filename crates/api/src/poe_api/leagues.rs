@@ -1,3 +1,103 @@
 //! League API endpoints
 
-// TODO: Implement league fetching
+use chrono::{DateTime, Utc};
+
+use super::client::PoeApiClient;
+use super::models::League;
+use crate::error::ApiError;
+
+/// Path of the public leagues endpoint
+const LEAGUES_PATH: &str = "/api/leagues";
+
+impl PoeApiClient {
+    /// List the main PC leagues (no session needed)
+    pub async fn get_leagues(&self) -> Result<Vec<League>, ApiError> {
+        let query = [("type", "main"), ("realm", "pc")];
+        let response = self.get(LEAGUES_PATH, &query, None).await?;
+
+        response
+            .json()
+            .await
+            .map_err(|e| ApiError::InvalidResponse(e.to_string()))
+    }
+}
+
+/// Pick the current softcore trade challenge league
+///
+/// Skips Standard, Hardcore, SSF and Ruthless variants and leagues that
+/// have not started or already ended. An open `endAt` counts as running.
+/// If several leagues qualify, the most recently started wins.
+pub fn current_challenge_league(leagues: &[League], now: DateTime<Utc>) -> Option<&League> {
+    leagues
+        .iter()
+        .filter(|league| {
+            !league.is_standard()
+                && !league.is_hardcore()
+                && !league.is_solo_self_found()
+                && !league.is_ruthless()
+                && league.is_active_at(now)
+        })
+        .max_by_key(|league| league.start_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn leagues() -> Vec<League> {
+        serde_json::from_str(include_str!("../../tests/fixtures/leagues.json")).unwrap()
+    }
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_leagues() {
+        let leagues = leagues();
+
+        let standard = &leagues[0];
+        assert!(standard.is_standard());
+        assert_eq!(
+            standard.start_at,
+            Some(Utc.with_ymd_and_hms(2013, 1, 23, 21, 0, 0).unwrap())
+        );
+        assert_eq!(standard.end_at, None);
+
+        assert!(leagues[1].is_hardcore());
+        assert!(leagues.iter().any(|l| l.id == "SSF Settlers" && l.is_solo_self_found()));
+        assert!(leagues.iter().any(|l| l.id == "Ruthless Settlers" && l.is_ruthless()));
+    }
+
+    #[test]
+    fn test_current_challenge_league() {
+        let leagues = leagues();
+
+        let league = current_challenge_league(&leagues, at(2024, 9, 1)).unwrap();
+        assert_eq!(league.id, "Settlers");
+        assert_eq!(league.end_at, None);
+    }
+
+    #[test]
+    fn test_challenge_league_not_started_or_ended() {
+        let leagues = leagues();
+
+        // Before Settlers launched, the previous league is still running
+        let league = current_challenge_league(&leagues, at(2024, 7, 1)).unwrap();
+        assert_eq!(league.id, "Necropolis");
+
+        // Between leagues only permanent leagues are left
+        assert!(current_challenge_league(&leagues, at(2013, 2, 1)).is_none());
+    }
+
+    #[test]
+    fn test_challenge_league_without_dates() {
+        let leagues: Vec<League> =
+            serde_json::from_str(r#"[{"id": "Announced", "startAt": null, "endAt": null}]"#)
+                .unwrap();
+
+        assert!(leagues[0].start_at.is_none());
+        assert!(current_challenge_league(&leagues, at(2024, 9, 1)).is_none());
+    }
+}
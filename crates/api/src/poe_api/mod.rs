@@ -1,7 +1,21 @@
 //! PoE API client modules
 
+pub mod character;
+pub mod client;
 pub mod leagues;
 pub mod stash;
+pub mod trade;
 pub mod models;
+pub mod rate_limit;
 
-// TODO: Implement API client
+pub use client::{PoeApiClient, POE_API_URL};
+pub use leagues::current_challenge_league;
+pub use models::{
+    CharacterPassives, League, LeagueRule, StashItem, StashResponse, StashTab, TradeListing,
+    TradePrice, TradeSearch,
+};
+pub use trade::{
+    add_mod_filters, build_search_payload, build_trade_site_url, FETCH_BATCH_SIZE,
+    TRADE_SITE_MAX_FILTERS,
+};
+pub use rate_limit::{RateLimitState, RateLimitWindow, RateLimiter, WindowUsage};
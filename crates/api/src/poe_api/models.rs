@@ -1,15 +1,261 @@
 //! API response models
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use poe_item_analyzer_core::analyzers::TimelessJewelConfig;
+use std::collections::{BTreeMap, HashSet};
 
-// TODO: Add models for API responses
-
+/// A league as listed by the leagues endpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct League {
+    /// League name (e.g., "Settlers")
     pub id: String,
+
+    /// Realm the league runs on (e.g., "pc")
+    #[serde(default)]
     pub realm: Option<String>,
-    #[serde(rename = "startAt")]
-    pub start_at: Option<String>,
-    #[serde(rename = "endAt")]
-    pub end_at: Option<String>,
+
+    /// League description
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// When the league started (None if not announced)
+    #[serde(rename = "startAt", default)]
+    pub start_at: Option<DateTime<Utc>>,
+
+    /// When the league ends (None for permanent or open-ended leagues)
+    #[serde(rename = "endAt", default)]
+    pub end_at: Option<DateTime<Utc>>,
+
+    /// League rules (e.g., "Hardcore", "NoParties")
+    #[serde(default)]
+    pub rules: Vec<LeagueRule>,
+}
+
+/// A rule modifying a league
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeagueRule {
+    /// Rule id (e.g., "Hardcore")
+    pub id: String,
+
+    /// Display name
+    #[serde(default)]
+    pub name: String,
+}
+
+impl League {
+    /// Whether the league has the given rule
+    pub fn has_rule(&self, rule: &str) -> bool {
+        self.rules.iter().any(|r| r.id == rule)
+    }
+
+    /// Hardcore league (characters move to the parent league on death)
+    pub fn is_hardcore(&self) -> bool {
+        self.has_rule("Hardcore") || self.id.contains("Hardcore") || self.id.starts_with("HC ")
+    }
+
+    /// Solo Self-Found league
+    pub fn is_solo_self_found(&self) -> bool {
+        self.has_rule("NoParties") || self.id.contains("SSF")
+    }
+
+    /// Ruthless league
+    pub fn is_ruthless(&self) -> bool {
+        self.has_rule("HardMode") || self.id.contains("Ruthless")
+    }
+
+    /// Permanent Standard league
+    pub fn is_standard(&self) -> bool {
+        self.id == "Standard"
+    }
+
+    /// Whether the league is running at `now`
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        let started = self.start_at.is_some_and(|start| start <= now);
+        let ended = self.end_at.is_some_and(|end| end <= now);
+        started && !ended
+    }
+}
+
+/// Response of the character-window passive skills endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterPassives {
+    /// Allocated passive node hashes
+    #[serde(default)]
+    pub hashes: Vec<u32>,
+
+    /// Allocated cluster jewel node hashes
+    #[serde(default)]
+    pub hashes_ex: Vec<u32>,
+
+    /// Jewels socketed in the tree; `x` is the jewel slot index
+    #[serde(default)]
+    pub items: Vec<StashItem>,
+
+    /// Passive tree version the hashes belong to (e.g., "3.25.0"), when the
+    /// API reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree_version: Option<String>,
+}
+
+impl CharacterPassives {
+    /// Allocated main tree nodes, as used by `TimelessJewelConfig`
+    pub fn allocated_nodes(&self) -> HashSet<u32> {
+        self.hashes.iter().copied().collect()
+    }
+
+    /// Config counting only the allocated nodes
+    ///
+    /// Carries the tree version, so the analysis warns when the lookup data
+    /// is for another tree.
+    pub fn to_config(&self) -> TimelessJewelConfig {
+        let config = TimelessJewelConfig::new().with_allocated_nodes(self.allocated_nodes());
+        match &self.tree_version {
+            Some(version) => config.with_tree_version(version.clone()),
+            None => config,
+        }
+    }
+
+    /// Socketed jewels by jewel slot index
+    pub fn jewel_sockets(&self) -> Vec<(u32, &StashItem)> {
+        self.items.iter().map(|item| (item.x, item)).collect()
+    }
+
+    /// Jewel slot index holding a timeless jewel, if any
+    pub fn timeless_jewel_socket(&self) -> Option<u32> {
+        self.items
+            .iter()
+            .find(|item| item.type_line == "Timeless Jewel")
+            .map(|item| item.x)
+    }
+}
+
+/// Result of a trade search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSearch {
+    /// Query id, needed to fetch the listings
+    pub id: String,
+
+    /// Listing ids, cheapest first (at most 100)
+    #[serde(default)]
+    pub result: Vec<String>,
+
+    /// Total number of matching listings
+    #[serde(default)]
+    pub total: u32,
+}
+
+/// Asking price of a trade listing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradePrice {
+    /// Amount of currency
+    pub amount: f64,
+
+    /// Currency id (e.g., "divine", "chaos")
+    pub currency: String,
+}
+
+/// A listed item fetched from the trade API
+#[derive(Debug, Clone)]
+pub struct TradeListing {
+    /// Listing id
+    pub id: String,
+
+    /// Seller account name
+    pub seller: String,
+
+    /// Asking price (None if the item is unpriced)
+    pub price: Option<TradePrice>,
+
+    /// When the item was listed
+    pub indexed: Option<DateTime<Utc>>,
+
+    /// Whisper message to contact the seller
+    pub whisper: Option<String>,
+
+    /// The listed item
+    pub item: StashItem,
+}
+
+/// Response of the character-window stash endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashResponse {
+    /// Number of tabs in the stash
+    #[serde(rename = "numTabs", default)]
+    pub num_tabs: u32,
+
+    /// Tab list (only when requested with `tabs=1`)
+    #[serde(default)]
+    pub tabs: Vec<StashTab>,
+
+    /// Items in the requested tab
+    #[serde(default)]
+    pub items: Vec<StashItem>,
+}
+
+/// A stash tab as listed by the stash endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashTab {
+    /// Tab id
+    #[serde(default)]
+    pub id: String,
+
+    /// Tab name
+    #[serde(rename = "n")]
+    pub name: String,
+
+    /// Position of the tab (used as `tabIndex`)
+    #[serde(rename = "i")]
+    pub index: u32,
+
+    /// Tab type (e.g., "PremiumStash", "QuadStash")
+    #[serde(rename = "type", default)]
+    pub tab_type: String,
+
+    /// Whether the tab is hidden (e.g., removed-only tabs)
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// An item in a stash tab
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashItem {
+    /// Item id
+    pub id: String,
+
+    /// Item name (e.g., "Lethal Pride"; empty for most non-unique items)
+    #[serde(default)]
+    pub name: String,
+
+    /// Type line (e.g., "Timeless Jewel")
+    #[serde(rename = "typeLine")]
+    pub type_line: String,
+
+    /// Base type, if different from the type line
+    #[serde(rename = "baseType", default, skip_serializing_if = "Option::is_none")]
+    pub base_type: Option<String>,
+
+    /// Explicit modifier lines
+    #[serde(rename = "explicitMods", default, skip_serializing_if = "Vec::is_empty")]
+    pub explicit_mods: Vec<String>,
+
+    /// Column in the stash tab (jewel slot index for tree jewels)
+    #[serde(default)]
+    pub x: u32,
+
+    /// Row in the stash tab
+    #[serde(default)]
+    pub y: u32,
+
+    /// Every other field of the item, as sent by the API
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl StashItem {
+    /// The item as the original JSON object
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
 }
@@ -0,0 +1,431 @@
+//! Client-side throttling for the Path of Exile API
+//!
+//! GGG's API describes its limits in response headers:
+//!
+//! - `X-Rate-Limit-Rules: Account,Ip` names the rules that apply
+//! - `X-Rate-Limit-Account: 45:60:120,240:240:900` lists each window of a
+//!   rule as `max_hits:period:restriction` (seconds)
+//! - `X-Rate-Limit-Account-State: 1:60:0,1:240:0` gives the current
+//!   `hits:period:active_restriction` of each window
+//!
+//! Going over a limit earns a temporary restriction, so `RateLimiter` tracks
+//! every window and delays requests just enough to stay under all of them.
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// One window of a rate limit rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitWindow {
+    /// Requests allowed per period
+    pub max_hits: u32,
+
+    /// Length of the window
+    pub period: Duration,
+
+    /// How long requests are blocked after exceeding the window
+    pub restriction: Duration,
+}
+
+/// Usage of one window, for display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowUsage {
+    /// Rule the window belongs to (e.g., "Account")
+    pub rule: String,
+
+    /// Requests counted in the current window
+    pub hits: u32,
+
+    /// Requests allowed per period
+    pub max_hits: u32,
+
+    /// Length of the window
+    pub period: Duration,
+}
+
+/// Snapshot of the limiter, e.g. to show "waiting 12s for rate limit"
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RateLimitState {
+    /// How long the next request has to wait
+    pub wait: Duration,
+
+    /// Usage of every known window
+    pub windows: Vec<WindowUsage>,
+}
+
+impl RateLimitState {
+    /// Whether the next request will be delayed
+    pub fn is_waiting(&self) -> bool {
+        !self.wait.is_zero()
+    }
+}
+
+#[derive(Debug)]
+struct TrackedWindow {
+    rule: String,
+    window: RateLimitWindow,
+    /// Times of requests within the window, oldest first
+    hits: VecDeque<Instant>,
+}
+
+impl TrackedWindow {
+    fn prune(&mut self, now: Instant) {
+        while let Some(&oldest) = self.hits.front() {
+            if now.saturating_duration_since(oldest) >= self.window.period {
+                self.hits.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Time until one more request fits in the window
+    fn delay(&self, now: Instant) -> Duration {
+        let max_hits = self.window.max_hits as usize;
+        if self.hits.len() < max_hits {
+            return Duration::ZERO;
+        }
+
+        let frees_up = self.hits[self.hits.len() - max_hits] + self.window.period;
+        frees_up.saturating_duration_since(now)
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    windows: Vec<TrackedWindow>,
+    blocked_until: Option<Instant>,
+}
+
+/// Tracks the API's rate limit rules and spaces requests to respect them
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+impl RateLimiter {
+    /// Create a limiter with no known rules (nothing is delayed)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait until a request is allowed, then count it
+    pub async fn acquire(&self) {
+        loop {
+            let delay = self.delay();
+            if delay.is_zero() {
+                break;
+            }
+            tokio::time::sleep(delay).await;
+        }
+
+        self.record_request_at(Instant::now());
+    }
+
+    /// How long the next request has to wait
+    pub fn delay(&self) -> Duration {
+        self.delay_at(Instant::now())
+    }
+
+    /// Current waiting time and window usage
+    pub fn state(&self) -> RateLimitState {
+        let now = Instant::now();
+        let mut state = self.lock();
+
+        for window in &mut state.windows {
+            window.prune(now);
+        }
+
+        RateLimitState {
+            wait: delay_for(&state, now),
+            windows: state
+                .windows
+                .iter()
+                .map(|window| WindowUsage {
+                    rule: window.rule.clone(),
+                    hits: window.hits.len() as u32,
+                    max_hits: window.window.max_hits,
+                    period: window.window.period,
+                })
+                .collect(),
+        }
+    }
+
+    /// Update the rules and usage from a response's headers
+    ///
+    /// On a 429 response the `Retry-After` header blocks further requests
+    /// until it has passed.
+    pub fn update_from_headers(&self, headers: &HeaderMap) {
+        self.update_at(headers, Instant::now());
+    }
+
+    fn update_at(&self, headers: &HeaderMap, now: Instant) {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+        let mut state = self.lock();
+        let mut block = |duration: Duration| {
+            let until = now + duration;
+            state.blocked_until = Some(state.blocked_until.map_or(until, |b| b.max(until)));
+        };
+
+        if let Some(retry_after) = header(RETRY_AFTER.as_str()).and_then(|v| v.trim().parse().ok()) {
+            block(Duration::from_secs(retry_after));
+        }
+
+        let Some(rules) = header("x-rate-limit-rules") else {
+            return;
+        };
+
+        let mut windows = Vec::new();
+        for rule in rules.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let name = format!("x-rate-limit-{}", rule.to_ascii_lowercase());
+            let limits = header(&name).map(parse_triples).unwrap_or_default();
+            let states = header(&format!("{}-state", name))
+                .map(parse_triples)
+                .unwrap_or_default();
+
+            for (max_hits, period, restriction) in limits {
+                let window = RateLimitWindow {
+                    max_hits,
+                    period: Duration::from_secs(period as u64),
+                    restriction: Duration::from_secs(restriction as u64),
+                };
+
+                let current = states.iter().find(|(_, p, _)| *p == period);
+                windows.push((rule.to_string(), window, current.copied()));
+            }
+        }
+
+        let mut previous = std::mem::take(&mut state.windows);
+        for (rule, window, current) in windows {
+            // Keep our own request log for windows we already knew
+            let hits = previous
+                .iter()
+                .position(|w| w.rule == rule && w.window.period == window.period)
+                .map(|index| previous.swap_remove(index).hits)
+                .unwrap_or_default();
+
+            let mut tracked = TrackedWindow { rule, window, hits };
+            tracked.prune(now);
+
+            if let Some((hits, _, restricted)) = current {
+                // The server's count wins; unknown extra hits count from now
+                while tracked.hits.len() > hits as usize {
+                    tracked.hits.pop_front();
+                }
+                while tracked.hits.len() < hits as usize {
+                    tracked.hits.push_back(now);
+                }
+
+                if restricted > 0 {
+                    let until = now + Duration::from_secs(restricted as u64);
+                    state.blocked_until =
+                        Some(state.blocked_until.map_or(until, |b| b.max(until)));
+                }
+            }
+
+            state.windows.push(tracked);
+        }
+    }
+
+    fn delay_at(&self, now: Instant) -> Duration {
+        let mut state = self.lock();
+        for window in &mut state.windows {
+            window.prune(now);
+        }
+        delay_for(&state, now)
+    }
+
+    fn record_request_at(&self, now: Instant) {
+        for window in &mut self.lock().windows {
+            window.hits.push_back(now);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn delay_for(state: &LimiterState, now: Instant) -> Duration {
+    let blocked = state
+        .blocked_until
+        .map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+
+    state
+        .windows
+        .iter()
+        .map(|window| window.delay(now))
+        .fold(blocked, Duration::max)
+}
+
+/// Parse "45:60:120,240:240:900" into (a, b, c) triples, skipping bad entries
+fn parse_triples(value: &str) -> Vec<(u32, u32, u32)> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.trim().split(':').map(|p| p.parse::<u32>().ok());
+            Some((parts.next()??, parts.next()??, parts.next()??))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn account_headers(state: &str) -> HeaderMap {
+        headers(&[
+            ("x-rate-limit-rules", "Account"),
+            ("x-rate-limit-account", "3:10:60,10:60:300"),
+            ("x-rate-limit-account-state", state),
+        ])
+    }
+
+    #[test]
+    fn test_parse_triples() {
+        assert_eq!(
+            parse_triples("45:60:120, 240:240:900"),
+            vec![(45, 60, 120), (240, 240, 900)]
+        );
+        assert_eq!(parse_triples("45:60,bad,1:2:3"), vec![(1, 2, 3)]);
+    }
+
+    #[test]
+    fn test_no_rules_no_delay() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        limiter.record_request_at(now);
+        limiter.update_at(&HeaderMap::new(), now);
+
+        assert_eq!(limiter.delay_at(now), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_delays_until_window_frees_up() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+
+        // Server reports 1 of 3 hits used in the 10s window
+        limiter.update_at(&account_headers("1:10:0,1:60:0"), start);
+        assert_eq!(limiter.delay_at(start), Duration::ZERO);
+
+        limiter.record_request_at(start + Duration::from_secs(2));
+        limiter.update_at(
+            &account_headers("2:10:0,2:60:0"),
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(limiter.delay_at(start + Duration::from_secs(2)), Duration::ZERO);
+
+        limiter.record_request_at(start + Duration::from_secs(4));
+        limiter.update_at(
+            &account_headers("3:10:0,3:60:0"),
+            start + Duration::from_secs(4),
+        );
+
+        // Full: the first hit (at `start`) leaves the 10s window at start + 10s
+        assert_eq!(
+            limiter.delay_at(start + Duration::from_secs(4)),
+            Duration::from_secs(6)
+        );
+        assert_eq!(
+            limiter.delay_at(start + Duration::from_secs(10)),
+            Duration::ZERO
+        );
+
+        let state = limiter.state();
+        assert_eq!(state.windows.len(), 2);
+        assert_eq!(state.windows[1].max_hits, 10);
+        assert_eq!(state.windows[1].period, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_server_count_overrides_local_log() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        // Other clients on the same account already used the whole window
+        limiter.update_at(&account_headers("3:10:0,3:60:0"), now);
+
+        assert_eq!(limiter.delay_at(now), Duration::from_secs(10));
+
+        // The server says the window has emptied
+        let later = now + Duration::from_secs(1);
+        limiter.update_at(&account_headers("0:10:0,3:60:0"), later);
+        assert_eq!(limiter.delay_at(later), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_active_restriction_blocks() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        limiter.update_at(&account_headers("4:10:60,4:60:0"), now);
+
+        assert_eq!(limiter.delay_at(now), Duration::from_secs(60));
+        assert!(limiter.state().is_waiting());
+    }
+
+    #[test]
+    fn test_retry_after_on_429() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        let mut response = account_headers("3:10:0,3:60:0");
+        response.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        limiter.update_at(&response, now);
+
+        assert_eq!(limiter.delay_at(now), Duration::from_secs(30));
+        assert_eq!(
+            limiter.delay_at(now + Duration::from_secs(20)),
+            Duration::from_secs(10)
+        );
+        assert_eq!(limiter.delay_at(now + Duration::from_secs(30)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_multiple_rules() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        limiter.update_at(
+            &headers(&[
+                ("x-rate-limit-rules", "Account,Ip"),
+                ("x-rate-limit-account", "45:60:120"),
+                ("x-rate-limit-account-state", "1:60:0"),
+                ("x-rate-limit-ip", "1:4:60"),
+                ("x-rate-limit-ip-state", "1:4:0"),
+            ]),
+            now,
+        );
+
+        // The Ip rule is the tighter one
+        assert_eq!(limiter.delay_at(now), Duration::from_secs(4));
+
+        let rules: Vec<_> = limiter.state().windows.into_iter().map(|w| w.rule).collect();
+        assert_eq!(rules, vec!["Account", "Ip"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_acquire_waits() {
+        let limiter = RateLimiter::new();
+        limiter.update_from_headers(&account_headers("3:10:0,3:60:0"));
+
+        let started = tokio::time::Instant::now();
+        limiter.acquire().await;
+
+        assert!(started.elapsed() >= Duration::from_secs(10));
+    }
+}
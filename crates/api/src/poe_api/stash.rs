@@ -1,3 +1,63 @@
 //! Stash tab API endpoints
+//!
+//! Uses the character-window stash API, which needs the account's
+//! `POESESSID` session cookie.
 
-// TODO: Implement stash tab fetching
+use super::client::PoeApiClient;
+use super::models::{StashItem, StashResponse, StashTab};
+use crate::error::ApiError;
+
+/// Path of the character-window stash endpoint
+const STASH_ITEMS_PATH: &str = "/character-window/get-stash-items";
+
+impl PoeApiClient {
+    /// List the stash tabs of `account` in `league`
+    pub async fn get_stash_tabs(
+        &self,
+        account: &str,
+        league: &str,
+        session_id: &str,
+    ) -> Result<Vec<StashTab>, ApiError> {
+        let stash = self.get_stash(account, league, 0, true, session_id).await?;
+        Ok(stash.tabs)
+    }
+
+    /// Get the items in the stash tab at `tab_index`
+    pub async fn get_stash_items(
+        &self,
+        account: &str,
+        league: &str,
+        tab_index: u32,
+        session_id: &str,
+    ) -> Result<Vec<StashItem>, ApiError> {
+        let stash = self
+            .get_stash(account, league, tab_index, false, session_id)
+            .await?;
+        Ok(stash.items)
+    }
+
+    async fn get_stash(
+        &self,
+        account: &str,
+        league: &str,
+        tab_index: u32,
+        include_tabs: bool,
+        session_id: &str,
+    ) -> Result<StashResponse, ApiError> {
+        let tab_index = tab_index.to_string();
+        let query = [
+            ("accountName", account),
+            ("realm", "pc"),
+            ("league", league),
+            ("tabs", if include_tabs { "1" } else { "0" }),
+            ("tabIndex", tab_index.as_str()),
+        ];
+
+        let response = self.get(STASH_ITEMS_PATH, &query, Some(session_id)).await?;
+
+        response
+            .json()
+            .await
+            .map_err(|e| ApiError::InvalidResponse(e.to_string()))
+    }
+}
@@ -0,0 +1,426 @@
+//! Official trade API endpoints
+//!
+//! Searches go through the shared rate limiter like every other request;
+//! the trade API restricts quickly, so avoid firing searches in a loop.
+
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Utc};
+use poe_item_analyzer_core::items::{JewelType, MatchedMod};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::client::{PoeApiClient, POE_API_URL};
+use super::models::{StashItem, TradeListing, TradePrice, TradeSearch};
+use crate::error::ApiError;
+
+/// Path of the trade search endpoint (followed by the league)
+const TRADE_SEARCH_PATH: &str = "/api/trade/search";
+
+/// Path of the trade fetch endpoint (followed by listing ids)
+const TRADE_FETCH_PATH: &str = "/api/trade/fetch";
+
+/// Path of the trade website (followed by the league)
+const TRADE_SITE_PATH: &str = "/trade/search";
+
+/// Listings the fetch endpoint accepts per request
+pub const FETCH_BATCH_SIZE: usize = 10;
+
+/// Stat filters the trade site accepts in one search
+pub const TRADE_SITE_MAX_FILTERS: usize = 30;
+
+/// Build a trade search for a timeless jewel with a seed in `seed_range`
+///
+/// The seed is matched through the "Commanded leadership over # warriors
+/// under Kaom" style pseudo stat of each conqueror. Without a `conqueror`
+/// any variant of the jewel matches.
+pub fn build_search_payload(
+    jewel_type: JewelType,
+    seed_range: RangeInclusive<u32>,
+    conqueror: Option<&str>,
+) -> Result<Value, ApiError> {
+    let conquerors = search_conquerors(jewel_type, conqueror)?;
+    Ok(seed_ranges_payload(jewel_type, &[seed_range], &conquerors))
+}
+
+/// Trade site links searching for any of `seeds`
+///
+/// Runs of consecutive seeds share one filter. When the filters of every
+/// conqueror searched exceed [`TRADE_SITE_MAX_FILTERS`], the seeds are
+/// split over several links, in ascending order.
+pub fn build_trade_site_url(
+    league: &str,
+    jewel_type: JewelType,
+    seeds: &[u32],
+    conqueror: Option<&str>,
+) -> Result<Vec<String>, ApiError> {
+    if seeds.is_empty() {
+        return Err(ApiError::InvalidRequest("No seeds to search for".to_string()));
+    }
+
+    let conquerors = search_conquerors(jewel_type, conqueror)?;
+    let ranges_per_link = (TRADE_SITE_MAX_FILTERS / conquerors.len()).max(1);
+
+    Ok(seed_runs(jewel_type, seeds)
+        .chunks(ranges_per_link)
+        .map(|ranges| {
+            let payload = seed_ranges_payload(jewel_type, ranges, &conquerors);
+            trade_site_url(POE_API_URL, league, &payload)
+        })
+        .collect())
+}
+
+/// Conquerors a search covers: `conqueror`, or all of them
+fn search_conquerors(
+    jewel_type: JewelType,
+    conqueror: Option<&str>,
+) -> Result<Vec<&'static str>, ApiError> {
+    let conquerors: Vec<&str> = match conqueror {
+        Some(name) => {
+            let known = jewel_type
+                .conquerors()
+                .iter()
+                .find(|c| c.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    ApiError::InvalidRequest(format!(
+                        "{} is not a conqueror of {}",
+                        name,
+                        jewel_type.as_str()
+                    ))
+                })?;
+            vec![known]
+        }
+        None => jewel_type.conquerors().to_vec(),
+    };
+    Ok(conquerors)
+}
+
+/// Sorted `seeds` merged into runs of consecutive valid seeds
+fn seed_runs(jewel_type: JewelType, seeds: &[u32]) -> Vec<RangeInclusive<u32>> {
+    // Elegant Hubris seeds are multiples of 20
+    let step = if jewel_type == JewelType::ElegantHubris { 20 } else { 1 };
+    let mut seeds = seeds.to_vec();
+    seeds.sort_unstable();
+    seeds.dedup();
+
+    let mut runs: Vec<RangeInclusive<u32>> = Vec::new();
+    for seed in seeds {
+        match runs.last_mut() {
+            Some(run) if *run.end() + step == seed => *run = *run.start()..=seed,
+            _ => runs.push(seed..=seed),
+        }
+    }
+    runs
+}
+
+/// Search matching a seed in any of `seed_ranges` for any of `conquerors`
+fn seed_ranges_payload(
+    jewel_type: JewelType,
+    seed_ranges: &[RangeInclusive<u32>],
+    conquerors: &[&str],
+) -> Value {
+    let filters: Vec<Value> = seed_ranges
+        .iter()
+        .flat_map(|range| {
+            conquerors.iter().map(move |name| {
+                json!({
+                    "id": format!(
+                        "explicit.pseudo_timeless_jewel_{}",
+                        name.to_ascii_lowercase()
+                    ),
+                    "value": { "min": range.start(), "max": range.end() },
+                })
+            })
+        })
+        .collect();
+
+    json!({
+        "query": {
+            "status": { "option": "online" },
+            "name": jewel_type.as_str(),
+            "type": "Timeless Jewel",
+            "stats": [{
+                "type": "count",
+                "value": { "min": 1 },
+                "filters": filters,
+            }],
+        },
+        "sort": { "price": "asc" },
+    })
+}
+
+/// Require the mods in `mods` on top of a search from [`build_search_payload`]
+///
+/// Mods with a trade stat id go into one "and" stat group, each with at
+/// least its count. Returns the texts of the mods without an id, which
+/// can't be searched for.
+pub fn add_mod_filters(payload: &mut Value, mods: &[MatchedMod]) -> Vec<String> {
+    let mut filters = Vec::new();
+    let mut unmapped = Vec::new();
+
+    for matched in mods {
+        match &matched.trade_stat_id {
+            Some(id) => filters.push(json!({
+                "id": id,
+                "value": { "min": matched.count },
+            })),
+            None => unmapped.push(matched.mod_text.clone()),
+        }
+    }
+
+    if !filters.is_empty() {
+        if let Some(stats) = payload["query"]["stats"].as_array_mut() {
+            stats.push(json!({ "type": "and", "filters": filters }));
+        }
+    }
+
+    unmapped
+}
+
+#[derive(Deserialize)]
+struct FetchResponse {
+    #[serde(default)]
+    result: Vec<Option<FetchEntry>>,
+}
+
+#[derive(Deserialize)]
+struct FetchEntry {
+    id: String,
+    listing: ListingInfo,
+    item: StashItem,
+}
+
+#[derive(Deserialize)]
+struct ListingInfo {
+    #[serde(default)]
+    indexed: Option<DateTime<Utc>>,
+    account: ListingAccount,
+    #[serde(default)]
+    price: Option<TradePrice>,
+    #[serde(default)]
+    whisper: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ListingAccount {
+    name: String,
+}
+
+impl PoeApiClient {
+    /// Run a trade search built by [`build_search_payload`] in `league`
+    pub async fn search(&self, league: &str, payload: &Value) -> Result<TradeSearch, ApiError> {
+        let path = format!("{}/{}", TRADE_SEARCH_PATH, path_segment(league));
+        let response = self.post_json(&path, payload, None).await?;
+
+        response
+            .json()
+            .await
+            .map_err(|e| ApiError::InvalidResponse(e.to_string()))
+    }
+
+    /// Fetch the listings `ids` of the search `query_id`
+    ///
+    /// Requests are batched by [`FETCH_BATCH_SIZE`]; listings removed since
+    /// the search are skipped.
+    pub async fn fetch(&self, ids: &[String], query_id: &str) -> Result<Vec<TradeListing>, ApiError> {
+        let mut listings = Vec::with_capacity(ids.len());
+
+        for batch in ids.chunks(FETCH_BATCH_SIZE) {
+            let path = format!("{}/{}", TRADE_FETCH_PATH, batch.join(","));
+            let response = self.get(&path, &[("query", query_id)], None).await?;
+
+            let fetched: FetchResponse = response
+                .json()
+                .await
+                .map_err(|e| ApiError::InvalidResponse(e.to_string()))?;
+
+            listings.extend(fetched.result.into_iter().flatten().map(|entry| TradeListing {
+                id: entry.id,
+                seller: entry.listing.account.name,
+                price: entry.listing.price,
+                indexed: entry.listing.indexed,
+                whisper: entry.listing.whisper,
+                item: entry.item,
+            }));
+        }
+
+        Ok(listings)
+    }
+
+    /// Link to the search `payload` on the trade website, to open in a browser
+    pub fn build_trade_url(&self, league: &str, payload: &Value) -> String {
+        trade_site_url(self.base_url(), league, payload)
+    }
+}
+
+/// `<base_url>/trade/search/<league>?q=<payload>`
+fn trade_site_url(base_url: &str, league: &str, payload: &Value) -> String {
+    let url = format!("{}{}/{}", base_url, TRADE_SITE_PATH, path_segment(league));
+
+    match Url::parse_with_params(&url, [("q", payload.to_string())]) {
+        Ok(url) => url.to_string(),
+        Err(_) => url,
+    }
+}
+
+/// Percent-encode `value` for use as a single path segment
+fn path_segment(value: &str) -> String {
+    let mut url = Url::parse("http://localhost/").expect("valid base URL");
+    url.path_segments_mut()
+        .expect("base URL has a path")
+        .push(value);
+    url.path()[1..].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_for_conqueror() {
+        let payload =
+            build_search_payload(JewelType::LethalPride, 14032..=14032, Some("kaom")).unwrap();
+
+        let query = &payload["query"];
+        assert_eq!(query["name"], "Lethal Pride");
+        assert_eq!(query["type"], "Timeless Jewel");
+        assert_eq!(query["status"]["option"], "online");
+        assert_eq!(payload["sort"]["price"], "asc");
+
+        let filters = query["stats"][0]["filters"].as_array().unwrap();
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0]["id"], "explicit.pseudo_timeless_jewel_kaom");
+        assert_eq!(filters[0]["value"]["min"], 14032);
+        assert_eq!(filters[0]["value"]["max"], 14032);
+    }
+
+    #[test]
+    fn test_payload_for_any_conqueror() {
+        let payload =
+            build_search_payload(JewelType::GloriousVanity, 100..=8000, None).unwrap();
+
+        let stats = &payload["query"]["stats"][0];
+        assert_eq!(stats["type"], "count");
+        assert_eq!(stats["value"]["min"], 1);
+
+        let ids: Vec<_> = stats["filters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "explicit.pseudo_timeless_jewel_doryani",
+                "explicit.pseudo_timeless_jewel_xibaqua",
+                "explicit.pseudo_timeless_jewel_zerphi",
+                "explicit.pseudo_timeless_jewel_ahuana",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_payload_rejects_wrong_conqueror() {
+        let error =
+            build_search_payload(JewelType::LethalPride, 1..=2, Some("Dominus")).unwrap_err();
+
+        assert!(matches!(error, ApiError::InvalidRequest(_)));
+        assert!(error.to_string().contains("Lethal Pride"));
+    }
+
+    #[test]
+    fn test_build_trade_url() {
+        let client = PoeApiClient::new();
+        let payload =
+            build_search_payload(JewelType::LethalPride, 14032..=14032, Some("Kaom")).unwrap();
+
+        let url = client.build_trade_url("Hardcore Settlers", &payload);
+        assert!(url.starts_with("https://www.pathofexile.com/trade/search/Hardcore%20Settlers?q="));
+
+        let parsed = Url::parse(&url).unwrap();
+        let (_, q) = parsed.query_pairs().find(|(k, _)| k == "q").unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&q).unwrap(), payload);
+    }
+
+    /// Seed filters of a trade site link, as (id, min, max)
+    fn link_filters(url: &str) -> Vec<(String, u64, u64)> {
+        let parsed = Url::parse(url).unwrap();
+        let (_, q) = parsed.query_pairs().find(|(k, _)| k == "q").unwrap();
+        let payload: Value = serde_json::from_str(&q).unwrap();
+
+        payload["query"]["stats"][0]["filters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| {
+                let value = &f["value"];
+                let id = f["id"].as_str().unwrap().to_string();
+                (id, value["min"].as_u64().unwrap(), value["max"].as_u64().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trade_site_url_merges_consecutive_seeds() {
+        let urls = build_trade_site_url(
+            "Settlers",
+            JewelType::LethalPride,
+            &[14032, 10000, 14033, 14031, 10000],
+            Some("Kaom"),
+        )
+        .unwrap();
+
+        assert_eq!(urls.len(), 1);
+        assert!(urls[0].starts_with("https://www.pathofexile.com/trade/search/Settlers?q=%7B"));
+        // The JSON is percent-encoded, not passed through
+        assert!(!urls[0].contains('"'));
+        assert!(urls[0].contains("explicit.pseudo_timeless_jewel_kaom"));
+
+        let id = "explicit.pseudo_timeless_jewel_kaom".to_string();
+        assert_eq!(
+            link_filters(&urls[0]),
+            vec![(id.clone(), 10000, 10000), (id, 14031, 14033)]
+        );
+    }
+
+    #[test]
+    fn test_trade_site_url_elegant_hubris_steps() {
+        let seeds = [2000, 2020, 2060];
+        let urls = build_trade_site_url("Standard", JewelType::ElegantHubris, &seeds, Some("Cadiro"))
+            .unwrap();
+
+        let ranges: Vec<(u64, u64)> =
+            link_filters(&urls[0]).into_iter().map(|(_, min, max)| (min, max)).collect();
+        assert_eq!(ranges, vec![(2000, 2020), (2060, 2060)]);
+    }
+
+    #[test]
+    fn test_trade_site_url_splits_at_the_cap() {
+        // Every other seed, so no two merge
+        let seeds: Vec<u32> = (0..20).map(|i| 10000 + i * 2).collect();
+
+        // 4 conquerors per seed: 7 seeds (28 filters) per link
+        let urls = build_trade_site_url("Standard", JewelType::LethalPride, &seeds, None).unwrap();
+        let per_link: Vec<usize> = urls.iter().map(|url| link_filters(url).len()).collect();
+        assert_eq!(per_link, vec![28, 28, 24]);
+        assert!(per_link.iter().all(|&filters| filters <= TRADE_SITE_MAX_FILTERS));
+
+        // The links cover the seeds in order
+        let first_seeds: Vec<u64> = urls.iter().map(|url| link_filters(url)[0].1).collect();
+        assert_eq!(first_seeds, vec![10000, 10014, 10028]);
+
+        // One conqueror: all 20 seeds fit one link
+        let urls =
+            build_trade_site_url("Standard", JewelType::LethalPride, &seeds, Some("Akoya")).unwrap();
+        assert_eq!(urls.len(), 1);
+        assert_eq!(link_filters(&urls[0]).len(), 20);
+    }
+
+    #[test]
+    fn test_trade_site_url_needs_seeds() {
+        let error = build_trade_site_url("Standard", JewelType::LethalPride, &[], None).unwrap_err();
+        assert!(matches!(error, ApiError::InvalidRequest(_)));
+    }
+}
@@ -0,0 +1,144 @@
+//! Repair of a damaged data directory
+//!
+//! Partial downloads and disk problems leave files that no longer match the
+//! manifest, and parsing then fails. `repair` finds those files, downloads
+//! just them again and parses only what changed.
+
+use std::path::Path;
+
+use tracing::info;
+
+use crate::checksum::{verify_directory, FileVerification};
+use crate::downloader::DataDownloader;
+use crate::error::{DownloadError, FileContext, FileOperation};
+use crate::manifest::DataManifest;
+use crate::parser::{LutData, ParseMode, ParseReport, PobDataParser};
+use crate::validation::{validate_file, ValidationStatus};
+
+/// Outcome of `repair`
+#[derive(Debug, Clone, Default)]
+pub struct RepairReport {
+    /// Files that failed their checksum or content check and were deleted,
+    /// with the reason
+    pub damaged: Vec<(String, String)>,
+
+    /// Files that were downloaded again (damaged or missing ones)
+    pub downloaded: Vec<String>,
+
+    /// Data files that were parsed again; every file when the previous
+    /// parsed LUT couldn't be reused
+    pub reparsed: Vec<String>,
+
+    /// Statistics of the repaired data
+    pub parse_report: ParseReport,
+}
+
+impl RepairReport {
+    /// Whether the directory needed no repair
+    pub fn is_clean(&self) -> bool {
+        self.damaged.is_empty() && self.downloaded.is_empty()
+    }
+}
+
+/// Repair the data in `data_dir` and parse it
+///
+/// Checks every required file against `manifest` (size and SHA256) and its
+/// contents (see `validation::validate_file`), deletes the files that fail,
+/// and downloads them plus any missing ones again with `DataDownloader::sync`.
+/// When only jewel data files were damaged and the recorded parsed LUT is
+/// current, just those files are parsed again; otherwise the whole
+/// directory is.
+pub async fn repair(
+    data_dir: &Path,
+    manifest: &DataManifest,
+) -> Result<(LutData, RepairReport), DownloadError> {
+    let mut report = RepairReport::default();
+    let files: Vec<_> = manifest.required_files().into_iter().cloned().collect();
+
+    for (name, result) in verify_directory(data_dir, &files)?.files {
+        let path = data_dir.join(&name);
+        let reason = match result {
+            FileVerification::Missing => continue,
+            FileVerification::Mismatched { expected, actual } => {
+                format!("expected {}, found {}", expected, actual)
+            }
+            FileVerification::Ok => match validate_file(&path).status {
+                ValidationStatus::Valid | ValidationStatus::Missing => continue,
+                ValidationStatus::TooSmall { bytes } => format!("only {} bytes", bytes),
+                ValidationStatus::InvalidContent(reason) => reason,
+            },
+        };
+
+        info!("Removing damaged data file {}: {}", name, reason);
+        std::fs::remove_file(&path).file_context(FileOperation::Write, &path)?;
+        report.damaged.push((name, reason));
+    }
+
+    let sync = DataDownloader::new(data_dir.to_path_buf())
+        .sync(manifest, data_dir)
+        .await?;
+    // The error already names the file
+    if let Some((_, error)) = sync.failed.first() {
+        return Err(DownloadError::DownloadFailed(error.clone()));
+    }
+    report.downloaded = sync.downloaded;
+
+    let (data, parse_report, reparsed) = reparse(data_dir, manifest, &report.downloaded)?;
+    report.parse_report = parse_report;
+    report.reparsed = reparsed;
+
+    info!(
+        "Repair complete: {} damaged, {} downloaded, {} parsed again",
+        report.damaged.len(),
+        report.downloaded.len(),
+        report.reparsed.len()
+    );
+
+    Ok((data, report))
+}
+
+/// Parse what changed in `downloaded`, reusing the recorded parsed LUT
+///
+/// Returns the data, its report and the names of the files parsed again.
+fn reparse(
+    data_dir: &Path,
+    manifest: &DataManifest,
+    downloaded: &[String],
+) -> Result<(LutData, ParseReport, Vec<String>), DownloadError> {
+    let jewel_types: Option<Vec<&str>> = downloaded
+        .iter()
+        .map(|name| PobDataParser::jewel_file_type(name))
+        .collect();
+
+    let previous = match (&manifest.parsed_artifact, jewel_types) {
+        (Some(artifact), Some(jewel_types)) if !manifest.needs_reparse(data_dir)? => {
+            let path = artifact.resolve(data_dir);
+            PobDataParser::load_artifact(&path, &artifact.format)
+                .ok()
+                .map(|data| {
+                    let report = ParseReport::load_from_json(&ParseReport::sidecar_path(&path))
+                        .unwrap_or_default();
+                    (data, report, jewel_types)
+                })
+        }
+        _ => None,
+    };
+
+    let Some((mut data, mut parse_report, jewel_types)) = previous else {
+        let (data, parse_report) = PobDataParser::parse_directory(data_dir)?;
+        let parsed = parse_report.files.iter().map(|file| file.name.clone()).collect();
+        return Ok((data, parse_report, parsed));
+    };
+
+    for jewel_type in &jewel_types {
+        PobDataParser::parse_jewel_file(
+            data_dir,
+            jewel_type,
+            ParseMode::Strict,
+            &mut data,
+            &mut parse_report,
+        )?;
+    }
+
+    Ok((data, parse_report, downloaded.to_vec()))
+}
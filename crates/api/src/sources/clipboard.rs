@@ -0,0 +1,241 @@
+//! Item text pasted from the game clipboard
+
+use async_trait::async_trait;
+use poe_item_analyzer_core::items::TimelessJewel;
+use tracing::warn;
+
+use super::stash_jewels::TIMELESS_JEWEL;
+use super::traits::ItemSource;
+use crate::error::SourceError;
+
+/// A block of pasted text that looked like a jewel but couldn't be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextBlockError {
+    /// Index of the block in the paste (0-based)
+    pub index: usize,
+
+    /// What went wrong
+    pub message: String,
+}
+
+/// Result of parsing a paste
+#[derive(Debug, Clone, Default)]
+pub struct ParsedText {
+    /// Timeless jewels found, in paste order
+    pub jewels: Vec<TimelessJewel>,
+
+    /// Blocks that failed to parse
+    pub errors: Vec<TextBlockError>,
+
+    /// Other items that were skipped (rings, flasks, ...)
+    pub ignored: usize,
+}
+
+impl ParsedText {
+    /// One-line summary (e.g., "2 jewels, 1 ignored, 1 failed")
+    pub fn summary(&self) -> String {
+        format!(
+            "{} jewels, {} ignored, {} failed",
+            self.jewels.len(),
+            self.ignored,
+            self.errors.len()
+        )
+    }
+}
+
+/// Timeless jewels from item text copied in game with Ctrl+C
+///
+/// The paste may hold several items separated by blank lines; a new
+/// "Item Class:" line also starts a new item.
+pub struct ClipboardTextSource {
+    text: String,
+}
+
+impl ClipboardTextSource {
+    /// Create a source for pasted `text`
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+
+    /// Parse every item in the paste
+    pub fn parse(&self) -> ParsedText {
+        let mut parsed = ParsedText::default();
+
+        for (index, block) in split_items(&self.text).into_iter().enumerate() {
+            let is_item = block.lines().any(|line| line.trim().starts_with("Rarity:"));
+            let is_jewel = block.lines().any(|line| line.trim() == TIMELESS_JEWEL);
+
+            if is_item && !is_jewel {
+                parsed.ignored += 1;
+                continue;
+            }
+
+            match TimelessJewel::from_item_text(&block) {
+                Ok(jewel) => parsed.jewels.push(jewel),
+                Err(e) => parsed.errors.push(TextBlockError {
+                    index,
+                    message: e.to_string(),
+                }),
+            }
+        }
+
+        parsed
+    }
+}
+
+#[async_trait]
+impl ItemSource for ClipboardTextSource {
+    /// Parse the paste, failing only if nothing usable was found
+    async fn fetch_items(&self) -> Result<Vec<TimelessJewel>, SourceError> {
+        let parsed = self.parse();
+
+        if parsed.jewels.is_empty() {
+            if let Some(error) = parsed.errors.first() {
+                return Err(SourceError::ParseError(format!(
+                    "block {}: {}",
+                    error.index, error.message
+                )));
+            }
+        }
+
+        for error in &parsed.errors {
+            warn!("Skipping pasted block {}: {}", error.index, error.message);
+        }
+
+        Ok(parsed.jewels)
+    }
+
+    fn source_name(&self) -> String {
+        "Clipboard".to_string()
+    }
+}
+
+/// Split a paste into per-item blocks
+fn split_items(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        let starts_item = trimmed.starts_with("Item Class:");
+
+        if trimmed.is_empty() || (starts_item && !current.is_empty()) {
+            if !current.is_empty() {
+                blocks.push(current.join("\n"));
+                current.clear();
+            }
+            if trimmed.is_empty() {
+                continue;
+            }
+        }
+
+        current.push(trimmed);
+    }
+
+    if !current.is_empty() {
+        blocks.push(current.join("\n"));
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poe_item_analyzer_core::items::JewelType;
+
+    const PASTE: &str = "Item Class: Jewels
+Rarity: Unique
+Lethal Pride
+Timeless Jewel
+--------
+Limited to: 1 Historic
+Radius: Large
+--------
+Item Level: 84
+--------
+Commanded leadership over 18000 warriors under Kaom
+Passives in radius are Conquered by the Karui
+Historic
+
+Item Class: Rings
+Rarity: Rare
+Doom Loop
+Amethyst Ring
+--------
+Requirements:
+Level: 58
+--------
+Item Level: 82
+--------
++17% to Chaos Resistance (implicit)
+--------
++55 to maximum Life
++38% to Fire Resistance
+
+Item Class: Jewels
+Rarity: Unique
+Glorious Vanity
+Timeless Jewel
+--------
+Limited to: 1 Historic
+Radius: Large
+--------
+Item Level: 86
+--------
+Bathed in the blood of 2000 sacrificed in the name of Doryani
+Passives in radius are Conquered by the Vaal
+Historic
+--------
+Corrupted
+
+Rarity: Unique
+Brutal Restraint
+Timeless Jewel
+--------
+Denoted service of many dekhara in the akhara of Balbala
+";
+
+    #[test]
+    fn test_parse_multi_item_paste() {
+        let parsed = ClipboardTextSource::new(PASTE).parse();
+
+        assert_eq!(parsed.jewels.len(), 2);
+        assert_eq!(parsed.jewels[0].jewel_type, JewelType::LethalPride);
+        assert_eq!(parsed.jewels[0].seed, 18000);
+        assert_eq!(parsed.jewels[1].jewel_type, JewelType::GloriousVanity);
+        assert_eq!(parsed.jewels[1].conqueror, "Doryani");
+
+        assert_eq!(parsed.ignored, 1);
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].index, 3);
+        assert!(parsed.errors[0].message.contains("seed and conqueror"));
+        assert_eq!(parsed.summary(), "2 jewels, 1 ignored, 1 failed");
+    }
+
+    #[test]
+    fn test_split_on_item_class_without_blank_line() {
+        let paste = "Item Class: Rings\nRarity: Rare\nDoom Loop\nAmethyst Ring\nItem Class: Jewels\nRarity: Unique";
+
+        assert_eq!(split_items(paste).len(), 2);
+        assert!(split_items("\n\n  \n").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_items() {
+        let source = ClipboardTextSource::new(PASTE);
+        assert_eq!(source.fetch_items().await.unwrap().len(), 2);
+        assert_eq!(source.source_name(), "Clipboard");
+
+        // Nothing but garbage fails the import
+        let garbage = ClipboardTextSource::new("some random text\nthat is not an item");
+        assert!(matches!(
+            garbage.fetch_items().await,
+            Err(SourceError::ParseError(m)) if m.starts_with("block 0")
+        ));
+
+        // A paste without jewels is an empty import
+        let ring = ClipboardTextSource::new("Rarity: Rare\nDoom Loop\nAmethyst Ring");
+        assert!(ring.fetch_items().await.unwrap().is_empty());
+    }
+}
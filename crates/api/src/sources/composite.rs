@@ -0,0 +1,225 @@
+//! Several item sources fetched as one
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use poe_item_analyzer_core::items::{ItemCollection, TimelessJewel};
+use tracing::warn;
+
+use super::traits::ItemSource;
+use crate::error::SourceError;
+
+/// Outcome of fetching one source of a `CompositeSource`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceReport {
+    /// Name of the source
+    pub source: String,
+
+    /// Jewels the source returned
+    pub fetched: usize,
+
+    /// Jewels that weren't already provided by an earlier source
+    pub added: usize,
+
+    /// Why the source failed, if it did
+    pub error: Option<String>,
+}
+
+/// Merged result of all sources
+#[derive(Debug, Clone, Default)]
+pub struct CompositeFetch {
+    /// Deduplicated jewels, in source order
+    pub jewels: Vec<TimelessJewel>,
+
+    /// One report per source, in source order
+    pub reports: Vec<SourceReport>,
+}
+
+impl CompositeFetch {
+    /// Reports of the sources that failed
+    pub fn failures(&self) -> impl Iterator<Item = &SourceReport> {
+        self.reports.iter().filter(|report| report.error.is_some())
+    }
+
+    /// Jewels dropped as duplicates
+    pub fn duplicates(&self) -> usize {
+        self.reports.iter().map(|r| r.fetched - r.added).sum()
+    }
+}
+
+/// Fetches from several sources concurrently and merges the results
+///
+/// Jewels are deduplicated with `ItemCollection`, so the first source
+/// providing a jewel keeps it. A failing source is reported instead of
+/// failing the whole fetch.
+#[derive(Default)]
+pub struct CompositeSource {
+    sources: Vec<Box<dyn ItemSource>>,
+}
+
+impl CompositeSource {
+    /// Create a composite without sources
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source
+    pub fn with_source(mut self, source: Box<dyn ItemSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Add a source
+    pub fn push(&mut self, source: Box<dyn ItemSource>) {
+        self.sources.push(source);
+    }
+
+    /// Number of sources
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Whether there are no sources
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Fetch all sources, merging what succeeded
+    pub async fn fetch_all(&self) -> CompositeFetch {
+        let results = join_all(self.sources.iter().map(|source| source.fetch_items())).await;
+
+        let mut collection = ItemCollection::new();
+        let mut reports = Vec::with_capacity(results.len());
+
+        for (source, result) in self.sources.iter().zip(results) {
+            let report = match result {
+                Ok(jewels) => SourceReport {
+                    source: source.source_name(),
+                    fetched: jewels.len(),
+                    added: collection.add_all(jewels),
+                    error: None,
+                },
+                Err(e) => SourceReport {
+                    source: source.source_name(),
+                    fetched: 0,
+                    added: 0,
+                    error: Some(e.to_string()),
+                },
+            };
+            reports.push(report);
+        }
+
+        CompositeFetch {
+            jewels: collection.into_items(),
+            reports,
+        }
+    }
+}
+
+#[async_trait]
+impl ItemSource for CompositeSource {
+    /// Fetch all sources; fails only if every source failed
+    async fn fetch_items(&self) -> Result<Vec<TimelessJewel>, SourceError> {
+        let fetched = self.fetch_all().await;
+
+        if !fetched.reports.is_empty() && fetched.failures().count() == fetched.reports.len() {
+            let errors: Vec<String> = fetched
+                .failures()
+                .map(|r| format!("{}: {}", r.source, r.error.as_deref().unwrap_or_default()))
+                .collect();
+            return Err(SourceError::FetchFailed(errors.join("; ")));
+        }
+
+        for failure in fetched.failures() {
+            warn!(
+                "Skipping {}: {}",
+                failure.source,
+                failure.error.as_deref().unwrap_or_default()
+            );
+        }
+
+        Ok(fetched.jewels)
+    }
+
+    fn source_name(&self) -> String {
+        let names: Vec<String> = self.sources.iter().map(|s| s.source_name()).collect();
+        names.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::{ClipboardTextSource, LocalFileSource};
+
+    /// Source that always fails
+    struct FailingSource;
+
+    #[async_trait]
+    impl ItemSource for FailingSource {
+        async fn fetch_items(&self) -> Result<Vec<TimelessJewel>, SourceError> {
+            Err(SourceError::FetchFailed("stash is private".to_string()))
+        }
+
+        fn source_name(&self) -> String {
+            "Failing".to_string()
+        }
+    }
+
+    fn stash_file() -> Box<dyn ItemSource> {
+        Box::new(LocalFileSource::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/stash_tab.json"
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_partial_success() {
+        let composite = CompositeSource::new()
+            .with_source(stash_file())
+            .with_source(Box::new(FailingSource));
+
+        let fetched = composite.fetch_all().await;
+
+        assert_eq!(fetched.jewels.len(), 2);
+        assert_eq!(fetched.reports.len(), 2);
+        assert_eq!(fetched.reports[0].source, "File stash_tab.json");
+        assert_eq!((fetched.reports[0].fetched, fetched.reports[0].added), (2, 2));
+
+        let failures: Vec<_> = fetched.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].source, "Failing");
+        assert!(failures[0].error.as_ref().unwrap().contains("stash is private"));
+
+        assert_eq!(composite.fetch_items().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_deduplicates_across_sources() {
+        let paste = "Rarity: Unique\nLethal Pride\nTimeless Jewel\n--------\n\
+                     Commanded leadership over 18000 warriors under Kaom";
+        let composite = CompositeSource::new()
+            .with_source(stash_file())
+            .with_source(stash_file())
+            .with_source(Box::new(ClipboardTextSource::new(paste)))
+            .with_source(Box::new(ClipboardTextSource::new(paste)));
+
+        let fetched = composite.fetch_all().await;
+
+        assert_eq!(fetched.jewels.len(), 3);
+        assert_eq!(fetched.reports[1].added, 0);
+        assert_eq!(fetched.reports[2].added, 1);
+        assert_eq!(fetched.duplicates(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_all_sources_failing() {
+        let composite = CompositeSource::new()
+            .with_source(Box::new(FailingSource))
+            .with_source(Box::new(FailingSource));
+
+        let error = composite.fetch_items().await.unwrap_err();
+        assert!(matches!(error, SourceError::FetchFailed(m) if m.contains("Failing: ")));
+
+        assert!(CompositeSource::new().fetch_items().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Download sources for PoB data files
+//!
+//! A `DownloadSource` tells the downloader where else a data file can be
+//! fetched from when its primary URL fails: a GitHub raw mirror, any plain
+//! base URL, or a local directory for offline / air-gapped use.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Where a data file can be fetched from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DownloadSource {
+    /// raw.githubusercontent.com for a repository path
+    GithubRaw {
+        repo: String,
+        branch: String,
+        path: String,
+    },
+
+    /// Plain HTTP(S) base URL; files are fetched from `<base_url>/<name>`
+    Url { base_url: String },
+
+    /// Local directory containing the files
+    LocalDir { path: PathBuf },
+}
+
+/// Resolved location of one file within a source
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceLocation {
+    /// Fetch over HTTP
+    Remote(String),
+
+    /// Copy from disk
+    Local(PathBuf),
+}
+
+impl DownloadSource {
+    /// Location of `file_name` within this source
+    pub fn location(&self, file_name: &str) -> SourceLocation {
+        match self {
+            DownloadSource::GithubRaw { repo, branch, path } => SourceLocation::Remote(format!(
+                "https://raw.githubusercontent.com/{}/{}/{}/{}",
+                repo,
+                branch,
+                path.trim_matches('/'),
+                file_name
+            )),
+            DownloadSource::Url { base_url } => SourceLocation::Remote(format!(
+                "{}/{}",
+                base_url.trim_end_matches('/'),
+                file_name
+            )),
+            DownloadSource::LocalDir { path } => SourceLocation::Local(path.join(file_name)),
+        }
+    }
+}
+
+impl fmt::Display for DownloadSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadSource::GithubRaw { repo, branch, .. } => write!(f, "github:{}@{}", repo, branch),
+            DownloadSource::Url { base_url } => write!(f, "{}", base_url),
+            DownloadSource::LocalDir { path } => write!(f, "local:{}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_locations() {
+        let github = DownloadSource::GithubRaw {
+            repo: "PathOfBuildingCommunity/PathOfBuilding".to_string(),
+            branch: "master".to_string(),
+            path: "/src/Data/TimelessJewelData/".to_string(),
+        };
+        assert_eq!(
+            github.location("LethalPride.zip"),
+            SourceLocation::Remote(
+                "https://raw.githubusercontent.com/PathOfBuildingCommunity/PathOfBuilding/master/src/Data/TimelessJewelData/LethalPride.zip"
+                    .to_string()
+            )
+        );
+
+        let url = DownloadSource::Url {
+            base_url: "https://mirror.example.com/pob/".to_string(),
+        };
+        assert_eq!(
+            url.location("LethalPride.zip"),
+            SourceLocation::Remote("https://mirror.example.com/pob/LethalPride.zip".to_string())
+        );
+
+        let local = DownloadSource::LocalDir {
+            path: PathBuf::from("/mnt/pob-data"),
+        };
+        assert_eq!(
+            local.location("LethalPride.zip"),
+            SourceLocation::Local(PathBuf::from("/mnt/pob-data/LethalPride.zip"))
+        );
+    }
+
+    #[test]
+    fn test_source_serde_roundtrip() {
+        let json = r#"{"type":"local_dir","path":"/mnt/pob-data"}"#;
+        let source: DownloadSource = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            source,
+            DownloadSource::LocalDir {
+                path: PathBuf::from("/mnt/pob-data")
+            }
+        );
+        assert_eq!(serde_json::to_string(&source).unwrap(), json);
+    }
+}
@@ -1,3 +1,164 @@
 //! File-based item source
 
-// TODO: Implement file import
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use poe_item_analyzer_core::items::TimelessJewel;
+use serde_json::Value;
+
+use super::stash_jewels::{StashJewelExtractor, TIMELESS_JEWEL};
+use super::traits::ItemSource;
+use crate::error::SourceError;
+use crate::poe_api::StashItem;
+
+/// Reads timeless jewels from a JSON dump of items on disk
+///
+/// The file holds either an array of stash API items or a whole stash tab
+/// response (an object with an `items` array). Items that aren't timeless
+/// jewels are ignored; malformed entries fail the import.
+pub struct LocalFileSource {
+    path: PathBuf,
+}
+
+impl LocalFileSource {
+    /// Create a source reading `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// File the items are read from
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Parse a JSON dump of items
+    pub fn parse(json: &str) -> Result<Vec<TimelessJewel>, SourceError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| SourceError::ParseError(e.to_string()))?;
+
+        let entries = match value {
+            Value::Array(entries) => entries,
+            Value::Object(mut object) => match object.remove("items") {
+                Some(Value::Array(entries)) => entries,
+                _ => {
+                    return Err(SourceError::ParseError(
+                        "expected an array of items or an object with an \"items\" array"
+                            .to_string(),
+                    ))
+                }
+            },
+            _ => {
+                return Err(SourceError::ParseError(
+                    "expected an array of items".to_string(),
+                ))
+            }
+        };
+
+        let mut jewels = Vec::new();
+        for (index, entry) in entries.into_iter().enumerate() {
+            let item: StashItem = serde_json::from_value(entry)
+                .map_err(|e| SourceError::ParseError(format!("item {}: {}", index, e)))?;
+
+            if item.type_line != TIMELESS_JEWEL {
+                continue;
+            }
+
+            let jewel = StashJewelExtractor::extract_jewel(&item).map_err(|reason| {
+                SourceError::ParseError(format!("item {} ({}): {}", index, item.name, reason))
+            })?;
+            jewels.push(jewel);
+        }
+
+        Ok(jewels)
+    }
+}
+
+#[async_trait]
+impl ItemSource for LocalFileSource {
+    async fn fetch_items(&self) -> Result<Vec<TimelessJewel>, SourceError> {
+        let json = tokio::fs::read_to_string(&self.path).await?;
+
+        Self::parse(&json).map_err(|e| match e {
+            SourceError::ParseError(message) => {
+                SourceError::ParseError(format!("{}: {}", self.path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    fn source_name(&self) -> String {
+        let name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string());
+
+        format!("File {}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poe_item_analyzer_core::items::{Item, JewelType};
+
+    #[tokio::test]
+    async fn test_reads_stash_tab_dump() {
+        let source = LocalFileSource::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/stash_tab.json"
+        ));
+
+        let jewels = source.fetch_items().await.unwrap();
+
+        assert_eq!(jewels.len(), 2);
+        assert_eq!(jewels[0].jewel_type, JewelType::LethalPride);
+        assert_eq!(jewels[0].seed, 18000);
+        assert_eq!(jewels[1].conqueror, "Doryani");
+        assert!(jewels[0].id().starts_with("9e4f0c6d"));
+        assert_eq!(source.source_name(), "File stash_tab.json");
+    }
+
+    #[test]
+    fn test_parses_item_array() {
+        let json = r#"[
+            {"id": "a", "name": "Militant Faith", "typeLine": "Timeless Jewel",
+             "explicitMods": ["Carved to glorify 4500 new faithful converted by High Templar Dominus"]},
+            {"id": "b", "name": "", "typeLine": "Cobalt Jewel"}
+        ]"#;
+
+        let jewels = LocalFileSource::parse(json).unwrap();
+
+        assert_eq!(jewels.len(), 1);
+        assert_eq!(jewels[0].seed, 4500);
+        assert_eq!(jewels[0].conqueror, "Dominus");
+    }
+
+    #[test]
+    fn test_malformed_entries() {
+        // Missing typeLine
+        let error = LocalFileSource::parse(r#"[{"id": "a", "name": "Lethal Pride"}]"#).unwrap_err();
+        assert!(matches!(&error, SourceError::ParseError(m) if m.starts_with("item 0")));
+
+        // Unreadable seed
+        let json = r#"{"items": [
+            {"id": "a", "typeLine": "Cobalt Jewel"},
+            {"id": "b", "name": "Brutal Restraint", "typeLine": "Timeless Jewel",
+             "explicitMods": ["Denoted service of many dekhara"]}
+        ]}"#;
+        let error = LocalFileSource::parse(json).unwrap_err();
+        assert!(matches!(&error, SourceError::ParseError(m) if m.starts_with("item 1 (Brutal Restraint)")));
+
+        assert!(matches!(
+            LocalFileSource::parse("\"not items\""),
+            Err(SourceError::ParseError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        let source = LocalFileSource::new("/nonexistent/items.json");
+
+        assert!(matches!(source.fetch_items().await, Err(SourceError::IoError(_))));
+    }
+}
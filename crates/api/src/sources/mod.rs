@@ -1,6 +1,18 @@
 //! Item sources
 
+pub mod clipboard;
+pub mod composite;
 pub mod public_stash;
 pub mod file;
+pub mod download;
+pub mod stash_jewels;
+pub mod stash_tab;
+pub mod traits;
 
-// TODO: Implement item sources
+pub use clipboard::{ClipboardTextSource, ParsedText, TextBlockError};
+pub use composite::{CompositeFetch, CompositeSource, SourceReport};
+pub use download::{DownloadSource, SourceLocation};
+pub use file::LocalFileSource;
+pub use stash_jewels::{ExtractedJewels, SkipReason, SkippedItem, StashJewelExtractor};
+pub use stash_tab::StashTabSource;
+pub use traits::ItemSource;
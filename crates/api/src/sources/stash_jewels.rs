@@ -0,0 +1,225 @@
+//! Timeless jewels from stash API items
+//!
+//! `StashJewelExtractor` turns the `StashItem`s returned by the stash API
+//! into core `TimelessJewel` values ready for analysis. The seed and
+//! conqueror come from the jewel's first explicit mod, e.g. "Commanded
+//! leadership over 18000 warriors under Kaom".
+
+use std::fmt;
+
+use poe_item_analyzer_core::items::{JewelType, TimelessJewel};
+
+use crate::poe_api::StashItem;
+
+/// Type line shared by all timeless jewels
+pub(crate) const TIMELESS_JEWEL: &str = "Timeless Jewel";
+
+/// An item that was not turned into a jewel
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedItem {
+    /// Item id
+    pub id: String,
+
+    /// Item name and type line (e.g., "Havoc Spiral Large Cluster Jewel")
+    pub name: String,
+
+    /// Why it was skipped
+    pub reason: SkipReason,
+}
+
+/// Why an item was not turned into a jewel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Not a timeless jewel (e.g., a cluster jewel or a replica unique)
+    NotAJewel,
+
+    /// No explicit mod holds a seed, e.g. phrasing from an old league
+    UnparseableSeedLine { text: String },
+
+    /// The seed line names no conqueror of the jewel type
+    UnknownConqueror { text: String },
+
+    /// The seed can't roll on the jewel type
+    SeedOutOfRange { seed: u32 },
+}
+
+impl SkipReason {
+    /// Short description shared by every skip with this reason, for grouping
+    pub fn label(&self) -> &'static str {
+        match self {
+            SkipReason::NotAJewel => "Not a timeless jewel",
+            SkipReason::UnparseableSeedLine { .. } => "Unreadable seed line",
+            SkipReason::UnknownConqueror { .. } => "Unknown conqueror",
+            SkipReason::SeedOutOfRange { .. } => "Seed out of range",
+        }
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::NotAJewel => write!(f, "not a {}", TIMELESS_JEWEL),
+            SkipReason::UnparseableSeedLine { text } => {
+                write!(f, "no seed and conqueror found in \"{}\"", text)
+            }
+            SkipReason::UnknownConqueror { text } => write!(f, "unknown conqueror in \"{}\"", text),
+            SkipReason::SeedOutOfRange { seed } => write!(f, "seed {} out of range", seed),
+        }
+    }
+}
+
+/// Result of extracting jewels from a list of items
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedJewels {
+    /// Timeless jewels found, in item order
+    pub jewels: Vec<TimelessJewel>,
+
+    /// Every other item, with the reason it was skipped
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// Converts stash API items into `TimelessJewel`s
+pub struct StashJewelExtractor;
+
+impl StashJewelExtractor {
+    /// Extract all timeless jewels from `items`
+    ///
+    /// Items that aren't timeless jewels, or whose mods can't be read, are
+    /// listed in `skipped` instead of failing the whole tab.
+    pub fn extract(items: &[StashItem]) -> ExtractedJewels {
+        let mut extracted = ExtractedJewels::default();
+
+        for item in items {
+            match Self::extract_jewel(item) {
+                Ok(jewel) => extracted.jewels.push(jewel),
+                Err(reason) => extracted.skipped.push(SkippedItem {
+                    id: item.id.clone(),
+                    name: format!("{} {}", item.name, item.type_line).trim().to_string(),
+                    reason,
+                }),
+            }
+        }
+
+        extracted
+    }
+
+    /// Convert a single item, or explain why it isn't a usable timeless jewel
+    ///
+    /// Items with another type line, or a name that is no timeless jewel
+    /// (e.g., unidentified), are `NotAJewel`. A mod line with a number
+    /// counts as the seed line even if its conqueror isn't known.
+    pub fn extract_jewel(item: &StashItem) -> Result<TimelessJewel, SkipReason> {
+        if item.type_line != TIMELESS_JEWEL {
+            return Err(SkipReason::NotAJewel);
+        }
+        let jewel_type = JewelType::from_str(&item.name).ok_or(SkipReason::NotAJewel)?;
+
+        let parsed = item
+            .explicit_mods
+            .iter()
+            .find_map(|line| jewel_type.parse_seed_line(line));
+        let Some((seed, conqueror)) = parsed else {
+            let has_number = |line: &&String| {
+                line.split_whitespace().any(|word| word.parse::<u32>().is_ok())
+            };
+            return Err(match item.explicit_mods.iter().find(has_number) {
+                Some(line) => SkipReason::UnknownConqueror { text: line.clone() },
+                None => SkipReason::UnparseableSeedLine {
+                    text: item.explicit_mods.first().cloned().unwrap_or_default(),
+                },
+            });
+        };
+
+        if !jewel_type.is_valid_seed(seed) {
+            return Err(SkipReason::SeedOutOfRange { seed });
+        }
+
+        Ok(TimelessJewel::new(
+            item.id.clone(),
+            jewel_type,
+            seed,
+            conqueror.to_string(),
+            item.to_json(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poe_api::StashResponse;
+    use poe_item_analyzer_core::items::{AnalyzableItem, Item};
+
+    fn fixture_items() -> Vec<StashItem> {
+        let response: StashResponse =
+            serde_json::from_str(include_str!("../../tests/fixtures/stash_jewels.json")).unwrap();
+        response.items
+    }
+
+    #[test]
+    fn test_extract_stash_tab() {
+        let extracted = StashJewelExtractor::extract(&fixture_items());
+
+        assert_eq!(extracted.jewels.len(), 2);
+
+        let lethal_pride = &extracted.jewels[0];
+        assert_eq!(lethal_pride.jewel_type, JewelType::LethalPride);
+        assert_eq!(lethal_pride.seed(), 18000);
+        assert_eq!(lethal_pride.conqueror(), "Kaom");
+        assert!(lethal_pride.id().starts_with("9e4f0c6d"));
+        assert_eq!(lethal_pride.raw_data()["explicitMods"][2], "Historic");
+
+        let glorious_vanity = &extracted.jewels[1];
+        assert_eq!(glorious_vanity.jewel_type, JewelType::GloriousVanity);
+        assert_eq!(glorious_vanity.seed(), 2000);
+        assert_eq!(glorious_vanity.conqueror(), "Doryani");
+        assert_eq!(glorious_vanity.raw_data()["corrupted"], true);
+
+        assert_eq!(extracted.skipped.len(), 2);
+        assert_eq!(extracted.skipped[0].name, "Havoc Spiral Large Cluster Jewel");
+        assert_eq!(extracted.skipped[0].reason, SkipReason::NotAJewel);
+        assert_eq!(extracted.skipped[1].name, "Brutal Restraint Timeless Jewel");
+        assert!(matches!(
+            extracted.skipped[1].reason,
+            SkipReason::UnparseableSeedLine { .. }
+        ));
+    }
+
+    #[test]
+    fn test_skip_reasons() {
+        let response: StashResponse =
+            serde_json::from_str(include_str!("../../tests/fixtures/stash_skips.json")).unwrap();
+        let extracted = StashJewelExtractor::extract(&response.items);
+
+        assert!(extracted.jewels.is_empty());
+        let reasons: Vec<(&str, &SkipReason)> = extracted
+            .skipped
+            .iter()
+            .map(|skipped| (skipped.name.as_str(), &skipped.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("Replica Dragonfang's Flight Onyx Amulet", &SkipReason::NotAJewel),
+                ("Timeless Jewel", &SkipReason::NotAJewel),
+                (
+                    "Militant Faith Timeless Jewel",
+                    &SkipReason::UnparseableSeedLine {
+                        text: "Carved to glorify the new faithful".to_string()
+                    }
+                ),
+                (
+                    "Lethal Pride Timeless Jewel",
+                    &SkipReason::UnknownConqueror {
+                        text: "Commanded leadership over 12000 warriors under Ahuana".to_string()
+                    }
+                ),
+                (
+                    "Elegant Hubris Timeless Jewel",
+                    &SkipReason::SeedOutOfRange { seed: 2010 }
+                ),
+            ]
+        );
+        assert_eq!(extracted.skipped[4].reason.to_string(), "seed 2010 out of range");
+    }
+}
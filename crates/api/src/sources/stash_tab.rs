@@ -0,0 +1,74 @@
+//! Stash tab item source
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use poe_item_analyzer_core::items::TimelessJewel;
+use tracing::warn;
+
+use super::stash_jewels::{ExtractedJewels, SkipReason, StashJewelExtractor};
+use super::traits::ItemSource;
+use crate::error::SourceError;
+use crate::poe_api::PoeApiClient;
+
+/// Imports the timeless jewels in one of an account's stash tabs
+///
+/// The client is shared so several tabs go through the same rate limiter.
+pub struct StashTabSource {
+    client: Arc<PoeApiClient>,
+    account: String,
+    league: String,
+    tab_index: u32,
+    session_id: String,
+}
+
+impl StashTabSource {
+    /// Create a source for the tab at `tab_index`
+    pub fn new(
+        client: Arc<PoeApiClient>,
+        account: impl Into<String>,
+        league: impl Into<String>,
+        tab_index: u32,
+        session_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            client,
+            account: account.into(),
+            league: league.into(),
+            tab_index,
+            session_id: session_id.into(),
+        }
+    }
+
+    /// Fetch the tab and sort its items into jewels and skipped items
+    pub async fn fetch_extracted(&self) -> Result<ExtractedJewels, SourceError> {
+        let items = self
+            .client
+            .get_stash_items(&self.account, &self.league, self.tab_index, &self.session_id)
+            .await?;
+
+        Ok(StashJewelExtractor::extract(&items))
+    }
+}
+
+#[async_trait]
+impl ItemSource for StashTabSource {
+    /// Fetch the tab's timeless jewels
+    ///
+    /// Other items are ignored; timeless jewels whose mods can't be read
+    /// are logged and skipped.
+    async fn fetch_items(&self) -> Result<Vec<TimelessJewel>, SourceError> {
+        let extracted = self.fetch_extracted().await?;
+        for skipped in &extracted.skipped {
+            if skipped.reason != SkipReason::NotAJewel {
+                warn!("Skipping {}: {}", skipped.name, skipped.reason);
+            }
+        }
+
+        Ok(extracted.jewels)
+    }
+
+    fn source_name(&self) -> String {
+        format!("Stash tab {} ({})", self.tab_index, self.league)
+    }
+}
@@ -0,0 +1,19 @@
+//! Item source trait
+
+use async_trait::async_trait;
+use poe_item_analyzer_core::items::TimelessJewel;
+
+use crate::error::SourceError;
+
+/// Somewhere timeless jewels can be imported from
+///
+/// Implemented by stash tabs, local files and other sources so the import
+/// flow doesn't depend on where items come from.
+#[async_trait]
+pub trait ItemSource: Send + Sync {
+    /// Fetch all timeless jewels from this source
+    async fn fetch_items(&self) -> Result<Vec<TimelessJewel>, SourceError>;
+
+    /// Human-readable name of the source (e.g., "Stash tab 3 (Settlers)")
+    fn source_name(&self) -> String;
+}
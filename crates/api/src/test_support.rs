@@ -0,0 +1,161 @@
+//! In-memory fakes for tests and offline mode
+//!
+//! `FakeCommitProvider` answers `CommitProvider` calls from data set up in
+//! advance, so update checking can run without network access.
+
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::error::ApiError;
+use crate::github::{Author, CommitInfo, CommitProvider, GitHubCommit, GitHubFile};
+
+#[derive(Default)]
+struct FakeState {
+    /// Commit history, newest first
+    commits: Vec<GitHubCommit>,
+    files: BTreeMap<String, GitHubFile>,
+    error: Option<String>,
+}
+
+/// `CommitProvider` backed by in-memory data
+#[derive(Default)]
+pub struct FakeCommitProvider {
+    state: Mutex<FakeState>,
+}
+
+impl FakeCommitProvider {
+    /// Create an empty provider (every call fails with "not found")
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `sha` as the latest commit
+    pub fn with_commit(self, sha: &str) -> Self {
+        self.set_commit(sha);
+        self
+    }
+
+    /// Add a file at `path` (e.g., "src/Data/TimelessJewelData/LethalPride.zip")
+    pub fn with_file(self, path: &str, sha: &str, size: u64) -> Self {
+        self.set_file(path, sha, size);
+        self
+    }
+
+    /// Fail every call with `message`
+    pub fn with_error(self, message: &str) -> Self {
+        self.lock().error = Some(message.to_string());
+        self
+    }
+
+    /// Add a new latest commit on top of the history
+    pub fn set_commit(&self, sha: &str) {
+        self.lock().commits.insert(0, fake_commit(sha));
+    }
+
+    /// Add or replace a file
+    pub fn set_file(&self, path: &str, sha: &str, size: u64) {
+        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+        let file = GitHubFile {
+            name,
+            path: path.to_string(),
+            sha: sha.to_string(),
+            size,
+            url: format!("fake://contents/{}", path),
+            download_url: Some(format!("fake://raw/{}", path)),
+            entry_type: "file".to_string(),
+        };
+
+        self.lock().files.insert(path.to_string(), file);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn check_error(&self) -> Result<(), ApiError> {
+        match &self.lock().error {
+            Some(message) => Err(ApiError::ApiError(message.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl CommitProvider for FakeCommitProvider {
+    async fn get_latest_commit(&self, _repo: &str, _path: &str) -> Result<GitHubCommit, ApiError> {
+        self.check_error()?;
+
+        self.lock()
+            .commits
+            .first()
+            .cloned()
+            .ok_or_else(|| ApiError::InvalidResponse("No commits found".to_string()))
+    }
+
+    async fn get_commits_since(
+        &self,
+        _repo: &str,
+        _path: &str,
+        since_sha: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubCommit>, ApiError> {
+        self.check_error()?;
+
+        Ok(self
+            .lock()
+            .commits
+            .iter()
+            .take_while(|commit| commit.sha != since_sha)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_file_info(
+        &self,
+        _repo: &str,
+        path: &str,
+        _branch: &str,
+    ) -> Result<GitHubFile, ApiError> {
+        self.check_error()?;
+
+        self.lock()
+            .files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| ApiError::ApiError(format!("GitHub API error: 404 ({})", path)))
+    }
+
+    async fn list_directory(
+        &self,
+        _repo: &str,
+        path: &str,
+        _branch: &str,
+    ) -> Result<Vec<GitHubFile>, ApiError> {
+        self.check_error()?;
+
+        let dir = path.trim_end_matches('/');
+        Ok(self
+            .lock()
+            .files
+            .values()
+            .filter(|file| file.path.rsplit_once('/').map(|(parent, _)| parent) == Some(dir))
+            .cloned()
+            .collect())
+    }
+}
+
+fn fake_commit(sha: &str) -> GitHubCommit {
+    GitHubCommit {
+        sha: sha.to_string(),
+        commit: CommitInfo {
+            message: format!("Commit {}", sha),
+            author: Author {
+                name: "Fake".to_string(),
+                email: "fake@example.com".to_string(),
+                date: "2025-01-01T00:00:00Z".to_string(),
+            },
+        },
+    }
+}
@@ -3,16 +3,47 @@
 use std::path::PathBuf;
 
 use crate::downloader::DataDownloader;
+use crate::manifest::{DataManifest, DataSource};
+
+fn test_manifest(url: &str) -> DataManifest {
+    DataManifest {
+        data_version: "test".to_string(),
+        poe_league: "Test".to_string(),
+        last_updated: "2025-01-01T00:00:00Z".to_string(),
+        source: DataSource {
+            source_type: "github".to_string(),
+            repo: "test/test".to_string(),
+            branch: "master".to_string(),
+            path: "data".to_string(),
+            url: url.to_string(),
+            mirrors: Vec::new(),
+            raw_url: None,
+            api_url: None,
+        },
+        files: vec![],
+        ignored_versions: Vec::new(),
+        parsed_artifact: None,
+    }
+}
 
 #[test]
 fn test_downloader_creation() {
-    let downloader = DataDownloader::new(
-        "https://example.com/manifest.json".to_string(),
-        PathBuf::from("/tmp/data"),
-    );
+    let downloader = DataDownloader::new(PathBuf::from("/tmp/data"));
+
+    assert_eq!(downloader.target_dir(), &PathBuf::from("/tmp/data"));
+    assert!(downloader.manifest().is_none());
+    assert!(downloader.base_url().is_none());
+}
+
+#[test]
+fn test_downloader_builders() {
+    let downloader = DataDownloader::new(PathBuf::from("/tmp/data"))
+        .with_manifest(test_manifest("https://github.com/test/test"))
+        .with_base_url("https://mirror.example.com/pob");
 
-    // Just verify it can be created without panicking
-    let _ = downloader;
+    assert_eq!(downloader.target_dir(), &PathBuf::from("/tmp/data"));
+    assert_eq!(downloader.manifest().unwrap().data_version, "test");
+    assert_eq!(downloader.base_url(), Some("https://mirror.example.com/pob"));
 }
 
 #[test]
@@ -24,8 +55,9 @@ fn test_downloader_with_different_urls() {
     ];
 
     for url in urls {
-        let downloader = DataDownloader::new(url.to_string(), PathBuf::from("/tmp"));
-        let _ = downloader;
+        let downloader =
+            DataDownloader::new(PathBuf::from("/tmp")).with_manifest(test_manifest(url));
+        assert_eq!(downloader.manifest().unwrap().source.url, url);
     }
 }
 
@@ -38,8 +70,7 @@ fn test_downloader_with_different_paths() {
     ];
 
     for path in paths {
-        let downloader =
-            DataDownloader::new("https://example.com/manifest.json".to_string(), path);
-        let _ = downloader;
+        let downloader = DataDownloader::new(path.clone());
+        assert_eq!(downloader.target_dir(), &path);
     }
 }
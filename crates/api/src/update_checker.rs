@@ -1,9 +1,19 @@
 //! Update checker service for data management
 
-use crate::error::DownloadError;
-use crate::github::GitHubClient;
-use crate::manifest::DataManifest;
+use crate::checksum;
+use crate::disk_space::{self, SpaceProbe};
+use crate::downloader::{CancellationToken, DataDownloader, DownloadEvent};
+use crate::error::{ApiError, DownloadError, FileContext, FileOperation};
+use crate::github::{CommitProvider, CommitSummary, GitHubClient, GitHubCommit, GitHubFile};
+use crate::http_config::HttpConfig;
+use crate::layout::DataLayout;
+use crate::manifest::{DataFile, DataManifest, ManifestDiff, ManifestLock};
+use crate::parser::{LuaParser, ParseEvent, ParseReport, PobDataParser, TREE_VERSION_FILE};
+use crate::validation::{self, ValidationStatus};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
 
 /// Update information
 #[derive(Debug, Clone)]
@@ -22,65 +32,602 @@ pub struct UpdateInfo {
 
     /// Date of the latest commit
     pub commit_date: Option<String>,
+
+    /// Latest version, if the user chose to ignore it (then `available` is false)
+    pub ignored_latest: Option<String>,
+
+    /// Commits between the current and latest version, newest first
+    ///
+    /// Empty when no update is available or the current version is unknown.
+    pub commits: Vec<CommitSummary>,
+
+    /// Files that the update would change (None if no update is available
+    /// or the directory listing couldn't be fetched)
+    pub diff: Option<ManifestDiff>,
+
+    /// Passive tree version of the available data (e.g., "3_25"), if known
+    pub tree_version: Option<String>,
+}
+
+/// Maximum number of commits listed in `UpdateInfo::commits`
+const CHANGELOG_LIMIT: usize = 50;
+
+/// A data file whose upstream blob differs from the manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedFile {
+    /// File name as listed in the manifest
+    pub name: String,
+
+    /// `github_sha` recorded in the manifest (empty if never recorded)
+    pub old_sha: String,
+
+    /// Current blob SHA upstream (part SHAs joined with '+' for split files)
+    pub new_sha: String,
+
+    /// Current size upstream
+    pub size: u64,
+}
+
+/// Stages of `UpdateChecker::perform_update`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateStage {
+    /// Comparing local data with upstream
+    Checking,
+
+    /// Downloading changed files into the staging directory
+    Downloading,
+
+    /// Checking checksums and file contents
+    Verifying,
+
+    /// Re-parsing the data into the LUT artifact
+    Parsing,
+
+    /// Swapping the staged data into place
+    Swapping,
+}
+
+/// Progress events emitted by `UpdateChecker::perform_update`
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateEvent {
+    /// A stage has started
+    StageStarted(UpdateStage),
+
+    /// Download progress within the `Downloading` stage
+    FileProgress(DownloadEvent),
+
+    /// Per-file progress within the `Parsing` stage
+    ParseProgress(ParseEvent),
+
+    /// A stage finished successfully
+    StageFinished(UpdateStage),
+
+    /// The update failed during a stage; previous data is left in place
+    Failed { stage: UpdateStage, error: String },
+
+    /// The update was cancelled during a stage; previous data is left in place
+    Cancelled(UpdateStage),
+}
+
+/// Result of `UpdateChecker::perform_update`
+#[derive(Debug, Clone)]
+pub struct UpdateOutcome {
+    /// Whether anything was changed on disk
+    pub updated: bool,
+
+    /// Installed data version afterwards
+    pub version: String,
+
+    /// Files that were re-downloaded
+    pub changed_files: Vec<String>,
+
+    /// Report from re-parsing (None if the existing artifact was kept)
+    pub parse_report: Option<ParseReport>,
+
+    /// Bytes downloaded for the changed files
+    pub bytes_downloaded: u64,
 }
 
 /// Update checker service
 pub struct UpdateChecker {
-    github_client: GitHubClient,
+    github_client: Box<dyn CommitProvider>,
     manifest_path: PathBuf,
+    cancel: CancellationToken,
+    space_probe: SpaceProbe,
+    layout: Option<DataLayout>,
+    http_config: HttpConfig,
 }
 
 impl UpdateChecker {
     /// Create a new update checker
+    ///
+    /// If `manifest_path` doesn't exist yet (first run), the embedded
+    /// `DataManifest::default_pob` is used until an update writes it.
     pub fn new(manifest_path: PathBuf) -> Self {
         Self {
-            github_client: GitHubClient::new(),
+            github_client: Box::new(GitHubClient::new()),
             manifest_path,
+            cancel: CancellationToken::new(),
+            space_probe: disk_space::available_space,
+            layout: None,
+            http_config: HttpConfig::default(),
         }
     }
 
+    /// Look up free disk space with `probe` instead of asking the filesystem
+    pub fn with_space_probe(mut self, probe: SpaceProbe) -> Self {
+        self.space_probe = probe;
+        self
+    }
+
+    /// Use a preconfigured GitHub client
+    pub fn with_github_client(self, client: GitHubClient) -> Self {
+        self.with_commit_provider(Box::new(client))
+    }
+
+    /// Install new data versions next to the current one in `layout`
+    ///
+    /// `perform_update` then leaves the directories it was given alone when
+    /// the upstream version changed: the new version gets its own `raw` and
+    /// `parsed` directories and manifest, named by `UpdateOutcome::version`.
+    /// Updates that keep the version (e.g., repairing edited files) still
+    /// replace the given directory.
+    pub fn with_layout(mut self, layout: DataLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Use any commit provider (e.g., `FakeCommitProvider` for offline use)
+    pub fn with_commit_provider(mut self, provider: Box<dyn CommitProvider>) -> Self {
+        self.github_client = provider;
+        self
+    }
+
+    /// Download files with the timeouts, pooling and proxy of `config`
+    ///
+    /// Only affects downloads; pass a `GitHubClient` built with the same
+    /// config to `with_github_client` for the update checks themselves.
+    pub fn with_http_config(mut self, config: HttpConfig) -> Self {
+        self.http_config = config;
+        self
+    }
+
+    /// Use a cancellation token to abort `perform_update` from another task
+    /// or thread
+    ///
+    /// Cancelling stops in-flight downloads and is checked between stages,
+    /// so a running parse finishes first. The staging copy is removed and the
+    /// swap skipped; `perform_update` returns `DownloadError::Cancelled`.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
     /// Check if updates are available
     pub async fn check_for_updates(&self) -> Result<UpdateInfo, DownloadError> {
         // Load local manifest
-        let manifest = DataManifest::load_from_file(&self.manifest_path)
-            .map_err(|e| DownloadError::InvalidManifest(e.to_string()))?;
+        let manifest = self.load_manifest()?;
 
         let current_version = manifest.data_version.clone();
 
         // Check GitHub for latest commit
-        let latest_commit = self
-            .github_client
-            .get_latest_commit(&manifest.source.repo, &manifest.source.path)
-            .await
-            .map_err(|e| DownloadError::DownloadFailed(e.to_string()))?;
+        let latest_commit = self.latest_commit(&manifest).await?;
 
         let latest_version = latest_commit.sha.clone();
-        let available = current_version != latest_version && current_version != "pob-unknown";
+        let newer = current_version != latest_version && current_version != "pob-unknown";
+        let ignored = newer && manifest.is_ignored(&latest_version);
+        let available = newer && !ignored;
+
+        // The changelog is a nice-to-have; don't fail the check without it
+        let commits = if available {
+            match self
+                .github_client
+                .get_commits_since(
+                    &manifest.source.repo,
+                    &manifest.source.path,
+                    &current_version,
+                    CHANGELOG_LIMIT,
+                )
+                .await
+            {
+                Ok(commits) => commits.iter().map(CommitSummary::from).collect(),
+                Err(e) => {
+                    warn!("Could not fetch changelog: {}", e);
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let diff = if available {
+            match self.fetch_listing(&manifest).await {
+                Ok(listing) => {
+                    let mut diff = manifest.diff(&upstream_manifest(&manifest, &listing));
+                    if diff.unknown_sizes() > 0 {
+                        let data_dir = self.manifest_path.parent().unwrap_or(Path::new("."));
+                        match DataDownloader::new(data_dir.to_path_buf())
+                            .with_http_config(&self.http_config)
+                        {
+                            Ok(downloader) => downloader.resolve_sizes(&mut diff).await,
+                            Err(e) => warn!("Could not look up file sizes: {}", e),
+                        }
+                    }
+                    Some(diff)
+                }
+                Err(e) => {
+                    warn!("Could not list changed files: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let tree_version = if available {
+            upstream_tree_version(&manifest).await
+        } else {
+            None
+        };
 
         Ok(UpdateInfo {
             available,
             current_version,
-            latest_version: if available {
-                Some(latest_version)
-            } else {
-                None
-            },
+            latest_version: available.then(|| latest_version.clone()),
             commit_message: Some(latest_commit.commit.message),
             commit_date: Some(latest_commit.commit.author.date),
+            ignored_latest: ignored.then_some(latest_version),
+            commits,
+            diff,
+            tree_version,
+        })
+    }
+
+    /// Download, verify, re-parse and install the latest data in one call
+    ///
+    /// Changed files are downloaded into a staging copy of `data_dir`, checked
+    /// against the manifest checksums and content validation, and parsed into
+    /// a new LUT artifact. Only then is the staging directory swapped into
+    /// place, the artifact moved to `parsed_output_path` and the manifest's
+    /// `data_version` / `last_updated` bumped. Any failure before the swap
+    /// removes the staging copy and leaves the previous data untouched.
+    /// Before anything is copied, the free space on `data_dir`'s volume is
+    /// checked (`DownloadError::InsufficientSpace`).
+    ///
+    /// With a layout (see `with_layout`), a new version is installed into its
+    /// own directories instead, keeping the artifact's file name.
+    ///
+    /// Re-parsing is skipped when no data file changed and the manifest's
+    /// `parsed_artifact` record matches the file at `parsed_output_path`.
+    /// After a successful update the record describes the new artifact.
+    ///
+    /// A failure ends with `UpdateEvent::Failed`, a cancellation (see
+    /// `with_cancellation`) with `UpdateEvent::Cancelled`.
+    pub async fn perform_update<F>(
+        &self,
+        data_dir: &Path,
+        parsed_output_path: &Path,
+        progress: F,
+    ) -> Result<UpdateOutcome, DownloadError>
+    where
+        F: Fn(UpdateEvent),
+    {
+        // A mutex rather than a cell keeps the future `Send`
+        let stage = Mutex::new(UpdateStage::Checking);
+        let current_stage = || *stage.lock().unwrap_or_else(|e| e.into_inner());
+        let tracked = |event: UpdateEvent| {
+            if let UpdateEvent::StageStarted(started) = event {
+                *stage.lock().unwrap_or_else(|e| e.into_inner()) = started;
+            }
+            progress(event)
+        };
+
+        let result = self
+            .run_update(data_dir, parsed_output_path, &tracked)
+            .await;
+
+        match &result {
+            Err(DownloadError::Cancelled) => progress(UpdateEvent::Cancelled(current_stage())),
+            Err(e) => progress(UpdateEvent::Failed {
+                stage: current_stage(),
+                error: e.to_string(),
+            }),
+            Ok(_) => {}
+        }
+        result
+    }
+
+    /// The stages of `perform_update`
+    async fn run_update<F>(
+        &self,
+        data_dir: &Path,
+        parsed_output_path: &Path,
+        progress: &F,
+    ) -> Result<UpdateOutcome, DownloadError>
+    where
+        F: Fn(UpdateEvent),
+    {
+        let manifest = self.load_manifest()?;
+
+        progress(UpdateEvent::StageStarted(UpdateStage::Checking));
+
+        let latest_commit = self.latest_commit(&manifest).await?;
+        let changed = match self.fetch_listing(&manifest).await {
+            Ok(listing) => {
+                let diff = manifest.diff(&upstream_manifest(&manifest, &listing));
+                if !diff.is_empty() {
+                    info!("Upstream data changes: {}", diff);
+                }
+                changed_files(&manifest, &listing, data_dir).await
+            }
+            Err(e) => {
+                warn!("Per-file update check failed, refreshing all files: {}", e);
+                all_files_changed(&manifest)
+            }
+        };
+
+        let version_changed = manifest.data_version != latest_commit.sha;
+        let artifact_exists = artifact_is_current(&manifest, data_dir, parsed_output_path)?;
+
+        // The tree version file lives outside the listed directory, so the
+        // listing never reports it changed; fetch it with every new version
+        let mut changed = changed;
+        if version_changed && !changed.iter().any(|c| c.name == TREE_VERSION_FILE) {
+            if let Some(file) = manifest.files.iter().find(|f| f.name == TREE_VERSION_FILE) {
+                changed.push(ChangedFile {
+                    name: file.name.clone(),
+                    old_sha: file.github_sha.clone(),
+                    new_sha: file.github_sha.clone(),
+                    size: file.size,
+                });
+            }
+        }
+
+        progress(UpdateEvent::StageFinished(UpdateStage::Checking));
+
+        if !version_changed && changed.is_empty() && artifact_exists {
+            return Ok(UpdateOutcome {
+                updated: false,
+                version: manifest.data_version,
+                changed_files: Vec::new(),
+                parse_report: None,
+                bytes_downloaded: 0,
+            });
+        }
+
+        let reparse = !changed.is_empty() || !artifact_exists;
+
+        // Staging holds a copy of the data directory plus the changed files,
+        // and the new artifact is written before the old one is replaced
+        let download_bytes =
+            dir_files_size(data_dir) + changed.iter().map(|c| c.size).sum::<u64>();
+        let parsed_bytes = match std::fs::metadata(parsed_output_path) {
+            _ if !reparse => 0,
+            Ok(metadata) => metadata.len(),
+            Err(_) => disk_space::estimated_artifact_size(download_bytes),
+        };
+        disk_space::ensure_space(
+            self.space_probe,
+            data_dir,
+            disk_space::required_space(download_bytes, parsed_bytes),
+        )?;
+
+        self.check_cancelled()?;
+
+        // A new version in a layout gets directories of its own
+        let (target_dir, artifact_path, manifest_path) = match &self.layout {
+            Some(layout) if version_changed => {
+                let artifact_name = parsed_output_path.file_name().unwrap_or_default();
+                let parsed_dir = layout.parsed_dir(&latest_commit.sha);
+                std::fs::create_dir_all(&parsed_dir)
+                    .file_context(FileOperation::Write, &parsed_dir)?;
+                (
+                    layout.raw_dir(&latest_commit.sha),
+                    parsed_dir.join(artifact_name),
+                    layout.manifest_path(&latest_commit.sha),
+                )
+            }
+            _ => (
+                data_dir.to_path_buf(),
+                parsed_output_path.to_path_buf(),
+                self.manifest_path.clone(),
+            ),
+        };
+
+        // Shared with the blocking pool, where the file work runs (see
+        // `checksum::run_blocking`) so it doesn't hold up other tasks
+        let downloader = Arc::new(
+            DataDownloader::new(target_dir.clone())
+                .with_http_config(&self.http_config)?
+                .with_cancellation(self.cancel.clone()),
+        );
+        let staging_dir = downloader.staging_path();
+        let staged_artifact = sibling_temp_path(&artifact_path);
+
+        let prepared = async {
+            progress(UpdateEvent::StageStarted(UpdateStage::Downloading));
+            let (source, staging) = (data_dir.to_path_buf(), staging_dir.clone());
+            checksum::run_blocking(move || copy_dir_files(&source, &staging)).await?;
+
+            let report = downloader
+                .sync_changed(&manifest, &staging_dir, &changed, |event| {
+                    progress(UpdateEvent::FileProgress(event))
+                })
+                .await?;
+
+            // The error already names the file
+            if let Some((_, error)) = report.failed.first() {
+                return Err(DownloadError::DownloadFailed(error.clone()));
+            }
+            progress(UpdateEvent::StageFinished(UpdateStage::Downloading));
+            self.check_cancelled()?;
+
+            progress(UpdateEvent::StageStarted(UpdateStage::Verifying));
+            verify_staged_files(&manifest, &staging_dir).await?;
+            progress(UpdateEvent::StageFinished(UpdateStage::Verifying));
+            self.check_cancelled()?;
+
+            progress(UpdateEvent::StageStarted(UpdateStage::Parsing));
+            let parse_report = if reparse {
+                // The parse sends its progress back to be reported from here
+                let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+                let (source, output) = (staging_dir.clone(), staged_artifact.clone());
+                let parsing = checksum::run_blocking(move || {
+                    let (lut_data, report) =
+                        PobDataParser::parse_directory_with_progress(&source, |event| {
+                            let _ = sender.send(event);
+                        })?;
+                    PobDataParser::save_to_json(&lut_data, &output)?;
+                    Ok(report)
+                });
+                let forwarding = async {
+                    while let Some(event) = receiver.recv().await {
+                        progress(UpdateEvent::ParseProgress(event));
+                    }
+                };
+                let (report, ()) = tokio::join!(parsing, forwarding);
+                Some(report?)
+            } else {
+                None
+            };
+            progress(UpdateEvent::StageFinished(UpdateStage::Parsing));
+            self.check_cancelled()?;
+
+            Ok((parse_report, report.bytes_downloaded))
+        }
+        .await;
+
+        let (parse_report, bytes_downloaded) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&staging_dir);
+                let _ = std::fs::remove_file(&staged_artifact);
+                // Don't leave an empty directory for a version never installed
+                if manifest_path != self.manifest_path && !manifest_path.exists() {
+                    if let Some(version_dir) = manifest_path.parent() {
+                        let _ = std::fs::remove_dir_all(version_dir);
+                    }
+                }
+                return Err(e);
+            }
+        };
+
+        progress(UpdateEvent::StageStarted(UpdateStage::Swapping));
+
+        let swapping = Arc::clone(&downloader);
+        let staging = staging_dir.clone();
+        let swapped = checksum::run_blocking(move || swapping.swap_into_place(&staging)).await;
+        if let Err(e) = swapped {
+            let _ = std::fs::remove_file(&staged_artifact);
+            return Err(e);
+        }
+
+        let (artifact, previous) = (artifact_path.clone(), parsed_output_path.to_path_buf());
+        let parse_report = checksum::run_blocking(move || {
+            if let Some(report) = &parse_report {
+                std::fs::rename(&staged_artifact, &artifact)
+                    .file_context(FileOperation::Write, &artifact)?;
+                report.save_to_json(&ParseReport::sidecar_path(&artifact))?;
+            } else if artifact != previous {
+                std::fs::copy(&previous, &artifact).file_context(FileOperation::Write, &artifact)?;
+            }
+            Ok(parse_report)
+        })
+        .await?;
+
+        // The manifest may have been edited since it was loaded (e.g. by
+        // `mark_files_updated`), so the update is applied to a fresh copy
+        let install = |manifest: &mut DataManifest| {
+            record_changed_files(manifest, &changed);
+            manifest.set_installed_version(latest_commit.sha.clone());
+            manifest.record_parsed_artifact(&target_dir, &artifact_path, "json")
+        };
+        if manifest_path == self.manifest_path {
+            self.edit_manifest(install)?;
+        } else {
+            // A new version's manifest starts from the current one
+            let _lock = self.lock_manifest()?;
+            let mut manifest = self.load_manifest()?;
+            install(&mut manifest)?;
+
+            let _new_lock = lock_manifest(&manifest_path)?;
+            save_manifest(&manifest_path, &manifest)?;
+        }
+
+        progress(UpdateEvent::StageFinished(UpdateStage::Swapping));
+
+        Ok(UpdateOutcome {
+            updated: true,
+            version: latest_commit.sha,
+            changed_files: changed.into_iter().map(|c| c.name).collect(),
+            parse_report,
+            bytes_downloaded,
+        })
+    }
+
+    /// `DownloadError::Cancelled` once the cancellation token has fired
+    fn check_cancelled(&self) -> Result<(), DownloadError> {
+        if self.cancel.is_cancelled() {
+            return Err(DownloadError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Latest upstream commit for the manifest's data path
+    async fn latest_commit(&self, manifest: &DataManifest) -> Result<GitHubCommit, DownloadError> {
+        self.github_client
+            .get_latest_commit(&manifest.source.repo, &manifest.source.path)
+            .await
+            .map_err(github_error)
+    }
+
+    /// Find data files whose upstream blob SHA differs from the manifest
+    ///
+    /// Uses a single directory listing rather than the latest commit, so a
+    /// change to one file doesn't mark every file as outdated. The blob SHA
+    /// of each file in `data_dir` is computed locally and compared with the
+    /// listing, so files edited or replaced on disk are caught too. Split
+    /// files are compared by their recorded `github_sha`. Files missing from
+    /// `data_dir` are reported as well. Files not found upstream are skipped.
+    pub async fn check_file_updates(
+        &self,
+        data_dir: &Path,
+    ) -> Result<Vec<ChangedFile>, DownloadError> {
+        let manifest = self.load_manifest()?;
+        let listing = self.fetch_listing(&manifest).await?;
+
+        Ok(changed_files(&manifest, &listing, data_dir).await)
+    }
+
+    /// Upstream directory listing for the manifest's data path
+    async fn fetch_listing(&self, manifest: &DataManifest) -> Result<Vec<GitHubFile>, DownloadError> {
+        self.github_client
+            .list_directory(
+                &manifest.source.repo,
+                &manifest.source.path,
+                &manifest.source.branch,
+            )
+            .await
+            .map_err(github_error)
+    }
+
+    /// Record the upstream SHAs of files that have been re-downloaded
+    pub fn mark_files_updated(&self, updated: &[ChangedFile]) -> Result<(), DownloadError> {
+        self.edit_manifest(|manifest| {
+            record_changed_files(manifest, updated);
+            Ok(())
         })
     }
 
     /// Get current data version
     pub fn get_current_version(&self) -> Result<String, DownloadError> {
-        let manifest = DataManifest::load_from_file(&self.manifest_path)
-            .map_err(|e| DownloadError::InvalidManifest(e.to_string()))?;
+        let manifest = self.load_manifest()?;
         Ok(manifest.data_version)
     }
 
     /// Check if data files exist locally
     pub fn data_exists(&self, data_dir: &Path) -> Result<bool, DownloadError> {
-        let manifest = DataManifest::load_from_file(&self.manifest_path)
-            .map_err(|e| DownloadError::InvalidManifest(e.to_string()))?;
+        let manifest = self.load_manifest()?;
 
         // Check if all required files exist
         for file in manifest.required_files() {
@@ -93,16 +640,34 @@ impl UpdateChecker {
         Ok(true)
     }
 
-    /// Get list of missing files
+    /// Check that all required files exist and match their manifest checksums
+    ///
+    /// Strict version of `data_exists`: every file is hashed (see
+    /// `checksum::verify_directory`), so it is much slower.
+    pub fn data_exists_strict(&self, data_dir: &Path) -> Result<bool, DownloadError> {
+        let manifest = self.load_manifest()?;
+        let required: Vec<DataFile> = manifest.required_files().into_iter().cloned().collect();
+
+        let verification = checksum::verify_directory(data_dir, &required)?;
+
+        for name in verification.mismatched() {
+            warn!("{} doesn't match the manifest", name);
+        }
+
+        Ok(verification.is_ok())
+    }
+
+    /// Get list of required files that are missing or don't match the manifest
+    ///
+    /// Uses the same size/checksum check as `DataDownloader::sync`, which
+    /// should be used to actually fetch them.
     pub fn get_missing_files(&self, data_dir: &Path) -> Result<Vec<String>, DownloadError> {
-        let manifest = DataManifest::load_from_file(&self.manifest_path)
-            .map_err(|e| DownloadError::InvalidManifest(e.to_string()))?;
+        let manifest = self.load_manifest()?;
 
         let mut missing = Vec::new();
 
         for file in manifest.required_files() {
-            let file_path = data_dir.join(&file.name);
-            if !file_path.exists() {
+            if !file.is_up_to_date(data_dir)? {
                 missing.push(file.name.clone());
             }
         }
@@ -110,17 +675,330 @@ impl UpdateChecker {
         Ok(missing)
     }
 
-    /// Update manifest with new version
+    /// Skip `version` in future update checks until a newer one appears
+    pub fn ignore_version(&self, version: &str) -> Result<(), DownloadError> {
+        self.edit_ignored(|ignored| {
+            if !ignored.iter().any(|v| v == version) {
+                ignored.push(version.to_string());
+            }
+        })
+    }
+
+    /// Stop ignoring `version`
+    pub fn unignore_version(&self, version: &str) -> Result<(), DownloadError> {
+        self.edit_ignored(|ignored| ignored.retain(|v| v != version))
+    }
+
+    fn edit_ignored<F>(&self, edit: F) -> Result<(), DownloadError>
+    where
+        F: FnOnce(&mut Vec<String>),
+    {
+        self.edit_manifest(|manifest| {
+            edit(&mut manifest.ignored_versions);
+            Ok(())
+        })
+    }
+
+    /// Load, change and save the manifest under its lock
+    ///
+    /// The manifest is read again after locking, so changes other processes
+    /// saved in the meantime are kept.
+    fn edit_manifest<F>(&self, edit: F) -> Result<(), DownloadError>
+    where
+        F: FnOnce(&mut DataManifest) -> Result<(), DownloadError>,
+    {
+        let _lock = self.lock_manifest()?;
+        let mut manifest = self.load_manifest()?;
+
+        edit(&mut manifest)?;
+
+        self.save_manifest(&manifest)
+    }
+
+    /// Lock the manifest against concurrent changes (see `DataManifest::lock`)
+    fn lock_manifest(&self) -> Result<ManifestLock, DownloadError> {
+        lock_manifest(&self.manifest_path)
+    }
+
+    /// Load the manifest, falling back to `DataManifest::default_pob` if missing
+    fn load_manifest(&self) -> Result<DataManifest, DownloadError> {
+        if !self.manifest_path.exists() {
+            return Ok(DataManifest::default_pob());
+        }
+
+        DataManifest::load_from_file(&self.manifest_path)
+            .map_err(|e| DownloadError::InvalidManifest(e.to_string()))
+    }
+
+    /// Save the manifest atomically, creating its directory on first use
+    fn save_manifest(&self, manifest: &DataManifest) -> Result<(), DownloadError> {
+        save_manifest(&self.manifest_path, manifest)
+    }
+
+    /// Update manifest with new version (clearing ignored versions)
     pub fn update_manifest_version(&self, new_version: String) -> Result<(), DownloadError> {
-        let mut manifest = DataManifest::load_from_file(&self.manifest_path)
-            .map_err(|e| DownloadError::InvalidManifest(e.to_string()))?;
+        self.edit_manifest(|manifest| {
+            manifest.set_installed_version(new_version);
+            Ok(())
+        })
+    }
+}
+
+/// Record the new upstream SHAs of `changed` files in `manifest`
+fn record_changed_files(manifest: &mut DataManifest, changed: &[ChangedFile]) {
+    for change in changed {
+        if let Some(file) = manifest.files.iter_mut().find(|f| f.name == change.name) {
+            file.github_sha = change.new_sha.clone();
+        }
+    }
+}
+
+/// Index the files of a directory listing by name
+fn index_listing(listing: &[GitHubFile]) -> HashMap<&str, &GitHubFile> {
+    listing
+        .iter()
+        .filter(|entry| entry.is_file())
+        .map(|entry| (entry.name.as_str(), entry))
+        .collect()
+}
+
+/// Manifest files whose local copy differs from the upstream blob
+async fn changed_files(
+    manifest: &DataManifest,
+    listing: &[GitHubFile],
+    data_dir: &Path,
+) -> Vec<ChangedFile> {
+    let remote = index_listing(listing);
+    let mut changed = Vec::new();
+
+    for file in &manifest.files {
+        let Some((new_sha, size)) = remote_version(file, &remote) else {
+            continue;
+        };
+
+        if !local_matches(file, &new_sha, data_dir).await {
+            changed.push(ChangedFile {
+                name: file.name.clone(),
+                old_sha: file.github_sha.clone(),
+                new_sha,
+                size,
+            });
+        }
+    }
+
+    changed
+}
+
+/// Whether the copy of `file` in `data_dir` has the upstream blob SHA
+async fn local_matches(file: &DataFile, remote_sha: &str, data_dir: &Path) -> bool {
+    let path = data_dir.join(&file.name);
+    if !path.exists() {
+        return false;
+    }
+
+    // Parts aren't kept on disk, so the joined file can't be hashed per part
+    if file.is_split() {
+        return remote_sha == file.github_sha;
+    }
+
+    match checksum::calculate_git_blob_sha_async(&path).await {
+        Ok(local_sha) => local_sha == remote_sha,
+        Err(e) => {
+            warn!("Could not hash {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// The installed manifest with changed blobs taken from the listing
+///
+/// Files whose blob SHA changed get the upstream SHA and size; their sha256
+/// is unknown until they're downloaded, so it's cleared. Other files are
+/// kept as they are.
+fn upstream_manifest(manifest: &DataManifest, listing: &[GitHubFile]) -> DataManifest {
+    let remote = index_listing(listing);
+    let mut upstream = manifest.clone();
+
+    for file in &mut upstream.files {
+        if let Some((sha, size)) = remote_version(file, &remote) {
+            if sha != file.github_sha {
+                file.github_sha = sha;
+                file.sha256.clear();
+                file.size = size;
+            }
+        }
+    }
+
+    upstream
+}
+
+/// Tree version named by the upstream copy of the manifest's tree version file
+///
+/// Only informational, so any failure just leaves it unknown.
+async fn upstream_tree_version(manifest: &DataManifest) -> Option<String> {
+    let file = manifest.files.iter().find(|f| f.name == TREE_VERSION_FILE)?;
+    let response = match reqwest::get(&file.url).await {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            warn!("Could not fetch {}: {}", TREE_VERSION_FILE, response.status());
+            return None;
+        }
+        Err(e) => {
+            warn!("Could not fetch {}: {}", TREE_VERSION_FILE, e);
+            return None;
+        }
+    };
+
+    LuaParser::parse_tree_version(&response.text().await.ok()?)
+}
 
-        manifest.data_version = new_version;
-        manifest.last_updated = chrono::Utc::now().to_rfc3339();
+/// Whether `artifact_path` is the recorded, up-to-date parse of the data
+fn artifact_is_current(
+    manifest: &DataManifest,
+    data_dir: &Path,
+    artifact_path: &Path,
+) -> Result<bool, DownloadError> {
+    let recorded_here = manifest
+        .parsed_artifact
+        .as_ref()
+        .is_some_and(|artifact| artifact.resolve(data_dir) == artifact_path);
 
-        manifest
-            .save_to_file(&self.manifest_path)
-            .map_err(|e| DownloadError::DownloadFailed(e.to_string()))
+    Ok(recorded_here && !manifest.needs_reparse(data_dir)?)
+}
+
+/// Treat every manifest file as changed (used when the listing is unavailable)
+fn all_files_changed(manifest: &DataManifest) -> Vec<ChangedFile> {
+    manifest
+        .files
+        .iter()
+        .map(|file| ChangedFile {
+            name: file.name.clone(),
+            old_sha: file.github_sha.clone(),
+            new_sha: String::new(),
+            size: file.size,
+        })
+        .collect()
+}
+
+/// Exclusive lock on the manifest at `path`
+fn lock_manifest(path: &Path) -> Result<ManifestLock, DownloadError> {
+    DataManifest::lock(path).file_context(FileOperation::Write, path)
+}
+
+/// Save `manifest` atomically to `path`, creating its directory on first use
+fn save_manifest(path: &Path, manifest: &DataManifest) -> Result<(), DownloadError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).file_context(FileOperation::Write, parent)?;
+    }
+
+    manifest.save_atomic(path).file_context(FileOperation::Write, path)
+}
+
+/// Copy the files of `source` into a new `target` directory
+fn copy_dir_files(source: &Path, target: &Path) -> Result<(), DownloadError> {
+    std::fs::create_dir_all(target).file_context(FileOperation::Write, target)?;
+
+    if !source.exists() {
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(source).file_context(FileOperation::Read, source)? {
+        let entry = entry.file_context(FileOperation::Read, source)?;
+        let path = entry.path();
+        if !entry.file_type().file_context(FileOperation::Read, &path)?.is_file() {
+            continue;
+        }
+
+        std::fs::copy(&path, target.join(entry.file_name()))
+            .file_context(FileOperation::Read, &path)?;
+    }
+
+    Ok(())
+}
+
+/// Total size of the files `copy_dir_files` copies from `dir`
+fn dir_files_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok()?.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Check every required file in the staging directory
+async fn verify_staged_files(
+    manifest: &DataManifest,
+    staging_dir: &Path,
+) -> Result<(), DownloadError> {
+    for file in manifest.required_files() {
+        if !file.is_up_to_date_async(staging_dir).await? {
+            return Err(DownloadError::file_failed(
+                FileOperation::Download,
+                &file.name,
+                "it does not match the manifest",
+            ));
+        }
+
+        let result = validation::validate_file(&staging_dir.join(&file.name));
+        let reason = match result.status {
+            ValidationStatus::Valid => continue,
+            ValidationStatus::Missing => "file is missing".to_string(),
+            ValidationStatus::TooSmall { bytes } => format!("only {} bytes", bytes),
+            ValidationStatus::InvalidContent(reason) => reason,
+        };
+
+        return Err(DownloadError::file_failed(
+            FileOperation::Download,
+            &file.name,
+            format!("failed validation: {}", reason),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Temporary path next to `path` for writing before an atomic rename
+fn sibling_temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "lut_data.json".to_string());
+
+    path.with_file_name(format!(".{}.tmp", name))
+}
+
+/// Upstream SHA and size of a manifest file, if it is in the listing
+fn remote_version(file: &DataFile, remote: &HashMap<&str, &GitHubFile>) -> Option<(String, u64)> {
+    if !file.is_split() {
+        return remote
+            .get(file.name.as_str())
+            .map(|entry| (entry.sha.clone(), entry.size));
+    }
+
+    let mut shas = Vec::with_capacity(file.parts.len());
+    let mut size = 0;
+
+    for part in &file.parts {
+        let part_name = part.url.rsplit('/').next()?;
+        let entry = remote.get(part_name)?;
+        shas.push(entry.sha.as_str());
+        size += entry.size;
+    }
+
+    Some((shas.join("+"), size))
+}
+
+/// Convert a GitHub API error, keeping rate limiting distinct
+fn github_error(error: ApiError) -> DownloadError {
+    match error {
+        ApiError::RateLimited { message, reset_at } => {
+            DownloadError::RateLimited { message, reset_at }
+        }
+        other => DownloadError::DownloadFailed(other.to_string()),
     }
 }
 
@@ -128,7 +1006,9 @@ impl UpdateChecker {
 mod tests {
     use super::*;
     use crate::manifest::{DataFile, DataSource};
+    use crate::test_support::FakeCommitProvider;
     use std::fs;
+    use std::sync::Arc;
     use tempfile::TempDir;
 
     fn create_test_manifest(temp_dir: &TempDir) -> PathBuf {
@@ -144,6 +1024,9 @@ mod tests {
                 branch: "master".to_string(),
                 path: "src/Data/TimelessJewelData".to_string(),
                 url: "https://github.com/PathOfBuildingCommunity/PathOfBuilding".to_string(),
+                mirrors: Vec::new(),
+                raw_url: None,
+                api_url: None,
             },
             files: vec![
                 DataFile {
@@ -154,6 +1037,7 @@ mod tests {
                     size: 0,
                     required: true,
                     description: "Test file 1".to_string(),
+                    parts: Vec::new(),
                 },
                 DataFile {
                     name: "test2.zip".to_string(),
@@ -163,8 +1047,11 @@ mod tests {
                     size: 0,
                     required: true,
                     description: "Test file 2".to_string(),
+                    parts: Vec::new(),
                 },
             ],
+            ignored_versions: Vec::new(),
+            parsed_artifact: None,
         };
 
         manifest.save_to_file(&manifest_path).unwrap();
@@ -219,6 +1106,30 @@ mod tests {
         assert!(!exists); // Not all files exist
     }
 
+    #[test]
+    fn test_data_exists_strict_checks_checksums() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let mut manifest = DataManifest::load_from_file(&manifest_path).unwrap();
+        manifest.files[0].sha256 = checksum::calculate_sha256_bytes(b"test data 1");
+        manifest.files[1].sha256 = checksum::calculate_sha256_bytes(b"test data 2");
+        manifest.save_to_file(&manifest_path).unwrap();
+
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir_all(&data_dir).unwrap();
+        fs::write(data_dir.join("test1.zip"), b"test data 1").unwrap();
+        fs::write(data_dir.join("test2.zip"), b"corrupted").unwrap();
+
+        let checker = UpdateChecker::new(manifest_path);
+
+        assert!(checker.data_exists(&data_dir).unwrap());
+        assert!(!checker.data_exists_strict(&data_dir).unwrap());
+
+        fs::write(data_dir.join("test2.zip"), b"test data 2").unwrap();
+        assert!(checker.data_exists_strict(&data_dir).unwrap());
+    }
+
     #[test]
     fn test_data_exists_all() {
         let temp_dir = TempDir::new().unwrap();
@@ -255,6 +1166,283 @@ mod tests {
         assert_eq!(missing[0], "test2.zip");
     }
 
+    #[test]
+    fn test_get_missing_files_detects_size_mismatch() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        // Record the expected size of test1.zip in the manifest
+        let mut manifest = DataManifest::load_from_file(&manifest_path).unwrap();
+        manifest.files[0].size = 9;
+        manifest.save_to_file(&manifest_path).unwrap();
+
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir_all(&data_dir).unwrap();
+
+        fs::write(data_dir.join("test1.zip"), b"truncated data").unwrap();
+        fs::write(data_dir.join("test2.zip"), b"test data 2").unwrap();
+
+        let checker = UpdateChecker::new(manifest_path);
+        let missing = checker.get_missing_files(&data_dir).unwrap();
+
+        assert_eq!(missing, vec!["test1.zip"]);
+    }
+
+    fn fake_checker(manifest_path: PathBuf, provider: FakeCommitProvider) -> UpdateChecker {
+        UpdateChecker::new(manifest_path).with_commit_provider(Box::new(provider))
+    }
+
+    #[tokio::test]
+    async fn test_check_for_updates_up_to_date() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let checker = fake_checker(
+            manifest_path,
+            FakeCommitProvider::new().with_commit("test-version"),
+        );
+        let info = checker.check_for_updates().await.unwrap();
+
+        assert!(!info.available);
+        assert_eq!(info.current_version, "test-version");
+        assert!(info.latest_version.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_for_updates_behind() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let provider = FakeCommitProvider::new()
+            .with_commit("test-version")
+            .with_commit("def456")
+            .with_commit("abc123");
+        let checker = fake_checker(manifest_path, provider);
+        let info = checker.check_for_updates().await.unwrap();
+
+        assert!(info.available);
+        assert_eq!(info.latest_version.as_deref(), Some("abc123"));
+        assert_eq!(info.commit_message.as_deref(), Some("Commit abc123"));
+
+        let changelog: Vec<_> = info.commits.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(changelog, vec!["abc123", "def456"]);
+    }
+
+    #[tokio::test]
+    async fn test_check_for_updates_unknown_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let checker = fake_checker(
+            manifest_path.clone(),
+            FakeCommitProvider::new().with_commit("abc123"),
+        );
+        checker
+            .update_manifest_version("pob-unknown".to_string())
+            .unwrap();
+
+        let info = checker.check_for_updates().await.unwrap();
+
+        // Without a known version there is nothing to compare against
+        assert!(!info.available);
+        assert!(info.latest_version.is_none());
+        assert!(info.commits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ignored_latest_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let provider = Arc::new(FakeCommitProvider::new().with_commit("broken"));
+        let checker = UpdateChecker::new(manifest_path.clone())
+            .with_commit_provider(Box::new(provider.clone()));
+
+        checker.ignore_version("broken").unwrap();
+
+        let info = checker.check_for_updates().await.unwrap();
+        assert!(!info.available);
+        assert_eq!(info.ignored_latest.as_deref(), Some("broken"));
+
+        // A newer version is offered again
+        provider.set_commit("fixed");
+        let info = checker.check_for_updates().await.unwrap();
+        assert!(info.available);
+        assert_eq!(info.latest_version.as_deref(), Some("fixed"));
+        assert!(info.ignored_latest.is_none());
+
+        // Installing clears the ignore list
+        checker.update_manifest_version("fixed".to_string()).unwrap();
+        let manifest = DataManifest::load_from_file(&manifest_path).unwrap();
+        assert!(manifest.ignored_versions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unignore_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let checker = fake_checker(manifest_path, FakeCommitProvider::new().with_commit("abc123"));
+        checker.ignore_version("abc123").unwrap();
+        checker.unignore_version("abc123").unwrap();
+
+        assert!(checker.check_for_updates().await.unwrap().available);
+    }
+
+    #[tokio::test]
+    async fn test_check_for_updates_provider_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let checker = fake_checker(
+            manifest_path,
+            FakeCommitProvider::new().with_error("offline"),
+        );
+        let error = checker.check_for_updates().await.unwrap_err();
+
+        assert!(matches!(error, DownloadError::DownloadFailed(msg) if msg.contains("offline")));
+    }
+
+    const DATA_DIR: &str = "src/Data/TimelessJewelData";
+
+    #[tokio::test]
+    async fn test_check_file_updates_reports_only_changed_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let sha_1 = checksum::calculate_git_blob_sha_bytes(b"test data 1");
+        let sha_2 = checksum::calculate_git_blob_sha_bytes(b"test data 2");
+        let sha_2_new = checksum::calculate_git_blob_sha_bytes(b"test data 2, v2");
+
+        let mut manifest = DataManifest::load_from_file(&manifest_path).unwrap();
+        manifest.files[0].github_sha = sha_1.clone();
+        manifest.files[1].github_sha = sha_2.clone();
+        manifest.save_to_file(&manifest_path).unwrap();
+
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir_all(&data_dir).unwrap();
+        fs::write(data_dir.join("test1.zip"), b"test data 1").unwrap();
+        fs::write(data_dir.join("test2.zip"), b"test data 2").unwrap();
+
+        let provider = FakeCommitProvider::new()
+            .with_file(&format!("{}/test1.zip", DATA_DIR), &sha_1, 11)
+            .with_file(&format!("{}/test2.zip", DATA_DIR), &sha_2_new, 15);
+        let checker = fake_checker(manifest_path, provider);
+
+        let changed = checker.check_file_updates(&data_dir).await.unwrap();
+
+        assert_eq!(
+            changed,
+            vec![ChangedFile {
+                name: "test2.zip".to_string(),
+                old_sha: sha_2,
+                new_sha: sha_2_new,
+                size: 15,
+            }]
+        );
+
+        // Once the new content is on disk nothing is reported
+        fs::write(data_dir.join("test2.zip"), b"test data 2, v2").unwrap();
+        checker.mark_files_updated(&changed).unwrap();
+        assert!(checker.check_file_updates(&data_dir).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_file_updates_uses_local_blob_sha() {
+        let temp_dir = TempDir::new().unwrap();
+        // No github_sha recorded in the manifest
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir_all(&data_dir).unwrap();
+        fs::write(data_dir.join("test1.zip"), b"test data 1").unwrap();
+        fs::write(data_dir.join("test2.zip"), b"edited locally").unwrap();
+
+        let provider = FakeCommitProvider::new()
+            .with_file(
+                &format!("{}/test1.zip", DATA_DIR),
+                &checksum::calculate_git_blob_sha_bytes(b"test data 1"),
+                11,
+            )
+            .with_file(
+                &format!("{}/test2.zip", DATA_DIR),
+                &checksum::calculate_git_blob_sha_bytes(b"test data 2"),
+                11,
+            );
+        let checker = fake_checker(manifest_path, provider);
+
+        let changed = checker.check_file_updates(&data_dir).await.unwrap();
+
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].name, "test2.zip");
+    }
+
+    #[tokio::test]
+    async fn test_check_file_updates_reports_missing_local_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let mut manifest = DataManifest::load_from_file(&manifest_path).unwrap();
+        manifest.files[0].github_sha = "sha-1".to_string();
+        manifest.save_to_file(&manifest_path).unwrap();
+
+        let provider = FakeCommitProvider::new()
+            .with_file(&format!("{}/test1.zip", DATA_DIR), "sha-1", 11);
+        let checker = fake_checker(manifest_path, provider);
+
+        // test2.zip isn't upstream, so only the missing test1.zip is reported
+        let changed = checker
+            .check_file_updates(&temp_dir.path().join("data"))
+            .await
+            .unwrap();
+
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].name, "test1.zip");
+    }
+
+    #[tokio::test]
+    async fn test_check_for_updates_lists_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+
+        let mut manifest = DataManifest::load_from_file(&manifest_path).unwrap();
+        manifest.files[0].github_sha = "sha-1".to_string();
+        manifest.files[1].github_sha = "sha-2".to_string();
+        manifest.save_to_file(&manifest_path).unwrap();
+
+        let provider = FakeCommitProvider::new()
+            .with_commit("test-version")
+            .with_commit("abc123")
+            .with_file(&format!("{}/test1.zip", DATA_DIR), "sha-1", 11)
+            .with_file(&format!("{}/test2.zip", DATA_DIR), "sha-2-new", 2048);
+        let checker = fake_checker(manifest_path, provider);
+
+        let diff = checker.check_for_updates().await.unwrap().diff.unwrap();
+
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, "test2.zip");
+        assert_eq!(diff.download_size(), 2048);
+    }
+
+    #[test]
+    fn test_missing_manifest_falls_back_to_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = temp_dir.path().join("data").join("manifest.json");
+
+        let checker = UpdateChecker::new(manifest_path.clone());
+
+        assert_eq!(checker.get_current_version().unwrap(), "pob-unknown");
+        assert!(!checker.data_exists(temp_dir.path()).unwrap());
+        assert!(!manifest_path.exists());
+
+        // The first update writes the manifest to disk
+        checker.update_manifest_version("abc123".to_string()).unwrap();
+        let saved = DataManifest::load_from_file(&manifest_path).unwrap();
+        assert_eq!(saved.data_version, "abc123");
+        assert_eq!(saved.files.len(), DataManifest::default_pob().files.len());
+    }
+
     #[test]
     fn test_update_manifest_version() {
         let temp_dir = TempDir::new().unwrap();
@@ -269,4 +1457,67 @@ mod tests {
         let updated = checker.get_current_version().unwrap();
         assert_eq!(updated, "new-version");
     }
+
+    #[test]
+    fn test_concurrent_manifest_updates() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = create_test_manifest(&temp_dir);
+        let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        // Readers must never see a half-written file
+        let reader = {
+            let (path, done) = (manifest_path.clone(), done.clone());
+            std::thread::spawn(move || {
+                while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                    let json = fs::read_to_string(&path).unwrap();
+                    serde_json::from_str::<DataManifest>(&json).unwrap();
+                }
+            })
+        };
+
+        let writers: Vec<_> = ["version-a", "version-b"]
+            .into_iter()
+            .map(|version| {
+                let checker = UpdateChecker::new(manifest_path.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        checker.update_manifest_version(version.to_string()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let version = UpdateChecker::new(manifest_path.clone()).get_current_version().unwrap();
+        assert!(version == "version-a" || version == "version-b", "{}", version);
+
+        // Changes to different fields are all kept
+        let ignorer = {
+            let checker = UpdateChecker::new(manifest_path.clone());
+            std::thread::spawn(move || {
+                for i in 0..50 {
+                    checker.ignore_version(&format!("ignored-{}", i)).unwrap();
+                }
+            })
+        };
+        let checker = UpdateChecker::new(manifest_path.clone());
+        for i in 0..50 {
+            let change = ChangedFile {
+                name: "test1.zip".to_string(),
+                old_sha: String::new(),
+                new_sha: format!("sha-{}", i),
+                size: 0,
+            };
+            checker.mark_files_updated(&[change]).unwrap();
+        }
+        ignorer.join().unwrap();
+        done.store(true, std::sync::atomic::Ordering::Relaxed);
+        reader.join().unwrap();
+
+        let manifest = DataManifest::load_from_file(&manifest_path).unwrap();
+        assert_eq!(manifest.ignored_versions.len(), 50);
+        assert_eq!(manifest.find_file("test1.zip").unwrap().github_sha, "sha-49");
+    }
 }
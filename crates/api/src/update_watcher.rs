@@ -0,0 +1,273 @@
+//! Background periodic update checking
+//!
+//! `UpdateWatcher` runs `UpdateChecker::check_for_updates` on its own thread
+//! (or as a task on an existing runtime) at a fixed interval and sends an `UpdateInfo` over a channel whenever a
+//! new version becomes available. Each version is reported once.
+
+use std::sync::mpsc::Sender;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use tokio::runtime::Handle;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::DownloadError;
+use crate::update_checker::{UpdateChecker, UpdateInfo};
+
+/// Handle to a running background update check
+///
+/// Dropping the handle stops the watcher as well.
+pub struct UpdateWatcher {
+    stop: CancellationToken,
+    worker: Option<Worker>,
+}
+
+/// Where the watch loop runs
+enum Worker {
+    Thread(JoinHandle<()>),
+    Task(tokio::task::JoinHandle<()>),
+}
+
+impl UpdateWatcher {
+    /// Start checking every `interval`, sending available updates to `sender`
+    ///
+    /// The first check runs immediately. When GitHub reports a rate limit the
+    /// next check waits until the limit resets (or `interval`, if longer).
+    /// The watcher also stops by itself once the receiver is dropped.
+    pub fn spawn(checker: UpdateChecker, interval: Duration, sender: Sender<UpdateInfo>) -> Self {
+        let stop = CancellationToken::new();
+        let token = stop.clone();
+
+        let thread = std::thread::spawn(move || {
+            let rt = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(e) => {
+                    error!("Update watcher failed to start: {}", e);
+                    return;
+                }
+            };
+
+            rt.block_on(watch(checker, interval, sender, token));
+        });
+
+        Self {
+            stop,
+            worker: Some(Worker::Thread(thread)),
+        }
+    }
+
+    /// Like `spawn`, but run as a task on the runtime behind `handle`
+    pub fn spawn_on(
+        handle: &Handle,
+        checker: UpdateChecker,
+        interval: Duration,
+        sender: Sender<UpdateInfo>,
+    ) -> Self {
+        let stop = CancellationToken::new();
+        let task = handle.spawn(watch(checker, interval, sender, stop.clone()));
+
+        Self {
+            stop,
+            worker: Some(Worker::Task(task)),
+        }
+    }
+
+    /// Whether the watch loop is still running
+    pub fn is_running(&self) -> bool {
+        match &self.worker {
+            Some(Worker::Thread(thread)) => !thread.is_finished(),
+            Some(Worker::Task(task)) => !task.is_finished(),
+            None => false,
+        }
+    }
+
+    /// Stop the watcher and wait for its thread to exit
+    ///
+    /// A watcher running as a task stops at its next await point.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.cancel();
+
+        if let Some(Worker::Thread(thread)) = self.worker.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for UpdateWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+async fn watch(
+    checker: UpdateChecker,
+    interval: Duration,
+    sender: Sender<UpdateInfo>,
+    stop: CancellationToken,
+) {
+    let mut last_notified: Option<String> = None;
+
+    loop {
+        let result = tokio::select! {
+            _ = stop.cancelled() => return,
+            result = checker.check_for_updates() => result,
+        };
+
+        let wait = match result {
+            Ok(info) => {
+                if info.available && info.latest_version != last_notified {
+                    last_notified = info.latest_version.clone();
+
+                    if sender.send(info).is_err() {
+                        // Nobody is listening any more
+                        return;
+                    }
+                }
+                interval
+            }
+            Err(DownloadError::RateLimited { message, reset_at }) => {
+                info!("Update check: {}", message);
+                reset_at
+                    .and_then(|reset_at| (reset_at - chrono::Utc::now()).to_std().ok())
+                    .map_or(interval, |until_reset| until_reset.max(interval))
+            }
+            Err(e) => {
+                warn!("Update check failed: {}", e);
+                interval
+            }
+        };
+
+        tokio::select! {
+            _ = stop.cancelled() => return,
+            _ = tokio::time::sleep(wait) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{DataManifest, DataSource};
+    use crate::test_support::FakeCommitProvider;
+    use std::sync::mpsc::{channel, RecvTimeoutError, TryRecvError};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn checker(temp_dir: &TempDir, provider: Arc<FakeCommitProvider>) -> UpdateChecker {
+        let manifest_path = temp_dir.path().join("manifest.json");
+        DataManifest {
+            data_version: "installed".to_string(),
+            poe_league: "Test".to_string(),
+            last_updated: "2025-01-01T00:00:00Z".to_string(),
+            source: DataSource {
+                source_type: "github".to_string(),
+                repo: "test/test".to_string(),
+                branch: "master".to_string(),
+                path: "data".to_string(),
+                url: "https://github.com/test/test".to_string(),
+                mirrors: Vec::new(),
+                raw_url: None,
+                api_url: None,
+            },
+            files: Vec::new(),
+            ignored_versions: Vec::new(),
+            parsed_artifact: None,
+        }
+        .save_to_file(&manifest_path)
+        .unwrap();
+
+        UpdateChecker::new(manifest_path).with_commit_provider(Box::new(provider))
+    }
+
+    #[test]
+    fn test_notifies_once_per_new_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = Arc::new(FakeCommitProvider::new().with_commit("installed"));
+        let (tx, rx) = channel();
+
+        let watcher = UpdateWatcher::spawn(
+            checker(&temp_dir, provider.clone()),
+            Duration::from_millis(10),
+            tx,
+        );
+
+        // Up to date: nothing is sent
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(rx.try_recv().unwrap_err(), TryRecvError::Empty);
+
+        provider.set_commit("v2");
+        let info = rx.recv_timeout(WAIT).unwrap();
+        assert_eq!(info.latest_version.as_deref(), Some("v2"));
+
+        // Several more checks see the same version without notifying again
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(rx.try_recv().unwrap_err(), TryRecvError::Empty);
+
+        provider.set_commit("v3");
+        let info = rx.recv_timeout(WAIT).unwrap();
+        assert_eq!(info.latest_version.as_deref(), Some("v3"));
+
+        assert!(watcher.is_running());
+        watcher.stop();
+
+        // The sender was dropped with the thread
+        assert_eq!(
+            rx.recv_timeout(WAIT).unwrap_err(),
+            RecvTimeoutError::Disconnected
+        );
+    }
+
+    #[test]
+    fn test_stop_interrupts_long_interval() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = Arc::new(FakeCommitProvider::new().with_commit("installed"));
+        let (tx, _rx) = channel();
+
+        let watcher = UpdateWatcher::spawn(
+            checker(&temp_dir, provider),
+            Duration::from_secs(3600),
+            tx,
+        );
+
+        let started = std::time::Instant::now();
+        watcher.stop();
+
+        assert!(started.elapsed() < WAIT);
+    }
+
+    #[test]
+    fn test_spawn_on_runtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = Arc::new(FakeCommitProvider::new().with_commit("v2"));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (tx, rx) = channel();
+
+        let watcher = UpdateWatcher::spawn_on(
+            runtime.handle(),
+            checker(&temp_dir, provider),
+            Duration::from_secs(3600),
+            tx,
+        );
+
+        let info = rx.recv_timeout(WAIT).unwrap();
+        assert_eq!(info.latest_version.as_deref(), Some("v2"));
+        assert!(watcher.is_running());
+
+        // The task ends and drops the sender once stopped
+        watcher.stop();
+        assert_eq!(
+            rx.recv_timeout(WAIT).unwrap_err(),
+            RecvTimeoutError::Disconnected
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Content validation for downloaded data files
+//!
+//! Checks that a file on disk plausibly is what its name says: Lua files
+//! compile, jewel data files have a zlib header and decompress, and nothing
+//! is suspiciously small. Catches HTML error pages saved under a data file
+//! name before the parser trips over them.
+
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+use std::path::Path;
+
+use crate::parser::LuaParser;
+
+/// Files smaller than this are rejected (e.g., "404: Not Found")
+pub const MIN_FILE_SIZE: u64 = 64;
+
+/// How much data to decompress when checking a zlib stream
+const ZLIB_PROBE_BYTES: usize = 64 * 1024;
+
+/// Outcome of validating one file
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationStatus {
+    /// The file looks usable
+    Valid,
+
+    /// The file doesn't exist
+    Missing,
+
+    /// The file is too small to be real data
+    TooSmall { bytes: u64 },
+
+    /// The file exists but its contents are wrong for its type
+    InvalidContent(String),
+}
+
+/// Validation result for a single file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationResult {
+    /// File name (e.g., "LethalPride.zip")
+    pub file_name: String,
+
+    /// What was found
+    pub status: ValidationStatus,
+}
+
+impl ValidationResult {
+    /// Whether the file passed validation
+    pub fn is_valid(&self) -> bool {
+        self.status == ValidationStatus::Valid
+    }
+}
+
+/// Validate the contents of a single data file
+///
+/// `.lua` files must compile (without being executed), `.zip` files must
+/// be zlib streams whose first block decompresses, and every file must be
+/// at least `MIN_FILE_SIZE` bytes.
+pub fn validate_file(path: &Path) -> ValidationResult {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+
+    let status = match std::fs::metadata(path) {
+        Err(_) => ValidationStatus::Missing,
+        Ok(metadata) if metadata.len() < MIN_FILE_SIZE => ValidationStatus::TooSmall {
+            bytes: metadata.len(),
+        },
+        Ok(_) => match check_contents(path) {
+            Ok(()) => ValidationStatus::Valid,
+            Err(reason) => ValidationStatus::InvalidContent(reason),
+        },
+    };
+
+    ValidationResult { file_name, status }
+}
+
+/// Check the contents of a file according to its extension
+fn check_contents(path: &Path) -> Result<(), String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("lua") => LuaParser::check_syntax(path).map_err(|e| e.to_string()),
+        Some("zip") => check_zlib(path),
+        _ => Ok(()),
+    }
+}
+
+/// Check for a zlib header and decompress the start of the stream
+fn check_zlib(path: &Path) -> Result<(), String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut header = [0u8; 2];
+    (&file)
+        .take(2)
+        .read_exact(&mut header)
+        .map_err(|e| format!("Cannot read header: {}", e))?;
+
+    // CMF must declare deflate and CMF/FLG must pass the header checksum
+    let checksum = (u16::from(header[0]) << 8) | u16::from(header[1]);
+    if header[0] & 0x0F != 8 || !checksum.is_multiple_of(31) {
+        return Err(format!(
+            "Not a zlib stream (starts with {:02x} {:02x})",
+            header[0], header[1]
+        ));
+    }
+
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut decoder = ZlibDecoder::new(file).take(ZLIB_PROBE_BYTES as u64);
+    let mut probe = Vec::with_capacity(ZLIB_PROBE_BYTES);
+
+    match decoder.read_to_end(&mut probe) {
+        Ok(0) => Err("Compressed stream is empty".to_string()),
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to decompress: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    /// Deterministic data that doesn't compress well
+    fn noisy_bytes(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_valid_files() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let lua = temp_dir.path().join("NodeIndexMapping.lua");
+        std::fs::write(
+            &lua,
+            "nodeIDList = { size = 2, sizeNotable = 1 }\nnodeIDList[100] = { index = 0, size = 1 }\n",
+        )
+        .unwrap();
+
+        let zip = temp_dir.path().join("LethalPride.zip");
+        std::fs::write(&zip, zlib(&noisy_bytes(4096))).unwrap();
+
+        assert!(validate_file(&lua).is_valid());
+        assert!(validate_file(&zip).is_valid());
+    }
+
+    #[test]
+    fn test_html_page_saved_as_zip() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip = temp_dir.path().join("LethalPride.zip");
+        std::fs::write(
+            &zip,
+            "<!DOCTYPE html><html><head><title>404</title></head><body>Not Found</body></html>",
+        )
+        .unwrap();
+
+        let result = validate_file(&zip);
+
+        assert_eq!(result.file_name, "LethalPride.zip");
+        assert!(matches!(
+            result.status,
+            ValidationStatus::InvalidContent(ref reason) if reason.contains("zlib")
+        ));
+    }
+
+    #[test]
+    fn test_truncated_zlib_stream() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip = temp_dir.path().join("BrutalRestraint.zip");
+
+        let compressed = zlib(&noisy_bytes(256 * 1024));
+        std::fs::write(&zip, &compressed[..8 * 1024]).unwrap();
+
+        assert!(matches!(
+            validate_file(&zip).status,
+            ValidationStatus::InvalidContent(_)
+        ));
+    }
+
+    #[test]
+    fn test_missing_small_and_bad_lua() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let missing = temp_dir.path().join("LegionPassives.lua");
+        assert_eq!(validate_file(&missing).status, ValidationStatus::Missing);
+
+        let small = temp_dir.path().join("MilitantFaith.zip");
+        std::fs::write(&small, "404: Not Found").unwrap();
+        assert_eq!(
+            validate_file(&small).status,
+            ValidationStatus::TooSmall { bytes: 14 }
+        );
+
+        let bad_lua = temp_dir.path().join("LegionPassives.lua");
+        std::fs::write(
+            &bad_lua,
+            "<html><body>this is not a lua file at all, just markup</body></html>",
+        )
+        .unwrap();
+        assert!(matches!(
+            validate_file(&bad_lua).status,
+            ValidationStatus::InvalidContent(_)
+        ));
+    }
+}
@@ -1,18 +1,35 @@
 //! Integration test: Data downloader workflow
 //!
-//! Tests the full download workflow (when implemented)
+//! Tests the download workflow against a local mock server
 
-use poe_item_analyzer_api::downloader::DataDownloader;
+use poe_item_analyzer_api::checksum::{
+    calculate_git_blob_sha, calculate_sha256, calculate_sha256_bytes,
+};
+use poe_item_analyzer_api::downloader::{
+    join_parts, progress_channel, CancellationToken, DataDownloader, DownloadEvent, ProgressEvent,
+    RetryPolicy,
+};
+use poe_item_analyzer_api::http_cache::{CacheValidators, HttpCache};
+use poe_item_analyzer_api::sources::DownloadSource;
+use poe_item_analyzer_api::validation::ValidationStatus;
+use poe_item_analyzer_api::{
+    ChangedFile, DataFile, DataLayout, DataManifest, DataSource, DownloadError, FileOperation,
+    FilePart, HttpConfig, ManifestDiff,
+};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// SHA256 of "Test data"
+const TEST_DATA_SHA256: &str = "e27c8214be8b7cf5bccc7c08247e3cb0c1514a48ee1f63197fe4ef3ef51d7e6f";
 
 #[test]
 fn test_downloader_instantiation() {
     // Test that we can create a downloader with valid parameters
-    let downloader = DataDownloader::new(
-        "https://raw.githubusercontent.com/Regisle/TimelessJewelData/master/manifest.json"
-            .to_string(),
-        PathBuf::from("/tmp/test-data"),
-    );
+    let downloader = DataDownloader::new(PathBuf::from("/tmp/test-data"));
 
     // Verify it was created successfully
     let _ = downloader;
@@ -20,10 +37,7 @@ fn test_downloader_instantiation() {
 
 #[test]
 fn test_downloader_with_relative_path() {
-    let downloader = DataDownloader::new(
-        "https://example.com/manifest.json".to_string(),
-        PathBuf::from("./data"),
-    );
+    let downloader = DataDownloader::new(PathBuf::from("./data"));
 
     let _ = downloader;
 }
@@ -34,16 +48,1426 @@ fn test_downloader_with_home_directory() {
     let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/tmp"));
     let data_path = home_dir.join(".local/share/poe-item-analyzer/data");
 
-    let downloader =
-        DataDownloader::new("https://example.com/manifest.json".to_string(), data_path);
+    let downloader = DataDownloader::new(data_path);
 
     let _ = downloader;
 }
 
-// Note: These tests are placeholders until download logic is implemented
-// When implemented, we'll add tests for:
-// - Actual download functionality (with mocked HTTP)
-// - Checksum validation
-// - Error handling
-// - Progress tracking
-// - File extraction
+fn collect_events() -> (Arc<Mutex<Vec<DownloadEvent>>>, impl Fn(DownloadEvent)) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    (events, move |event| sink.lock().unwrap().push(event))
+}
+
+#[tokio::test]
+async fn test_download_file_streams_digests() {
+    // Large enough to arrive in many chunks
+    let body: Vec<u8> = (0..3_000_000u32).map(|i| (i % 253) as u8).collect();
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/LethalPride.zip"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(body.clone()))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let (events, progress) = collect_events();
+
+    let url = format!("{}/LethalPride.zip", server.uri());
+    let (saved, digests) = downloader
+        .download_file_with_digests(&url, "LethalPride.zip", &calculate_sha256_bytes(&body), &progress)
+        .await
+        .unwrap();
+
+    let chunks = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|e| matches!(e, DownloadEvent::Progress(_)))
+        .count();
+    assert!(chunks > 1, "body arrived in {} chunk(s)", chunks);
+
+    assert_eq!(digests.bytes, body.len() as u64);
+    assert_eq!(digests.sha256, calculate_sha256(&saved).unwrap());
+    assert_eq!(
+        digests.git_blob_sha.as_deref(),
+        Some(calculate_git_blob_sha(&saved).unwrap().as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_download_file_checksum_match() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/LegionPassives.lua"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"Test data".to_vec()))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let (events, progress) = collect_events();
+
+    let url = format!("{}/LegionPassives.lua", server.uri());
+    let saved = downloader
+        .download_file(&url, "LegionPassives.lua", TEST_DATA_SHA256, &progress)
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(&saved).unwrap(), b"Test data");
+    assert!(!events
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|e| matches!(e, DownloadEvent::Warning(_))));
+}
+
+#[tokio::test]
+async fn test_download_file_checksum_mismatch_removes_file() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/LethalPride.zip"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"<html>Not Found</html>".to_vec()))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let (_events, progress) = collect_events();
+
+    let url = format!("{}/LethalPride.zip", server.uri());
+    let result = downloader
+        .download_file(&url, "LethalPride.zip", TEST_DATA_SHA256, &progress)
+        .await;
+
+    match result {
+        Err(DownloadError::ChecksumMismatch { expected, .. }) => {
+            assert_eq!(expected, TEST_DATA_SHA256);
+        }
+        other => panic!("Expected ChecksumMismatch, got {:?}", other),
+    }
+
+    assert!(!temp_dir.path().join("LethalPride.zip").exists());
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_download_file_without_checksum_warns() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/MilitantFaith.zip"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"anything".to_vec()))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let (events, progress) = collect_events();
+
+    let url = format!("{}/MilitantFaith.zip", server.uri());
+    let saved = downloader
+        .download_file(&url, "MilitantFaith.zip", "", &progress)
+        .await
+        .unwrap();
+
+    assert!(saved.exists());
+
+    let events = events.lock().unwrap();
+    assert!(events.iter().any(|e| matches!(
+        e,
+        DownloadEvent::Warning(message) if message.contains("MilitantFaith.zip")
+    )));
+}
+
+fn data_file(server: &MockServer, name: &str, required: bool) -> DataFile {
+    DataFile {
+        name: name.to_string(),
+        url: format!("{}/data/{}", server.uri(), name),
+        sha256: String::new(),
+        github_sha: String::new(),
+        size: 0,
+        required,
+        description: format!("{} fixture", name),
+        parts: Vec::new(),
+    }
+}
+
+fn test_manifest(files: Vec<DataFile>) -> DataManifest {
+    DataManifest {
+        data_version: "test-version".to_string(),
+        poe_league: "Test".to_string(),
+        last_updated: "2025-01-01T00:00:00Z".to_string(),
+        source: DataSource {
+            source_type: "github".to_string(),
+            repo: "test/test".to_string(),
+            branch: "master".to_string(),
+            path: "data".to_string(),
+            url: "https://github.com/test/test".to_string(),
+            mirrors: Vec::new(),
+            raw_url: None,
+            api_url: None,
+        },
+        files,
+        ignored_versions: Vec::new(),
+        parsed_artifact: None,
+    }
+}
+
+async fn mount_file(server: &MockServer, name: &str, body: &[u8]) {
+    Mock::given(method("GET"))
+        .and(path(format!("/data/{}", name)))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(body.to_vec()))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_download_manifest_fetches_required_files() {
+    let server = MockServer::start().await;
+    mount_file(&server, "NodeIndexMapping.lua", b"nodes").await;
+    mount_file(&server, "LethalPride.zip", b"lethal pride data").await;
+    mount_file(&server, "LegionTradeIds.lua", b"trade ids").await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "NodeIndexMapping.lua", true),
+        data_file(&server, "LethalPride.zip", true),
+        data_file(&server, "LegionTradeIds.lua", false),
+    ]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let (events, progress) = collect_events();
+
+    let updated = downloader.download_manifest(&manifest, progress).await.unwrap();
+
+    // Only the required files were requested, in manifest order
+    let requested: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.url.path().to_string())
+        .collect();
+    assert_eq!(
+        requested,
+        vec!["/data/NodeIndexMapping.lua", "/data/LethalPride.zip"]
+    );
+
+    assert!(temp_dir.path().join("LethalPride.zip").exists());
+    assert!(!temp_dir.path().join("LegionTradeIds.lua").exists());
+
+    // Progress reports only count the files being downloaded
+    let started: Vec<(usize, usize)> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|e| match e {
+            DownloadEvent::FileStarted { current, total, .. } => Some((*current, *total)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(started, vec![(1, 2), (2, 2)]);
+
+    // The returned manifest records the actual size and hash
+    let lethal_pride = updated.find_file("LethalPride.zip").unwrap();
+    assert_eq!(lethal_pride.size, b"lethal pride data".len() as u64);
+    assert_eq!(lethal_pride.sha256, calculate_sha256_bytes(b"lethal pride data"));
+
+    let trade_ids = updated.find_file("LegionTradeIds.lua").unwrap();
+    assert_eq!(trade_ids.size, 0);
+    assert!(trade_ids.sha256.is_empty());
+
+    assert_ne!(updated.last_updated, manifest.last_updated);
+    assert_eq!(updated.data_version, manifest.data_version);
+}
+
+#[tokio::test]
+async fn test_download_manifest_includes_optional_when_asked() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LegionPassives.lua", b"passives").await;
+    mount_file(&server, "LegionTradeIds.lua", b"trade ids").await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "LegionPassives.lua", true),
+        data_file(&server, "LegionTradeIds.lua", false),
+    ]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf()).include_optional(true);
+
+    let updated = downloader.download_manifest(&manifest, |_| {}).await.unwrap();
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    assert!(temp_dir.path().join("LegionTradeIds.lua").exists());
+    assert_eq!(updated.find_file("LegionTradeIds.lua").unwrap().size, 9);
+}
+
+#[tokio::test]
+async fn test_download_pob_data_uses_configured_manifest() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LegionPassives.lua", b"passives").await;
+
+    let manifest = test_manifest(vec![data_file(&server, "LegionPassives.lua", true)]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf()).with_manifest(manifest);
+
+    let updated = downloader.download_pob_data().await.unwrap();
+
+    assert_eq!(updated.find_file("LegionPassives.lua").unwrap().size, 8);
+    assert!(temp_dir.path().join("LegionPassives.lua").exists());
+}
+
+#[tokio::test]
+async fn test_download_pob_data_without_manifest_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+
+    let result = downloader.download_pob_data().await;
+
+    assert!(matches!(result, Err(DownloadError::InvalidManifest(_))));
+}
+
+#[tokio::test]
+async fn test_download_file_streams_byte_progress() {
+    // 4 MB body, large enough to arrive in many chunks
+    let body: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    let server = MockServer::start().await;
+    mount_file(&server, "GloriousVanity.zip.part0", &body).await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let (events, progress) = collect_events();
+
+    let url = format!("{}/data/GloriousVanity.zip.part0", server.uri());
+    let saved = downloader
+        .download_file(&url, "GloriousVanity.zip.part0", &calculate_sha256_bytes(&body), &progress)
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(&saved).unwrap(), body);
+
+    let progress_events: Vec<ProgressEvent> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|e| match e {
+            DownloadEvent::Progress(p) => Some(p.clone()),
+            _ => None,
+        })
+        .collect();
+
+    assert!(progress_events.len() > 1, "expected multiple chunks");
+
+    for pair in progress_events.windows(2) {
+        assert!(pair[1].bytes_downloaded > pair[0].bytes_downloaded);
+    }
+
+    let last = progress_events.last().unwrap();
+    assert_eq!(last.file, "GloriousVanity.zip.part0");
+    assert_eq!(last.bytes_downloaded, body.len() as u64);
+    assert_eq!(last.total_bytes, Some(body.len() as u64));
+    assert_eq!(last.fraction(), Some(1.0));
+}
+
+#[tokio::test]
+async fn test_download_file_http_error_leaves_no_file() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+
+    let url = format!("{}/data/Missing.zip", server.uri());
+    let result = downloader.download_file(&url, "Missing.zip", "", &|_| {}).await;
+
+    match result {
+        Err(DownloadError::FileFailed { operation, file, .. }) => {
+            assert_eq!(operation, FileOperation::Download);
+            assert_eq!(file, "Missing.zip");
+        }
+        other => panic!("Expected a failed download, got {:?}", other),
+    }
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+fn no_delay_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_delay: Duration::ZERO,
+        ..RetryPolicy::default()
+    }
+}
+
+#[tokio::test]
+async fn test_download_file_retries_server_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    mount_file(&server, "LethalPride.zip", b"Test data").await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_retry_policy(no_delay_retries(4));
+    let (events, progress) = collect_events();
+
+    let url = format!("{}/data/LethalPride.zip", server.uri());
+    let saved = downloader
+        .download_file(&url, "LethalPride.zip", TEST_DATA_SHA256, &progress)
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(&saved).unwrap(), b"Test data");
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+
+    let retries: Vec<(u32, u32)> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|e| match e {
+            DownloadEvent::Retrying { file_name, attempt, max_attempts, .. } => {
+                assert_eq!(file_name, "LethalPride.zip");
+                Some((*attempt, *max_attempts))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(retries, vec![(2, 4), (3, 4)]);
+}
+
+#[tokio::test]
+async fn test_download_file_gives_up_after_max_attempts() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_retry_policy(no_delay_retries(3));
+
+    let url = format!("{}/data/LethalPride.zip", server.uri());
+    let result = downloader.download_file(&url, "LethalPride.zip", "", &|_| {}).await;
+
+    assert!(matches!(result, Err(DownloadError::FileFailed { .. })));
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_download_file_does_not_retry_client_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_retry_policy(no_delay_retries(4));
+    let (events, progress) = collect_events();
+
+    let url = format!("{}/data/Missing.zip", server.uri());
+    let result = downloader.download_file(&url, "Missing.zip", "", &progress).await;
+
+    assert!(result.is_err());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    assert!(!events
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|e| matches!(e, DownloadEvent::Retrying { .. })));
+}
+
+#[test]
+fn test_retry_policy_backoff_grows() {
+    let policy = RetryPolicy {
+        max_attempts: 4,
+        initial_delay: Duration::from_millis(100),
+        multiplier: 2.0,
+        jitter: 0.0,
+    };
+
+    assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+    assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+    assert_eq!(policy.delay_for(3), Duration::from_millis(400));
+
+    let jittered = RetryPolicy { jitter: 0.5, ..policy };
+    for _ in 0..20 {
+        let delay = jittered.delay_for(2);
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(300));
+    }
+}
+
+/// Live data directory with an old file, inside a temp parent
+fn live_data_dir(parent: &TempDir) -> PathBuf {
+    let live = parent.path().join("data");
+    std::fs::create_dir_all(&live).unwrap();
+    std::fs::write(live.join("LethalPride.zip"), b"old data").unwrap();
+    live
+}
+
+#[tokio::test]
+async fn test_download_and_swap_replaces_live_directory() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LethalPride.zip", b"new data").await;
+    mount_file(&server, "MilitantFaith.zip", b"more data").await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "LethalPride.zip", true),
+        data_file(&server, "MilitantFaith.zip", true),
+    ]);
+
+    let parent = TempDir::new().unwrap();
+    let live = live_data_dir(&parent);
+    let downloader = DataDownloader::new(live.clone());
+
+    let updated = downloader.download_and_swap(&manifest, |_| {}).await.unwrap();
+
+    assert_eq!(std::fs::read(live.join("LethalPride.zip")).unwrap(), b"new data");
+    assert!(live.join("MilitantFaith.zip").exists());
+
+    let saved = DataManifest::load_from_file(&live.join("manifest.json")).unwrap();
+    assert_eq!(
+        saved.find_file("LethalPride.zip").unwrap().sha256,
+        updated.find_file("LethalPride.zip").unwrap().sha256
+    );
+
+    // Only the live directory remains: no staging or backup left behind
+    let entries: Vec<String> = std::fs::read_dir(parent.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(entries, vec!["data"]);
+}
+
+#[tokio::test]
+async fn test_download_version_into_layout() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LethalPride.zip", b"new data").await;
+    let mut manifest = test_manifest(vec![data_file(&server, "LethalPride.zip", true)]);
+    manifest.data_version = "abc123".to_string();
+
+    let parent = TempDir::new().unwrap();
+    let layout = DataLayout::new(parent.path().join("pob-data"));
+
+    DataDownloader::new(PathBuf::from("unused"))
+        .download_version(&layout, &manifest, |_| {})
+        .await
+        .unwrap();
+
+    let raw = layout.raw_dir("abc123");
+    assert_eq!(std::fs::read(raw.join("LethalPride.zip")).unwrap(), b"new data");
+    assert!(!raw.join("manifest.json").exists());
+    assert!(layout.parsed_dir("abc123").is_dir());
+    assert_eq!(layout.versions(), vec!["abc123"]);
+
+    let entries: Vec<String> = std::fs::read_dir(layout.version_dir("abc123"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(entries.len(), 3, "{:?}", entries);
+}
+
+#[tokio::test]
+async fn test_download_and_swap_checks_free_space_first() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LethalPride.zip", b"new data").await;
+    Mock::given(method("HEAD"))
+        .and(path("/data/LethalPride.zip"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 1000]))
+        .mount(&server)
+        .await;
+    let manifest = test_manifest(vec![data_file(&server, "LethalPride.zip", true)]);
+
+    let parent = TempDir::new().unwrap();
+    let live = live_data_dir(&parent);
+
+    // 1000 bytes with headroom, plus the parsed LUT
+    let error = DataDownloader::new(live.clone())
+        .with_space_probe(|_| Ok(5000))
+        .download_and_swap(&manifest, |_| {})
+        .await
+        .unwrap_err();
+    match error {
+        DownloadError::InsufficientSpace { needed, available } => {
+            assert_eq!((needed, available), (5250, 5000));
+        }
+        other => panic!("Expected insufficient space, got {:?}", other),
+    }
+
+    // Nothing was downloaded or staged
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests.iter().all(|request| request.method.as_str() == "HEAD"));
+    assert_eq!(std::fs::read(live.join("LethalPride.zip")).unwrap(), b"old data");
+    assert_eq!(std::fs::read_dir(parent.path()).unwrap().count(), 1);
+
+    DataDownloader::new(live.clone())
+        .with_space_probe(|_| Ok(5250))
+        .download_and_swap(&manifest, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(live.join("LethalPride.zip")).unwrap(), b"new data");
+}
+
+#[tokio::test]
+async fn test_download_and_swap_reports_to_channel() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LethalPride.zip", b"new data").await;
+    mount_file(&server, "MilitantFaith.zip", b"more data").await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "LethalPride.zip", true),
+        data_file(&server, "MilitantFaith.zip", true),
+    ]);
+
+    let parent = TempDir::new().unwrap();
+    let live = live_data_dir(&parent);
+    let (tx, rx) = std::sync::mpsc::channel::<DownloadEvent>();
+
+    DataDownloader::new(live)
+        .download_and_swap(&manifest, progress_channel(tx))
+        .await
+        .unwrap();
+
+    let events: Vec<DownloadEvent> = rx.try_iter().collect();
+    let started: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            DownloadEvent::FileStarted { current, total, file_name } => {
+                Some((*current, *total, file_name.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        started,
+        vec![(1, 2, "LethalPride.zip"), (2, 2, "MilitantFaith.zip")]
+    );
+    assert!(events.contains(&DownloadEvent::FileCompleted {
+        file_name: "MilitantFaith.zip".to_string(),
+        bytes: 9,
+    }));
+}
+
+#[tokio::test]
+async fn test_download_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data/LethalPride.zip"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+
+    let manifest = test_manifest(vec![data_file(&server, "LethalPride.zip", true)]);
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_http_config(&read_timeout(Duration::from_millis(200)))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::none());
+
+    let started = std::time::Instant::now();
+    let result = downloader.download_manifest(&manifest, |_| {}).await;
+
+    let error = result.unwrap_err();
+    assert!(matches!(error, DownloadError::FileFailed { .. }));
+    assert!(error.to_string().contains("LethalPride.zip"), "{}", error);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+/// Serve `body` one byte every `gap` to a single request, going silent
+/// after `stall_after` bytes if given
+///
+/// wiremock can only delay a whole response, not pause within its body.
+async fn serve_slowly(body: &'static [u8], gap: Duration, stall_after: Option<usize>) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = [0u8; 1024];
+        let _ = socket.read(&mut request).await;
+
+        let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len());
+        socket.write_all(head.as_bytes()).await.unwrap();
+        for byte in &body[..stall_after.unwrap_or(body.len())] {
+            socket.write_all(std::slice::from_ref(byte)).await.unwrap();
+            socket.flush().await.unwrap();
+            tokio::time::sleep(gap).await;
+        }
+        tokio::time::sleep(Duration::from_secs(10)).await;
+    });
+
+    format!("http://{}/LethalPride.zip", address)
+}
+
+fn read_timeout(timeout: Duration) -> HttpConfig {
+    HttpConfig {
+        read_timeout: timeout,
+        ..HttpConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_download_stalled_body_times_out() {
+    let url = serve_slowly(b"Test data", Duration::ZERO, Some(4)).await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_http_config(&read_timeout(Duration::from_millis(200)))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::none());
+
+    let started = std::time::Instant::now();
+    let error = downloader
+        .download_file(&url, "LethalPride.zip", TEST_DATA_SHA256, &|_| {})
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("no data received for 0.2s"), "{}", error);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(!temp_dir.path().join("LethalPride.zip").exists());
+}
+
+#[tokio::test]
+async fn test_download_slow_but_steady_body_completes() {
+    // ~900ms in total, but never more than 100ms without data
+    let url = serve_slowly(b"Test data", Duration::from_millis(100), None).await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_http_config(&read_timeout(Duration::from_millis(500)))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::none());
+
+    let saved = downloader
+        .download_file(&url, "LethalPride.zip", TEST_DATA_SHA256, &|_| {})
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(saved).unwrap(), b"Test data");
+}
+
+#[tokio::test]
+async fn test_download_and_swap_failure_leaves_live_directory_untouched() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LethalPride.zip", b"new data").await;
+    mount_file(&server, "MilitantFaith.zip", b"more data").await;
+    Mock::given(method("GET"))
+        .and(path("/data/BrutalRestraint.zip"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "LethalPride.zip", true),
+        data_file(&server, "MilitantFaith.zip", true),
+        data_file(&server, "BrutalRestraint.zip", true),
+    ]);
+
+    let parent = TempDir::new().unwrap();
+    let live = live_data_dir(&parent);
+    let downloader = DataDownloader::new(live.clone()).with_retry_policy(RetryPolicy::none());
+
+    let result = downloader.download_and_swap(&manifest, |_| {}).await;
+
+    assert!(result.is_err());
+    // The sizes were asked for first (HEAD), then each file downloaded once
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.iter().filter(|r| r.method.as_str() == "GET").count(), 3);
+
+    assert_eq!(std::fs::read(live.join("LethalPride.zip")).unwrap(), b"old data");
+    assert!(!live.join("MilitantFaith.zip").exists());
+    assert_eq!(std::fs::read_dir(parent.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn test_download_and_swap_rejects_unparseable_data() {
+    let server = MockServer::start().await;
+    mount_file(&server, "NodeIndexMapping.lua", b"<html>this is not lua</html>").await;
+
+    let manifest = test_manifest(vec![data_file(&server, "NodeIndexMapping.lua", true)]);
+
+    let parent = TempDir::new().unwrap();
+    let live = live_data_dir(&parent);
+    let downloader = DataDownloader::new(live.clone());
+
+    let result = downloader.download_and_swap(&manifest, |_| {}).await;
+
+    assert!(result.is_err());
+    assert_eq!(std::fs::read(live.join("LethalPride.zip")).unwrap(), b"old data");
+    assert!(!live.join("NodeIndexMapping.lua").exists());
+    assert_eq!(std::fs::read_dir(parent.path()).unwrap().count(), 1);
+}
+
+fn split_file(server: &MockServer, name: &str, parts: &[&[u8]]) -> DataFile {
+    let joined: Vec<u8> = parts.concat();
+
+    DataFile {
+        sha256: calculate_sha256_bytes(&joined),
+        parts: parts
+            .iter()
+            .enumerate()
+            .map(|(index, part)| FilePart {
+                url: format!("{}/data/{}.part{}", server.uri(), name, index),
+                sha256: calculate_sha256_bytes(part),
+                size: part.len() as u64,
+            })
+            .collect(),
+        ..data_file(server, name, true)
+    }
+}
+
+#[tokio::test]
+async fn test_download_manifest_joins_split_file_in_order() {
+    let parts: [&[u8]; 3] = [b"first-", b"second-", b"third"];
+
+    let server = MockServer::start().await;
+    // Mount out of order to make sure the join follows the manifest
+    mount_file(&server, "GloriousVanity.zip.part2", parts[2]).await;
+    mount_file(&server, "GloriousVanity.zip.part0", parts[0]).await;
+    mount_file(&server, "GloriousVanity.zip.part1", parts[1]).await;
+
+    let manifest = test_manifest(vec![split_file(&server, "GloriousVanity.zip", &parts)]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let (events, progress) = collect_events();
+
+    let updated = downloader.download_manifest(&manifest, progress).await.unwrap();
+
+    let joined = temp_dir.path().join("GloriousVanity.zip");
+    assert_eq!(std::fs::read(&joined).unwrap(), b"first-second-third");
+
+    // Parts are removed once joined
+    for index in 0..parts.len() {
+        assert!(!temp_dir
+            .path()
+            .join(format!("GloriousVanity.zip.part{}", index))
+            .exists());
+    }
+
+    assert_eq!(updated.find_file("GloriousVanity.zip").unwrap().size, 18);
+    assert!(!events
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|e| matches!(e, DownloadEvent::Warning(_))));
+}
+
+#[tokio::test]
+async fn test_download_split_file_missing_part_fails() {
+    let parts: [&[u8]; 3] = [b"first-", b"second-", b"third"];
+
+    let server = MockServer::start().await;
+    mount_file(&server, "GloriousVanity.zip.part0", parts[0]).await;
+    mount_file(&server, "GloriousVanity.zip.part2", parts[2]).await;
+
+    let file = split_file(&server, "GloriousVanity.zip", &parts);
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_retry_policy(RetryPolicy::none());
+
+    let error = downloader
+        .download_split_file(&file, &|_| {})
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("GloriousVanity.zip.part1"));
+    assert!(!temp_dir.path().join("GloriousVanity.zip").exists());
+}
+
+#[test]
+fn test_join_parts_missing_middle_part() {
+    let file = DataFile {
+        name: "GloriousVanity.zip".to_string(),
+        url: String::new(),
+        sha256: String::new(),
+        github_sha: String::new(),
+        size: 0,
+        required: true,
+        description: String::new(),
+        parts: (0..3)
+            .map(|index| FilePart {
+                url: format!("http://localhost/GloriousVanity.zip.part{}", index),
+                sha256: String::new(),
+                size: 0,
+            })
+            .collect(),
+    };
+
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("GloriousVanity.zip.part0"), b"first-").unwrap();
+    std::fs::write(temp_dir.path().join("GloriousVanity.zip.part2"), b"third").unwrap();
+
+    let error = join_parts(temp_dir.path(), &file).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Could not write GloriousVanity.zip: part 2 (GloriousVanity.zip.part1) is missing"
+    );
+    assert!(!temp_dir.path().join("GloriousVanity.zip").exists());
+}
+
+#[tokio::test]
+async fn test_sync_fetches_only_outdated_files() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LegionPassives.lua", b"passives").await;
+    mount_file(&server, "LethalPride.zip", b"lethal pride data").await;
+    mount_file(&server, "MilitantFaith.zip", b"militant faith data").await;
+
+    let with_checksum = |name: &str, body: &[u8]| DataFile {
+        sha256: calculate_sha256_bytes(body),
+        size: body.len() as u64,
+        ..data_file(&server, name, true)
+    };
+
+    let manifest = test_manifest(vec![
+        with_checksum("LegionPassives.lua", b"passives"),
+        with_checksum("LethalPride.zip", b"lethal pride data"),
+        with_checksum("MilitantFaith.zip", b"militant faith data"),
+    ]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    // One valid file, one corrupted (same size, different bytes), one missing
+    std::fs::write(data_dir.join("LegionPassives.lua"), b"passives").unwrap();
+    std::fs::write(data_dir.join("LethalPride.zip"), b"lethal pride DATA").unwrap();
+
+    let downloader = DataDownloader::new(temp_dir.path().join("unused"));
+    let report = downloader.sync(&manifest, &data_dir).await.unwrap();
+
+    let requested: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.url.path().to_string())
+        .collect();
+    assert_eq!(
+        requested,
+        vec!["/data/LethalPride.zip", "/data/MilitantFaith.zip"]
+    );
+
+    assert_eq!(report.skipped, vec!["LegionPassives.lua"]);
+    assert_eq!(report.downloaded, vec!["LethalPride.zip", "MilitantFaith.zip"]);
+    assert!(report.is_complete());
+
+    assert_eq!(
+        std::fs::read(data_dir.join("LethalPride.zip")).unwrap(),
+        b"lethal pride data"
+    );
+}
+
+#[tokio::test]
+async fn test_sync_changed_refetches_only_changed_files() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LegionPassives.lua", b"new passives").await;
+    mount_file(&server, "GloriousVanity.zip", b"glorious vanity data").await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "LegionPassives.lua", true),
+        data_file(&server, "GloriousVanity.zip", true),
+    ]);
+
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("LegionPassives.lua"), b"old passives").unwrap();
+    std::fs::write(temp_dir.path().join("GloriousVanity.zip"), b"glorious vanity data").unwrap();
+
+    let changed = vec![ChangedFile {
+        name: "LegionPassives.lua".to_string(),
+        old_sha: "sha-old".to_string(),
+        new_sha: "sha-new".to_string(),
+        size: 12,
+    }];
+
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let report = downloader
+        .sync_changed(&manifest, temp_dir.path(), &changed, |_| {})
+        .await
+        .unwrap();
+
+    let requested: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.url.path().to_string())
+        .collect();
+    assert_eq!(requested, vec!["/data/LegionPassives.lua"]);
+
+    assert_eq!(report.downloaded, vec!["LegionPassives.lua"]);
+    assert_eq!(report.skipped, vec!["GloriousVanity.zip"]);
+    assert_eq!(
+        std::fs::read(temp_dir.path().join("LegionPassives.lua")).unwrap(),
+        b"new passives"
+    );
+}
+
+#[tokio::test]
+async fn test_sync_records_failures_and_continues() {
+    let server = MockServer::start().await;
+    mount_file(&server, "MilitantFaith.zip", b"militant faith data").await;
+    Mock::given(method("GET"))
+        .and(path("/data/LethalPride.zip"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "LethalPride.zip", true),
+        data_file(&server, "MilitantFaith.zip", true),
+    ]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+
+    let report = downloader.sync(&manifest, temp_dir.path()).await.unwrap();
+
+    assert_eq!(report.downloaded, vec!["MilitantFaith.zip"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "LethalPride.zip");
+    assert!(!report.is_complete());
+}
+
+/// Server that sends the headers and first 64 KB of a 1 MB body, then stalls
+async fn start_stalling_server() -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+
+                let headers = "HTTP/1.1 200 OK\r\nContent-Length: 1048576\r\n\r\n";
+                let _ = socket.write_all(headers.as_bytes()).await;
+                let _ = socket.write_all(&vec![0u8; 64 * 1024]).await;
+                let _ = socket.flush().await;
+
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            });
+        }
+    });
+
+    format!("http://{}", address)
+}
+
+#[tokio::test]
+async fn test_cancel_mid_stream_returns_promptly() {
+    let base_url = start_stalling_server().await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let token = downloader.cancellation_token();
+
+    // Cancel as soon as the first bytes arrive
+    let progress = move |event: DownloadEvent| {
+        if matches!(event, DownloadEvent::Progress(_)) {
+            token.cancel();
+        }
+    };
+
+    let started = std::time::Instant::now();
+    let url = format!("{}/data/LethalPride.zip", base_url);
+    let result = downloader.download_file(&url, "LethalPride.zip", "", &progress).await;
+
+    assert!(matches!(result, Err(DownloadError::Cancelled)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_cancel_split_download_leaves_no_part_files() {
+    let parts: [&[u8]; 2] = [b"first-", b"second"];
+
+    let server = MockServer::start().await;
+    mount_file(&server, "GloriousVanity.zip.part0", parts[0]).await;
+    Mock::given(method("GET"))
+        .and(path("/data/GloriousVanity.zip.part1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_bytes(parts[1].to_vec())
+                .set_delay(Duration::from_secs(30)),
+        )
+        .mount(&server)
+        .await;
+
+    let manifest = test_manifest(vec![split_file(&server, "GloriousVanity.zip", &parts)]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let token = CancellationToken::new();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_cancellation(token.clone());
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        token.cancel();
+    });
+
+    let started = std::time::Instant::now();
+    let result = downloader.download_manifest(&manifest, |_| {}).await;
+
+    assert!(matches!(result, Err(DownloadError::Cancelled)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_sync_not_modified_keeps_existing_file() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data/LegionPassives.lua"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .mount(&server)
+        .await;
+
+    // No checksum in the manifest, so the file can only be revalidated remotely
+    let manifest = test_manifest(vec![data_file(&server, "LegionPassives.lua", true)]);
+
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("LegionPassives.lua"), b"cached passives").unwrap();
+
+    let mut cache = HttpCache::default();
+    cache.insert(
+        "LegionPassives.lua",
+        CacheValidators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        },
+    );
+    cache.save(temp_dir.path()).unwrap();
+
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let report = downloader.sync(&manifest, temp_dir.path()).await.unwrap();
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    assert_eq!(report.skipped, vec!["LegionPassives.lua"]);
+    assert!(report.downloaded.is_empty());
+    assert_eq!(
+        std::fs::read(temp_dir.path().join("LegionPassives.lua")).unwrap(),
+        b"cached passives"
+    );
+}
+
+#[tokio::test]
+async fn test_download_records_validators_for_next_sync() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data/LegionPassives.lua"))
+        .and(header("If-None-Match", "\"v2\""))
+        .respond_with(ResponseTemplate::new(304))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/data/LegionPassives.lua"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v2\"")
+                .insert_header("Last-Modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+                .set_body_bytes(b"passives".to_vec()),
+        )
+        .mount(&server)
+        .await;
+
+    let manifest = test_manifest(vec![data_file(&server, "LegionPassives.lua", true)]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+
+    downloader.download_manifest(&manifest, |_| {}).await.unwrap();
+
+    let cache = HttpCache::load(temp_dir.path());
+    let validators = cache.get("LegionPassives.lua").unwrap();
+    assert_eq!(validators.etag.as_deref(), Some("\"v2\""));
+    assert_eq!(
+        validators.last_modified.as_deref(),
+        Some("Wed, 01 Jan 2025 00:00:00 GMT")
+    );
+
+    // The follow-up sync revalidates instead of downloading again
+    let report = downloader.sync(&manifest, temp_dir.path()).await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[1].headers.get("If-Modified-Since").unwrap(),
+        "Wed, 01 Jan 2025 00:00:00 GMT"
+    );
+    assert_eq!(report.skipped, vec!["LegionPassives.lua"]);
+}
+
+#[tokio::test]
+async fn test_sync_falls_back_to_mirror() {
+    let primary = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&primary)
+        .await;
+
+    let mirror = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/mirror/LethalPride.zip"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"Test data".to_vec()))
+        .mount(&mirror)
+        .await;
+
+    let mut manifest = test_manifest(vec![DataFile {
+        sha256: TEST_DATA_SHA256.to_string(),
+        ..data_file(&primary, "LethalPride.zip", true)
+    }]);
+    let mirror_source = DownloadSource::Url {
+        base_url: format!("{}/mirror", mirror.uri()),
+    };
+    manifest.source.mirrors = vec![mirror_source.clone()];
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_retry_policy(no_delay_retries(2));
+
+    let report = downloader.sync(&manifest, temp_dir.path()).await.unwrap();
+
+    assert_eq!(primary.received_requests().await.unwrap().len(), 2);
+    assert_eq!(report.downloaded, vec!["LethalPride.zip"]);
+    assert_eq!(
+        report.fallbacks,
+        vec![("LethalPride.zip".to_string(), mirror_source.to_string())]
+    );
+    assert_eq!(
+        std::fs::read(temp_dir.path().join("LethalPride.zip")).unwrap(),
+        b"Test data"
+    );
+}
+
+#[tokio::test]
+async fn test_mirror_not_used_for_client_errors() {
+    let primary = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&primary)
+        .await;
+
+    let mirror = MockServer::start().await;
+
+    let mut manifest = test_manifest(vec![data_file(&primary, "LethalPride.zip", true)]);
+    manifest.source.mirrors = vec![DownloadSource::Url { base_url: mirror.uri() }];
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+
+    let report = downloader.sync(&manifest, temp_dir.path()).await.unwrap();
+
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.fallbacks.is_empty());
+    assert!(mirror.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_local_directory_mirror_serves_split_parts() {
+    let primary = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&primary)
+        .await;
+
+    let parts: [&[u8]; 2] = [b"first-", b"second"];
+
+    // Offline copy of the data, e.g. on a USB drive
+    let offline = TempDir::new().unwrap();
+    for (index, part) in parts.iter().enumerate() {
+        std::fs::write(offline.path().join(format!("GloriousVanity.zip.part{}", index)), part)
+            .unwrap();
+    }
+
+    let mut manifest = test_manifest(vec![split_file(&primary, "GloriousVanity.zip", &parts)]);
+    manifest.source.mirrors = vec![DownloadSource::LocalDir {
+        path: offline.path().to_path_buf(),
+    }];
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_retry_policy(RetryPolicy::none());
+
+    let updated = downloader.download_manifest(&manifest, |_| {}).await.unwrap();
+
+    assert_eq!(
+        std::fs::read(temp_dir.path().join("GloriousVanity.zip")).unwrap(),
+        b"first-second"
+    );
+    assert_eq!(updated.find_file("GloriousVanity.zip").unwrap().size, 12);
+}
+
+#[tokio::test]
+async fn test_validate_files_flags_html_saved_as_zip() {
+    let html = b"<!DOCTYPE html><html><head><title>Rate limited</title></head><body>Try again later</body></html>";
+
+    let server = MockServer::start().await;
+    mount_file(&server, "LethalPride.zip", html).await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "LethalPride.zip", true),
+        data_file(&server, "MilitantFaith.zip", true),
+    ]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader =
+        DataDownloader::new(temp_dir.path().to_path_buf()).with_manifest(manifest.clone());
+
+    // No checksum in the manifest, so the download itself is accepted
+    downloader
+        .download_file(&manifest.files[0].url, "LethalPride.zip", "", &|_| {})
+        .await
+        .unwrap();
+
+    let results = downloader.validate_files().await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].file_name, "LethalPride.zip");
+    assert!(matches!(results[0].status, ValidationStatus::InvalidContent(_)));
+    assert_eq!(results[1].status, ValidationStatus::Missing);
+}
+
+#[tokio::test]
+async fn test_download_pob_data_uses_base_url() {
+    let server = MockServer::start().await;
+    mount_file(&server, "LegionPassives.lua", b"passives").await;
+    mount_file(&server, "GloriousVanity.zip.part0", b"first-").await;
+    mount_file(&server, "GloriousVanity.zip.part1", b"second").await;
+
+    // Manifest URLs point somewhere unreachable; the base URL wins
+    let unreachable = DataFile {
+        url: "http://127.0.0.1:9/LegionPassives.lua".to_string(),
+        ..data_file(&server, "LegionPassives.lua", true)
+    };
+    let mut split = split_file(&server, "GloriousVanity.zip", &[b"first-", b"second"]);
+    for part in &mut split.parts {
+        part.url = "http://127.0.0.1:9/unused".to_string();
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf())
+        .with_manifest(test_manifest(vec![unreachable, split]))
+        .with_base_url(format!("{}/data/", server.uri()));
+
+    downloader.download_pob_data().await.unwrap();
+
+    assert_eq!(
+        std::fs::read(temp_dir.path().join("LegionPassives.lua")).unwrap(),
+        b"passives"
+    );
+    assert_eq!(
+        std::fs::read(temp_dir.path().join("GloriousVanity.zip")).unwrap(),
+        b"first-second"
+    );
+}
+
+#[tokio::test]
+async fn test_sync_reports_bytes_transferred_and_saved() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data/LegionPassives.lua"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .mount(&server)
+        .await;
+    mount_file(&server, "LethalPride.zip", b"lethal pride data").await;
+
+    let manifest = test_manifest(vec![
+        data_file(&server, "LegionPassives.lua", true),
+        data_file(&server, "LethalPride.zip", true),
+    ]);
+
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("LegionPassives.lua"), b"cached passives").unwrap();
+
+    let mut cache = HttpCache::default();
+    cache.insert(
+        "LegionPassives.lua",
+        CacheValidators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        },
+    );
+    cache.save(temp_dir.path()).unwrap();
+
+    let downloader = DataDownloader::new(temp_dir.path().to_path_buf());
+    let report = downloader.sync(&manifest, temp_dir.path()).await.unwrap();
+
+    assert_eq!(
+        report.transferred,
+        vec![
+            ("LegionPassives.lua".to_string(), 0),
+            ("LethalPride.zip".to_string(), 17),
+        ]
+    );
+    assert_eq!(report.bytes_downloaded, 17);
+    assert_eq!(report.bytes_saved, "cached passives".len() as u64);
+}
+
+#[tokio::test]
+async fn test_resolve_sizes_asks_server_for_unknown_sizes() {
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/data/LethalPride.zip"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 4096]))
+        .mount(&server)
+        .await;
+
+    let known = DataFile {
+        size: 100,
+        ..data_file(&server, "LegionPassives.lua", true)
+    };
+    let split = DataFile {
+        parts: vec![
+            FilePart {
+                url: format!("{}/data/GloriousVanity.zip.part0", server.uri()),
+                sha256: String::new(),
+                size: 30,
+            },
+            FilePart {
+                url: format!("{}/data/GloriousVanity.zip.part1", server.uri()),
+                sha256: String::new(),
+                size: 12,
+            },
+        ],
+        ..data_file(&server, "GloriousVanity.zip", true)
+    };
+    let mut diff = ManifestDiff {
+        added: vec![split],
+        removed: Vec::new(),
+        changed: vec![known, data_file(&server, "LethalPride.zip", true)],
+    };
+    assert_eq!(diff.unknown_sizes(), 2);
+
+    let temp_dir = TempDir::new().unwrap();
+    DataDownloader::new(temp_dir.path().to_path_buf())
+        .resolve_sizes(&mut diff)
+        .await;
+
+    assert_eq!(diff.unknown_sizes(), 0);
+    assert_eq!(diff.download_size(), 100 + 42 + 4096);
+
+    // Only the single file of unknown size needed a request
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url.path(), "/data/LethalPride.zip");
+}
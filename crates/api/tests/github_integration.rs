@@ -0,0 +1,560 @@
+//! Integration test: GitHub client against a local mock server
+
+use chrono::{TimeZone, Utc};
+use poe_item_analyzer_api::{
+    data_files_from_listing, ApiError, CommitSummary, DataDownloader, DataManifest, DataSource, DownloadError,
+    GitHubClient, GitHubConfig, HttpConfig, RetryPolicy, UpdateChecker,
+};
+use std::time::Duration;
+use tempfile::TempDir;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const REPO: &str = "PathOfBuildingCommunity/PathOfBuilding";
+const DATA_PATH: &str = "src/Data/TimelessJewelData";
+
+/// 2025-01-01T00:00:00Z
+const RESET_EPOCH: i64 = 1_735_689_600;
+
+fn commits_path() -> String {
+    format!("/repos/{}/commits", REPO)
+}
+
+fn commit_json(sha: &str) -> serde_json::Value {
+    commits_json(&[sha])
+}
+
+fn commits_json(shas: &[&str]) -> serde_json::Value {
+    shas.iter()
+        .map(|sha| {
+            serde_json::json!({
+                "sha": sha,
+                "commit": {
+                    "message": format!("Update {}\n\nLonger description", sha),
+                    "author": {
+                        "name": "PoB",
+                        "email": "pob@example.com",
+                        "date": "2025-01-01T00:00:00Z"
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+fn data_source() -> DataSource {
+    DataSource {
+        source_type: "github".to_string(),
+        repo: REPO.to_string(),
+        branch: "master".to_string(),
+        path: DATA_PATH.to_string(),
+        url: format!("https://github.com/{}", REPO),
+        mirrors: Vec::new(),
+        raw_url: None,
+        api_url: None,
+    }
+}
+
+fn contents_path() -> String {
+    format!("/repos/{}/contents/{}", REPO, DATA_PATH)
+}
+
+fn contents_entry(name: &str, sha: &str, size: u64, download: bool) -> serde_json::Value {
+    let path = format!("{}/{}", DATA_PATH, name);
+    serde_json::json!({
+        "name": name,
+        "path": path,
+        "sha": sha,
+        "size": size,
+        "url": format!("https://api.github.com/repos/{}/contents/{}", REPO, path),
+        "download_url": download
+            .then(|| format!("https://raw.githubusercontent.com/{}/master/{}", REPO, path)),
+        "type": "file"
+    })
+}
+
+async fn mount_rate_limited(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .respond_with(
+            ResponseTemplate::new(403)
+                .insert_header("X-RateLimit-Limit", "60")
+                .insert_header("X-RateLimit-Remaining", "0")
+                .insert_header("X-RateLimit-Reset", RESET_EPOCH.to_string().as_str())
+                .set_body_string("{\"message\":\"API rate limit exceeded\"}"),
+        )
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_rate_limited_response_maps_to_rate_limited() {
+    let server = MockServer::start().await;
+    mount_rate_limited(&server).await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let error = client.get_latest_commit(REPO, DATA_PATH).await.unwrap_err();
+
+    match error {
+        ApiError::RateLimited { reset_at, .. } => {
+            assert_eq!(reset_at, Utc.timestamp_opt(RESET_EPOCH, 0).single());
+        }
+        other => panic!("Expected RateLimited, got {:?}", other),
+    }
+
+    let status = client.rate_limit_status().unwrap();
+    assert_eq!(status.limit, Some(60));
+    assert_eq!(status.remaining, Some(0));
+}
+
+#[tokio::test]
+async fn test_server_errors_are_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(commits_json(&["abc123"])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new()
+        .with_api_url(server.uri())
+        .with_retry_policy(RetryPolicy {
+            initial_delay: std::time::Duration::ZERO,
+            ..RetryPolicy::default()
+        });
+    let commit = client.get_latest_commit(REPO, DATA_PATH).await.unwrap();
+
+    assert_eq!(commit.sha, "abc123");
+}
+
+#[tokio::test]
+async fn test_forbidden_without_rate_limit_is_generic_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(403).insert_header("X-RateLimit-Remaining", "42"))
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let error = client.get_latest_commit(REPO, DATA_PATH).await.unwrap_err();
+
+    assert!(matches!(error, ApiError::ApiError(_)));
+}
+
+#[tokio::test]
+async fn test_successful_response_records_rate_limit() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-RateLimit-Limit", "60")
+                .insert_header("X-RateLimit-Remaining", "59")
+                .set_body_json(commit_json("abc123")),
+        )
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let commit = client.get_latest_commit(REPO, DATA_PATH).await.unwrap();
+
+    assert_eq!(commit.sha, "abc123");
+    assert_eq!(client.rate_limit_status().unwrap().remaining, Some(59));
+}
+
+#[tokio::test]
+async fn test_update_checker_surfaces_rate_limit() {
+    let server = MockServer::start().await;
+    mount_rate_limited(&server).await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let manifest_path = temp_dir.path().join("manifest.json");
+    DataManifest {
+        data_version: "old-sha".to_string(),
+        poe_league: "Test".to_string(),
+        last_updated: "2025-01-01T00:00:00Z".to_string(),
+        source: data_source(),
+        files: Vec::new(),
+        ignored_versions: Vec::new(),
+        parsed_artifact: None,
+    }
+    .save_to_file(&manifest_path)
+    .unwrap();
+
+    let checker = UpdateChecker::new(manifest_path)
+        .with_github_client(GitHubClient::new().with_api_url(server.uri()));
+
+    let error = checker.check_for_updates().await.unwrap_err();
+
+    match error {
+        DownloadError::RateLimited { message, reset_at } => {
+            assert!(message.contains("try again at"));
+            assert_eq!(reset_at, Utc.timestamp_opt(RESET_EPOCH, 0).single());
+        }
+        other => panic!("Expected RateLimited, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_token_sent_as_bearer_header() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .and(header("Authorization", "Bearer ghp_test_token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(commit_json("abc123")))
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new()
+        .with_api_url(server.uri())
+        .with_token("ghp_test_token");
+
+    client.get_latest_commit(REPO, DATA_PATH).await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("User-Agent").is_some());
+}
+
+#[tokio::test]
+async fn test_no_authorization_header_without_token() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(commit_json("abc123")))
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::from_config(&GitHubConfig {
+        token: None,
+        api_url: Some(server.uri()),
+    });
+
+    client.get_latest_commit(REPO, DATA_PATH).await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("Authorization").is_none());
+}
+
+#[tokio::test]
+async fn test_invalid_token_maps_to_unauthorized() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(401).set_body_string("{\"message\":\"Bad credentials\"}"),
+        )
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new()
+        .with_api_url(server.uri())
+        .with_token("ghp_revoked");
+
+    let error = client.get_latest_commit(REPO, DATA_PATH).await.unwrap_err();
+
+    match error {
+        ApiError::Unauthorized(message) => {
+            assert!(message.contains("token"));
+            assert!(!message.contains("ghp_revoked"));
+        }
+        other => panic!("Expected Unauthorized, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_list_directory_converts_to_data_files() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(contents_path()))
+        .and(query_param("ref", "master"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            contents_entry("LethalPride.zip", "sha-lp", 1000, true),
+            contents_entry("NodeIndexMapping.lua", "sha-nim", 200, true),
+            contents_entry("LegionPassives.lua", "sha-lgp", 300, false),
+            {
+                "name": "old",
+                "path": format!("{}/old", DATA_PATH),
+                "sha": "sha-dir",
+                "size": 0,
+                "url": "https://api.github.com/dir",
+                "download_url": null,
+                "type": "dir"
+            }
+        ])))
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let listing = client.list_directory(REPO, DATA_PATH, "master").await.unwrap();
+    assert_eq!(listing.len(), 4);
+
+    let files = data_files_from_listing(&listing, &data_source());
+    assert_eq!(files.len(), 3);
+
+    assert_eq!(files[0].name, "LethalPride.zip");
+    assert_eq!(files[0].github_sha, "sha-lp");
+    assert_eq!(files[0].size, 1000);
+    assert_eq!(
+        files[0].url,
+        format!(
+            "https://raw.githubusercontent.com/{}/master/{}/LethalPride.zip",
+            REPO, DATA_PATH
+        )
+    );
+
+    // Missing download_url falls back to the raw URL for the source
+    assert_eq!(files[2].name, "LegionPassives.lua");
+    assert_eq!(files[2].github_sha, "sha-lgp");
+    assert_eq!(
+        files[2].url,
+        format!(
+            "https://raw.githubusercontent.com/{}/master/{}/LegionPassives.lua",
+            REPO, DATA_PATH
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_get_file_info() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("{}/LethalPride.zip", contents_path())))
+        .and(query_param("ref", "master"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(contents_entry("LethalPride.zip", "sha-lp", 1000, true)),
+        )
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let file = client
+        .get_file_info(REPO, &format!("{}/LethalPride.zip", DATA_PATH), "master")
+        .await
+        .unwrap();
+
+    assert!(file.is_file());
+    assert_eq!(file.name, "LethalPride.zip");
+    assert_eq!(file.sha, "sha-lp");
+    assert_eq!(file.size, 1000);
+}
+
+#[tokio::test]
+async fn test_listing_downloads_from_source_raw_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(contents_path()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            contents_entry("NodeIndexMapping.lua", "sha-nim", 5, false),
+            contents_entry("LegionPassives.lua", "sha-lgp", 8, false),
+        ])))
+        .mount(&server)
+        .await;
+    for (name, body) in [("NodeIndexMapping.lua", "nodes"), ("LegionPassives.lua", "passives")] {
+        Mock::given(method("GET"))
+            .and(path(format!("/{}/master/{}/{}", REPO, DATA_PATH, name)))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(&server)
+            .await;
+    }
+
+    let source = DataSource {
+        raw_url: Some(server.uri()),
+        api_url: Some(server.uri()),
+        ..data_source()
+    };
+    let client = GitHubClient::new().with_api_url(source.api_url.as_deref().unwrap());
+    let listing = client.list_directory(REPO, DATA_PATH, "master").await.unwrap();
+
+    let mut manifest = DataManifest::default_pob();
+    manifest.source = source.clone();
+    manifest.files = data_files_from_listing(&listing, &source);
+    assert!(manifest.files.iter().all(|file| file.url.starts_with(&server.uri())));
+
+    let temp_dir = TempDir::new().unwrap();
+    DataDownloader::new(temp_dir.path().to_path_buf())
+        .download_manifest(&manifest, |_| {})
+        .await
+        .unwrap();
+
+    let passives = std::fs::read_to_string(temp_dir.path().join("LegionPassives.lua")).unwrap();
+    assert_eq!(passives, "passives");
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_list_directory_follows_pagination() {
+    let server = MockServer::start().await;
+    let next = format!("{}{}?ref=master&page=2", server.uri(), contents_path());
+
+    Mock::given(method("GET"))
+        .and(path(contents_path()))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            contents_entry("GloriousVanity.zip", "sha-gv", 5000, true)
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(contents_path()))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Link", format!("<{}>; rel=\"next\"", next).as_str())
+                .set_body_json(serde_json::json!([
+                    contents_entry("LethalPride.zip", "sha-lp", 1000, true)
+                ])),
+        )
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let listing = client.list_directory(REPO, DATA_PATH, "master").await.unwrap();
+
+    let names: Vec<_> = listing.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["LethalPride.zip", "GloriousVanity.zip"]);
+}
+
+#[tokio::test]
+async fn test_list_directory_rejects_file_path() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(contents_path()))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(contents_entry("LethalPride.zip", "sha-lp", 1000, true)),
+        )
+        .mount(&server)
+        .await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let error = client
+        .list_directory(REPO, DATA_PATH, "master")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, ApiError::InvalidResponse(msg) if msg.contains("not a directory")));
+}
+
+async fn mount_commit_pages(server: &MockServer) {
+    let next = format!("{}{}?path={}&page=2", server.uri(), commits_path(), DATA_PATH);
+
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .and(query_param("page", "2"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(commits_json(&["c4", "installed", "c6"])),
+        )
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Link", format!("<{}>; rel=\"next\"", next).as_str())
+                .set_body_json(commits_json(&["c1", "c2", "c3"])),
+        )
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_commits_since_follows_pages_until_installed_version() {
+    let server = MockServer::start().await;
+    mount_commit_pages(&server).await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let commits = client
+        .get_commits_since(REPO, DATA_PATH, "installed", 50)
+        .await
+        .unwrap();
+
+    let shas: Vec<_> = commits.iter().map(|c| c.sha.as_str()).collect();
+    assert_eq!(shas, vec!["c1", "c2", "c3", "c4"]);
+
+    let summary = CommitSummary::from(&commits[0]);
+    assert_eq!(summary.message, "Update c1");
+    assert_eq!(summary.author, "PoB");
+}
+
+#[tokio::test]
+async fn test_commits_since_truncates_at_limit() {
+    let server = MockServer::start().await;
+    mount_commit_pages(&server).await;
+
+    let client = GitHubClient::new().with_api_url(server.uri());
+    let commits = client
+        .get_commits_since(REPO, DATA_PATH, "installed", 2)
+        .await
+        .unwrap();
+
+    let shas: Vec<_> = commits.iter().map(|c| c.sha.as_str()).collect();
+    assert_eq!(shas, vec!["c1", "c2"]);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_http_config_proxy_routes_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(commits_path()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(commit_json("abc123")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // The host doesn't resolve; only the proxy can answer
+    let config = HttpConfig {
+        proxy: Some(server.uri()),
+        ..HttpConfig::default()
+    };
+    let client = GitHubClient::new()
+        .with_http_config(&config)
+        .unwrap()
+        .with_api_url("http://api.github.invalid");
+
+    let commit = client.get_latest_commit(REPO, DATA_PATH).await.unwrap();
+    assert_eq!(commit.sha, "abc123");
+}
+
+#[tokio::test]
+async fn test_http_config_read_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+
+    let config = HttpConfig {
+        read_timeout: Duration::from_millis(200),
+        ..HttpConfig::default()
+    };
+    let client = GitHubClient::new()
+        .with_http_config(&config)
+        .unwrap()
+        .with_api_url(server.uri())
+        .with_retry_policy(RetryPolicy::none());
+
+    let started = std::time::Instant::now();
+    let error = client.get_latest_commit(REPO, DATA_PATH).await.unwrap_err();
+
+    assert!(matches!(error, ApiError::RequestFailed(_)), "{:?}", error);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_http_config_rejects_invalid_proxy() {
+    let config = HttpConfig {
+        proxy: Some("not a proxy url".to_string()),
+        ..HttpConfig::default()
+    };
+
+    assert!(GitHubClient::new().with_http_config(&config).is_err());
+    assert!(DataDownloader::new("data".into()).with_http_config(&config).is_err());
+}
@@ -0,0 +1,135 @@
+//! Integration test: shared retry handling against a local mock server
+
+use std::time::{Duration, Instant};
+
+use poe_item_analyzer_api::{send_with_retry, RetryPolicy};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn no_delay_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_delay: Duration::ZERO,
+        ..RetryPolicy::default()
+    }
+}
+
+async fn get(server: &MockServer, policy: &RetryPolicy) -> reqwest::Response {
+    let client = reqwest::Client::new();
+    let request = client.get(format!("{}/resource", server.uri()));
+    send_with_retry(&client, request, policy).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_retries_server_errors_until_success() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = get(&server, &no_delay_retries(4)).await;
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "ok");
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let response = get(&server, &no_delay_retries(3)).await;
+
+    assert_eq!(response.status(), 500);
+}
+
+#[tokio::test]
+async fn test_does_not_retry_client_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = get(&server, &no_delay_retries(4)).await;
+
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_honors_retry_after() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let started = Instant::now();
+    let response = get(&server, &no_delay_retries(2)).await;
+
+    assert_eq!(response.status(), 200);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_long_or_missing_retry_after_is_returned() {
+    for retry_after in [Some("3600"), None] {
+        let server = MockServer::start().await;
+        let mut template = ResponseTemplate::new(429);
+        if let Some(value) = retry_after {
+            template = template.insert_header("Retry-After", value);
+        }
+        Mock::given(method("GET"))
+            .and(path("/resource"))
+            .respond_with(template)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = get(&server, &no_delay_retries(4)).await;
+
+        assert_eq!(response.status(), 429);
+    }
+}
+
+#[tokio::test]
+async fn test_retries_connection_errors() {
+    // Nothing listens on this port once the listener is dropped
+    let uri = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+
+    let client = reqwest::Client::new();
+    let request = client.get(format!("{}/resource", uri));
+    let result = send_with_retry(&client, request, &no_delay_retries(3)).await;
+
+    let error = result.unwrap_err();
+    assert!(error.is_connect());
+}
@@ -0,0 +1,34 @@
+//! Integration test: starter config from a Path of Building build code
+
+use std::collections::HashSet;
+
+use poe_item_analyzer_api::{Attribute, PobBuild};
+
+const BUILD_CODE: &str = include_str!("fixtures/build_code.txt");
+
+#[test]
+fn test_build_code_gives_nodes_and_class() {
+    let build = PobBuild::from_code(BUILD_CODE).unwrap();
+
+    assert_eq!(build.class_name, "Duelist");
+    assert_eq!(build.ascendancy.as_deref(), Some("Slayer"));
+    assert_eq!(build.level, Some(94));
+
+    // The active spec is the second one
+    let nodes: HashSet<u32> = [50986, 26725, 36634, 33545, 61419, 12809, 55190].into();
+    assert_eq!(build.allocated_nodes, nodes);
+
+    assert_eq!(build.attributes, Some([281.0, 226.0, 118.0]));
+    assert_eq!(build.main_attributes(), vec![Attribute::Strength, Attribute::Dexterity]);
+}
+
+#[test]
+fn test_starter_config() {
+    let config = PobBuild::from_code(BUILD_CODE).unwrap().to_config();
+
+    assert_eq!(config.allocated_nodes.as_ref().map(HashSet::len), Some(7));
+    assert_eq!(config.valuable_mods().len(), 8);
+    assert_eq!(config.valuable_mods().get("+20 to Strength"), Some(&2.0));
+    assert_eq!(config.valuable_mods().get("+2 to Dexterity"), Some(&0.2));
+    assert!(config.valuable_mods().get("+20 to Intelligence").is_none());
+}
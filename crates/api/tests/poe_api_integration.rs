@@ -0,0 +1,385 @@
+//! Integration test: PoE stash API client against a local mock server
+
+use poe_item_analyzer_api::poe_api::build_search_payload;
+use poe_item_analyzer_api::{
+    current_challenge_league, ApiError, HttpConfig, PoeApiClient, RetryPolicy,
+};
+use std::time::Duration;
+use poe_item_analyzer_core::items::JewelType;
+use wiremock::matchers::{body_json, header, method, path, path_regex, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const STASH_PATH: &str = "/character-window/get-stash-items";
+const SESSION_ID: &str = "0123456789abcdef0123456789abcdef";
+
+fn stash_fixture() -> serde_json::Value {
+    serde_json::from_str(include_str!("fixtures/stash_tab.json")).unwrap()
+}
+
+fn client(server: &MockServer) -> PoeApiClient {
+    PoeApiClient::new().with_base_url(server.uri())
+}
+
+#[tokio::test]
+async fn test_get_stash_tabs() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .and(query_param("accountName", "Some Account"))
+        .and(query_param("league", "Settlers"))
+        .and(query_param("tabs", "1"))
+        .and(header("cookie", format!("POESESSID={}", SESSION_ID).as_str()))
+        .and(header("user-agent", "poe-item-analyzer/0.1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(stash_fixture()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let tabs = client(&server)
+        .get_stash_tabs("Some Account", "Settlers", SESSION_ID)
+        .await
+        .unwrap();
+
+    assert_eq!(tabs.len(), 3);
+    assert_eq!(tabs[0].name, "Jewels");
+    assert_eq!(tabs[0].index, 0);
+    assert_eq!(tabs[0].tab_type, "PremiumStash");
+    assert_eq!(tabs[2].name, "Dump");
+    assert_eq!(tabs[2].tab_type, "QuadStash");
+}
+
+#[tokio::test]
+async fn test_get_stash_items() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .and(query_param("tabIndex", "0"))
+        .and(query_param("tabs", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(stash_fixture()))
+        .mount(&server)
+        .await;
+
+    let items = client(&server)
+        .get_stash_items("Some Account", "Settlers", 0, SESSION_ID)
+        .await
+        .unwrap();
+
+    assert_eq!(items.len(), 3);
+
+    let timeless: Vec<_> = items
+        .iter()
+        .filter(|item| item.type_line == "Timeless Jewel")
+        .collect();
+    assert_eq!(timeless.len(), 2);
+
+    let lethal_pride = timeless[0];
+    assert_eq!(lethal_pride.name, "Lethal Pride");
+    assert_eq!(
+        lethal_pride.explicit_mods[0],
+        "Commanded leadership over 18000 warriors under Kaom"
+    );
+    assert_eq!((lethal_pride.x, lethal_pride.y), (0, 0));
+    assert!(lethal_pride.id.starts_with("9e4f0c6d"));
+
+    let glorious_vanity = timeless[1];
+    assert_eq!(glorious_vanity.name, "Glorious Vanity");
+    assert_eq!((glorious_vanity.x, glorious_vanity.y), (1, 0));
+
+    // Fields without a typed model are kept
+    let raw = glorious_vanity.to_json();
+    assert_eq!(raw["corrupted"], true);
+    assert_eq!(raw["typeLine"], "Timeless Jewel");
+    assert_eq!(raw["ilvl"], 84);
+}
+
+#[tokio::test]
+async fn test_unauthorized_session_is_not_leaked() {
+    for status in [401, 403] {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(STASH_PATH))
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&server)
+            .await;
+
+        let poe = client(&server);
+        let error = poe
+            .get_stash_items("Some Account", "Settlers", 0, SESSION_ID)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, ApiError::Unauthorized(_)), "{}: {:?}", status, error);
+        assert!(!error.to_string().contains(SESSION_ID));
+        assert!(!format!("{:?}", error).contains(SESSION_ID));
+        assert!(!format!("{:?}", poe).contains(SESSION_ID));
+    }
+}
+
+#[tokio::test]
+async fn test_rate_limited() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "60"))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let error = client
+        .get_stash_tabs("Some Account", "Settlers", SESSION_ID)
+        .await
+        .unwrap_err();
+
+    match error {
+        ApiError::RateLimited { message, reset_at } => {
+            assert!(message.contains("60s"));
+            let wait = reset_at.unwrap() - chrono::Utc::now();
+            assert!(wait.num_seconds() > 50 && wait.num_seconds() <= 60);
+        }
+        other => panic!("Expected RateLimited, got {:?}", other),
+    }
+
+    // The next request has to wait out Retry-After
+    let state = client.rate_limit_state();
+    assert!(state.is_waiting());
+    assert!(state.wait.as_secs() > 50 && state.wait.as_secs() <= 60);
+}
+
+#[tokio::test]
+async fn test_rate_limit_headers_are_tracked() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(stash_fixture())
+                .insert_header("X-Rate-Limit-Rules", "Account")
+                .insert_header("X-Rate-Limit-Account", "45:60:120,240:240:900")
+                .insert_header("X-Rate-Limit-Account-State", "7:60:0,12:240:0"),
+        )
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    client
+        .get_stash_tabs("Some Account", "Settlers", SESSION_ID)
+        .await
+        .unwrap();
+
+    let state = client.rate_limit_state();
+    assert!(!state.is_waiting());
+    assert_eq!(state.windows.len(), 2);
+    assert_eq!(state.windows[0].rule, "Account");
+    assert_eq!((state.windows[0].hits, state.windows[0].max_hits), (7, 45));
+    assert_eq!((state.windows[1].hits, state.windows[1].max_hits), (12, 240));
+}
+
+#[tokio::test]
+async fn test_retries_go_through_the_rate_limiter() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .respond_with(
+            ResponseTemplate::new(503)
+                .insert_header("X-Rate-Limit-Rules", "Account")
+                .insert_header("X-Rate-Limit-Account", "45:60:120")
+                .insert_header("X-Rate-Limit-Account-State", "1:60:0"),
+        )
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .respond_with(ResponseTemplate::new(200).set_body_json(stash_fixture()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server).with_retry_policy(RetryPolicy {
+        initial_delay: Duration::ZERO,
+        ..RetryPolicy::default()
+    });
+    client
+        .get_stash_tabs("Some Account", "Settlers", SESSION_ID)
+        .await
+        .unwrap();
+
+    // The 503 taught the limiter the rule and its first hit; the retry
+    // acquired a second one
+    let state = client.rate_limit_state();
+    assert_eq!(state.windows.len(), 1);
+    assert_eq!(state.windows[0].hits, 2);
+}
+
+#[tokio::test]
+async fn test_get_leagues() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/leagues"))
+        .and(query_param("type", "main"))
+        .and(query_param("realm", "pc"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/leagues.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let leagues = client(&server).get_leagues().await.unwrap();
+    assert_eq!(leagues.len(), 7);
+
+    let now = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 8, 15, 12, 0, 0).unwrap();
+    let league = current_challenge_league(&leagues, now).unwrap();
+    assert_eq!(league.id, "Settlers");
+    assert!(league.end_at.is_none());
+
+    let necropolis = leagues.iter().find(|l| l.id == "Necropolis").unwrap();
+    assert!(necropolis.end_at.is_some());
+    assert!(!necropolis.is_active_at(now));
+}
+
+#[tokio::test]
+async fn test_get_character_passives() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/character-window/get-passive-skills"))
+        .and(query_param("accountName", "Some Account"))
+        .and(query_param("character", "SomeWitch"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/passive_skills.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let passives = client(&server)
+        .get_character_passives("Some Account", "SomeWitch")
+        .await
+        .unwrap();
+
+    let nodes = passives.allocated_nodes();
+    assert_eq!(nodes.len(), 8);
+    assert!(nodes.contains(&36634));
+    assert_eq!(passives.timeless_jewel_socket(), Some(12));
+}
+
+#[tokio::test]
+async fn test_private_character_passives() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/character-window/get-passive-skills"))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&server)
+        .await;
+
+    let error = client(&server)
+        .get_character_passives("Some Account", "SomeWitch")
+        .await
+        .unwrap_err();
+
+    match error {
+        ApiError::Unauthorized(message) => {
+            assert!(message.contains("Some Account"));
+            assert!(message.contains("private"));
+        }
+        other => panic!("Expected Unauthorized, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_trade_search_and_fetch() {
+    let payload =
+        build_search_payload(JewelType::LethalPride, 14032..=14032, Some("Kaom")).unwrap();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/trade/search/Settlers"))
+        .and(body_json(&payload))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "Gl4Dk5Ec9",
+            "complexity": 8,
+            "result": ["a1b2c3d4", "deadbeef", "0f1e2d3c"],
+            "total": 3
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/trade/fetch/a1b2c3d4,deadbeef,0f1e2d3c"))
+        .and(query_param("query", "Gl4Dk5Ec9"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/trade_fetch.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let search = client.search("Settlers", &payload).await.unwrap();
+    assert_eq!(search.id, "Gl4Dk5Ec9");
+    assert_eq!(search.total, 3);
+
+    let listings = client.fetch(&search.result, &search.id).await.unwrap();
+
+    // The delisted entry (null) is skipped
+    assert_eq!(listings.len(), 2);
+
+    let first = &listings[0];
+    assert_eq!(first.seller, "Seller#1234");
+    let price = first.price.as_ref().unwrap();
+    assert_eq!(price.amount, 2.0);
+    assert_eq!(price.currency, "divine");
+    assert!(first.whisper.as_ref().unwrap().starts_with("@SellerChar"));
+    assert!(first.indexed.is_some());
+    assert_eq!(first.item.name, "Lethal Pride");
+    assert!(first.item.explicit_mods[0].contains("14032"));
+
+    assert_eq!(listings[1].seller, "Other#5678");
+    assert!(listings[1].price.is_none());
+}
+
+#[tokio::test]
+async fn test_trade_fetch_batches() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/api/trade/fetch/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"result": []})))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let ids: Vec<String> = (0..15).map(|i| format!("id{}", i)).collect();
+    let listings = client(&server).fetch(&ids, "query").await.unwrap();
+
+    assert!(listings.is_empty());
+}
+
+#[tokio::test]
+async fn test_http_config_proxy_routes_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/leagues"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/leagues.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // The host doesn't resolve; only the proxy can answer
+    let config = HttpConfig {
+        proxy: Some(server.uri()),
+        ..HttpConfig::default()
+    };
+    let client = PoeApiClient::new()
+        .with_http_config(&config)
+        .unwrap()
+        .with_base_url("http://www.pathofexile.invalid");
+
+    assert_eq!(client.get_leagues().await.unwrap().len(), 7);
+}
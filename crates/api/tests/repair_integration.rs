@@ -0,0 +1,137 @@
+//! Integration test: repairing a damaged data directory against a mock server
+
+use poe_item_analyzer_api::checksum::calculate_sha256_bytes;
+use poe_item_analyzer_api::{repair, DataFile, DataManifest, DataSource, PobDataParser};
+use std::path::PathBuf;
+use tempfile::TempDir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const NODE_INDEX_MAPPING: &str = r#"nodeIDList = {}
+nodeIDList["size"] = 2
+nodeIDList["sizeNotable"] = 1
+nodeIDList[100] = { index = 0, size = 1 }
+nodeIDList[200] = { index = 1, size = 1 }
+"#;
+
+const PASSIVES: &str = r#"return {
+    additions = {
+        [1] = { id = "karui_notable_add_strength", dn = "Strength", sd = { "+20 to Strength" } },
+    },
+}
+"#;
+
+const FILES: [(&str, &str); 2] = [
+    ("NodeIndexMapping.lua", NODE_INDEX_MAPPING),
+    ("LegionPassives.lua", PASSIVES),
+];
+
+/// Intact data directory with a manifest recording each file's checksum
+fn create_data_dir(server: &MockServer) -> (TempDir, PathBuf, DataManifest) {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    let files = FILES
+        .iter()
+        .map(|(name, body)| {
+            std::fs::write(data_dir.join(name), body).unwrap();
+            DataFile {
+                name: name.to_string(),
+                url: format!("{}/data/{}", server.uri(), name),
+                sha256: calculate_sha256_bytes(body.as_bytes()),
+                github_sha: String::new(),
+                size: body.len() as u64,
+                required: true,
+                description: String::new(),
+                parts: Vec::new(),
+            }
+        })
+        .collect();
+
+    let manifest = DataManifest {
+        data_version: "sha".to_string(),
+        poe_league: "Test".to_string(),
+        last_updated: "2025-01-01T00:00:00Z".to_string(),
+        source: DataSource {
+            source_type: "url".to_string(),
+            repo: String::new(),
+            branch: String::new(),
+            path: String::new(),
+            url: server.uri(),
+            mirrors: Vec::new(),
+            raw_url: None,
+            api_url: None,
+        },
+        files,
+        ignored_versions: Vec::new(),
+        parsed_artifact: None,
+    };
+
+    (temp_dir, data_dir, manifest)
+}
+
+async fn mount_files(server: &MockServer) {
+    for (name, body) in FILES {
+        Mock::given(method("GET"))
+            .and(path(format!("/data/{}", name)))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(server)
+            .await;
+    }
+}
+
+async fn requested_paths(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_repair_refetches_only_the_damaged_file() {
+    let server = MockServer::start().await;
+    mount_files(&server).await;
+    let (_temp_dir, data_dir, manifest) = create_data_dir(&server);
+
+    // Same size, different bytes: only the checksum can tell
+    let damaged = PASSIVES.replace("Strength", "Strengtj");
+    std::fs::write(data_dir.join("LegionPassives.lua"), damaged).unwrap();
+
+    let (data, report) = repair(&data_dir, &manifest).await.unwrap();
+
+    assert_eq!(report.damaged.len(), 1);
+    assert_eq!(report.damaged[0].0, "LegionPassives.lua");
+    assert_eq!(report.downloaded, vec!["LegionPassives.lua"]);
+    assert_eq!(requested_paths(&server).await, vec!["/data/LegionPassives.lua"]);
+
+    assert_eq!(
+        std::fs::read_to_string(data_dir.join("LegionPassives.lua")).unwrap(),
+        PASSIVES
+    );
+    assert!(data.modifiers.contains_key("karui_notable_add_strength"));
+    assert_eq!(data.node_indices.len(), 2);
+    assert!(report.reparsed.contains(&"LegionPassives.lua".to_string()));
+}
+
+#[tokio::test]
+async fn test_repair_reuses_current_parsed_data() {
+    let server = MockServer::start().await;
+    mount_files(&server).await;
+    let (_temp_dir, data_dir, mut manifest) = create_data_dir(&server);
+
+    let (data, _) = PobDataParser::parse_directory(&data_dir).unwrap();
+    let artifact = data_dir.join("lut_data.json");
+    PobDataParser::save_to_json(&data, &artifact).unwrap();
+    manifest.record_parsed_artifact(&data_dir, &artifact, "json").unwrap();
+
+    let (data, report) = repair(&data_dir, &manifest).await.unwrap();
+
+    assert!(report.is_clean());
+    assert!(report.reparsed.is_empty());
+    assert!(requested_paths(&server).await.is_empty());
+    assert!(data.modifiers.contains_key("karui_notable_add_strength"));
+}
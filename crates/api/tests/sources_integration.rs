@@ -0,0 +1,99 @@
+//! Integration test: item sources against a local mock server
+
+use std::sync::Arc;
+
+use poe_item_analyzer_api::{ApiError, ItemSource, PoeApiClient, SourceError, StashTabSource};
+use poe_item_analyzer_core::items::JewelType;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const STASH_PATH: &str = "/character-window/get-stash-items";
+const SESSION_ID: &str = "0123456789abcdef0123456789abcdef";
+
+fn source(server: &MockServer) -> StashTabSource {
+    let client = Arc::new(PoeApiClient::new().with_base_url(server.uri()));
+    StashTabSource::new(client, "Some Account", "Settlers", 2, SESSION_ID)
+}
+
+#[tokio::test]
+async fn test_stash_tab_source() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .and(query_param("tabIndex", "2"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/stash_jewels.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let source = source(&server);
+    let jewels = source.fetch_items().await.unwrap();
+
+    // The cluster jewel and the unreadable Brutal Restraint are left out
+    assert_eq!(jewels.len(), 2);
+    assert_eq!(jewels[0].jewel_type, JewelType::LethalPride);
+    assert_eq!(jewels[1].jewel_type, JewelType::GloriousVanity);
+    assert_eq!(source.source_name(), "Stash tab 2 (Settlers)");
+}
+
+#[tokio::test]
+async fn test_stash_tab_source_fetch_extracted() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/stash_jewels.json"), "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let extracted = source(&server).fetch_extracted().await.unwrap();
+
+    assert_eq!(extracted.jewels.len(), 2);
+    assert_eq!(extracted.skipped.len(), 2);
+}
+
+#[tokio::test]
+async fn test_stash_tab_source_api_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&server)
+        .await;
+
+    let error = source(&server).fetch_items().await.unwrap_err();
+
+    assert!(matches!(error, SourceError::ApiError(ApiError::Unauthorized(_))));
+}
+
+#[tokio::test]
+async fn test_sources_as_trait_objects() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(STASH_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/stash_tab.json"), "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let sources: Vec<Box<dyn ItemSource>> = vec![
+        Box::new(source(&server)),
+        Box::new(poe_item_analyzer_api::LocalFileSource::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/stash_tab.json"
+        ))),
+    ];
+
+    let mut total = 0;
+    for source in &sources {
+        total += source.fetch_items().await.unwrap().len();
+    }
+    assert_eq!(total, 4);
+}
@@ -0,0 +1,92 @@
+//! Integration test: trade stat ids from parsing to a trade search payload
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use poe_item_analyzer_api::parser::{JewelLutData, LutEntry};
+use poe_item_analyzer_api::poe_api::{add_mod_filters, build_search_payload};
+use poe_item_analyzer_api::PobDataParser;
+use poe_item_analyzer_core::analyzers::{Analyzer, TimelessJewelAnalyzer, TimelessJewelConfig};
+use poe_item_analyzer_core::items::{JewelType, TimelessJewel};
+use tempfile::TempDir;
+
+const NODE_INDEX_MAPPING: &str = r#"nodeIDList = {}
+nodeIDList["size"] = 2
+nodeIDList["sizeNotable"] = 1
+nodeIDList[100] = { index = 0, size = 1 }
+nodeIDList[200] = { index = 1, size = 1 }
+"#;
+
+const PASSIVES: &str = r#"return {
+    additions = {
+        [1] = { id = "karui_notable_add_strength", dn = "Strength", sd = { "+20 to Strength" } },
+        [2] = { id = "karui_notable_onslaught", dn = "Onslaught", sd = { "Gain Onslaught on Kill" } },
+    },
+}
+"#;
+
+#[test]
+fn test_mapped_and_unmapped_mods_reach_the_search_payload() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("NodeIndexMapping.lua"), NODE_INDEX_MAPPING).unwrap();
+    std::fs::write(temp_dir.path().join("LegionPassives.lua"), PASSIVES).unwrap();
+
+    let (mut lut_data, report) = PobDataParser::parse_directory(temp_dir.path()).unwrap();
+
+    // The unmapped modifier is kept and listed
+    assert_eq!(report.unmapped_trade_stats, vec!["karui_notable_onslaught"]);
+    assert!(report.warnings.iter().any(|w| w == "1 of 2 modifiers have no trade stat id"));
+    assert!(lut_data.modifiers["karui_notable_onslaught"].trade_stat_id.is_none());
+    assert_eq!(
+        lut_data.modifiers["karui_notable_add_strength"].trade_stat_id.as_deref(),
+        Some("explicit.stat_4080418644")
+    );
+
+    lut_data.jewels.insert(
+        "LethalPride".to_string(),
+        JewelLutData {
+            jewel_type: "LethalPride".to_string(),
+            seed_range: (10000, 18000),
+            lookup_table: HashMap::from([(
+                14032,
+                HashMap::from([
+                    (0, LutEntry::from("karui_notable_add_strength")),
+                    (1, LutEntry::from("karui_notable_onslaught")),
+                ]),
+            )]),
+            variants: HashMap::new(),
+            truncated: false,
+        },
+    );
+
+    let jewel = TimelessJewel::new(
+        "Lethal Pride:14032:Kaom".to_string(),
+        JewelType::LethalPride,
+        14032,
+        "Kaom".to_string(),
+        serde_json::Value::Null,
+    );
+    let mut config = TimelessJewelConfig::new();
+    config.add_mod("+20 to Strength".to_string(), 2.0);
+    config.add_mod("Onslaught".to_string(), 1.0);
+
+    let result = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(lut_data))
+        .analyze(&jewel, &config)
+        .unwrap();
+    let matched = &result.metrics.socket_results[0].matched_mods;
+    assert_eq!(matched.len(), 2);
+
+    let mut payload =
+        build_search_payload(JewelType::LethalPride, 14032..=14032, Some("Kaom")).unwrap();
+    let unmapped = add_mod_filters(&mut payload, matched);
+
+    assert_eq!(unmapped, vec!["Onslaught"]);
+    let stats = payload["query"]["stats"].as_array().unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[1]["type"], "and");
+    assert_eq!(
+        stats[1]["filters"],
+        serde_json::json!([{ "id": "explicit.stat_4080418644", "value": { "min": 1 } }])
+    );
+}
@@ -0,0 +1,525 @@
+//! Integration test: one-call update workflow against a local mock server
+
+use poe_item_analyzer_api::checksum::calculate_git_blob_sha_bytes;
+use poe_item_analyzer_api::{
+    CancellationToken, ChangedFile, DataFile, DataLayout, DataManifest, DataSource, DownloadError,
+    DownloadEvent, GitHubClient, PobDataParser, UpdateChecker, UpdateEvent, UpdateStage,
+};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const REPO: &str = "PathOfBuildingCommunity/PathOfBuilding";
+const DATA_PATH: &str = "src/Data/TimelessJewelData";
+
+const NODE_INDEX_MAPPING: &str = r#"nodeIDList = {}
+nodeIDList["size"] = 2
+nodeIDList["sizeNotable"] = 1
+nodeIDList[100] = { index = 0, size = 1 }
+nodeIDList[200] = { index = 1, size = 1 }
+"#;
+
+const OLD_PASSIVES: &str = r#"return {
+    additions = {
+        [1] = { id = "karui_notable_add_strength", dn = "Strength", sd = { "+20 to Strength" } },
+    },
+}
+"#;
+
+const NEW_PASSIVES: &str = r#"return {
+    additions = {
+        [1] = { id = "karui_notable_add_strength", dn = "Strength", sd = { "+20 to Strength" } },
+        [2] = { id = "karui_notable_add_life", dn = "Life", sd = { "+10 to maximum Life" } },
+    },
+}
+"#;
+
+struct Fixture {
+    _temp_dir: TempDir,
+    manifest_path: PathBuf,
+    data_dir: PathBuf,
+    artifact_path: PathBuf,
+}
+
+/// Installed data at "old-sha": both Lua files with the old passives
+fn create_fixture(server: &MockServer) -> Fixture {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    std::fs::write(data_dir.join("NodeIndexMapping.lua"), NODE_INDEX_MAPPING).unwrap();
+    std::fs::write(data_dir.join("LegionPassives.lua"), OLD_PASSIVES).unwrap();
+
+    let data_file = |name: &str, github_sha: &str| DataFile {
+        name: name.to_string(),
+        url: format!("{}/data/{}", server.uri(), name),
+        sha256: String::new(),
+        github_sha: github_sha.to_string(),
+        size: 0,
+        required: true,
+        description: String::new(),
+        parts: Vec::new(),
+    };
+
+    let manifest_path = temp_dir.path().join("manifest.json");
+    DataManifest {
+        data_version: "old-sha".to_string(),
+        poe_league: "Test".to_string(),
+        last_updated: "2025-01-01T00:00:00Z".to_string(),
+        source: DataSource {
+            source_type: "github".to_string(),
+            repo: REPO.to_string(),
+            branch: "master".to_string(),
+            path: DATA_PATH.to_string(),
+            url: format!("https://github.com/{}", REPO),
+            mirrors: Vec::new(),
+            raw_url: None,
+            api_url: None,
+        },
+        files: vec![
+            data_file(
+                "NodeIndexMapping.lua",
+                &calculate_git_blob_sha_bytes(NODE_INDEX_MAPPING.as_bytes()),
+            ),
+            data_file(
+                "LegionPassives.lua",
+                &calculate_git_blob_sha_bytes(OLD_PASSIVES.as_bytes()),
+            ),
+        ],
+        ignored_versions: Vec::new(),
+        parsed_artifact: None,
+    }
+    .save_to_file(&manifest_path)
+    .unwrap();
+
+    Fixture {
+        artifact_path: temp_dir.path().join("lut_data.json"),
+        _temp_dir: temp_dir,
+        manifest_path,
+        data_dir,
+    }
+}
+
+/// Upstream at "new-sha" where only LegionPassives.lua changed (to `passives`)
+async fn mount_upstream(server: &MockServer, passives: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/repos/{}/commits", REPO)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+            "sha": "new-sha",
+            "commit": {
+                "message": "Update legion passives",
+                "author": {
+                    "name": "PoB",
+                    "email": "pob@example.com",
+                    "date": "2025-02-01T00:00:00Z"
+                }
+            }
+        }])))
+        .mount(server)
+        .await;
+
+    let entry = |name: &str, sha: &str| {
+        serde_json::json!({
+            "name": name,
+            "path": format!("{}/{}", DATA_PATH, name),
+            "sha": sha,
+            "size": 100,
+            "url": "https://api.github.com/unused",
+            "download_url": null,
+            "type": "file"
+        })
+    };
+
+    Mock::given(method("GET"))
+        .and(path(format!("/repos/{}/contents/{}", REPO, DATA_PATH)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            entry(
+                "NodeIndexMapping.lua",
+                &calculate_git_blob_sha_bytes(NODE_INDEX_MAPPING.as_bytes()),
+            ),
+            entry(
+                "LegionPassives.lua",
+                &calculate_git_blob_sha_bytes(passives.as_bytes()),
+            ),
+        ])))
+        .mount(server)
+        .await;
+
+    for (name, body) in [
+        ("NodeIndexMapping.lua", NODE_INDEX_MAPPING),
+        ("LegionPassives.lua", passives),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(format!("/data/{}", name)))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(server)
+            .await;
+    }
+}
+
+fn checker(fixture: &Fixture, server: &MockServer) -> UpdateChecker {
+    UpdateChecker::new(fixture.manifest_path.clone())
+        .with_github_client(GitHubClient::new().with_api_url(server.uri()))
+}
+
+fn stage_events(events: &[UpdateEvent]) -> Vec<UpdateEvent> {
+    events
+        .iter()
+        .filter(|e| {
+            !matches!(
+                e,
+                UpdateEvent::FileProgress(_) | UpdateEvent::ParseProgress(_)
+            )
+        })
+        .cloned()
+        .collect()
+}
+
+/// Record `sha` for NodeIndexMapping.lua (unchanged upstream) in the manifest
+fn edit_node_index_sha(manifest_path: &Path, sha: &str) {
+    UpdateChecker::new(manifest_path.to_path_buf())
+        .mark_files_updated(&[ChangedFile {
+            name: "NodeIndexMapping.lua".to_string(),
+            old_sha: String::new(),
+            new_sha: sha.to_string(),
+            size: 0,
+        }])
+        .unwrap();
+}
+
+fn leftover_staging_dirs(parent: &Path) -> usize {
+    std::fs::read_dir(parent)
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .contains("staging")
+        })
+        .count()
+}
+
+#[tokio::test]
+async fn test_perform_update_runs_full_pipeline() {
+    let server = MockServer::start().await;
+    mount_upstream(&server, NEW_PASSIVES).await;
+    let fixture = create_fixture(&server);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+
+    let outcome = checker(&fixture, &server)
+        .perform_update(&fixture.data_dir, &fixture.artifact_path, move |event| {
+            sink.lock().unwrap().push(event)
+        })
+        .await
+        .unwrap();
+
+    assert!(outcome.updated);
+    assert_eq!(outcome.version, "new-sha");
+    assert_eq!(outcome.changed_files, vec!["LegionPassives.lua"]);
+    assert_eq!(outcome.parse_report.unwrap().modifier_count, 2);
+
+    // Only the changed file was downloaded
+    let downloaded: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.url.path().to_string())
+        .filter(|p| p.starts_with("/data/"))
+        .collect();
+    assert_eq!(downloaded, vec!["/data/LegionPassives.lua"]);
+
+    assert_eq!(
+        std::fs::read_to_string(fixture.data_dir.join("LegionPassives.lua")).unwrap(),
+        NEW_PASSIVES
+    );
+    let lut_data = PobDataParser::load_from_json(&fixture.artifact_path).unwrap();
+    assert_eq!(lut_data.modifiers.len(), 2);
+
+    let manifest = DataManifest::load_from_file(&fixture.manifest_path).unwrap();
+    assert_eq!(manifest.data_version, "new-sha");
+    let artifact = manifest.parsed_artifact.as_ref().unwrap();
+    assert_eq!(artifact.generated_from_version, "new-sha");
+    assert_eq!(artifact.resolve(&fixture.data_dir), fixture.artifact_path);
+    assert!(!manifest.needs_reparse(&fixture.data_dir).unwrap());
+    assert_eq!(
+        manifest.find_file("LegionPassives.lua").unwrap().github_sha,
+        calculate_git_blob_sha_bytes(NEW_PASSIVES.as_bytes())
+    );
+
+    let stages = [
+        UpdateStage::Checking,
+        UpdateStage::Downloading,
+        UpdateStage::Verifying,
+        UpdateStage::Parsing,
+        UpdateStage::Swapping,
+    ];
+    let expected: Vec<UpdateEvent> = stages
+        .iter()
+        .flat_map(|stage| {
+            [
+                UpdateEvent::StageStarted(*stage),
+                UpdateEvent::StageFinished(*stage),
+            ]
+        })
+        .collect();
+    let events = events.lock().unwrap().clone();
+    assert_eq!(stage_events(&events), expected);
+
+    // The parse runs off the runtime, but its progress still arrives in order
+    let position = |wanted: &UpdateEvent| events.iter().position(|e| e == wanted).unwrap();
+    let parsing = position(&UpdateEvent::StageStarted(UpdateStage::Parsing))
+        ..position(&UpdateEvent::StageFinished(UpdateStage::Parsing));
+    let parse_events: Vec<usize> = (0..events.len())
+        .filter(|&i| matches!(events[i], UpdateEvent::ParseProgress(_)))
+        .collect();
+    assert!(!parse_events.is_empty());
+    assert!(parse_events.iter().all(|i| parsing.contains(i)));
+
+    assert_eq!(leftover_staging_dirs(fixture.data_dir.parent().unwrap()), 0);
+
+    // A second run finds nothing to do
+    let outcome = checker(&fixture, &server)
+        .perform_update(&fixture.data_dir, &fixture.artifact_path, |_| {})
+        .await
+        .unwrap();
+    assert!(!outcome.updated);
+}
+
+#[tokio::test]
+async fn test_perform_update_with_layout_installs_new_version_alongside() {
+    let server = MockServer::start().await;
+    mount_upstream(&server, NEW_PASSIVES).await;
+    let fixture = create_fixture(&server);
+
+    // The fixture's data as version "old-sha" of a layout
+    let layout = DataLayout::new(fixture.data_dir.parent().unwrap().join("pob-data"));
+    layout.create("old-sha").unwrap();
+    std::fs::rename(&fixture.manifest_path, layout.manifest_path("old-sha")).unwrap();
+    for name in ["NodeIndexMapping.lua", "LegionPassives.lua"] {
+        std::fs::rename(fixture.data_dir.join(name), layout.raw_dir("old-sha").join(name))
+            .unwrap();
+    }
+    let old_artifact = layout.parsed_dir("old-sha").join("lut_data.json");
+
+    // Edits made to the current manifest during the update carry over
+    let old_manifest = layout.manifest_path("old-sha");
+    let outcome = UpdateChecker::new(old_manifest.clone())
+        .with_github_client(GitHubClient::new().with_api_url(server.uri()))
+        .with_layout(layout.clone())
+        .perform_update(&layout.raw_dir("old-sha"), &old_artifact, |event| {
+            if event == UpdateEvent::StageStarted(UpdateStage::Parsing) {
+                edit_node_index_sha(&old_manifest, "edited-sha");
+            }
+        })
+        .await
+        .unwrap();
+
+    assert!(outcome.updated);
+    assert_eq!(outcome.version, "new-sha");
+    assert_eq!(layout.versions(), vec!["new-sha", "old-sha"]);
+
+    // The previous version is still complete
+    assert_eq!(
+        std::fs::read_to_string(layout.raw_dir("old-sha").join("LegionPassives.lua")).unwrap(),
+        OLD_PASSIVES
+    );
+    let old = DataManifest::load_from_file(&layout.manifest_path("old-sha")).unwrap();
+    assert_eq!(old.data_version, "old-sha");
+    assert!(!old_artifact.exists());
+
+    let new_raw = layout.raw_dir("new-sha");
+    assert_eq!(
+        std::fs::read_to_string(new_raw.join("LegionPassives.lua")).unwrap(),
+        NEW_PASSIVES
+    );
+    assert!(new_raw.join("NodeIndexMapping.lua").is_file());
+
+    let new = DataManifest::load_from_file(&layout.manifest_path("new-sha")).unwrap();
+    assert_eq!(new.data_version, "new-sha");
+    let artifact = new.parsed_artifact.as_ref().unwrap();
+    assert_eq!(
+        artifact.resolve(&new_raw),
+        layout.parsed_dir("new-sha").join("lut_data.json")
+    );
+    assert!(!new.needs_reparse(&new_raw).unwrap());
+    assert_eq!(new.find_file("NodeIndexMapping.lua").unwrap().github_sha, "edited-sha");
+    let lut_data = PobDataParser::load_from_json(&artifact.resolve(&new_raw)).unwrap();
+    assert_eq!(lut_data.modifiers.len(), 2);
+
+    assert_eq!(leftover_staging_dirs(&layout.version_dir("new-sha")), 0);
+}
+
+#[tokio::test]
+async fn test_perform_update_keeps_concurrent_manifest_edits() {
+    let server = MockServer::start().await;
+    mount_upstream(&server, NEW_PASSIVES).await;
+    let fixture = create_fixture(&server);
+
+    let manifest_path = fixture.manifest_path.clone();
+    let outcome = checker(&fixture, &server)
+        .perform_update(&fixture.data_dir, &fixture.artifact_path, move |event| {
+            if event == UpdateEvent::StageStarted(UpdateStage::Parsing) {
+                edit_node_index_sha(&manifest_path, "edited-sha");
+            }
+        })
+        .await
+        .unwrap();
+    assert!(outcome.updated);
+
+    let manifest = DataManifest::load_from_file(&fixture.manifest_path).unwrap();
+    assert_eq!(manifest.data_version, "new-sha");
+    assert_eq!(
+        manifest.find_file("NodeIndexMapping.lua").unwrap().github_sha,
+        "edited-sha"
+    );
+    assert_eq!(
+        manifest.find_file("LegionPassives.lua").unwrap().github_sha,
+        calculate_git_blob_sha_bytes(NEW_PASSIVES.as_bytes())
+    );
+    assert!(manifest.parsed_artifact.is_some());
+}
+
+#[tokio::test]
+async fn test_perform_update_checks_free_space_first() {
+    let server = MockServer::start().await;
+    mount_upstream(&server, NEW_PASSIVES).await;
+    let fixture = create_fixture(&server);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+
+    let error = checker(&fixture, &server)
+        .with_space_probe(|_| Ok(0))
+        .perform_update(&fixture.data_dir, &fixture.artifact_path, move |event| {
+            sink.lock().unwrap().push(event)
+        })
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        DownloadError::InsufficientSpace { needed, available: 0 } if needed > 0
+    ));
+    assert!(matches!(
+        events.lock().unwrap().last(),
+        Some(UpdateEvent::Failed { stage: UpdateStage::Checking, .. })
+    ));
+
+    // Nothing was downloaded, staged or installed
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests.iter().all(|request| !request.url.path().starts_with("/data/")));
+    assert_eq!(leftover_staging_dirs(fixture.data_dir.parent().unwrap()), 0);
+    let manifest = DataManifest::load_from_file(&fixture.manifest_path).unwrap();
+    assert_eq!(manifest.data_version, "old-sha");
+
+    let outcome = checker(&fixture, &server)
+        .with_space_probe(|_| Ok(u64::MAX))
+        .perform_update(&fixture.data_dir, &fixture.artifact_path, |_| {})
+        .await
+        .unwrap();
+    assert!(outcome.updated);
+}
+
+#[tokio::test]
+async fn test_perform_update_failure_keeps_previous_data() {
+    let server = MockServer::start().await;
+    mount_upstream(
+        &server,
+        "return { additions = { this is not valid lua at all, just padding to pass the size check",
+    )
+    .await;
+    let fixture = create_fixture(&server);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+
+    let error = checker(&fixture, &server)
+        .perform_update(&fixture.data_dir, &fixture.artifact_path, move |event| {
+            sink.lock().unwrap().push(event)
+        })
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("LegionPassives.lua"));
+    assert!(matches!(
+        events.lock().unwrap().last(),
+        Some(UpdateEvent::Failed { error: message, .. }) if *message == error.to_string()
+    ));
+
+    assert_eq!(
+        std::fs::read_to_string(fixture.data_dir.join("LegionPassives.lua")).unwrap(),
+        OLD_PASSIVES
+    );
+    assert!(!fixture.artifact_path.exists());
+    assert_eq!(leftover_staging_dirs(fixture.data_dir.parent().unwrap()), 0);
+
+    let manifest = DataManifest::load_from_file(&fixture.manifest_path).unwrap();
+    assert_eq!(manifest.data_version, "old-sha");
+}
+
+#[tokio::test]
+async fn test_cancel_during_download_keeps_previous_data() {
+    let server = MockServer::start().await;
+    // Hold the download open long enough to cancel it mid-flight
+    Mock::given(method("GET"))
+        .and(path("/data/LegionPassives.lua"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(NEW_PASSIVES)
+                .set_delay(Duration::from_secs(30)),
+        )
+        .mount(&server)
+        .await;
+    mount_upstream(&server, NEW_PASSIVES).await;
+    let fixture = create_fixture(&server);
+
+    let cancel = CancellationToken::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let token = cancel.clone();
+
+    let error = checker(&fixture, &server)
+        .with_cancellation(cancel)
+        .perform_update(&fixture.data_dir, &fixture.artifact_path, move |event| {
+            if let UpdateEvent::FileProgress(DownloadEvent::FileStarted { .. }) = event {
+                token.cancel();
+            }
+            sink.lock().unwrap().push(event)
+        })
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, DownloadError::Cancelled));
+    assert_eq!(
+        stage_events(&events.lock().unwrap()),
+        vec![
+            UpdateEvent::StageStarted(UpdateStage::Checking),
+            UpdateEvent::StageFinished(UpdateStage::Checking),
+            UpdateEvent::StageStarted(UpdateStage::Downloading),
+            UpdateEvent::Cancelled(UpdateStage::Downloading),
+        ]
+    );
+
+    // The live data, artifact and manifest are untouched and staging is gone
+    assert_eq!(
+        std::fs::read_to_string(fixture.data_dir.join("LegionPassives.lua")).unwrap(),
+        OLD_PASSIVES
+    );
+    assert_eq!(
+        std::fs::read_to_string(fixture.data_dir.join("NodeIndexMapping.lua")).unwrap(),
+        NODE_INDEX_MAPPING
+    );
+    assert!(!fixture.artifact_path.exists());
+    assert_eq!(leftover_staging_dirs(fixture.data_dir.parent().unwrap()), 0);
+
+    let manifest = DataManifest::load_from_file(&fixture.manifest_path).unwrap();
+    assert_eq!(manifest.data_version, "old-sha");
+}
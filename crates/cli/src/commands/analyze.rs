@@ -0,0 +1,103 @@
+//! `analyze`: score one jewel at every socket
+
+use std::path::Path;
+
+use anyhow::bail;
+use poe_item_analyzer_core::analyzers::{
+    Analyzer, ResultExportV1, TimelessJewelAnalysisResult, TimelessJewelAnalyzer,
+};
+use poe_item_analyzer_core::items::{JewelType, SocketSortKey, TimelessJewel};
+
+use super::{format_score, matched_mods};
+use crate::context::{load_weights, resolve_conqueror, Context};
+
+pub fn run(
+    context: &Context,
+    jewel_type: JewelType,
+    seed: u32,
+    conqueror: Option<String>,
+    weights: &Path,
+) -> anyhow::Result<()> {
+    let (result, lut_version) = analyze(context, jewel_type, seed, conqueror, weights)?;
+
+    if context.json {
+        return context.print_json(&ResultExportV1::from_result(&result, Some(lut_version)));
+    }
+
+    println!(
+        "{} {} ({}): best score {}",
+        jewel_type.as_str(),
+        seed,
+        result.jewel.conqueror,
+        format_score(result.best_score)
+    );
+
+    for socket in result.metrics.sorted_by(SocketSortKey::Score) {
+        println!(
+            "  {:>8}  {}: {}",
+            format_score(socket.score),
+            socket.socket_name,
+            matched_mods(socket)
+        );
+    }
+
+    // A jewel sits in one socket, so these are alternatives
+    let summary = result.metrics.summary();
+    if !summary.mods.is_empty() {
+        println!("Best socket per mod:");
+        for summary in &summary.mods {
+            println!(
+                "  {:>8}  {}× {} ({})",
+                format_score(summary.contribution),
+                summary.count,
+                summary.mod_text,
+                summary.socket_name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Analyze the jewel, printing warnings and timings on stderr
+///
+/// Returns the result and the version of the LUT it was computed with.
+pub fn analyze(
+    context: &Context,
+    jewel_type: JewelType,
+    seed: u32,
+    conqueror: Option<String>,
+    weights: &Path,
+) -> anyhow::Result<(TimelessJewelAnalysisResult, String)> {
+    if !jewel_type.is_valid_seed(seed) {
+        let range = jewel_type.seed_range();
+        bail!(
+            "{} is not a valid {} seed ({}-{})",
+            seed,
+            jewel_type.as_str(),
+            range.start(),
+            range.end()
+        );
+    }
+    let conqueror = resolve_conqueror(jewel_type, conqueror)?;
+    let config = load_weights(weights)?.with_timings(context.timings);
+    let data = context.load_lut()?;
+    let lut_version = data.version.clone();
+
+    let jewel = TimelessJewel::new(
+        format!("{}:{}:{}", jewel_type.as_str(), seed, conqueror),
+        jewel_type,
+        seed,
+        conqueror,
+        serde_json::Value::Null,
+    );
+    let result = TimelessJewelAnalyzer::new()
+        .with_lookup(data)
+        .analyze(&jewel, &config)?;
+    for warning in result.warnings.iter() {
+        eprintln!("warning: {}", warning);
+    }
+    context.print_timings(result.timings.as_ref());
+
+    Ok((result, lut_version))
+}
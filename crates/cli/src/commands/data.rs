@@ -0,0 +1,185 @@
+//! `data`: download, verify and parse the latest PoB data, and share the
+//! parsed LUT as a signed cache file
+
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+use poe_item_analyzer_api::parser::PobDataParser;
+use poe_item_analyzer_api::{
+    DataManifest, DownloadEvent, GitHubClient, ParseEvent, UpdateChecker, UpdateEvent, UpdateStage,
+};
+use poe_item_analyzer_core::DataError;
+use serde_json::json;
+
+use crate::context::Context;
+
+/// LUT written to the parsed data directory, as the desktop app's updates do
+const ARTIFACT_FILE: &str = "lut_data.json";
+
+/// Imported cache in the parsed data directory, named like the desktop app's
+const CACHE_FILE: &str = "lut_data.bin";
+
+/// Update the most recently updated data version, installing a new upstream
+/// version next to it
+pub fn update(context: &Context, github_token: Option<String>) -> anyhow::Result<()> {
+    let layout = context.layout();
+    std::fs::create_dir_all(layout.root())
+        .with_context(|| format!("Could not create {}", layout.root().display()))?;
+    if let Some(version) = layout.migrate_flat()? {
+        eprintln!(
+            "Moved the existing data to {}",
+            layout.version_dir(&version).display()
+        );
+    }
+
+    let version = layout
+        .latest_version()
+        .unwrap_or_else(|| DataManifest::default_pob().data_version);
+    let mut checker =
+        UpdateChecker::new(layout.manifest_path(&version)).with_layout(layout.clone());
+    if let Some(token) = github_token {
+        checker = checker.with_github_client(GitHubClient::new().with_token(token));
+    }
+
+    let runtime = tokio::runtime::Runtime::new().context("Could not start the async runtime")?;
+    let outcome = runtime.block_on(checker.perform_update(
+        &layout.raw_dir(&version),
+        &layout.parsed_dir(&version).join(ARTIFACT_FILE),
+        print_progress,
+    ))?;
+
+    if context.json {
+        return context.print_json(&json!({
+            "updated": outcome.updated,
+            "version": outcome.version,
+            "changed_files": outcome.changed_files,
+            "bytes_downloaded": outcome.bytes_downloaded,
+        }));
+    }
+
+    if outcome.updated {
+        println!(
+            "Updated {} to {} ({} files changed, {} bytes downloaded)",
+            layout.version_dir(&outcome.version).display(),
+            outcome.version,
+            outcome.changed_files.len(),
+            outcome.bytes_downloaded
+        );
+    } else {
+        println!("Already up to date ({})", outcome.version);
+    }
+
+    Ok(())
+}
+
+/// Save the LUT as a signed binary cache to `out`, and check the result
+pub fn export_cache(context: &Context, out: &Path) -> anyhow::Result<()> {
+    let data = context.load_lut()?;
+    PobDataParser::save_to_binary(&data, out)
+        .with_context(|| format!("Could not export the cache to {}", out.display()))?;
+    let header = PobDataParser::verify_binary(out)
+        .with_context(|| format!("The exported cache {} is unreadable", out.display()))?;
+
+    if context.json {
+        return context.print_json(&json!({
+            "cache": out,
+            "sha256": header.payload_sha256,
+            "generator_version": header.generator_version,
+        }));
+    }
+    println!(
+        "Saved cache to {} (sha256 {})",
+        out.display(),
+        header.payload_sha256
+    );
+    Ok(())
+}
+
+/// Verify the cache `file` and record it as the parsed LUT of the most
+/// recently updated data version
+pub fn import_cache(context: &Context, file: &Path) -> anyhow::Result<()> {
+    // Loading checks the header and that the payload parses
+    PobDataParser::load_from_binary(file)
+        .map_err(DataError::from)
+        .with_context(|| format!("Refusing to import {}", file.display()))?;
+
+    let layout = context.layout();
+    let Some(version) = layout.latest_version().filter(|_| !layout.is_flat()) else {
+        bail!(
+            "No installed data in {}; run `data update` first",
+            context.data_dir.display()
+        );
+    };
+    let parsed_dir = layout.parsed_dir(&version);
+    let target = parsed_dir.join(CACHE_FILE);
+    std::fs::create_dir_all(&parsed_dir)
+        .with_context(|| format!("Could not create {}", parsed_dir.display()))?;
+    std::fs::copy(file, &target)
+        .with_context(|| format!("Could not copy {} to {}", file.display(), target.display()))?;
+
+    let manifest_path = layout.manifest_path(&version);
+    let _lock = DataManifest::lock(&manifest_path)
+        .with_context(|| format!("Could not lock {}", manifest_path.display()))?;
+    let mut manifest = DataManifest::load_from_file(&manifest_path)?;
+    manifest.record_parsed_artifact(&layout.raw_dir(&version), &target, "bincode")?;
+    manifest
+        .save_atomic(&manifest_path)
+        .with_context(|| format!("Could not save {}", manifest_path.display()))?;
+
+    if context.json {
+        return context.print_json(&json!({ "version": version, "cache": target }));
+    }
+    println!("Imported {} for data version {}", file.display(), version);
+    Ok(())
+}
+
+/// Report update progress on stderr
+fn print_progress(event: UpdateEvent) {
+    match event {
+        UpdateEvent::StageStarted(stage) => eprintln!("{}...", stage_label(stage)),
+        UpdateEvent::FileProgress(DownloadEvent::FileStarted {
+            current,
+            total,
+            file_name,
+        }) => {
+            eprintln!("  [{}/{}] {}", current, total, file_name);
+        }
+        UpdateEvent::FileProgress(DownloadEvent::Retrying {
+            file_name,
+            attempt,
+            max_attempts,
+            ..
+        }) => {
+            eprintln!(
+                "  Retrying {} (attempt {}/{})",
+                file_name, attempt, max_attempts
+            );
+        }
+        UpdateEvent::FileProgress(DownloadEvent::Warning(message)) => {
+            eprintln!("warning: {}", message);
+        }
+        UpdateEvent::ParseProgress(ParseEvent::FileStarted {
+            current,
+            total,
+            file_name,
+        }) => {
+            eprintln!("  [{}/{}] {}", current, total, file_name);
+        }
+        // The returned error is reported by the caller
+        UpdateEvent::FileProgress(_)
+        | UpdateEvent::ParseProgress(_)
+        | UpdateEvent::StageFinished(_)
+        | UpdateEvent::Failed { .. }
+        | UpdateEvent::Cancelled(_) => {}
+    }
+}
+
+fn stage_label(stage: UpdateStage) -> &'static str {
+    match stage {
+        UpdateStage::Checking => "Checking for updates",
+        UpdateStage::Downloading => "Downloading",
+        UpdateStage::Verifying => "Verifying",
+        UpdateStage::Parsing => "Parsing",
+        UpdateStage::Swapping => "Installing",
+    }
+}
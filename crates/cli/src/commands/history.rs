@@ -0,0 +1,108 @@
+//! `history`: list recorded runs and compare the latest with an earlier one
+
+use anyhow::bail;
+use poe_item_analyzer_api::{HistoryRun, HistoryStore, RunKind};
+use poe_item_analyzer_core::analyzers::ScoreChange;
+use serde_json::json;
+
+use super::format_score;
+use crate::context::Context;
+
+/// Score changes in the order the diff lists them
+const CHANGES: [(ScoreChange, &str); 5] = [
+    (ScoreChange::Improved, "Improved"),
+    (ScoreChange::Worsened, "Worsened"),
+    (ScoreChange::New, "New"),
+    (ScoreChange::Dropped, "Dropped"),
+    (ScoreChange::Unchanged, "Unchanged"),
+];
+
+/// Record `run` in the data directory's history, reporting its id on stderr
+pub fn record(context: &Context, run: HistoryRun) -> anyhow::Result<()> {
+    let run = HistoryStore::in_data_dir(&context.data_dir).record(run)?;
+    eprintln!("Recorded run {}", run.id);
+    Ok(())
+}
+
+pub fn list(context: &Context) -> anyhow::Result<()> {
+    let runs = HistoryStore::in_data_dir(&context.data_dir).list_history()?;
+    if context.json {
+        return context.print_json(&runs);
+    }
+
+    if runs.is_empty() {
+        println!("No runs recorded; pass --record to search or import");
+        return Ok(());
+    }
+    for run in &runs {
+        let kind = match run.kind {
+            RunKind::SeedSearch => "search",
+            RunKind::Batch => "batch",
+        };
+        let best = run
+            .results
+            .first()
+            .map_or("-".to_string(), |e| format_score(e.score));
+        println!(
+            "{:>4}  {}  {:<6}  {}  weights {}  best {}",
+            run.id,
+            run.timestamp.format("%Y-%m-%d %H:%M"),
+            kind,
+            run.label,
+            run.config_hash,
+            best
+        );
+    }
+
+    Ok(())
+}
+
+/// Compare run `previous` with its latest rerun
+pub fn diff(context: &Context, previous: &str) -> anyhow::Result<()> {
+    let store = HistoryStore::in_data_dir(&context.data_dir);
+    let Some(diff) = store.diff_against(previous)? else {
+        bail!(
+            "No run {} with a later run of the same kind and label in {}",
+            previous,
+            store.path().display()
+        );
+    };
+
+    if context.json {
+        let seeds: Vec<_> = diff
+            .seeds
+            .iter()
+            .map(|seed| {
+                json!({
+                    "jewel_id": seed.jewel_id,
+                    "seed": seed.seed,
+                    "score_a": seed.score_a,
+                    "score_b": seed.score_b,
+                    "score_delta": seed.score_delta(),
+                    "change": seed.change(),
+                })
+            })
+            .collect();
+        return context.print_json(&json!({ "previous": previous, "seeds": seeds }));
+    }
+
+    for (change, title) in CHANGES {
+        let seeds: Vec<_> = diff.with_change(change).collect();
+        if seeds.is_empty() {
+            continue;
+        }
+        println!("{} ({})", title, seeds.len());
+        for seed in seeds {
+            let score = |score: Option<f64>| score.map_or("-".to_string(), format_score);
+            println!(
+                "  {:<32}  {:>8} -> {:>8}  ({:+.2})",
+                seed.jewel_id,
+                score(seed.score_a),
+                score(seed.score_b),
+                seed.score_delta()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! `import`: read jewels from item text copied in game
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+use poe_item_analyzer_api::{ClipboardTextSource, HistoryRun, RunKind};
+use poe_item_analyzer_core::analyzers::{
+    Analyzer, ResultExportV1, RunEntry, TimelessJewelAnalyzer,
+};
+use poe_item_analyzer_core::items::TimelessJewel;
+
+use super::{format_score, history};
+use crate::context::{load_weights, Context};
+
+pub fn run(context: &Context, weights: Option<&Path>, record: bool) -> anyhow::Result<()> {
+    let mut text = String::new();
+    std::io::stdin()
+        .read_to_string(&mut text)
+        .context("Could not read stdin")?;
+
+    let parsed = ClipboardTextSource::new(text).parse();
+    for error in &parsed.errors {
+        eprintln!("warning: item {}: {}", error.index + 1, error.message);
+    }
+    if parsed.jewels.is_empty() {
+        bail!("No timeless jewels found ({})", parsed.summary());
+    }
+
+    let Some(weights) = weights else {
+        if context.json {
+            // Records keep the item text, so the output can be read back
+            let records: Vec<_> = parsed.jewels.iter().map(TimelessJewel::to_record).collect();
+            return context.print_json(&records);
+        }
+        for jewel in &parsed.jewels {
+            println!(
+                "{} {} ({})",
+                jewel.jewel_type.as_str(),
+                jewel.seed,
+                jewel.conqueror
+            );
+        }
+        return Ok(());
+    };
+
+    let config = load_weights(weights)?;
+    let data = context.load_lut()?;
+    let lut_version = data.version.clone();
+    let ranked = TimelessJewelAnalyzer::new()
+        .with_lookup(data)
+        .analyze_batch(&parsed.jewels, &config)?;
+    for ranked in &ranked {
+        let jewel = &ranked.result.jewel;
+        for warning in ranked.result.warnings.iter() {
+            eprintln!(
+                "warning: {} {}: {}",
+                jewel.jewel_type.as_str(),
+                jewel.seed,
+                warning
+            );
+        }
+    }
+    if record {
+        let label = format!("{} imported jewels", ranked.len());
+        let entries = RunEntry::from_results(ranked.iter().map(|ranked| &ranked.result));
+        let run = HistoryRun::new(
+            RunKind::Batch,
+            label,
+            &config,
+            Some(lut_version.clone()),
+            entries,
+        );
+        history::record(context, run)?;
+    }
+
+    if context.json {
+        return context.print_json(&ResultExportV1::from_ranked(&ranked, Some(lut_version)));
+    }
+    for ranked in &ranked {
+        let jewel = &ranked.result.jewel;
+        println!(
+            "{:>4}. {} {} ({})  {}",
+            ranked.rank,
+            jewel.jewel_type.as_str(),
+            jewel.seed,
+            jewel.conqueror,
+            format_score(ranked.result.best_score)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+//! Subcommand implementations
+
+pub mod analyze;
+pub mod data;
+pub mod history;
+pub mod import;
+pub mod report;
+pub mod search;
+
+use poe_item_analyzer_core::items::SocketResult;
+
+/// Matched mods of a socket (e.g., "2× Double Damage, 1× Onslaught")
+fn matched_mods(socket: &SocketResult) -> String {
+    if socket.matched_mods.is_empty() {
+        return "-".to_string();
+    }
+
+    socket
+        .matched_mods
+        .iter()
+        .map(|m| format!("{}× {}", m.count, m.mod_text))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Score with two decimals; empty sums are -0.0, which would print as "-0.00"
+fn format_score(score: f64) -> String {
+    format!("{:.2}", score + 0.0)
+}
@@ -0,0 +1,70 @@
+//! `report`: save an analysis or seed search as a standalone HTML file
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use poe_item_analyzer_core::analyzers::{
+    render_analysis_report, render_search_report, SeedSearcher,
+};
+use poe_item_analyzer_core::items::JewelType;
+use serde_json::json;
+
+use super::{analyze, search};
+use crate::context::{load_weights, resolve_conqueror, Context};
+
+/// What to report on
+pub struct ReportOptions<'a> {
+    /// Seed to analyze; None searches for the best seeds
+    pub seed: Option<u32>,
+
+    /// Number of seeds to keep when searching
+    pub top: usize,
+
+    /// Socket id to search at, or "all"
+    pub socket: &'a str,
+
+    /// Socket layouts file
+    pub sockets: Option<&'a Path>,
+
+    /// File to write
+    pub out: &'a Path,
+}
+
+pub fn run(
+    context: &Context,
+    jewel_type: JewelType,
+    conqueror: Option<String>,
+    weights: &Path,
+    options: ReportOptions,
+) -> anyhow::Result<()> {
+    let html = match options.seed {
+        Some(seed) => {
+            let (result, lut_version) =
+                analyze::analyze(context, jewel_type, seed, conqueror, weights)?;
+            render_analysis_report(&result, Some(&lut_version))
+        }
+        None => {
+            let conqueror = resolve_conqueror(jewel_type, conqueror)?;
+            let config = load_weights(weights)?.with_timings(context.timings);
+            let socket = search::find_socket(options.socket, options.sockets)?;
+            let data = context.load_lut()?;
+            let lut_version = data.version.clone();
+
+            let mut searcher = SeedSearcher::new(data).with_top_n(options.top);
+            if let Some(socket) = socket {
+                searcher = searcher.with_socket(socket);
+            }
+            let result = search::search(context, &searcher, jewel_type, &conqueror, &config)?;
+            render_search_report(&result, Some(&lut_version))
+        }
+    };
+
+    std::fs::write(options.out, html)
+        .with_context(|| format!("Could not write {}", options.out.display()))?;
+
+    if context.json {
+        return context.print_json(&json!({ "report": options.out }));
+    }
+    println!("Saved report to {}", options.out.display());
+    Ok(())
+}
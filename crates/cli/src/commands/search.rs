@@ -0,0 +1,196 @@
+//! `search`: find the best seeds of a jewel type
+
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+use poe_item_analyzer_api::poe_api::build_trade_site_url;
+use poe_item_analyzer_api::{HistoryRun, RunKind};
+use poe_item_analyzer_core::analyzers::{
+    RunEntry, SeedSearchResult, SeedSearcher, TimelessJewelConfig,
+};
+use poe_item_analyzer_core::data::{JewelSocket, PassiveTree};
+use poe_item_analyzer_core::items::JewelType;
+
+use super::{format_score, history, matched_mods};
+use crate::context::{load_weights, resolve_conqueror, Context};
+
+/// Where and how many seeds to search for
+pub struct SearchOptions<'a> {
+    /// Number of seeds to keep
+    pub top: usize,
+
+    /// Socket id, or "all"
+    pub socket: &'a str,
+
+    /// Socket layouts file
+    pub sockets: Option<&'a Path>,
+
+    /// League to link trade searches in
+    pub trade_league: Option<&'a str>,
+
+    /// Page of the seeds found to print (from 1; None prints them all)
+    pub page: Option<usize>,
+
+    /// Seeds per page
+    pub page_size: usize,
+
+    /// Record the seeds found in the history
+    pub record: bool,
+}
+
+pub fn run(
+    context: &Context,
+    jewel_type: JewelType,
+    conqueror: Option<String>,
+    weights: &Path,
+    options: SearchOptions,
+) -> anyhow::Result<()> {
+    let conqueror = resolve_conqueror(jewel_type, conqueror)?;
+    let config = load_weights(weights)?.with_timings(context.timings);
+    let socket = find_socket(options.socket, options.sockets)?;
+    let data = context.load_lut()?;
+    let lut_version = data.version.clone();
+
+    let mut searcher = SeedSearcher::new(data).with_top_n(options.top);
+    if let Some(socket) = socket {
+        searcher = searcher.with_socket(socket);
+    }
+    let result = search(context, &searcher, jewel_type, &conqueror, &config)?;
+    if options.record {
+        let label = format!("{} ({})", jewel_type.as_str(), conqueror);
+        let entries = RunEntry::from_search(&result);
+        let run = HistoryRun::new(
+            RunKind::SeedSearch,
+            label,
+            &config,
+            Some(lut_version),
+            entries,
+        );
+        history::record(context, run)?;
+    }
+
+    // Ranks of the seeds to print; the results are only sorted once
+    let first_rank = options
+        .page
+        .map_or(1, |page| (page - 1) * options.page_size + 1);
+    let shown = match options.page {
+        Some(page) => result.results.page(page - 1, options.page_size),
+        None => &result.results[..],
+    };
+
+    let seeds: Vec<u32> = shown.iter().map(|seed| seed.seed).collect();
+    let trade_urls = match options.trade_league {
+        Some(league) if !seeds.is_empty() => {
+            build_trade_site_url(league, jewel_type, &seeds, Some(conqueror.as_str()))?
+        }
+        _ => Vec::new(),
+    };
+
+    if context.json {
+        let mut output = serde_json::to_value(&result)?;
+        if let Some(page) = options.page {
+            output["results"] = serde_json::to_value(shown)?;
+            output["page"] = serde_json::json!(page);
+            output["page_count"] = serde_json::json!(result.results.page_count(options.page_size));
+            output["total_count"] = serde_json::json!(result.results.total_count());
+        }
+        if options.trade_league.is_some() {
+            output["trade_urls"] = serde_json::json!(trade_urls);
+        }
+        return context.print_json(&output);
+    }
+
+    println!(
+        "Best {} seeds for {} ({} scanned)",
+        jewel_type.as_str(),
+        result.conqueror,
+        result.scanned
+    );
+    if let Some(page) = options.page {
+        println!(
+            "Page {} of {} ({} seeds)",
+            page,
+            result.results.page_count(options.page_size),
+            result.results.total_count()
+        );
+    }
+    for (index, seed) in shown.iter().enumerate() {
+        println!(
+            "{:>4}. {:>6}  {:>8}  {}",
+            first_rank + index,
+            seed.seed,
+            format_score(seed.score),
+            matched_mods(&seed.socket)
+        );
+    }
+
+    if !trade_urls.is_empty() {
+        println!();
+        println!("Trade searches:");
+        for url in &trade_urls {
+            println!("  {}", url);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `searcher`, showing progress and timings on stderr
+pub fn search(
+    context: &Context,
+    searcher: &SeedSearcher,
+    jewel_type: JewelType,
+    conqueror: &str,
+    config: &TimelessJewelConfig,
+) -> anyhow::Result<SeedSearchResult> {
+    // Only redraw a progress line when someone is watching
+    let show_progress = std::io::stderr().is_terminal();
+    let result = searcher.search(jewel_type, conqueror, config, |progress| {
+        if show_progress {
+            eprint!("\rScanned {}/{} seeds", progress.scanned, progress.total);
+            let _ = std::io::stderr().flush();
+        }
+    })?;
+    if show_progress {
+        eprintln!();
+    }
+    context.print_timings(result.timings.as_ref());
+
+    Ok(result)
+}
+
+/// Socket named `id` in the `sockets` file; None for every node
+///
+/// The file is a list of sockets, or the passive tree JSON to compute them
+/// from.
+pub fn find_socket(id: &str, sockets: Option<&Path>) -> anyhow::Result<Option<JewelSocket>> {
+    if id == "all" {
+        return Ok(None);
+    }
+    let Some(path) = sockets else {
+        bail!(
+            "Socket layouts aren't bundled; pass --sockets FILE to use --socket {}",
+            id
+        );
+    };
+
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    let sockets: Vec<JewelSocket> = match serde_json::from_str(&json) {
+        Ok(sockets) => sockets,
+        Err(_) => PassiveTree::from_json(&json)
+            .with_context(|| {
+                format!(
+                    "{} is not a list of sockets or a passive tree",
+                    path.display()
+                )
+            })?
+            .sockets(),
+    };
+
+    match sockets.into_iter().find(|socket| socket.id == id) {
+        Some(socket) => Ok(Some(socket)),
+        None => bail!("No socket \"{}\" in {}", id, path.display()),
+    }
+}
@@ -0,0 +1,257 @@
+//! Options shared by the subcommands
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Context as _};
+use poe_item_analyzer_api::layout::MANIFEST_FILE;
+use poe_item_analyzer_api::parser::{LutData, PobDataParser};
+use poe_item_analyzer_api::{DataLayout, DataManifest};
+use poe_item_analyzer_core::analyzers::{AnalysisTimings, TimelessJewelConfig};
+use poe_item_analyzer_core::items::JewelType;
+use poe_item_analyzer_core::DataError;
+use serde::{Deserialize, Serialize};
+
+/// Global options
+pub struct Context {
+    /// PoB data directory, holding a directory per data version
+    pub data_dir: PathBuf,
+    /// Parsed LUT to use instead of the data directory
+    lut: Option<PathBuf>,
+    /// Whether to print JSON
+    pub json: bool,
+    /// Whether to measure and print where analyses spend their time
+    pub timings: bool,
+}
+
+impl Context {
+    pub fn new(data_dir: Option<PathBuf>, lut: Option<PathBuf>, json: bool, timings: bool) -> Self {
+        Self {
+            data_dir: data_dir.unwrap_or_else(default_data_dir),
+            lut,
+            json,
+            timings,
+        }
+    }
+
+    /// Per-version layout of the data directory
+    pub fn layout(&self) -> DataLayout {
+        DataLayout::new(&self.data_dir)
+    }
+
+    /// Data files to load and their manifest
+    ///
+    /// The most recently updated version's, or the data directory's own
+    /// files if they haven't been moved into a version yet.
+    fn installed_data(&self) -> (PathBuf, PathBuf) {
+        let layout = self.layout();
+        match layout.latest_version() {
+            Some(version) if !layout.is_flat() => {
+                (layout.raw_dir(&version), layout.manifest_path(&version))
+            }
+            _ => (self.data_dir.clone(), self.data_dir.join(MANIFEST_FILE)),
+        }
+    }
+
+    /// Load the LUT from `--lut`, or from the data directory
+    ///
+    /// Uses the parsed LUT recorded in the data version's manifest when it
+    /// is current, and parses the data files otherwise.
+    pub fn load_lut(&self) -> anyhow::Result<Arc<LutData>> {
+        if let Some(path) = &self.lut {
+            let format = match path.extension().and_then(|e| e.to_str()) {
+                Some("bin") => "bincode",
+                _ => "json",
+            };
+            let data = PobDataParser::load_artifact(path, format)
+                .with_context(|| format!("Could not load {}", path.display()))?;
+            return Ok(Arc::new(data));
+        }
+
+        let (raw_dir, manifest_path) = self.installed_data();
+        let dir = &raw_dir;
+        if !dir.join("NodeIndexMapping.lua").exists() {
+            bail!(
+                "No PoB data in {}; run `data update` first or pass --data-dir",
+                self.data_dir.display()
+            );
+        }
+
+        if let Ok(manifest) = DataManifest::load_from_file(&manifest_path) {
+            if let (Some(artifact), Ok(false)) =
+                (&manifest.parsed_artifact, manifest.needs_reparse(dir))
+            {
+                let path = artifact.resolve(dir);
+                match PobDataParser::load_artifact(&path, &artifact.format) {
+                    Ok(data) => return Ok(Arc::new(data)),
+                    Err(e) => eprintln!("warning: {}: {}; parsing instead", path.display(), e),
+                }
+            }
+        }
+
+        let parsed = PobDataParser::parse_directory(dir).map_err(DataError::from);
+        if let Err(e) = &parsed {
+            if e.needs_redownload() {
+                bail!("{}; run `data update` to download the data again", e);
+            }
+        }
+        let (data, report) =
+            parsed.with_context(|| format!("Could not parse the data in {}", dir.display()))?;
+        for warning in &report.warnings {
+            eprintln!("warning: {}", warning);
+        }
+        Ok(Arc::new(data))
+    }
+
+    /// Print `timings` on stderr, keeping stdout for the results
+    pub fn print_timings(&self, timings: Option<&AnalysisTimings>) {
+        let Some(timings) = timings else {
+            return;
+        };
+        eprintln!("Timings:");
+        for line in timings.to_string().lines() {
+            eprintln!("  {}", line);
+        }
+    }
+
+    /// Print `value` as pretty JSON
+    pub fn print_json(&self, value: &impl Serialize) -> anyhow::Result<()> {
+        println!("{}", serde_json::to_string_pretty(value)?);
+        Ok(())
+    }
+}
+
+/// Same default as the desktop app (e.g., ~/.local/share/poe-item-analyzer/pob-data)
+fn default_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("poe-item-analyzer").join("pob-data"))
+        .unwrap_or_else(|| std::env::temp_dir().join("poe-item-analyzer-test"))
+}
+
+/// Weights file: a bare config, or a profile saved by the desktop app
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WeightsFile {
+    Profile { config: TimelessJewelConfig },
+    Config(TimelessJewelConfig),
+}
+
+/// Load the weights in `path`
+pub fn load_weights(path: &Path) -> anyhow::Result<TimelessJewelConfig> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    let weights: WeightsFile = serde_json::from_str(&json)
+        .with_context(|| format!("{} is not a weights file", path.display()))?;
+
+    let config = match weights {
+        WeightsFile::Profile { config } | WeightsFile::Config(config) => config,
+    };
+    if config.valuable_mods().is_empty() {
+        bail!("{} has no weighted mods", path.display());
+    }
+    Ok(config)
+}
+
+/// Parse a jewel type like "lethal-pride", "LethalPride" or "Lethal Pride"
+pub fn parse_jewel_type(text: &str) -> Result<JewelType, String> {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase()
+    };
+    let wanted = normalize(text);
+
+    JewelType::ALL
+        .into_iter()
+        .find(|jewel_type| normalize(jewel_type.as_str()) == wanted)
+        .ok_or_else(|| {
+            let names: Vec<String> = JewelType::ALL
+                .iter()
+                .map(|t| t.as_str().to_ascii_lowercase().replace(' ', "-"))
+                .collect();
+            format!("expected one of {}", names.join(", "))
+        })
+}
+
+/// `conqueror`, checked against `jewel_type`, or its first conqueror
+pub fn resolve_conqueror(
+    jewel_type: JewelType,
+    conqueror: Option<String>,
+) -> anyhow::Result<String> {
+    let conquerors = jewel_type.conquerors();
+
+    match conqueror {
+        None => Ok(conquerors[0].to_string()),
+        Some(name) => match conquerors.iter().find(|c| c.eq_ignore_ascii_case(&name)) {
+            Some(conqueror) => Ok(conqueror.to_string()),
+            None => bail!(
+                "{} has no conqueror \"{}\" (expected one of {})",
+                jewel_type.as_str(),
+                name,
+                conquerors.join(", ")
+            ),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_jewel_type() {
+        assert_eq!(parse_jewel_type("lethal-pride"), Ok(JewelType::LethalPride));
+        assert_eq!(
+            parse_jewel_type("BrutalRestraint"),
+            Ok(JewelType::BrutalRestraint)
+        );
+        assert_eq!(
+            parse_jewel_type("Elegant Hubris"),
+            Ok(JewelType::ElegantHubris)
+        );
+        assert!(parse_jewel_type("grand-spectrum")
+            .unwrap_err()
+            .contains("militant-faith"));
+    }
+
+    #[test]
+    fn test_resolve_conqueror() {
+        let jewel_type = JewelType::LethalPride;
+        assert_eq!(resolve_conqueror(jewel_type, None).unwrap(), "Kaom");
+        assert_eq!(
+            resolve_conqueror(jewel_type, Some("akoya".into())).unwrap(),
+            "Akoya"
+        );
+        assert!(resolve_conqueror(jewel_type, Some("Doryani".into())).is_err());
+    }
+
+    #[test]
+    fn test_load_weights_accepts_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let profile = temp_dir.path().join("profile.json");
+        std::fs::write(
+            &profile,
+            r#"{"name": "Offense", "config": {"valuable_mods": {"Onslaught": 2.0}}}"#,
+        )
+        .unwrap();
+        let config = temp_dir.path().join("config.json");
+        std::fs::write(&config, r#"{"valuable_mods": {"Onslaught": 2.0}}"#).unwrap();
+
+        assert_eq!(
+            load_weights(&profile).unwrap(),
+            load_weights(&config).unwrap()
+        );
+        assert_eq!(
+            load_weights(&config)
+                .unwrap()
+                .valuable_mods()
+                .get("Onslaught"),
+            Some(&2.0)
+        );
+
+        std::fs::write(&config, "{}").unwrap();
+        assert!(load_weights(&config).is_err());
+    }
+}
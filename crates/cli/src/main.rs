@@ -0,0 +1,294 @@
+//! PoE Item Analyzer command line interface
+//!
+//! Headless access to data updates, analysis and seed search, for scripts
+//! and CI. Results go to stdout (human-readable, or JSON with `--json`);
+//! progress and warnings go to stderr. Exits with 1 on errors and 2 on
+//! invalid arguments.
+
+mod commands;
+mod context;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use poe_item_analyzer_core::items::JewelType;
+
+use context::{parse_jewel_type, Context};
+
+#[derive(Parser)]
+#[command(name = "poe-item-analyzer-cli", version, about)]
+struct Cli {
+    /// PoB data directory (defaults to the desktop app's)
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Use this parsed LUT file instead of the data directory
+    #[arg(long, global = true, value_name = "FILE")]
+    lut: Option<PathBuf>,
+
+    /// Print machine-readable JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Print where the analysis spent its time on stderr
+    #[arg(long, global = true)]
+    timings: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the PoB data
+    #[command(subcommand)]
+    Data(DataCommand),
+
+    /// Score one jewel at every socket
+    Analyze {
+        /// Jewel type (e.g., lethal-pride)
+        #[arg(long = "type", value_parser = parse_jewel_type)]
+        jewel_type: JewelType,
+
+        /// Jewel seed
+        #[arg(long)]
+        seed: u32,
+
+        /// Conqueror (defaults to the jewel type's first)
+        #[arg(long)]
+        conqueror: Option<String>,
+
+        /// Weights as a JSON config or a saved desktop profile
+        #[arg(long, value_name = "FILE")]
+        weights: PathBuf,
+    },
+
+    /// Find the best seeds of a jewel type
+    Search {
+        /// Jewel type (e.g., brutal-restraint)
+        #[arg(long = "type", value_parser = parse_jewel_type)]
+        jewel_type: JewelType,
+
+        /// Conqueror (defaults to the jewel type's first)
+        #[arg(long)]
+        conqueror: Option<String>,
+
+        /// Number of seeds to keep
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Weights as a JSON config or a saved desktop profile
+        #[arg(long, value_name = "FILE")]
+        weights: PathBuf,
+
+        /// Socket to score at ("all" for every node the data covers)
+        #[arg(long, default_value = "all")]
+        socket: String,
+
+        /// JSON list of sockets and their nodes, or the passive tree JSON to
+        /// compute them from, for `--socket`
+        #[arg(long, value_name = "FILE")]
+        sockets: Option<PathBuf>,
+
+        /// Also print trade site searches for the seeds found, in this league
+        #[arg(long, value_name = "LEAGUE")]
+        trade_url: Option<String>,
+
+        /// Only print this page of the seeds found (from 1)
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        page: Option<u64>,
+
+        /// Seeds per page, for `--page`
+        #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u64).range(1..))]
+        page_size: u64,
+
+        /// Record the seeds found in the data directory's history
+        #[arg(long)]
+        record: bool,
+    },
+
+    /// Save an analysis (with `--seed`) or seed search as an HTML report
+    Report {
+        /// Jewel type (e.g., lethal-pride)
+        #[arg(long = "type", value_parser = parse_jewel_type)]
+        jewel_type: JewelType,
+
+        /// Seed to analyze (searches for the best seeds without one)
+        #[arg(long)]
+        seed: Option<u32>,
+
+        /// Conqueror (defaults to the jewel type's first)
+        #[arg(long)]
+        conqueror: Option<String>,
+
+        /// Weights as a JSON config or a saved desktop profile
+        #[arg(long, value_name = "FILE")]
+        weights: PathBuf,
+
+        /// Number of seeds to keep when searching
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Socket to search at ("all" for every node the data covers)
+        #[arg(long, default_value = "all")]
+        socket: String,
+
+        /// JSON list of sockets and their nodes, or the passive tree JSON to
+        /// compute them from, for `--socket`
+        #[arg(long, value_name = "FILE")]
+        sockets: Option<PathBuf>,
+
+        /// HTML file to write
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+    },
+
+    /// Read jewels from item text copied in game
+    Import {
+        /// Read the item text from stdin
+        #[arg(long, required = true)]
+        stdin: bool,
+
+        /// Rank the jewels with these weights
+        #[arg(long, value_name = "FILE")]
+        weights: Option<PathBuf>,
+
+        /// Record the ranking in the data directory's history (with
+        /// `--weights`)
+        #[arg(long, requires = "weights")]
+        record: bool,
+    },
+
+    /// List recorded runs and compare them
+    #[command(subcommand)]
+    History(HistoryCommand),
+}
+
+#[derive(Subcommand)]
+enum DataCommand {
+    /// Download, verify and parse the latest data
+    Update {
+        /// GitHub token (raises the API rate limit)
+        #[arg(long, env = "GITHUB_TOKEN", hide_env_values = true)]
+        github_token: Option<String>,
+    },
+
+    /// Save the parsed LUT as a signed cache file others can import
+    ExportCache {
+        /// Cache file to write
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+    },
+
+    /// Verify a cache file from `data export-cache` and use it for the
+    /// installed data, skipping the parse
+    ///
+    /// The cache must come from the same PoB data version and app version.
+    ImportCache {
+        /// Cache file to import
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// List the recorded runs, oldest first
+    List,
+
+    /// Show how scores changed from an earlier run to the latest one
+    Diff {
+        /// Id of the earlier run (see `history list`)
+        #[arg(value_name = "RUN")]
+        previous: String,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let context = Context::new(cli.data_dir, cli.lut, cli.json, cli.timings);
+
+    let result = match cli.command {
+        Command::Data(DataCommand::Update { github_token }) => {
+            commands::data::update(&context, github_token)
+        }
+        Command::Data(DataCommand::ExportCache { out }) => {
+            commands::data::export_cache(&context, &out)
+        }
+        Command::Data(DataCommand::ImportCache { file }) => {
+            commands::data::import_cache(&context, &file)
+        }
+        Command::Analyze {
+            jewel_type,
+            seed,
+            conqueror,
+            weights,
+        } => commands::analyze::run(&context, jewel_type, seed, conqueror, &weights),
+        Command::Search {
+            jewel_type,
+            conqueror,
+            top,
+            weights,
+            socket,
+            sockets,
+            trade_url,
+            page,
+            page_size,
+            record,
+        } => commands::search::run(
+            &context,
+            jewel_type,
+            conqueror,
+            &weights,
+            commands::search::SearchOptions {
+                top,
+                socket: &socket,
+                sockets: sockets.as_deref(),
+                trade_league: trade_url.as_deref(),
+                page: page.map(|page| page as usize),
+                page_size: page_size as usize,
+                record,
+            },
+        ),
+        Command::Report {
+            jewel_type,
+            seed,
+            conqueror,
+            weights,
+            top,
+            socket,
+            sockets,
+            out,
+        } => commands::report::run(
+            &context,
+            jewel_type,
+            conqueror,
+            &weights,
+            commands::report::ReportOptions {
+                seed,
+                top,
+                socket: &socket,
+                sockets: sockets.as_deref(),
+                out: &out,
+            },
+        ),
+        Command::Import {
+            stdin: _,
+            weights,
+            record,
+        } => commands::import::run(&context, weights.as_deref(), record),
+        Command::History(HistoryCommand::List) => commands::history::list(&context),
+        Command::History(HistoryCommand::Diff { previous }) => {
+            commands::history::diff(&context, &previous)
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,539 @@
+//! Integration test: the CLI binary against a small LUT fixture
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use poe_item_analyzer_api::{DataLayout, DataManifest};
+use serde_json::Value;
+
+const LUT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/lut_data.json");
+const WEIGHTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/weights.json");
+const ITEMS: &str = include_str!("fixtures/items.txt");
+
+fn cli(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_poe-item-analyzer-cli"));
+    command.args(["--lut", LUT, "--json"]).args(args);
+    command
+}
+
+fn run(args: &[&str]) -> Output {
+    cli(args).output().unwrap()
+}
+
+/// Run with the item text fixture on stdin
+fn run_with_items(args: &[&str]) -> Output {
+    let mut child = cli(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(ITEMS.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn json(output: &Output) -> Value {
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_analyze() {
+    let output = run(&[
+        "analyze",
+        "--type",
+        "lethal-pride",
+        "--seed",
+        "14032",
+        "--weights",
+        WEIGHTS,
+    ]);
+    let export = json(&output);
+    assert_eq!(export["schema_version"], 1);
+    let result = &export["results"][0];
+
+    // Double Damage on both nodes
+    assert_eq!(result["best_score"], 10.0);
+    assert_eq!(result["jewel"]["seed"], 14032);
+    assert_eq!(result["jewel"]["conqueror"], "Kaom");
+}
+
+#[test]
+fn test_analyze_prints_best_socket_per_mod() {
+    let output = Command::new(env!("CARGO_BIN_EXE_poe-item-analyzer-cli"))
+        .args(["--lut", LUT])
+        .args([
+            "analyze",
+            "--type",
+            "lethal-pride",
+            "--seed",
+            "14032",
+            "--weights",
+            WEIGHTS,
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (_, summary) = stdout.split_once("Best socket per mod:\n").unwrap();
+    assert!(
+        summary.lines().next().unwrap().contains("× Double Damage"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_analyze_rejects_invalid_input() {
+    let output = run(&[
+        "analyze",
+        "--type",
+        "lethal-pride",
+        "--seed",
+        "5",
+        "--weights",
+        WEIGHTS,
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a valid"));
+
+    let output = run(&[
+        "analyze",
+        "--type",
+        "grand-spectrum",
+        "--seed",
+        "1",
+        "--weights",
+        WEIGHTS,
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run(&[
+        "analyze",
+        "--type",
+        "lethal-pride",
+        "--seed",
+        "14032",
+        "--weights",
+        "missing.json",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_timings_flag() {
+    let args = [
+        "analyze",
+        "--type",
+        "lethal-pride",
+        "--seed",
+        "14032",
+        "--weights",
+        WEIGHTS,
+    ];
+
+    let output = run(&args);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Timings"));
+
+    let output = cli(&args).arg("--timings").output().unwrap();
+    json(&output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for phase in [
+        "LUT lookup",
+        "Matching",
+        "Scoring",
+        "Socket iteration",
+        "Total",
+    ] {
+        assert!(stderr.contains(phase), "{}", stderr);
+    }
+
+    // Search results carry them in the JSON too
+    let output = cli(&[
+        "search",
+        "--type",
+        "lethal-pride",
+        "--weights",
+        WEIGHTS,
+        "--timings",
+    ])
+    .output()
+    .unwrap();
+    assert!(json(&output)["timings"]["total"].is_object());
+}
+
+#[test]
+fn test_search() {
+    let output = run(&[
+        "search",
+        "--type",
+        "brutal-restraint",
+        "--top",
+        "1",
+        "--weights",
+        WEIGHTS,
+    ]);
+    let result = json(&output);
+
+    let results = result["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["seed"], 600);
+    assert_eq!(results[0]["score"], 7.0);
+}
+
+#[test]
+fn test_search_pages() {
+    let search = |page: &str| {
+        json(&run(&[
+            "search",
+            "--type",
+            "brutal-restraint",
+            "--top",
+            "3",
+            "--weights",
+            WEIGHTS,
+            "--page",
+            page,
+            "--page-size",
+            "2",
+        ]))
+    };
+
+    let first = search("1");
+    assert_eq!(first["total_count"], 3);
+    assert_eq!(first["page_count"], 2);
+    assert_eq!(first["results"].as_array().unwrap().len(), 2);
+    assert_eq!(first["results"][0]["seed"], 600);
+
+    let last = search("2");
+    assert_eq!(last["results"].as_array().unwrap().len(), 1);
+
+    let past_end = search("3");
+    assert!(past_end["results"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_search_trade_urls() {
+    let output = run(&[
+        "search",
+        "--type",
+        "brutal-restraint",
+        "--top",
+        "1",
+        "--weights",
+        WEIGHTS,
+        "--trade-url",
+        "Standard",
+    ]);
+    let result = json(&output);
+
+    let urls = result["trade_urls"].as_array().unwrap();
+    assert_eq!(urls.len(), 1);
+    let url = urls[0].as_str().unwrap();
+    assert!(url.starts_with("https://www.pathofexile.com/trade/search/Standard?q="));
+    assert!(url.contains("explicit.pseudo_timeless_jewel_asenath"));
+}
+
+#[test]
+fn test_search_needs_socket_layouts() {
+    let output = run(&[
+        "search",
+        "--type",
+        "brutal-restraint",
+        "--weights",
+        WEIGHTS,
+        "--socket",
+        "scion",
+    ]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--sockets"));
+}
+
+#[test]
+fn test_search_computes_sockets_from_passive_tree() {
+    let tree = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../core/tests/fixtures/passive_tree.json"
+    );
+    let output = run(&[
+        "search",
+        "--type",
+        "brutal-restraint",
+        "--top",
+        "1",
+        "--weights",
+        WEIGHTS,
+        "--sockets",
+        tree,
+        "--socket",
+        "36634",
+    ]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json(&output)["results"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_report_writes_html() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let analysis = temp_dir.path().join("analysis.html");
+    let search = temp_dir.path().join("search.html");
+
+    let output = run(&[
+        "report",
+        "--type",
+        "lethal-pride",
+        "--seed",
+        "14032",
+        "--weights",
+        WEIGHTS,
+        "--out",
+        analysis.to_str().unwrap(),
+    ]);
+    assert_eq!(json(&output)["report"], analysis.to_str().unwrap());
+    let html = std::fs::read_to_string(&analysis).unwrap();
+    assert!(html.contains("<h1>Lethal Pride 14032 (Kaom)</h1>"));
+    assert!(html.contains("<td>Double Damage</td>"));
+
+    let output = run(&[
+        "report",
+        "--type",
+        "brutal-restraint",
+        "--top",
+        "1",
+        "--weights",
+        WEIGHTS,
+        "--out",
+        search.to_str().unwrap(),
+    ]);
+    json(&output);
+    let html = std::fs::read_to_string(&search).unwrap();
+    assert!(html.contains("<td class=\"num\">600</td>"));
+}
+
+#[test]
+fn test_import_ranks_pasted_jewels() {
+    let export = json(&run_with_items(&[
+        "import",
+        "--stdin",
+        "--weights",
+        WEIGHTS,
+    ]));
+
+    let ranked = export["results"].as_array().unwrap();
+    assert_eq!(ranked.len(), 2);
+    assert_eq!(ranked[0]["rank"], 1);
+    assert_eq!(ranked[0]["jewel"]["seed"], 14032);
+    assert_eq!(ranked[1]["jewel"]["seed"], 15000);
+}
+
+#[test]
+fn test_import_lists_jewel_records() {
+    let records = json(&run_with_items(&["import", "--stdin"]));
+
+    let records = records.as_array().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1]["id"], "Lethal Pride:14032:Akoya");
+    assert!(records[1]["raw_data"]["text"]
+        .as_str()
+        .unwrap()
+        .contains("14032"));
+}
+
+#[test]
+fn test_missing_data_dir() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("empty");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_poe-item-analyzer-cli"))
+        .args(["--data-dir", data_dir.to_str().unwrap()])
+        .args([
+            "analyze",
+            "--type",
+            "lethal-pride",
+            "--seed",
+            "14032",
+            "--weights",
+            WEIGHTS,
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("data update"));
+}
+
+#[test]
+fn test_data_dir_loads_latest_version() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let layout = DataLayout::new(temp_dir.path());
+    layout.create("abc123").unwrap();
+
+    // Unparseable data files: only the recorded LUT can be used
+    let raw_dir = layout.raw_dir("abc123");
+    std::fs::write(raw_dir.join("NodeIndexMapping.lua"), "return {").unwrap();
+    let artifact = layout.parsed_dir("abc123").join("lut_data.json");
+    std::fs::copy(LUT, &artifact).unwrap();
+
+    let mut manifest = DataManifest::default_pob();
+    manifest.data_version = "abc123".to_string();
+    manifest
+        .record_parsed_artifact(&raw_dir, &artifact, "json")
+        .unwrap();
+    manifest
+        .save_to_file(&layout.manifest_path("abc123"))
+        .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_poe-item-analyzer-cli"))
+        .args(["--data-dir", temp_dir.path().to_str().unwrap(), "--json"])
+        .args([
+            "analyze",
+            "--type",
+            "lethal-pride",
+            "--seed",
+            "14032",
+            "--weights",
+            WEIGHTS,
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(json(&output)["schema_version"], 1);
+}
+
+#[test]
+fn test_export_and_import_cache() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let cache = temp_dir.path().join("shared.bin");
+    let data_dir = temp_dir.path().join("pob-data");
+    let layout = DataLayout::new(&data_dir);
+    layout.create("abc123").unwrap();
+    let raw_dir = layout.raw_dir("abc123");
+    std::fs::write(raw_dir.join("NodeIndexMapping.lua"), "return {").unwrap();
+    let mut manifest = DataManifest::default_pob();
+    manifest.data_version = "abc123".to_string();
+    manifest
+        .save_to_file(&layout.manifest_path("abc123"))
+        .unwrap();
+
+    let output = run(&["data", "export-cache", "--out", cache.to_str().unwrap()]);
+    assert_eq!(json(&output)["sha256"].as_str().unwrap().len(), 64);
+
+    let import = |file: &Path| {
+        cli(&["--data-dir", data_dir.to_str().unwrap()])
+            .args(["data", "import-cache", file.to_str().unwrap()])
+            .output()
+            .unwrap()
+    };
+
+    // A flipped byte is refused, and nothing is recorded
+    let mut bytes = std::fs::read(&cache).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    let tampered = temp_dir.path().join("tampered.bin");
+    std::fs::write(&tampered, &bytes).unwrap();
+    let output = import(&tampered);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("tampered.bin"), "{}", stderr);
+    let manifest = DataManifest::load_from_file(&layout.manifest_path("abc123")).unwrap();
+    assert!(manifest.parsed_artifact.is_none());
+
+    // The verified cache is used instead of the unparseable data files
+    assert_eq!(json(&import(&cache))["version"], "abc123");
+    let output = Command::new(env!("CARGO_BIN_EXE_poe-item-analyzer-cli"))
+        .args(["--data-dir", data_dir.to_str().unwrap(), "--json"])
+        .args([
+            "analyze",
+            "--type",
+            "lethal-pride",
+            "--seed",
+            "14032",
+            "--weights",
+            WEIGHTS,
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(json(&output)["schema_version"], 1);
+}
+
+#[test]
+fn test_corrupt_data_suggests_update() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("NodeIndexMapping.lua"), "return {").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_poe-item-analyzer-cli"))
+        .args(["--data-dir", temp_dir.path().to_str().unwrap()])
+        .args([
+            "analyze",
+            "--type",
+            "lethal-pride",
+            "--seed",
+            "14032",
+            "--weights",
+            WEIGHTS,
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("NodeIndexMapping.lua"), "{}", stderr);
+    assert!(stderr.contains("run `data update`"), "{}", stderr);
+}
+
+#[test]
+fn test_recorded_searches_can_be_compared() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let data_dir = temp_dir.path().to_str().unwrap();
+    let weights = temp_dir.path().join("weights.json");
+    std::fs::write(
+        &weights,
+        r#"{"valuable_mods": {"Double Damage": 1.0, "Onslaught": 4.0}}"#,
+    )
+    .unwrap();
+    let search = |weights: &str, top: &str| {
+        cli(&["--data-dir", data_dir])
+            .args(["search", "--type", "lethal-pride", "--top", top])
+            .args(["--weights", weights, "--record"])
+            .output()
+            .unwrap()
+    };
+
+    json(&search(WEIGHTS, "3"));
+    json(&search(weights.to_str().unwrap(), "2"));
+
+    let history = json(&run(&["--data-dir", data_dir, "history", "list"]));
+    let runs = history.as_array().unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[1]["id"], "2");
+    assert_eq!(runs[1]["kind"], "seed_search");
+    assert_ne!(runs[0]["config_hash"], runs[1]["config_hash"]);
+
+    let diff = json(&run(&["--data-dir", data_dir, "history", "diff", "1"]));
+    let changes: Vec<(u64, &str)> = diff["seeds"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|seed| (seed["seed"].as_u64().unwrap(), seed["change"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![(15000, "improved"), (14032, "worsened"), (10000, "dropped")]
+    );
+
+    let output = run(&["--data-dir", data_dir, "history", "diff", "9"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,180 @@
+//! Side-by-side comparison of two analyzed timeless jewels
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AnalysisError;
+use crate::items::{SocketResult, TimelessJewel};
+
+use super::timeless::TimelessJewelAnalysisResult;
+
+/// How often a mod appears at a socket for each jewel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModDiff {
+    /// Mod text
+    pub mod_text: String,
+
+    /// Occurrences for jewel A
+    pub count_a: usize,
+
+    /// Occurrences for jewel B
+    pub count_b: usize,
+}
+
+impl ModDiff {
+    /// Whether both jewels have the mod
+    pub fn is_shared(&self) -> bool {
+        self.count_a > 0 && self.count_b > 0
+    }
+}
+
+/// Comparison of both jewels at one socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketComparison {
+    /// Socket identifier
+    pub socket_id: String,
+
+    /// Human-readable socket name
+    pub socket_name: String,
+
+    /// Score of jewel A (0 if A wasn't analyzed at this socket)
+    pub score_a: f64,
+
+    /// Score of jewel B (0 if B wasn't analyzed at this socket)
+    pub score_b: f64,
+
+    /// Every mod either jewel provides, sorted by mod text
+    pub mods: Vec<ModDiff>,
+}
+
+impl SocketComparison {
+    /// Score of B minus score of A
+    pub fn score_delta(&self) -> f64 {
+        self.score_b - self.score_a
+    }
+
+    /// Mods both jewels provide
+    pub fn shared(&self) -> impl Iterator<Item = &ModDiff> {
+        self.mods.iter().filter(|m| m.is_shared())
+    }
+
+    /// Mods only jewel A provides
+    pub fn only_a(&self) -> impl Iterator<Item = &ModDiff> {
+        self.mods.iter().filter(|m| m.count_b == 0)
+    }
+
+    /// Mods only jewel B provides
+    pub fn only_b(&self) -> impl Iterator<Item = &ModDiff> {
+        self.mods.iter().filter(|m| m.count_a == 0)
+    }
+}
+
+/// Per-socket diff of two jewels of the same type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JewelComparison {
+    /// First jewel
+    pub jewel_a: TimelessJewel,
+
+    /// Second jewel
+    pub jewel_b: TimelessJewel,
+
+    /// Sockets analyzed for either jewel, in the order of A's results
+    pub sockets: Vec<SocketComparison>,
+}
+
+impl JewelComparison {
+    /// Compare two analysis results
+    ///
+    /// Fails if the jewels are of different types, since their mods
+    /// wouldn't be comparable.
+    pub fn new(
+        a: &TimelessJewelAnalysisResult,
+        b: &TimelessJewelAnalysisResult,
+    ) -> Result<Self, AnalysisError> {
+        if a.jewel.jewel_type != b.jewel.jewel_type {
+            return Err(AnalysisError::InvalidItemData(format!(
+                "can't compare {} with {}",
+                a.jewel.jewel_type.as_str(),
+                b.jewel.jewel_type.as_str()
+            )));
+        }
+
+        let sockets_a = &a.metrics.socket_results;
+        let sockets_b = &b.metrics.socket_results;
+        let sockets = line_up(sockets_a, sockets_b, |socket| socket.socket_id.as_str())
+            .into_iter()
+            .map(|(socket_a, socket_b)| compare_socket(socket_a, socket_b))
+            .collect();
+
+        Ok(Self {
+            jewel_a: a.jewel.clone(),
+            jewel_b: b.jewel.clone(),
+            sockets,
+        })
+    }
+
+    /// Comparison at `socket_id`, if either jewel was analyzed there
+    pub fn socket(&self, socket_id: &str) -> Option<&SocketComparison> {
+        self.sockets.iter().find(|s| s.socket_id == socket_id)
+    }
+}
+
+/// Pair the items of `first` and `second` that have the same key: every
+/// item of `first` in order, then those only `second` has
+///
+/// Keys are looked up in maps, so long lists (e.g., recorded runs) take
+/// linear time. Of items sharing a key, the first is paired.
+pub(super) fn line_up<'a, T, K: Eq + Hash>(
+    first: &'a [T],
+    second: &'a [T],
+    key: impl Fn(&'a T) -> K,
+) -> Vec<(Option<&'a T>, Option<&'a T>)> {
+    let mut second_by_key: HashMap<K, &'a T> = HashMap::with_capacity(second.len());
+    for item in second {
+        second_by_key.entry(key(item)).or_insert(item);
+    }
+    let first_keys: HashSet<K> = first.iter().map(&key).collect();
+
+    let mut pairs: Vec<(Option<&'a T>, Option<&'a T>)> = first
+        .iter()
+        .map(|item| (Some(item), second_by_key.get(&key(item)).copied()))
+        .collect();
+    pairs.extend(
+        second
+            .iter()
+            .filter(|item| !first_keys.contains(&key(item)))
+            .map(|item| (None, Some(item))),
+    );
+    pairs
+}
+
+/// Compare the mods of one socket; at least one side must be present
+fn compare_socket(a: Option<&SocketResult>, b: Option<&SocketResult>) -> SocketComparison {
+    let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+
+    for mod_text in a.iter().flat_map(|s| &s.all_mods) {
+        counts.entry(mod_text).or_default().0 += 1;
+    }
+    for mod_text in b.iter().flat_map(|s| &s.all_mods) {
+        counts.entry(mod_text).or_default().1 += 1;
+    }
+
+    let socket = a.or(b).expect("at least one socket to compare");
+
+    SocketComparison {
+        socket_id: socket.socket_id.clone(),
+        socket_name: socket.socket_name.clone(),
+        score_a: a.map_or(0.0, |s| s.score),
+        score_b: b.map_or(0.0, |s| s.score),
+        mods: counts
+            .into_iter()
+            .map(|(mod_text, (count_a, count_b))| ModDiff {
+                mod_text: mod_text.to_string(),
+                count_a,
+                count_b,
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,170 @@
+//! Score distribution over a jewel type's seeds
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+/// Number of histogram buckets between the lowest and highest score
+pub const HISTOGRAM_BUCKETS: usize = 20;
+
+/// Percentiles reported in `ScoreDistribution::percentiles`
+pub const REPORTED_PERCENTILES: [f64; 6] = [25.0, 50.0, 75.0, 90.0, 99.0, 99.9];
+
+/// Score at a percentile
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScorePercentile {
+    /// Percentile, from 0 to 100
+    pub percentile: f64,
+
+    /// Highest score among the lowest `percentile`% of seeds
+    pub score: f64,
+}
+
+/// Seeds scoring within `[min, max)` (the last bucket includes `max`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub min: f64,
+    pub max: f64,
+    pub count: usize,
+}
+
+/// How the scores of every scanned seed are spread
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreDistribution {
+    /// Seeds scored
+    pub count: usize,
+
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+
+    /// Scores at `REPORTED_PERCENTILES`
+    pub percentiles: Vec<ScorePercentile>,
+
+    /// `HISTOGRAM_BUCKETS` equal-width buckets from `min` to `max`
+    /// (one bucket when every seed scores the same)
+    pub histogram: Vec<HistogramBucket>,
+
+    /// Each distinct score with its number of seeds, lowest first
+    ///
+    /// Scores are sums of a few weights, so this stays short even over
+    /// 150,000 seeds.
+    scores: Vec<(f64, usize)>,
+}
+
+impl ScoreDistribution {
+    /// Distribution of `scores`
+    pub fn from_scores(scores: impl IntoIterator<Item = f64>) -> Self {
+        let mut builder = DistributionBuilder::default();
+        scores.into_iter().for_each(|score| builder.add(score));
+        builder.finish()
+    }
+
+    /// Share of seeds scoring below `score`, from 0.0 to 100.0
+    ///
+    /// "Better than 97.4% of seeds" for 97.4.
+    pub fn percentile_of(&self, score: f64) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        let below: usize = self
+            .scores
+            .iter()
+            .take_while(|(s, _)| *s < score)
+            .map(|(_, count)| count)
+            .sum();
+        below as f64 * 100.0 / self.count as f64
+    }
+
+    /// Lowest score that `percentile`% of seeds are at or below
+    pub fn score_at(&self, percentile: f64) -> f64 {
+        // Nearest rank, counting seeds from 1
+        let rank = ((percentile / 100.0 * self.count as f64).ceil() as usize).max(1);
+        let mut seen = 0;
+
+        for (score, count) in &self.scores {
+            seen += count;
+            if seen >= rank {
+                return *score;
+            }
+        }
+        self.max
+    }
+}
+
+/// Collects scores during a scan
+#[derive(Debug, Default)]
+pub(crate) struct DistributionBuilder {
+    scores: Vec<f64>,
+}
+
+impl DistributionBuilder {
+    pub(crate) fn add(&mut self, score: f64) {
+        self.scores.push(score);
+    }
+
+    pub(crate) fn finish(mut self) -> ScoreDistribution {
+        if self.scores.is_empty() {
+            return ScoreDistribution::default();
+        }
+
+        self.scores
+            .sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let count = self.scores.len();
+        let min = self.scores[0];
+        let max = self.scores[count - 1];
+        let mean = self.scores.iter().sum::<f64>() / count as f64;
+
+        let mut scores: Vec<(f64, usize)> = Vec::new();
+        for score in &self.scores {
+            match scores.last_mut() {
+                Some((last, seeds)) if last == score => *seeds += 1,
+                _ => scores.push((*score, 1)),
+            }
+        }
+
+        let mut distribution = ScoreDistribution {
+            count,
+            min,
+            max,
+            mean,
+            percentiles: Vec::new(),
+            histogram: histogram(&scores, min, max),
+            scores,
+        };
+        distribution.percentiles = REPORTED_PERCENTILES
+            .iter()
+            .map(|&percentile| ScorePercentile {
+                percentile,
+                score: distribution.score_at(percentile),
+            })
+            .collect();
+        distribution
+    }
+}
+
+/// Equal-width buckets over distinct `scores`
+fn histogram(scores: &[(f64, usize)], min: f64, max: f64) -> Vec<HistogramBucket> {
+    if max <= min {
+        let count = scores.iter().map(|(_, count)| count).sum();
+        return vec![HistogramBucket { min, max, count }];
+    }
+
+    let width = (max - min) / HISTOGRAM_BUCKETS as f64;
+    let mut buckets: Vec<HistogramBucket> = (0..HISTOGRAM_BUCKETS)
+        .map(|index| HistogramBucket {
+            min: min + width * index as f64,
+            max: min + width * (index + 1) as f64,
+            count: 0,
+        })
+        .collect();
+    buckets[HISTOGRAM_BUCKETS - 1].max = max;
+
+    for (score, count) in scores {
+        let index = (((score - min) / width) as usize).min(HISTOGRAM_BUCKETS - 1);
+        buckets[index].count += count;
+    }
+    buckets
+}
@@ -0,0 +1,211 @@
+//! Versioned JSON schema for exported results
+//!
+//! Spreadsheets and bots read the CLI's `--json` output and the desktop
+//! app's JSON exports, so their shape is kept apart from the internal
+//! result types: renaming an internal field must not change it. Fields may
+//! be added within a schema version; anything else needs a new version.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::ExportError;
+use crate::items::{Item, KeystoneChange, MatchedMod, SocketResult, TimelessJewel};
+
+use super::timeless::TimelessJewelAnalysisResult;
+use super::traits::RankedResult;
+
+/// Schema version written by this release
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Exported analysis results, best first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultExportV1 {
+    /// Always `EXPORT_SCHEMA_VERSION` when written by this release
+    pub schema_version: u32,
+
+    /// Version of the app that wrote the export
+    pub app_version: String,
+
+    /// Version of the LUT the results were computed with, if known
+    pub lut_version: Option<String>,
+
+    pub results: Vec<JewelResultV1>,
+}
+
+/// One analyzed jewel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JewelResultV1 {
+    /// Rank among the exported results, from 1
+    pub rank: usize,
+
+    pub jewel: JewelV1,
+
+    /// Score at the best socket
+    pub best_score: f64,
+
+    pub best_socket_id: String,
+
+    /// Result at every socket, in the order they were scored
+    pub sockets: Vec<SocketV1>,
+}
+
+/// The jewel that was analyzed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JewelV1 {
+    pub id: String,
+
+    /// Display name (e.g., "Lethal Pride")
+    pub jewel_type: String,
+
+    pub seed: u32,
+
+    pub conqueror: String,
+}
+
+/// Result at one socket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SocketV1 {
+    pub socket_id: String,
+    pub socket_name: String,
+    pub score: f64,
+    pub matched_mods: Vec<MatchedModV1>,
+
+    /// Keystone the jewel replaces at this socket, if any
+    pub keystone: Option<KeystoneV1>,
+
+    /// Passive points needed to reach the socket's weighted nodes, when
+    /// travel costs were computed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub travel_cost: Option<u32>,
+}
+
+/// A weighted mod found at a socket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchedModV1 {
+    pub mod_text: String,
+    pub weight: f64,
+    pub count: usize,
+    pub trade_stat_id: Option<String>,
+
+    /// Number in the mod text the weight was multiplied by, for weights per
+    /// value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+}
+
+/// A keystone replaced by the jewel's conqueror keystone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeystoneV1 {
+    pub original: String,
+    pub replacement: String,
+    pub weight: f64,
+}
+
+impl ResultExportV1 {
+    /// Export of a single result
+    pub fn from_result(
+        result: &TimelessJewelAnalysisResult,
+        lut_version: Option<String>,
+    ) -> Self {
+        Self::new(vec![JewelResultV1::from_result(result, 1)], lut_version)
+    }
+
+    /// Export of a ranking, keeping its ranks
+    pub fn from_ranked(
+        ranked: &[RankedResult<TimelessJewelAnalysisResult>],
+        lut_version: Option<String>,
+    ) -> Self {
+        let results = ranked
+            .iter()
+            .map(|ranked| JewelResultV1::from_result(&ranked.result, ranked.rank))
+            .collect();
+        Self::new(results, lut_version)
+    }
+
+    fn new(results: Vec<JewelResultV1>, lut_version: Option<String>) -> Self {
+        Self {
+            schema_version: EXPORT_SCHEMA_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            lut_version,
+            results,
+        }
+    }
+
+    /// Read an export back
+    ///
+    /// Exports from a newer schema version are rejected rather than read
+    /// with fields silently missing.
+    pub fn from_json(json: &str) -> Result<Self, ExportError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| ExportError::Invalid(e.to_string()))?;
+
+        let version = value
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .ok_or(ExportError::MissingVersion)?;
+        if version != u64::from(EXPORT_SCHEMA_VERSION) {
+            return Err(ExportError::UnsupportedVersion(version));
+        }
+
+        serde_json::from_value(value).map_err(|e| ExportError::Invalid(e.to_string()))
+    }
+}
+
+impl JewelResultV1 {
+    /// Exported form of `result` at `rank`
+    pub fn from_result(result: &TimelessJewelAnalysisResult, rank: usize) -> Self {
+        Self {
+            rank,
+            jewel: JewelV1::from(&result.jewel),
+            best_score: result.best_score,
+            best_socket_id: result.best_socket_id.clone(),
+            sockets: result.metrics.socket_results.iter().map(SocketV1::from).collect(),
+        }
+    }
+}
+
+impl From<&TimelessJewel> for JewelV1 {
+    fn from(jewel: &TimelessJewel) -> Self {
+        Self {
+            id: jewel.id(),
+            jewel_type: jewel.jewel_type.as_str().to_string(),
+            seed: jewel.seed,
+            conqueror: jewel.conqueror.clone(),
+        }
+    }
+}
+
+impl From<&SocketResult> for SocketV1 {
+    fn from(socket: &SocketResult) -> Self {
+        Self {
+            socket_id: socket.socket_id.clone(),
+            socket_name: socket.socket_name.clone(),
+            score: socket.score,
+            matched_mods: socket.matched_mods.iter().map(MatchedModV1::from).collect(),
+            keystone: socket.keystone_change.as_ref().map(KeystoneV1::from),
+            travel_cost: socket.travel_cost,
+        }
+    }
+}
+
+impl From<&MatchedMod> for MatchedModV1 {
+    fn from(matched: &MatchedMod) -> Self {
+        Self {
+            mod_text: matched.mod_text.clone(),
+            weight: matched.weight,
+            count: matched.count,
+            trade_stat_id: matched.trade_stat_id.clone(),
+            value: matched.value,
+        }
+    }
+}
+
+impl From<&KeystoneChange> for KeystoneV1 {
+    fn from(change: &KeystoneChange) -> Self {
+        Self {
+            original: change.original.clone(),
+            replacement: change.replacement.clone(),
+            weight: change.weight,
+        }
+    }
+}
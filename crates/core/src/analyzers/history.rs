@@ -0,0 +1,135 @@
+//! Score changes between two recorded runs
+//!
+//! After adjusting weights, the previous run's numbers are the only way to
+//! tell whether the best seeds got better. `RunEntry` keeps what a run found
+//! per jewel; `RunDiff` lines two runs up like `JewelComparison` lines up
+//! two jewels' sockets (see `compare::line_up`), with A the earlier run and
+//! B the later one.
+
+use serde::{Deserialize, Serialize};
+
+use crate::items::Item;
+
+use super::compare::line_up;
+use super::seed_search::SeedSearchResult;
+use super::timeless::TimelessJewelAnalysisResult;
+
+/// Score differences smaller than this count as unchanged
+const SCORE_EPSILON: f64 = 1e-9;
+
+/// Best score of one jewel in a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunEntry {
+    /// Jewel id (seed search ids look like "Lethal Pride:14032:Kaom")
+    pub jewel_id: String,
+
+    pub seed: u32,
+
+    pub score: f64,
+}
+
+impl RunEntry {
+    /// Entries of a seed search's ranked seeds, best first
+    pub fn from_search(result: &SeedSearchResult) -> Vec<Self> {
+        result
+            .jewels()
+            .into_iter()
+            .zip(result.results.iter())
+            .map(|(jewel, seed)| RunEntry {
+                jewel_id: jewel.id(),
+                seed: seed.seed,
+                score: seed.score,
+            })
+            .collect()
+    }
+
+    /// Entries of analyzed jewels (e.g., a ranked batch), in the given order
+    pub fn from_results<'a>(
+        results: impl IntoIterator<Item = &'a TimelessJewelAnalysisResult>,
+    ) -> Vec<Self> {
+        results
+            .into_iter()
+            .map(|result| RunEntry {
+                jewel_id: result.jewel.id(),
+                seed: result.jewel.seed,
+                score: result.best_score,
+            })
+            .collect()
+    }
+}
+
+/// How a jewel's score changed from run A to run B
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreChange {
+    Improved,
+    Worsened,
+    Unchanged,
+    /// Only in run B
+    New,
+    /// Only in run A
+    Dropped,
+}
+
+/// Scores of one jewel in both runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeedDelta {
+    pub jewel_id: String,
+
+    pub seed: u32,
+
+    /// Score in run A (None if A didn't have the jewel)
+    pub score_a: Option<f64>,
+
+    /// Score in run B (None if B didn't have the jewel)
+    pub score_b: Option<f64>,
+}
+
+impl SeedDelta {
+    /// Score in B minus score in A, counting a missing score as 0
+    pub fn score_delta(&self) -> f64 {
+        self.score_b.unwrap_or(0.0) - self.score_a.unwrap_or(0.0)
+    }
+
+    pub fn change(&self) -> ScoreChange {
+        match (self.score_a, self.score_b) {
+            (None, _) => ScoreChange::New,
+            (_, None) => ScoreChange::Dropped,
+            _ if self.score_delta() > SCORE_EPSILON => ScoreChange::Improved,
+            _ if self.score_delta() < -SCORE_EPSILON => ScoreChange::Worsened,
+            _ => ScoreChange::Unchanged,
+        }
+    }
+}
+
+/// Per-jewel score changes between two runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunDiff {
+    /// Jewels of run B in B's order, then those only run A had
+    pub seeds: Vec<SeedDelta>,
+}
+
+impl RunDiff {
+    /// Compare run `a` (earlier) with run `b` (later)
+    pub fn new(a: &[RunEntry], b: &[RunEntry]) -> Self {
+        let seeds = line_up(b, a, |entry| entry.jewel_id.as_str())
+            .into_iter()
+            .filter_map(|(entry_b, entry_a)| {
+                let entry = entry_b.or(entry_a)?;
+                Some(SeedDelta {
+                    jewel_id: entry.jewel_id.clone(),
+                    seed: entry.seed,
+                    score_a: entry_a.map(|entry| entry.score),
+                    score_b: entry_b.map(|entry| entry.score),
+                })
+            })
+            .collect();
+
+        Self { seeds }
+    }
+
+    /// Jewels whose score changed as `change`
+    pub fn with_change(&self, change: ScoreChange) -> impl Iterator<Item = &SeedDelta> {
+        self.seeds.iter().filter(move |seed| seed.change() == change)
+    }
+}
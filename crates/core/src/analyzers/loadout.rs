@@ -0,0 +1,315 @@
+//! Best placement of several timeless jewels at once
+//!
+//! Endgame trees often run two or more timeless jewels, and the best pair is
+//! not simply the two best jewels: both may be best at the same socket.
+//! `best_loadout` scores every candidate at every socket once, then tries
+//! every assignment of jewels to distinct sockets.
+//!
+//! Sockets can share nodes, and a node in two radii is transformed by one
+//! jewel only. Which one depends on the order they were socketed, so a shared
+//! node counts once, for the jewel it does more for.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AnalysisError;
+use crate::items::{JewelType, MatchedMod, SocketResult, TimelessJewel};
+
+use super::timeless::{TimelessJewelAnalyzer, TimelessJewelConfig};
+
+/// One jewel placed in a socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadoutSlot {
+    pub jewel: TimelessJewel,
+
+    /// The jewel's result at the socket it was placed in
+    pub socket: SocketResult,
+}
+
+/// Jewels placed in distinct sockets, maximizing their combined score
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Loadout {
+    /// Placed jewels, in the order their sockets were given
+    pub slots: Vec<LoadoutSlot>,
+
+    /// Sum of the slots' scores, with each shared node counted once
+    pub total_score: f64,
+
+    /// Matched mods of every slot, added up by mod text, best first (mods on
+    /// a shared node count for the jewel it counts for in `total_score`)
+    pub matched_mods: Vec<MatchedMod>,
+}
+
+impl Loadout {
+    /// `owners` maps each shared node to the socket id of the slot it counts for
+    fn new(slots: Vec<LoadoutSlot>, total_score: f64, owners: &HashMap<u32, &str>) -> Self {
+        let mut matched_mods: Vec<MatchedMod> = Vec::new();
+        for matched in slots.iter().flat_map(|slot| &slot.socket.matched_mods) {
+            let same = |m: &&mut MatchedMod| {
+                m.mod_text == matched.mod_text && m.matched_pattern == matched.matched_pattern
+            };
+            match matched_mods.iter_mut().find(same) {
+                Some(existing) => existing.count += matched.count,
+                None => matched_mods.push(matched.clone()),
+            }
+        }
+
+        // Take back the mods of shared nodes that count for another slot
+        for slot in &slots {
+            let lost = slot.socket.node_contributions.iter().filter(|contribution| {
+                let owner = owners.get(&contribution.node_id);
+                owner.is_some_and(|owner| *owner != slot.socket.socket_id)
+            });
+            for (mod_text, pattern) in lost.flat_map(|contribution| &contribution.matched) {
+                let same = |m: &&mut MatchedMod| {
+                    &m.mod_text == mod_text && m.matched_pattern.as_ref() == Some(pattern)
+                };
+                if let Some(existing) = matched_mods.iter_mut().find(same) {
+                    existing.count = existing.count.saturating_sub(1);
+                }
+            }
+        }
+        matched_mods.retain(|matched| matched.count > 0);
+        matched_mods.sort_by(|a, b| b.total().total_cmp(&a.total()));
+
+        Self {
+            total_score,
+            slots,
+            matched_mods,
+        }
+    }
+}
+
+/// A candidate's score at a socket, split around the shared nodes
+#[derive(Default)]
+struct SlotScore {
+    /// Score from everything but the shared nodes
+    base: f64,
+
+    /// Contribution of each shared node the jewel has weighted mods on
+    shared: HashMap<u32, f64>,
+
+    /// The most the slot can add to a loadout, for pruning
+    optimistic: f64,
+}
+
+impl SlotScore {
+    fn new(socket: &SocketResult, shared_nodes: &[u32]) -> Self {
+        let shared: HashMap<u32, f64> = socket
+            .node_contributions
+            .iter()
+            .filter(|contribution| shared_nodes.contains(&contribution.node_id))
+            .map(|contribution| (contribution.node_id, contribution.score))
+            .collect();
+        let base = socket.score - shared.values().sum::<f64>();
+        let optimistic = base + shared.values().map(|score| score.max(0.0)).sum::<f64>();
+        Self { base, shared, optimistic }
+    }
+}
+
+/// Search state: scores of every candidate at every socket
+struct Assignment<'a> {
+    /// `scores[jewel][socket]`, None where the jewel wasn't scored
+    scores: Vec<Vec<Option<f64>>>,
+
+    /// `slots[jewel][socket]`, the scores split for shared nodes
+    slots: Vec<Vec<SlotScore>>,
+
+    /// Nodes each socket shares with another socket
+    shared_nodes: &'a [Vec<u32>],
+
+    /// Type of each candidate (timeless jewels are Historic: one per type)
+    types: Vec<JewelType>,
+
+    /// Most any candidate can add at each socket, for pruning
+    socket_best: &'a [f64],
+
+    /// Jewel index per socket of the best assignment so far
+    best: Vec<Option<usize>>,
+    best_score: f64,
+}
+
+impl Assignment<'_> {
+    /// Combined score of jewels placed per socket, each shared node counted
+    /// at the better of the placed jewels whose radius has it
+    fn total(&self, placed: &[Option<usize>]) -> f64 {
+        let base: f64 = placed
+            .iter()
+            .enumerate()
+            .filter_map(|(socket, jewel)| jewel.map(|jewel| self.slots[jewel][socket].base))
+            .sum();
+        base + self.owners(placed).values().map(|(_, score)| score).sum::<f64>()
+    }
+
+    /// The socket each shared node counts for, with its score there: the
+    /// placed jewel that does the most for it, the first on ties
+    ///
+    /// A jewel with no weighted mods on a shared node does 0 for it.
+    fn owners(&self, placed: &[Option<usize>]) -> HashMap<u32, (usize, f64)> {
+        let mut owners: HashMap<u32, (usize, f64)> = HashMap::new();
+        for (socket, jewel) in placed.iter().enumerate() {
+            let Some(jewel) = *jewel else {
+                continue;
+            };
+            let slot = &self.slots[jewel][socket];
+            for node in &self.shared_nodes[socket] {
+                let score = slot.shared.get(node).copied().unwrap_or_default();
+                let owner = owners.entry(*node).or_insert((socket, score));
+                if score > owner.1 {
+                    *owner = (socket, score);
+                }
+            }
+        }
+        owners
+    }
+
+    /// Try every jewel (or none) at `socket` and the sockets after it
+    ///
+    /// `bound` adds up the optimistic scores of the placed slots.
+    fn search(&mut self, socket: usize, current: &mut Vec<Option<usize>>, bound: f64) {
+        if socket == self.socket_best.len() {
+            let score = self.total(current);
+            if score > self.best_score {
+                self.best_score = score;
+                self.best = current.clone();
+            }
+            return;
+        }
+
+        // Even the best jewel at every remaining socket can't do better
+        let remaining: f64 = self.socket_best[socket..].iter().sum();
+        if bound + remaining <= self.best_score {
+            return;
+        }
+
+        current.push(None);
+        self.search(socket + 1, current, bound);
+        current.pop();
+
+        for jewel in 0..self.scores.len() {
+            if !self.scores[jewel][socket].is_some_and(|score| score > 0.0) {
+                continue;
+            }
+            let taken = current
+                .iter()
+                .flatten()
+                .any(|&other| other == jewel || self.types[other] == self.types[jewel]);
+            if taken {
+                continue;
+            }
+
+            current.push(Some(jewel));
+            let optimistic = self.slots[jewel][socket].optimistic;
+            self.search(socket + 1, current, bound + optimistic);
+            current.pop();
+        }
+    }
+}
+
+impl TimelessJewelAnalyzer {
+    /// Best placement of `candidates` in the sockets in `socket_ids`
+    ///
+    /// Each jewel is analyzed once, then every assignment of jewels to
+    /// distinct sockets is tried, so the work grows quickly with the number
+    /// of sockets; keep it to the few a tree can use. At most one jewel of
+    /// each type is placed, as timeless jewels are Historic. Sockets where
+    /// no jewel scores above zero stay empty. A node in the radius of two
+    /// placed jewels counts once, for the jewel that does more for it (see
+    /// the module docs).
+    pub fn best_loadout(
+        &self,
+        candidates: &[TimelessJewel],
+        socket_ids: &[String],
+        config: &TimelessJewelConfig,
+    ) -> Result<Loadout, AnalysisError> {
+        let mut results: Vec<HashMap<String, SocketResult>> = Vec::new();
+        for jewel in candidates {
+            let result = self.analyze_sockets_with(jewel, config, Some(socket_ids), true)?;
+            let sockets = result.metrics.socket_results.into_iter();
+            results.push(sockets.map(|socket| (socket.socket_id.clone(), socket)).collect());
+        }
+
+        let socket_nodes: Vec<HashSet<u32>> = socket_ids
+            .iter()
+            .map(|id| {
+                let socket = self.sockets().iter().find(|socket| &socket.id == id);
+                socket.map(|socket| socket.nodes.iter().copied().collect()).unwrap_or_default()
+            })
+            .collect();
+        let shared_nodes: Vec<Vec<u32>> = socket_nodes
+            .iter()
+            .enumerate()
+            .map(|(socket, nodes)| {
+                let in_other = |node: &u32| {
+                    let mut others = socket_nodes.iter().enumerate().filter(|(o, _)| *o != socket);
+                    others.any(|(_, other)| other.contains(node))
+                };
+                let mut shared: Vec<u32> = nodes.iter().copied().filter(in_other).collect();
+                shared.sort_unstable();
+                shared
+            })
+            .collect();
+
+        let scores: Vec<Vec<Option<f64>>> = results
+            .iter()
+            .map(|sockets| {
+                let score = |id: &String| sockets.get(id).map(|socket| socket.score);
+                socket_ids.iter().map(score).collect()
+            })
+            .collect();
+        let slots: Vec<Vec<SlotScore>> = results
+            .iter()
+            .map(|sockets| {
+                let slot = |(id, shared): (&String, &Vec<u32>)| {
+                    let socket = sockets.get(id);
+                    socket.map(|socket| SlotScore::new(socket, shared)).unwrap_or_default()
+                };
+                socket_ids.iter().zip(&shared_nodes).map(slot).collect()
+            })
+            .collect();
+        let socket_best: Vec<f64> = (0..socket_ids.len())
+            .map(|socket| {
+                let best = slots.iter().map(|jewel| jewel[socket].optimistic);
+                best.fold(0.0, f64::max)
+            })
+            .collect();
+
+        let mut assignment = Assignment {
+            scores,
+            slots,
+            shared_nodes: &shared_nodes,
+            types: candidates.iter().map(|jewel| jewel.jewel_type).collect(),
+            socket_best: &socket_best,
+            best: Vec::new(),
+            best_score: 0.0,
+        };
+        assignment.search(0, &mut Vec::new(), 0.0);
+        let total_score = assignment.best_score;
+        let owners: HashMap<u32, &str> = assignment
+            .owners(&assignment.best)
+            .into_iter()
+            .map(|(node, (socket, _))| (node, socket_ids[socket].as_str()))
+            .collect();
+
+        let slots = assignment
+            .best
+            .iter()
+            .zip(socket_ids)
+            .filter_map(|(jewel, socket_id)| {
+                let jewel = (*jewel)?;
+                let socket = results[jewel].remove(socket_id)?;
+                Some(LoadoutSlot { jewel: candidates[jewel].clone(), socket })
+            })
+            .collect();
+
+        let mut loadout = Loadout::new(slots, total_score, &owners);
+        for slot in &mut loadout.slots {
+            if slot.socket.travel_cost.is_none() {
+                // Only kept for the search and the matched mods
+                slot.socket.node_contributions = Vec::new();
+            }
+        }
+        Ok(loadout)
+    }
+}
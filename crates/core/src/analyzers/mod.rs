@@ -2,10 +2,41 @@
 
 pub mod traits;
 pub mod timeless;
+pub mod seed_search;
+pub mod distribution;
+pub mod compare;
+pub mod profiles;
+pub mod owned;
+pub mod loadout;
+pub mod history;
+pub mod export;
+pub mod report;
+pub mod warnings;
+pub mod ranked;
+pub mod timings;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export commonly used types
 pub use traits::{Analyzer, RankedResult};
-pub use timeless::{TimelessJewelAnalysisResult, TimelessJewelAnalyzer, TimelessJewelConfig};
+pub use compare::{JewelComparison, ModDiff, SocketComparison};
+pub use export::{
+    JewelResultV1, JewelV1, KeystoneV1, MatchedModV1, ResultExportV1, SocketV1,
+    EXPORT_SCHEMA_VERSION,
+};
+pub use distribution::{HistogramBucket, ScoreDistribution, ScorePercentile};
+pub use owned::{dominates, BatchItem, BatchResult};
+pub use loadout::{Loadout, LoadoutSlot};
+pub use history::{RunDiff, RunEntry, ScoreChange, SeedDelta};
+pub use ranked::RankedResultSet;
+pub use report::{escape_html, render_analysis_report, render_search_report};
+pub use profiles::{
+    MultiProfileResult, ProfileRanking, ProfileResult, ProfileTable, ProfileTableRow,
+};
+pub use seed_search::{CancelFlag, SearchProgress, SeedScore, SeedSearchResult, SeedSearcher};
+pub use timeless::{
+    SocketConfig, TimelessJewelAnalysisResult, TimelessJewelAnalyzer, TimelessJewelConfig,
+};
+pub use timings::AnalysisTimings;
+pub use warnings::{AnalysisWarning, AnalysisWarnings};
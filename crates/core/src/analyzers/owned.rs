@@ -0,0 +1,134 @@
+//! Ranking candidates against jewels the user already owns
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AnalysisError;
+use crate::items::{Item, SocketResult, TimelessJewel};
+
+use super::timeless::{TimelessJewelAnalysisResult, TimelessJewelAnalyzer, TimelessJewelConfig};
+use super::traits::{Analyzer, RankedResult};
+
+/// A jewel to rank, marked if the user already owns it
+#[derive(Debug, Clone)]
+pub struct BatchItem {
+    pub jewel: TimelessJewel,
+
+    /// Whether the jewel is already owned (e.g., imported from a stash tab)
+    pub owned: bool,
+}
+
+impl BatchItem {
+    /// A jewel the user already owns
+    pub fn owned(jewel: TimelessJewel) -> Self {
+        Self { jewel, owned: true }
+    }
+
+    /// A jewel the user is considering (e.g., from trade)
+    pub fn candidate(jewel: TimelessJewel) -> Self {
+        Self {
+            jewel,
+            owned: false,
+        }
+    }
+}
+
+/// Analysis of one batch item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub result: TimelessJewelAnalysisResult,
+
+    /// Whether the jewel is already owned
+    pub owned: bool,
+
+    /// Id of the best-ranked owned jewel that is at least as good
+    /// (always None for owned jewels)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dominated_by: Option<String>,
+}
+
+/// Whether `owned` makes `candidate` pointless to get
+///
+/// Both must be the same jewel type with the same best socket, `owned`
+/// must score at least as high, and at that socket it must have every mod
+/// `candidate` matched, at least as many times.
+pub fn dominates(
+    owned: &TimelessJewelAnalysisResult,
+    candidate: &TimelessJewelAnalysisResult,
+) -> bool {
+    if owned.jewel.jewel_type != candidate.jewel.jewel_type
+        || owned.best_socket_id != candidate.best_socket_id
+        || owned.best_score < candidate.best_score
+    {
+        return false;
+    }
+
+    match (best_socket(owned), best_socket(candidate)) {
+        (Some(owned), Some(candidate)) => candidate.matched_mods.iter().all(|wanted| {
+            owned
+                .matched_mods
+                .iter()
+                .any(|m| m.mod_text == wanted.mod_text && m.count >= wanted.count)
+        }),
+        _ => false,
+    }
+}
+
+/// Result at the best socket
+fn best_socket(result: &TimelessJewelAnalysisResult) -> Option<&SocketResult> {
+    result
+        .metrics
+        .socket_results
+        .iter()
+        .find(|socket| socket.socket_id == result.best_socket_id)
+}
+
+impl TimelessJewelAnalyzer {
+    /// Rank owned jewels and candidates together
+    ///
+    /// After ranking, each candidate that an owned jewel dominates (see
+    /// `dominates`) gets `dominated_by` set.
+    pub fn analyze_batch_owned(
+        &self,
+        items: &[BatchItem],
+        config: &TimelessJewelConfig,
+    ) -> Result<Vec<RankedResult<BatchResult>>, AnalysisError> {
+        let mut results = items
+            .iter()
+            .map(|item| {
+                Ok(BatchResult {
+                    result: self.analyze(&item.jewel, config)?,
+                    owned: item.owned,
+                    dominated_by: None,
+                })
+            })
+            .collect::<Result<Vec<_>, AnalysisError>>()?;
+
+        results.sort_by(|a, b| self.compare_results(&a.result, &b.result));
+
+        let dominated: Vec<Option<String>> = results
+            .iter()
+            .map(|candidate| {
+                if candidate.owned {
+                    return None;
+                }
+                results
+                    .iter()
+                    .find(|owned| owned.owned && dominates(&owned.result, &candidate.result))
+                    .map(|owned| owned.result.jewel.id())
+            })
+            .collect();
+
+        Ok(results
+            .into_iter()
+            .zip(dominated)
+            .enumerate()
+            .map(|(index, (result, dominated_by))| RankedResult {
+                rank: index + 1,
+                result: BatchResult {
+                    dominated_by,
+                    ..result
+                },
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,187 @@
+//! Scoring one jewel under several weight profiles
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AnalysisError;
+use crate::items::TimelessJewel;
+
+use super::timeless::{TimelessJewelAnalysisResult, TimelessJewelAnalyzer, TimelessJewelConfig};
+use super::traits::{Analyzer, RankedResult};
+
+/// One jewel's analysis under one profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileResult {
+    /// Profile name (e.g., "offense")
+    pub profile: String,
+
+    /// Analysis with the profile's weights
+    pub result: TimelessJewelAnalysisResult,
+}
+
+/// One jewel analyzed under every profile, in the order they were given
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiProfileResult {
+    /// The jewel that was analyzed
+    pub jewel: TimelessJewel,
+
+    /// Result per profile
+    pub profiles: Vec<ProfileResult>,
+}
+
+impl MultiProfileResult {
+    /// Result under the profile named `profile`
+    pub fn profile(&self, profile: &str) -> Option<&TimelessJewelAnalysisResult> {
+        self.profiles
+            .iter()
+            .find(|p| p.profile == profile)
+            .map(|p| &p.result)
+    }
+
+    /// Highest best score across profiles
+    pub fn max_score(&self) -> f64 {
+        self.profiles
+            .iter()
+            .map(|p| p.result.best_score)
+            .reduce(f64::max)
+            .unwrap_or(0.0)
+    }
+
+    /// Score used to rank this jewel (0 if the profile doesn't exist)
+    pub fn ranking_score(&self, ranking: &ProfileRanking) -> f64 {
+        match ranking {
+            ProfileRanking::Profile(name) => {
+                self.profile(name).map_or(0.0, |result| result.best_score)
+            }
+            ProfileRanking::Max => self.max_score(),
+        }
+    }
+
+    /// Socket scores with one column per profile
+    pub fn table(&self) -> ProfileTable {
+        let mut rows: Vec<ProfileTableRow> = Vec::new();
+
+        for (column, profile) in self.profiles.iter().enumerate() {
+            for socket in &profile.result.metrics.socket_results {
+                let index = match rows.iter().position(|row| row.socket_id == socket.socket_id) {
+                    Some(index) => index,
+                    None => {
+                        rows.push(ProfileTableRow {
+                            socket_id: socket.socket_id.clone(),
+                            socket_name: socket.socket_name.clone(),
+                            scores: vec![0.0; self.profiles.len()],
+                        });
+                        rows.len() - 1
+                    }
+                };
+                rows[index].scores[column] = socket.score;
+            }
+        }
+
+        ProfileTable {
+            columns: self.profiles.iter().map(|p| p.profile.clone()).collect(),
+            best_sockets: self
+                .profiles
+                .iter()
+                .map(|p| p.result.best_socket_id.clone())
+                .collect(),
+            rows,
+        }
+    }
+}
+
+/// Socket scores of one jewel, laid out for a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileTable {
+    /// Profile names, one per score column
+    pub columns: Vec<String>,
+
+    /// Best socket under each profile, in column order
+    pub best_sockets: Vec<String>,
+
+    /// One row per socket, in the order the sockets were scored
+    pub rows: Vec<ProfileTableRow>,
+}
+
+/// Scores at one socket, in column order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileTableRow {
+    pub socket_id: String,
+    pub socket_name: String,
+    pub scores: Vec<f64>,
+}
+
+/// What a multi-profile batch is ranked by
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProfileRanking {
+    /// The best score under one profile
+    Profile(String),
+
+    /// The highest best score under any profile
+    Max,
+}
+
+impl TimelessJewelAnalyzer {
+    /// Analyze `item` once per named config
+    ///
+    /// Each profile is scored on its own; weights never carry over from one
+    /// profile to another.
+    pub fn analyze_multi(
+        &self,
+        item: &TimelessJewel,
+        configs: &[(String, TimelessJewelConfig)],
+    ) -> Result<MultiProfileResult, AnalysisError> {
+        let profiles = configs
+            .iter()
+            .map(|(profile, config)| {
+                Ok(ProfileResult {
+                    profile: profile.clone(),
+                    result: self.analyze(item, config)?,
+                })
+            })
+            .collect::<Result<_, AnalysisError>>()?;
+
+        Ok(MultiProfileResult {
+            jewel: item.clone(),
+            profiles,
+        })
+    }
+
+    /// Analyze every item under every profile and rank them by `ranking`
+    pub fn analyze_batch_multi(
+        &self,
+        items: &[TimelessJewel],
+        configs: &[(String, TimelessJewelConfig)],
+        ranking: &ProfileRanking,
+    ) -> Result<Vec<RankedResult<MultiProfileResult>>, AnalysisError> {
+        if let ProfileRanking::Profile(name) = ranking {
+            if !configs.iter().any(|(profile, _)| profile == name) {
+                return Err(AnalysisError::AnalysisFailed(format!(
+                    "No weight profile named '{}'",
+                    name
+                )));
+            }
+        }
+
+        let mut results = items
+            .iter()
+            .map(|item| self.analyze_multi(item, configs))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        results.sort_by(|a, b| {
+            b.ranking_score(ranking)
+                .partial_cmp(&a.ranking_score(ranking))
+                .unwrap_or(Ordering::Equal)
+        });
+
+        Ok(results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| RankedResult {
+                rank: index + 1,
+                result,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,100 @@
+//! Ranked result sets read a page at a time
+//!
+//! A permissive seed search can rank tens of thousands of results. They are
+//! sorted once when the set is built; pages are slices of the sorted list.
+
+use std::cmp::Ordering;
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use super::traits::RankedResult;
+
+/// Results sorted best first, read by page or from a rank
+///
+/// Derefs to the sorted slice (index 0 is rank 1). Serializes as the plain
+/// list, best first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RankedResultSet<T> {
+    results: Vec<T>,
+}
+
+impl<T> RankedResultSet<T> {
+    /// Rank `results` with `compare` (`Ordering::Less` for the better one)
+    ///
+    /// The sort is stable, so equal results keep their order.
+    pub fn new(mut results: Vec<T>, compare: impl FnMut(&T, &T) -> Ordering) -> Self {
+        results.sort_by(compare);
+        Self { results }
+    }
+
+    /// Number of ranked results
+    pub fn total_count(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Whether there are no results
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Page `index` (from 0) of `size` results
+    ///
+    /// The last page may be shorter; pages past it, and pages of size 0, are
+    /// empty.
+    pub fn page(&self, index: usize, size: usize) -> &[T] {
+        let Some(start) = index.checked_mul(size).filter(|start| *start < self.results.len())
+        else {
+            return &[];
+        };
+        let end = start.saturating_add(size).min(self.results.len());
+        &self.results[start..end]
+    }
+
+    /// Number of pages of `size` results (0 for size 0)
+    pub fn page_count(&self, size: usize) -> usize {
+        if size == 0 {
+            return 0;
+        }
+        self.results.len().div_ceil(size)
+    }
+
+    /// Results from `rank` on (1 = best), with their ranks
+    pub fn iter_from(&self, rank: usize) -> impl Iterator<Item = RankedResult<&T>> {
+        let skip = rank.max(1) - 1;
+        self.results
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .map(|(index, result)| RankedResult { rank: index + 1, result })
+    }
+
+    /// Keep only the `count` best results
+    pub fn truncate(&mut self, count: usize) {
+        self.results.truncate(count);
+    }
+}
+
+impl<T> Default for RankedResultSet<T> {
+    fn default() -> Self {
+        Self { results: Vec::new() }
+    }
+}
+
+impl<T> Deref for RankedResultSet<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.results
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RankedResultSet<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.iter()
+    }
+}
@@ -0,0 +1,184 @@
+//! Standalone HTML snapshots of analysis results
+//!
+//! A report is one file with its styles inline and no scripts, so it can be
+//! attached to a forum post or opened from a download folder years later and
+//! look the same. Everything taken from results (mod texts, jewel ids, socket
+//! names) is escaped, as mod texts can contain `<` and `>`.
+
+use std::fmt::Write;
+
+use crate::items::{Item, SocketResult, SocketSortKey};
+
+use super::seed_search::SeedSearchResult;
+use super::timeless::TimelessJewelAnalysisResult;
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2em auto;max-width:60em;color:#222}\
+h1{font-size:1.5em}h2{font-size:1.2em;margin-top:1.5em}\
+table{border-collapse:collapse;margin:0.5em 0}\
+th,td{border:1px solid #ccc;padding:0.25em 0.75em;text-align:left}\
+td.num{text-align:right}th{background:#f0f0f0}\
+.summary td:first-child{font-weight:bold}.warning{color:#a60}\
+footer{margin-top:2em;color:#777;font-size:0.85em}";
+
+/// Report of one jewel's analysis, every socket best first
+pub fn render_analysis_report(
+    result: &TimelessJewelAnalysisResult,
+    lut_version: Option<&str>,
+) -> String {
+    let jewel = &result.jewel;
+    let title = format!(
+        "{} {} ({})",
+        jewel.jewel_type.as_str(),
+        jewel.seed,
+        jewel.conqueror
+    );
+
+    let mut body = format!("<h1>{}</h1>\n", escape_html(&title));
+    summary_table(
+        &mut body,
+        &[
+            ("Jewel", jewel.id().to_string()),
+            ("Best score", format!("{:.2}", result.best_score)),
+            ("Best socket", result.best_socket_id.clone()),
+            ("Sockets", result.metrics.socket_results.len().to_string()),
+        ],
+    );
+    for warning in result.warnings.iter() {
+        let warning = escape_html(&warning.to_string());
+        let _ = writeln!(body, "<p class=\"warning\">Warning: {}</p>", warning);
+    }
+
+    for socket in result.metrics.sorted_by(SocketSortKey::Score) {
+        let _ = writeln!(
+            body,
+            "<h2>{} &mdash; {:.2}</h2>",
+            escape_html(&socket.socket_name),
+            socket.score
+        );
+        socket_tables(&mut body, socket);
+    }
+
+    document(&title, &body, lut_version)
+}
+
+/// Report of a seed search: the ranking, then each seed's socket
+pub fn render_search_report(result: &SeedSearchResult, lut_version: Option<&str>) -> String {
+    let title = format!(
+        "{} seeds for {}",
+        result.jewel_type.as_str(),
+        result.conqueror
+    );
+
+    let mut body = format!("<h1>{}</h1>\n", escape_html(&title));
+    let mut summary = vec![
+        ("Seeds scanned", result.scanned.to_string()),
+        ("Seeds ranked", result.results.total_count().to_string()),
+    ];
+    if let Some(best) = result.results.first() {
+        summary.push(("Best score", format!("{:.2}", best.score)));
+    }
+    if result.cancelled {
+        summary.push(("Cancelled", "yes".to_string()));
+    }
+    summary_table(&mut body, &summary);
+
+    body.push_str("<table>\n<tr><th>Rank</th><th>Seed</th><th>Score</th><th>Socket</th></tr>\n");
+    for ranked in result.results.iter_from(1) {
+        let _ = writeln!(
+            body,
+            "<tr><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+             <td class=\"num\">{:.2}</td><td>{}</td></tr>",
+            ranked.rank,
+            ranked.result.seed,
+            ranked.result.score,
+            escape_html(&ranked.result.socket.socket_name)
+        );
+    }
+    body.push_str("</table>\n");
+
+    for ranked in result.results.iter_from(1) {
+        let _ = writeln!(
+            body,
+            "<h2>#{} seed {} &mdash; {:.2}</h2>",
+            ranked.rank, ranked.result.seed, ranked.result.score
+        );
+        socket_tables(&mut body, &ranked.result.socket);
+    }
+
+    document(&title, &body, lut_version)
+}
+
+/// `text` with the characters HTML gives a meaning replaced by entities
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Two-column table of labels and values
+fn summary_table(body: &mut String, rows: &[(&str, String)]) {
+    body.push_str("<table class=\"summary\">\n");
+    for (label, value) in rows {
+        let _ = writeln!(body, "<tr><td>{}</td><td>{}</td></tr>", label, escape_html(value));
+    }
+    body.push_str("</table>\n");
+}
+
+/// The keystone change and matched mods of a socket
+fn socket_tables(body: &mut String, socket: &SocketResult) {
+    if let Some(keystone) = &socket.keystone_change {
+        let _ = writeln!(
+            body,
+            "<p>Keystone: {} &rarr; {} (weight {})</p>",
+            escape_html(&keystone.original),
+            escape_html(&keystone.replacement),
+            keystone.weight
+        );
+    }
+    if let Some(cost) = socket.travel_cost {
+        let _ = writeln!(body, "<p>Travel cost: {} points</p>", cost);
+    }
+
+    if socket.matched_mods.is_empty() {
+        body.push_str("<p>No matched mods</p>\n");
+        return;
+    }
+
+    body.push_str("<table>\n<tr><th>Mod</th><th>Count</th><th>Weight</th><th>Total</th></tr>\n");
+    for matched in &socket.matched_mods {
+        let _ = writeln!(
+            body,
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+             <td class=\"num\">{}</td></tr>",
+            escape_html(&matched.mod_text),
+            matched.count,
+            matched.weight,
+            matched.total()
+        );
+    }
+    body.push_str("</table>\n");
+}
+
+/// Full document around `body`, with the versions in the footer
+fn document(title: &str, body: &str, lut_version: Option<&str>) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}\
+         <footer>LUT version {} &middot; poe-item-analyzer {}</footer>\n</body>\n</html>\n",
+        escape_html(title),
+        STYLE,
+        body,
+        escape_html(lut_version.unwrap_or("unknown")),
+        env!("CARGO_PKG_VERSION")
+    )
+}
@@ -0,0 +1,300 @@
+//! Best-seed search over a jewel type's seed range
+
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{JewelSocket, TimelessLookup};
+use crate::error::AnalysisError;
+use crate::items::{JewelType, SocketResult, TimelessJewel};
+
+use super::distribution::{DistributionBuilder, ScoreDistribution};
+use super::ranked::RankedResultSet;
+use super::timeless::{TimelessJewelAnalyzer, TimelessJewelConfig};
+use super::timings::AnalysisTimings;
+use super::traits::Analyzer;
+
+/// Number of progress reports over a full scan
+const PROGRESS_STEPS: usize = 100;
+
+/// Stops a running seed search from another thread
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    /// Create a flag that isn't cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the search to stop
+    pub fn cancel(&self) {
+        self.0.store(true, AtomicOrdering::Relaxed);
+    }
+
+    /// Whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
+    }
+}
+
+/// Progress of a running seed search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchProgress {
+    /// Seeds scanned so far
+    pub scanned: usize,
+
+    /// Seeds to scan in total
+    pub total: usize,
+}
+
+impl SearchProgress {
+    /// Scanned fraction, from 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.scanned as f32 / self.total as f32
+        }
+    }
+}
+
+/// Score of one seed at the searched socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedScore {
+    /// Jewel seed
+    pub seed: u32,
+
+    /// Score at the socket
+    pub score: f64,
+
+    /// Socket breakdown, including the matched mods
+    pub socket: SocketResult,
+}
+
+/// Best seeds found by a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedSearchResult {
+    /// Jewel type searched
+    pub jewel_type: JewelType,
+
+    /// Conqueror the seeds were scored for
+    pub conqueror: String,
+
+    /// Best seeds, highest score first
+    pub results: RankedResultSet<SeedScore>,
+
+    /// Seeds scanned
+    pub scanned: usize,
+
+    /// Whether the search was cancelled before scanning every seed
+    pub cancelled: bool,
+
+    /// Scores of every scanned seed
+    #[serde(default)]
+    pub distribution: ScoreDistribution,
+
+    /// Time spent in each phase, summed over the scanned seeds, if the
+    /// config asked for timings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<AnalysisTimings>,
+}
+
+impl SeedSearchResult {
+    /// The ranked seeds as jewels, best first (e.g., as loadout candidates)
+    pub fn jewels(&self) -> Vec<TimelessJewel> {
+        self.results
+            .iter_from(1)
+            .map(|ranked| seed_jewel(self.jewel_type, ranked.result.seed, &self.conqueror))
+            .collect()
+    }
+}
+
+/// Scores every seed of a jewel type and keeps the best ones
+pub struct SeedSearcher {
+    /// Lookup the seeds are scored with
+    lookup: Arc<dyn TimelessLookup>,
+
+    /// Analyzer scoring one socket
+    analyzer: TimelessJewelAnalyzer,
+
+    /// Number of seeds to keep
+    top_n: usize,
+
+    /// Stops the search early
+    cancel: CancelFlag,
+}
+
+impl SeedSearcher {
+    /// Create a searcher over `lookup`, keeping the 10 best seeds
+    pub fn new(lookup: Arc<dyn TimelessLookup>) -> Self {
+        Self {
+            analyzer: TimelessJewelAnalyzer::new().with_lookup(Arc::clone(&lookup)),
+            lookup,
+            top_n: 10,
+            cancel: CancelFlag::new(),
+        }
+    }
+
+    /// Score seeds at `socket` instead of every node the lookup covers
+    pub fn with_socket(mut self, socket: JewelSocket) -> Self {
+        self.analyzer = self.analyzer.with_sockets(vec![socket]);
+        self
+    }
+
+    /// Keep the `top_n` best seeds
+    pub fn with_top_n(mut self, top_n: usize) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
+    /// Stop when `cancel` is cancelled
+    pub fn with_cancel(mut self, cancel: CancelFlag) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Score every valid seed of `jewel_type`
+    ///
+    /// `progress` is called about a hundred times over the scan. A
+    /// cancelled search returns the best seeds found so far.
+    pub fn search(
+        &self,
+        jewel_type: JewelType,
+        conqueror: &str,
+        config: &TimelessJewelConfig,
+        progress: impl FnMut(SearchProgress),
+    ) -> Result<SeedSearchResult, AnalysisError> {
+        let mut best: Vec<SeedScore> = Vec::new();
+        let mut distribution = DistributionBuilder::default();
+
+        let (scanned, cancelled, timings) =
+            self.scan(&self.analyzer, jewel_type, conqueror, config, progress, |seed, socket| {
+                distribution.add(socket.score);
+                best.push(SeedScore {
+                    seed,
+                    score: socket.score,
+                    socket,
+                });
+                if best.len() >= self.top_n.max(1) * 2 {
+                    self.keep_best(&mut best);
+                }
+            })?;
+
+        let mut results = RankedResultSet::new(best, compare_scores);
+        results.truncate(self.top_n);
+
+        Ok(SeedSearchResult {
+            jewel_type,
+            conqueror: conqueror.to_string(),
+            results,
+            scanned,
+            cancelled,
+            distribution: distribution.finish(),
+            timings,
+        })
+    }
+
+    /// Scores of every valid seed of `jewel_type` at `socket`
+    ///
+    /// `conqueror` picks the keystone and, for jewels with conqueror
+    /// variants, the effect set. A cancelled scan covers the seeds scored
+    /// so far.
+    pub fn compute_distribution(
+        &self,
+        jewel_type: JewelType,
+        conqueror: &str,
+        config: &TimelessJewelConfig,
+        socket: &JewelSocket,
+    ) -> Result<ScoreDistribution, AnalysisError> {
+        let analyzer = TimelessJewelAnalyzer::new()
+            .with_lookup(Arc::clone(&self.lookup))
+            .with_sockets(vec![socket.clone()]);
+
+        let mut distribution = DistributionBuilder::default();
+        self.scan(&analyzer, jewel_type, conqueror, config, |_| {}, |_, socket| {
+            distribution.add(socket.score)
+        })?;
+
+        Ok(distribution.finish())
+    }
+
+    /// Score every valid seed with `analyzer`, passing each seed's best
+    /// socket to `on_seed`
+    ///
+    /// Returns the number of seeds scanned, whether the scan was cancelled
+    /// and the summed timings of the analyses, if the config asked for them.
+    fn scan(
+        &self,
+        analyzer: &TimelessJewelAnalyzer,
+        jewel_type: JewelType,
+        conqueror: &str,
+        config: &TimelessJewelConfig,
+        mut progress: impl FnMut(SearchProgress),
+        mut on_seed: impl FnMut(u32, SocketResult),
+    ) -> Result<(usize, bool, Option<AnalysisTimings>), AnalysisError> {
+        let seeds: Vec<u32> = jewel_type
+            .seed_range()
+            .filter(|seed| jewel_type.is_valid_seed(*seed))
+            .collect();
+        let total = seeds.len();
+        let report_every = (total / PROGRESS_STEPS).max(1);
+        let mut scanned = 0;
+        let mut timings = config.timings.then(AnalysisTimings::default);
+
+        for seed in seeds {
+            if self.cancel.is_cancelled() {
+                return Ok((scanned, true, timings));
+            }
+
+            let jewel = seed_jewel(jewel_type, seed, conqueror);
+            let analysis = analyzer.analyze(&jewel, config)?;
+            if let (Some(timings), Some(analysis_timings)) = (&mut timings, analysis.timings) {
+                *timings += analysis_timings;
+            }
+
+            if let Some(socket) = analysis
+                .metrics
+                .socket_results
+                .into_iter()
+                .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal))
+            {
+                on_seed(seed, socket);
+            }
+
+            scanned += 1;
+            if scanned % report_every == 0 || scanned == total {
+                progress(SearchProgress { scanned, total });
+            }
+        }
+
+        Ok((scanned, false, timings))
+    }
+
+    /// Sort by score and drop all but `top_n`
+    fn keep_best(&self, scores: &mut Vec<SeedScore>) {
+        scores.sort_by(compare_scores);
+        scores.truncate(self.top_n);
+    }
+}
+
+/// Jewel for a searched seed, without item data
+fn seed_jewel(jewel_type: JewelType, seed: u32, conqueror: &str) -> TimelessJewel {
+    TimelessJewel::new(
+        format!("{}:{}:{}", jewel_type.as_str(), seed, conqueror),
+        jewel_type,
+        seed,
+        conqueror.to_string(),
+        serde_json::Value::Null,
+    )
+}
+
+/// Highest score first, lowest seed first on ties
+fn compare_scores(a: &SeedScore, b: &SeedScore) -> Ordering {
+    b.score
+        .partial_cmp(&a.score)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| a.seed.cmp(&b.seed))
+}
@@ -1,8 +1,12 @@
 //! Unit tests for analyzers module
 
 use super::*;
-use crate::items::{JewelType, TimelessJewel};
+use super::distribution::HISTOGRAM_BUCKETS;
+use crate::data::{JewelSocket, PassiveTree, TimelessLookup};
+use crate::error::ExportError;
+use crate::items::{Item, JewelType, KeystoneChange, SocketResult, TimelessJewel};
 use serde_json::Value;
+use std::sync::Arc;
 
 #[test]
 fn test_config_creation() {
@@ -19,6 +23,33 @@ fn test_config_creation() {
 fn test_config_default() {
     let config = TimelessJewelConfig::default();
     assert_eq!(config.valuable_mods().len(), 0);
+    assert!(config.allocated_nodes.is_none());
+}
+
+#[test]
+fn test_config_allocated_nodes() {
+    let config = TimelessJewelConfig::new().with_allocated_nodes([26725, 36634].into());
+
+    let nodes = config.allocated_nodes.unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert!(nodes.contains(&26725));
+}
+
+#[test]
+fn test_config_json_roundtrip() {
+    let mut config = TimelessJewelConfig::new();
+    config.add_mod("Double Damage".to_string(), 5.0);
+    config.add_mod("Onslaught".to_string(), -2.5);
+
+    let json = serde_json::to_string(&config).unwrap();
+    assert!(!json.contains("allocated_nodes"));
+    assert_eq!(serde_json::from_str::<TimelessJewelConfig>(&json).unwrap(), config);
+
+    // Presets written by hand may leave fields out
+    let preset: TimelessJewelConfig =
+        serde_json::from_str(r#"{"valuable_mods": {"Onslaught": 1.0}}"#).unwrap();
+    assert_eq!(preset.valuable_mods().get("Onslaught"), Some(&1.0));
+    assert!(preset.allocated_nodes.is_none());
 }
 
 #[test]
@@ -55,3 +86,1167 @@ fn test_analyzer_placeholder_behavior() {
     assert_eq!(analysis.metrics.socket_results.len(), 0);
     assert_eq!(analysis.best_score, 0.0);
 }
+
+/// Lookup where seed 14032 gives fixed mods to three nodes
+struct FixedLookup;
+
+impl TimelessLookup for FixedLookup {
+    fn node_mods(&self, _jewel_type: JewelType, seed: u32, node_id: u32) -> Option<Vec<String>> {
+        if seed != 14032 {
+            return None;
+        }
+
+        let mods: &[&str] = match node_id {
+            1 => &["Double Damage", "+10 to Strength"],
+            2 => &["Double Damage"],
+            3 => &["Onslaught"],
+            _ => return None,
+        };
+        Some(mods.iter().map(|m| m.to_string()).collect())
+    }
+
+    fn has_seed(&self, _jewel_type: JewelType, seed: u32) -> Option<bool> {
+        Some(seed == 14032)
+    }
+
+    fn nodes(&self) -> Vec<u32> {
+        vec![1, 2, 3]
+    }
+
+    fn tree_version(&self) -> Option<&str> {
+        Some("3_25")
+    }
+}
+
+fn lethal_pride(seed: u32) -> TimelessJewel {
+    TimelessJewel::new(
+        format!("lp-{}", seed),
+        JewelType::LethalPride,
+        seed,
+        "Kaom".to_string(),
+        Value::Null,
+    )
+}
+
+fn weights() -> TimelessJewelConfig {
+    let mut config = TimelessJewelConfig::new();
+    config.add_mod("Double Damage".to_string(), 5.0);
+    config.add_mod("Onslaught".to_string(), -1.0);
+    config
+}
+
+#[test]
+fn test_analyze_with_lookup_scores_sockets() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![
+            JewelSocket::new("a", "Socket A", vec![1, 2]),
+            JewelSocket::new("b", "Socket B", vec![3]),
+        ]);
+
+    let result = analyzer.analyze(&lethal_pride(14032), &weights()).unwrap();
+    let sockets = &result.metrics.socket_results;
+
+    assert_eq!(sockets.len(), 2);
+    assert_eq!(sockets[0].score, 10.0);
+    assert_eq!(sockets[0].matched_mods.len(), 1);
+    assert_eq!(sockets[0].matched_mods[0].mod_text, "Double Damage");
+    assert_eq!(sockets[0].matched_mods[0].count, 2);
+    assert_eq!(sockets[0].all_mods.len(), 3);
+
+    // Negative weights lower the score
+    assert_eq!(sockets[1].score, -1.0);
+
+    assert_eq!(result.best_score, 10.0);
+    assert_eq!(result.best_socket_id, "a");
+}
+
+#[test]
+fn test_best_score_of_negative_sockets() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![
+            JewelSocket::new("a", "Socket A", vec![1, 2]),
+            JewelSocket::new("b", "Socket B", vec![3]),
+        ]);
+    let mut config = TimelessJewelConfig::new();
+    config.add_mod("Double Damage".to_string(), -2.0);
+    config.add_mod("Onslaught".to_string(), -1.0);
+
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+
+    // The least bad socket is the best one, and its score is the best score
+    assert_eq!(result.best_socket_id, "b");
+    assert_eq!(result.best_score, -1.0);
+}
+
+#[test]
+fn test_analyze_without_sockets_uses_all_nodes() {
+    let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(FixedLookup));
+
+    let result = analyzer.analyze(&lethal_pride(14032), &weights()).unwrap();
+    let sockets = &result.metrics.socket_results;
+
+    assert_eq!(sockets.len(), 1);
+    assert_eq!(sockets[0].socket_id, "all");
+    assert_eq!(sockets[0].score, 9.0);
+}
+
+#[test]
+fn test_analyze_respects_allocated_nodes() {
+    let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(FixedLookup));
+    let config = weights().with_allocated_nodes([2].into());
+
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    let socket = &result.metrics.socket_results[0];
+
+    assert_eq!(socket.score, 5.0);
+    assert_eq!(socket.all_mods, vec!["Double Damage".to_string()]);
+}
+
+#[test]
+fn test_analyze_weights_per_value() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![JewelSocket::new("a", "Socket A", vec![1, 2, 3])]);
+    let mut config = weights();
+    config.add_mod_per_value("+# to Strength".to_string(), 0.5);
+
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    let socket = &result.metrics.socket_results[0];
+
+    // 2 × Double Damage (5) - Onslaught (1) + 10 Strength × 0.5
+    assert_eq!(socket.score, 14.0);
+    let strength = socket
+        .matched_mods
+        .iter()
+        .find(|m| m.mod_text == "+10 to Strength")
+        .unwrap();
+    assert_eq!((strength.weight, strength.value, strength.count), (0.5, Some(10.0), 1));
+    assert_eq!(strength.total(), 5.0);
+
+    // Saved configs keep the flag
+    let json = serde_json::to_string(&config).unwrap();
+    let loaded: TimelessJewelConfig = serde_json::from_str(&json).unwrap();
+    assert!(loaded.per_value_mods.contains("+# to Strength"));
+
+    // A rolled example is stored as its stat template and matches any roll
+    let mut config = weights();
+    config.add_mod_per_value("+25 to Strength".to_string(), 0.5);
+    assert!(config.per_value_mods.contains("+# to Strength"));
+    assert_eq!(config.valuable_mods().get("+# to Strength"), Some(&0.5));
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    assert_eq!(result.metrics.socket_results[0].score, 14.0);
+}
+
+#[test]
+fn test_overlapping_entries_count_once() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![JewelSocket::new("a", "Socket A", vec![1, 2, 3])]);
+    let mut config = weights();
+    config.add_mod_per_value("+# to Strength".to_string(), 0.5);
+    config.add_mod("+10 to Strength".to_string(), 3.0);
+    let strength = |socket: &SocketResult| {
+        let matched = socket.matched_mods.iter().filter(|m| m.mod_text == "+10 to Strength");
+        matched.map(|m| (m.matched_pattern.clone().unwrap(), m.total())).collect::<Vec<_>>()
+    };
+
+    // The exact entry claims the mod: 2 × Double Damage (5) - Onslaught (1) + 3
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    let socket = &result.metrics.socket_results[0];
+    assert_eq!(socket.score, 12.0);
+    assert_eq!(strength(socket), [("+10 to Strength".to_string(), 3.0)]);
+    assert_eq!(socket.matched_mods[0].matched_pattern.as_deref(), Some("Double Damage"));
+
+    // Opting into multi-count also counts it per value (10 × 0.5)
+    let config = config.with_multi_count(true);
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    let socket = &result.metrics.socket_results[0];
+    assert_eq!(socket.score, 17.0);
+    assert_eq!(
+        strength(socket),
+        [("+# to Strength".to_string(), 5.0), ("+10 to Strength".to_string(), 3.0)]
+    );
+}
+
+#[test]
+fn test_summary_takes_the_most_of_each_mod() {
+    // The sockets share nodes, so every mod is in more than one of them
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![
+            JewelSocket::new("a", "Socket A", vec![1, 2]),
+            JewelSocket::new("b", "Socket B", vec![2, 3]),
+            JewelSocket::new("c", "Socket C", vec![1, 3]),
+        ]);
+    let mut config = weights();
+    config.add_mod_per_value("+# to Strength".to_string(), 0.5);
+
+    let rows = |config: &TimelessJewelConfig| {
+        let result = analyzer.analyze(&lethal_pride(14032), config).unwrap();
+        let summary = result.metrics.summary();
+        let rows: Vec<(String, String, usize, f64)> = summary
+            .mods
+            .into_iter()
+            .map(|m| (m.mod_text, m.socket_id, m.count, m.contribution))
+            .collect();
+        rows
+    };
+    let row = |mod_text: &str, socket_id: &str, count: usize, contribution: f64| {
+        (mod_text.to_string(), socket_id.to_string(), count, contribution)
+    };
+
+    // A scores 15, C 9 and B 4; Onslaught is in B and C, so the better C wins
+    assert_eq!(
+        rows(&config),
+        vec![
+            row("Double Damage", "a", 2, 10.0),
+            row("+10 to Strength", "a", 1, 5.0),
+            row("Onslaught", "c", 1, -1.0),
+        ]
+    );
+
+    // C (30) now outscores A (15), but A still has the most Double Damage;
+    // only the tie on Strength goes to C
+    config.add_mod("Onslaught".to_string(), 20.0);
+    assert_eq!(
+        rows(&config),
+        vec![
+            row("Onslaught", "c", 1, 20.0),
+            row("Double Damage", "a", 2, 10.0),
+            row("+10 to Strength", "c", 1, 5.0),
+        ]
+    );
+
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    let strength = result.metrics.summary().mods.pop().unwrap();
+    assert_eq!(strength.matched_pattern.as_deref(), Some("+# to Strength"));
+
+    let empty = TimelessJewelAnalyzer::new().analyze(&lethal_pride(14032), &config).unwrap();
+    assert_eq!(empty.metrics.summary(), Default::default());
+}
+
+#[test]
+fn test_analyze_reports_keystone_change() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![
+            JewelSocket::new("a", "Socket A", vec![1, 2]).with_keystone(31961, "Resolute Technique"),
+            JewelSocket::new("b", "Socket B", vec![3]),
+        ]);
+    let mut config = weights();
+    config.add_keystone("Strength of Blood".to_string(), 3.0);
+
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    let sockets = &result.metrics.socket_results;
+
+    assert_eq!(
+        sockets[0].keystone_change,
+        Some(KeystoneChange {
+            original: "Resolute Technique".to_string(),
+            replacement: "Strength of Blood".to_string(),
+            weight: 3.0,
+        })
+    );
+    assert_eq!(sockets[0].score, 13.0);
+    assert_eq!(sockets[1].keystone_change, None);
+    assert_eq!(sockets[1].score, -1.0);
+
+    // An unallocated keystone isn't replaced
+    let config = config.with_allocated_nodes([1, 2].into());
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    assert_eq!(result.metrics.socket_results[0].keystone_change, None);
+    assert_eq!(result.metrics.socket_results[0].score, 10.0);
+}
+
+fn two_socket_analyzer() -> TimelessJewelAnalyzer {
+    TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![
+            JewelSocket::new("a", "Socket A", vec![1, 2, 3]),
+            JewelSocket::new("b", "Socket B", vec![1, 2, 3]),
+        ])
+}
+
+#[test]
+fn test_socket_override_changes_only_that_socket() {
+    let config = weights()
+        .with_socket_override("a", SocketConfig::new().with_allocated_nodes([2, 3].into()));
+
+    let result = two_socket_analyzer().analyze(&lethal_pride(14032), &config).unwrap();
+    let sockets = &result.metrics.socket_results;
+
+    assert_eq!(sockets[0].score, 4.0);
+    assert_eq!(sockets[1].score, 9.0);
+
+    // Bonus weights add to the config's at that socket only
+    let mut socket_config = SocketConfig::new();
+    socket_config.add_bonus("Onslaught".to_string(), 3.0);
+    socket_config.add_bonus("+10 to Strength".to_string(), 1.0);
+    let config = weights().with_socket_override("b", socket_config);
+
+    let result = two_socket_analyzer().analyze(&lethal_pride(14032), &config).unwrap();
+    let sockets = &result.metrics.socket_results;
+
+    assert_eq!(sockets[0].score, 9.0);
+    assert_eq!(sockets[1].score, 13.0);
+    assert_eq!(result.best_socket_id, "b");
+
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(serde_json::from_str::<TimelessJewelConfig>(&json).unwrap(), config);
+}
+
+#[test]
+fn test_analyze_sockets_filter() {
+    let analyzer = two_socket_analyzer();
+    let jewel = lethal_pride(14032);
+
+    let result = analyzer.analyze_sockets(&jewel, &weights(), Some(&["b".to_string()])).unwrap();
+    assert_eq!(result.metrics.socket_results.len(), 1);
+    assert_eq!(result.best_socket_id, "b");
+
+    let result = analyzer.analyze_sockets(&jewel, &weights(), None).unwrap();
+    assert_eq!(result.metrics.socket_results.len(), 2);
+}
+
+#[test]
+fn test_timings_only_when_enabled() {
+    let analyzer = two_socket_analyzer();
+    let jewel = lethal_pride(14032);
+
+    let result = analyzer.analyze(&jewel, &weights()).unwrap();
+    assert_eq!(result.timings, None);
+    assert!(!serde_json::to_string(&result).unwrap().contains("timings"));
+    assert!(!serde_json::to_string(&weights()).unwrap().contains("timings"));
+
+    let config = weights().with_timings(true);
+    let result = analyzer.analyze(&jewel, &config).unwrap();
+    let timings = result.timings.unwrap();
+    assert!(timings.total > std::time::Duration::ZERO);
+    assert!(timings.phase_total() <= timings.total);
+    for (name, duration) in timings.phases() {
+        assert!(duration <= timings.total, "{}", name);
+    }
+
+    // Timing doesn't change the scores
+    let untimed = analyzer.analyze(&jewel, &weights()).unwrap();
+    assert_eq!(result.best_score, untimed.best_score);
+
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(serde_json::from_str::<TimelessJewelConfig>(&json).unwrap(), config);
+}
+
+/// Lookup where seed s gives node 1 "Double Damage" when s % 1000 == 0
+/// and "Onslaught" otherwise
+struct SeedDependentLookup;
+
+impl TimelessLookup for SeedDependentLookup {
+    fn node_mods(&self, _jewel_type: JewelType, seed: u32, node_id: u32) -> Option<Vec<String>> {
+        match (node_id, seed % 1000) {
+            (1, 0) => Some(vec!["Double Damage".to_string()]),
+            (1, _) => Some(vec!["Onslaught".to_string()]),
+            _ => None,
+        }
+    }
+
+    fn nodes(&self) -> Vec<u32> {
+        vec![1]
+    }
+}
+
+#[test]
+fn test_seed_search_sums_timings() {
+    let searcher = SeedSearcher::new(Arc::new(SeedDependentLookup));
+
+    let result = searcher.search(JewelType::LethalPride, "Kaom", &weights(), |_| {}).unwrap();
+    assert_eq!(result.timings, None);
+
+    let config = weights().with_timings(true);
+    let result = searcher.search(JewelType::LethalPride, "Kaom", &config, |_| {}).unwrap();
+    let timings = result.timings.unwrap();
+
+    // Over thousands of seeds, the phases account for most of the time
+    assert!(timings.lut_lookup > std::time::Duration::ZERO);
+    assert!(timings.matching > std::time::Duration::ZERO);
+    assert!(timings.scoring > std::time::Duration::ZERO);
+    assert!(timings.phase_total() <= timings.total);
+    assert!(timings.phase_total() >= timings.total / 2, "{}", timings);
+}
+
+#[test]
+fn test_seed_search_keeps_top_seeds() {
+    let mut reports = Vec::new();
+    let result = SeedSearcher::new(Arc::new(SeedDependentLookup))
+        .with_top_n(3)
+        .search(JewelType::LethalPride, "Kaom", &weights(), |p| reports.push(p))
+        .unwrap();
+
+    assert_eq!(result.scanned, 8001);
+    assert!(!result.cancelled);
+
+    let seeds: Vec<u32> = result.results.iter().map(|r| r.seed).collect();
+    assert_eq!(seeds, vec![10000, 11000, 12000]);
+    assert_eq!(result.results[0].score, 5.0);
+    assert_eq!(result.results[0].socket.matched_mods[0].mod_text, "Double Damage");
+
+    let jewels = result.jewels();
+    assert_eq!(jewels.len(), 3);
+    assert_eq!(jewels[0].id(), "Lethal Pride:10000:Kaom");
+    assert_eq!(jewels[2].seed, 12000);
+
+    // Progress is throttled, and the last report covers every seed
+    assert!(reports.len() <= 101);
+    assert_eq!(reports.last().unwrap().fraction(), 1.0);
+}
+
+#[test]
+fn test_seed_search_skips_invalid_elegant_hubris_seeds() {
+    let result = SeedSearcher::new(Arc::new(SeedDependentLookup))
+        .search(JewelType::ElegantHubris, "Cadiro", &weights(), |_| {})
+        .unwrap();
+
+    assert_eq!(result.scanned, (160000 - 2000) / 20 + 1);
+    assert_eq!(result.results.len(), 10);
+}
+
+#[test]
+fn test_seed_search_cancel() {
+    let cancel = CancelFlag::new();
+    let searcher = SeedSearcher::new(Arc::new(SeedDependentLookup))
+        .with_socket(JewelSocket::new("a", "Socket A", vec![1]))
+        .with_cancel(cancel.clone());
+
+    let result = searcher
+        .search(JewelType::LethalPride, "Kaom", &weights(), |p| {
+            if p.scanned >= 800 {
+                cancel.cancel();
+            }
+        })
+        .unwrap();
+
+    assert!(result.cancelled);
+    assert!(result.scanned < 8001);
+    assert_eq!(result.results[0].seed, 10000);
+    assert_eq!(result.results[0].socket.socket_id, "a");
+}
+
+#[test]
+fn test_seed_search_collects_distribution() {
+    let result = SeedSearcher::new(Arc::new(SeedDependentLookup))
+        .with_top_n(1)
+        .search(JewelType::LethalPride, "Kaom", &weights(), |_| {})
+        .unwrap();
+
+    // Every scanned seed counts, not just the kept ones
+    assert_eq!(result.distribution.count, 8001);
+    assert_eq!(result.distribution.max, 5.0);
+}
+
+#[test]
+fn test_compute_distribution() {
+    let socket = JewelSocket::new("a", "Socket A", vec![1]);
+    let distribution = SeedSearcher::new(Arc::new(SeedDependentLookup))
+        .compute_distribution(JewelType::LethalPride, "Kaom", &weights(), &socket)
+        .unwrap();
+
+    // 9 seeds with Double Damage (5.0), 7992 with Onslaught (-1.0)
+    assert_eq!(distribution.count, 8001);
+    assert_eq!(distribution.min, -1.0);
+    assert_eq!(distribution.max, 5.0);
+    assert!((distribution.mean - (9.0 * 5.0 - 7992.0) / 8001.0).abs() < 1e-9);
+
+    assert_eq!(distribution.histogram.len(), HISTOGRAM_BUCKETS);
+    assert_eq!(distribution.histogram[0].count, 7992);
+    assert_eq!(distribution.histogram[HISTOGRAM_BUCKETS - 1].count, 9);
+    assert_eq!(distribution.histogram[HISTOGRAM_BUCKETS - 1].max, 5.0);
+    let counted: usize = distribution.histogram.iter().map(|b| b.count).sum();
+    assert_eq!(counted, 8001);
+
+    assert!((distribution.percentile_of(5.0) - 7992.0 * 100.0 / 8001.0).abs() < 1e-9);
+    assert_eq!(distribution.percentile_of(-1.0), 0.0);
+    assert_eq!(distribution.percentile_of(6.0), 100.0);
+}
+
+#[test]
+fn test_distribution_percentiles() {
+    let distribution = ScoreDistribution::from_scores((1..=100).map(f64::from));
+
+    assert_eq!(distribution.score_at(50.0), 50.0);
+    assert_eq!(distribution.score_at(0.0), 1.0);
+    assert_eq!(distribution.score_at(100.0), 100.0);
+    let reported: Vec<(f64, f64)> =
+        distribution.percentiles.iter().map(|p| (p.percentile, p.score)).collect();
+    assert_eq!(
+        reported,
+        vec![(25.0, 25.0), (50.0, 50.0), (75.0, 75.0), (90.0, 90.0), (99.0, 99.0), (99.9, 100.0)]
+    );
+    assert_eq!(distribution.percentile_of(98.0), 97.0);
+
+    // 1..=100 over 20 buckets of width 4.95
+    let counts: Vec<usize> = distribution.histogram.iter().map(|b| b.count).collect();
+    assert_eq!(counts, vec![5; 20]);
+
+    let json = serde_json::to_string(&distribution).unwrap();
+    let restored: ScoreDistribution = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.percentiles, distribution.percentiles);
+    assert_eq!(restored.histogram.len(), 20);
+    assert_eq!(restored.percentile_of(98.0), 97.0);
+}
+
+#[test]
+fn test_distribution_of_equal_scores() {
+    let distribution = ScoreDistribution::from_scores([2.0; 4]);
+
+    assert_eq!(distribution.histogram.len(), 1);
+    assert_eq!(distribution.histogram[0].count, 4);
+    assert_eq!(distribution.percentile_of(2.0), 0.0);
+    assert_eq!(ScoreDistribution::from_scores([]).count, 0);
+}
+
+fn profiles() -> Vec<(String, TimelessJewelConfig)> {
+    let mut offense = TimelessJewelConfig::new();
+    offense.add_mod("Double Damage".to_string(), 5.0);
+    let mut defense = TimelessJewelConfig::new();
+    defense.add_mod("Onslaught".to_string(), 2.0);
+
+    vec![("offense".to_string(), offense), ("defense".to_string(), defense)]
+}
+
+#[test]
+fn test_analyze_multi_keeps_profiles_independent() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(SeedDependentLookup))
+        .with_sockets(vec![
+            JewelSocket::new("s1", "Socket 1", vec![1]),
+            JewelSocket::new("s2", "Socket 2", vec![2]),
+        ]);
+
+    let result = analyzer.analyze_multi(&lethal_pride(10000), &profiles()).unwrap();
+
+    // Double Damage only counts under offense
+    assert_eq!(result.profile("offense").unwrap().best_score, 5.0);
+    let defense = result.profile("defense").unwrap();
+    assert_eq!(defense.best_score, 0.0);
+    assert!(defense.metrics.socket_results[0].matched_mods.is_empty());
+    assert!(result.profile("budget").is_none());
+
+    let table = result.table();
+    assert_eq!(table.columns, vec!["offense", "defense"]);
+    assert_eq!(table.best_sockets[0], "s1");
+    assert_eq!(table.rows.len(), 2);
+    assert_eq!(table.rows[0].socket_id, "s1");
+    assert_eq!(table.rows[0].scores, vec![5.0, 0.0]);
+    assert_eq!(table.rows[1].scores, vec![0.0, 0.0]);
+}
+
+#[test]
+fn test_analyze_batch_multi_ranking() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(SeedDependentLookup))
+        .with_sockets(vec![JewelSocket::new("s1", "Socket 1", vec![1])]);
+    let items = vec![lethal_pride(10001), lethal_pride(10000)];
+    let seeds = |ranking: ProfileRanking| -> Vec<u32> {
+        analyzer
+            .analyze_batch_multi(&items, &profiles(), &ranking)
+            .unwrap()
+            .iter()
+            .map(|ranked| ranked.result.jewel.seed)
+            .collect()
+    };
+
+    assert_eq!(seeds(ProfileRanking::Profile("offense".to_string())), vec![10000, 10001]);
+    assert_eq!(seeds(ProfileRanking::Profile("defense".to_string())), vec![10001, 10000]);
+    // 5.0 under offense beats 2.0 under defense
+    assert_eq!(seeds(ProfileRanking::Max), vec![10000, 10001]);
+
+    let error = analyzer
+        .analyze_batch_multi(&items, &profiles(), &ProfileRanking::Profile("budget".to_string()))
+        .unwrap_err();
+    assert!(error.to_string().contains("budget"));
+}
+
+#[test]
+fn test_jewel_comparison() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(SeedDependentLookup))
+        .with_sockets(vec![JewelSocket::new("s1", "Socket 1", vec![1])]);
+
+    let a = analyzer.analyze(&lethal_pride(10000), &weights()).unwrap();
+    let b = analyzer.analyze(&lethal_pride(10001), &weights()).unwrap();
+    let comparison = JewelComparison::new(&a, &b).unwrap();
+
+    let socket = comparison.socket("s1").unwrap();
+    assert_eq!(socket.score_delta(), socket.score_b - socket.score_a);
+    assert!(socket.score_a > socket.score_b);
+    assert_eq!(socket.shared().count(), 0);
+    assert_eq!(
+        socket.only_a().map(|m| m.mod_text.as_str()).collect::<Vec<_>>(),
+        vec!["Double Damage"]
+    );
+    assert_eq!(
+        socket.only_b().map(|m| m.mod_text.as_str()).collect::<Vec<_>>(),
+        vec!["Onslaught"]
+    );
+
+    // Comparing a jewel with itself only has shared mods
+    let same = JewelComparison::new(&a, &a).unwrap();
+    assert_eq!(same.sockets[0].shared().count(), 1);
+    assert_eq!(same.sockets[0].score_delta(), 0.0);
+}
+
+#[test]
+fn test_jewel_comparison_rejects_mixed_types() {
+    let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(SeedDependentLookup));
+    let a = analyzer.analyze(&lethal_pride(10000), &weights()).unwrap();
+
+    let mut jewel = lethal_pride(10000);
+    jewel.jewel_type = JewelType::BrutalRestraint;
+    let b = analyzer.analyze(&jewel, &weights()).unwrap();
+
+    assert!(JewelComparison::new(&a, &b).is_err());
+}
+
+#[test]
+fn test_batch_owned_flags_dominated_candidates() {
+    let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(SeedDependentLookup));
+    let brutal_restraint = TimelessJewel::new(
+        "br-1000".to_string(),
+        JewelType::BrutalRestraint,
+        1000,
+        "Asenath".to_string(),
+        Value::Null,
+    );
+    let items = vec![
+        BatchItem::candidate(lethal_pride(11000)),
+        BatchItem::candidate(lethal_pride(10001)),
+        BatchItem::owned(lethal_pride(10000)),
+        BatchItem::candidate(brutal_restraint),
+    ];
+
+    let ranked = analyzer.analyze_batch_owned(&items, &weights()).unwrap();
+    let dominated_by = |id: &str| {
+        let ranked = ranked.iter().find(|r| r.result.result.jewel.id() == id).unwrap();
+        ranked.result.dominated_by.clone()
+    };
+
+    assert_eq!(ranked.len(), 4);
+    // Same type, socket, score and mods as the owned jewel
+    assert_eq!(dominated_by("lp-11000"), Some("lp-10000".to_string()));
+    // Matches a mod the owned jewel doesn't have
+    assert_eq!(dominated_by("lp-10001"), None);
+    // Another jewel type
+    assert_eq!(dominated_by("br-1000"), None);
+    assert_eq!(dominated_by("lp-10000"), None);
+    assert!(ranked.iter().any(|r| r.result.owned));
+}
+
+fn export_fixture() -> ResultExportV1 {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![JewelSocket::new("a", "Socket A", vec![1, 2, 3])]);
+    let result = analyzer.analyze(&lethal_pride(14032), &weights()).unwrap();
+
+    ResultExportV1::from_result(&result, Some("abc123".to_string()))
+}
+
+#[test]
+fn test_export_v1_field_names() {
+    // Renaming any of these breaks consumers; add a schema version instead
+    let expected = serde_json::json!({
+        "schema_version": 1,
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "lut_version": "abc123",
+        "results": [{
+            "rank": 1,
+            "jewel": {
+                "id": "lp-14032",
+                "jewel_type": "Lethal Pride",
+                "seed": 14032,
+                "conqueror": "Kaom"
+            },
+            "best_score": 9.0,
+            "best_socket_id": "a",
+            "sockets": [{
+                "socket_id": "a",
+                "socket_name": "Socket A",
+                "score": 9.0,
+                "matched_mods": [
+                    {
+                        "mod_text": "Double Damage",
+                        "weight": 5.0,
+                        "count": 2,
+                        "trade_stat_id": null
+                    },
+                    {
+                        "mod_text": "Onslaught",
+                        "weight": -1.0,
+                        "count": 1,
+                        "trade_stat_id": null
+                    }
+                ],
+                "keystone": null
+            }]
+        }]
+    });
+
+    assert_eq!(serde_json::to_value(export_fixture()).unwrap(), expected);
+}
+
+#[test]
+fn test_export_from_ranked_keeps_ranks() {
+    let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(FixedLookup));
+    let ranked = analyzer
+        .analyze_batch(&[lethal_pride(10000), lethal_pride(14032)], &weights())
+        .unwrap();
+
+    let export = ResultExportV1::from_ranked(&ranked, None);
+
+    let ranks: Vec<_> = export.results.iter().map(|r| (r.rank, r.jewel.seed)).collect();
+    assert_eq!(ranks, vec![(1, 14032), (2, 10000)]);
+    assert_eq!(export.lut_version, None);
+}
+
+#[test]
+fn test_export_from_json() {
+    let json = serde_json::to_string(&export_fixture()).unwrap();
+    assert_eq!(ResultExportV1::from_json(&json).unwrap(), export_fixture());
+
+    let mut future = serde_json::to_value(export_fixture()).unwrap();
+    future["schema_version"] = 2.into();
+    assert!(matches!(
+        ResultExportV1::from_json(&future.to_string()),
+        Err(ExportError::UnsupportedVersion(2))
+    ));
+
+    assert!(matches!(
+        ResultExportV1::from_json(r#"{"results": []}"#),
+        Err(ExportError::MissingVersion)
+    ));
+    assert!(matches!(
+        ResultExportV1::from_json("not json"),
+        Err(ExportError::Invalid(_))
+    ));
+}
+
+#[test]
+fn test_analysis_report_tables() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![JewelSocket::new("a", "Socket A", vec![1, 2, 3])]);
+    let result = analyzer.analyze(&lethal_pride(14032), &weights()).unwrap();
+
+    let html = render_analysis_report(&result, Some("abc123"));
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h1>Lethal Pride 14032 (Kaom)</h1>"));
+    assert!(html.contains("<tr><td>Best score</td><td>9.00</td></tr>"));
+    assert!(html.contains("<h2>Socket A &mdash; 9.00</h2>"));
+    assert!(html.contains(
+        "<tr><td>Double Damage</td><td class=\"num\">2</td><td class=\"num\">5</td>\
+         <td class=\"num\">10</td></tr>"
+    ));
+    assert!(html.contains(
+        "<tr><td>Onslaught</td><td class=\"num\">1</td><td class=\"num\">-1</td>\
+         <td class=\"num\">-1</td></tr>"
+    ));
+    assert!(html.contains("LUT version abc123"));
+    assert!(html.contains(env!("CARGO_PKG_VERSION")));
+    // Self-contained
+    assert!(!html.contains("<script") && !html.contains("<link"));
+}
+
+#[test]
+fn test_report_escapes_mod_text() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![JewelSocket::new("a", "Socket <A>", vec![1, 2, 3])]);
+    let mut result = analyzer.analyze(&lethal_pride(14032), &weights()).unwrap();
+    result.metrics.socket_results[0].matched_mods[0].mod_text =
+        "<script>alert(\"x\")</script> & more".to_string();
+
+    let html = render_analysis_report(&result, None);
+
+    assert!(!html.contains("<script>"));
+    assert!(html.contains("&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; more"));
+    assert!(html.contains("<h2>Socket &lt;A&gt; &mdash; 9.00</h2>"));
+    assert!(html.contains("LUT version unknown"));
+    assert_eq!(escape_html("a < b > 'c'"), "a &lt; b &gt; &#39;c&#39;");
+}
+
+#[test]
+fn test_search_report_ranks_seeds() {
+    let result = SeedSearcher::new(Arc::new(SeedDependentLookup))
+        .with_top_n(2)
+        .search(JewelType::LethalPride, "Kaom", &weights(), |_| {})
+        .unwrap();
+
+    let html = render_search_report(&result, Some("abc123"));
+
+    assert!(html.contains("<h1>Lethal Pride seeds for Kaom</h1>"));
+    assert!(html.contains("<tr><td>Seeds scanned</td><td>8001</td></tr>"));
+    assert!(html.contains(
+        "<tr><td class=\"num\">1</td><td class=\"num\">10000</td>\
+         <td class=\"num\">5.00</td>"
+    ));
+    assert!(html.contains("<h2>#2 seed 11000 &mdash; 5.00</h2>"));
+    assert!(html.contains("<tr><td>Double Damage</td><td class=\"num\">1</td>"));
+}
+
+/// Tree where class start 10 leads to 1, 2 and 3 in a line and to 20; 30
+/// is an ascendancy shortcut from 10 to 3
+fn path_tree() -> PassiveTree {
+    PassiveTree::from_json(
+        r#"{
+            "groups": {"1": {"x": 0, "y": 0}},
+            "nodes": {
+                "10": {"skill": 10, "group": 1, "classStartIndex": 3, "out": ["1", "20"]},
+                "1": {"skill": 1, "group": 1, "out": ["2"]},
+                "2": {"skill": 2, "group": 1, "in": ["1"], "out": [3]},
+                "3": {"skill": 3, "group": 1},
+                "20": {"skill": 20, "group": 1},
+                "30": {"skill": 30, "group": 1, "ascendancyName": "Elementalist",
+                       "out": ["10", "3"]}
+            }
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_path_distances() {
+    let tree = path_tree();
+    assert_eq!(tree.class_start(3), Some(10));
+    assert_eq!(tree.neighbours(10), &[1, 20, 30]);
+
+    let paths = tree.distances_from([10]);
+    let distances: Vec<_> = [10, 1, 2, 3, 20, 30].map(|id| paths.distance(id)).into();
+    assert_eq!(distances, vec![Some(0), Some(1), Some(2), Some(3), Some(1), None]);
+
+    // Shared path nodes count once
+    assert_eq!(paths.path_cost([2, 3]), 3);
+    assert_eq!(paths.path_cost([1, 20]), 2);
+    assert_eq!(paths.path_cost([10, 30]), 0);
+
+    // Several starting nodes: the nearest one counts
+    assert_eq!(tree.distances_from([10, 3]).distance(2), Some(1));
+}
+
+#[test]
+fn test_travel_costs_adjust_score() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(FixedLookup))
+        .with_sockets(vec![JewelSocket::new("a", "Socket A", vec![1, 2, 3])])
+        .with_passive_tree(Arc::new(path_tree()));
+    let config = weights().with_path_origin([10].into());
+
+    let result = analyzer.analyze(&lethal_pride(14032), &config).unwrap();
+    let socket = &result.metrics.socket_results[0];
+
+    let contributions: Vec<_> = socket
+        .node_contributions
+        .iter()
+        .map(|c| (c.node_id, c.score, c.travel_cost))
+        .collect();
+    assert_eq!(
+        contributions,
+        vec![(1, 5.0, Some(1)), (2, 5.0, Some(2)), (3, -1.0, Some(3))]
+    );
+    // Node 3 only lowers the score, so it isn't travelled to
+    assert_eq!(socket.travel_cost, Some(2));
+    assert_eq!(socket.score, 9.0);
+
+    let result = analyzer
+        .analyze(&lethal_pride(14032), &config.with_point_cost(1.5))
+        .unwrap();
+    assert_eq!(result.metrics.socket_results[0].score, 6.0);
+
+    // Without the tree nothing changes
+    let plain = two_socket_analyzer().analyze(&lethal_pride(14032), &weights()).unwrap();
+    assert!(plain.metrics.socket_results[0].node_contributions.is_empty());
+    assert_eq!(plain.metrics.socket_results[0].travel_cost, None);
+}
+
+#[test]
+fn test_clean_analysis_has_no_warnings() {
+    let result = two_socket_analyzer().analyze(&lethal_pride(14032), &weights()).unwrap();
+    assert!(result.warnings.is_empty());
+    assert!(!serde_json::to_string(&result).unwrap().contains("warnings"));
+}
+
+#[test]
+fn test_warning_seed_not_in_lut() {
+    let result = two_socket_analyzer().analyze(&lethal_pride(10000), &weights()).unwrap();
+    assert!(result.warnings.contains(&AnalysisWarning::SeedNotInLut { seed: 10000 }));
+
+    // Out of the type's range, even when the lookup can't tell
+    let result = TimelessJewelAnalyzer::new().analyze(&lethal_pride(99), &weights()).unwrap();
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.warnings.to_string(), "seed 99 is not in the loaded data");
+}
+
+#[test]
+fn test_warning_empty_config() {
+    let result = two_socket_analyzer()
+        .analyze(&lethal_pride(14032), &TimelessJewelConfig::new())
+        .unwrap();
+    assert!(result.warnings.contains(&AnalysisWarning::EmptyConfig));
+
+    let mut config = TimelessJewelConfig::new();
+    config.add_mod("Double Damage".to_string(), 0.0);
+    config.add_keystone("Strength of Blood".to_string(), 0.0);
+    let result = two_socket_analyzer().analyze(&lethal_pride(14032), &config).unwrap();
+    assert!(result.warnings.contains(&AnalysisWarning::EmptyConfig));
+
+    // A keystone weight alone is enough
+    config.add_keystone("Strength of Blood".to_string(), 3.0);
+    let result = two_socket_analyzer().analyze(&lethal_pride(14032), &config).unwrap();
+    assert!(result.warnings.is_empty());
+}
+
+#[test]
+fn test_warning_invalid_conqueror() {
+    let jewel = TimelessJewel::new(
+        "lp-doryani".to_string(),
+        JewelType::LethalPride,
+        14032,
+        "Doryani".to_string(),
+        Value::Null,
+    );
+    let result = two_socket_analyzer().analyze(&jewel, &weights()).unwrap();
+
+    let expected = AnalysisWarning::InvalidConqueror { conqueror: "Doryani".to_string() };
+    assert_eq!(result.warnings.iter().collect::<Vec<_>>(), vec![&expected]);
+}
+
+#[test]
+fn test_warning_no_sockets_matched() {
+    let analyzer = two_socket_analyzer();
+    let jewel = lethal_pride(14032);
+
+    let missing = ["c".to_string()];
+    let result = analyzer.analyze_sockets(&jewel, &weights(), Some(&missing)).unwrap();
+    assert!(result.warnings.contains(&AnalysisWarning::NoSocketsMatched));
+
+    let existing = ["a".to_string()];
+    let result = analyzer.analyze_sockets(&jewel, &weights(), Some(&existing)).unwrap();
+    assert!(result.warnings.is_empty());
+}
+
+#[test]
+fn test_warning_tree_version_mismatch() {
+    let analyzer = two_socket_analyzer();
+    let jewel = lethal_pride(14032);
+
+    let older = weights().with_tree_version("3.24.0");
+    let result = analyzer.analyze(&jewel, &older).unwrap();
+    assert!(result.warnings.contains(&AnalysisWarning::TreeVersionMismatch {
+        data: "3_25".to_string(),
+        character: "3.24.0".to_string(),
+    }));
+
+    // Same patch in another notation
+    let current = weights().with_tree_version("3.25.1");
+    let result = analyzer.analyze(&jewel, &current).unwrap();
+    assert!(result.warnings.is_empty());
+}
+
+#[test]
+fn test_ranked_result_set_pages() {
+    let set = RankedResultSet::new((1..=7).collect(), |a: &u32, b: &u32| b.cmp(a));
+
+    assert_eq!(set.total_count(), 7);
+    assert_eq!(set.page_count(3), 3);
+    assert_eq!(set.page(0, 3), &[7, 6, 5]);
+    assert_eq!(set.page(1, 3), &[4, 3, 2]);
+
+    // The final page is partial, pages past it are empty
+    assert_eq!(set.page(2, 3), &[1]);
+    assert!(set.page(3, 3).is_empty());
+    assert!(set.page(usize::MAX, 3).is_empty());
+    assert!(set.page(0, 0).is_empty());
+    assert_eq!(set.page(0, 100), &[7, 6, 5, 4, 3, 2, 1]);
+}
+
+#[test]
+fn test_ranked_result_set_iter_from() {
+    let set = RankedResultSet::new(vec!["b", "c", "a"], |a, b| a.cmp(b));
+
+    let ranked: Vec<(usize, &str)> =
+        set.iter_from(2).map(|ranked| (ranked.rank, *ranked.result)).collect();
+    assert_eq!(ranked, vec![(2, "b"), (3, "c")]);
+    assert_eq!(set.iter_from(0).count(), 3);
+    assert_eq!(set.iter_from(4).count(), 0);
+
+    // Serializes as the sorted list
+    assert_eq!(serde_json::to_string(&set).unwrap(), r#"["a","b","c"]"#);
+}
+
+/// Scores per (jewel, socket), where placing each jewel at its best socket
+/// is not the best combination:
+///
+/// | jewel               | a  | b |
+/// |---------------------|----|---|
+/// | Lethal Pride 10000  | 10 | 8 |
+/// | Lethal Pride 10020  | 9  | 9 |
+/// | Glorious Vanity 100 | 9  | 1 |
+struct LoadoutLookup;
+
+impl TimelessLookup for LoadoutLookup {
+    fn node_mods(&self, jewel_type: JewelType, seed: u32, node_id: u32) -> Option<Vec<String>> {
+        let points = match (jewel_type, seed, node_id) {
+            (JewelType::LethalPride, 10000, 1) => 10,
+            (JewelType::LethalPride, 10000, 2) => 8,
+            (JewelType::LethalPride, 10020, _) => 9,
+            (JewelType::GloriousVanity, 100, 1) => 9,
+            (JewelType::GloriousVanity, 100, 2) => 1,
+            (JewelType::LethalPride, 10040, 1) => 2,
+            (JewelType::LethalPride, 10040, 2) => 8,
+            (JewelType::LethalPride, 10040, 3) => 1,
+            (JewelType::GloriousVanity, 140, 2) => 8,
+            (JewelType::GloriousVanity, 140, 3) => 1,
+            (JewelType::GloriousVanity, 140, 4) => 5,
+            _ => return None,
+        };
+        Some(vec!["Point".to_string(); points])
+    }
+
+    fn nodes(&self) -> Vec<u32> {
+        vec![1, 2]
+    }
+}
+
+fn loadout_analyzer() -> TimelessJewelAnalyzer {
+    TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(LoadoutLookup))
+        .with_sockets(vec![
+            JewelSocket::new("a", "Socket A", vec![1]),
+            JewelSocket::new("b", "Socket B", vec![2]),
+        ])
+}
+
+fn loadout_config() -> TimelessJewelConfig {
+    let mut config = TimelessJewelConfig::new();
+    config.add_mod("Point".to_string(), 1.0);
+    config
+}
+
+fn glorious_vanity(seed: u32) -> TimelessJewel {
+    TimelessJewel::new(
+        format!("gv-{}", seed),
+        JewelType::GloriousVanity,
+        seed,
+        "Doryani".to_string(),
+        Value::Null,
+    )
+}
+
+fn placements(loadout: &Loadout) -> Vec<(String, String)> {
+    loadout
+        .slots
+        .iter()
+        .map(|slot| (slot.jewel.id(), slot.socket.socket_id.clone()))
+        .collect()
+}
+
+#[test]
+fn test_best_loadout_beats_greedy_choice() {
+    let sockets = ["a".to_string(), "b".to_string()];
+    let candidates = [lethal_pride(10000), glorious_vanity(100)];
+
+    // Greedy: Lethal Pride at its best socket (10), Glorious Vanity left with b (1)
+    let loadout = loadout_analyzer()
+        .best_loadout(&candidates, &sockets, &loadout_config())
+        .unwrap();
+
+    assert_eq!(
+        placements(&loadout),
+        vec![("gv-100".to_string(), "a".to_string()), ("lp-10000".to_string(), "b".to_string())]
+    );
+    assert_eq!(loadout.total_score, 17.0);
+    assert_eq!(loadout.matched_mods.len(), 1);
+    assert_eq!(loadout.matched_mods[0].count, 17);
+}
+
+#[test]
+fn test_best_loadout_places_one_jewel_per_type() {
+    let sockets = ["a".to_string(), "b".to_string()];
+    let candidates = [lethal_pride(10000), lethal_pride(10020), glorious_vanity(100)];
+
+    // Both Lethal Prides (10 + 9) would score more, but they are Historic
+    let loadout = loadout_analyzer()
+        .best_loadout(&candidates, &sockets, &loadout_config())
+        .unwrap();
+
+    assert_eq!(
+        placements(&loadout),
+        vec![("gv-100".to_string(), "a".to_string()), ("lp-10020".to_string(), "b".to_string())]
+    );
+    assert_eq!(loadout.total_score, 18.0);
+}
+
+#[test]
+fn test_best_loadout_counts_shared_nodes_once() {
+    // Node 2 is in the radius of both a and b
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(LoadoutLookup))
+        .with_sockets(vec![
+            JewelSocket::new("a", "Socket A", vec![1, 2]),
+            JewelSocket::new("b", "Socket B", vec![2, 3]),
+            JewelSocket::new("c", "Socket C", vec![4]),
+        ]);
+    let sockets = ["a".to_string(), "b".to_string(), "c".to_string()];
+    let candidates = [lethal_pride(10040), glorious_vanity(140)];
+
+    // Counting node 2 for both, Lethal Pride at a (10) and Glorious Vanity
+    // at b (9) would win with 19; node 2 only gives 8 once, leaving 11
+    let loadout = analyzer.best_loadout(&candidates, &sockets, &loadout_config()).unwrap();
+
+    assert_eq!(
+        placements(&loadout),
+        vec![("lp-10040".to_string(), "a".to_string()), ("gv-140".to_string(), "c".to_string())]
+    );
+    assert_eq!(loadout.total_score, 15.0);
+    assert!(loadout.slots.iter().all(|slot| slot.socket.node_contributions.is_empty()));
+}
+
+#[test]
+fn test_best_loadout_matched_mods_count_shared_nodes_once() {
+    let analyzer = TimelessJewelAnalyzer::new()
+        .with_lookup(Arc::new(LoadoutLookup))
+        .with_sockets(vec![
+            JewelSocket::new("a", "Socket A", vec![1, 2]),
+            JewelSocket::new("b", "Socket B", vec![2, 3]),
+        ]);
+    let sockets = ["a".to_string(), "b".to_string()];
+    let candidates = [lethal_pride(10040), glorious_vanity(140)];
+
+    // Both jewels give node 2 eight points; it counts for Lethal Pride, the
+    // first placed, so Glorious Vanity's eight are left out
+    let loadout = analyzer.best_loadout(&candidates, &sockets, &loadout_config()).unwrap();
+
+    assert_eq!(
+        placements(&loadout),
+        vec![("lp-10040".to_string(), "a".to_string()), ("gv-140".to_string(), "b".to_string())]
+    );
+    assert_eq!(loadout.total_score, 11.0);
+    assert_eq!(loadout.matched_mods.len(), 1);
+    assert_eq!(loadout.matched_mods[0].count, 11);
+    assert_eq!(loadout.slots[1].socket.matched_mods[0].count, 9);
+}
+
+#[test]
+fn test_best_loadout_leaves_useless_sockets_empty() {
+    let sockets = ["a".to_string(), "b".to_string()];
+
+    let loadout = loadout_analyzer()
+        .best_loadout(&[lethal_pride(10000)], &sockets, &loadout_config())
+        .unwrap();
+    assert_eq!(placements(&loadout), vec![("lp-10000".to_string(), "a".to_string())]);
+
+    let loadout = loadout_analyzer()
+        .best_loadout(&[lethal_pride(12345)], &sockets, &loadout_config())
+        .unwrap();
+    assert!(loadout.slots.is_empty());
+    assert_eq!(loadout.total_score, 0.0);
+}
@@ -1,18 +1,74 @@
 //! Timeless jewel analyzer
 
+use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
 
+use serde::{Deserialize, Serialize};
+
+use crate::data::{JewelSocket, PassiveTree, PathDistances, TimelessLookup};
 use crate::error::AnalysisError;
-use crate::items::{SocketResult, TimelessJewel, TimelessJewelMetrics};
+use crate::items::{
+    KeystoneChange, MatchedMod, NodeContribution, SocketResult, TimelessJewel,
+    TimelessJewelMetrics,
+};
+use crate::scoring::{mod_value, stat_template, WeightedScorer};
 
+use super::timings::{timed, AnalysisTimings};
 use super::traits::Analyzer;
+use super::warnings::{AnalysisWarning, AnalysisWarnings};
 
 /// Configuration for timeless jewel analysis
-#[derive(Debug, Clone)]
+///
+/// Serializes to JSON so weight presets can be saved and shared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimelessJewelConfig {
-    /// Valuable mods with their weights
+    /// Valuable mods with their weights (negative weights penalize a mod)
+    #[serde(default)]
     pub valuable_mods: HashMap<String, f64>,
+
+    /// Valuable mods whose weight counts per point of the number in the mod
+    /// text, matching every roll of the stat (e.g., "+# to Strength")
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub per_value_mods: HashSet<String>,
+
+    /// Passive nodes the character has allocated (None counts every node in radius)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocated_nodes: Option<HashSet<u32>>,
+
+    /// Weights of the keystones jewels replace others with (e.g., "Chainbreaker")
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub keystone_weights: HashMap<String, f64>,
+
+    /// Settings for single sockets, by socket id
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub socket_overrides: HashMap<String, SocketConfig>,
+
+    /// Nodes paths to a socket's nodes start from, such as a class start
+    /// (None starts them from the allocated nodes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_origin: Option<HashSet<u32>>,
+
+    /// Score taken off per passive point needed to reach a socket's weighted
+    /// nodes (None only reports the points)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point_cost: Option<f64>,
+
+    /// Passive tree version the allocated nodes come from (e.g., "3_25"),
+    /// checked against the lookup data's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree_version: Option<String>,
+
+    /// Whether to measure where the analysis spends its time
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timings: bool,
+
+    /// Whether a mod counts for every weight entry it matches instead of
+    /// only the one that wins (see `WeightedScorer` for the precedence)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multi_count: bool,
 }
 
 impl TimelessJewelConfig {
@@ -20,14 +76,85 @@ impl TimelessJewelConfig {
     pub fn new() -> Self {
         Self {
             valuable_mods: HashMap::new(),
+            per_value_mods: HashSet::new(),
+            allocated_nodes: None,
+            keystone_weights: HashMap::new(),
+            socket_overrides: HashMap::new(),
+            path_origin: None,
+            point_cost: None,
+            tree_version: None,
+            timings: false,
+            multi_count: false,
         }
     }
 
+    /// Only count mods on the given allocated passive nodes
+    pub fn with_allocated_nodes(mut self, nodes: HashSet<u32>) -> Self {
+        self.allocated_nodes = Some(nodes);
+        self
+    }
+
+    /// Measure travel costs from `nodes` (e.g., a class start)
+    pub fn with_path_origin(mut self, nodes: HashSet<u32>) -> Self {
+        self.path_origin = Some(nodes);
+        self
+    }
+
+    /// Take `cost` off the score per passive point of travel
+    pub fn with_point_cost(mut self, cost: f64) -> Self {
+        self.point_cost = Some(cost);
+        self
+    }
+
+    /// Record the passive tree version of the allocated nodes
+    pub fn with_tree_version(mut self, version: impl Into<String>) -> Self {
+        self.tree_version = Some(version.into());
+        self
+    }
+
+    /// Record `AnalysisTimings` in the results
+    pub fn with_timings(mut self, enabled: bool) -> Self {
+        self.timings = enabled;
+        self
+    }
+
+    /// Count mods for every weight entry they match
+    pub fn with_multi_count(mut self, enabled: bool) -> Self {
+        self.multi_count = enabled;
+        self
+    }
+
+    /// Use `socket_config` at the socket `socket_id`
+    pub fn with_socket_override(
+        mut self,
+        socket_id: impl Into<String>,
+        socket_config: SocketConfig,
+    ) -> Self {
+        self.socket_overrides.insert(socket_id.into(), socket_config);
+        self
+    }
+
     /// Add a valuable mod with a weight
     pub fn add_mod(&mut self, mod_text: String, weight: f64) {
         self.valuable_mods.insert(mod_text, weight);
     }
 
+    /// Add a valuable mod whose weight is multiplied by the mod's value
+    ///
+    /// With `mod_text` "+# to Strength" or "+25 to Strength", a
+    /// "+30 to Strength" node adds 30 × `weight`. The mod is stored as its
+    /// stat template.
+    pub fn add_mod_per_value(&mut self, mod_text: String, weight: f64) {
+        let template = stat_template(&mod_text);
+        self.per_value_mods.insert(template.clone());
+        self.valuable_mods.insert(template, weight);
+    }
+
+    /// Weight a keystone the jewel can grant (e.g., "Strength of Blood")
+    pub fn add_keystone(&mut self, keystone: String, weight: f64) {
+        self.keystone_weights.insert(keystone, weight);
+    }
+
     /// Get all valuable mods
     pub fn valuable_mods(&self) -> &HashMap<String, f64> {
         &self.valuable_mods
@@ -40,8 +167,47 @@ impl Default for TimelessJewelConfig {
     }
 }
 
+/// Settings for one socket, used instead of (or on top of) the config's
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SocketConfig {
+    /// Passive nodes allocated around this socket (None uses the config's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocated_nodes: Option<HashSet<u32>>,
+
+    /// Weights added to the config's mod weights at this socket
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub bonus_weights: HashMap<String, f64>,
+}
+
+impl SocketConfig {
+    /// Create an override that changes nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only count mods on the given allocated passive nodes at this socket
+    pub fn with_allocated_nodes(mut self, nodes: HashSet<u32>) -> Self {
+        self.allocated_nodes = Some(nodes);
+        self
+    }
+
+    /// Add `weight` to a mod's weight at this socket
+    pub fn add_bonus(&mut self, mod_text: String, weight: f64) {
+        self.bonus_weights.insert(mod_text, weight);
+    }
+
+    /// `valuable_mods` with the bonus weights added
+    fn weights(&self, valuable_mods: &HashMap<String, f64>) -> HashMap<String, f64> {
+        let mut weights = valuable_mods.clone();
+        for (mod_text, bonus) in &self.bonus_weights {
+            *weights.entry(mod_text.clone()).or_default() += bonus;
+        }
+        weights
+    }
+}
+
 /// Result of analyzing a timeless jewel
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimelessJewelAnalysisResult {
     /// The jewel that was analyzed
     pub jewel: TimelessJewel,
@@ -54,59 +220,354 @@ pub struct TimelessJewelAnalysisResult {
 
     /// Best socket ID
     pub best_socket_id: String,
+
+    /// Reasons the result may be meaningless (e.g., an unknown seed)
+    #[serde(default, skip_serializing_if = "AnalysisWarnings::is_empty")]
+    pub warnings: AnalysisWarnings,
+
+    /// Time spent in each phase, if the config asked for timings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<AnalysisTimings>,
 }
 
 /// Analyzer for timeless jewels
 pub struct TimelessJewelAnalyzer {
-    // TODO: Add LUT data reference
+    /// Lookup data (without it every jewel scores zero)
+    lookup: Option<Arc<dyn TimelessLookup>>,
+
+    /// Sockets to score (empty scores every node the lookup covers)
+    sockets: Vec<JewelSocket>,
+
+    /// Passive tree for travel costs (without it none are computed)
+    tree: Option<Arc<PassiveTree>>,
+
+    /// Paths from the last path origin, which rarely changes between calls
+    paths: Mutex<Option<(Vec<u32>, Arc<PathDistances>)>>,
 }
 
 impl TimelessJewelAnalyzer {
     /// Create a new timeless jewel analyzer
     pub fn new() -> Self {
-        Self {}
+        Self {
+            lookup: None,
+            sockets: Vec::new(),
+            tree: None,
+            paths: Mutex::new(None),
+        }
     }
-}
 
-impl Default for TimelessJewelAnalyzer {
-    fn default() -> Self {
-        Self::new()
+    /// Read jewel mods from `lookup`
+    pub fn with_lookup(mut self, lookup: Arc<dyn TimelessLookup>) -> Self {
+        self.lookup = Some(lookup);
+        self
     }
-}
 
-impl Analyzer<TimelessJewel> for TimelessJewelAnalyzer {
-    type Config = TimelessJewelConfig;
-    type Result = TimelessJewelAnalysisResult;
+    /// Score only the given sockets
+    pub fn with_sockets(mut self, sockets: Vec<JewelSocket>) -> Self {
+        self.sockets = sockets;
+        self
+    }
 
-    fn analyze(
+    /// Compute travel costs on `tree`
+    pub fn with_passive_tree(mut self, tree: Arc<PassiveTree>) -> Self {
+        self.tree = Some(tree);
+        self
+    }
+
+    /// Shortest paths from the config's path origin, if travel costs apply
+    fn path_distances(&self, config: &TimelessJewelConfig) -> Option<Arc<PathDistances>> {
+        let tree = self.tree.as_ref()?;
+        let origin = config.path_origin.as_ref().or(config.allocated_nodes.as_ref())?;
+        let mut origin: Vec<u32> = origin.iter().copied().collect();
+        origin.sort_unstable();
+
+        let mut cached = self.paths.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((cached_origin, paths)) = cached.as_ref() {
+            if *cached_origin == origin {
+                return Some(paths.clone());
+            }
+        }
+        let paths = Arc::new(tree.distances_from(origin.iter().copied()));
+        *cached = Some((origin, paths.clone()));
+        Some(paths)
+    }
+
+    /// Analyze `item` at the sockets in `socket_ids` only (every socket if None)
+    ///
+    /// Skipping sockets skips their lookups, which adds up over many seeds.
+    pub fn analyze_sockets(
         &self,
         item: &TimelessJewel,
-        _config: &Self::Config,
-    ) -> Result<Self::Result, AnalysisError> {
-        // TODO: Implement actual analysis logic
-        // For now, return a placeholder result
+        config: &TimelessJewelConfig,
+        socket_ids: Option<&[String]>,
+    ) -> Result<TimelessJewelAnalysisResult, AnalysisError> {
+        self.analyze_sockets_with(item, config, socket_ids, false)
+    }
 
-        let socket_results: Vec<SocketResult> = vec![];
+    /// Sockets to score (empty scores every node the lookup covers)
+    pub(super) fn sockets(&self) -> &[JewelSocket] {
+        &self.sockets
+    }
 
-        let best_score = socket_results
-            .iter()
-            .map(|r| r.score)
-            .fold(0.0, f64::max);
+    /// Like `analyze_sockets`, keeping node contributions without travel
+    /// costs too if `node_contributions` is set
+    pub(super) fn analyze_sockets_with(
+        &self,
+        item: &TimelessJewel,
+        config: &TimelessJewelConfig,
+        socket_ids: Option<&[String]>,
+        node_contributions: bool,
+    ) -> Result<TimelessJewelAnalysisResult, AnalysisError> {
+        let start = Instant::now();
+        let mut timings = config.timings.then(AnalysisTimings::default);
+
+        let socket_results: Vec<SocketResult> = match &self.lookup {
+            Some(lookup) => {
+                let sockets_start = Instant::now();
+                let scorer = WeightedScorer::new(config.valuable_mods.clone())
+                    .with_per_value(config.per_value_mods.clone())
+                    .with_multi_count(config.multi_count);
+                let paths = self.path_distances(config);
+                let all_nodes;
+                let sockets = if self.sockets.is_empty() {
+                    all_nodes = [JewelSocket::all_nodes(lookup.nodes())];
+                    &all_nodes[..]
+                } else {
+                    &self.sockets[..]
+                };
+
+                let results = sockets
+                    .iter()
+                    .filter(|socket| socket_ids.is_none_or(|ids| ids.contains(&socket.id)))
+                    .map(|socket| {
+                        let paths = paths.as_deref();
+                        let lookup = lookup.as_ref();
+                        let timings = &mut timings;
+                        let mut result = Self::analyze_socket(
+                            lookup, item, socket, config, &scorer, paths, timings,
+                        );
+                        if paths.is_none() && !node_contributions {
+                            result.node_contributions = Vec::new();
+                        }
+                        result
+                    })
+                    .collect();
 
-        let best_socket_id = socket_results
+                // Whatever the phases inside the loop didn't account for
+                if let Some(timings) = &mut timings {
+                    timings.socket_iteration =
+                        sockets_start.elapsed().saturating_sub(timings.phase_total());
+                }
+                results
+            }
+            None => Vec::new(),
+        };
+
+        // The best socket's score, even when every socket scores below zero
+        let best_socket = socket_results
             .iter()
-            .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal))
-            .map(|r| r.socket_id.clone())
-            .unwrap_or_default();
+            .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal));
+        let best_score = best_socket.map_or(0.0, |r| r.score);
+        let best_socket_id = best_socket.map(|r| r.socket_id.clone()).unwrap_or_default();
+
+        let mut warnings = AnalysisWarnings::check(self.lookup.as_deref(), item, config);
+        if socket_ids.is_some() && self.lookup.is_some() && socket_results.is_empty() {
+            warnings.push(AnalysisWarning::NoSocketsMatched);
+        }
+
+        if let Some(timings) = &mut timings {
+            timings.total = start.elapsed();
+        }
 
         Ok(TimelessJewelAnalysisResult {
             jewel: item.clone(),
             metrics: TimelessJewelMetrics { socket_results },
             best_score,
             best_socket_id,
+            warnings,
+            timings,
         })
     }
 
+    /// Score a jewel at one socket, applying the socket's override if any
+    ///
+    /// Every weighted node's contribution is recorded. With `paths`, so are
+    /// the points needed to reach the nodes, and with a point cost they are
+    /// taken off the score. Phases are
+    /// added to `timings` if they are collected.
+    fn analyze_socket(
+        lookup: &dyn TimelessLookup,
+        item: &TimelessJewel,
+        socket: &JewelSocket,
+        config: &TimelessJewelConfig,
+        scorer: &WeightedScorer,
+        paths: Option<&PathDistances>,
+        timings: &mut Option<AnalysisTimings>,
+    ) -> SocketResult {
+        let mut all_mods = Vec::new();
+        // Counted by the mod text and the entry it matched: entries per value
+        // or contained in a text count each text apart, and with multi-count
+        // a text can count for several entries
+        let mut counts: HashMap<(Cow<str>, &str), usize> = HashMap::new();
+        let mut node_contributions = Vec::new();
+
+        let socket_config = config.socket_overrides.get(&socket.id);
+        let allocated_nodes = socket_config
+            .and_then(|socket_config| socket_config.allocated_nodes.as_ref())
+            .or(config.allocated_nodes.as_ref());
+        let is_allocated =
+            |node: &u32| allocated_nodes.is_none_or(|allocated| allocated.contains(node));
+
+        let socket_scorer;
+        let scorer = match socket_config.filter(|s| !s.bonus_weights.is_empty()) {
+            Some(socket_config) => {
+                socket_scorer = WeightedScorer::new(socket_config.weights(&config.valuable_mods))
+                    .with_per_value(config.per_value_mods.clone())
+                    .with_multi_count(config.multi_count);
+                &socket_scorer
+            }
+            None => scorer,
+        };
+
+        // The conqueror's era picks the effect set for jewels that have them
+        let variant = item.jewel_type.variant(&item.conqueror);
+        for &node in socket.nodes.iter().filter(|node| is_allocated(node)) {
+            let (jewel_type, seed) = (item.jewel_type, item.seed);
+            let mods = timed(timings, |t| &mut t.lut_lookup, || match variant {
+                Some(variant) => lookup.variant_node_mods(jewel_type, seed, node, variant),
+                None => lookup.node_mods(jewel_type, seed, node),
+            });
+            let Some(mods) = mods else {
+                continue;
+            };
+
+            let mut node_score = None;
+            let mut node_matched = Vec::new();
+            timed(timings, |t| &mut t.matching, || {
+                // A node's name and stat lines often repeat the same words, so
+                // an entry doesn't count again for another text of the node
+                let mut node_keys: Vec<(&str, &str)> = Vec::new();
+                for mod_text in &mods {
+                    for matched in scorer.match_mods(mod_text) {
+                        let counted_for_other = node_keys
+                            .iter()
+                            .any(|(key, text)| *key == matched.key && text != mod_text);
+                        if counted_for_other {
+                            continue;
+                        }
+                        node_keys.push((matched.key, mod_text));
+                        let counted = match matched.value {
+                            None if matched.key == mod_text => Cow::Borrowed(matched.key),
+                            _ => Cow::Owned(mod_text.clone()),
+                        };
+                        node_matched.push((counted.to_string(), matched.key.to_string()));
+                        *counts.entry((counted, matched.key)).or_default() += 1;
+                        *node_score.get_or_insert(0.0) += matched.score();
+                    }
+                }
+                all_mods.extend(mods);
+            });
+
+            if let Some(score) = node_score {
+                node_contributions.push(NodeContribution {
+                    node_id: node,
+                    score,
+                    travel_cost: paths.and_then(|paths| paths.distance(node)),
+                    matched: node_matched,
+                });
+            }
+        }
+
+        let matched_mods = timed(timings, |t| &mut t.matching, || {
+            let mut matched_mods: Vec<MatchedMod> = counts
+                .into_iter()
+                .map(|((mod_text, pattern), count)| {
+                    let per_value = scorer.is_per_value(pattern);
+                    MatchedMod {
+                        weight: scorer.get_weight(pattern).unwrap_or_default(),
+                        value: per_value.then(|| mod_value(&mod_text)).flatten(),
+                        count,
+                        trade_stat_id: lookup.trade_stat_id(&mod_text).map(str::to_string),
+                        matched_pattern: Some(pattern.to_string()),
+                        mod_text: mod_text.into_owned(),
+                    }
+                })
+                .collect();
+            matched_mods.sort_by(|a, b| {
+                b.total()
+                    .partial_cmp(&a.total())
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| a.mod_text.cmp(&b.mod_text))
+                    .then_with(|| a.matched_pattern.cmp(&b.matched_pattern))
+            });
+            matched_mods
+        });
+
+        let (keystone_change, travel_cost, score) = timed(timings, |t| &mut t.scoring, || {
+            let keystone_change = socket
+                .keystone
+                .as_ref()
+                .filter(|keystone| is_allocated(&keystone.node_id))
+                .and_then(|keystone| {
+                    let replacement = item.jewel_type.keystone(&item.conqueror)?;
+                    let weight = config.keystone_weights.get(replacement).copied();
+                    Some(KeystoneChange {
+                        original: keystone.name.clone(),
+                        replacement: replacement.to_string(),
+                        weight: weight.unwrap_or_default(),
+                    })
+                });
+
+            // Only nodes worth having are worth travelling to
+            let travel_cost = paths.map(|paths| {
+                paths.path_cost(
+                    node_contributions
+                        .iter()
+                        .filter(|contribution| contribution.score > 0.0)
+                        .map(|contribution| contribution.node_id),
+                )
+            });
+            let travel_penalty = travel_cost
+                .zip(config.point_cost)
+                .map_or(0.0, |(points, cost)| points as f64 * cost);
+
+            let score = scorer.calculate_score(&matched_mods)
+                + keystone_change.as_ref().map_or(0.0, |change| change.weight)
+                - travel_penalty;
+            (keystone_change, travel_cost, score)
+        });
+
+        SocketResult {
+            socket_id: socket.id.clone(),
+            socket_name: socket.name.clone(),
+            score,
+            matched_mods,
+            all_mods,
+            keystone_change,
+            node_contributions,
+            travel_cost,
+        }
+    }
+}
+
+impl Default for TimelessJewelAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Analyzer<TimelessJewel> for TimelessJewelAnalyzer {
+    type Config = TimelessJewelConfig;
+    type Result = TimelessJewelAnalysisResult;
+
+    fn analyze(
+        &self,
+        item: &TimelessJewel,
+        config: &Self::Config,
+    ) -> Result<Self::Result, AnalysisError> {
+        self.analyze_sockets(item, config, None)
+    }
+
     fn compare_results(&self, a: &Self::Result, b: &Self::Result) -> Ordering {
         // Higher score is better, so reverse the comparison
         b.best_score
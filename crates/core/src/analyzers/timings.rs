@@ -0,0 +1,82 @@
+//! Where an analysis spends its time
+//!
+//! Collected only when `TimelessJewelConfig::timings` is set: every phase is
+//! measured with `Instant`, which is cheap but not free in the per-node loop.
+
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Time spent in each phase of one or more analyses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisTimings {
+    /// Looking up the jewel's mods on each node
+    pub lut_lookup: Duration,
+
+    /// Matching the mods against the weights
+    pub matching: Duration,
+
+    /// Scoring sockets (mod weights, keystones and travel costs)
+    pub scoring: Duration,
+
+    /// Walking the sockets and their nodes, outside the phases above
+    pub socket_iteration: Duration,
+
+    /// The whole analysis, including checks for warnings
+    pub total: Duration,
+}
+
+impl AnalysisTimings {
+    /// The phases with their names, in the order they run
+    pub fn phases(&self) -> [(&'static str, Duration); 4] {
+        [
+            ("LUT lookup", self.lut_lookup),
+            ("Matching", self.matching),
+            ("Scoring", self.scoring),
+            ("Socket iteration", self.socket_iteration),
+        ]
+    }
+
+    /// Sum of the phases (at most `total`)
+    pub fn phase_total(&self) -> Duration {
+        self.phases().iter().map(|(_, duration)| *duration).sum()
+    }
+}
+
+impl AddAssign for AnalysisTimings {
+    fn add_assign(&mut self, other: Self) {
+        self.lut_lookup += other.lut_lookup;
+        self.matching += other.matching;
+        self.scoring += other.scoring;
+        self.socket_iteration += other.socket_iteration;
+        self.total += other.total;
+    }
+}
+
+impl fmt::Display for AnalysisTimings {
+    /// One phase per line, then the total
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, duration) in self.phases() {
+            writeln!(f, "{:<18}{:>12.3?}", name, duration)?;
+        }
+        write!(f, "{:<18}{:>12.3?}", "Total", self.total)
+    }
+}
+
+/// Add the time `f` takes to the phase `phase` picks, if timings are collected
+pub(crate) fn timed<T>(
+    timings: &mut Option<AnalysisTimings>,
+    phase: fn(&mut AnalysisTimings) -> &mut Duration,
+    f: impl FnOnce() -> T,
+) -> T {
+    let Some(timings) = timings else {
+        return f();
+    };
+
+    let start = Instant::now();
+    let result = f();
+    *phase(timings) += start.elapsed();
+    result
+}
@@ -2,6 +2,8 @@
 
 use std::cmp::Ordering;
 
+use serde::{Deserialize, Serialize};
+
 use crate::error::AnalysisError;
 use crate::items::AnalyzableItem;
 
@@ -49,7 +51,7 @@ pub trait Analyzer<T: AnalyzableItem>: Send + Sync {
 }
 
 /// A ranked analysis result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RankedResult<R> {
     /// Rank (1 = best)
     pub rank: usize,
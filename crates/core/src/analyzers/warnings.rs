@@ -0,0 +1,172 @@
+//! Warnings about inputs that make an analysis meaningless
+//!
+//! An unknown seed, a conqueror from another jewel type or a config without
+//! weights all score a clean 0, which looks like a bad jewel rather than a
+//! bad input. The analyzer records why alongside the result.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::TimelessLookup;
+use crate::items::TimelessJewel;
+
+use super::timeless::TimelessJewelConfig;
+
+/// Something suspicious about an analysis's inputs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnalysisWarning {
+    /// The lookup data has nothing for the jewel's seed
+    SeedNotInLut { seed: u32 },
+
+    /// Every mod and keystone weight is 0 (or there are none)
+    EmptyConfig,
+
+    /// The conqueror isn't one of the jewel type's
+    InvalidConqueror { conqueror: String },
+
+    /// None of the requested sockets exist
+    NoSocketsMatched,
+
+    /// The data has effects specific to conqueror variants, but the conqueror
+    /// is missing or doesn't pick one
+    AmbiguousConqueror { conqueror: String },
+
+    /// The lookup data and the allocated nodes come from different passive trees
+    TreeVersionMismatch { data: String, character: String },
+}
+
+impl fmt::Display for AnalysisWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisWarning::SeedNotInLut { seed } => {
+                write!(f, "seed {} is not in the loaded data", seed)
+            }
+            AnalysisWarning::EmptyConfig => write!(f, "no mod or keystone has a weight"),
+            AnalysisWarning::InvalidConqueror { conqueror } => {
+                write!(f, "{} is not a conqueror of this jewel type", conqueror)
+            }
+            AnalysisWarning::NoSocketsMatched => write!(f, "none of the given sockets exist"),
+            AnalysisWarning::AmbiguousConqueror { conqueror } if conqueror.is_empty() => {
+                write!(f, "no conqueror given, but the jewel's effects depend on it")
+            }
+            AnalysisWarning::AmbiguousConqueror { conqueror } => write!(
+                f,
+                "{} doesn't pick one of the jewel's conqueror variants; using shared effects",
+                conqueror
+            ),
+            AnalysisWarning::TreeVersionMismatch { data, character } => write!(
+                f,
+                "the data is for passive tree {} but the character's is {}",
+                data, character
+            ),
+        }
+    }
+}
+
+/// Warnings of one analysis, in the order they were found
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AnalysisWarnings(Vec<AnalysisWarning>);
+
+impl AnalysisWarnings {
+    /// Warnings about `item`, `config` and the lookup data
+    pub(crate) fn check(
+        lookup: Option<&dyn TimelessLookup>,
+        item: &TimelessJewel,
+        config: &TimelessJewelConfig,
+    ) -> Self {
+        let mut warnings = Self::default();
+
+        let jewel_type = item.jewel_type;
+        let seed_known = lookup.and_then(|lookup| lookup.has_seed(jewel_type, item.seed));
+        if !jewel_type.is_valid_seed(item.seed) || seed_known == Some(false) {
+            warnings.push(AnalysisWarning::SeedNotInLut { seed: item.seed });
+        }
+
+        let mut weights = config.valuable_mods.values().chain(config.keystone_weights.values());
+        if weights.all(|weight| *weight == 0.0) {
+            warnings.push(AnalysisWarning::EmptyConfig);
+        }
+
+        if !jewel_type.conquerors().contains(&item.conqueror.as_str()) {
+            warnings.push(AnalysisWarning::InvalidConqueror {
+                conqueror: item.conqueror.clone(),
+            });
+        }
+
+        let has_variants = lookup.is_some_and(|lookup| lookup.has_variants(jewel_type, item.seed));
+        if has_variants && jewel_type.variant(&item.conqueror).is_none() {
+            warnings.push(AnalysisWarning::AmbiguousConqueror {
+                conqueror: item.conqueror.clone(),
+            });
+        }
+
+        let data_tree = lookup.and_then(|lookup| lookup.tree_version());
+        if let (Some(data), Some(character)) = (data_tree, config.tree_version.as_deref()) {
+            if !same_tree_version(data, character) {
+                warnings.push(AnalysisWarning::TreeVersionMismatch {
+                    data: data.to_string(),
+                    character: character.to_string(),
+                });
+            }
+        }
+
+        warnings
+    }
+
+    /// Record a warning
+    pub fn push(&mut self, warning: AnalysisWarning) {
+        self.0.push(warning);
+    }
+
+    /// Whether there are no warnings
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Number of warnings
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether `warning` was recorded
+    pub fn contains(&self, warning: &AnalysisWarning) -> bool {
+        self.0.contains(warning)
+    }
+
+    /// Every warning
+    pub fn iter(&self) -> impl Iterator<Item = &AnalysisWarning> {
+        self.0.iter()
+    }
+}
+
+/// Whether two tree versions name the same patch
+///
+/// Only the major and minor version count, and "." and "_" both separate
+/// them, so PoB's "3_25" matches "3.25.0".
+fn same_tree_version(a: &str, b: &str) -> bool {
+    let patch = |version: &str| -> Vec<String> {
+        version
+            .trim()
+            .split(['.', '_'])
+            .take(2)
+            .map(str::to_string)
+            .collect()
+    };
+    patch(a) == patch(b)
+}
+
+impl fmt::Display for AnalysisWarnings {
+    /// The warnings separated by "; "
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, warning) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", warning)?;
+        }
+        Ok(())
+    }
+}
@@ -3,9 +3,12 @@
 //! This module will handle loading and parsing timeless jewel lookup tables
 //! and other game data files.
 
+pub mod passive_tree;
+pub mod sockets;
 pub mod traits;
 
-pub use traits::DataSource;
+pub use passive_tree::{NodeKind, PassiveTree, PathDistances, TreeNode, LARGE_JEWEL_RADIUS};
+pub use sockets::{JewelSocket, Keystone};
+pub use traits::{DataSource, TimelessLookup};
 
 // TODO: Add LUT parser module
-// TODO: Add socket location data module
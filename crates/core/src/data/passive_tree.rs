@@ -0,0 +1,435 @@
+//! Passive tree geometry
+//!
+//! Reads the passive tree JSON that GGG publishes (and Path of Building
+//! ships), places every node on the tree from its group, orbit and orbit
+//! index, finds the nodes within a jewel socket's radius, and measures how
+//! many passive points it takes to reach a node.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f64::consts::PI;
+
+use serde::Deserialize;
+
+use crate::error::DataError;
+
+use super::sockets::JewelSocket;
+
+/// Radius of a large jewel, which every timeless jewel is
+pub const LARGE_JEWEL_RADIUS: f64 = 1800.0;
+
+/// Orbit radii used when the tree has no `constants`
+const DEFAULT_ORBIT_RADII: [f64; 7] = [0.0, 82.0, 162.0, 335.0, 493.0, 662.0, 846.0];
+
+/// Nodes per orbit used when the tree has no `constants`
+const DEFAULT_SKILLS_PER_ORBIT: [usize; 7] = [1, 6, 16, 16, 40, 72, 72];
+
+/// Angles of the 16 slots of a 16-node orbit, in degrees
+const ORBIT_ANGLES_16: [f64; 16] = [
+    0.0, 30.0, 45.0, 60.0, 90.0, 120.0, 135.0, 150.0, 180.0, 210.0, 225.0, 240.0, 270.0, 300.0,
+    315.0, 330.0,
+];
+
+/// Angles of the 40 slots of a 40-node orbit, in degrees
+const ORBIT_ANGLES_40: [f64; 40] = [
+    0.0, 10.0, 20.0, 30.0, 40.0, 45.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0, 110.0, 120.0, 130.0,
+    135.0, 140.0, 150.0, 160.0, 170.0, 180.0, 190.0, 200.0, 210.0, 220.0, 225.0, 230.0, 240.0,
+    250.0, 260.0, 270.0, 280.0, 290.0, 300.0, 310.0, 315.0, 320.0, 330.0, 340.0, 350.0,
+];
+
+/// What a passive node is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Small,
+    Notable,
+    Keystone,
+    JewelSocket,
+    Mastery,
+    ClassStart,
+    Ascendancy,
+}
+
+impl NodeKind {
+    /// Whether a jewel in radius can change this node
+    pub fn is_affected_by_jewels(&self) -> bool {
+        matches!(self, NodeKind::Small | NodeKind::Notable | NodeKind::Keystone)
+    }
+}
+
+/// A passive node placed on the tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub id: u32,
+    pub name: String,
+    pub kind: NodeKind,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl TreeNode {
+    /// Distance to `other` in tree units
+    pub fn distance(&self, other: &TreeNode) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+/// The passive tree's nodes with their positions and connections
+#[derive(Debug, Clone, Default)]
+pub struct PassiveTree {
+    nodes: HashMap<u32, TreeNode>,
+
+    /// Neighbours of every connected node, lowest id first
+    edges: HashMap<u32, Vec<u32>>,
+
+    /// Class start node by the tree's class index
+    class_starts: HashMap<u32, u32>,
+}
+
+/// Shortest paths from a set of starting nodes, in passive points
+#[derive(Debug, Clone, Default)]
+pub struct PathDistances {
+    distances: HashMap<u32, u32>,
+
+    /// The node before each reached node on its shortest path
+    previous: HashMap<u32, u32>,
+}
+
+impl PathDistances {
+    /// Points needed to reach `node_id` (0 for a starting node, None if it
+    /// can't be reached)
+    pub fn distance(&self, node_id: u32) -> Option<u32> {
+        self.distances.get(&node_id).copied()
+    }
+
+    /// Points needed to reach every node in `node_ids`
+    ///
+    /// Nodes shared by several paths are counted once, so this is at most the
+    /// sum of the distances. Nodes that can't be reached are left out.
+    pub fn path_cost(&self, node_ids: impl IntoIterator<Item = u32>) -> u32 {
+        let mut on_path = HashSet::new();
+        for node_id in node_ids {
+            if !self.distances.contains_key(&node_id) {
+                continue;
+            }
+            let mut node = node_id;
+            while let Some(&previous) = self.previous.get(&node) {
+                if !on_path.insert(node) {
+                    break;
+                }
+                node = previous;
+            }
+        }
+        on_path.len() as u32
+    }
+}
+
+impl PassiveTree {
+    /// Read the passive tree JSON
+    ///
+    /// Nodes outside any group (e.g., the tree's root) and nodes in proxy
+    /// groups (cluster jewel placeholders) are left out.
+    pub fn from_json(json: &str) -> Result<Self, DataError> {
+        let raw: RawTree = serde_json::from_str(json)
+            .map_err(|e| DataError::InvalidFormat(format!("passive tree: {}", e)))?;
+
+        let orbit_radii = raw
+            .constants
+            .as_ref()
+            .map_or(DEFAULT_ORBIT_RADII.to_vec(), |c| c.orbit_radii.clone());
+        let skills_per_orbit = raw
+            .constants
+            .as_ref()
+            .map_or(DEFAULT_SKILLS_PER_ORBIT.to_vec(), |c| c.skills_per_orbit.clone());
+
+        let mut nodes = HashMap::new();
+        let mut links = Vec::new();
+        let mut class_starts = HashMap::new();
+        for (key, node) in raw.nodes {
+            let Some(group) = node.group.and_then(|group| raw.groups.get(&group.to_string()))
+            else {
+                continue;
+            };
+            if group.is_proxy {
+                continue;
+            }
+
+            let id = match node.skill {
+                Some(id) => id,
+                None => key.parse().map_err(|_| {
+                    DataError::InvalidFormat(format!("passive tree: invalid node id '{}'", key))
+                })?,
+            };
+
+            let orbit = node.orbit;
+            let radius = *orbit_radii.get(orbit).ok_or_else(|| {
+                DataError::InvalidFormat(format!(
+                    "passive tree: node {} has unknown orbit {}",
+                    id, orbit
+                ))
+            })?;
+            let slots = skills_per_orbit.get(orbit).copied().unwrap_or(1);
+            let angle = orbit_angle(slots, node.orbit_index);
+
+            for other in node.out.iter().chain(&node.in_) {
+                links.push((id, other.id()?));
+            }
+            if let Some(class_index) = node.class_start_index {
+                class_starts.insert(class_index, id);
+            }
+
+            nodes.insert(
+                id,
+                TreeNode {
+                    id,
+                    kind: node.kind(),
+                    name: node.name,
+                    x: group.x + radius * angle.sin(),
+                    y: group.y - radius * angle.cos(),
+                },
+            );
+        }
+
+        let mut edges: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (a, b) in links {
+            if a != b && nodes.contains_key(&a) && nodes.contains_key(&b) {
+                edges.entry(a).or_default().push(b);
+                edges.entry(b).or_default().push(a);
+            }
+        }
+        for neighbours in edges.values_mut() {
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        }
+
+        Ok(Self {
+            nodes,
+            edges,
+            class_starts,
+        })
+    }
+
+    /// Node `id`, if the tree has it
+    pub fn node(&self, id: u32) -> Option<&TreeNode> {
+        self.nodes.get(&id)
+    }
+
+    /// Number of placed nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the tree has no placed nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Nodes connected to `id`, lowest id first
+    pub fn neighbours(&self, id: u32) -> &[u32] {
+        self.edges.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Start node of the class at `class_index` (the tree's `classStartIndex`)
+    pub fn class_start(&self, class_index: u32) -> Option<u32> {
+        self.class_starts.get(&class_index).copied()
+    }
+
+    /// Shortest paths from the nodes in `origin` to every node they reach
+    ///
+    /// `origin` is usually the allocated passives or a class start. Paths
+    /// don't go through masteries, ascendancy nodes or other class starts,
+    /// since those can't be allocated on the way.
+    pub fn distances_from(&self, origin: impl IntoIterator<Item = u32>) -> PathDistances {
+        let mut paths = PathDistances::default();
+        let mut queue = VecDeque::new();
+        for id in origin {
+            if self.nodes.contains_key(&id) && paths.distances.insert(id, 0).is_none() {
+                queue.push_back(id);
+            }
+        }
+
+        while let Some(id) = queue.pop_front() {
+            let distance = paths.distances[&id];
+            for &next in self.neighbours(id) {
+                let passable = self.nodes.get(&next).is_some_and(|node| {
+                    !matches!(
+                        node.kind,
+                        NodeKind::Mastery | NodeKind::Ascendancy | NodeKind::ClassStart
+                    )
+                });
+                if passable && !paths.distances.contains_key(&next) {
+                    paths.distances.insert(next, distance + 1);
+                    paths.previous.insert(next, id);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        paths
+    }
+
+    /// Ids of the jewel sockets, lowest first
+    pub fn jewel_sockets(&self) -> Vec<u32> {
+        let mut sockets: Vec<u32> = self
+            .nodes
+            .values()
+            .filter(|node| node.kind == NodeKind::JewelSocket)
+            .map(|node| node.id)
+            .collect();
+        sockets.sort_unstable();
+        sockets
+    }
+
+    /// Nodes a jewel in `socket_node_id` changes, lowest id first
+    ///
+    /// Passives within `radius` of the socket, leaving out other sockets,
+    /// masteries, class starts and ascendancy nodes. None if the tree has
+    /// no such node.
+    pub fn nodes_in_radius(&self, socket_node_id: u32, radius: f64) -> Option<Vec<u32>> {
+        let socket = self.nodes.get(&socket_node_id)?;
+
+        let mut nodes: Vec<u32> = self
+            .nodes
+            .values()
+            .filter(|node| node.kind.is_affected_by_jewels() && node.distance(socket) <= radius)
+            .map(|node| node.id)
+            .collect();
+        nodes.sort_unstable();
+        Some(nodes)
+    }
+
+    /// Socket `socket_node_id` with the nodes in a large jewel's radius
+    ///
+    /// The keystone in radius, if there is one, is recorded too.
+    pub fn socket(&self, socket_node_id: u32, name: impl Into<String>) -> Option<JewelSocket> {
+        let nodes = self.nodes_in_radius(socket_node_id, LARGE_JEWEL_RADIUS)?;
+        let keystone = nodes
+            .iter()
+            .filter_map(|id| self.nodes.get(id))
+            .find(|node| node.kind == NodeKind::Keystone);
+
+        let socket = JewelSocket::new(socket_node_id.to_string(), name, Vec::new());
+        let socket = match keystone {
+            Some(keystone) => socket.with_keystone(keystone.id, keystone.name.clone()),
+            None => socket,
+        };
+        Some(JewelSocket { nodes, ..socket })
+    }
+
+    /// Every jewel socket with the nodes in a large jewel's radius
+    ///
+    /// Sockets are named after their node id (e.g., "Jewel Socket 26725").
+    pub fn sockets(&self) -> Vec<JewelSocket> {
+        self.jewel_sockets()
+            .into_iter()
+            .filter_map(|id| self.socket(id, format!("Jewel Socket {}", id)))
+            .collect()
+    }
+}
+
+/// Angle of slot `index` on an orbit with `slots` slots, in radians
+///
+/// 16- and 40-slot orbits aren't evenly spaced: they keep slots at 45°
+/// steps so they line up with the smaller orbits.
+fn orbit_angle(slots: usize, index: usize) -> f64 {
+    let degrees = match slots {
+        16 => ORBIT_ANGLES_16.get(index).copied(),
+        40 => ORBIT_ANGLES_40.get(index).copied(),
+        _ => None,
+    };
+
+    match degrees {
+        Some(degrees) => degrees.to_radians(),
+        None => 2.0 * PI * index as f64 / slots.max(1) as f64,
+    }
+}
+
+/// The parts of the tree JSON used to place and connect nodes
+#[derive(Deserialize)]
+struct RawTree {
+    #[serde(default)]
+    groups: HashMap<String, RawGroup>,
+    nodes: HashMap<String, RawNode>,
+    constants: Option<RawConstants>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawConstants {
+    orbit_radii: Vec<f64>,
+    skills_per_orbit: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawGroup {
+    x: f64,
+    y: f64,
+    #[serde(default)]
+    is_proxy: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawNode {
+    skill: Option<u32>,
+    #[serde(default)]
+    name: String,
+    group: Option<u32>,
+    #[serde(default)]
+    orbit: usize,
+    #[serde(default)]
+    orbit_index: usize,
+    #[serde(default)]
+    is_notable: bool,
+    #[serde(default)]
+    is_keystone: bool,
+    #[serde(default)]
+    is_jewel_socket: bool,
+    #[serde(default)]
+    is_mastery: bool,
+    ascendancy_name: Option<String>,
+    class_start_index: Option<u32>,
+    #[serde(default)]
+    out: Vec<RawNodeId>,
+    #[serde(default, rename = "in")]
+    in_: Vec<RawNodeId>,
+}
+
+/// A node id in `out`/`in`, written as a string by GGG and as a number by
+/// some exports
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawNodeId {
+    Number(u32),
+    Text(String),
+}
+
+impl RawNodeId {
+    fn id(&self) -> Result<u32, DataError> {
+        match self {
+            RawNodeId::Number(id) => Ok(*id),
+            RawNodeId::Text(id) => id.parse().map_err(|_| {
+                DataError::InvalidFormat(format!("passive tree: invalid node id '{}'", id))
+            }),
+        }
+    }
+}
+
+impl RawNode {
+    fn kind(&self) -> NodeKind {
+        if self.ascendancy_name.is_some() {
+            NodeKind::Ascendancy
+        } else if self.class_start_index.is_some() {
+            NodeKind::ClassStart
+        } else if self.is_jewel_socket {
+            NodeKind::JewelSocket
+        } else if self.is_mastery {
+            NodeKind::Mastery
+        } else if self.is_keystone {
+            NodeKind::Keystone
+        } else if self.is_notable {
+            NodeKind::Notable
+        } else {
+            NodeKind::Small
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Jewel socket locations
+
+use serde::{Deserialize, Serialize};
+
+/// A jewel socket and the passive nodes in its radius
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JewelSocket {
+    /// Socket identifier (the socket's passive node id)
+    pub id: String,
+
+    /// Human-readable socket name (e.g., "Far-Left (near Marauder)")
+    pub name: String,
+
+    /// Passive nodes within the jewel radius
+    pub nodes: Vec<u32>,
+
+    /// Keystone within the jewel radius, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystone: Option<Keystone>,
+}
+
+/// A keystone passive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keystone {
+    /// Passive node id
+    pub node_id: u32,
+
+    /// Keystone name (e.g., "Resolute Technique")
+    pub name: String,
+}
+
+impl JewelSocket {
+    /// Create a socket
+    pub fn new(id: impl Into<String>, name: impl Into<String>, nodes: Vec<u32>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            nodes,
+            keystone: None,
+        }
+    }
+
+    /// Set the keystone within the jewel radius
+    pub fn with_keystone(mut self, node_id: u32, name: impl Into<String>) -> Self {
+        self.keystone = Some(Keystone {
+            node_id,
+            name: name.into(),
+        });
+        self
+    }
+
+    /// A pseudo-socket covering every node in `nodes`
+    ///
+    /// Used when no socket layout is available, so a jewel can still be
+    /// scored against everything the lookup data covers.
+    pub fn all_nodes(nodes: Vec<u32>) -> Self {
+        Self::new("all", "All affected nodes", nodes)
+    }
+}
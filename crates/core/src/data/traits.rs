@@ -1,6 +1,7 @@
 //! Traits for data sources
 
 use crate::error::DataError;
+use crate::items::JewelType;
 
 /// Trait for data sources that provide game data
 pub trait DataSource: Send + Sync {
@@ -13,3 +14,47 @@ pub trait DataSource: Send + Sync {
     /// Validate data integrity
     fn validate(&self) -> Result<(), DataError>;
 }
+
+/// Lookup of the mods a timeless jewel puts on passive nodes
+pub trait TimelessLookup: Send + Sync {
+    /// Mod texts `jewel_type` with `seed` gives to `node_id`
+    ///
+    /// Returns None if the node isn't affected or the seed is unknown.
+    fn node_mods(&self, jewel_type: JewelType, seed: u32, node_id: u32) -> Option<Vec<String>>;
+
+    /// Mod texts `variant` (see `JewelType::variant`) of the jewel gives to `node_id`
+    ///
+    /// Data without effects specific to a variant gives the same as `node_mods`.
+    fn variant_node_mods(
+        &self,
+        jewel_type: JewelType,
+        seed: u32,
+        node_id: u32,
+        _variant: &str,
+    ) -> Option<Vec<String>> {
+        self.node_mods(jewel_type, seed, node_id)
+    }
+
+    /// Whether `seed` of `jewel_type` has effects specific to a variant
+    fn has_variants(&self, _jewel_type: JewelType, _seed: u32) -> bool {
+        false
+    }
+
+    /// Whether the data has `seed` for `jewel_type` (None if it can't tell)
+    fn has_seed(&self, _jewel_type: JewelType, _seed: u32) -> Option<bool> {
+        None
+    }
+
+    /// Every passive node the data covers
+    fn nodes(&self) -> Vec<u32>;
+
+    /// Passive tree version the data's node ids belong to (e.g., "3_25"), if known
+    fn tree_version(&self) -> Option<&str> {
+        None
+    }
+
+    /// Trade API stat id of a mod text, if known
+    fn trade_stat_id(&self, _mod_text: &str) -> Option<&str> {
+        None
+    }
+}
@@ -15,6 +15,10 @@ pub enum AnalysisError {
 
     #[error("Analysis failed: {0}")]
     AnalysisFailed(String),
+
+    /// Items couldn't be fetched; the source error is kept for matching
+    #[error("Item source unavailable: {0}")]
+    SourceUnavailable(#[source] Box<dyn std::error::Error + Send + Sync>),
 }
 
 #[derive(Error, Debug)]
@@ -31,3 +35,24 @@ pub enum DataError {
     #[error("Data corruption detected: {0}")]
     CorruptedData(String),
 }
+
+/// Why an exported results file couldn't be read
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Invalid export: {0}")]
+    Invalid(String),
+
+    #[error("Invalid export: it has no schema version")]
+    MissingVersion,
+
+    /// Written by a newer release
+    #[error("Export schema version {0} is not supported")]
+    UnsupportedVersion(u64),
+}
+
+impl DataError {
+    /// Whether downloading the data again could fix this
+    pub fn needs_redownload(&self) -> bool {
+        matches!(self, DataError::MissingFile(_) | DataError::CorruptedData(_))
+    }
+}
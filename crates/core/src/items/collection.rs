@@ -0,0 +1,82 @@
+//! Deduplicated item collections
+
+use std::collections::HashSet;
+
+use super::traits::Item;
+
+/// Items gathered from several places, without duplicates
+///
+/// Two items are the same when their ids match; the first one added wins.
+#[derive(Debug, Clone)]
+pub struct ItemCollection<T: Item> {
+    items: Vec<T>,
+    ids: HashSet<String>,
+}
+
+impl<T: Item> ItemCollection<T> {
+    /// Create an empty collection
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            ids: HashSet::new(),
+        }
+    }
+
+    /// Add an item, returning false if it was already present
+    pub fn add(&mut self, item: T) -> bool {
+        if !self.ids.insert(item.id()) {
+            return false;
+        }
+
+        self.items.push(item);
+        true
+    }
+
+    /// Add several items, returning how many were new
+    pub fn add_all(&mut self, items: impl IntoIterator<Item = T>) -> usize {
+        items
+            .into_iter()
+            .fold(0, |added, item| added + usize::from(self.add(item)))
+    }
+
+    /// Remove the item with `id`, returning it if it was present
+    pub fn remove(&mut self, id: &str) -> Option<T> {
+        if !self.ids.remove(id) {
+            return None;
+        }
+
+        let index = self.items.iter().position(|item| item.id() == id)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Whether an item with `id` is present
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Number of items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the collection is empty
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Items in the order they were added
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Take the items out of the collection
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T: Item> Default for ItemCollection<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,6 @@
 //! Item models and traits
 
+pub mod collection;
 pub mod traits;
 pub mod timeless_jewel;
 
@@ -7,7 +8,9 @@ pub mod timeless_jewel;
 mod tests;
 
 // Re-export commonly used types
+pub use collection::ItemCollection;
 pub use traits::{AnalyzableItem, Item};
 pub use timeless_jewel::{
-    JewelType, MatchedMod, SocketResult, TimelessJewel, TimelessJewelMetrics,
+    JewelSummary, JewelType, KeystoneChange, MatchedMod, ModSummary, NodeContribution,
+    SocketResult, SocketSortKey, TimelessJewel, TimelessJewelMetrics, TimelessJewelRecord,
 };
@@ -26,6 +26,150 @@ fn test_jewel_type_from_str() {
     assert_eq!(JewelType::from_str(""), None);
 }
 
+#[test]
+fn test_jewel_type_conquerors() {
+    assert_eq!(
+        JewelType::LethalPride.conquerors(),
+        &["Kaom", "Rakiata", "Kiloava", "Akoya"]
+    );
+    assert!(JewelType::MilitantFaith.conquerors().contains(&"Dominus"));
+}
+
+#[test]
+fn test_jewel_type_keystones() {
+    assert_eq!(JewelType::LethalPride.keystone("Kaom"), Some("Strength of Blood"));
+    assert_eq!(JewelType::GloriousVanity.keystone("Ahuana"), Some("Immortal Ambition"));
+    assert_eq!(JewelType::LethalPride.keystone("Doryani"), None);
+
+    for jewel_type in JewelType::ALL {
+        for conqueror in jewel_type.conquerors() {
+            assert!(jewel_type.keystone(conqueror).is_some(), "{}", conqueror);
+        }
+    }
+}
+
+#[test]
+fn test_jewel_type_variants() {
+    let hubris = JewelType::ElegantHubris;
+    assert_eq!(hubris.variants(), &["Cadiro", "Victario", "Caspiro"]);
+    assert_eq!(hubris.variant("Victario"), Some("Victario"));
+    assert_eq!(hubris.variant("Chitus"), Some("Caspiro"));
+    assert_eq!(hubris.variant(""), None);
+
+    assert!(JewelType::LethalPride.variants().is_empty());
+    assert_eq!(JewelType::LethalPride.variant("Kaom"), None);
+}
+
+#[test]
+fn test_jewel_type_seed_range() {
+    assert_eq!(JewelType::LethalPride.seed_range(), 10000..=18000);
+    assert!(JewelType::LethalPride.is_valid_seed(14032));
+    assert!(!JewelType::LethalPride.is_valid_seed(9999));
+    assert!(!JewelType::GloriousVanity.is_valid_seed(8001));
+
+    // Elegant Hubris only rolls multiples of 20
+    assert!(JewelType::ElegantHubris.is_valid_seed(158360));
+    assert!(!JewelType::ElegantHubris.is_valid_seed(158361));
+
+    assert_eq!(JewelType::ALL.len(), 5);
+}
+
+#[test]
+fn test_parse_seed_line() {
+    assert_eq!(
+        JewelType::MilitantFaith
+            .parse_seed_line("Carved to glorify 7200 new faithful converted by High Templar Venarius"),
+        Some((7200, "Venarius"))
+    );
+    assert_eq!(
+        JewelType::ElegantHubris.parse_seed_line("Commissioned 158360 coins to commemorate Cadiro"),
+        Some((158360, "Cadiro"))
+    );
+
+    // A conqueror from another jewel type doesn't count
+    assert_eq!(
+        JewelType::LethalPride
+            .parse_seed_line("Commanded leadership over 10000 warriors under Doryani"),
+        None
+    );
+    assert_eq!(JewelType::LethalPride.parse_seed_line("Historic"), None);
+}
+
+const LETHAL_PRIDE_TEXT: &str = "Item Class: Jewels
+Rarity: Unique
+Lethal Pride
+Timeless Jewel
+--------
+Limited to: 1 Historic
+Radius: Large
+--------
+Item Level: 84
+--------
+Commanded leadership over 18000 warriors under Kaom
+Passives in radius are Conquered by the Karui
+Historic
+--------
+They believed themselves the chosen, but they were merely the first.
+--------
+Place into an allocated Jewel Socket on the Passive Skill Tree. Right click to remove from the Socket.";
+
+#[test]
+fn test_timeless_jewel_from_item_text() {
+    let jewel = TimelessJewel::from_item_text(LETHAL_PRIDE_TEXT).unwrap();
+
+    assert_eq!(jewel.jewel_type, JewelType::LethalPride);
+    assert_eq!(jewel.seed(), 18000);
+    assert_eq!(jewel.conqueror(), "Kaom");
+    assert_eq!(jewel.id(), "Lethal Pride:18000:Kaom");
+    assert_eq!(jewel.raw_data()["text"], LETHAL_PRIDE_TEXT);
+}
+
+#[test]
+fn test_timeless_jewel_record_round_trip() {
+    let jewel = TimelessJewel::from_item_text(LETHAL_PRIDE_TEXT).unwrap();
+
+    let json = serde_json::to_string(&jewel.to_record()).unwrap();
+    let record: TimelessJewelRecord = serde_json::from_str(&json).unwrap();
+    let restored = TimelessJewel::from_record(record);
+
+    assert_eq!(restored.id(), "Lethal Pride:18000:Kaom");
+    assert_eq!(restored.jewel_type, JewelType::LethalPride);
+    assert_eq!(restored.seed(), 18000);
+    assert_eq!(restored.conqueror(), "Kaom");
+    assert_eq!(restored.raw_data(), jewel.raw_data());
+
+    // Jewels without raw data leave it out
+    let jewel = TimelessJewel::new(
+        "a".to_string(),
+        JewelType::BrutalRestraint,
+        600,
+        "Asenath".to_string(),
+        Value::Null,
+    );
+    let json = serde_json::to_value(jewel.to_record()).unwrap();
+    assert!(json.get("raw_data").is_none());
+    let restored = TimelessJewel::from_record(serde_json::from_value(json).unwrap());
+    assert_eq!(restored.id(), "a");
+    assert!(restored.raw_data().is_null());
+}
+
+#[test]
+fn test_timeless_jewel_from_bad_item_text() {
+    let ring = "Rarity: Rare\nDoom Loop\nAmethyst Ring\n--------\nItem Level: 80";
+    assert!(matches!(
+        TimelessJewel::from_item_text(ring),
+        Err(crate::error::AnalysisError::InvalidItemData(_))
+    ));
+
+    assert!(TimelessJewel::from_item_text("hello world").is_err());
+
+    let no_seed = LETHAL_PRIDE_TEXT.replace("18000 ", "");
+    assert!(matches!(
+        TimelessJewel::from_item_text(&no_seed),
+        Err(crate::error::AnalysisError::MissingField(_))
+    ));
+}
+
 #[test]
 fn test_timeless_jewel_creation() {
     let jewel = TimelessJewel::new(
@@ -66,3 +210,108 @@ fn test_timeless_jewel_different_types() {
         assert_eq!(jewel.conqueror(), conqueror);
     }
 }
+
+fn jewel(id: &str, seed: u32) -> TimelessJewel {
+    TimelessJewel::new(
+        id.to_string(),
+        JewelType::LethalPride,
+        seed,
+        "Kaom".to_string(),
+        Value::Null,
+    )
+}
+
+#[test]
+fn test_item_collection_deduplicates_by_id() {
+    let mut collection = ItemCollection::new();
+
+    assert!(collection.add(jewel("a", 1000)));
+    assert!(!collection.add(jewel("a", 2000)));
+    assert_eq!(collection.add_all(vec![jewel("b", 3000), jewel("a", 4000), jewel("c", 5000)]), 2);
+
+    assert_eq!(collection.len(), 3);
+    assert!(collection.contains("b"));
+
+    // The first copy wins
+    let seeds: Vec<u32> = collection.items().iter().map(|j| j.seed).collect();
+    assert_eq!(seeds, vec![1000, 3000, 5000]);
+    assert_eq!(collection.into_items().len(), 3);
+}
+
+#[test]
+fn test_item_collection_remove() {
+    let mut collection = ItemCollection::new();
+    collection.add_all(vec![jewel("a", 1000), jewel("b", 2000)]);
+
+    assert_eq!(collection.remove("a").map(|j| j.seed), Some(1000));
+    assert!(collection.remove("a").is_none());
+    assert!(!collection.contains("a"));
+    assert_eq!(collection.len(), 1);
+
+    // A removed item can be added again
+    assert!(collection.add(jewel("a", 3000)));
+}
+
+fn socket(id: &str, name: &str, score: f64, count: usize) -> SocketResult {
+    SocketResult {
+        socket_id: id.to_string(),
+        socket_name: name.to_string(),
+        score,
+        matched_mods: vec![MatchedMod {
+            mod_text: "Double Damage".to_string(),
+            weight: 1.0,
+            count,
+            trade_stat_id: None,
+            value: None,
+            matched_pattern: None,
+        }],
+        all_mods: Vec::new(),
+        keystone_change: None,
+        node_contributions: Vec::new(),
+        travel_cost: None,
+    }
+}
+
+fn metrics() -> TimelessJewelMetrics {
+    TimelessJewelMetrics {
+        socket_results: vec![
+            socket("a", "Zeta", 5.0, 1),
+            socket("b", "Alpha", 5.0, 3),
+            socket("c", "Mid", 9.0, 1),
+            socket("d", "Alpha", 2.0, 3),
+        ],
+    }
+}
+
+fn ids(sockets: &[&SocketResult]) -> Vec<String> {
+    sockets.iter().map(|socket| socket.socket_id.clone()).collect()
+}
+
+#[test]
+fn test_metrics_sorted_by_each_key() {
+    let metrics = metrics();
+
+    // Ties are broken by socket id
+    assert_eq!(ids(&metrics.sorted_by(SocketSortKey::Score)), ["c", "a", "b", "d"]);
+    assert_eq!(ids(&metrics.sorted_by(SocketSortKey::MatchedCount)), ["b", "d", "a", "c"]);
+    assert_eq!(ids(&metrics.sorted_by(SocketSortKey::Name)), ["b", "d", "c", "a"]);
+
+    assert!(SocketSortKey::Score.default_descending());
+    assert!(!SocketSortKey::Name.default_descending());
+}
+
+#[test]
+fn test_metrics_filter_and_top_sockets() {
+    let metrics = metrics();
+
+    let above = metrics.filter_sockets(|socket| socket.score >= 5.0);
+    assert_eq!(ids(&above), ["a", "b", "c"]);
+    assert_eq!(ids(&metrics.top_sockets(2)), ["c", "a"]);
+    assert_eq!(metrics.top_sockets(10).len(), 4);
+
+    let owned = metrics.top_sockets_owned(2);
+    assert_eq!(ids(&owned.iter().collect::<Vec<_>>()), ["c", "a"]);
+    let json = serde_json::to_value(metrics.sorted_by_owned(SocketSortKey::Name)).unwrap();
+    assert_eq!(json[0]["socket_id"], "b");
+    assert_eq!(metrics.filter_sockets_owned(|socket| socket.score > 100.0).len(), 0);
+}
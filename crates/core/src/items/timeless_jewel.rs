@@ -1,9 +1,18 @@
 //! Timeless Jewel item model
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
 
 use super::traits::{AnalyzableItem, Item};
+use crate::error::AnalysisError;
+
+/// Base type line shared by all timeless jewels
+const TIMELESS_JEWEL: &str = "Timeless Jewel";
+
+/// Line separating the sections of copied item text
+const SECTION_SEPARATOR: &str = "--------";
 
 /// Types of timeless jewels in Path of Exile
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -16,6 +25,15 @@ pub enum JewelType {
 }
 
 impl JewelType {
+    /// Every timeless jewel type
+    pub const ALL: [JewelType; 5] = [
+        JewelType::GloriousVanity,
+        JewelType::LethalPride,
+        JewelType::BrutalRestraint,
+        JewelType::MilitantFaith,
+        JewelType::ElegantHubris,
+    ];
+
     /// Get the string representation of the jewel type
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -28,6 +46,7 @@ impl JewelType {
     }
 
     /// Parse jewel type from string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "Lethal Pride" => Some(JewelType::LethalPride),
@@ -38,6 +57,101 @@ impl JewelType {
             _ => None,
         }
     }
+
+    /// Conquerors (jewel variants) of this jewel type
+    pub fn conquerors(&self) -> &'static [&'static str] {
+        match self {
+            JewelType::LethalPride => &["Kaom", "Rakiata", "Kiloava", "Akoya"],
+            JewelType::BrutalRestraint => &["Asenath", "Nasima", "Balbala", "Deshret"],
+            JewelType::GloriousVanity => &["Doryani", "Xibaqua", "Zerphi", "Ahuana"],
+            JewelType::ElegantHubris => &["Cadiro", "Victario", "Chitus", "Caspiro"],
+            JewelType::MilitantFaith => &["Avarius", "Dominus", "Maxarius", "Venarius"],
+        }
+    }
+
+    /// Conquerors whose jewels have their own effect sets, not just their
+    /// own keystone
+    ///
+    /// Elegant Hubris commissioned by Cadiro, Victario or Caspiro draws on a
+    /// different notable pool per era; other jewel types have no variants.
+    pub fn variants(&self) -> &'static [&'static str] {
+        match self {
+            JewelType::ElegantHubris => &["Cadiro", "Victario", "Caspiro"],
+            _ => &[],
+        }
+    }
+
+    /// Variant whose effects `conqueror` gets, if the jewel type has variants
+    ///
+    /// Conquerors that were later replaced get their successor's.
+    pub fn variant(&self, conqueror: &str) -> Option<&'static str> {
+        let variant = match (self, conqueror) {
+            (JewelType::ElegantHubris, "Cadiro") => "Cadiro",
+            (JewelType::ElegantHubris, "Victario") => "Victario",
+            (JewelType::ElegantHubris, "Chitus" | "Caspiro") => "Caspiro",
+            _ => return None,
+        };
+        Some(variant)
+    }
+
+    /// Keystone that replaces any keystone in radius, for `conqueror`
+    pub fn keystone(&self, conqueror: &str) -> Option<&'static str> {
+        // Conquerors that were later replaced share their successor's keystone
+        let keystone = match (self, conqueror) {
+            (JewelType::LethalPride, "Kaom") => "Strength of Blood",
+            (JewelType::LethalPride, "Rakiata") => "Tempered by War",
+            (JewelType::LethalPride, "Kiloava" | "Akoya") => "Chainbreaker",
+            (JewelType::BrutalRestraint, "Asenath") => "Dance with Death",
+            (JewelType::BrutalRestraint, "Nasima") => "Second Sight",
+            (JewelType::BrutalRestraint, "Balbala" | "Deshret") => "The Traitor",
+            (JewelType::GloriousVanity, "Doryani") => "Corrupted Soul",
+            (JewelType::GloriousVanity, "Xibaqua") => "Divine Flesh",
+            (JewelType::GloriousVanity, "Zerphi" | "Ahuana") => "Immortal Ambition",
+            (JewelType::ElegantHubris, "Cadiro") => "Supreme Decadence",
+            (JewelType::ElegantHubris, "Victario") => "Supreme Grandstanding",
+            (JewelType::ElegantHubris, "Chitus" | "Caspiro") => "Supreme Ostentation",
+            (JewelType::MilitantFaith, "Avarius") => "Power of Purpose",
+            (JewelType::MilitantFaith, "Dominus") => "Inner Conviction",
+            (JewelType::MilitantFaith, "Maxarius" | "Venarius") => "Transcendence",
+            _ => return None,
+        };
+        Some(keystone)
+    }
+
+    /// Seeds this jewel type can roll
+    pub fn seed_range(&self) -> RangeInclusive<u32> {
+        match self {
+            JewelType::LethalPride => 10000..=18000,
+            JewelType::BrutalRestraint => 500..=8000,
+            JewelType::GloriousVanity => 100..=8000,
+            JewelType::ElegantHubris => 2000..=160000,
+            JewelType::MilitantFaith => 2000..=10000,
+        }
+    }
+
+    /// Whether `seed` can exist on this jewel type
+    ///
+    /// Elegant Hubris seeds are multiples of 20.
+    pub fn is_valid_seed(&self, seed: u32) -> bool {
+        let step_ok = *self != JewelType::ElegantHubris || seed.is_multiple_of(20);
+        self.seed_range().contains(&seed) && step_ok
+    }
+
+    /// Read the seed and conqueror from a mod line like
+    /// "Bathed in the blood of 2000 sacrificed in the name of Doryani"
+    pub fn parse_seed_line(&self, line: &str) -> Option<(u32, &'static str)> {
+        let seed = line
+            .split_whitespace()
+            .find_map(|word| word.parse::<u32>().ok())?;
+
+        let last_word = line.split_whitespace().last()?;
+        let conqueror = self
+            .conquerors()
+            .iter()
+            .find(|name| last_word.eq_ignore_ascii_case(name))?;
+
+        Some((seed, conqueror))
+    }
 }
 
 /// A timeless jewel item
@@ -56,10 +170,29 @@ pub struct TimelessJewel {
     pub conqueror: String,
 
     /// Raw JSON data from the game
+    ///
+    /// Left out of the serialized jewel to keep results small; use
+    /// `TimelessJewelRecord` to keep it.
     #[serde(skip)]
     raw_data: Value,
 }
 
+/// Serialized form of a `TimelessJewel` that keeps every field
+///
+/// Use it wherever jewels are stored and read back (e.g., sessions), so the
+/// id and the original item data survive the round trip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelessJewelRecord {
+    pub id: String,
+    pub jewel_type: JewelType,
+    pub seed: u32,
+    pub conqueror: String,
+
+    /// Raw JSON data from the game, if any
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub raw_data: Value,
+}
+
 impl TimelessJewel {
     /// Create a new timeless jewel
     pub fn new(
@@ -78,6 +211,72 @@ impl TimelessJewel {
         }
     }
 
+    /// Parse the text the game copies to the clipboard with Ctrl+C
+    ///
+    /// Item text carries no item id, so the id is built from the jewel
+    /// type, seed and conqueror.
+    pub fn from_item_text(text: &str) -> Result<Self, AnalysisError> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && *line != SECTION_SEPARATOR)
+            .collect();
+
+        if !lines.iter().any(|line| line.starts_with("Rarity:")) {
+            return Err(AnalysisError::InvalidItemData(
+                "not Path of Exile item text".to_string(),
+            ));
+        }
+
+        let base = lines
+            .iter()
+            .position(|line| *line == TIMELESS_JEWEL)
+            .ok_or_else(|| AnalysisError::InvalidItemData("not a Timeless Jewel".to_string()))?;
+
+        let name = base
+            .checked_sub(1)
+            .map(|index| lines[index])
+            .ok_or_else(|| AnalysisError::MissingField("name".to_string()))?;
+        let jewel_type = JewelType::from_str(name).ok_or_else(|| {
+            AnalysisError::InvalidItemData(format!("unknown timeless jewel \"{}\"", name))
+        })?;
+
+        let (seed, conqueror) = lines[base + 1..]
+            .iter()
+            .find_map(|line| jewel_type.parse_seed_line(line))
+            .ok_or_else(|| AnalysisError::MissingField("seed and conqueror".to_string()))?;
+
+        Ok(Self::new(
+            format!("{}:{}:{}", jewel_type.as_str(), seed, conqueror),
+            jewel_type,
+            seed,
+            conqueror.to_string(),
+            json!({ "text": text }),
+        ))
+    }
+
+    /// Copy every field, including the raw data, into a record
+    pub fn to_record(&self) -> TimelessJewelRecord {
+        TimelessJewelRecord {
+            id: self.id.clone(),
+            jewel_type: self.jewel_type,
+            seed: self.seed,
+            conqueror: self.conqueror.clone(),
+            raw_data: self.raw_data.clone(),
+        }
+    }
+
+    /// Rebuild a jewel from a record
+    pub fn from_record(record: TimelessJewelRecord) -> Self {
+        Self::new(
+            record.id,
+            record.jewel_type,
+            record.seed,
+            record.conqueror,
+            record.raw_data,
+        )
+    }
+
     /// Get the seed number
     pub fn seed(&self) -> u32 {
         self.seed
@@ -110,6 +309,153 @@ pub struct TimelessJewelMetrics {
     pub socket_results: Vec<SocketResult>,
 }
 
+/// Order of the sockets returned by `TimelessJewelMetrics::sorted_by`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketSortKey {
+    /// Highest score first
+    #[default]
+    Score,
+
+    /// Most matched mod occurrences first
+    MatchedCount,
+
+    /// Socket name, alphabetically
+    Name,
+}
+
+impl SocketSortKey {
+    /// Whether this key sorts largest first (every key but `Name`)
+    pub fn default_descending(self) -> bool {
+        self != SocketSortKey::Name
+    }
+
+    /// Order of `a` and `b` by this key, then by socket id
+    fn compare(self, a: &SocketResult, b: &SocketResult) -> Ordering {
+        let ordering = match self {
+            SocketSortKey::Score => b.score.total_cmp(&a.score),
+            SocketSortKey::MatchedCount => b.matched_count().cmp(&a.matched_count()),
+            SocketSortKey::Name => a.socket_name.cmp(&b.socket_name),
+        };
+        ordering.then_with(|| a.socket_id.cmp(&b.socket_id))
+    }
+}
+
+impl TimelessJewelMetrics {
+    /// Sockets for which `predicate` holds, in their original order
+    pub fn filter_sockets(&self, predicate: impl Fn(&SocketResult) -> bool) -> Vec<&SocketResult> {
+        self.socket_results.iter().filter(|socket| predicate(socket)).collect()
+    }
+
+    /// Sockets ordered by `key`, ties broken by socket id
+    pub fn sorted_by(&self, key: SocketSortKey) -> Vec<&SocketResult> {
+        let mut sockets: Vec<&SocketResult> = self.socket_results.iter().collect();
+        sockets.sort_by(|a, b| key.compare(a, b));
+        sockets
+    }
+
+    /// The `n` best scoring sockets, best first
+    pub fn top_sockets(&self, n: usize) -> Vec<&SocketResult> {
+        let mut sockets = self.sorted_by(SocketSortKey::Score);
+        sockets.truncate(n);
+        sockets
+    }
+
+    /// Owned copy of `filter_sockets`, for export
+    pub fn filter_sockets_owned(
+        &self,
+        predicate: impl Fn(&SocketResult) -> bool,
+    ) -> Vec<SocketResult> {
+        self.filter_sockets(predicate).into_iter().cloned().collect()
+    }
+
+    /// Owned copy of `sorted_by`, for export
+    pub fn sorted_by_owned(&self, key: SocketSortKey) -> Vec<SocketResult> {
+        self.sorted_by(key).into_iter().cloned().collect()
+    }
+
+    /// Owned copy of `top_sockets`, for export
+    pub fn top_sockets_owned(&self, n: usize) -> Vec<SocketResult> {
+        self.top_sockets(n).into_iter().cloned().collect()
+    }
+
+    /// The most of each matched mod any one socket gives
+    ///
+    /// A jewel sits in one socket, so the maxima can't be added up: "up to
+    /// 2× Double Damage (socket A) or +80 Strength (socket C)". A mod is
+    /// taken where it occurs most, then where it contributes most; among
+    /// sockets equal in both, the best scoring one wins (as ranked by
+    /// `sorted_by(SocketSortKey::Score)`).
+    pub fn summary(&self) -> JewelSummary {
+        let mut mods: Vec<ModSummary> = Vec::new();
+
+        for socket in self.sorted_by(SocketSortKey::Score) {
+            for matched in &socket.matched_mods {
+                let summary = ModSummary {
+                    mod_text: matched.mod_text.clone(),
+                    matched_pattern: matched.matched_pattern.clone(),
+                    socket_id: socket.socket_id.clone(),
+                    socket_name: socket.socket_name.clone(),
+                    count: matched.count,
+                    contribution: matched.total(),
+                };
+                let best = mods.iter_mut().find(|m| {
+                    m.mod_text == summary.mod_text && m.matched_pattern == summary.matched_pattern
+                });
+                match best {
+                    Some(best) if summary.is_more_than(best) => *best = summary,
+                    Some(_) => {}
+                    None => mods.push(summary),
+                }
+            }
+        }
+
+        mods.sort_by(|a, b| {
+            b.contribution
+                .total_cmp(&a.contribution)
+                .then_with(|| a.mod_text.cmp(&b.mod_text))
+        });
+        JewelSummary { mods }
+    }
+}
+
+/// Jewel-level rollup of the matched mods of every socket
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JewelSummary {
+    /// Each matched mod at its best socket, largest contribution first
+    pub mods: Vec<ModSummary>,
+}
+
+/// A matched mod at the socket with the most of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModSummary {
+    pub mod_text: String,
+
+    /// Weight entry that claimed the mod (see `MatchedMod::matched_pattern`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_pattern: Option<String>,
+
+    /// Socket with the most occurrences of the mod
+    pub socket_id: String,
+    pub socket_name: String,
+
+    /// Occurrences at that socket
+    pub count: usize,
+
+    /// What the mod adds to that socket's score
+    pub contribution: f64,
+}
+
+impl ModSummary {
+    /// More occurrences, or as many with a larger contribution
+    fn is_more_than(&self, other: &ModSummary) -> bool {
+        self.count
+            .cmp(&other.count)
+            .then_with(|| self.contribution.total_cmp(&other.contribution))
+            .is_gt()
+    }
+}
+
 /// Analysis result for a specific socket location
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SocketResult {
@@ -127,6 +473,59 @@ pub struct SocketResult {
 
     /// All mods this jewel provides at this socket
     pub all_mods: Vec<String>,
+
+    /// Keystone the jewel replaces at this socket (None if there is none
+    /// in radius)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystone_change: Option<KeystoneChange>,
+
+    /// Nodes with weighted mods and the points needed to reach them (empty
+    /// unless travel costs were computed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub node_contributions: Vec<NodeContribution>,
+
+    /// Points needed to reach every node that adds to the score (None unless
+    /// travel costs were computed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub travel_cost: Option<u32>,
+}
+
+impl SocketResult {
+    /// Total occurrences of the matched mods
+    pub fn matched_count(&self) -> usize {
+        self.matched_mods.iter().map(|m| m.count).sum()
+    }
+}
+
+/// What one node adds to a socket's score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeContribution {
+    /// Passive node id
+    pub node_id: u32,
+
+    /// Sum of the weights of the node's matched mods
+    pub score: f64,
+
+    /// Points from the path origin to the node (None if it can't be reached)
+    pub travel_cost: Option<u32>,
+
+    /// The node's matched mods, by the mod text and weight entry they are
+    /// counted under in the socket's `matched_mods`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched: Vec<(String, String)>,
+}
+
+/// A keystone in radius replaced by the jewel's own keystone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeystoneChange {
+    /// Keystone on the tree (e.g., "Resolute Technique")
+    pub original: String,
+
+    /// Keystone the jewel turns it into (e.g., "Strength of Blood")
+    pub replacement: String,
+
+    /// Weight assigned by user (0 if none), added to the socket score
+    pub weight: f64,
 }
 
 /// A mod that matched the user's valuable mod criteria
@@ -140,6 +539,27 @@ pub struct MatchedMod {
 
     /// How many times this mod appears
     pub count: usize,
+
+    /// Trade API stat id, for trade searches (None if unknown)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trade_stat_id: Option<String>,
+
+    /// Number in the mod text the weight is multiplied by, for weights per
+    /// value (None for flat weights and mods without a number)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+
+    /// Weight entry that claimed the mod (e.g., "+# to Strength" for
+    /// "+25 to Strength"; None in results saved before it was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_pattern: Option<String>,
+}
+
+impl MatchedMod {
+    /// What the mod adds to the score: weight × value × count
+    pub fn total(&self) -> f64 {
+        self.weight * self.value.unwrap_or(1.0) * self.count as f64
+    }
 }
 
 impl AnalyzableItem for TimelessJewel {
@@ -11,4 +11,4 @@ pub mod scoring;
 pub mod error;
 
 // Re-export commonly used types
-pub use error::{AnalysisError, DataError};
+pub use error::{AnalysisError, DataError, ExportError};
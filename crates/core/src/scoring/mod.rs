@@ -1,8 +1,10 @@
 //! Scoring systems for item analysis
 
+pub mod values;
 pub mod weighted;
 
 #[cfg(test)]
 mod tests;
 
-pub use weighted::WeightedScorer;
+pub use values::{mod_value, stat_template};
+pub use weighted::{ModMatch, WeightedScorer};
@@ -42,6 +42,9 @@ fn test_calculate_score_single_mod() {
         mod_text: "Double Damage".to_string(),
         weight: 5.0,
         count: 2,
+        trade_stat_id: None,
+        value: None,
+        matched_pattern: None,
     }];
 
     assert_eq!(scorer.calculate_score(&matched_mods), 10.0);
@@ -60,11 +63,17 @@ fn test_calculate_score_multiple_mods() {
             mod_text: "Double Damage".to_string(),
             weight: 5.0,
             count: 2,
+            trade_stat_id: None,
+            value: None,
+            matched_pattern: None,
         },
         MatchedMod {
             mod_text: "Onslaught".to_string(),
             weight: 3.0,
             count: 1,
+            trade_stat_id: None,
+            value: None,
+            matched_pattern: None,
         },
     ];
 
@@ -92,7 +101,181 @@ fn test_calculate_score_zero_weight() {
         mod_text: "Zero Weight Mod".to_string(),
         weight: 0.0,
         count: 100,
+        trade_stat_id: None,
+        value: None,
+        matched_pattern: None,
     }];
 
     assert_eq!(scorer.calculate_score(&matched_mods), 0.0);
 }
+
+#[test]
+fn test_mod_value() {
+    assert_eq!(mod_value("+25 to Strength"), Some(25.0));
+    assert_eq!(mod_value("0.4% of Life Regenerated per second"), Some(0.4));
+    assert_eq!(mod_value("12% increased Area of Effect"), Some(12.0));
+    assert_eq!(mod_value("-10% to Fire Resistance"), Some(-10.0));
+    assert_eq!(mod_value("Gain 2 Endurance Charges, 15% more"), Some(2.0));
+    assert_eq!(mod_value("Onslaught"), None);
+    assert_eq!(stat_template("-10% to Fire Resistance"), "-#% to Fire Resistance");
+}
+
+fn per_value_scorer() -> WeightedScorer {
+    let mut weights = HashMap::new();
+    weights.insert("+# to Strength".to_string(), 0.5);
+    weights.insert("#% of Life Regenerated per second".to_string(), 10.0);
+    weights.insert("#% increased Area of Effect".to_string(), 2.0);
+    weights.insert("Onslaught".to_string(), 3.0);
+    weights.insert("+# to Dexterity".to_string(), 1.0);
+
+    let per_value = ["+# to Strength", "#% of Life Regenerated per second", "Onslaught"];
+    WeightedScorer::new(weights).with_per_value(per_value.iter().map(|s| s.to_string()).collect())
+}
+
+#[test]
+fn test_match_mod_per_value() {
+    let scorer = per_value_scorer();
+
+    // Integer
+    let matched = scorer.match_mod("+25 to Strength").unwrap();
+    assert_eq!((matched.key, matched.value), ("+# to Strength", Some(25.0)));
+    assert_eq!(matched.score(), 12.5);
+    assert_eq!(scorer.match_mod("+30 to Strength").unwrap().score(), 15.0);
+
+    // Decimal, with a percent sign
+    let matched = scorer.match_mod("0.4% of Life Regenerated per second").unwrap();
+    assert_eq!(matched.value, Some(0.4));
+    assert!((matched.score() - 4.0).abs() < 1e-9);
+
+    // Without a number, the weight is flat
+    let matched = scorer.match_mod("Onslaught").unwrap();
+    assert_eq!(matched.value, None);
+    assert_eq!(matched.score(), 3.0);
+
+    // Flat entries still need the exact text
+    assert!(scorer.match_mod("12% increased Area of Effect").is_none());
+    assert!(scorer.match_mod("+10 to Dexterity").is_none());
+    assert_eq!(scorer.match_mod("+# to Dexterity").unwrap().value, None);
+}
+
+#[test]
+fn test_per_value_entry_matches_other_rolls() {
+    let weights = HashMap::from([("+25 to Strength".to_string(), 0.5)]);
+    let per_value = ["+25 to Strength".to_string()].into_iter().collect();
+    let scorer = WeightedScorer::new(weights).with_per_value(per_value);
+
+    let matched = scorer.match_mod("+30 to Strength").unwrap();
+    assert_eq!((matched.key, matched.value), ("+# to Strength", Some(30.0)));
+    assert_eq!(matched.score(), 30.0 * 0.5);
+    assert_eq!(scorer.match_mod("+25 to Strength").unwrap().score(), 25.0 * 0.5);
+    assert!(scorer.is_per_value("+# to Strength"));
+    assert_eq!(scorer.get_weight("+25 to Strength"), None);
+
+    // An explicit template keeps its own weight
+    let weights = HashMap::from([
+        ("+25 to Strength".to_string(), 0.5),
+        ("+# to Strength".to_string(), 2.0),
+    ]);
+    let per_value = ["+25 to Strength".to_string()].into_iter().collect();
+    let scorer = WeightedScorer::new(weights).with_per_value(per_value);
+    assert_eq!(scorer.match_mod("+30 to Strength").unwrap().score(), 60.0);
+}
+
+#[test]
+fn test_match_mod_precedence() {
+    let mut weights = HashMap::new();
+    weights.insert("+25 to Strength".to_string(), 2.0);
+    weights.insert("+# to Strength".to_string(), 0.5);
+    weights.insert("Double Damage".to_string(), 1.0);
+    weights.insert("5% chance to deal Double Damage".to_string(), 10.0);
+    let per_value = ["+# to Strength".to_string()].into_iter().collect();
+    let scorer = WeightedScorer::new(weights).with_per_value(per_value);
+    let keys = |scorer: &WeightedScorer, mod_text: &str| {
+        let matches = scorer.match_mods(mod_text);
+        matches.iter().map(|m| m.key.to_string()).collect::<Vec<_>>()
+    };
+
+    // Exact text wins over the stat template, and counts once
+    assert_eq!(scorer.match_mod("+25 to Strength").unwrap().key, "+25 to Strength");
+    assert_eq!(keys(&scorer, "+25 to Strength"), ["+25 to Strength"]);
+    assert_eq!(keys(&scorer, "+30 to Strength"), ["+# to Strength"]);
+
+    // Exact text wins over an entry it contains
+    assert_eq!(
+        keys(&scorer, "5% chance to deal Double Damage"),
+        ["5% chance to deal Double Damage"]
+    );
+    assert_eq!(keys(&scorer, "Double Damage"), ["Double Damage"]);
+    assert_eq!(keys(&scorer, "10% chance to deal Double Damage"), ["Double Damage"]);
+    assert!(keys(&scorer, "Onslaught").is_empty());
+
+    // Multi-count counts every entry, the exact one first, each once
+    let scorer = scorer.with_multi_count(true);
+    assert_eq!(keys(&scorer, "+25 to Strength"), ["+25 to Strength", "+# to Strength"]);
+    assert_eq!(keys(&scorer, "+# to Strength"), ["+# to Strength"]);
+    assert_eq!(keys(&scorer, "+30 to Strength"), ["+# to Strength"]);
+    assert_eq!(scorer.match_mod("+25 to Strength").unwrap().key, "+25 to Strength");
+    assert_eq!(
+        keys(&scorer, "5% chance to deal Double Damage"),
+        ["5% chance to deal Double Damage", "Double Damage"]
+    );
+}
+
+#[test]
+fn test_longest_contained_entry_wins() {
+    let mut weights = HashMap::new();
+    weights.insert("Damage".to_string(), 1.0);
+    weights.insert("Double Damage".to_string(), 5.0);
+    weights.insert("+# to Strength".to_string(), 0.5);
+    let per_value = ["+# to Strength".to_string()].into_iter().collect();
+    let scorer = WeightedScorer::new(weights).with_per_value(per_value);
+    let keys = |scorer: &WeightedScorer, mod_text: &str| {
+        let matches = scorer.match_mods(mod_text);
+        matches.iter().map(|m| m.key.to_string()).collect::<Vec<_>>()
+    };
+
+    // Both entries are in the text; the longer one claims the mod
+    let matched = scorer.match_mod("10% chance to deal Double Damage").unwrap();
+    assert_eq!((matched.key, matched.score()), ("Double Damage", 5.0));
+    assert_eq!(keys(&scorer, "10% chance to deal Double Damage"), ["Double Damage"]);
+    assert_eq!(keys(&scorer, "40% increased Damage"), ["Damage"]);
+
+    // Entries per value are found in the text's template
+    let matched = scorer.match_mod("+30 to Strength and Dexterity").unwrap();
+    assert_eq!((matched.key, matched.value), ("+# to Strength", Some(30.0)));
+
+    // Multi-count counts each contained entry, longest first
+    let scorer = scorer.with_multi_count(true);
+    assert_eq!(
+        keys(&scorer, "10% chance to deal Double Damage"),
+        ["Double Damage", "Damage"]
+    );
+    assert_eq!(keys(&scorer, "Double Damage"), ["Double Damage", "Damage"]);
+}
+
+#[test]
+fn test_calculate_score_per_value() {
+    let scorer = per_value_scorer();
+
+    let matched_mods = vec![
+        MatchedMod {
+            mod_text: "+25 to Strength".to_string(),
+            weight: 0.5,
+            count: 2,
+            trade_stat_id: None,
+            value: Some(25.0),
+            matched_pattern: None,
+        },
+        MatchedMod {
+            mod_text: "Onslaught".to_string(),
+            weight: 3.0,
+            count: 1,
+            trade_stat_id: None,
+            value: None,
+            matched_pattern: None,
+        },
+    ];
+
+    // (0.5 * 25 * 2) + (3.0 * 1)
+    assert_eq!(scorer.calculate_score(&matched_mods), 28.0);
+}
@@ -0,0 +1,49 @@
+//! Numbers in mod texts
+//!
+//! "+25 to Strength" and "+30 to Strength" are the same stat with different
+//! rolls. Weight entries marked per value match every roll of their stat
+//! (by `stat_template`) and are multiplied by the roll (by `mod_value`).
+
+/// `text` with its numbers replaced by "#" (e.g., "+# to Strength")
+pub fn stat_template(text: &str) -> String {
+    let mut template = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if !c.is_ascii_digit() {
+            template.push(c);
+            continue;
+        }
+
+        // Skip the rest of the number, decimals included
+        while let Some(&next) = chars.peek() {
+            if next.is_ascii_digit() || next == '.' {
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        template.push('#');
+    }
+
+    template
+}
+
+/// The first number in `text`, negative if a '-' comes right before it
+///
+/// A '%' after the number is part of the text, not the value: "0.4% of Life
+/// Regenerated" is 0.4. None if the text has no number.
+pub fn mod_value(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let digits = &text[start..];
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(digits.len());
+    let value: f64 = digits[..end].trim_end_matches('.').parse().ok()?;
+
+    if text[..start].ends_with('-') {
+        Some(-value)
+    } else {
+        Some(value)
+    }
+}
@@ -1,28 +1,193 @@
 //! Weighted scoring system
+//!
+//! # Matching precedence
+//!
+//! A mod text matches a weight entry in three tiers, and the first tier with
+//! a match decides:
+//!
+//! 1. Its exact text.
+//! 2. Its stat template, for entries per value ("+# to Strength" for
+//!    "+25 to Strength").
+//! 3. An entry contained in the text ("Double Damage" in "5% chance to deal
+//!    Double Damage"; entries per value in the text's template). The longest
+//!    such entry wins.
+//!
+//! The mod counts for the winning entry alone, so weighting "+25 to Strength"
+//! and "+# to Strength" doesn't score the same mod twice. A config can opt
+//! into multi-count to have a mod count for every entry it matches.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::items::MatchedMod;
 
+use super::values::{mod_value, stat_template};
+
 /// Weighted scorer for calculating item scores based on matched mods
 #[derive(Debug, Clone)]
 pub struct WeightedScorer {
     /// Mod weights
     weights: HashMap<String, f64>,
+
+    /// Weight entries multiplied by the number in the mod text
+    per_value: HashSet<String>,
+
+    /// Whether mods count for every entry they match
+    multi_count: bool,
+}
+
+/// A mod text matched to a weight entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModMatch<'a> {
+    /// The weight entry (a stat template for entries per value)
+    pub key: &'a str,
+
+    pub weight: f64,
+
+    /// Number in the mod text, for entries per value
+    pub value: Option<f64>,
+}
+
+impl ModMatch<'_> {
+    /// What one occurrence of the mod adds to the score
+    pub fn score(&self) -> f64 {
+        self.weight * self.value.unwrap_or(1.0)
+    }
 }
 
 impl WeightedScorer {
     /// Create a new weighted scorer
     pub fn new(weights: HashMap<String, f64>) -> Self {
-        Self { weights }
+        Self {
+            weights,
+            per_value: HashSet::new(),
+            multi_count: false,
+        }
+    }
+
+    /// Multiply the weights of `entries` by the number in the mod text
+    ///
+    /// An entry per value matches every roll of its stat: "+# to Strength"
+    /// (or "+25 to Strength") matches "+30 to Strength" for 30 × its weight.
+    /// Mods without a number count their flat weight.
+    ///
+    /// Entries are keyed by their stat template, so a weight given for
+    /// "+25 to Strength" moves to "+# to Strength" unless that has one.
+    pub fn with_per_value(mut self, entries: HashSet<String>) -> Self {
+        self.per_value = entries
+            .into_iter()
+            .map(|entry| {
+                let template = stat_template(&entry);
+                if template != entry {
+                    if let Some(weight) = self.weights.remove(&entry) {
+                        self.weights.entry(template.clone()).or_insert(weight);
+                    }
+                }
+                template
+            })
+            .collect();
+        self
+    }
+
+    /// Count mods for every entry they match, not just the one that wins
+    pub fn with_multi_count(mut self, enabled: bool) -> Self {
+        self.multi_count = enabled;
+        self
     }
 
     /// Calculate score from matched mods
     pub fn calculate_score(&self, matched_mods: &[MatchedMod]) -> f64 {
-        matched_mods
+        matched_mods.iter().map(MatchedMod::total).sum()
+    }
+
+    /// The weight entry `mod_text` matches, if any
+    ///
+    /// Texts are matched exactly first, then by their stat template for
+    /// entries per value, then by the longest entry they contain (see the
+    /// module docs).
+    pub fn match_mod(&self, mod_text: &str) -> Option<ModMatch<'_>> {
+        self.exact_match(mod_text)
+            .or_else(|| self.template_match(mod_text))
+            .or_else(|| self.substring_matches(mod_text).into_iter().next())
+    }
+
+    /// Every weight entry `mod_text` counts for
+    ///
+    /// The entry `match_mod` picks, or with multi-count each entry the text
+    /// matches, in the order of the tiers and each once.
+    pub fn match_mods(&self, mod_text: &str) -> Vec<ModMatch<'_>> {
+        if !self.multi_count {
+            return self.match_mod(mod_text).into_iter().collect();
+        }
+
+        let mut matches: Vec<ModMatch<'_>> = Vec::new();
+        let tiers = self
+            .exact_match(mod_text)
+            .into_iter()
+            .chain(self.template_match(mod_text))
+            .chain(self.substring_matches(mod_text));
+        for tier_match in tiers {
+            if !matches.iter().any(|m| m.key == tier_match.key) {
+                matches.push(tier_match);
+            }
+        }
+        matches
+    }
+
+    fn exact_match(&self, mod_text: &str) -> Option<ModMatch<'_>> {
+        let (key, weight) = self.weights.get_key_value(mod_text)?;
+        let value = self.is_per_value(key).then(|| mod_value(mod_text)).flatten();
+        Some(ModMatch { key, weight: *weight, value })
+    }
+
+    fn template_match(&self, mod_text: &str) -> Option<ModMatch<'_>> {
+        if self.per_value.is_empty() {
+            return None;
+        }
+
+        let template = stat_template(mod_text);
+        let (key, weight) = self.weights.get_key_value(&template)?;
+        if !self.is_per_value(key) {
+            // Flat entries only match their exact text
+            return None;
+        }
+        Some(ModMatch {
+            key,
+            weight: *weight,
+            value: mod_value(mod_text),
+        })
+    }
+
+    /// Entries contained in `mod_text` but not equal to it, longest first
+    ///
+    /// Entries per value are looked for in the text's stat template. Ties
+    /// are ordered by text.
+    fn substring_matches(&self, mod_text: &str) -> Vec<ModMatch<'_>> {
+        let template = (!self.per_value.is_empty()).then(|| stat_template(mod_text));
+
+        let mut matches: Vec<ModMatch<'_>> = self
+            .weights
             .iter()
-            .map(|m| m.weight * m.count as f64)
-            .sum()
+            .filter(|(key, _)| !key.is_empty() && key.as_str() != mod_text)
+            .filter_map(|(key, weight)| {
+                let per_value = self.is_per_value(key);
+                let text = match &template {
+                    Some(template) if per_value => template.as_str(),
+                    _ => mod_text,
+                };
+                if !text.contains(key.as_str()) || text == key.as_str() {
+                    return None;
+                }
+                let value = per_value.then(|| mod_value(mod_text)).flatten();
+                Some(ModMatch { key, weight: *weight, value })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.key.len().cmp(&a.key.len()).then_with(|| a.key.cmp(b.key)));
+        matches
+    }
+
+    /// Every mod weight
+    pub fn weights(&self) -> &HashMap<String, f64> {
+        &self.weights
     }
 
     /// Get weight for a specific mod
@@ -34,4 +199,9 @@ impl WeightedScorer {
     pub fn is_valuable(&self, mod_text: &str) -> bool {
         self.weights.contains_key(mod_text)
     }
+
+    /// Whether the weight entry `key` is multiplied by mod values
+    pub fn is_per_value(&self, key: &str) -> bool {
+        self.per_value.contains(key)
+    }
 }
@@ -0,0 +1,49 @@
+//! Integration test: socket membership computed from passive tree geometry
+
+use poe_item_analyzer_core::data::{JewelSocket, NodeKind, PassiveTree, LARGE_JEWEL_RADIUS};
+
+const TREE: &str = include_str!("fixtures/passive_tree.json");
+const MEMBERSHIP: &str = include_str!("fixtures/socket_membership.json");
+
+#[test]
+fn test_computed_sockets_match_fixture() {
+    let tree = PassiveTree::from_json(TREE).unwrap();
+    let expected: Vec<JewelSocket> = serde_json::from_str(MEMBERSHIP).unwrap();
+
+    assert_eq!(tree.sockets(), expected);
+}
+
+#[test]
+fn test_node_positions_follow_orbits() {
+    let tree = PassiveTree::from_json(TREE).unwrap();
+    let position = |id: u32| {
+        let node = tree.node(id).unwrap();
+        (node.x.round(), node.y.round())
+    };
+
+    // 16-slot orbit: slot 4 is at 90°, clockwise from the top
+    assert_eq!(position(202), (1162.0, 0.0));
+    // 40-slot orbit: slot 10 is at 90°, slot 30 at 270°
+    assert_eq!(position(401), (1993.0, -900.0));
+    assert_eq!(position(402), (1007.0, -900.0));
+    // 6-slot orbit: evenly spaced
+    assert_eq!(position(301), (2000.0, 582.0));
+
+    // The root has no group, and proxy groups are placeholders
+    assert!(tree.node(801).is_none());
+    assert_eq!(tree.node(303).unwrap().kind, NodeKind::Mastery);
+    assert_eq!(tree.node(601).unwrap().kind, NodeKind::Ascendancy);
+}
+
+#[test]
+fn test_nodes_in_radius() {
+    let tree = PassiveTree::from_json(TREE).unwrap();
+
+    assert_eq!(tree.nodes_in_radius(26725, 1200.0).unwrap(), vec![201, 202, 203]);
+    assert_eq!(
+        tree.nodes_in_radius(26725, LARGE_JEWEL_RADIUS).unwrap(),
+        vec![201, 202, 203, 402, 701]
+    );
+    assert!(tree.nodes_in_radius(12345, LARGE_JEWEL_RADIUS).is_none());
+    assert!(PassiveTree::from_json("{}").is_err());
+}
@@ -23,16 +23,25 @@ fn test_realistic_scoring_scenario() {
             mod_text: "5% chance to deal Double Damage".to_string(),
             weight: 10.0,
             count: 2, // Found 2 nodes with this mod
+            trade_stat_id: None,
+            value: None,
+            matched_pattern: None,
         },
         MatchedMod {
             mod_text: "Onslaught on Hit".to_string(),
             weight: 8.0,
             count: 1,
+            trade_stat_id: None,
+            value: None,
+            matched_pattern: None,
         },
         MatchedMod {
             mod_text: "+20 to Strength".to_string(),
             weight: 2.0,
             count: 5,
+            trade_stat_id: None,
+            value: None,
+            matched_pattern: None,
         },
     ];
 
@@ -45,6 +54,9 @@ fn test_realistic_scoring_scenario() {
         mod_text: "Endurance Charge on Kill".to_string(),
         weight: 5.0,
         count: 1,
+        trade_stat_id: None,
+        value: None,
+        matched_pattern: None,
     }];
 
     let score_2 = scorer.calculate_score(&matched_mods_2);
@@ -67,6 +79,9 @@ fn test_scoring_comparison() {
         mod_text: "High Value Mod".to_string(),
         weight: 100.0,
         count: 1,
+        trade_stat_id: None,
+        value: None,
+        matched_pattern: None,
     }];
 
     // Many low-value mods
@@ -74,6 +89,9 @@ fn test_scoring_comparison() {
         mod_text: "Low Value Mod".to_string(),
         weight: 1.0,
         count: 50,
+        trade_stat_id: None,
+        value: None,
+        matched_pattern: None,
     }];
 
     let high_score = scorer.calculate_score(&high_value);
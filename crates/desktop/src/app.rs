@@ -1,551 +1,2776 @@
 //! Main application state
 
 use egui::Context;
-use poe_item_analyzer_api::parser::{PobDataParser, LutData};
-use poe_item_analyzer_api::DataDownloader;
-use std::path::PathBuf;
+use poe_item_analyzer_api::parser::{
+    JewelStatistics, LutData, ModifierIndex, ParseEvent, ParseReport, PobDataParser,
+};
+use poe_item_analyzer_api::{
+    progress_channel, CancellationToken, ClipboardTextSource, CompositeFetch, CompositeSource, DataDownloader,
+    DataManifest, DownloadError, DownloadEvent, FileContext, FileOperation, GitHubClient,
+    HistoryRun, HistoryStore,
+    HttpConfig, ItemSource, DataLayout, LocalFileSource, LutHandle, LutService, PoeApiClient,
+    RepairReport,
+    SourceError, SourceReport, StashTab, StashTabSource, UpdateChecker, UpdateEvent, UpdateInfo,
+    RunKind, UpdateOutcome, UpdateStage, UpdateWatcher,
+};
+use poe_item_analyzer_api::layout::MANIFEST_FILE;
+use poe_item_analyzer_api::sources::ExtractedJewels;
+use poe_item_analyzer_core::analyzers::{
+    Analyzer, BatchItem, BatchResult, CancelFlag, JewelComparison, RankedResult, ResultExportV1,
+    RunEntry, SearchProgress, SeedSearchResult, SeedSearcher, TimelessJewelAnalysisResult,
+    TimelessJewelAnalyzer,
+};
+use poe_item_analyzer_core::items::{Item, ItemCollection, JewelType, TimelessJewel};
+use poe_item_analyzer_core::DataError;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver, Sender};
-
-/// Download files with progress reporting
-async fn download_with_progress(
-    temp_dir: PathBuf,
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, info_span, warn, Instrument};
+
+use crate::export::{export_buttons, save_export, ExportRow};
+use crate::logging::LogBuffer;
+use crate::profiles::ProfileStore;
+use crate::runtime::TaskRuntime;
+use crate::session::{load_session, save_session, RestoredSession, Session, SESSION_VERSION};
+use crate::settings::{legacy_data_dir, migrate_legacy_data, Settings, SettingsStore};
+use crate::ui::compare::CompareState;
+use crate::ui::data_dir::{
+    data_dir_from_dropped, describe_problems, validate_data_dir, DataDirState, FileStatus,
+};
+use crate::ui::history::HistoryState;
+use crate::ui::log::LogPanel;
+use crate::ui::mods::ModsState;
+use crate::ui::seed_search::SeedSearchState;
+use crate::ui::stash::{import_summary, StashAction, StashImportState};
+use crate::ui::timeless_jewels::AnalysisState;
+use crate::ui::toast::Toasts;
+use crate::ui::weights::WeightEditor;
+
+/// How often to check GitHub for new PoB data
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+
+/// Binary LUT cache written to the parsed data directory after each parse
+const LUT_CACHE_FILE: &str = "lut_data.bin";
+
+/// LUT written by in-place data updates
+const UPDATE_ARTIFACT_FILE: &str = "lut_data.json";
+
+/// Download the PoB data as a version of `layout`, forwarding progress to the UI
+///
+/// Files are staged and swapped in at the end, so a failed or interrupted
+/// download leaves the existing data untouched. Returns the downloaded version.
+async fn download_pob_data(
+    layout: DataLayout,
+    http: HttpConfig,
     tx: Sender<AsyncMessage>,
-) -> Result<PathBuf, String> {
-    eprintln!("DEBUG: Starting download_with_progress");
-
-    // Create target directory
-    std::fs::create_dir_all(&temp_dir)
-        .map_err(|e| {
-            eprintln!("DEBUG: Failed to create directory: {}", e);
-            format!("Failed to create directory: {}", e)
-        })?;
-
-    eprintln!("DEBUG: Directory created: {}", temp_dir.display());
-
-    // List of files to download
-    let files = vec![
-        "NodeIndexMapping.lua",
-        "LegionPassives.lua",
-        "LethalPride.zip",
-        "BrutalRestraint.zip",
-        "ElegantHubris.zip",
-        "MilitantFaith.zip",
-        "GloriousVanity.zip.part0",
-        "GloriousVanity.zip.part1",
-        "GloriousVanity.zip.part2",
-        "GloriousVanity.zip.part3",
-        "GloriousVanity.zip.part4",
-    ];
-
-    let total = files.len();
-    let base_url = "https://raw.githubusercontent.com/PathOfBuildingCommunity/PathOfBuilding/master/src/Data/TimelessJewelData";
-
-    eprintln!("DEBUG: Creating reqwest client");
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| {
-            eprintln!("DEBUG: Failed to create client: {}", e);
-            format!("Failed to create HTTP client: {}", e)
-        })?;
-
-    eprintln!("DEBUG: Starting download loop for {} files", total);
-
-    for (index, file_name) in files.iter().enumerate() {
-        let current = index + 1;
-
-        eprintln!("DEBUG: Downloading file {}/{}: {}", current, total, file_name);
-
-        // Send progress update
-        if let Err(e) = tx.send(AsyncMessage::DownloadProgress {
-            current,
-            total,
-            file_name: file_name.to_string(),
-        }) {
-            eprintln!("DEBUG: Failed to send progress: {}", e);
-        }
-
-        let url = format!("{}/{}", base_url, file_name);
-        eprintln!("DEBUG: URL: {}", url);
-
-        let response = client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| {
-                eprintln!("DEBUG: Request failed: {}", e);
-                format!("Failed to download {}: {}", file_name, e)
-            })?;
-
-        eprintln!("DEBUG: Response status: {}", response.status());
-
-        if !response.status().is_success() {
-            return Err(format!(
-                "Failed to download {}: HTTP {}",
-                file_name,
-                response.status()
-            ));
-        }
-
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| {
-                eprintln!("DEBUG: Failed to read bytes: {}", e);
-                format!("Failed to read {}: {}", file_name, e)
-            })?;
-
-        eprintln!("DEBUG: Downloaded {} bytes", bytes.len());
-
-        let file_path = temp_dir.join(file_name);
-        std::fs::write(&file_path, &bytes)
-            .map_err(|e| {
-                eprintln!("DEBUG: Failed to write file: {}", e);
-                format!("Failed to save {}: {}", file_name, e)
-            })?;
+    cancel: CancellationToken,
+) -> Result<String, DownloadError> {
+    let manifest = DataDownloader::new(layout.root().to_path_buf())
+        .with_http_config(&http)?
+        .with_cancellation(cancel)
+        .download_version(&layout, &DataManifest::default_pob(), progress_channel(tx))
+        .await
+        .inspect_err(|e| error!("Download failed: {}", e))?;
+
+    info!("All downloads complete");
+
+    Ok(manifest.data_version)
+}
 
-        eprintln!("DEBUG: Saved to: {}", file_path.display());
+/// Format a byte count for display (e.g., "12.4 MB")
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+
+    let bytes_f = bytes as f64;
+    if bytes_f >= MB {
+        format!("{:.1} MB", bytes_f / MB)
+    } else if bytes_f >= KB {
+        format!("{:.1} KB", bytes_f / KB)
+    } else {
+        format!("{} B", bytes)
     }
+}
 
-    eprintln!("DEBUG: All downloads complete!");
-
-    // Concatenate GloriousVanity parts into single file
-    eprintln!("DEBUG: Concatenating GloriousVanity parts...");
-    let part_files = vec![
-        "GloriousVanity.zip.part0",
-        "GloriousVanity.zip.part1",
-        "GloriousVanity.zip.part2",
-        "GloriousVanity.zip.part3",
-        "GloriousVanity.zip.part4",
-    ];
-
-    let mut glorious_vanity_data = Vec::new();
-    for part in &part_files {
-        let part_path = temp_dir.join(part);
-        let part_data = std::fs::read(&part_path)
-            .map_err(|e| format!("Failed to read {}: {}", part, e))?;
-        glorious_vanity_data.extend_from_slice(&part_data);
-        eprintln!("DEBUG: Added {} bytes from {}", part_data.len(), part);
+/// Message for a failed download or update, `action` naming which
+///
+/// Running out of disk space gets the numbers and what to do about it.
+fn download_failure_message(action: &str, error: &DownloadError) -> String {
+    match error {
+        DownloadError::InsufficientSpace { needed, available } => format!(
+            "{} needs {} of free disk space, but only {} is available. \
+             Free up some space or choose another data folder.",
+            action,
+            format_bytes(*needed),
+            format_bytes(*available)
+        ),
+        _ => format!("{} failed: {}", action, error),
     }
-
-    let glorious_vanity_path = temp_dir.join("GloriousVanity.zip");
-    std::fs::write(&glorious_vanity_path, &glorious_vanity_data)
-        .map_err(|e| format!("Failed to write GloriousVanity.zip: {}", e))?;
-
-    eprintln!("DEBUG: Created GloriousVanity.zip ({} bytes)", glorious_vanity_data.len());
-
-    Ok(temp_dir)
 }
 
 /// Messages from async tasks
 enum AsyncMessage {
-    DownloadProgress { current: usize, total: usize, file_name: String },
-    DownloadComplete(Result<PathBuf, String>),
-    ParseComplete(Result<LutData, String>),
+    Download(DownloadEvent),
+    DownloadComplete(Result<String, DownloadError>),
+    Parse(ParseEvent),
+    ParseComplete(Box<Result<(LutData, ParseReport), DataError>>),
+    RepairComplete(Box<Result<(LutData, RepairReport), DownloadError>>),
+    CacheLoaded(Box<Result<(LutData, Option<ParseReport>), String>>),
+    UpdateChecked(Box<Result<UpdateInfo, String>>),
+    Update(UpdateEvent),
+    UpdateComplete(Box<Result<UpdateOutcome, DownloadError>>),
+    ImportComplete(CompositeFetch),
+    StashTabs(Result<Vec<StashTab>, SourceError>),
+    /// Files of `dir` were checked; `load` data from it if they're usable
+    DataDirValidated {
+        dir: PathBuf,
+        files: Result<Vec<FileStatus>, DownloadError>,
+        load: bool,
+    },
+    StashImported(Result<ExtractedJewels, SourceError>),
+    AnalysisComplete(Box<Result<TimelessJewelAnalysisResult, String>>),
+    SearchProgress(SearchProgress),
+    SearchComplete(Box<Result<SeedSearchResult, String>>),
+    ModifierIndex(Arc<LutData>, Box<ModifierIndex>, Vec<JewelStatistics>),
+    CompareComplete(Box<Result<JewelComparison, String>>),
+    RankComplete(Box<Result<Vec<RankedResult<BatchResult>>, String>>),
+}
+
+impl From<DownloadEvent> for AsyncMessage {
+    fn from(event: DownloadEvent) -> Self {
+        AsyncMessage::Download(event)
+    }
+}
+
+/// Top-level tabs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Analyze,
+    Search,
+    Compare,
+    Mods,
+    History,
+    Data,
+    Log,
+    Settings,
 }
 
 /// Main application state
 pub struct AnalyzerApp {
+    /// Settings kept across restarts
+    settings: Settings,
+    /// Settings as last saved, to detect changes
+    saved_settings: Settings,
+    /// Where settings are saved (None if there is no config directory)
+    settings_store: Option<SettingsStore>,
+    /// Settings tab text fields, applied on demand
+    settings_form: SettingsForm,
+    /// Selected tab
+    tab: Tab,
+    /// Analyze tab state
+    analysis: AnalysisState,
+    /// Seed search tab state
+    seed_search: SeedSearchState,
+    /// Compare tab state
+    compare: CompareState,
+    /// Modifier browser state
+    mods: ModsState,
+    /// Recorded runs shown on the history tab
+    history: HistoryState,
     /// Parser test tab state
     parser_test: ParserTestState,
+    /// Imported jewels
+    import: ImportState,
+    /// Stash tab import panel
+    stash: StashImportState,
+    /// Per-file status of the data directory
+    data_dir: DataDirState,
+    /// Data versions installed in the data directory, newest first
+    data_versions: Vec<String>,
+    /// Path of Exile API client, shared so imports go through one rate limiter
+    poe_client: Arc<PoeApiClient>,
+    /// Notifications
+    toasts: Toasts,
+    /// Log panel fed by the tracing subscriber
+    log: LogPanel,
     /// Channel receiver for async messages
     rx: Receiver<AsyncMessage>,
     /// Channel sender for async messages
     tx: Sender<AsyncMessage>,
+    /// Runtime for downloads, update checks and imports
+    runtime: TaskRuntime,
+    /// Background update checker (stopped when dropped)
+    _update_watcher: Option<UpdateWatcher>,
+    /// Updates reported by the watcher
+    update_rx: Option<Receiver<UpdateInfo>>,
+    /// Newest available data update not yet dismissed
+    available_update: Option<UpdateInfo>,
+    /// Whether the banner is asking to confirm the update's download
+    confirming_update: bool,
+    /// Data version of the opened session's results, until they're checked
+    /// against the loaded data
+    session_lut_version: Option<String>,
+    /// Problems opening a session, shown until dismissed
+    session_dialog: Option<SessionDialog>,
+}
+
+/// Dialog listing what went wrong opening a session
+struct SessionDialog {
+    title: String,
+    lines: Vec<String>,
+}
+
+/// Text fields of the settings tab
+#[derive(Default)]
+struct SettingsForm {
+    /// Data directory as typed
+    data_dir: String,
+    /// GitHub token as typed
+    github_token: String,
+    /// Path of Exile account name as typed
+    poe_account: String,
+    /// POESESSID as typed
+    poe_session_id: String,
+    /// Proxy URL as typed
+    proxy: String,
 }
 
 /// State for parser testing UI
+#[derive(Default)]
 struct ParserTestState {
     /// Selected data directory path
     data_dir: String,
-    /// Parsed LUT data (if successful)
-    parsed_data: Option<LutData>,
+    /// Parsed LUT data (if successful), shared with running analyses
+    lut: Option<LutService>,
+    /// Statistics from the last successful parse
+    parse_report: Option<ParseReport>,
     /// Error message (if parsing failed)
     error_message: Option<String>,
-    /// Whether parsing is in progress
+    /// Whether downloading the data again could fix the error
+    redownload_suggested: bool,
+    /// Whether the data failed to parse, so repairing it could fix the error
+    repair_suggested: bool,
+    /// Whether parsing (or loading the cached LUT) is in progress
     parsing: bool,
+    /// Whether the cached LUT is being loaded instead of parsing
+    loading_cache: bool,
     /// Whether downloading is in progress
     downloading: bool,
+    /// Cancels the in-flight download, if any
+    cancel_token: Option<CancellationToken>,
     /// Download progress
     download_progress: Option<(usize, usize, String)>, // (current, total, current_file)
+    /// Byte progress within the current file
+    download_bytes: Option<(u64, Option<u64>)>, // (downloaded, total)
+    /// Stage of the running data update, if one is running
+    update_stage: Option<UpdateStage>,
+    /// Parse progress
+    parse_progress: Option<(usize, usize, String)>, // (completed, total, current_file)
     /// Parsing log messages
     log_messages: Vec<String>,
 }
 
-impl Default for ParserTestState {
-    fn default() -> Self {
-        // Default to temp directory for downloads
-        let temp_dir = std::env::temp_dir().join("poe-item-analyzer-test");
+impl ParserTestState {
+    /// Current parsed data, if any
+    fn data(&self) -> Option<Arc<LutData>> {
+        self.lut.as_ref().map(LutService::data)
+    }
+
+    /// Handle to the parsed data for an analysis on another thread
+    fn lut_handle(&self) -> Option<LutHandle> {
+        self.lut.as_ref().map(LutService::handle)
+    }
 
-        Self {
-            data_dir: temp_dir.display().to_string(),
-            parsed_data: None,
-            error_message: None,
-            parsing: false,
-            downloading: false,
-            download_progress: None,
-            log_messages: Vec::new(),
+    /// Use newly parsed data, swapping it in under running analyses
+    fn set_data(&mut self, data: LutData) {
+        match &self.lut {
+            Some(lut) => {
+                lut.swap(data);
+            }
+            None => self.lut = Some(LutService::new(data)),
         }
     }
+
+    /// Log and show `message`, offering a re-download if that could fix `error`
+    fn show_error(&mut self, message: String, error: DataError) {
+        self.log_messages.push(format!("✗ {}", message));
+        self.redownload_suggested = error.needs_redownload();
+        self.error_message = Some(message);
+    }
+}
+
+/// State for importing jewels from item sources
+#[derive(Default)]
+struct ImportState {
+    /// Whether an import is in progress
+    importing: bool,
+    /// Jewels imported so far, without duplicates
+    jewels: ItemCollection<TimelessJewel>,
+    /// Per-source results of the last import
+    reports: Vec<SourceReport>,
+    /// Whether the session jewels are being ranked
+    ranking: bool,
+    /// Rank and best score from the last ranking, by jewel id
+    ranks: HashMap<String, (usize, f64)>,
+    /// Results of the last ranking, best first
+    ranked: Vec<RankedResult<TimelessJewelAnalysisResult>>,
+    /// Ids of jewels the user already owns
+    owned: HashSet<String>,
+    /// Owned jewel that makes each dominated jewel pointless, by jewel id
+    dominated: HashMap<String, String>,
 }
 
 impl AnalyzerApp {
     /// Create a new application
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(_cc: &eframe::CreationContext<'_>, log: LogBuffer) -> Self {
         let (tx, rx) = channel();
 
+        let settings_store = SettingsStore::default_path().map(SettingsStore::new);
+        let first_run = settings_store.as_ref().is_some_and(|store| !store.exists());
+        let (settings, settings_warning) = settings_store
+            .as_ref()
+            .map(SettingsStore::load)
+            .unwrap_or_else(|| (Settings::default(), None));
+
         let mut app = Self {
+            saved_settings: settings.clone(),
+            settings,
+            settings_store,
+            settings_form: SettingsForm::default(),
+            tab: Tab::Analyze,
+            analysis: AnalysisState {
+                weights: WeightEditor::new(ProfileStore::default_dir().map(ProfileStore::new)),
+                ..AnalysisState::default()
+            },
+            seed_search: SeedSearchState::default(),
+            compare: CompareState::default(),
+            mods: ModsState::default(),
+            history: HistoryState {
+                stale: true,
+                ..HistoryState::default()
+            },
             parser_test: ParserTestState::default(),
+            import: ImportState::default(),
+            stash: StashImportState::default(),
+            data_dir: DataDirState::default(),
+            data_versions: Vec::new(),
+            poe_client: Arc::new(PoeApiClient::new()),
+            toasts: Toasts::default(),
+            log: LogPanel::new(log),
             rx,
             tx,
+            runtime: TaskRuntime::new().expect("Failed to create tokio runtime"),
+            _update_watcher: None,
+            update_rx: None,
+            available_update: None,
+            confirming_update: false,
+            session_lut_version: None,
+            session_dialog: None,
         };
 
+        if let Some(warning) = settings_warning {
+            app.toasts.error(warning.clone());
+            app.parser_test.log_messages.push(format!("⚠ {}", warning));
+        }
+        if first_run {
+            app.migrate_legacy_data();
+        }
+        app.migrate_flat_data();
+        app.apply_settings();
+
         // Check if data already exists
         app.check_existing_data();
+        app.validate_data_dir(false);
+        app.start_update_watcher();
 
         app
     }
 
-    /// Check if data already exists and auto-parse if it does
-    fn check_existing_data(&mut self) {
-        let temp_dir = std::env::temp_dir().join("poe-item-analyzer-test");
+    /// Copy data downloaded by older versions to the data directory
+    fn migrate_legacy_data(&mut self) {
+        let legacy_dir = legacy_data_dir();
+
+        match migrate_legacy_data(&legacy_dir, &self.settings.data_dir) {
+            Ok(true) => self.parser_test.log_messages.push(format!(
+                "✓ Copied existing data from {} to {}",
+                legacy_dir.display(),
+                self.settings.data_dir.display()
+            )),
+            Ok(false) => {}
+            Err(e) => self
+                .parser_test
+                .log_messages
+                .push(format!("✗ Could not copy existing data: {}", e)),
+        }
+    }
 
-        if !temp_dir.exists() {
-            return;
+    /// Move data saved before per-version directories into its version
+    fn migrate_flat_data(&mut self) {
+        let layout = self.layout();
+
+        match layout.migrate_flat() {
+            Ok(Some(version)) => self.parser_test.log_messages.push(format!(
+                "✓ Moved existing data to {}",
+                layout.version_dir(&version).display()
+            )),
+            Ok(None) => {}
+            Err(e) => self
+                .parser_test
+                .log_messages
+                .push(format!("✗ Could not move existing data: {}", e)),
         }
 
-        // Check if required files exist
-        let required_files = vec![
-            "NodeIndexMapping.lua",
-            "LegionPassives.lua",
-        ];
+        self.data_versions = layout.versions();
+    }
 
-        let all_exist = required_files.iter().all(|f| temp_dir.join(f).exists());
+    /// Set up the tabs from the loaded settings
+    fn apply_settings(&mut self) {
+        let jewel_type = self.settings.jewel_type;
+        self.analysis.jewel_type = jewel_type;
+        self.analysis.conqueror = jewel_type.conquerors()[0].to_string();
+        self.seed_search.jewel_type = jewel_type;
+        self.seed_search.conqueror = jewel_type.conquerors()[0].to_string();
+
+        self.analysis
+            .weights
+            .select(self.settings.active_profile.as_deref());
+
+        self.parser_test.data_dir = self.raw_dir().display().to_string();
+        self.settings_form = SettingsForm {
+            data_dir: self.settings.data_dir.display().to_string(),
+            github_token: self.settings.github_token.clone().unwrap_or_default(),
+            poe_account: self.settings.poe_account.clone(),
+            poe_session_id: self.settings.poe_session_id.clone().unwrap_or_default(),
+            proxy: self.settings.proxy.clone().unwrap_or_default(),
+        };
+        self.apply_http_config();
+    }
 
-        if all_exist {
-            self.parser_test.log_messages.push("✓ Found existing data files".to_string());
-            self.parser_test.data_dir = temp_dir.display().to_string();
-            // Auto-parse existing data
-            self.parse_directory();
+    /// Rebuild the PoE API client with the connection settings
+    ///
+    /// Downloads and update checks pick the settings up when they start.
+    fn apply_http_config(&mut self) {
+        match PoeApiClient::new().with_http_config(&self.settings.http_config()) {
+            Ok(client) => self.poe_client = Arc::new(client),
+            Err(e) => warn!("Invalid connection settings, using defaults: {}", e),
         }
     }
 
-    /// Process async messages
-    fn process_messages(&mut self) {
-        while let Ok(msg) = self.rx.try_recv() {
-            match msg {
-                AsyncMessage::DownloadProgress { current, total, file_name } => {
-                    self.parser_test.download_progress = Some((current, total, file_name.clone()));
-
-                    // Only log when starting a new file
-                    if current > 0 && current <= total {
-                        let last_log = self.parser_test.log_messages.last();
-                        let new_log = format!("  [{}/{}] Downloading: {}", current, total, file_name);
-
-                        // Update or add log message
-                        if let Some(last) = last_log {
-                            if last.contains(&format!("[{}/{}]", current, total)) {
-                                // Replace the last message
-                                if let Some(last_msg) = self.parser_test.log_messages.last_mut() {
-                                    *last_msg = new_log;
-                                }
-                            } else {
-                                self.parser_test.log_messages.push(new_log);
-                            }
-                        } else {
-                            self.parser_test.log_messages.push(new_log);
-                        }
-                    }
-                }
-                AsyncMessage::DownloadComplete(result) => {
-                    self.parser_test.downloading = false;
-                    self.parser_test.download_progress = None;
+    /// GitHub client with the connection settings and token
+    fn github_client(&self) -> GitHubClient {
+        let client = GitHubClient::new()
+            .with_http_config(&self.settings.http_config())
+            .unwrap_or_else(|e| {
+                warn!("Invalid connection settings, using defaults: {}", e);
+                GitHubClient::new()
+            });
 
-                    match result {
-                        Ok(path) => {
-                            self.parser_test.log_messages.push("✓ Download complete!".to_string());
-                            self.parser_test.data_dir = path.display().to_string();
+        match &self.settings.github_token {
+            Some(token) => client.with_token(token),
+            None => client,
+        }
+    }
 
-                            // Automatically parse after download
-                            self.parser_test.log_messages.push("Starting parse...".to_string());
-                            self.parse_directory();
-                        }
-                        Err(e) => {
-                            self.parser_test.log_messages.push(format!("✗ Download failed: {}", e));
-                            self.parser_test.error_message = Some(e);
-                        }
-                    }
-                }
-                AsyncMessage::ParseComplete(result) => {
-                    self.parser_test.parsing = false;
+    /// Copy UI selections into the settings and save them if they changed
+    fn sync_settings(&mut self) {
+        self.settings.active_profile = self.analysis.weights.active().map(str::to_string);
 
-                    match result {
-                        Ok(data) => {
-                            self.parser_test.log_messages.push("✓ Parsing successful!".to_string());
-                            self.parser_test.log_messages.push(format!("  - {} node indices", data.node_indices.len()));
-                            self.parser_test.log_messages.push(format!("  - {} modifiers", data.modifiers.len()));
-                            self.parser_test.log_messages.push(format!("  - {} jewel types", data.jewels.len()));
-
-                            for (jewel_type, jewel_data) in &data.jewels {
-                                self.parser_test.log_messages.push(format!(
-                                    "  - {}: {} seeds parsed",
-                                    jewel_type,
-                                    jewel_data.lookup_table.len()
-                                ));
-                            }
+        // Whichever tab changed the jewel type last wins
+        if self.analysis.jewel_type != self.settings.jewel_type {
+            self.settings.jewel_type = self.analysis.jewel_type;
+        } else if self.seed_search.jewel_type != self.settings.jewel_type {
+            self.settings.jewel_type = self.seed_search.jewel_type;
+        }
 
-                            self.parser_test.parsed_data = Some(data);
-                        }
-                        Err(e) => {
-                            self.parser_test.log_messages.push(format!("✗ {}", e));
-                            self.parser_test.error_message = Some(e);
-                        }
-                    }
-                }
-            }
+        if self.settings != self.saved_settings {
+            self.save_settings();
         }
     }
 
-    /// Render the parser test tab
-    fn render_parser_test(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Parser Test - PoB Data");
-        ui.add_space(10.0);
-
-        let has_data = self.parser_test.parsed_data.is_some();
-        let is_busy = self.parser_test.downloading || self.parser_test.parsing;
-
-        // Show status
-        if has_data {
-            ui.horizontal(|ui| {
-                ui.colored_label(egui::Color32::GREEN, "✓ Data loaded");
+    /// Write the settings to disk
+    fn save_settings(&mut self) {
+        // Remember the attempt even if it fails, so errors aren't repeated every frame
+        self.saved_settings = self.settings.clone();
 
-                if ui.add_enabled(!is_busy, egui::Button::new("🔄 Re-download")).clicked() {
-                    self.download_and_parse();
-                }
-            });
-        } else if !is_busy {
-            ui.label("No data loaded. Click below to download:");
-            ui.add_space(5.0);
+        let Some(store) = &self.settings_store else {
+            return;
+        };
 
-            if ui.button("🚀 Download & Parse Data").clicked() {
-                self.download_and_parse();
-            }
+        if let Err(e) = store.save(&self.settings) {
+            self.toasts.error(format!("Could not save settings: {}", e));
         }
+    }
 
-        ui.add_space(5.0);
+    /// Per-version layout of the data directory
+    fn layout(&self) -> DataLayout {
+        DataLayout::new(&self.settings.data_dir)
+    }
 
-        // Progress bars
-        if self.parser_test.downloading {
-            if let Some((current, total, file_name)) = &self.parser_test.download_progress {
-                ui.label(format!("Downloading: {} ({}/{})", file_name, current, total));
-                let progress = *current as f32 / *total as f32;
-                ui.add(egui::ProgressBar::new(progress).show_percentage());
-            } else {
-                ui.label("Initializing download...");
-                ui.add(egui::ProgressBar::new(0.0));
-            }
-        } else if self.parser_test.parsing {
-            ui.label("Parsing data files...");
-            ui.add(egui::ProgressBar::new(0.5).show_percentage());
-        }
+    /// Recorded runs in the data directory
+    fn history_store(&self) -> HistoryStore {
+        HistoryStore::in_data_dir(&self.settings.data_dir)
+    }
 
-        ui.add_space(10.0);
-        ui.separator();
+    /// Data version in use: the selected one if installed, else the newest
+    fn data_version(&self) -> String {
+        self.settings
+            .data_version
+            .as_ref()
+            .filter(|version| self.data_versions.contains(version))
+            .or(self.data_versions.first())
+            .cloned()
+            .unwrap_or_else(|| DataManifest::default_pob().data_version)
+    }
 
-        // Display results
-        if let Some(error) = &self.parser_test.error_message {
-            ui.colored_label(egui::Color32::RED, "❌ Error:");
-            ui.label(error);
-            ui.add_space(10.0);
-        }
+    /// Data files of the version in use
+    fn raw_dir(&self) -> PathBuf {
+        self.layout().raw_dir(&self.data_version())
+    }
 
-        if let Some(data) = &self.parser_test.parsed_data {
-            ui.heading("📊 Parsed Data Summary");
-            ui.add_space(5.0);
+    /// Parsed LUTs of the version in use
+    fn parsed_dir(&self) -> PathBuf {
+        self.layout().parsed_dir(&self.data_version())
+    }
 
-            egui::Grid::new("parser_stats_grid")
-                .num_columns(2)
-                .spacing([20.0, 8.0])
-                .striped(true)
-                .show(ui, |ui| {
-                    ui.label("Version:");
-                    ui.label(&data.version);
-                    ui.end_row();
+    /// Manifest of the version in use
+    fn installed_manifest_path(&self) -> PathBuf {
+        self.layout().manifest_path(&self.data_version())
+    }
 
-                    ui.label("Node Indices:");
-                    ui.label(format!("{}", data.node_indices.len()));
-                    ui.end_row();
+    /// Where the LUT parsed from `dir` is cached, and the manifest recording it
+    ///
+    /// The version in use keeps them in its own directories; any other
+    /// directory (e.g., one typed in the parser test tab) holds both itself.
+    fn cache_paths(&self, dir: &Path) -> (PathBuf, PathBuf) {
+        if dir == self.raw_dir() {
+            (self.parsed_dir(), self.installed_manifest_path())
+        } else {
+            (dir.to_path_buf(), dir.join(MANIFEST_FILE))
+        }
+    }
 
-                    ui.label("Modifiers:");
-                    ui.label(format!("{}", data.modifiers.len()));
-                    ui.end_row();
+    /// Update checker for the installed data, using the GitHub token if set
+    fn update_checker(&self) -> UpdateChecker {
+        UpdateChecker::new(self.installed_manifest_path())
+            .with_github_client(self.github_client())
+            .with_http_config(self.settings.http_config())
+    }
 
-                    ui.label("Jewel Types:");
-                    ui.label(format!("{}", data.jewels.len()));
-                    ui.end_row();
-                });
+    /// Watch for new PoB data if enabled and a downloaded manifest is present
+    fn start_update_watcher(&mut self) {
+        self._update_watcher = None;
+        self.update_rx = None;
 
-            ui.add_space(10.0);
+        if !self.settings.auto_check_updates || !self.installed_manifest_path().exists() {
+            return;
+        }
 
-            // Display jewel details
-            if !data.jewels.is_empty() {
-                ui.heading("💎 Jewel Data");
-                ui.add_space(5.0);
+        let (tx, rx) = channel();
+        let checker = self.update_checker();
 
-                egui::ScrollArea::vertical()
-                    .id_source("jewel_data_scroll")
-                    .max_height(300.0)
-                    .show(ui, |ui| {
-                        for (idx, (jewel_type, jewel_data)) in data.jewels.iter().enumerate() {
-                            ui.group(|ui| {
-                                ui.strong(jewel_type);
+        self._update_watcher = Some(UpdateWatcher::spawn_on(
+            self.runtime.handle(),
+            checker,
+            UPDATE_CHECK_INTERVAL,
+            tx,
+        ));
+        self.update_rx = Some(rx);
+    }
 
-                                ui.horizontal(|ui| {
-                                    ui.label("Seed Range:");
-                                    ui.monospace(format!("{} - {}",
-                                        jewel_data.seed_range.0,
-                                        jewel_data.seed_range.1
-                                    ));
-                                });
+    /// Render the "new data available" banner
+    fn render_update_banner(&mut self, ui: &mut egui::Ui) {
+        let Some(info) = &self.available_update else {
+            return;
+        };
 
-                                ui.horizontal(|ui| {
-                                    ui.label("Seeds with data:");
-                                    ui.monospace(format!("{}", jewel_data.lookup_table.len()));
-                                });
+        let version = info.latest_version.as_deref().unwrap_or("unknown");
+        let short_version = &version[..version.len().min(7)];
+        let summary = info
+            .commit_message
+            .as_deref()
+            .and_then(|message| message.lines().next())
+            .unwrap_or("");
+        let date = info
+            .commit_date
+            .as_deref()
+            .map(|date| format!(", {}", &date[..date.len().min(10)]))
+            .unwrap_or_default();
+        let tree = info
+            .tree_version
+            .as_deref()
+            .map(|tree| format!(", passive tree {}", tree))
+            .unwrap_or_default();
+        let size = info
+            .diff
+            .as_ref()
+            .filter(|diff| diff.download_count() > 0)
+            .map(|diff| {
+                format!(
+                    " ({} files, {})",
+                    diff.download_count(),
+                    format_bytes(diff.download_size())
+                )
+            })
+            .unwrap_or_default();
+
+        let confirmation = match &info.diff {
+            Some(diff) if diff.download_size() > 0 && diff.unknown_sizes() > 0 => format!(
+                "Download ~{} (plus {} file(s) of unknown size)?",
+                format_bytes(diff.download_size()),
+                diff.unknown_sizes()
+            ),
+            Some(diff) if diff.download_size() > 0 => {
+                format!("Download ~{}?", format_bytes(diff.download_size()))
+            }
+            _ => "Download the update? Its size is unknown.".to_string(),
+        };
 
-                                // Show sample seed data
-                                if let Some((seed, node_mods)) = jewel_data.lookup_table.iter().next() {
-                                    ui.horizontal(|ui| {
-                                        ui.label("Sample seed:");
-                                        ui.monospace(format!("{} ({} nodes)", seed, node_mods.len()));
-                                    });
-                                }
-                            });
+        let is_busy = self.parser_test.downloading || self.parser_test.parsing;
+        let mut update_clicked = false;
+        let mut confirm_clicked = false;
+        let mut dismiss_clicked = false;
+        let mut skip_clicked = false;
+
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                egui::Color32::LIGHT_BLUE,
+                format!(
+                    "⬆ New PoB data available ({}{}{}): {}{}",
+                    short_version, date, tree, summary, size
+                ),
+            );
+
+            if self.confirming_update {
+                ui.label(&confirmation);
+                confirm_clicked =
+                    ui.add_enabled(!is_busy, egui::Button::new("Download")).clicked();
+                if ui.button("Cancel").clicked() {
+                    self.confirming_update = false;
+                }
+            } else {
+                update_clicked =
+                    ui.add_enabled(!is_busy, egui::Button::new("Update now")).clicked();
+                dismiss_clicked = ui.button("Dismiss").clicked();
+                skip_clicked = ui.button("Skip this version").clicked();
+            }
+        });
+        ui.separator();
 
-                            if idx < data.jewels.len() - 1 {
-                                ui.add_space(5.0);
-                            }
-                        }
-                    });
+        if update_clicked {
+            self.confirming_update = true;
+        } else if confirm_clicked {
+            self.available_update = None;
+            self.confirming_update = false;
+            self.perform_update();
+        } else if dismiss_clicked {
+            self.available_update = None;
+        } else if skip_clicked {
+            let checker = self.update_checker();
+
+            if let Err(e) = checker.ignore_version(version) {
+                self.parser_test.log_messages.push(format!("✗ Could not skip version: {}", e));
             }
+            self.available_update = None;
         }
+    }
 
-        // Log messages
-        if !self.parser_test.log_messages.is_empty() {
-            ui.add_space(10.0);
-            ui.separator();
+    /// Check for new data once on a background thread
+    ///
+    /// Failures only end up in the log; they never interrupt the user.
+    fn check_for_updates(&mut self) {
+        self.parser_test.log_messages.push("Checking for updates...".to_string());
 
-            ui.collapsing("📋 Parse Log", |ui| {
-                egui::ScrollArea::vertical()
-                    .id_source("parse_log_scroll")
-                    .max_height(200.0)
-                    .stick_to_bottom(true)
-                    .show(ui, |ui| {
-                        for (idx, msg) in self.parser_test.log_messages.iter().enumerate() {
-                            ui.label(msg);
-                            if idx < self.parser_test.log_messages.len() - 1 {
-                                ui.add_space(2.0);
-                            }
-                        }
-                    });
-            });
-        }
-    }
+        let checker = self.update_checker();
 
-    /// Download data from GitHub and parse it
-    fn download_and_parse(&mut self) {
-        eprintln!("DEBUG: download_and_parse called");
+        self.runtime.spawn_task(self.tx.clone(), async move {
+            let result = checker.check_for_updates().await.map_err(|e| e.to_string());
+            AsyncMessage::UpdateChecked(Box::new(result))
+        });
+    }
 
+    /// Download, verify and re-parse the latest data in the background
+    fn perform_update(&mut self) {
         self.parser_test.downloading = true;
         self.parser_test.error_message = None;
-        // Don't clear parsed_data here - keep it until new data is ready
-        self.parser_test.log_messages.clear();
+        self.parser_test.redownload_suggested = false;
+        self.parser_test.repair_suggested = false;
         self.parser_test.download_progress = None;
+        self.parser_test.download_bytes = None;
+        self.parser_test.update_stage = None;
+        self.parser_test.parse_progress = None;
+        self.parser_test.log_messages.push("Updating PoB data...".to_string());
+
+        let cancel = CancellationToken::new();
+        self.parser_test.cancel_token = Some(cancel.clone());
+
+        let progress_tx = self.tx.clone();
+        let checker = self
+            .update_checker()
+            .with_layout(self.layout())
+            .with_cancellation(cancel);
+        let data_dir = self.raw_dir();
+        let artifact_path = self.parsed_dir().join(UPDATE_ARTIFACT_FILE);
+
+        self.runtime.spawn_task(self.tx.clone(), async move {
+            let result = checker
+                .perform_update(&data_dir, &artifact_path, |event| {
+                    let _ = progress_tx.send(AsyncMessage::Update(event));
+                })
+                .await;
+
+            AsyncMessage::UpdateComplete(Box::new(result))
+        });
+    }
 
-        let temp_dir = std::env::temp_dir().join("poe-item-analyzer-test");
-        self.parser_test.log_messages.push(format!("Download directory: {}", temp_dir.display()));
-        self.parser_test.log_messages.push("Starting download...".to_string());
+    /// Use `dir` as the data directory, loading its data if the files are usable
+    fn select_data_dir(&mut self, dir: PathBuf) {
+        if self.parser_test.downloading || self.parser_test.parsing {
+            self.toasts.error("Wait for the current download or parse to finish");
+            return;
+        }
 
-        let tx = self.tx.clone();
+        self.settings.data_dir = dir;
+        self.settings.data_version = None;
+        self.history.stale = true;
+        self.migrate_flat_data();
+        self.settings_form.data_dir = self.settings.data_dir.display().to_string();
+        self.parser_test.data_dir = self.raw_dir().display().to_string();
+        self.parser_test.lut = None;
+        self.parser_test.parse_report = None;
+
+        self.validate_data_dir(true);
+        self.start_update_watcher();
+    }
 
-        eprintln!("DEBUG: Spawning thread with tokio runtime");
+    /// Switch to another installed data version, loading its data
+    ///
+    /// The current data stays loaded until the other version's is ready.
+    fn select_data_version(&mut self, version: String) {
+        if self.parser_test.downloading || self.parser_test.parsing {
+            self.toasts.error("Wait for the current download or parse to finish");
+            return;
+        }
 
-        // Spawn a thread with its own tokio runtime
-        std::thread::spawn(move || {
-            eprintln!("DEBUG: Thread started, creating tokio runtime");
+        self.parser_test
+            .log_messages
+            .push(format!("Switching to data version {}", version));
+        self.settings.data_version = Some(version);
+        self.parser_test.data_dir = self.raw_dir().display().to_string();
 
-            let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+        self.validate_data_dir(true);
+        self.start_update_watcher();
+    }
 
-            eprintln!("DEBUG: Running async task on runtime");
-            rt.block_on(async move {
-                eprintln!("DEBUG: Async task started");
-                let result = download_with_progress(temp_dir.clone(), tx.clone()).await;
-                eprintln!("DEBUG: Download result: {:?}", result.is_ok());
-                if let Err(e) = tx.send(AsyncMessage::DownloadComplete(result)) {
-                    eprintln!("DEBUG: Failed to send complete message: {}", e);
-                }
-            });
+    /// Pick the data directory with a folder dialog
+    fn browse_data_dir(&mut self) {
+        if let Some(dir) = rfd::FileDialog::new()
+            .set_directory(&self.settings.data_dir)
+            .pick_folder()
+        {
+            self.select_data_dir(dir);
+        }
+    }
 
-            eprintln!("DEBUG: Thread finishing");
-        });
+    /// Check the data files in the background, then load them if `load` is set
+    fn validate_data_dir(&mut self, load: bool) {
+        self.data_dir.validating = true;
 
-        eprintln!("DEBUG: Thread spawned");
+        let dir = self.raw_dir();
+        let manifest_path = self.installed_manifest_path();
+        self.runtime.spawn_task(self.tx.clone(), async move {
+            let files = validate_data_dir(dir.clone(), &manifest_path).await;
+            AsyncMessage::DataDirValidated { dir, files, load }
+        });
     }
 
-    /// Parse the selected directory
-    fn parse_directory(&mut self) {
-        self.parser_test.parsing = true;
-        self.parser_test.error_message = None;
-        self.parser_test.parsed_data = None;
+    /// Check if data already exists and load or parse it
+    ///
+    /// Uses the parsed LUT recorded in the installed manifest when it is
+    /// current, and re-parses the data files otherwise.
+    fn check_existing_data(&mut self) {
+        let temp_dir = self.raw_dir();
 
-        let path = PathBuf::from(&self.parser_test.data_dir);
+        if !temp_dir.exists() {
+            return;
+        }
+
+        let manifest = DataManifest::load_from_file(&self.installed_manifest_path())
+            .unwrap_or_else(|_| DataManifest::default_pob());
+
+        match manifest.needs_reparse(&temp_dir) {
+            Ok(false) => {
+                if let Some(artifact) = &manifest.parsed_artifact {
+                    self.parser_test.data_dir = temp_dir.display().to_string();
+                    self.load_parsed_artifact(artifact.resolve(&temp_dir), artifact.format.clone());
+                    return;
+                }
+            }
+            Ok(true) => {}
+            Err(e) => {
+                self.parser_test
+                    .log_messages
+                    .push(format!("✗ Could not check parsed data: {}", e));
+            }
+        }
+
+        let all_exist = manifest
+            .files
+            .iter()
+            .filter(|f| f.required && f.name.ends_with(".lua"))
+            .all(|f| temp_dir.join(&f.name).exists());
+
+        if all_exist {
+            self.parser_test.log_messages.push("✓ Found existing data files".to_string());
+            self.parser_test.data_dir = temp_dir.display().to_string();
+            // Auto-parse existing data
+            self.parse_directory();
+        }
+    }
+
+    /// Load a previously parsed LUT on a background thread
+    ///
+    /// Falls back to parsing the data files if the LUT can't be loaded.
+    fn load_parsed_artifact(&mut self, path: PathBuf, format: String) {
+        self.parser_test.parsing = true;
+        self.parser_test.loading_cache = true;
+        self.parser_test.error_message = None;
+        self.parser_test.redownload_suggested = false;
+        self.parser_test.repair_suggested = false;
+        self.parser_test
+            .log_messages
+            .push(format!("Loading parsed data from {}", path.display()));
+
+        let tx = self.tx.clone();
+
+        std::thread::spawn(move || {
+            let result = PobDataParser::load_artifact(&path, &format)
+                .map(|data| {
+                    let report = ParseReport::load_from_json(&ParseReport::sidecar_path(&path)).ok();
+                    (data, report)
+                })
+                .map_err(|e| e.to_string());
+
+            if let Err(e) = tx.send(AsyncMessage::CacheLoaded(Box::new(result))) {
+                warn!("Failed to send cached data: {}", e);
+            }
+        });
+    }
+
+    /// Process async messages
+    fn process_messages(&mut self) {
+        if let Some(update_rx) = &self.update_rx {
+            while let Ok(info) = update_rx.try_recv() {
+                self.available_update = Some(info);
+                self.confirming_update = false;
+            }
+        }
+
+        while let Ok(msg) = self.rx.try_recv() {
+            match msg {
+                AsyncMessage::Download(event) => self.handle_download_event(event),
+                AsyncMessage::DownloadComplete(result) => {
+                    self.parser_test.downloading = false;
+                    self.parser_test.cancel_token = None;
+                    self.parser_test.download_progress = None;
+                    self.parser_test.download_bytes = None;
+
+                    match result {
+                        Ok(version) => {
+                            self.parser_test.log_messages.push("✓ Download complete!".to_string());
+                            self.settings.data_version = Some(version);
+                            self.data_versions = self.layout().versions();
+                            self.parser_test.data_dir = self.raw_dir().display().to_string();
+                            self.validate_data_dir(false);
+
+                            // Automatically parse after download
+                            self.parser_test.log_messages.push("Starting parse...".to_string());
+                            self.parse_directory();
+                        }
+                        Err(DownloadError::Cancelled) => {
+                            // User asked for this, so it isn't an error
+                            self.parser_test.log_messages.push("Download cancelled".to_string());
+                        }
+                        Err(e) => {
+                            let message = download_failure_message("Download", &e);
+                            self.parser_test.show_error(message, e.into());
+                        }
+                    }
+                }
+                AsyncMessage::ImportComplete(fetched) => {
+                    self.import.importing = false;
+                    let added = self.import.jewels.add_all(fetched.jewels);
+                    self.import.reports = fetched.reports;
+
+                    if added > 0 && !self.import.ranking {
+                        if let Err(e) = self.rank_session(false) {
+                            debug!("Not ranking imported jewels: {}", e);
+                        }
+                    }
+                }
+                AsyncMessage::DataDirValidated { dir, files, load } => {
+                    // Ignore results for a directory that is no longer selected
+                    if dir != self.raw_dir() {
+                        continue;
+                    }
+                    self.data_dir.validating = false;
+
+                    match files {
+                        Ok(files) => {
+                            let problems = describe_problems(&files);
+                            self.data_dir.files = files;
+                            self.data_dir.error = None;
+
+                            match problems {
+                                None if load => self.check_existing_data(),
+                                None => {}
+                                Some(problems) if load => {
+                                    self.parser_test.log_messages.push(format!("✗ {}", problems));
+                                    self.toasts.error(problems);
+                                }
+                                Some(_) => {}
+                            }
+                        }
+                        Err(e) => {
+                            self.data_dir.files.clear();
+                            self.data_dir.error = Some(e.to_string());
+                        }
+                    }
+                }
+                AsyncMessage::StashTabs(result) => {
+                    self.stash.loading_tabs = false;
+
+                    match result {
+                        Ok(tabs) => {
+                            self.stash.auth_failed = false;
+                            let stash = &mut self.stash;
+                            stash.selected.retain(|index| tabs.iter().any(|t| t.index == *index));
+                            stash.tabs = tabs;
+                        }
+                        Err(e) => self.stash.set_error(&e),
+                    }
+                }
+                AsyncMessage::StashImported(result) => {
+                    self.stash.importing = false;
+
+                    match result {
+                        Ok(extracted) => {
+                            let fetched = extracted.jewels.len();
+                            // Jewels in the user's stash are already owned
+                            self.import
+                                .owned
+                                .extend(extracted.jewels.iter().map(|jewel| jewel.id()));
+                            let added = self.import.jewels.add_all(extracted.jewels);
+                            let summary =
+                                import_summary(added, fetched - added, extracted.skipped.len());
+                            info!("{}", summary);
+                            self.toasts.info(summary.clone());
+                            self.stash.summary = Some(summary);
+                            self.stash.skipped = extracted.skipped;
+
+                            if added > 0 && !self.import.ranking {
+                                if let Err(e) = self.rank_session(false) {
+                                    debug!("Not ranking stash jewels: {}", e);
+                                }
+                            }
+                        }
+                        Err(e) => self.stash.set_error(&e),
+                    }
+                }
+                AsyncMessage::RankComplete(result) => {
+                    self.import.ranking = false;
+                    self.history.stale = true;
+
+                    match *result {
+                        Ok(ranked) => {
+                            self.import.dominated = ranked
+                                .iter()
+                                .filter_map(|r| {
+                                    let owned = r.result.dominated_by.clone()?;
+                                    Some((r.result.result.jewel.id(), owned))
+                                })
+                                .collect();
+                            let ranked: Vec<_> = ranked
+                                .into_iter()
+                                .map(|r| RankedResult {
+                                    rank: r.rank,
+                                    result: r.result.result,
+                                })
+                                .collect();
+                            self.import.ranks = ranks_by_id(&ranked);
+                            self.import.ranked = ranked;
+                        }
+                        Err(e) => self.toasts.error(format!("Ranking failed: {}", e)),
+                    }
+                }
+                AsyncMessage::AnalysisComplete(result) => {
+                    self.analysis.running = false;
+
+                    match *result {
+                        Ok(result) => self.analysis.result = Some(result),
+                        Err(e) => self.analysis.error = Some(e),
+                    }
+                }
+                AsyncMessage::SearchProgress(progress) => {
+                    self.seed_search.progress = Some(progress);
+                }
+                AsyncMessage::ModifierIndex(data, index, statistics) => {
+                    self.mods.set_index(&data, *index, statistics)
+                }
+                AsyncMessage::CompareComplete(result) => {
+                    self.compare.running = false;
+
+                    match *result {
+                        Ok(comparison) => self.compare.comparison = Some(comparison),
+                        Err(e) => self.compare.error = Some(e),
+                    }
+                }
+                AsyncMessage::SearchComplete(result) => {
+                    self.seed_search.cancel = None;
+                    self.history.stale = true;
+                    self.seed_search.progress = None;
+
+                    match *result {
+                        Ok(result) => {
+                            self.seed_search.selected = None;
+                            self.seed_search.result = Some(result);
+                        }
+                        Err(e) => self.seed_search.error = Some(e),
+                    }
+                }
+                AsyncMessage::Parse(event) => self.handle_parse_event(event),
+                AsyncMessage::UpdateChecked(result) => match *result {
+                    Ok(info) if info.available => {
+                        self.parser_test.log_messages.push("⬆ New PoB data available".to_string());
+                        self.available_update = Some(info);
+                        self.confirming_update = false;
+                    }
+                    Ok(_) => {
+                        self.parser_test.log_messages.push("✓ PoB data is up to date".to_string());
+                    }
+                    Err(e) => {
+                        self.parser_test
+                            .log_messages
+                            .push(format!("Update check failed: {}", e));
+                    }
+                },
+                AsyncMessage::Update(event) => match event {
+                    UpdateEvent::StageStarted(stage) => {
+                        self.parser_test.update_stage = Some(stage);
+                        self.parser_test.download_progress = None;
+                        self.parser_test.download_bytes = None;
+                        self.parser_test.parse_progress = None;
+                        self.parser_test
+                            .log_messages
+                            .push(format!("  {}...", update_stage_label(stage)));
+                    }
+                    UpdateEvent::FileProgress(event) => self.handle_download_event(event),
+                    UpdateEvent::ParseProgress(event) => self.handle_parse_event(event),
+                    UpdateEvent::StageFinished(_) => {}
+                    UpdateEvent::Failed { stage, error } => {
+                        self.parser_test.log_messages.push(format!(
+                            "✗ {} failed: {}",
+                            update_stage_label(stage),
+                            error
+                        ));
+                    }
+                    UpdateEvent::Cancelled(stage) => {
+                        self.parser_test.log_messages.push(format!(
+                            "✖ Cancelled while {}; previous data kept",
+                            update_stage_label(stage).to_lowercase()
+                        ));
+                    }
+                },
+                AsyncMessage::UpdateComplete(result) => {
+                    self.parser_test.downloading = false;
+                    self.parser_test.cancel_token = None;
+                    self.parser_test.download_progress = None;
+                    self.parser_test.download_bytes = None;
+                    self.parser_test.parse_progress = None;
+                    self.parser_test.update_stage = None;
+
+                    match *result {
+                        Ok(outcome) if outcome.updated => {
+                            self.parser_test.log_messages.push(format!(
+                                "✓ Updated {} file(s), {} downloaded",
+                                outcome.changed_files.len(),
+                                format_bytes(outcome.bytes_downloaded)
+                            ));
+                            self.toasts.info("PoB data updated");
+                            if let Some(report) = &outcome.parse_report {
+                                self.log_parse_summary(report);
+                            }
+                            // A new version was installed alongside the old one
+                            self.settings.data_version = Some(outcome.version);
+                            self.data_versions = self.layout().versions();
+                            self.check_existing_data();
+                        }
+                        Ok(_) => {
+                            self.parser_test.log_messages.push("✓ Already up to date".to_string());
+                        }
+                        Err(DownloadError::Cancelled) => self.toasts.info("Update cancelled"),
+                        Err(e) => {
+                            let message = download_failure_message("Update", &e);
+                            self.parser_test.show_error(message, e.into());
+                        }
+                    }
+                }
+                AsyncMessage::CacheLoaded(result) => {
+                    self.parser_test.parsing = false;
+                    self.parser_test.loading_cache = false;
+
+                    match *result {
+                        Ok((data, report)) => {
+                            self.parser_test.log_messages.push("✓ Loaded parsed data".to_string());
+                            self.parser_test.set_data(data);
+                            self.parser_test.parse_report = report;
+                            self.refresh_session_results();
+                        }
+                        Err(e) => {
+                            self.parser_test
+                                .log_messages
+                                .push(format!("✗ Could not load parsed data: {}", e));
+                            self.parse_directory();
+                        }
+                    }
+                }
+                AsyncMessage::ParseComplete(result) => {
+                    self.parser_test.parsing = false;
+                    self.parser_test.parse_progress = None;
+
+                    match *result {
+                        Ok((data, report)) => {
+                            self.log_parse_summary(&report);
+                            self.parser_test.set_data(data);
+                            self.parser_test.parse_report = Some(report);
+                            self.refresh_session_results();
+                        }
+                        Err(e) => {
+                            self.parser_test.show_error(format!("Failed to parse: {}", e), e);
+                            self.parser_test.repair_suggested = true;
+                        }
+                    }
+                }
+                AsyncMessage::RepairComplete(result) => {
+                    self.parser_test.parsing = false;
+
+                    match *result {
+                        Ok((data, report)) => {
+                            let summary = if report.is_clean() {
+                                "No damaged data files found".to_string()
+                            } else {
+                                format!(
+                                    "Repaired data: {} damaged, {} downloaded again",
+                                    report.damaged.len(),
+                                    report.downloaded.len()
+                                )
+                            };
+                            for (name, reason) in &report.damaged {
+                                self.parser_test
+                                    .log_messages
+                                    .push(format!("  - {}: {}", name, reason));
+                            }
+                            self.parser_test.log_messages.push(format!("✓ {}", summary));
+                            self.toasts.info(summary);
+
+                            self.log_parse_summary(&report.parse_report);
+                            self.parser_test.set_data(data);
+                            self.parser_test.parse_report = Some(report.parse_report);
+                            self.refresh_session_results();
+                        }
+                        Err(e) => {
+                            let message = format!("Repair failed: {}", e);
+                            self.parser_test.show_error(message, DataError::from(e));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Update parse progress from a parser event
+    fn handle_parse_event(&mut self, event: ParseEvent) {
+        self.parser_test.parse_progress = Some(match event {
+            ParseEvent::FileStarted { current, total, file_name } => {
+                (current - 1, total, file_name)
+            }
+            ParseEvent::FileCompleted { current, total, file_name } => (current, total, file_name),
+        });
+    }
+
+    /// Update download progress and the log from a downloader event
+    fn handle_download_event(&mut self, event: DownloadEvent) {
+        match event {
+            DownloadEvent::FileStarted { current, total, file_name } => {
+                debug!("Downloading file {}/{}: {}", current, total, file_name);
+                self.parser_test.download_progress = Some((current, total, file_name.clone()));
+                self.parser_test.download_bytes = None;
+
+                // Only log when starting a new file
+                if current > 0 && current <= total {
+                    let last_log = self.parser_test.log_messages.last();
+                    let new_log = format!("  [{}/{}] Downloading: {}", current, total, file_name);
+
+                    // Update or add log message
+                    if let Some(last) = last_log {
+                        if last.contains(&format!("[{}/{}]", current, total)) {
+                            // Replace the last message
+                            if let Some(last_msg) = self.parser_test.log_messages.last_mut() {
+                                *last_msg = new_log;
+                            }
+                        } else {
+                            self.parser_test.log_messages.push(new_log);
+                        }
+                    } else {
+                        self.parser_test.log_messages.push(new_log);
+                    }
+                }
+            }
+            DownloadEvent::Progress(progress) => {
+                self.parser_test.download_bytes =
+                    Some((progress.bytes_downloaded, progress.total_bytes));
+            }
+            DownloadEvent::Retrying { file_name, attempt, max_attempts, reason } => {
+                warn!("Retrying {} (attempt {}/{}): {}", file_name, attempt, max_attempts, reason);
+                self.parser_test.download_bytes = None;
+                self.parser_test.log_messages.push(format!(
+                    "  ↻ Retrying {} (attempt {}/{})",
+                    file_name, attempt, max_attempts
+                ));
+            }
+            DownloadEvent::Warning(message) => {
+                warn!("{}", message);
+                self.parser_test.log_messages.push(format!("  ⚠ {}", message));
+            }
+            DownloadEvent::FileCompleted { .. } | DownloadEvent::NotModified { .. } => {}
+        }
+    }
+
+    /// Append a parse summary to the log
+    fn log_parse_summary(&mut self, report: &ParseReport) {
+        let log = &mut self.parser_test.log_messages;
+
+        log.push("✓ Parsing successful!".to_string());
+        log.push(format!("  - {} node indices", report.node_index_count));
+        log.push(format!("  - {} modifiers", report.modifier_count));
+        log.push(format!("  - {} jewel types", report.jewels.len()));
+
+        for (jewel_type, jewel_report) in &report.jewels {
+            log.push(format!(
+                "  - {}: {} seeds parsed",
+                jewel_type,
+                jewel_report.seed_count
+            ));
+        }
+
+        for warning in &report.warnings {
+            log.push(format!("  ⚠ {}", warning));
+        }
+    }
+
+    /// Render the parser test tab
+    fn render_parser_test(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Parser Test - PoB Data");
+        ui.add_space(10.0);
+
+        let has_data = self.parser_test.lut.is_some();
+        let is_busy = self.parser_test.downloading || self.parser_test.parsing;
+
+        ui.horizontal(|ui| {
+            ui.label(format!("📁 {}", self.settings.data_dir.display()));
+            if ui
+                .add_enabled(!is_busy, egui::Button::new("📂 Browse..."))
+                .on_hover_text("Or drop the data folder or its files onto the window")
+                .clicked()
+            {
+                self.browse_data_dir();
+            }
+        });
+        if !self.data_versions.is_empty() {
+            let current_version = self.data_version();
+            let mut selected_version = current_version.clone();
+            let short_version = |version: &str| version.get(..7).unwrap_or(version).to_string();
+
+            ui.horizontal(|ui| {
+                ui.label("Data version:");
+                ui.add_enabled_ui(!is_busy, |ui| {
+                    egui::ComboBox::from_id_source("data_version_picker")
+                        .selected_text(short_version(&selected_version))
+                        .show_ui(ui, |ui| {
+                            for version in &self.data_versions {
+                                ui.selectable_value(
+                                    &mut selected_version,
+                                    version.clone(),
+                                    short_version(version),
+                                );
+                            }
+                        });
+                });
+            });
+
+            if selected_version != current_version {
+                self.select_data_version(selected_version);
+            }
+        }
+        self.data_dir.render(ui);
+        ui.add_space(5.0);
+
+        // Show status
+        if has_data {
+            ui.horizontal(|ui| {
+                ui.colored_label(egui::Color32::GREEN, "✓ Data loaded");
+
+                if ui.add_enabled(!is_busy, egui::Button::new("🔄 Re-download")).clicked() {
+                    self.download_and_parse();
+                }
+
+                if ui
+                    .add_enabled(!is_busy, egui::Button::new("♻ Force re-parse"))
+                    .on_hover_text("Parse the data files again instead of using the cache")
+                    .clicked()
+                {
+                    self.parse_directory();
+                }
+            });
+        } else if !is_busy {
+            ui.label("No data loaded. Click below to download:");
+            ui.add_space(5.0);
+
+            if ui.button("🚀 Download & Parse Data").clicked() {
+                self.download_and_parse();
+            }
+        }
+
+        ui.add_space(5.0);
+
+        // Progress bars
+        if self.parser_test.downloading {
+            if let Some(token) = &self.parser_test.cancel_token {
+                let cancelling = token.is_cancelled();
+                let label = if cancelling { "Cancelling..." } else { "✖ Cancel" };
+
+                if ui.add_enabled(!cancelling, egui::Button::new(label)).clicked() {
+                    token.cancel();
+                }
+            }
+
+            // Updates show the overall stage above the progress within it
+            if let Some(stage) = self.parser_test.update_stage {
+                let step = update_stage_step(stage);
+                ui.label(format!(
+                    "Step {}/{}: {}",
+                    step,
+                    UPDATE_STAGE_COUNT,
+                    update_stage_label(stage)
+                ));
+                ui.add(
+                    egui::ProgressBar::new((step - 1) as f32 / UPDATE_STAGE_COUNT as f32)
+                        .desired_height(6.0),
+                );
+            }
+
+            if let Some((current, total, file_name)) = &self.parser_test.download_progress {
+                ui.label(format!("Downloading: {} ({}/{})", file_name, current, total));
+                let progress = *current as f32 / *total as f32;
+                ui.add(egui::ProgressBar::new(progress).show_percentage());
+
+                match self.parser_test.download_bytes {
+                    Some((downloaded, Some(total_bytes))) if total_bytes > 0 => {
+                        let fraction = downloaded as f32 / total_bytes as f32;
+                        ui.add(egui::ProgressBar::new(fraction).text(format!(
+                            "{} / {}",
+                            format_bytes(downloaded),
+                            format_bytes(total_bytes)
+                        )));
+                    }
+                    Some((downloaded, _)) => {
+                        ui.label(format!("{} received", format_bytes(downloaded)));
+                    }
+                    None => {}
+                }
+            } else if let Some((completed, total, file_name)) = &self.parser_test.parse_progress {
+                ui.label(format!("Parsing {}...", file_name));
+                ui.add(
+                    egui::ProgressBar::new(*completed as f32 / *total as f32)
+                        .text(format!("{} / {} files", completed, total)),
+                );
+            } else if self.parser_test.update_stage.is_some() {
+                ui.add(egui::ProgressBar::new(0.0).animate(true));
+            } else {
+                ui.label("Initializing download...");
+                ui.add(egui::ProgressBar::new(0.0));
+            }
+        } else if self.parser_test.parsing {
+            match &self.parser_test.parse_progress {
+                Some((completed, total, file_name)) => {
+                    ui.label(format!("Parsing {}...", file_name));
+                    ui.add(
+                        egui::ProgressBar::new(*completed as f32 / *total as f32)
+                            .text(format!("{} / {} files", completed, total)),
+                    );
+                }
+                None if self.parser_test.loading_cache => {
+                    ui.label("Loading cached data...");
+                    ui.add(egui::ProgressBar::new(0.0).animate(true));
+                }
+                None => {
+                    ui.label("Parsing data files...");
+                    ui.add(egui::ProgressBar::new(0.0).animate(true));
+                }
+            }
+        }
+
+        ui.add_space(10.0);
+        ui.separator();
+
+        // Display results
+        let mut redownload = false;
+        let mut repair = false;
+        if let Some(error) = &self.parser_test.error_message {
+            ui.colored_label(egui::Color32::RED, "❌ Error:");
+            ui.label(error);
+            if self.parser_test.redownload_suggested {
+                ui.horizontal(|ui| {
+                    ui.label("The data files are missing or damaged.");
+                    if ui.add_enabled(!is_busy, egui::Button::new("🔄 Re-download")).clicked() {
+                        redownload = true;
+                    }
+                });
+            }
+            if self.parser_test.repair_suggested {
+                ui.horizontal(|ui| {
+                    ui.label("Re-fetch only the files that fail their checks:");
+                    if ui.add_enabled(!is_busy, egui::Button::new("🛠 Repair data")).clicked() {
+                        repair = true;
+                    }
+                });
+            }
+            ui.add_space(10.0);
+        }
+        if redownload {
+            self.download_and_parse();
+        }
+        if repair {
+            self.repair_data();
+        }
+
+        if let (Some(data), Some(report)) =
+            (self.parser_test.data(), &self.parser_test.parse_report)
+        {
+            ui.heading("📊 Parsed Data Summary");
+            ui.add_space(5.0);
+
+            egui::Grid::new("parser_stats_grid")
+                .num_columns(2)
+                .spacing([20.0, 8.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Version:");
+                    ui.label(&data.version);
+                    ui.end_row();
+
+                    ui.label("Node Indices:");
+                    ui.label(format!("{}", report.node_index_count));
+                    ui.end_row();
+
+                    ui.label("Modifiers:");
+                    ui.label(format!("{}", report.modifier_count));
+                    ui.end_row();
+
+                    ui.label("Jewel Types:");
+                    ui.label(format!("{}", report.jewels.len()));
+                    ui.end_row();
+
+                    ui.label("Parse Time:");
+                    ui.label(format!("{:.2?}", report.total_duration));
+                    ui.end_row();
+
+                    ui.label("Warnings:");
+                    ui.label(format!("{}", report.warnings.len()));
+                    ui.end_row();
+                });
+
+            ui.add_space(10.0);
+
+            ui.collapsing("📁 Files", |ui| {
+                egui::Grid::new("parser_files_grid")
+                    .num_columns(4)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("File");
+                        ui.strong("Size");
+                        ui.strong("Decompressed");
+                        ui.strong("Time");
+                        ui.end_row();
+
+                        for file in &report.files {
+                            ui.label(&file.name);
+                            ui.monospace(format!("{} bytes", file.bytes));
+                            ui.monospace(
+                                file.decompressed_bytes
+                                    .map(|b| format!("{} bytes", b))
+                                    .unwrap_or_else(|| "-".to_string()),
+                            );
+                            ui.monospace(format!("{:.2?}", file.duration));
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            ui.add_space(10.0);
+
+            // Display jewel details
+            if !data.jewels.is_empty() {
+                ui.heading("💎 Jewel Data");
+                ui.add_space(5.0);
+
+                egui::ScrollArea::vertical()
+                    .id_source("jewel_data_scroll")
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for (idx, (jewel_type, jewel_data)) in data.jewels.iter().enumerate() {
+                            ui.group(|ui| {
+                                ui.strong(jewel_type);
+
+                                ui.horizontal(|ui| {
+                                    ui.label("Seed Range:");
+                                    ui.monospace(format!("{} - {}",
+                                        jewel_data.seed_range.0,
+                                        jewel_data.seed_range.1
+                                    ));
+                                });
+
+                                if let Some(jewel_report) = report.jewels.get(jewel_type) {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Seeds with data:");
+                                        ui.monospace(format!("{}", jewel_report.seed_count));
+                                    });
+
+                                    ui.horizontal(|ui| {
+                                        ui.label("Nodes:");
+                                        ui.monospace(format!("{}", jewel_report.node_count));
+                                    });
+                                }
+
+                                // Show sample seed data
+                                if let Some((seed, node_mods)) = jewel_data.lookup_table.iter().next() {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Sample seed:");
+                                        ui.monospace(format!("{} ({} nodes)", seed, node_mods.len()));
+                                    });
+                                }
+                            });
+
+                            if idx < data.jewels.len() - 1 {
+                                ui.add_space(5.0);
+                            }
+                        }
+                    });
+            }
+        }
+
+        // Log messages
+        if !self.parser_test.log_messages.is_empty() {
+            ui.add_space(10.0);
+            ui.separator();
+
+            ui.collapsing("📋 Parse Log", |ui| {
+                egui::ScrollArea::vertical()
+                    .id_source("parse_log_scroll")
+                    .max_height(200.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for (idx, msg) in self.parser_test.log_messages.iter().enumerate() {
+                            ui.label(msg);
+                            if idx < self.parser_test.log_messages.len() - 1 {
+                                ui.add_space(2.0);
+                            }
+                        }
+                    });
+            });
+        }
+    }
+
+    /// Render the analyze tab
+    fn render_analysis(&mut self, ui: &mut egui::Ui) {
+        ui.heading("🔍 Analyze Jewel");
+        ui.add_space(5.0);
+
+        let data = self.parser_test.data();
+        if data.is_none() {
+            ui.label("No data loaded. Download and parse the PoB data on the Data tab.");
+            ui.add_space(5.0);
+        }
+
+        if self.analysis.render_form(ui, data.as_deref()) {
+            self.start_analysis();
+        }
+
+        ui.add_space(10.0);
+        ui.separator();
+
+        let lut_version = data.as_ref().map(|data| data.version.as_str());
+        let exported = self.analysis.render_results(ui, lut_version);
+        self.report_export(exported);
+
+        ui.add_space(10.0);
+        ui.separator();
+
+        self.render_import(ui);
+    }
+
+    /// Analyze the jewel from the form in the background
+    fn start_analysis(&mut self) {
+        self.analysis.error = None;
+        self.analysis.result = None;
+
+        let Some(lut) = self.parser_test.lut_handle() else {
+            self.analysis.error = Some("No data loaded".to_string());
+            return;
+        };
+
+        let (jewel, config) = match self.analysis.build_request() {
+            Ok(request) => request,
+            Err(e) => {
+                self.analysis.error = Some(e);
+                return;
+            }
+        };
+        let config = config.with_timings(self.settings.show_timings);
+
+        self.analysis.running = true;
+
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(lut));
+            let result = analyzer
+                .analyze(&jewel, &config)
+                .map_err(|e| e.to_string());
+
+            if let Err(e) = tx.send(AsyncMessage::AnalysisComplete(Box::new(result))) {
+                warn!("Failed to send analysis result: {}", e);
+            }
+        });
+    }
+
+    /// Render the settings tab
+    fn render_settings(&mut self, ui: &mut egui::Ui) {
+        ui.heading("⚙ Settings");
+        ui.add_space(5.0);
+
+        let is_busy = self.parser_test.downloading || self.parser_test.parsing;
+        let mut apply_data_dir = false;
+        let mut browse_clicked = false;
+        let mut token_changed = false;
+        let mut auto_check_changed = false;
+        let mut check_clicked = false;
+        let mut poe_login_changed = false;
+        let mut network_changed = false;
+
+        egui::Grid::new("settings_grid")
+            .num_columns(2)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                ui.label("Data directory:");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.settings_form.data_dir)
+                            .desired_width(360.0),
+                    );
+                    let changed = self.settings_form.data_dir.trim()
+                        != self.settings.data_dir.display().to_string();
+                    apply_data_dir = ui
+                        .add_enabled(changed && !is_busy, egui::Button::new("Apply"))
+                        .clicked();
+                    browse_clicked = ui
+                        .add_enabled(!is_busy, egui::Button::new("📂 Browse..."))
+                        .clicked();
+                });
+                ui.end_row();
+
+                ui.label("GitHub token:");
+                token_changed = ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.settings_form.github_token)
+                            .password(true)
+                            .hint_text("optional")
+                            .desired_width(360.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label("Updates:");
+                ui.horizontal(|ui| {
+                    auto_check_changed = ui
+                        .checkbox(
+                            &mut self.settings.auto_check_updates,
+                            "Check for new PoB data in the background",
+                        )
+                        .changed();
+                    check_clicked = ui
+                        .add_enabled(!is_busy, egui::Button::new("Check now"))
+                        .clicked();
+                });
+                ui.end_row();
+
+                ui.label("PoE account:");
+                poe_login_changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.settings_form.poe_account)
+                            .hint_text("for stash imports")
+                            .desired_width(360.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label("POESESSID:");
+                ui.horizontal(|ui| {
+                    poe_login_changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.settings_form.poe_session_id)
+                                .password(true)
+                                .hint_text("session cookie from pathofexile.com")
+                                .desired_width(360.0),
+                        )
+                        .lost_focus();
+                    if self.stash.auth_failed {
+                        ui.colored_label(egui::Color32::RED, "✗ Rejected by Path of Exile");
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Network:");
+                ui.horizontal(|ui| {
+                    ui.label("Connect timeout");
+                    let connect = ui.add(
+                        egui::DragValue::new(&mut self.settings.connect_timeout_secs)
+                            .clamp_range(1..=300)
+                            .suffix(" s"),
+                    );
+                    ui.label("Read timeout");
+                    let read = ui
+                        .add(
+                            egui::DragValue::new(&mut self.settings.read_timeout_secs)
+                                .clamp_range(1..=600)
+                                .suffix(" s"),
+                        )
+                        .on_hover_text("Give up once a server sends nothing for this long");
+                    ui.label("Idle connections");
+                    let idle = ui.add(
+                        egui::DragValue::new(&mut self.settings.max_idle_connections)
+                            .clamp_range(0..=64),
+                    );
+                    network_changed |= [connect, read, idle].iter().any(value_committed);
+                });
+                ui.end_row();
+
+                ui.label("Proxy:");
+                ui.horizontal(|ui| {
+                    network_changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.settings_form.proxy)
+                                .hint_text("optional, e.g. http://proxy.local:8080")
+                                .desired_width(360.0),
+                        )
+                        .lost_focus();
+                    if self.settings.http_config().build_client().is_err() {
+                        ui.colored_label(egui::Color32::RED, "✗ Invalid proxy URL");
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Debug:");
+                ui.checkbox(&mut self.settings.show_timings, "Show analysis timings");
+                ui.end_row();
+            });
+
+        if apply_data_dir {
+            self.select_data_dir(PathBuf::from(self.settings_form.data_dir.trim()));
+        }
+        if browse_clicked {
+            self.browse_data_dir();
+        }
+
+        if token_changed {
+            let token = self.settings_form.github_token.trim();
+            self.settings.github_token = (!token.is_empty()).then(|| token.to_string());
+        }
+
+        if network_changed {
+            let proxy = self.settings_form.proxy.trim();
+            self.settings.proxy = (!proxy.is_empty()).then(|| proxy.to_string());
+            self.apply_http_config();
+        }
+
+        if token_changed || auto_check_changed || network_changed {
+            self.start_update_watcher();
+        }
+
+        if check_clicked {
+            self.check_for_updates();
+        }
+
+        if poe_login_changed {
+            let account = self.settings_form.poe_account.trim().to_string();
+            let session_id = self.settings_form.poe_session_id.trim();
+            let session_id = (!session_id.is_empty()).then(|| session_id.to_string());
+
+            if account != self.settings.poe_account || session_id != self.settings.poe_session_id {
+                self.settings.poe_account = account;
+                self.settings.poe_session_id = session_id;
+                self.stash.reset();
+            }
+        }
+    }
+
+    /// Render the modifier browser
+    fn render_mods(&mut self, ui: &mut egui::Ui) {
+        ui.heading("📜 Modifiers");
+        ui.add_space(5.0);
+
+        self.index_mods();
+        self.mods.render(ui, &mut self.analysis.weights);
+    }
+
+    /// Index the seeds and frequencies of newly loaded data's mods in the background
+    fn index_mods(&mut self) {
+        if let Some(data) = self.parser_test.data() {
+            if self.mods.set_data(&data) {
+                self.mods.indexing = true;
+
+                let tx = self.tx.clone();
+                std::thread::spawn(move || {
+                    let index = data.modifier_index();
+                    let statistics =
+                        JewelType::ALL.map(|jewel_type| data.build_statistics(jewel_type)).to_vec();
+                    let _ = tx.send(AsyncMessage::ModifierIndex(data, Box::new(index), statistics));
+                });
+            }
+        }
+    }
+
+    /// Render the seed search tab
+    fn render_seed_search(&mut self, ui: &mut egui::Ui) {
+        ui.heading("🎯 Best Seed Search");
+        ui.add_space(5.0);
+
+        if self.parser_test.lut.is_none() {
+            ui.label("No data loaded. Download and parse the PoB data on the Data tab.");
+            ui.add_space(5.0);
+        }
+
+        if self.seed_search.render_form(ui, &mut self.analysis.weights) {
+            self.start_seed_search();
+        }
+
+        ui.add_space(10.0);
+        ui.separator();
+
+        // Rarity of the result mods
+        self.index_mods();
+        let statistics = self
+            .seed_search
+            .result
+            .as_ref()
+            .and_then(|result| self.mods.statistics(result.jewel_type));
+        let lut_version = self.parser_test.data().map(|data| data.version.clone());
+        let exported = self.seed_search.render_results(ui, statistics, lut_version.as_deref());
+        self.report_export(exported);
+
+        if let Some((seed_a, seed_b)) = self.seed_search.compare_request.take() {
+            if let Some(result) = &self.seed_search.result {
+                let conqueror = result.conqueror.clone();
+                self.compare
+                    .set_pair(result.jewel_type, (&conqueror, seed_a), (&conqueror, seed_b));
+                self.tab = Tab::Compare;
+                self.start_compare();
+            }
+        }
+    }
+
+    /// Render the compare tab
+    fn render_compare(&mut self, ui: &mut egui::Ui) {
+        ui.heading("⇄ Compare Seeds");
+        ui.add_space(5.0);
+
+        if self.parser_test.lut.is_none() {
+            ui.label("No data loaded. Download and parse the PoB data on the Data tab.");
+            ui.add_space(5.0);
+        }
+
+        if self.compare.render_form(ui, self.import.jewels.items()) {
+            self.start_compare();
+        }
+
+        ui.add_space(10.0);
+        ui.separator();
+
+        self.compare.render_results(ui);
+    }
+
+    /// Analyze both jewels of the compare tab and diff them in the background
+    fn start_compare(&mut self) {
+        self.compare.error = None;
+        self.compare.comparison = None;
+
+        let Some(lut) = self.parser_test.lut_handle() else {
+            self.compare.error =
+                Some("No data loaded. Download and parse the PoB data first.".to_string());
+            return;
+        };
 
-        self.parser_test.log_messages.push(format!("Parsing directory: {}", path.display()));
+        let (jewel_a, jewel_b) = match self.compare.build_request() {
+            Ok(request) => request,
+            Err(e) => {
+                self.compare.error = Some(e);
+                return;
+            }
+        };
+        let config = self.analysis.weights.config();
 
-        // First, list what files we have
-        match std::fs::read_dir(&path) {
-            Ok(entries) => {
-                self.parser_test.log_messages.push("Files found:".to_string());
-                for entry in entries.flatten() {
-                    if let Ok(metadata) = entry.metadata() {
-                        self.parser_test.log_messages.push(format!(
-                            "  - {} ({} bytes)",
-                            entry.file_name().to_string_lossy(),
-                            metadata.len()
-                        ));
+        self.compare.running = true;
+
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(lut));
+            let result = analyzer
+                .analyze(&jewel_a, &config)
+                .and_then(|a| Ok((a, analyzer.analyze(&jewel_b, &config)?)))
+                .and_then(|(a, b)| JewelComparison::new(&a, &b))
+                .map_err(|e| e.to_string());
+
+            let _ = tx.send(AsyncMessage::CompareComplete(Box::new(result)));
+        });
+    }
+
+    /// Scan every seed of the selected jewel type in the background
+    fn start_seed_search(&mut self) {
+        self.seed_search.error = None;
+        self.seed_search.result = None;
+
+        let Some(lut) = self.parser_test.lut_handle() else {
+            self.seed_search.error =
+                Some("No data loaded. Download and parse the PoB data first.".to_string());
+            return;
+        };
+
+        let config = self.analysis.weights.config();
+        if config.valuable_mods().is_empty() {
+            self.seed_search.error = Some("Add at least one weighted mod".to_string());
+            return;
+        }
+        let config = config.with_timings(self.settings.show_timings);
+
+        let cancel = CancelFlag::new();
+        self.seed_search.cancel = Some(cancel.clone());
+
+        let jewel_type = self.seed_search.jewel_type;
+        let conqueror = self.seed_search.conqueror.clone();
+        let history = self.history_store();
+        let lut_version = self.parser_test.data().map(|data| data.version.clone());
+        let searcher = SeedSearcher::new(Arc::new(lut))
+            .with_top_n(self.seed_search.top_n)
+            .with_cancel(cancel);
+
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let result = searcher
+                .search(jewel_type, &conqueror, &config, |progress| {
+                    let _ = tx.send(AsyncMessage::SearchProgress(progress));
+                })
+                .map_err(|e| e.to_string());
+
+            if let Ok(result) = &result {
+                let label = format!("{} ({})", jewel_type.as_str(), conqueror);
+                let entries = RunEntry::from_search(result);
+                let kind = RunKind::SeedSearch;
+                let run = HistoryRun::new(kind, label, &config, lut_version, entries);
+                if let Err(e) = history.record(run) {
+                    warn!("Could not record the search: {}", e);
+                }
+            }
+
+            if let Err(e) = tx.send(AsyncMessage::SearchComplete(Box::new(result))) {
+                warn!("Failed to send search result: {}", e);
+            }
+        });
+    }
+
+    /// Render the jewel session list with its import buttons
+    fn render_import(&mut self, ui: &mut egui::Ui) {
+        ui.heading("💎 Jewels");
+        ui.add_space(5.0);
+
+        let mut paste_clicked = false;
+        let mut rank_clicked = false;
+        let mut exported = None;
+        ui.horizontal(|ui| {
+            let enabled = !self.import.importing;
+
+            paste_clicked = ui
+                .add_enabled(enabled, egui::Button::new("📋 Paste from clipboard"))
+                .on_hover_text("Item text copied in game with Ctrl+C (or press Ctrl+V)")
+                .clicked();
+
+            let button = egui::Button::new("📂 Import from file...");
+            if ui.add_enabled(enabled, button).clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Item JSON", &["json"])
+                    .pick_file()
+                {
+                    self.import_from(vec![Box::new(LocalFileSource::new(path))]);
+                }
+            }
+
+            if !self.import.jewels.is_empty() {
+                rank_clicked = ui
+                    .add_enabled(!self.import.ranking, egui::Button::new("🏆 Rank all"))
+                    .clicked();
+
+                if ui.button("Clear").clicked() {
+                    self.import = ImportState::default();
+                }
+            }
+
+            if !self.import.ranked.is_empty() {
+                if let Some(format) = export_buttons(ui, false) {
+                    let rows: Vec<ExportRow> =
+                        self.import.ranked.iter().map(ExportRow::from_ranked).collect();
+                    let lut_version = self.parser_test.data().map(|data| data.version.clone());
+                    let json = ResultExportV1::from_ranked(&self.import.ranked, lut_version);
+                    exported = save_export(format, "ranked-jewels", &rows, &json, None);
+                }
+            }
+        });
+
+        self.report_export(exported);
+
+        if paste_clicked {
+            self.paste_from_clipboard();
+        }
+        if rank_clicked {
+            if let Err(e) = self.rank_session(true) {
+                self.toasts.error(e);
+            }
+        }
+
+        if self.import.importing {
+            ui.label("Importing...");
+        }
+        if self.import.ranking {
+            ui.label("Ranking...");
+        }
+
+        let mut stash_action = None;
+        egui::CollapsingHeader::new("📦 Stash tabs")
+            .id_source("stash_import")
+            .show(ui, |ui| {
+                let has_credentials = !self.settings.poe_account.is_empty()
+                    && self.settings.poe_session_id.is_some();
+                stash_action = self.stash.render(
+                    ui,
+                    &mut self.settings.poe_league,
+                    has_credentials,
+                    &self.poe_client.rate_limit_state(),
+                );
+            });
+
+        match stash_action {
+            Some(StashAction::LoadTabs) => self.load_stash_tabs(),
+            Some(StashAction::Import(tab_indices)) => self.import_stash_tabs(tab_indices),
+            Some(StashAction::OpenSettings) => self.tab = Tab::Settings,
+            None => {}
+        }
+
+        for report in &self.import.reports {
+            match &report.error {
+                None => {
+                    let duplicates = report.fetched - report.added;
+                    let mut summary =
+                        format!("✓ {}: {} jewels", report.source, report.fetched);
+                    if duplicates > 0 {
+                        summary.push_str(&format!(" ({} duplicates)", duplicates));
                     }
+                    ui.colored_label(egui::Color32::GREEN, summary);
+                }
+                Some(error) => {
+                    ui.colored_label(egui::Color32::RED, format!("✗ {}: {}", report.source, error));
                 }
             }
-            Err(e) => {
-                self.parser_test.log_messages.push(format!("Cannot read directory: {}", e));
+        }
+
+        if self.import.jewels.is_empty() {
+            return;
+        }
+
+        // Ranked jewels first, in rank order
+        let mut jewels: Vec<&TimelessJewel> = self.import.jewels.items().iter().collect();
+        jewels.sort_by_key(|jewel| {
+            self.import
+                .ranks
+                .get(&jewel.id())
+                .map_or(usize::MAX, |(rank, _)| *rank)
+        });
+
+        let mut remove = None;
+        let mut toggle_owned = None;
+        egui::ScrollArea::vertical()
+            .id_source("imported_jewels_scroll")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                egui::Grid::new("session_jewels_grid")
+                    .num_columns(7)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("#");
+                        ui.strong("Jewel");
+                        ui.strong("Seed");
+                        ui.strong("Conqueror");
+                        ui.strong("Best score");
+                        ui.strong("Owned");
+                        ui.label("");
+                        ui.end_row();
+
+                        for jewel in jewels {
+                            let id = jewel.id();
+                            let (rank, mut score) = match self.import.ranks.get(&id) {
+                                Some((rank, score)) => (rank.to_string(), format!("{:.1}", score)),
+                                None => ("-".to_string(), "-".to_string()),
+                            };
+                            let warnings = self
+                                .import
+                                .ranked
+                                .iter()
+                                .find(|ranked| ranked.result.jewel.id() == id)
+                                .filter(|ranked| !ranked.result.warnings.is_empty())
+                                .map(|ranked| {
+                                    let lines: Vec<String> = ranked
+                                        .result
+                                        .warnings
+                                        .iter()
+                                        .map(|warning| format!("⚠ {}", warning))
+                                        .collect();
+                                    lines.join("\n")
+                                });
+                            if warnings.is_some() {
+                                score.push_str(" ⚠");
+                            }
+                            let cells = [
+                                egui::RichText::new(rank),
+                                egui::RichText::new(jewel.jewel_type.as_str()),
+                                egui::RichText::new(jewel.seed.to_string()).monospace(),
+                                egui::RichText::new(&jewel.conqueror),
+                                egui::RichText::new(score).monospace(),
+                            ];
+
+                            // Grey out jewels an owned jewel already beats
+                            match self.import.dominated.get(&id) {
+                                Some(owned) => {
+                                    let owned_jewel =
+                                        self.import.jewels.items().iter().find(|j| j.id() == *owned);
+                                    let hover = match owned_jewel {
+                                        Some(owned) => format!(
+                                            "You already own a better {} (seed {}, {})",
+                                            owned.jewel_type.as_str(),
+                                            owned.seed,
+                                            owned.conqueror
+                                        ),
+                                        None => "You already own a better jewel".to_string(),
+                                    };
+                                    let hover = match &warnings {
+                                        Some(warnings) => format!("{}\n{}", hover, warnings),
+                                        None => hover,
+                                    };
+                                    for cell in cells {
+                                        ui.label(cell.weak()).on_hover_text(&hover);
+                                    }
+                                }
+                                None => {
+                                    for cell in cells {
+                                        let label = ui.label(cell);
+                                        if let Some(warnings) = &warnings {
+                                            label.on_hover_text(warnings);
+                                        }
+                                    }
+                                }
+                            }
+
+                            let mut owned = self.import.owned.contains(&id);
+                            if ui
+                                .checkbox(&mut owned, "")
+                                .on_hover_text("Already owned (e.g., in your stash)")
+                                .changed()
+                            {
+                                toggle_owned = Some((id.clone(), owned));
+                            }
+                            if ui.small_button("✖").clicked() {
+                                remove = Some(id);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if let Some((id, owned)) = toggle_owned {
+            if owned {
+                self.import.owned.insert(id);
+            } else {
+                self.import.owned.remove(&id);
+            }
+            if !self.import.ranking {
+                if let Err(e) = self.rank_session(false) {
+                    debug!("Not ranking after marking a jewel owned: {}", e);
+                }
+            }
+        }
+
+        if let Some(id) = remove {
+            self.import.jewels.remove(&id);
+            self.import.ranks.remove(&id);
+            self.import.ranked.retain(|ranked| ranked.result.jewel.id() != id);
+            self.import.owned.remove(&id);
+            self.import.dominated.retain(|jewel, owned| *jewel != id && *owned != id);
+        }
+    }
+
+    /// Toast the outcome of an export
+    fn report_export(&mut self, exported: Option<Result<PathBuf, String>>) {
+        match exported {
+            Some(Ok(path)) => self.toasts.info(format!("Exported to {}", path.display())),
+            Some(Err(e)) => self.toasts.error(e),
+            None => {}
+        }
+    }
+
+    /// Import item text from the OS clipboard
+    fn paste_from_clipboard(&mut self) {
+        let text = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text());
+
+        match text {
+            Ok(text) => self.import_text(&text),
+            Err(e) => self.toasts.error(format!("Could not read the clipboard: {}", e)),
+        }
+    }
+
+    /// Add the timeless jewels in pasted item text to the session
+    fn import_text(&mut self, text: &str) {
+        let parsed = ClipboardTextSource::new(text).parse();
+
+        if parsed.jewels.is_empty() {
+            let reason = match parsed.errors.first() {
+                Some(error) => error.message.clone(),
+                None if parsed.ignored > 0 => "the pasted item is not a Timeless Jewel".to_string(),
+                None => "the clipboard holds no item text".to_string(),
+            };
+            self.toasts.error(format!("Nothing imported: {}", reason));
+            return;
+        }
+
+        let added = self.import.jewels.add_all(parsed.jewels.iter().cloned());
+        self.toasts.info(format!("Pasted {} (added {})", parsed.summary(), added));
+        for error in &parsed.errors {
+            self.toasts.error(format!("Item {}: {}", error.index + 1, error.message));
+        }
+
+        // Score new jewels right away when possible
+        if added > 0 && !self.import.ranking {
+            if let Err(e) = self.rank_session(false) {
+                debug!("Not ranking pasted jewels: {}", e);
             }
         }
+    }
 
-        match PobDataParser::parse_directory(&path) {
-            Ok(data) => {
-                self.parser_test.log_messages.push("✓ Parsing successful!".to_string());
-                self.parser_test.log_messages.push(format!("  - {} node indices", data.node_indices.len()));
-                self.parser_test.log_messages.push(format!("  - {} modifiers", data.modifiers.len()));
-                self.parser_test.log_messages.push(format!("  - {} jewel types", data.jewels.len()));
+    /// Rank every session jewel with the current weights in the background
+    ///
+    /// With `record`, the ranking is recorded in the history.
+    fn rank_session(&mut self, record: bool) -> Result<(), String> {
+        let mut lut = self
+            .parser_test
+            .lut_handle()
+            .ok_or("No data loaded. Download and parse the PoB data first.")?;
+
+        let config = self.analysis.weights.config();
+        if config.valuable_mods().is_empty() {
+            return Err("Add at least one weighted mod to rank jewels".to_string());
+        }
 
-                for (jewel_type, jewel_data) in &data.jewels {
-                    self.parser_test.log_messages.push(format!(
-                        "  - {}: {} seeds parsed",
-                        jewel_type,
-                        jewel_data.lookup_table.len()
-                    ));
+        self.import.ranking = true;
+
+        let items: Vec<BatchItem> = self
+            .import
+            .jewels
+            .items()
+            .iter()
+            .map(|jewel| BatchItem {
+                owned: self.import.owned.contains(&jewel.id()),
+                jewel: jewel.clone(),
+            })
+            .collect();
+        let history = record.then(|| self.history_store());
+        let lut_version = self.parser_test.data().map(|data| data.version.clone());
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            // Rank again if new data was swapped in meanwhile
+            let result = loop {
+                let analyzer = TimelessJewelAnalyzer::new().with_lookup(Arc::new(lut.clone()));
+                let result = analyzer.analyze_batch_owned(&items, &config);
+                if !lut.is_stale() {
+                    break result.map_err(|e| e.to_string());
                 }
+                lut = lut.refresh();
+            };
+
+            if let (Some(history), Ok(ranked)) = (history, &result) {
+                let label = format!("{} session jewels", ranked.len());
+                let entries = RunEntry::from_results(ranked.iter().map(|r| &r.result.result));
+                let run = HistoryRun::new(RunKind::Batch, label, &config, lut_version, entries);
+                if let Err(e) = history.record(run) {
+                    warn!("Could not record the ranking: {}", e);
+                }
+            }
 
-                self.parser_test.parsed_data = Some(data);
+            if let Err(e) = tx.send(AsyncMessage::RankComplete(Box::new(result))) {
+                warn!("Failed to send ranking: {}", e);
             }
+        });
+
+        Ok(())
+    }
+
+    /// The jewels, weights and results to save
+    fn session(&self) -> Session {
+        Session {
+            version: SESSION_VERSION,
+            lut_version: self.parser_test.data().map(|data| data.version.clone()),
+            profile: self.analysis.weights.active().map(str::to_string),
+            config: self.analysis.weights.config(),
+            jewels: self.import.jewels.items().iter().map(TimelessJewel::to_record).collect(),
+            ranked: self.import.ranked.clone(),
+            analysis: self.analysis.result.clone(),
+        }
+    }
+
+    /// Ask for a path with a save dialog and save the session to it
+    fn save_session_as(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("session.json")
+            .add_filter("Session", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+
+        match save_session(&path, &self.session()) {
+            Ok(()) => self.toasts.info(format!("Saved session to {}", path.display())),
+            Err(e) => self
+                .toasts
+                .error(format!("Could not save the session to {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Ask for a session file and restore it
+    fn open_session(&mut self) {
+        if self.import.importing || self.import.ranking || self.analysis.running {
+            self.toasts.error("Wait for the running import or analysis to finish");
+            return;
+        }
+
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Session", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        match load_session(&path) {
+            Ok(restored) => self.apply_session(restored),
             Err(e) => {
-                let error_msg = format!("Failed to parse: {}", e);
-                self.parser_test.log_messages.push(format!("✗ {}", error_msg));
-                self.parser_test.error_message = Some(e.to_string());
+                warn!("Could not open session {}: {}", path.display(), e);
+                self.session_dialog = Some(SessionDialog {
+                    title: "Could not open session".to_string(),
+                    lines: vec![e.to_string()],
+                });
+            }
+        }
+    }
+
+    /// Replace the jewels, weights and results with a restored session
+    fn apply_session(&mut self, restored: RestoredSession) {
+        let RestoredSession { session, problems } = restored;
+
+        self.analysis
+            .weights
+            .restore(session.profile.as_deref(), &session.config);
+
+        self.import = ImportState::default();
+        self.import
+            .jewels
+            .add_all(session.jewels.into_iter().map(TimelessJewel::from_record));
+        self.import.ranks = ranks_by_id(&session.ranked);
+        self.import.ranked = session.ranked;
+
+        // Fill the form too, so the analysis can be re-run
+        if let Some(result) = &session.analysis {
+            self.analysis.jewel_type = result.jewel.jewel_type;
+            self.analysis.conqueror = result.jewel.conqueror.clone();
+            self.analysis.seed_text = result.jewel.seed.to_string();
+        }
+        self.analysis.result = session.analysis;
+        self.analysis.error = None;
+
+        self.tab = Tab::Analyze;
+        self.toasts.info(format!("Opened session with {} jewels", self.import.jewels.len()));
+        if !problems.is_empty() {
+            self.session_dialog = Some(SessionDialog {
+                title: "Session partly restored".to_string(),
+                lines: problems,
+            });
+        }
+
+        self.session_lut_version = session.lut_version;
+        self.refresh_session_results();
+    }
+
+    /// Re-run the opened session's results if they came from other data
+    ///
+    /// Waits for data to be loaded; results from the loaded data are kept.
+    fn refresh_session_results(&mut self) {
+        let Some(data) = self.parser_test.data() else {
+            return;
+        };
+        let Some(saved_version) = self.session_lut_version.take() else {
+            return;
+        };
+        if saved_version == data.version {
+            return;
+        }
+
+        info!(
+            "Session results are from data {}, loaded {}; re-analyzing",
+            saved_version, data.version
+        );
+
+        if !self.import.ranked.is_empty() {
+            if let Err(e) = self.rank_session(false) {
+                // Stale ranks would be misleading
+                self.import.ranks.clear();
+                self.import.ranked.clear();
+                self.toasts.error(format!("Could not re-rank the session: {}", e));
             }
         }
+        if self.analysis.result.is_some() {
+            self.start_analysis();
+        }
+    }
+
+    /// Render the session problems dialog, if open
+    fn render_session_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &self.session_dialog else {
+            return;
+        };
+
+        let mut close = false;
+        egui::Window::new(&dialog.title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                for line in &dialog.lines {
+                    ui.label(format!("• {}", line));
+                }
+                ui.add_space(5.0);
+                close = ui.button("OK").clicked();
+            });
+
+        if close {
+            self.session_dialog = None;
+        }
+    }
+
+    /// Fetch jewels from `sources` in the background
+    fn import_from(&mut self, sources: Vec<Box<dyn ItemSource>>) {
+        self.import.importing = true;
+        self.import.reports.clear();
+
+        let composite = sources
+            .into_iter()
+            .fold(CompositeSource::new(), CompositeSource::with_source);
+
+        self.runtime.spawn_task(self.tx.clone(), async move {
+            AsyncMessage::ImportComplete(composite.fetch_all().await)
+        });
+    }
+
+    /// List the stash tabs of the configured account in the background
+    fn load_stash_tabs(&mut self) {
+        let Some(session_id) = self.settings.poe_session_id.clone() else {
+            return;
+        };
+
+        self.stash.loading_tabs = true;
+        self.stash.error = None;
+
+        let client = Arc::clone(&self.poe_client);
+        let account = self.settings.poe_account.clone();
+        let league = self.settings.poe_league.trim().to_string();
+
+        self.runtime.spawn_task(self.tx.clone(), async move {
+            let tabs = client.get_stash_tabs(&account, &league, &session_id).await;
+            AsyncMessage::StashTabs(tabs.map_err(SourceError::from))
+        });
+    }
+
+    /// Import the timeless jewels of the tabs at `tab_indices` in the background
+    ///
+    /// Tabs are fetched one at a time through the shared client's rate limiter.
+    fn import_stash_tabs(&mut self, tab_indices: Vec<u32>) {
+        let Some(session_id) = self.settings.poe_session_id.clone() else {
+            return;
+        };
+
+        self.stash.importing = true;
+        self.stash.error = None;
+        self.stash.summary = None;
+
+        let sources: Vec<StashTabSource> = tab_indices
+            .into_iter()
+            .map(|tab_index| {
+                StashTabSource::new(
+                    Arc::clone(&self.poe_client),
+                    &self.settings.poe_account,
+                    self.settings.poe_league.trim(),
+                    tab_index,
+                    &session_id,
+                )
+            })
+            .collect();
+
+        self.runtime.spawn_task(self.tx.clone(), async move {
+            let mut extracted = ExtractedJewels::default();
+            for source in sources {
+                match source.fetch_extracted().await {
+                    Ok(tab) => {
+                        extracted.jewels.extend(tab.jewels);
+                        extracted.skipped.extend(tab.skipped);
+                    }
+                    Err(e) => return AsyncMessage::StashImported(Err(e)),
+                }
+            }
+            AsyncMessage::StashImported(Ok(extracted))
+        });
+    }
+
+    /// Download data from GitHub and parse it
+    fn download_and_parse(&mut self) {
+        self.parser_test.downloading = true;
+        self.parser_test.error_message = None;
+        self.parser_test.redownload_suggested = false;
+        self.parser_test.repair_suggested = false;
+        // Don't clear the parsed data here - keep it until new data is ready
+        self.parser_test.log_messages.clear();
+        self.parser_test.download_progress = None;
+        self.parser_test.download_bytes = None;
+
+        let layout = self.layout();
+        self.parser_test
+            .log_messages
+            .push(format!("Download directory: {}", layout.root().display()));
+        self.parser_test.log_messages.push("Starting download...".to_string());
+
+        let tx = self.tx.clone();
+        let cancel = CancellationToken::new();
+        self.parser_test.cancel_token = Some(cancel.clone());
+        let http = self.settings.http_config();
+
+        let span = info_span!("download", dir = %layout.root().display());
+        self.runtime.spawn_task(
+            self.tx.clone(),
+            async move {
+                AsyncMessage::DownloadComplete(download_pob_data(layout, http, tx, cancel).await)
+            }
+            .instrument(span),
+        );
+    }
+
+    /// Re-fetch damaged data files and parse the repaired data in the background
+    fn repair_data(&mut self) {
+        self.parser_test.parsing = true;
+        self.parser_test.parse_progress = None;
+        self.parser_test.error_message = None;
+        self.parser_test.redownload_suggested = false;
+        self.parser_test.repair_suggested = false;
+
+        let path = PathBuf::from(&self.parser_test.data_dir);
+        let (parsed_dir, manifest_path) = self.cache_paths(&path);
+        let manifest = DataManifest::load_from_file(&manifest_path)
+            .unwrap_or_else(|_| DataManifest::default_pob());
+        self.parser_test.log_messages.push(format!("Repairing data in {}", path.display()));
+
+        let span = info_span!("repair", dir = %path.display());
+        self.runtime.spawn_task(
+            self.tx.clone(),
+            async move {
+                let result = poe_item_analyzer_api::repair(&path, &manifest).await.map(
+                    |(data, mut report)| {
+                        // A missing cache only costs a re-parse next launch
+                        let cache = write_lut_cache(
+                            &path,
+                            &parsed_dir,
+                            &manifest_path,
+                            &data,
+                            &report.parse_report,
+                        );
+                        if let Err(e) = cache {
+                            report
+                                .parse_report
+                                .warn(format!("Could not cache parsed data: {}", e));
+                        }
+                        (data, report)
+                    },
+                );
+                AsyncMessage::RepairComplete(Box::new(result))
+            }
+            .instrument(span),
+        );
+    }
+
+    /// Parse the selected directory on a background thread
+    fn parse_directory(&mut self) {
+        self.parser_test.parsing = true;
+        self.parser_test.parse_progress = None;
+        self.parser_test.error_message = None;
+        self.parser_test.redownload_suggested = false;
+        self.parser_test.repair_suggested = false;
+        // Keep the current data for running analyses until the new data is swapped in
+
+        let path = PathBuf::from(&self.parser_test.data_dir);
+        let (parsed_dir, manifest_path) = self.cache_paths(&path);
+
+        self.parser_test.log_messages.push(format!("Parsing directory: {}", path.display()));
+
+        let tx = self.tx.clone();
+
+        std::thread::spawn(move || {
+            let _span = info_span!("parse", dir = %path.display()).entered();
+            let progress_tx = tx.clone();
+            let result = PobDataParser::parse_directory_with_progress(&path, |event| {
+                let _ = progress_tx.send(AsyncMessage::Parse(event));
+            })
+            .map(|(data, mut report)| {
+                // A missing cache only costs a re-parse next launch
+                if let Err(e) = write_lut_cache(&path, &parsed_dir, &manifest_path, &data, &report)
+                {
+                    report.warn(format!("Could not cache parsed data: {}", e));
+                }
+                (data, report)
+            })
+            .map_err(DataError::from);
+
+            if let Err(e) = tx.send(AsyncMessage::ParseComplete(Box::new(result))) {
+                warn!("Failed to send parse result: {}", e);
+            }
+        });
+    }
+}
+
+/// Short description of an update stage for the progress UI
+/// Number of stages in a data update
+const UPDATE_STAGE_COUNT: usize = 5;
+
+/// Whether a drag value was changed and let go of, so dragging it doesn't
+/// apply every intermediate value
+fn value_committed(response: &egui::Response) -> bool {
+    response.drag_stopped() || (response.changed() && !response.dragged())
+}
+
+/// Position of `stage` in a data update, counting from 1
+fn update_stage_step(stage: UpdateStage) -> usize {
+    match stage {
+        UpdateStage::Checking => 1,
+        UpdateStage::Downloading => 2,
+        UpdateStage::Verifying => 3,
+        UpdateStage::Parsing => 4,
+        UpdateStage::Swapping => 5,
+    }
+}
+
+fn update_stage_label(stage: UpdateStage) -> &'static str {
+    match stage {
+        UpdateStage::Checking => "Checking for changes",
+        UpdateStage::Downloading => "Downloading changed files",
+        UpdateStage::Verifying => "Verifying files",
+        UpdateStage::Parsing => "Parsing data",
+        UpdateStage::Swapping => "Installing",
+    }
+}
+
+/// Save the binary LUT parsed from `data_dir` in `parsed_dir` and record it
+/// in the manifest at `manifest_path`
+fn write_lut_cache(
+    data_dir: &Path,
+    parsed_dir: &Path,
+    manifest_path: &Path,
+    data: &LutData,
+    report: &ParseReport,
+) -> Result<(), DownloadError> {
+    std::fs::create_dir_all(parsed_dir).file_context(FileOperation::Write, parsed_dir)?;
+    let cache_path = parsed_dir.join(LUT_CACHE_FILE);
+    PobDataParser::save_to_binary(data, &cache_path)?;
+    report.save_to_json(&ParseReport::sidecar_path(&cache_path))?;
+
+    let _lock =
+        DataManifest::lock(manifest_path).file_context(FileOperation::Write, manifest_path)?;
+    let mut manifest = DataManifest::load_from_file(manifest_path)
+        .unwrap_or_else(|_| DataManifest::default_pob());
+    manifest.record_parsed_artifact(data_dir, &cache_path, "bincode")?;
+    manifest
+        .save_atomic(manifest_path)
+        .file_context(FileOperation::Write, manifest_path)
+}
+
+/// Rank and best score of each ranked jewel, by jewel id
+fn ranks_by_id(
+    ranked: &[RankedResult<TimelessJewelAnalysisResult>],
+) -> HashMap<String, (usize, f64)> {
+    ranked
+        .iter()
+        .map(|r| (r.result.jewel.id(), (r.rank, r.result.best_score)))
+        .collect()
+}
+
+/// Darken the window while files are dragged over it
+fn paint_drop_overlay(ctx: &Context) {
+    let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_overlay"));
+    let painter = ctx.layer_painter(layer);
+    let rect = ctx.screen_rect();
+
+    painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(192));
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        "Drop the PoB data folder or files to use them",
+        egui::FontId::proportional(24.0),
+        egui::Color32::WHITE,
+    );
+}
+
+impl Drop for AnalyzerApp {
+    fn drop(&mut self) {
+        self.sync_settings();
+        self.save_settings();
 
-        self.parser_test.parsing = false;
+        // Stop any background download or search when the window closes;
+        // dropping the runtime afterwards abandons whatever is left
+        if let Some(token) = &self.parser_test.cancel_token {
+            token.cancel();
+        }
+        if let Some(cancel) = &self.seed_search.cancel {
+            cancel.cancel();
+        }
     }
 }
 
@@ -554,16 +2779,105 @@ impl eframe::App for AnalyzerApp {
         // Process async messages
         self.process_messages();
 
+        // Ctrl+V on the analyze tab imports item text, unless a text field has focus
+        if self.tab == Tab::Analyze && !ctx.wants_keyboard_input() {
+            let pasted: Vec<String> = ctx.input(|input| {
+                input
+                    .events
+                    .iter()
+                    .filter_map(|event| match event {
+                        egui::Event::Paste(text) => Some(text.clone()),
+                        _ => None,
+                    })
+                    .collect()
+            });
+
+            for text in pasted {
+                self.import_text(&text);
+            }
+        }
+
+        // Dropping the data folder (or files in it) selects it
+        let dropped: Vec<PathBuf> = ctx.input(|input| {
+            input.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect()
+        });
+        if let Some(dir) = data_dir_from_dropped(&dropped) {
+            self.tab = Tab::Data;
+            self.select_data_dir(dir);
+        }
+        if ctx.input(|input| !input.raw.hovered_files.is_empty()) {
+            paint_drop_overlay(ctx);
+        }
+
         // Request repaint if operations are in progress
-        if self.parser_test.downloading || self.parser_test.parsing {
+        if self.parser_test.downloading
+            || self.parser_test.parsing
+            || self.data_dir.validating
+            || self.import.importing
+            || self.import.ranking
+            || self.stash.is_busy()
+            || self.analysis.running
+            || self.seed_search.is_running()
+            || self.compare.running
+            || self.mods.indexing
+        {
             ctx.request_repaint();
+        } else if self.update_rx.is_some() {
+            // Pick up update notifications without user input
+            ctx.request_repaint_after(Duration::from_secs(5));
         }
 
+        let mut save_session_clicked = false;
+        let mut open_session_clicked = false;
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("PoE Item Analyzer - Parser Testing");
+            ui.heading("PoE Item Analyzer");
+            ui.separator();
+
+            self.render_update_banner(ui);
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.tab, Tab::Analyze, "🔍 Analyze");
+                ui.selectable_value(&mut self.tab, Tab::Search, "🎯 Seed search");
+                ui.selectable_value(&mut self.tab, Tab::Compare, "⇄ Compare");
+                ui.selectable_value(&mut self.tab, Tab::Mods, "📜 Mods");
+                ui.selectable_value(&mut self.tab, Tab::History, "🕘 History");
+                ui.selectable_value(&mut self.tab, Tab::Data, "📦 Data");
+                ui.selectable_value(&mut self.tab, Tab::Log, "📝 Log");
+                ui.selectable_value(&mut self.tab, Tab::Settings, "⚙ Settings");
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    open_session_clicked = ui.button("📂 Open session...").clicked();
+                    save_session_clicked = ui.button("💾 Save session...").clicked();
+                });
+            });
             ui.separator();
 
-            self.render_parser_test(ui);
+            egui::ScrollArea::vertical()
+                .id_source("tab_scroll")
+                .show(ui, |ui| match self.tab {
+                    Tab::Analyze => self.render_analysis(ui),
+                    Tab::Search => self.render_seed_search(ui),
+                    Tab::Compare => self.render_compare(ui),
+                    Tab::Mods => self.render_mods(ui),
+                    Tab::History => {
+                        let store = self.history_store();
+                        self.history.render(ui, &store);
+                    }
+                    Tab::Data => self.render_parser_test(ui),
+                    Tab::Log => self.log.render(ui),
+                    Tab::Settings => self.render_settings(ui),
+                });
         });
+
+        if save_session_clicked {
+            self.save_session_as();
+        }
+        if open_session_clicked {
+            self.open_session();
+        }
+        self.render_session_dialog(ctx);
+
+        self.toasts.show(ctx);
+        self.sync_settings();
     }
 }
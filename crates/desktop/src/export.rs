@@ -0,0 +1,270 @@
+//! Export of ranked jewels and seed search results to CSV, JSON or an HTML
+//! report
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use poe_item_analyzer_core::analyzers::{
+    RankedResult, SeedSearchResult, TimelessJewelAnalysisResult,
+};
+use poe_item_analyzer_core::items::{JewelType, SocketResult};
+use serde::Serialize;
+
+/// CSV header, matching the fields of `ExportRow`
+const CSV_HEADER: &str = "rank,jewel_type,seed,conqueror,best_socket,score,matched_mods";
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    /// Standalone report, for results that have one
+    Html,
+}
+
+impl ExportFormat {
+    /// File extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// One result as a CSV row
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRow {
+    pub rank: usize,
+    pub jewel_type: JewelType,
+    pub seed: u32,
+    pub conqueror: String,
+    pub best_socket: String,
+    pub score: f64,
+    /// Matched mods at the best socket (e.g., "2× Double Damage, 1× Onslaught")
+    pub matched_mods: String,
+}
+
+impl ExportRow {
+    /// Row for a ranked analysis result
+    pub fn from_ranked(ranked: &RankedResult<TimelessJewelAnalysisResult>) -> Self {
+        let result = &ranked.result;
+        let socket = result
+            .metrics
+            .socket_results
+            .iter()
+            .find(|socket| socket.socket_id == result.best_socket_id);
+
+        Self {
+            rank: ranked.rank,
+            jewel_type: result.jewel.jewel_type,
+            seed: result.jewel.seed,
+            conqueror: result.jewel.conqueror.clone(),
+            best_socket: socket.map(|s| s.socket_name.clone()).unwrap_or_default(),
+            score: result.best_score,
+            matched_mods: socket.map(matched_mods_summary).unwrap_or_default(),
+        }
+    }
+
+    /// Rows for the seeds of a seed search, best first
+    pub fn from_search(result: &SeedSearchResult) -> Vec<Self> {
+        result
+            .results
+            .iter()
+            .enumerate()
+            .map(|(index, score)| Self {
+                rank: index + 1,
+                jewel_type: result.jewel_type,
+                seed: score.seed,
+                conqueror: result.conqueror.clone(),
+                best_socket: score.socket.socket_name.clone(),
+                score: score.score,
+                matched_mods: matched_mods_summary(&score.socket),
+            })
+            .collect()
+    }
+}
+
+/// Write `rows` as CSV with a header line
+///
+/// Scores always use a '.' decimal separator, whatever the system locale.
+pub fn write_csv(rows: &[ExportRow], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "{}", CSV_HEADER)?;
+
+    for row in rows {
+        writeln!(
+            out,
+            "{},{},{},{},{},{:.2},{}",
+            row.rank,
+            csv_field(row.jewel_type.as_str()),
+            row.seed,
+            csv_field(&row.conqueror),
+            csv_field(&row.best_socket),
+            row.score,
+            csv_field(&row.matched_mods)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Write `rows` as CSV, `json` as pretty-printed JSON or the `html` report
+/// to `path`
+pub fn write_export(
+    path: &Path,
+    format: ExportFormat,
+    rows: &[ExportRow],
+    json: &impl Serialize,
+    html: Option<&str>,
+) -> io::Result<()> {
+    if format == ExportFormat::Html && html.is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "these results have no HTML report",
+        ));
+    }
+    let mut out = io::BufWriter::new(std::fs::File::create(path)?);
+
+    match format {
+        ExportFormat::Csv => write_csv(rows, &mut out)?,
+        ExportFormat::Json => serde_json::to_writer_pretty(&mut out, json)?,
+        ExportFormat::Html => out.write_all(html.unwrap_or_default().as_bytes())?,
+    }
+
+    out.flush()
+}
+
+/// Ask for a path with a save dialog and export to it
+///
+/// Returns None if the dialog was cancelled.
+pub fn save_export(
+    format: ExportFormat,
+    file_stem: &str,
+    rows: &[ExportRow],
+    json: &impl Serialize,
+    html: Option<&str>,
+) -> Option<Result<PathBuf, String>> {
+    let extension = format.extension();
+    let path = rfd::FileDialog::new()
+        .set_file_name(format!("{}.{}", file_stem, extension))
+        .add_filter(extension.to_uppercase(), &[extension])
+        .save_file()?;
+
+    Some(
+        write_export(&path, format, rows, json, html)
+            .map(|()| path.clone())
+            .map_err(|e| format!("Could not export to {}: {}", path.display(), e)),
+    )
+}
+
+/// Render the export buttons, returning the format that was clicked
+///
+/// `report` offers an HTML report as well.
+pub fn export_buttons(ui: &mut egui::Ui, report: bool) -> Option<ExportFormat> {
+    let mut clicked = None;
+
+    ui.menu_button("💾 Export", |ui| {
+        if ui.button("CSV...").clicked() {
+            clicked = Some(ExportFormat::Csv);
+            ui.close_menu();
+        }
+        if ui.button("JSON...").clicked() {
+            clicked = Some(ExportFormat::Json);
+            ui.close_menu();
+        }
+        if report && ui.button("HTML report...").clicked() {
+            clicked = Some(ExportFormat::Html);
+            ui.close_menu();
+        }
+    });
+
+    clicked
+}
+
+/// Matched mods of a socket, heaviest first as listed by the analyzer
+fn matched_mods_summary(socket: &SocketResult) -> String {
+    socket
+        .matched_mods
+        .iter()
+        .map(|m| format!("{}× {}", m.count, m.mod_text))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<ExportRow> {
+        vec![
+            ExportRow {
+                rank: 1,
+                jewel_type: JewelType::LethalPride,
+                seed: 14032,
+                conqueror: "Kaom".to_string(),
+                best_socket: "All affected nodes".to_string(),
+                score: 12.5,
+                matched_mods: "2× Double Damage, 1× Onslaught".to_string(),
+            },
+            ExportRow {
+                rank: 2,
+                jewel_type: JewelType::ElegantHubris,
+                seed: 2020,
+                conqueror: "Cadiran".to_string(),
+                best_socket: "Socket \"A\"".to_string(),
+                score: 1.0 / 3.0,
+                matched_mods: String::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut out = Vec::new();
+        write_csv(&rows(), &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "rank,jewel_type,seed,conqueror,best_socket,score,matched_mods\n\
+             1,Lethal Pride,14032,Kaom,All affected nodes,12.50,\"2× Double Damage, 1× Onslaught\"\n\
+             2,Elegant Hubris,2020,Cadiran,\"Socket \"\"A\"\"\",0.33,\n"
+        );
+    }
+
+    #[test]
+    fn test_write_export_json() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("results.json");
+
+        write_export(&path, ExportFormat::Json, &[], &vec![1, 2], None).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json, serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn test_write_export_html() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("report.html");
+
+        write_export(&path, ExportFormat::Html, &[], &(), Some("<p>report</p>")).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "<p>report</p>");
+
+        // Nothing is written for results without a report
+        let missing = temp_dir.path().join("missing.html");
+        assert!(write_export(&missing, ExportFormat::Html, &[], &(), None).is_err());
+        assert!(!missing.exists());
+    }
+}
@@ -0,0 +1,240 @@
+//! Tracing setup feeding the in-app log panel
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{filter_fn, LevelFilter};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// Most records kept in the log panel
+const LOG_CAPACITY: usize = 5000;
+
+/// Prefix of the targets of our own crates
+const APP_TARGET_PREFIX: &str = "poe_item_analyzer";
+
+/// A formatted log event
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    /// Time since logging started
+    pub elapsed: Duration,
+    pub level: Level,
+    /// Module that emitted the event (e.g., "poe_item_analyzer_api::downloader")
+    pub target: String,
+    /// Message, prefixed with the enclosing spans and followed by any fields
+    pub message: String,
+}
+
+impl LogRecord {
+    /// One line of text (e.g., "[   1.250s] WARN  Retrying ...")
+    pub fn format(&self) -> String {
+        format!(
+            "[{:>8.3}s] {:<5} {}",
+            self.elapsed.as_secs_f64(),
+            self.level,
+            self.message
+        )
+    }
+}
+
+/// Bounded ring buffer of log records, shared with the tracing layer
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    records: Arc<Mutex<VecDeque<LogRecord>>>,
+    capacity: usize,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(LOG_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    /// Create a buffer keeping the last `capacity` records
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Add a record, dropping the oldest one when full
+    pub fn push(&self, record: LogRecord) {
+        let mut records = self.lock();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Records at least as severe as `min_level`, oldest first
+    pub fn records(&self, min_level: Level) -> Vec<LogRecord> {
+        self.lock()
+            .iter()
+            .filter(|record| record.level <= min_level)
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Lock the records, recovering from a panic in another thread
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<LogRecord>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Tracing layer that formats events into a `LogBuffer`
+pub struct LogLayer {
+    buffer: LogBuffer,
+    start: Instant,
+}
+
+impl LogLayer {
+    pub fn new(buffer: LogBuffer) -> Self {
+        Self {
+            buffer,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl<S> Layer<S> for LogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let mut message = String::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                let _ = write!(message, "{}: ", span.name());
+            }
+        }
+        message.push_str(&visitor.message);
+        message.push_str(&visitor.fields);
+
+        self.buffer.push(LogRecord {
+            elapsed: self.start.elapsed(),
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_string(),
+            message,
+        });
+    }
+}
+
+/// Collects the message and the other fields of an event
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    /// Remaining fields as " key=value" pairs
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Install the global subscriber and return the buffer the log panel reads
+///
+/// Debug events of our own crates and warnings from dependencies go to the
+/// panel; only errors are printed to stderr.
+pub fn init() -> LogBuffer {
+    let buffer = LogBuffer::default();
+
+    let panel_filter = filter_fn(|metadata| {
+        metadata.target().starts_with(APP_TARGET_PREFIX) || *metadata.level() <= Level::WARN
+    });
+    let stderr = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_filter(LevelFilter::ERROR);
+
+    let installed = tracing_subscriber::registry()
+        .with(LogLayer::new(buffer.clone()).with_filter(panel_filter))
+        .with(stderr)
+        .try_init();
+    if let Err(e) = installed {
+        eprintln!("Could not install the log subscriber: {}", e);
+    }
+
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::{debug, info_span, warn};
+
+    fn record(message: &str, level: Level) -> LogRecord {
+        LogRecord {
+            elapsed: Duration::ZERO,
+            level,
+            target: "test".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_buffer_drops_oldest_records() {
+        let buffer = LogBuffer::new(2);
+        buffer.push(record("first", Level::INFO));
+        buffer.push(record("second", Level::DEBUG));
+        buffer.push(record("third", Level::WARN));
+
+        assert_eq!(buffer.len(), 2);
+        let messages: Vec<_> =
+            buffer.records(Level::TRACE).into_iter().map(|r| r.message).collect();
+        assert_eq!(messages, ["second", "third"]);
+
+        // Filtering keeps only records at least as severe as the minimum
+        assert_eq!(buffer.records(Level::INFO), vec![record("third", Level::WARN)]);
+    }
+
+    #[test]
+    fn test_layer_formats_events() {
+        let buffer = LogBuffer::new(10);
+        let subscriber = tracing_subscriber::registry().with(LogLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = info_span!("download").entered();
+            warn!(attempt = 2, "Retrying {}", "LethalPride.zip");
+            debug!("done");
+        });
+
+        let records = buffer.records(Level::TRACE);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].level, Level::WARN);
+        assert_eq!(records[0].message, "download: Retrying LethalPride.zip attempt=2");
+        assert!(records[0].target.ends_with("logging::tests"));
+        assert_eq!(records[1].message, "download: done");
+    }
+}
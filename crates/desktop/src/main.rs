@@ -1,11 +1,19 @@
 //! PoE Item Analyzer Desktop Application
 
 mod app;
+mod export;
+mod logging;
+mod profiles;
+mod runtime;
+mod session;
+mod settings;
 mod ui;
 
 use app::AnalyzerApp;
 
 fn main() -> Result<(), eframe::Error> {
+    let log = logging::init();
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1280.0, 720.0])
@@ -16,6 +24,6 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "PoE Item Analyzer",
         options,
-        Box::new(|cc| Box::new(AnalyzerApp::new(cc))),
+        Box::new(|cc| Box::new(AnalyzerApp::new(cc, log))),
     )
 }
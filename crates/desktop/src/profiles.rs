@@ -0,0 +1,276 @@
+//! Named weight profiles saved under the platform config directory
+//!
+//! Profiles are shared as bundles: one JSON file with several profiles and
+//! who made them, so a whole set of weights travels as one download.
+
+use poe_item_analyzer_core::analyzers::TimelessJewelConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// A named set of mod weights
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightProfile {
+    /// Name shown in the profile picker
+    pub name: String,
+
+    /// Weights to analyze with
+    pub config: TimelessJewelConfig,
+}
+
+/// Several weight profiles in one shareable file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileBundle {
+    /// Who made the profiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    /// What the profiles are for (e.g., "League starter melee builds")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Anything else worth recording (e.g., "league": "Settlers")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+
+    pub profiles: Vec<WeightProfile>,
+}
+
+impl ProfileBundle {
+    /// Bundle of `profiles` with no details
+    pub fn new(profiles: Vec<WeightProfile>) -> Self {
+        Self {
+            profiles,
+            ..Self::default()
+        }
+    }
+
+    /// Write the bundle to `path`
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+
+    /// Read the bundle at `path`
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Directory of saved weight profiles, one JSON file each
+#[derive(Debug, Clone)]
+pub struct ProfileStore {
+    dir: PathBuf,
+}
+
+impl ProfileStore {
+    /// Store profiles in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Default profile directory (e.g., ~/.config/poe-item-analyzer/profiles)
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("poe-item-analyzer").join("profiles"))
+    }
+
+    /// Directory the profiles are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// All saved profiles, sorted by name
+    ///
+    /// Unreadable files are skipped so one bad profile doesn't hide the rest.
+    pub fn list(&self) -> io::Result<Vec<WeightProfile>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut profiles = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            match Self::load(&path) {
+                Ok(profile) => profiles.push(profile),
+                Err(e) => warn!("Skipping profile {}: {}", path.display(), e),
+            }
+        }
+
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+
+    /// Save `profile`, replacing any profile with the same name
+    pub fn save(&self, profile: &WeightProfile) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let json = serde_json::to_string_pretty(profile)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(self.path(&profile.name), json)
+    }
+
+    /// Delete the profile named `name` (missing profiles are ignored)
+    pub fn delete(&self, name: &str) -> io::Result<()> {
+        match std::fs::remove_file(self.path(name)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write every saved profile to `path` as a bundle
+    ///
+    /// Returns how many profiles were written.
+    pub fn export_bundle(&self, path: &Path) -> io::Result<usize> {
+        let bundle = ProfileBundle::new(self.list()?);
+        bundle.save(path)?;
+        Ok(bundle.profiles.len())
+    }
+
+    /// Save the profiles in the bundle at `path`
+    ///
+    /// Profiles whose name is taken by a different saved profile are not
+    /// saved but returned, so they can be renamed or replaced on purpose.
+    /// Profiles identical to a saved one are skipped.
+    pub fn import_bundle(&self, path: &Path) -> io::Result<Vec<(String, TimelessJewelConfig)>> {
+        let mut collisions = Vec::new();
+        for profile in ProfileBundle::load(path)?.profiles {
+            match self.get(&profile.name) {
+                Some(saved) if saved.config == profile.config => {}
+                Some(_) => collisions.push((profile.name, profile.config)),
+                None => self.save(&profile)?,
+            }
+        }
+        Ok(collisions)
+    }
+
+    /// Saved profile stored under `name`'s file, if there is one
+    ///
+    /// Names that only differ in characters unsafe in file names share a
+    /// file, so this is what saving under `name` would replace.
+    pub fn get(&self, name: &str) -> Option<WeightProfile> {
+        Self::load(&self.path(name)).ok()
+    }
+
+    fn load(path: &Path) -> io::Result<WeightProfile> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// File for `name`, with characters unsafe in file names replaced
+    fn path(&self, name: &str) -> PathBuf {
+        let file_name: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", file_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn profile(name: &str, weight: f64) -> WeightProfile {
+        let mut config = TimelessJewelConfig::new();
+        config.add_mod("Double Damage".to_string(), weight);
+        WeightProfile {
+            name: name.to_string(),
+            config,
+        }
+    }
+
+    #[test]
+    fn test_save_list_and_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ProfileStore::new(temp_dir.path().join("profiles"));
+
+        assert!(store.list().unwrap().is_empty());
+
+        store.save(&profile("Offense / DD", 5.0)).unwrap();
+        store.save(&profile("Defense", -1.0)).unwrap();
+        store.save(&profile("Offense / DD", 6.0)).unwrap();
+
+        let profiles = store.list().unwrap();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0], profile("Defense", -1.0));
+        assert_eq!(profiles[1], profile("Offense / DD", 6.0));
+
+        store.delete("Defense").unwrap();
+        store.delete("Defense").unwrap();
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ProfileStore::new(temp_dir.path().join("mine"));
+        store.save(&profile("Offense", 5.0)).unwrap();
+        store.save(&profile("Defense", -1.0)).unwrap();
+
+        let path = temp_dir.path().join("bundle.json");
+        assert_eq!(store.export_bundle(&path).unwrap(), 2);
+
+        let friend = ProfileStore::new(temp_dir.path().join("friend"));
+        assert!(friend.import_bundle(&path).unwrap().is_empty());
+        assert_eq!(friend.list().unwrap(), store.list().unwrap());
+
+        // Details survive too
+        let bundle = ProfileBundle {
+            author: Some("someone".to_string()),
+            description: Some("DD stacking".to_string()),
+            metadata: [("league".to_string(), "Settlers".to_string())].into(),
+            profiles: vec![profile("Offense", 5.0)],
+        };
+        bundle.save(&path).unwrap();
+        assert_eq!(ProfileBundle::load(&path).unwrap(), bundle);
+    }
+
+    #[test]
+    fn test_bundle_import_returns_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ProfileStore::new(temp_dir.path().join("profiles"));
+        store.save(&profile("Offense", 5.0)).unwrap();
+        store.save(&profile("Defense", -1.0)).unwrap();
+
+        let path = temp_dir.path().join("bundle.json");
+        ProfileBundle::new(vec![
+            profile("Offense", 8.0),
+            profile("Defense", -1.0),
+            profile("Speed", 2.0),
+        ])
+        .save(&path)
+        .unwrap();
+
+        let collisions = store.import_bundle(&path).unwrap();
+        assert_eq!(collisions, vec![("Offense".to_string(), profile("Offense", 8.0).config)]);
+
+        // The saved profile is kept; the new one is saved
+        assert_eq!(store.get("Offense"), Some(profile("Offense", 5.0)));
+        assert_eq!(store.get("Speed"), Some(profile("Speed", 2.0)));
+        assert_eq!(store.list().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_list_skips_corrupt_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ProfileStore::new(temp_dir.path());
+
+        store.save(&profile("Good", 1.0)).unwrap();
+        std::fs::write(temp_dir.path().join("bad.json"), "{ not json").unwrap();
+
+        let profiles = store.list().unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].name, "Good");
+    }
+}
@@ -0,0 +1,101 @@
+//! Shared tokio runtime for network work
+
+use std::future::Future;
+use std::io;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use tokio::runtime::{Handle, Runtime};
+use tracing::warn;
+
+/// Worker threads of the shared runtime; the work is network-bound
+const WORKER_THREADS: usize = 2;
+
+/// How long shutdown waits for tasks before abandoning them
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Multi-threaded runtime owned by the app
+///
+/// Dropping it cancels the tasks still running instead of waiting for them,
+/// so closing the window mid-download doesn't hang.
+pub struct TaskRuntime {
+    runtime: Option<Runtime>,
+}
+
+impl TaskRuntime {
+    pub fn new() -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(WORKER_THREADS)
+            .thread_name("analyzer-worker")
+            .enable_all()
+            .build()?;
+
+        Ok(Self {
+            runtime: Some(runtime),
+        })
+    }
+
+    /// Handle for code that spawns its own tasks (e.g., the update watcher)
+    pub fn handle(&self) -> &Handle {
+        self.runtime().handle()
+    }
+
+    /// Run `future` on the runtime and send its output to `tx`
+    pub fn spawn_task<F, M>(&self, tx: Sender<M>, future: F)
+    where
+        F: Future<Output = M> + Send + 'static,
+        M: Send + 'static,
+    {
+        self.runtime().spawn(async move {
+            if tx.send(future.await).is_err() {
+                warn!("Task finished after the app stopped listening");
+            }
+        });
+    }
+
+    fn runtime(&self) -> &Runtime {
+        self.runtime.as_ref().expect("runtime is only taken on drop")
+    }
+}
+
+impl Drop for TaskRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_timeout(SHUTDOWN_TIMEOUT);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use std::time::Instant;
+
+    #[test]
+    fn test_spawn_task_sends_output() {
+        let runtime = TaskRuntime::new().unwrap();
+        let (tx, rx) = channel();
+
+        runtime.spawn_task(tx, async { 42 });
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(42));
+    }
+
+    #[test]
+    fn test_drop_does_not_wait_for_pending_tasks() {
+        let runtime = TaskRuntime::new().unwrap();
+        let (tx, rx) = channel();
+
+        runtime.spawn_task(tx, async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+
+        let start = Instant::now();
+        drop(runtime);
+
+        assert!(start.elapsed() < SHUTDOWN_TIMEOUT);
+        // The task was dropped without sending anything
+        assert!(rx.recv().is_err());
+    }
+}
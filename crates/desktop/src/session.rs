@@ -0,0 +1,251 @@
+//! Saved sessions: imported jewels, weights and the last results
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use poe_item_analyzer_core::analyzers::{
+    RankedResult, TimelessJewelAnalysisResult, TimelessJewelConfig,
+};
+use poe_item_analyzer_core::items::TimelessJewelRecord;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Version written to new session files
+pub const SESSION_VERSION: u32 = 1;
+
+/// Everything a session file holds
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Session {
+    /// Format version, for files written by later releases
+    pub version: u32,
+    /// Version of the LUT the results were computed with
+    pub lut_version: Option<String>,
+    /// Weight profile that was active, if any
+    pub profile: Option<String>,
+    /// Weights the results were computed with
+    pub config: TimelessJewelConfig,
+    /// Imported jewels, with their original item data
+    pub jewels: Vec<TimelessJewelRecord>,
+    /// Last ranking of the jewels, best first
+    pub ranked: Vec<RankedResult<TimelessJewelAnalysisResult>>,
+    /// Last result of the analyze form
+    pub analysis: Option<TimelessJewelAnalysisResult>,
+}
+
+/// A session read back from disk
+#[derive(Debug)]
+pub struct RestoredSession {
+    pub session: Session,
+    /// What couldn't be restored (e.g., "Jewel 3: missing field `seed`")
+    pub problems: Vec<String>,
+}
+
+/// Why a session file couldn't be opened at all
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    /// Not JSON, or not a JSON object
+    Corrupt(String),
+    /// Written by a newer release (or missing its version)
+    UnsupportedVersion(Option<u64>),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Io(e) => write!(f, "Could not read the session: {}", e),
+            SessionError::Corrupt(reason) => write!(f, "Not a session file: {}", reason),
+            SessionError::UnsupportedVersion(Some(version)) => write!(
+                f,
+                "Session version {} is not supported (expected {} or older)",
+                version, SESSION_VERSION
+            ),
+            SessionError::UnsupportedVersion(None) => {
+                write!(f, "Not a session file: it has no version")
+            }
+        }
+    }
+}
+
+/// Write `session` to `path` as pretty JSON
+pub fn save_session(path: &Path, session: &Session) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(session)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, json)
+}
+
+/// Read the session in `path`
+pub fn load_session(path: &Path) -> Result<RestoredSession, SessionError> {
+    let json = std::fs::read_to_string(path).map_err(SessionError::Io)?;
+    read_session(&json)
+}
+
+/// Read a session from JSON
+///
+/// Each part is restored on its own: a bad jewel or an unreadable result is
+/// left out and listed in `problems` instead of failing the whole session.
+pub fn read_session(json: &str) -> Result<RestoredSession, SessionError> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| SessionError::Corrupt(e.to_string()))?;
+    let Value::Object(mut fields) = value else {
+        return Err(SessionError::Corrupt("expected a JSON object".to_string()));
+    };
+
+    let version = fields.get("version").and_then(Value::as_u64);
+    match version {
+        Some(version) if version >= 1 && version <= u64::from(SESSION_VERSION) => {}
+        _ => return Err(SessionError::UnsupportedVersion(version)),
+    }
+
+    let mut problems = Vec::new();
+    let mut session = Session {
+        version: SESSION_VERSION,
+        lut_version: restore(&mut fields, "lut_version", "Data version", &mut problems)
+            .flatten(),
+        profile: restore(&mut fields, "profile", "Weight profile", &mut problems).flatten(),
+        config: restore(&mut fields, "config", "Weights", &mut problems).unwrap_or_default(),
+        ranked: restore(&mut fields, "ranked", "Ranking", &mut problems).unwrap_or_default(),
+        analysis: restore(&mut fields, "analysis", "Analysis result", &mut problems).flatten(),
+        ..Session::default()
+    };
+
+    let jewels = take_field(&mut fields, "jewels", "Jewels", &mut problems);
+    match jewels {
+        Some(Value::Array(jewels)) => {
+            for (index, jewel) in jewels.into_iter().enumerate() {
+                match serde_json::from_value(jewel) {
+                    Ok(record) => session.jewels.push(record),
+                    Err(e) => problems.push(format!("Jewel {}: {}", index + 1, e)),
+                }
+            }
+        }
+        Some(_) => problems.push("Jewels: expected a list".to_string()),
+        None => {}
+    }
+
+    Ok(RestoredSession { session, problems })
+}
+
+/// Remove `name` from `fields`; a missing field is a problem, a null one isn't
+fn take_field(
+    fields: &mut Map<String, Value>,
+    name: &str,
+    label: &str,
+    problems: &mut Vec<String>,
+) -> Option<Value> {
+    let value = fields.remove(name);
+    if value.is_none() {
+        problems.push(format!("{}: missing", label));
+    }
+    value
+}
+
+/// Take `name` from `fields` and deserialize it, recording why it couldn't be read
+fn restore<T: DeserializeOwned>(
+    fields: &mut Map<String, Value>,
+    name: &str,
+    label: &str,
+    problems: &mut Vec<String>,
+) -> Option<T> {
+    let value = take_field(fields, name, label, problems)?;
+    match serde_json::from_value(value) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            problems.push(format!("{}: {}", label, e));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poe_item_analyzer_core::analyzers::{Analyzer, TimelessJewelAnalyzer};
+    use poe_item_analyzer_core::items::{Item, JewelType, TimelessJewel};
+    use serde_json::json;
+
+    fn session() -> Session {
+        let jewel = TimelessJewel::new(
+            "Lethal Pride:14032:Kaom".to_string(),
+            JewelType::LethalPride,
+            14032,
+            "Kaom".to_string(),
+            json!({ "text": "Lethal Pride" }),
+        );
+        let mut config = TimelessJewelConfig::new();
+        config.add_mod("Onslaught".to_string(), 2.0);
+        let analysis = TimelessJewelAnalyzer::new().analyze(&jewel, &config).unwrap();
+
+        Session {
+            version: SESSION_VERSION,
+            lut_version: Some("abc123".to_string()),
+            profile: Some("Offense".to_string()),
+            config,
+            jewels: vec![jewel.to_record()],
+            ranked: vec![RankedResult {
+                rank: 1,
+                result: analysis.clone(),
+            }],
+            analysis: Some(analysis),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("session.json");
+        let saved = session();
+
+        save_session(&path, &saved).unwrap();
+        let restored = load_session(&path).unwrap();
+
+        assert!(restored.problems.is_empty(), "{:?}", restored.problems);
+        let session = restored.session;
+        assert_eq!(session.lut_version, saved.lut_version);
+        assert_eq!(session.profile, saved.profile);
+        assert_eq!(session.config, saved.config);
+        assert_eq!(session.jewels, saved.jewels);
+        assert_eq!(session.ranked.len(), 1);
+        assert_eq!(session.ranked[0].result.jewel.id(), "Lethal Pride:14032:Kaom");
+        assert_eq!(session.analysis.unwrap().jewel.seed, 14032);
+    }
+
+    #[test]
+    fn test_partial_restore_lists_problems() {
+        let mut json = serde_json::to_value(session()).unwrap();
+        json["jewels"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "id": "broken", "jewel_type": "LethalPride" }));
+        json["ranked"] = json!("not a list");
+        json.as_object_mut().unwrap().remove("analysis");
+
+        let restored = read_session(&json.to_string()).unwrap();
+
+        assert_eq!(restored.session.jewels.len(), 1);
+        assert!(restored.session.ranked.is_empty());
+        assert_eq!(restored.session.config, session().config);
+        assert_eq!(restored.problems.len(), 3, "{:?}", restored.problems);
+        assert!(restored.problems[0].starts_with("Ranking: "));
+        assert_eq!(restored.problems[1], "Analysis result: missing");
+        assert!(restored.problems[2].starts_with("Jewel 2: missing field `seed`"));
+    }
+
+    #[test]
+    fn test_rejects_unreadable_sessions() {
+        assert!(matches!(read_session("{"), Err(SessionError::Corrupt(_))));
+        assert!(matches!(read_session("[]"), Err(SessionError::Corrupt(_))));
+        assert!(matches!(
+            read_session(r#"{"jewels": []}"#),
+            Err(SessionError::UnsupportedVersion(None))
+        ));
+
+        let error = read_session(r#"{"version": 2}"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Session version 2 is not supported (expected 1 or older)"
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Application settings saved under the platform config directory
+
+use poe_item_analyzer_api::HttpConfig;
+use poe_item_analyzer_core::items::JewelType;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Settings kept across restarts
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Directory the PoB data versions are downloaded to
+    pub data_dir: PathBuf,
+
+    /// Data version in use (None for the most recently updated one)
+    pub data_version: Option<String>,
+
+    /// Weight profile selected in the analysis tabs
+    pub active_profile: Option<String>,
+
+    /// Last used jewel type
+    pub jewel_type: JewelType,
+
+    /// Last used socket id (None for all affected nodes)
+    pub socket: Option<String>,
+
+    /// GitHub token for update checks (raises the API rate limit)
+    pub github_token: Option<String>,
+
+    /// Whether to check for new PoB data in the background
+    pub auto_check_updates: bool,
+
+    /// Whether analyses measure and show where they spend their time
+    pub show_timings: bool,
+
+    /// Path of Exile account name for stash imports
+    pub poe_account: String,
+
+    /// League whose stash is imported
+    pub poe_league: String,
+
+    /// `POESESSID` cookie for stash imports (never logged)
+    pub poe_session_id: Option<String>,
+
+    /// Seconds allowed to connect to a server
+    pub connect_timeout_secs: u64,
+
+    /// Seconds a request may go without receiving data
+    pub read_timeout_secs: u64,
+
+    /// Idle connections kept open per host
+    pub max_idle_connections: usize,
+
+    /// Proxy for all requests (e.g., "http://proxy.local:8080")
+    pub proxy: Option<String>,
+}
+
+impl Settings {
+    /// Connection settings for the HTTP clients
+    pub fn http_config(&self) -> HttpConfig {
+        HttpConfig {
+            connect_timeout: Duration::from_secs(self.connect_timeout_secs),
+            read_timeout: Duration::from_secs(self.read_timeout_secs),
+            max_idle_connections: self.max_idle_connections,
+            proxy: self.proxy.clone(),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let http = HttpConfig::default();
+        Self {
+            data_dir: default_data_dir(),
+            data_version: None,
+            active_profile: None,
+            jewel_type: JewelType::LethalPride,
+            socket: None,
+            github_token: None,
+            auto_check_updates: true,
+            show_timings: false,
+            poe_account: String::new(),
+            poe_league: "Standard".to_string(),
+            poe_session_id: None,
+            connect_timeout_secs: http.connect_timeout.as_secs(),
+            read_timeout_secs: http.read_timeout.as_secs(),
+            max_idle_connections: http.max_idle_connections,
+            proxy: None,
+        }
+    }
+}
+
+/// Default data directory (e.g., ~/.local/share/poe-item-analyzer/pob-data)
+pub fn default_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("poe-item-analyzer").join("pob-data"))
+        .unwrap_or_else(legacy_data_dir)
+}
+
+/// Temp directory older versions downloaded the data to
+pub fn legacy_data_dir() -> PathBuf {
+    std::env::temp_dir().join("poe-item-analyzer-test")
+}
+
+/// Copy data from `legacy_dir` into a `data_dir` that doesn't exist yet
+///
+/// Returns whether anything was copied. Only top-level files are copied;
+/// leftover staging directories are not worth keeping.
+pub fn migrate_legacy_data(legacy_dir: &Path, data_dir: &Path) -> io::Result<bool> {
+    if data_dir.exists() || !legacy_dir.is_dir() || legacy_dir == data_dir {
+        return Ok(false);
+    }
+
+    std::fs::create_dir_all(data_dir)?;
+
+    let mut copied = false;
+    for entry in std::fs::read_dir(legacy_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            std::fs::copy(entry.path(), data_dir.join(entry.file_name()))?;
+            copied = true;
+        }
+    }
+
+    Ok(copied)
+}
+
+/// Settings file on disk
+#[derive(Debug, Clone)]
+pub struct SettingsStore {
+    path: PathBuf,
+}
+
+impl SettingsStore {
+    /// Store settings in the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Default settings file (e.g., ~/.config/poe-item-analyzer/settings.json)
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("poe-item-analyzer").join("settings.json"))
+    }
+
+    /// Whether a settings file has been saved before
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Load the settings, falling back to defaults
+    ///
+    /// A corrupt file is renamed to `settings.json.bak` and the returned
+    /// message explains what happened.
+    pub fn load(&self) -> (Settings, Option<String>) {
+        let json = match std::fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return (Settings::default(), None),
+            Err(e) => {
+                let message = format!("Could not read {}: {}", self.path.display(), e);
+                return (Settings::default(), Some(message));
+            }
+        };
+
+        match serde_json::from_str(&json) {
+            Ok(settings) => (settings, None),
+            Err(e) => {
+                let backup = self.path.with_extension("json.bak");
+                let message = match std::fs::rename(&self.path, &backup) {
+                    Ok(()) => format!(
+                        "Settings were corrupt ({}); reset to defaults, old file saved as {}",
+                        e,
+                        backup.display()
+                    ),
+                    Err(rename_error) => format!(
+                        "Settings were corrupt ({}) and could not be backed up: {}",
+                        e, rename_error
+                    ),
+                };
+                (Settings::default(), Some(message))
+            }
+        }
+    }
+
+    /// Save the settings
+    ///
+    /// On Unix the file is only readable by the user, since it holds the
+    /// GitHub token and the session id. It is written to a temporary file
+    /// created that way and renamed into place, so the secrets are never
+    /// readable by others, not even while saving.
+    pub fn save(&self, settings: &Settings) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(settings)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut temp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp_path = self.path.with_file_name(temp_name);
+        // Left over from an interrupted save, possibly with other permissions
+        let _ = std::fs::remove_file(&temp_path);
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let written = options.open(&temp_path).and_then(|mut file| {
+            file.write_all(json.as_bytes())?;
+            file.sync_all()
+        });
+        let result = written.and_then(|()| std::fs::rename(&temp_path, &self.path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_returns_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SettingsStore::new(temp_dir.path().join("settings.json"));
+
+        let (settings, warning) = store.load();
+        assert!(settings == Settings::default());
+        assert!(warning.is_none());
+        assert!(!store.exists());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SettingsStore::new(temp_dir.path().join("nested").join("settings.json"));
+
+        let settings = Settings {
+            data_dir: PathBuf::from("/data/pob"),
+            data_version: Some("abc123".to_string()),
+            active_profile: Some("Offense".to_string()),
+            jewel_type: JewelType::ElegantHubris,
+            github_token: Some("ghp_token".to_string()),
+            auto_check_updates: false,
+            poe_account: "Some Account".to_string(),
+            poe_session_id: Some("0123456789abcdef".to_string()),
+            read_timeout_secs: 90,
+            proxy: Some("http://proxy.local:8080".to_string()),
+            ..Settings::default()
+        };
+        store.save(&settings).unwrap();
+
+        let (loaded, warning) = store.load();
+        assert!(loaded == settings);
+        assert!(warning.is_none());
+    }
+
+    #[test]
+    fn test_http_config() {
+        assert_eq!(Settings::default().http_config(), HttpConfig::default());
+
+        let settings = Settings {
+            connect_timeout_secs: 5,
+            read_timeout_secs: 90,
+            max_idle_connections: 2,
+            proxy: Some("http://proxy.local:8080".to_string()),
+            ..Settings::default()
+        };
+        let config = settings.http_config();
+
+        assert_eq!(config.connect_timeout, Duration::from_secs(5));
+        assert_eq!(config.read_timeout, Duration::from_secs(90));
+        assert_eq!(config.max_idle_connections, 2);
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.local:8080"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_saved_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+
+        // Also when replacing a file readable by others
+        std::fs::write(&path, "{}").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        SettingsStore::new(&path).save(&Settings::default()).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, r#"{"auto_check_updates": false}"#).unwrap();
+
+        let (settings, _) = SettingsStore::new(&path).load();
+        assert!(!settings.auto_check_updates);
+        assert_eq!(settings.jewel_type, JewelType::LethalPride);
+    }
+
+    #[test]
+    fn test_corrupt_settings_are_backed_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let (settings, warning) = SettingsStore::new(&path).load();
+
+        assert!(settings == Settings::default());
+        assert!(warning.unwrap().contains("settings.json.bak"));
+        assert!(!path.exists());
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("settings.json.bak")).unwrap(),
+            "{ not json"
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let legacy_dir = temp_dir.path().join("legacy");
+        let data_dir = temp_dir.path().join("data");
+        std::fs::create_dir_all(legacy_dir.join(".staging")).unwrap();
+        std::fs::write(legacy_dir.join("LethalPride.zip"), b"zip").unwrap();
+
+        assert!(migrate_legacy_data(&legacy_dir, &data_dir).unwrap());
+        assert_eq!(std::fs::read(data_dir.join("LethalPride.zip")).unwrap(), b"zip");
+        assert!(!data_dir.join(".staging").exists());
+
+        // Only once: an existing data directory is left alone
+        std::fs::write(legacy_dir.join("LethalPride.zip"), b"newer").unwrap();
+        assert!(!migrate_legacy_data(&legacy_dir, &data_dir).unwrap());
+        assert_eq!(std::fs::read(data_dir.join("LethalPride.zip")).unwrap(), b"zip");
+    }
+}
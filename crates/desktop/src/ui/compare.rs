@@ -0,0 +1,410 @@
+//! Side-by-side seed comparison tab
+
+use poe_item_analyzer_core::analyzers::{JewelComparison, SocketComparison};
+use poe_item_analyzer_core::items::{JewelType, TimelessJewel};
+use serde_json::Value;
+
+use super::timeless_jewels::parse_seed;
+
+/// Colors of the three kinds of rows
+const SHARED_COLOR: egui::Color32 = egui::Color32::GRAY;
+const ONLY_A_COLOR: egui::Color32 = egui::Color32::LIGHT_BLUE;
+const ONLY_B_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 200, 100);
+
+/// Which jewels provide a mod
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RowKind {
+    Shared,
+    OnlyA,
+    OnlyB,
+}
+
+/// A mod row of the comparison table
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareRow {
+    pub kind: RowKind,
+    pub mod_text: String,
+    /// Count for A, blank if A lacks the mod
+    pub count_a: String,
+    /// Count for B, blank if B lacks the mod
+    pub count_b: String,
+}
+
+/// Rows for a socket: shared mods first, then A's, then B's, each by mod text
+pub fn display_rows(socket: &SocketComparison) -> Vec<CompareRow> {
+    let count = |count: usize| {
+        if count == 0 {
+            String::new()
+        } else {
+            format!("{}×", count)
+        }
+    };
+
+    let mut rows: Vec<CompareRow> = socket
+        .mods
+        .iter()
+        .map(|diff| CompareRow {
+            kind: if diff.is_shared() {
+                RowKind::Shared
+            } else if diff.count_b == 0 {
+                RowKind::OnlyA
+            } else {
+                RowKind::OnlyB
+            },
+            mod_text: diff.mod_text.clone(),
+            count_a: count(diff.count_a),
+            count_b: count(diff.count_b),
+        })
+        .collect();
+
+    rows.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.mod_text.cmp(&b.mod_text)));
+    rows
+}
+
+/// Score delta with its sign (e.g., "+2.5", "-1.0", "±0.0")
+pub fn format_delta(delta: f64) -> String {
+    if delta.abs() < 0.05 {
+        "±0.0".to_string()
+    } else {
+        format!("{:+.1}", delta)
+    }
+}
+
+/// One of the two jewels being compared
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareSide {
+    pub conqueror: String,
+    pub seed_text: String,
+}
+
+/// State of the compare tab
+pub struct CompareState {
+    /// Jewel type of both jewels
+    pub jewel_type: JewelType,
+    pub a: CompareSide,
+    pub b: CompareSide,
+    /// Whether the comparison is running
+    pub running: bool,
+    /// Why the last comparison could not run or failed
+    pub error: Option<String>,
+    /// Result of the last comparison
+    pub comparison: Option<JewelComparison>,
+    /// Socket shown in the mod table
+    pub socket_id: Option<String>,
+}
+
+impl Default for CompareState {
+    fn default() -> Self {
+        let jewel_type = JewelType::LethalPride;
+        let side = CompareSide {
+            conqueror: jewel_type.conquerors()[0].to_string(),
+            seed_text: String::new(),
+        };
+
+        Self {
+            jewel_type,
+            a: side.clone(),
+            b: side,
+            running: false,
+            error: None,
+            comparison: None,
+            socket_id: None,
+        }
+    }
+}
+
+impl CompareState {
+    /// Fill in both jewels (e.g., from the seed search results)
+    pub fn set_pair(&mut self, jewel_type: JewelType, a: (&str, u32), b: (&str, u32)) {
+        self.jewel_type = jewel_type;
+        self.a = CompareSide {
+            conqueror: a.0.to_string(),
+            seed_text: a.1.to_string(),
+        };
+        self.b = CompareSide {
+            conqueror: b.0.to_string(),
+            seed_text: b.1.to_string(),
+        };
+    }
+
+    /// Build both jewels from the form
+    ///
+    /// Returns a message for the user if a seed is invalid.
+    pub fn build_request(&self) -> Result<(TimelessJewel, TimelessJewel), String> {
+        let jewel = |side: &CompareSide| -> Result<TimelessJewel, String> {
+            let seed = parse_seed(self.jewel_type, &side.seed_text)?;
+
+            Ok(TimelessJewel::new(
+                format!("{}:{}:{}", self.jewel_type.as_str(), seed, side.conqueror),
+                self.jewel_type,
+                seed,
+                side.conqueror.clone(),
+                Value::Null,
+            ))
+        };
+
+        Ok((jewel(&self.a)?, jewel(&self.b)?))
+    }
+
+    /// Socket comparison shown in the mod table
+    pub fn selected_socket(&self) -> Option<&SocketComparison> {
+        let comparison = self.comparison.as_ref()?;
+
+        self.socket_id
+            .as_deref()
+            .and_then(|id| comparison.socket(id))
+            .or_else(|| comparison.sockets.first())
+    }
+
+    /// Render the form, returning true when Compare was clicked
+    ///
+    /// `session` lists imported jewels that can fill in either side.
+    pub fn render_form(&mut self, ui: &mut egui::Ui, session: &[TimelessJewel]) -> bool {
+        egui::Grid::new("compare_form_grid")
+            .num_columns(2)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                ui.label("Jewel:");
+                let previous_type = self.jewel_type;
+                egui::ComboBox::from_id_source("compare_jewel_type")
+                    .selected_text(self.jewel_type.as_str())
+                    .show_ui(ui, |ui| {
+                        for jewel_type in JewelType::ALL {
+                            ui.selectable_value(&mut self.jewel_type, jewel_type, jewel_type.as_str());
+                        }
+                    });
+                if self.jewel_type != previous_type {
+                    let conqueror = self.jewel_type.conquerors()[0].to_string();
+                    self.a.conqueror = conqueror.clone();
+                    self.b.conqueror = conqueror;
+                }
+                ui.end_row();
+
+                let jewel_type = self.jewel_type;
+                for (label, side) in [("A:", &mut self.a), ("B:", &mut self.b)] {
+                    ui.label(label);
+                    render_side(ui, label, jewel_type, side, session);
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(5.0);
+
+        ui.add_enabled(!self.running, egui::Button::new("⇄ Compare"))
+            .clicked()
+    }
+
+    /// Render the error or the per-socket comparison
+    pub fn render_results(&mut self, ui: &mut egui::Ui) {
+        if self.running {
+            ui.label("Comparing...");
+            return;
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, format!("❌ {}", error));
+            return;
+        }
+
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+
+        ui.label(format!(
+            "A: {} ({})   B: {} ({})",
+            comparison.jewel_a.seed,
+            comparison.jewel_a.conqueror,
+            comparison.jewel_b.seed,
+            comparison.jewel_b.conqueror
+        ));
+        ui.add_space(5.0);
+
+        let selected_id = self.selected_socket().map(|s| s.socket_id.clone());
+        let mut clicked_socket = None;
+
+        egui::Grid::new("compare_sockets_grid")
+            .num_columns(4)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Socket");
+                ui.strong("Score A");
+                ui.strong("Score B");
+                ui.strong("Δ (B − A)");
+                ui.end_row();
+
+                for socket in &comparison.sockets {
+                    let selected = selected_id.as_deref() == Some(socket.socket_id.as_str());
+                    if ui.selectable_label(selected, &socket.socket_name).clicked() {
+                        clicked_socket = Some(socket.socket_id.clone());
+                    }
+                    ui.monospace(format!("{:.1}", socket.score_a));
+                    ui.monospace(format!("{:.1}", socket.score_b));
+
+                    let delta = socket.score_delta();
+                    let color = if delta > 0.0 {
+                        egui::Color32::GREEN
+                    } else if delta < 0.0 {
+                        egui::Color32::LIGHT_RED
+                    } else {
+                        egui::Color32::GRAY
+                    };
+                    ui.colored_label(color, format_delta(delta));
+                    ui.end_row();
+                }
+            });
+
+        if clicked_socket.is_some() {
+            self.socket_id = clicked_socket;
+        }
+
+        let Some(socket) = self.selected_socket() else {
+            return;
+        };
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.strong(&socket.socket_name);
+            ui.colored_label(SHARED_COLOR, "■ shared");
+            ui.colored_label(ONLY_A_COLOR, "■ only A");
+            ui.colored_label(ONLY_B_COLOR, "■ only B");
+        });
+
+        egui::ScrollArea::vertical()
+            .id_source("compare_mods_scroll")
+            .max_height(400.0)
+            .show(ui, |ui| {
+                egui::Grid::new("compare_mods_grid")
+                    .num_columns(3)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("A");
+                        ui.strong("B");
+                        ui.strong("Mod");
+                        ui.end_row();
+
+                        for row in display_rows(socket) {
+                            let color = match row.kind {
+                                RowKind::Shared => SHARED_COLOR,
+                                RowKind::OnlyA => ONLY_A_COLOR,
+                                RowKind::OnlyB => ONLY_B_COLOR,
+                            };
+                            ui.monospace(row.count_a);
+                            ui.monospace(row.count_b);
+                            ui.colored_label(color, row.mod_text);
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
+
+/// Conqueror and seed inputs of one side, plus a session jewel picker
+fn render_side(
+    ui: &mut egui::Ui,
+    id: &str,
+    jewel_type: JewelType,
+    side: &mut CompareSide,
+    session: &[TimelessJewel],
+) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(("compare_conqueror", id))
+            .selected_text(side.conqueror.as_str())
+            .show_ui(ui, |ui| {
+                for conqueror in jewel_type.conquerors() {
+                    ui.selectable_value(&mut side.conqueror, conqueror.to_string(), *conqueror);
+                }
+            });
+
+        let range = jewel_type.seed_range();
+        ui.add(
+            egui::TextEdit::singleline(&mut side.seed_text)
+                .hint_text(format!("{}-{}", range.start(), range.end()))
+                .desired_width(100.0),
+        );
+    });
+
+    let matching: Vec<&TimelessJewel> = session
+        .iter()
+        .filter(|jewel| jewel.jewel_type == jewel_type)
+        .collect();
+
+    ui.add_enabled_ui(!matching.is_empty(), |ui| {
+        egui::ComboBox::from_id_source(("compare_session", id))
+            .selected_text("From session")
+            .show_ui(ui, |ui| {
+                for jewel in matching {
+                    let label = format!("{} ({})", jewel.seed, jewel.conqueror);
+                    if ui.selectable_label(false, label).clicked() {
+                        side.conqueror = jewel.conqueror.clone();
+                        side.seed_text = jewel.seed.to_string();
+                    }
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poe_item_analyzer_core::analyzers::ModDiff;
+    use poe_item_analyzer_core::items::Item;
+
+    fn diff(mod_text: &str, count_a: usize, count_b: usize) -> ModDiff {
+        ModDiff {
+            mod_text: mod_text.to_string(),
+            count_a,
+            count_b,
+        }
+    }
+
+    #[test]
+    fn test_display_rows_group_shared_then_a_then_b() {
+        let socket = SocketComparison {
+            socket_id: "all".to_string(),
+            socket_name: "All affected nodes".to_string(),
+            score_a: 5.0,
+            score_b: 7.5,
+            mods: vec![
+                diff("Onslaught", 0, 1),
+                diff("Double Damage", 2, 1),
+                diff("Armour", 1, 0),
+                diff("Accuracy", 0, 3),
+            ],
+        };
+
+        let rows = display_rows(&socket);
+        let order: Vec<(RowKind, &str)> =
+            rows.iter().map(|r| (r.kind, r.mod_text.as_str())).collect();
+
+        assert_eq!(
+            order,
+            vec![
+                (RowKind::Shared, "Double Damage"),
+                (RowKind::OnlyA, "Armour"),
+                (RowKind::OnlyB, "Accuracy"),
+                (RowKind::OnlyB, "Onslaught"),
+            ]
+        );
+        assert_eq!(rows[0].count_a, "2×");
+        assert_eq!(rows[1].count_b, "");
+        assert_eq!(format_delta(socket.score_delta()), "+2.5");
+        assert_eq!(format_delta(-1.0), "-1.0");
+        assert_eq!(format_delta(0.0), "±0.0");
+    }
+
+    #[test]
+    fn test_build_request_validates_both_seeds() {
+        let mut state = CompareState::default();
+        state.set_pair(JewelType::LethalPride, ("Kaom", 14032), ("Akoya", 10000));
+
+        let (a, b) = state.build_request().unwrap();
+        assert_eq!((a.seed, a.conqueror.as_str()), (14032, "Kaom"));
+        assert_eq!((b.seed, b.conqueror.as_str()), (10000, "Akoya"));
+        assert_ne!(a.id(), b.id());
+
+        state.b.seed_text = "9".to_string();
+        assert!(state.build_request().is_err());
+    }
+}
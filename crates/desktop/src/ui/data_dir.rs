@@ -0,0 +1,202 @@
+//! Data directory selection and per-file status
+
+use std::path::{Path, PathBuf};
+
+use poe_item_analyzer_api::{
+    DataDownloader, DataManifest, DownloadError, ValidationResult, ValidationStatus,
+};
+
+use crate::app::format_bytes;
+
+/// A required data file as found on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileStatus {
+    pub result: ValidationResult,
+    /// File size, if the file exists
+    pub bytes: Option<u64>,
+}
+
+/// Validation state of the selected data directory
+#[derive(Default)]
+pub struct DataDirState {
+    /// Whether the directory is being validated
+    pub validating: bool,
+    /// Required files of the last validated directory
+    pub files: Vec<FileStatus>,
+    /// Error that prevented validation
+    pub error: Option<String>,
+}
+
+impl DataDirState {
+    /// Render the per-file status table and what's wrong, if anything
+    pub fn render(&self, ui: &mut egui::Ui) {
+        if self.validating {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Checking data files...");
+            });
+            return;
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, format!("✗ {}", error));
+            return;
+        }
+        if self.files.is_empty() {
+            return;
+        }
+
+        egui::Grid::new("data_dir_files_grid")
+            .num_columns(3)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for file in &self.files {
+                    ui.label(&file.result.file_name);
+                    match &file.result.status {
+                        ValidationStatus::Valid => {
+                            ui.colored_label(egui::Color32::GREEN, "✓ found");
+                        }
+                        ValidationStatus::Missing => {
+                            ui.colored_label(egui::Color32::RED, "✗ missing");
+                        }
+                        ValidationStatus::TooSmall { .. } => {
+                            ui.colored_label(egui::Color32::RED, "✗ too small");
+                        }
+                        ValidationStatus::InvalidContent(reason) => {
+                            ui.colored_label(egui::Color32::RED, "✗ invalid")
+                                .on_hover_text(reason);
+                        }
+                    }
+                    ui.label(file.bytes.map(format_bytes).unwrap_or_default());
+                    ui.end_row();
+                }
+            });
+
+        if let Some(problems) = describe_problems(&self.files) {
+            ui.colored_label(egui::Color32::RED, problems);
+        }
+    }
+}
+
+/// Validate the files in `dir` that the manifest at `manifest_path` (or the
+/// built-in one) requires
+pub async fn validate_data_dir(
+    dir: PathBuf,
+    manifest_path: &Path,
+) -> Result<Vec<FileStatus>, DownloadError> {
+    let manifest = DataManifest::load_from_file(manifest_path)
+        .unwrap_or_else(|_| DataManifest::default_pob());
+
+    let results = DataDownloader::new(dir.clone())
+        .with_manifest(manifest)
+        .include_optional(false)
+        .validate_files()
+        .await?;
+
+    Ok(results
+        .into_iter()
+        .map(|result| FileStatus {
+            bytes: std::fs::metadata(dir.join(&result.file_name)).ok().map(|m| m.len()),
+            result,
+        })
+        .collect())
+}
+
+/// Explain which required files are missing or unusable, if any
+pub fn describe_problems(files: &[FileStatus]) -> Option<String> {
+    let names = |wanted: fn(&ValidationStatus) -> bool| -> Vec<&str> {
+        files
+            .iter()
+            .filter(|file| wanted(&file.result.status))
+            .map(|file| file.result.file_name.as_str())
+            .collect()
+    };
+
+    let missing = names(|status| *status == ValidationStatus::Missing);
+    let invalid = names(|status| {
+        matches!(status, ValidationStatus::TooSmall { .. } | ValidationStatus::InvalidContent(_))
+    });
+
+    let mut problems = Vec::new();
+    if !missing.is_empty() {
+        problems.push(format!("Missing required files: {}", missing.join(", ")));
+    }
+    if !invalid.is_empty() {
+        problems.push(format!("Unusable files: {}", invalid.join(", ")));
+    }
+
+    (!problems.is_empty()).then(|| problems.join(". "))
+}
+
+/// Directory to use for dropped paths
+///
+/// A dropped folder is used as is; for dropped files, the folder they're in.
+pub fn data_dir_from_dropped(paths: &[PathBuf]) -> Option<PathBuf> {
+    paths
+        .iter()
+        .find(|path| path.is_dir())
+        .cloned()
+        .or_else(|| paths.first().and_then(|path| path.parent()).map(Path::to_path_buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn status(file_name: &str, status: ValidationStatus) -> FileStatus {
+        FileStatus {
+            result: ValidationResult {
+                file_name: file_name.to_string(),
+                status,
+            },
+            bytes: None,
+        }
+    }
+
+    #[test]
+    fn test_describe_problems() {
+        assert_eq!(describe_problems(&[status("A.zip", ValidationStatus::Valid)]), None);
+
+        let files = [
+            status("LethalPride.zip", ValidationStatus::Missing),
+            status("LegionPassives.lua", ValidationStatus::TooSmall { bytes: 9 }),
+            status("GloriousVanity.zip", ValidationStatus::Missing),
+        ];
+        assert_eq!(
+            describe_problems(&files).unwrap(),
+            "Missing required files: LethalPride.zip, GloriousVanity.zip. \
+             Unusable files: LegionPassives.lua"
+        );
+    }
+
+    #[test]
+    fn test_data_dir_from_dropped() {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = temp_dir.path().join("pob-data");
+        std::fs::create_dir(&folder).unwrap();
+        let file = folder.join("LethalPride.zip");
+
+        assert_eq!(data_dir_from_dropped(&[file]), Some(folder.clone()));
+        assert_eq!(
+            data_dir_from_dropped(&[temp_dir.path().join("x.zip"), folder.clone()]),
+            Some(folder)
+        );
+        assert_eq!(data_dir_from_dropped(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_validate_data_dir_lists_required_files() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let manifest_path = temp_dir.path().join("manifest.json");
+        let files = validate_data_dir(temp_dir.path().to_path_buf(), &manifest_path)
+            .await
+            .unwrap();
+
+        // Everything the built-in manifest requires, and nothing optional
+        assert_eq!(files.len(), 8);
+        assert!(files.iter().all(|f| f.result.status == ValidationStatus::Missing));
+        assert!(!files.iter().any(|f| f.result.file_name == "LegionTradeIds.lua"));
+    }
+}
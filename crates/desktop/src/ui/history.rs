@@ -0,0 +1,198 @@
+//! History tab: recorded runs and how scores changed since an earlier one
+
+use poe_item_analyzer_api::{HistoryRun, HistoryStore, RunKind};
+use poe_item_analyzer_core::analyzers::{RunDiff, ScoreChange};
+
+use super::compare::format_delta;
+
+/// Score changes in the order the diff lists them, with their colors
+const CHANGES: [(ScoreChange, &str, egui::Color32); 5] = [
+    (ScoreChange::Improved, "Improved", egui::Color32::LIGHT_GREEN),
+    (ScoreChange::Worsened, "Worsened", egui::Color32::LIGHT_RED),
+    (ScoreChange::New, "New", egui::Color32::LIGHT_BLUE),
+    (ScoreChange::Dropped, "Dropped", egui::Color32::GRAY),
+    (ScoreChange::Unchanged, "Unchanged", egui::Color32::GRAY),
+];
+
+/// One line describing a run (e.g., "#2 2026-10-17 08:36 · Seed search · ...")
+pub fn run_title(run: &HistoryRun) -> String {
+    let kind = match run.kind {
+        RunKind::SeedSearch => "Seed search",
+        RunKind::Batch => "Ranking",
+    };
+    let best = run
+        .results
+        .first()
+        .map_or("-".to_string(), |entry| format!("{:.1}", entry.score));
+
+    format!(
+        "#{} {} · {} · {} · best {}",
+        run.id,
+        run.timestamp.format("%Y-%m-%d %H:%M"),
+        kind,
+        run.label,
+        best
+    )
+}
+
+/// State of the history tab
+#[derive(Default)]
+pub struct HistoryState {
+    /// Recorded runs, oldest first
+    pub runs: Vec<HistoryRun>,
+    /// Whether `runs` must be read again (e.g., after a run was recorded)
+    pub stale: bool,
+    /// Id of the run compared with its latest rerun
+    pub previous: Option<String>,
+    /// Id of the latest rerun of `previous`
+    pub latest: Option<String>,
+    /// Changes from `previous` to `latest`
+    pub diff: Option<RunDiff>,
+    /// Why the history could not be read
+    pub error: Option<String>,
+}
+
+impl HistoryState {
+    /// Read the runs of `store` again, keeping the compared run if it's still there
+    pub fn refresh(&mut self, store: &HistoryStore) {
+        self.stale = false;
+        match store.list_history() {
+            Ok(runs) => {
+                self.runs = runs;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+
+        // Compare the latest run with its previous run unless another was chosen
+        let known = |id: &String| self.runs.iter().any(|run| &run.id == id);
+        if !self.previous.as_ref().is_some_and(known) {
+            self.previous = self.runs.split_last().and_then(|(latest, earlier)| {
+                let previous = earlier.iter().rev().find(|run| latest.is_rerun_of(run))?;
+                Some(previous.id.clone())
+            });
+        }
+        let position = self.previous.as_ref().and_then(|id| {
+            self.runs.iter().position(|run| &run.id == id)
+        });
+        let runs = position.and_then(|position| {
+            let previous = &self.runs[position];
+            let later = &self.runs[position + 1..];
+            let latest = later.iter().rev().find(|run| run.is_rerun_of(previous))?;
+            Some((previous, latest))
+        });
+        self.latest = runs.map(|(_, latest)| latest.id.clone());
+        self.diff = runs.map(|(previous, latest)| RunDiff::new(&previous.results, &latest.results));
+    }
+
+    /// Render the run list and the changes since the chosen run
+    pub fn render(&mut self, ui: &mut egui::Ui, store: &HistoryStore) {
+        if self.stale {
+            self.refresh(store);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("🔄 Reload").clicked() {
+                self.stale = true;
+            }
+            ui.label(
+                egui::RichText::new(store.path().display().to_string())
+                    .small()
+                    .weak(),
+            );
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if self.runs.is_empty() {
+            ui.label("No runs recorded yet. Seed searches and \"Rank all\" are recorded here.");
+            return;
+        }
+        ui.add_space(5.0);
+
+        let mut chosen = None;
+        egui::Grid::new("history_runs").striped(true).show(ui, |ui| {
+            for run in self.runs.iter().rev() {
+                let selected = self.previous.as_deref() == Some(run.id.as_str());
+                if ui.selectable_label(selected, run_title(run)).clicked() && !selected {
+                    chosen = Some(run.id.clone());
+                }
+                ui.label(egui::RichText::new(format!("weights {}", run.config_hash)).weak())
+                    .on_hover_text(format!(
+                        "LUT version: {}",
+                        run.lut_version.as_deref().unwrap_or("unknown")
+                    ));
+                ui.end_row();
+            }
+        });
+        if let Some(id) = chosen {
+            self.previous = Some(id);
+            self.stale = true;
+        }
+
+        ui.add_space(10.0);
+        ui.separator();
+        let (Some(previous), Some(latest)) = (&self.previous, &self.latest) else {
+            ui.label("Rerun a search or ranking to compare it with an earlier run.");
+            return;
+        };
+        ui.strong(format!("Changes from #{} to its latest rerun (#{})", previous, latest));
+        let Some(diff) = &self.diff else {
+            return;
+        };
+
+        for (change, title, color) in CHANGES {
+            let seeds: Vec<_> = diff.with_change(change).collect();
+            if seeds.is_empty() {
+                continue;
+            }
+            let header = egui::RichText::new(format!("{} ({})", title, seeds.len())).color(color);
+            egui::CollapsingHeader::new(header)
+                .id_source(("history_change", title))
+                .default_open(change != ScoreChange::Unchanged)
+                .show(ui, |ui| {
+                    egui::Grid::new(("history_diff", title)).striped(true).show(ui, |ui| {
+                        for seed in seeds {
+                            let score = |score: Option<f64>| {
+                                score.map_or("-".to_string(), |score| format!("{:.1}", score))
+                            };
+                            ui.label(&seed.jewel_id);
+                            ui.label(score(seed.score_a));
+                            ui.label("→");
+                            ui.label(score(seed.score_b));
+                            ui.colored_label(color, format_delta(seed.score_delta()));
+                            ui.end_row();
+                        }
+                    });
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poe_item_analyzer_core::analyzers::{RunEntry, TimelessJewelConfig};
+
+    #[test]
+    fn test_run_title() {
+        let results = vec![RunEntry {
+            jewel_id: "Lethal Pride:14032:Kaom".to_string(),
+            seed: 14032,
+            score: 10.0,
+        }];
+        let mut run = HistoryRun::new(
+            RunKind::SeedSearch,
+            "Lethal Pride (Kaom)",
+            &TimelessJewelConfig::new(),
+            None,
+            results,
+        );
+        run.id = "3".to_string();
+
+        let title = run_title(&run);
+        assert!(title.starts_with("#3 "), "{}", title);
+        let expected = " · Seed search · Lethal Pride (Kaom) · best 10.0";
+        assert!(title.ends_with(expected), "{}", title);
+    }
+}
@@ -0,0 +1,77 @@
+//! Log panel showing the records captured by the tracing layer
+
+use tracing::Level;
+
+use crate::logging::{LogBuffer, LogRecord};
+
+/// Levels offered in the filter dropdown, most verbose last
+const LEVELS: [Level; 4] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG];
+
+/// Log panel state
+pub struct LogPanel {
+    buffer: LogBuffer,
+    /// Least severe level shown
+    min_level: Level,
+}
+
+impl LogPanel {
+    pub fn new(buffer: LogBuffer) -> Self {
+        Self {
+            buffer,
+            min_level: Level::INFO,
+        }
+    }
+
+    /// Render the filter, the copy and clear buttons, and the records
+    pub fn render(&mut self, ui: &mut egui::Ui) {
+        let records = self.buffer.records(self.min_level);
+
+        ui.horizontal(|ui| {
+            ui.label("Show:");
+            egui::ComboBox::from_id_source("log_level")
+                .selected_text(self.min_level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in LEVELS {
+                        ui.selectable_value(&mut self.min_level, level, level.as_str());
+                    }
+                });
+
+            if ui
+                .add_enabled(!records.is_empty(), egui::Button::new("📋 Copy"))
+                .on_hover_text("Copy the shown records, e.g. for a bug report")
+                .clicked()
+            {
+                let text = records.iter().map(LogRecord::format).collect::<Vec<_>>().join("\n");
+                ui.output_mut(|o| o.copied_text = text);
+            }
+            if ui.button("🗑 Clear").clicked() {
+                self.buffer.clear();
+            }
+
+            ui.label(format!("{} of {} records", records.len(), self.buffer.len()));
+        });
+        ui.separator();
+
+        if records.is_empty() {
+            ui.label("Nothing logged at this level yet");
+            return;
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .id_source("log_records")
+            .max_height(500.0)
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, records.len(), |ui, range| {
+                for record in &records[range] {
+                    let text = egui::RichText::new(record.format()).monospace();
+                    let label = match record.level {
+                        Level::ERROR => ui.colored_label(egui::Color32::RED, text),
+                        Level::WARN => ui.colored_label(egui::Color32::YELLOW, text),
+                        _ => ui.label(text),
+                    };
+                    label.on_hover_text(&record.target);
+                }
+            });
+    }
+}
@@ -1,5 +1,16 @@
 //! UI components
 
+pub mod compare;
+pub mod data_dir;
+pub mod history;
+pub mod log;
+pub mod mods;
+pub mod seed_search;
+pub mod stash;
+pub mod timeless_jewels;
+pub mod timings;
+pub mod toast;
+pub mod weights;
+
 // TODO: Add UI modules
-// pub mod timeless_jewels;
 // pub mod components;
@@ -0,0 +1,237 @@
+//! Modifier browser tab
+
+use std::sync::Arc;
+
+use poe_item_analyzer_api::parser::{JewelStatistics, LutData, ModifierIndex};
+use poe_item_analyzer_core::items::JewelType;
+
+use super::weights::WeightEditor;
+
+/// Height of a row in the modifier list
+const ROW_HEIGHT: f32 = 18.0;
+
+/// State of the modifier browser tab
+pub struct ModsState {
+    /// Data the list was built from
+    data: Option<Arc<LutData>>,
+    /// Seeds per modifier, once built
+    index: Option<Arc<ModifierIndex>>,
+    /// Modifier frequencies per jewel type, built with the index
+    statistics: Vec<JewelStatistics>,
+    /// Whether the index is being built
+    pub indexing: bool,
+    /// Search box
+    search: String,
+    /// Query `filtered` was computed for (None to recompute)
+    filtered_query: Option<String>,
+    /// IDs of the modifiers matching `search`, sorted by name
+    filtered: Vec<String>,
+    /// Selected modifier ID
+    selected: Option<String>,
+    /// Weight used when adding the selected modifier
+    weight: f64,
+    /// Result of the last add
+    status: Option<String>,
+}
+
+impl Default for ModsState {
+    fn default() -> Self {
+        Self {
+            data: None,
+            index: None,
+            statistics: Vec::new(),
+            indexing: false,
+            search: String::new(),
+            filtered_query: None,
+            filtered: Vec::new(),
+            selected: None,
+            weight: 1.0,
+            status: None,
+        }
+    }
+}
+
+impl ModsState {
+    /// Show `data`, returning true if it is new and needs an index
+    pub fn set_data(&mut self, data: &Arc<LutData>) -> bool {
+        if self.data.as_ref().is_some_and(|current| Arc::ptr_eq(current, data)) {
+            return false;
+        }
+
+        self.data = Some(Arc::clone(data));
+        self.index = None;
+        self.statistics.clear();
+        self.indexing = false;
+        self.filtered_query = None;
+        self.selected = None;
+        true
+    }
+
+    /// Use `index` and `statistics` if they were built for the data being shown
+    pub fn set_index(
+        &mut self,
+        data: &Arc<LutData>,
+        index: ModifierIndex,
+        statistics: Vec<JewelStatistics>,
+    ) {
+        if self.data.as_ref().is_some_and(|current| Arc::ptr_eq(current, data)) {
+            self.index = Some(Arc::new(index));
+            self.statistics = statistics;
+            self.indexing = false;
+        }
+    }
+
+    /// Modifier frequencies of `jewel_type`, once built
+    pub fn statistics(&self, jewel_type: JewelType) -> Option<&JewelStatistics> {
+        self.statistics.iter().find(|s| s.jewel_type == jewel_type)
+    }
+
+    /// Render the search box, modifier list and selected modifier
+    pub fn render(&mut self, ui: &mut egui::Ui, weights: &mut WeightEditor) {
+        let Some(data) = self.data.clone() else {
+            ui.label("No data loaded. Download and parse the PoB data first.");
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("Search modifiers...")
+                    .desired_width(300.0),
+            );
+            ui.label(format!("{} of {} modifiers", self.filtered.len(), data.modifiers.len()));
+        });
+        self.refresh_filter(&data);
+
+        ui.add_space(5.0);
+
+        ui.horizontal_top(|ui| {
+            ui.vertical(|ui| {
+                ui.set_width(300.0);
+                self.render_list(ui, &data);
+            });
+            ui.separator();
+            ui.vertical(|ui| self.render_details(ui, &data, weights));
+        });
+    }
+
+    /// Recompute the filtered list when the search text changed
+    fn refresh_filter(&mut self, data: &LutData) {
+        let query = self.search.trim();
+        if self.filtered_query.as_deref() == Some(query) {
+            return;
+        }
+
+        self.filtered = data
+            .search_modifiers(query, usize::MAX)
+            .into_iter()
+            .map(|modifier| modifier.id.clone())
+            .collect();
+        self.filtered_query = Some(query.to_string());
+    }
+
+    fn render_list(&mut self, ui: &mut egui::Ui, data: &LutData) {
+        let mut clicked = None;
+
+        egui::ScrollArea::vertical()
+            .id_source("mods_list_scroll")
+            .max_height(400.0)
+            .auto_shrink([false, true])
+            .show_rows(ui, ROW_HEIGHT, self.filtered.len(), |ui, rows| {
+                for id in &self.filtered[rows] {
+                    let Some(modifier) = data.modifiers.get(id) else {
+                        continue;
+                    };
+                    let selected = self.selected.as_deref() == Some(id.as_str());
+
+                    if ui.selectable_label(selected, &modifier.display_name).clicked() {
+                        clicked = Some(id.clone());
+                    }
+                }
+            });
+
+        if clicked.is_some() {
+            self.selected = clicked;
+            self.status = None;
+        }
+    }
+
+    fn render_details(&mut self, ui: &mut egui::Ui, data: &LutData, weights: &mut WeightEditor) {
+        let Some(modifier) = self.selected.as_ref().and_then(|id| data.modifiers.get(id)) else {
+            ui.label("Select a modifier to see where it rolls");
+            return;
+        };
+
+        ui.heading(&modifier.display_name);
+        ui.weak(&modifier.id);
+        for description in &modifier.stat_descriptions {
+            ui.label(description);
+        }
+
+        ui.add_space(10.0);
+        ui.strong("Seeds with this modifier");
+
+        match &self.index {
+            Some(index) => {
+                egui::Grid::new("mods_seed_counts_grid")
+                    .num_columns(4)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.strong("Seeds");
+                        ui.strong("Share");
+                        ui.strong("Nodes");
+                        ui.end_row();
+
+                        for jewel_type in JewelType::ALL {
+                            let frequency = self
+                                .statistics(jewel_type)
+                                .and_then(|statistics| statistics.get(&modifier.id));
+
+                            ui.label(jewel_type.as_str());
+                            ui.monospace(format!("{}", index.seed_count(&modifier.id, jewel_type)));
+                            match frequency {
+                                Some(frequency) => {
+                                    ui.monospace(format!("{:.2}%", frequency.percentage));
+                                    ui.monospace(format!("{}", frequency.node_count));
+                                }
+                                None => {
+                                    ui.label("-");
+                                    ui.label("-");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Indexing seeds...");
+                });
+            }
+        }
+
+        ui.add_space(10.0);
+
+        let target = weights.active().unwrap_or("unsaved weights").to_string();
+        ui.horizontal(|ui| {
+            ui.label("Weight:");
+            ui.add(egui::DragValue::new(&mut self.weight).speed(0.1));
+
+            if ui.button(format!("➕ Add to {}", target)).clicked() {
+                weights.set_weight(&modifier.display_name, self.weight);
+                weights.save_active();
+                self.status = Some(format!(
+                    "✓ {} weighted {} in {}",
+                    modifier.display_name, self.weight, target
+                ));
+            }
+        });
+
+        if let Some(status) = &self.status {
+            ui.colored_label(egui::Color32::GREEN, status);
+        }
+    }
+}
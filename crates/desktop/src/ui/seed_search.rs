@@ -0,0 +1,342 @@
+//! Best-seed search tab
+
+use poe_item_analyzer_api::parser::JewelStatistics;
+use poe_item_analyzer_api::poe_api::build_trade_site_url;
+use poe_item_analyzer_core::analyzers::{
+    render_search_report, CancelFlag, SearchProgress, SeedScore, SeedSearchResult,
+};
+use poe_item_analyzer_core::items::JewelType;
+
+use std::path::PathBuf;
+
+use super::timings::render_timings;
+use super::weights::WeightEditor;
+use crate::export::{export_buttons, save_export, ExportFormat, ExportRow};
+
+/// Matched mods listed per result row
+const TOP_MODS_SHOWN: usize = 3;
+
+/// Result rows shown per page
+const RESULTS_PER_PAGE: usize = 50;
+
+/// State of the seed search tab
+pub struct SeedSearchState {
+    /// Jewel type to scan
+    pub jewel_type: JewelType,
+    /// Conqueror the seeds are scored for
+    pub conqueror: String,
+    /// Number of seeds to keep
+    pub top_n: usize,
+    /// League used for trade searches
+    pub league: String,
+    /// Cancels the running search, if any
+    pub cancel: Option<CancelFlag>,
+    /// Latest progress of the running search
+    pub progress: Option<SearchProgress>,
+    /// Why the search could not run or failed
+    pub error: Option<String>,
+    /// Result of the last search
+    pub result: Option<SeedSearchResult>,
+    /// Page of the results shown (from 0)
+    pub page: usize,
+    /// Seed selected for comparison
+    pub selected: Option<u32>,
+    /// Seeds to compare (selection, clicked row), taken by the app
+    pub compare_request: Option<(u32, u32)>,
+}
+
+impl Default for SeedSearchState {
+    fn default() -> Self {
+        let jewel_type = JewelType::LethalPride;
+
+        Self {
+            jewel_type,
+            conqueror: jewel_type.conquerors()[0].to_string(),
+            top_n: 20,
+            league: "Standard".to_string(),
+            cancel: None,
+            progress: None,
+            error: None,
+            result: None,
+            page: 0,
+            selected: None,
+            compare_request: None,
+        }
+    }
+}
+
+impl SeedSearchState {
+    /// Whether a search is running
+    pub fn is_running(&self) -> bool {
+        self.cancel.is_some()
+    }
+
+    /// Render the search form, returning true when Search was clicked
+    pub fn render_form(&mut self, ui: &mut egui::Ui, weights: &mut WeightEditor) -> bool {
+        let running = self.is_running();
+
+        egui::Grid::new("seed_search_form_grid")
+            .num_columns(2)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                ui.label("Jewel:");
+                let previous_type = self.jewel_type;
+                egui::ComboBox::from_id_source("seed_search_jewel_type")
+                    .selected_text(self.jewel_type.as_str())
+                    .show_ui(ui, |ui| {
+                        for jewel_type in JewelType::ALL {
+                            ui.selectable_value(&mut self.jewel_type, jewel_type, jewel_type.as_str());
+                        }
+                    });
+                if self.jewel_type != previous_type {
+                    self.conqueror = self.jewel_type.conquerors()[0].to_string();
+                }
+                ui.end_row();
+
+                ui.label("Conqueror:");
+                egui::ComboBox::from_id_source("seed_search_conqueror")
+                    .selected_text(self.conqueror.as_str())
+                    .show_ui(ui, |ui| {
+                        for conqueror in self.jewel_type.conquerors() {
+                            ui.selectable_value(&mut self.conqueror, conqueror.to_string(), *conqueror);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Socket:");
+                ui.label("All affected nodes");
+                ui.end_row();
+
+                ui.label("Weights:");
+                weights.render_profile_picker(ui);
+                ui.end_row();
+
+                ui.label("Top:");
+                ui.add(egui::DragValue::new(&mut self.top_n).clamp_range(1..=100_000));
+                ui.end_row();
+
+                ui.label("League:");
+                ui.add(egui::TextEdit::singleline(&mut self.league).desired_width(120.0));
+                ui.end_row();
+            });
+
+        ui.add_space(5.0);
+
+        let mut clicked = false;
+        ui.horizontal(|ui| {
+            clicked = ui.add_enabled(!running, egui::Button::new("🎯 Search")).clicked();
+
+            if let Some(cancel) = &self.cancel {
+                let cancelling = cancel.is_cancelled();
+                let label = if cancelling { "Cancelling..." } else { "✖ Cancel" };
+
+                if ui.add_enabled(!cancelling, egui::Button::new(label)).clicked() {
+                    cancel.cancel();
+                }
+            }
+        });
+
+        if let Some(progress) = &self.progress {
+            ui.add(egui::ProgressBar::new(progress.fraction()).text(format!(
+                "{} / {} seeds",
+                progress.scanned, progress.total
+            )));
+        }
+
+        clicked
+    }
+
+    /// Render the error or the best seeds
+    ///
+    /// `statistics` of the searched jewel type add each mod's rarity. `lut_version`
+    /// goes in the footer of HTML reports.
+    /// Returns the outcome of an export, if one was made this frame.
+    pub fn render_results(
+        &mut self,
+        ui: &mut egui::Ui,
+        statistics: Option<&JewelStatistics>,
+        lut_version: Option<&str>,
+    ) -> Option<Result<PathBuf, String>> {
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, format!("❌ {}", error));
+        }
+
+        let result = self.result.as_ref()?;
+
+        let mut summary = format!(
+            "{} ({}): best {} of {} seeds",
+            result.jewel_type.as_str(),
+            result.conqueror,
+            result.results.total_count(),
+            result.scanned
+        );
+        if result.cancelled {
+            summary.push_str(" (cancelled)");
+        }
+        let mut exported = None;
+        let mut copy_trade_urls = false;
+        ui.horizontal(|ui| {
+            ui.label(summary);
+
+            copy_trade_urls = ui
+                .button("📋 Trade URLs")
+                .on_hover_text("Copy trade site searches for every listed seed")
+                .clicked();
+
+            if let Some(format) = export_buttons(ui, true) {
+                let rows = ExportRow::from_search(result);
+                let html = (format == ExportFormat::Html)
+                    .then(|| render_search_report(result, lut_version));
+                exported = save_export(format, "seed-search", &rows, result, html.as_deref());
+            }
+        });
+
+        if let Some(timings) = &result.timings {
+            render_timings(ui, "seed_search_timings", timings);
+        }
+
+        // A new, shorter result can leave the page past the end
+        let page_count = result.results.page_count(RESULTS_PER_PAGE).max(1);
+        self.page = self.page.min(page_count - 1);
+        if page_count > 1 {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(self.page > 0, egui::Button::new("◀")).clicked() {
+                    self.page -= 1;
+                }
+                ui.label(format!("Page {} of {}", self.page + 1, page_count));
+                if ui.add_enabled(self.page + 1 < page_count, egui::Button::new("▶")).clicked() {
+                    self.page += 1;
+                }
+            });
+        }
+        ui.add_space(5.0);
+
+        let first_rank = self.page * RESULTS_PER_PAGE + 1;
+        let mut copy = None;
+        let mut trade = None;
+        let mut select = None;
+        let mut compare = None;
+
+        egui::ScrollArea::vertical()
+            .id_source("seed_search_results_scroll")
+            .max_height(400.0)
+            .show(ui, |ui| {
+                egui::Grid::new("seed_search_results_grid")
+                    .num_columns(5)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("#");
+                        ui.strong("Seed");
+                        ui.strong("Score");
+                        ui.strong("Top mods");
+                        ui.label("");
+                        ui.end_row();
+
+                        for ranked in result.results.iter_from(first_rank).take(RESULTS_PER_PAGE) {
+                            let score = ranked.result;
+                            ui.label(format!("{}", ranked.rank));
+                            ui.monospace(format!("{}", score.seed));
+                            ui.monospace(format!("{:.1}", score.score)).on_hover_text(
+                                format!(
+                                    "Better than {:.1}% of seeds",
+                                    result.distribution.percentile_of(score.score)
+                                ),
+                            );
+                            ui.label(top_mods(score, statistics));
+                            ui.horizontal(|ui| {
+                                if ui.small_button("📋 Seed").clicked() {
+                                    copy = Some(score.seed.to_string());
+                                }
+                                if ui.small_button("🔗 Trade").clicked() {
+                                    trade = Some(score.seed);
+                                }
+
+                                let is_selected = self.selected == Some(score.seed);
+                                if ui.selectable_label(is_selected, "☑ Select").clicked() {
+                                    select = Some((!is_selected).then_some(score.seed));
+                                }
+                                if let Some(selected) = self.selected.filter(|s| *s != score.seed) {
+                                    if ui
+                                        .small_button("⇄ Compare")
+                                        .on_hover_text(format!("Compare with {}", selected))
+                                        .clicked()
+                                    {
+                                        compare = Some((selected, score.seed));
+                                    }
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if let Some(selected) = select {
+            self.selected = selected;
+        }
+        if compare.is_some() {
+            self.compare_request = compare;
+        }
+
+        if let Some(text) = copy {
+            ui.output_mut(|output| output.copied_text = text);
+        }
+
+        if copy_trade_urls {
+            let seeds: Vec<u32> = self
+                .result
+                .iter()
+                .flat_map(|result| &result.results)
+                .map(|score| score.seed)
+                .collect();
+            match self.trade_urls(&seeds) {
+                Ok(urls) => ui.output_mut(|output| output.copied_text = urls.join("\n")),
+                Err(e) => self.error = Some(e),
+            }
+        }
+
+        if let Some(seed) = trade {
+            match self.trade_urls(&[seed]).map(|mut urls| urls.remove(0)) {
+                Ok(url) => {
+                    ui.output_mut(|output| output.copied_text = url.clone());
+                    ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+
+        exported
+    }
+
+    /// Trade site searches for `seeds` of the searched jewel
+    fn trade_urls(&self, seeds: &[u32]) -> Result<Vec<String>, String> {
+        let result = self.result.as_ref().ok_or("No search result")?;
+
+        build_trade_site_url(
+            self.league.trim(),
+            result.jewel_type,
+            seeds,
+            Some(result.conqueror.as_str()),
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// Short list of the heaviest matched mods (e.g., "2× Double Damage (0.8%), 1× Onslaught")
+///
+/// The percentage is the share of seeds rolling the mod, when `statistics` has it.
+fn top_mods(score: &SeedScore, statistics: Option<&JewelStatistics>) -> String {
+    score
+        .socket
+        .matched_mods
+        .iter()
+        .take(TOP_MODS_SHOWN)
+        .map(|m| match statistics.and_then(|s| s.by_name(&m.mod_text)) {
+            Some(frequency) => {
+                format!("{}× {} ({:.1}%)", m.count, m.mod_text, frequency.percentage)
+            }
+            None => format!("{}× {}", m.count, m.mod_text),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
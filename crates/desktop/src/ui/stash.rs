@@ -0,0 +1,246 @@
+//! Stash tab import panel
+
+use std::collections::BTreeSet;
+
+use poe_item_analyzer_api::sources::SkippedItem;
+use poe_item_analyzer_api::{ApiError, RateLimitState, SourceError, StashTab};
+
+/// What the user asked the stash panel to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum StashAction {
+    /// Fetch the tab list
+    LoadTabs,
+    /// Import the tabs at these indices
+    Import(Vec<u32>),
+    /// Switch to the settings tab to fix the credentials
+    OpenSettings,
+}
+
+/// Stash import state
+#[derive(Default)]
+pub struct StashImportState {
+    /// Tabs of the stash, as last listed
+    pub tabs: Vec<StashTab>,
+    /// Indices of the tabs to import
+    pub selected: BTreeSet<u32>,
+    /// Whether the tab list is being fetched
+    pub loading_tabs: bool,
+    /// Whether tabs are being imported
+    pub importing: bool,
+    /// Summary of the last import
+    pub summary: Option<String>,
+    /// Items the last import skipped
+    pub skipped: Vec<SkippedItem>,
+    /// Error of the last request
+    pub error: Option<String>,
+    /// Whether the last error was a rejected session id
+    pub auth_failed: bool,
+}
+
+impl StashImportState {
+    /// Whether a request is in flight
+    pub fn is_busy(&self) -> bool {
+        self.loading_tabs || self.importing
+    }
+
+    /// Show `error`, remembering whether the session id was rejected
+    pub fn set_error(&mut self, error: &SourceError) {
+        self.auth_failed = is_auth_error(error);
+        self.error = Some(error.to_string());
+    }
+
+    /// Forget the results of requests made with other credentials
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Render the panel
+    ///
+    /// `has_credentials` tells whether an account and session id are set;
+    /// `rate_limit` is shown while a request waits for the limiter.
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        league: &mut String,
+        has_credentials: bool,
+        rate_limit: &RateLimitState,
+    ) -> Option<StashAction> {
+        let mut action = None;
+
+        if !has_credentials {
+            ui.horizontal(|ui| {
+                ui.label("Set your account name and POESESSID to import stash tabs");
+                if ui.button("⚙ Open settings").clicked() {
+                    action = Some(StashAction::OpenSettings);
+                }
+            });
+            return action;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("League:");
+            ui.add(egui::TextEdit::singleline(league).desired_width(140.0));
+
+            let button = egui::Button::new("🔄 Load tabs");
+            if ui.add_enabled(!self.is_busy(), button).clicked() {
+                action = Some(StashAction::LoadTabs);
+            }
+        });
+
+        if !self.tabs.is_empty() {
+            egui::ScrollArea::vertical()
+                .id_source("stash_tabs_scroll")
+                .max_height(150.0)
+                .show(ui, |ui| {
+                    for tab in self.tabs.iter().filter(|tab| !tab.hidden) {
+                        let mut checked = self.selected.contains(&tab.index);
+                        if ui.checkbox(&mut checked, &tab.name).changed() {
+                            if checked {
+                                self.selected.insert(tab.index);
+                            } else {
+                                self.selected.remove(&tab.index);
+                            }
+                        }
+                    }
+                });
+
+            let label = format!("📥 Import {} tabs", self.selected.len());
+            let enabled = !self.selected.is_empty() && !self.is_busy();
+            if ui.add_enabled(enabled, egui::Button::new(label)).clicked() {
+                action = Some(StashAction::Import(self.selected.iter().copied().collect()));
+            }
+        }
+
+        if self.is_busy() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(if self.loading_tabs { "Loading tabs..." } else { "Importing..." });
+            });
+            if rate_limit.is_waiting() {
+                ui.label(format!(
+                    "⏳ Waiting {}s for the Path of Exile rate limit",
+                    rate_limit.wait.as_secs_f32().ceil()
+                ));
+            }
+        }
+
+        if let Some(summary) = &self.summary {
+            ui.colored_label(egui::Color32::GREEN, summary);
+        }
+        for (label, items) in group_skips(&self.skipped) {
+            egui::CollapsingHeader::new(format!("{} ({})", label, items.len()))
+                .id_source(("stash_skips", label))
+                .show(ui, |ui| {
+                    for item in items.iter().take(MAX_SKIP_EXAMPLES) {
+                        ui.label(format!("{}: {}", item.name, item.reason));
+                    }
+                    if items.len() > MAX_SKIP_EXAMPLES {
+                        ui.weak(format!("and {} more", items.len() - MAX_SKIP_EXAMPLES));
+                    }
+                });
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, format!("✗ {}", error));
+            if self.auth_failed {
+                ui.horizontal(|ui| {
+                    ui.label("Check the POESESSID in the settings; it changes when you log out");
+                    if ui.button("⚙ Open settings").clicked() {
+                        action = Some(StashAction::OpenSettings);
+                    }
+                });
+            }
+        }
+
+        action
+    }
+}
+
+/// Whether `error` means the session id was rejected
+pub fn is_auth_error(error: &SourceError) -> bool {
+    matches!(error, SourceError::ApiError(ApiError::Unauthorized(_)))
+}
+
+/// Skipped items listed under each reason of the import summary
+const MAX_SKIP_EXAMPLES: usize = 10;
+
+/// `skipped` grouped by reason, reasons in order of first appearance
+pub fn group_skips(skipped: &[SkippedItem]) -> Vec<(&'static str, Vec<&SkippedItem>)> {
+    let mut groups: Vec<(&'static str, Vec<&SkippedItem>)> = Vec::new();
+    for item in skipped {
+        let label = item.reason.label();
+        match groups.iter_mut().find(|(group, _)| *group == label) {
+            Some((_, items)) => items.push(item),
+            None => groups.push((label, vec![item])),
+        }
+    }
+    groups
+}
+
+/// Import summary (e.g., "Imported 7 timeless jewels, skipped 42 other items")
+pub fn import_summary(added: usize, duplicates: usize, skipped: usize) -> String {
+    let mut summary = format!(
+        "Imported {} timeless jewels, skipped {} other items",
+        added, skipped
+    );
+    if duplicates > 0 {
+        summary.push_str(&format!(" ({} already in the list)", duplicates));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poe_item_analyzer_api::sources::SkipReason;
+
+    #[test]
+    fn test_import_summary() {
+        assert_eq!(
+            import_summary(7, 0, 42),
+            "Imported 7 timeless jewels, skipped 42 other items"
+        );
+        assert_eq!(
+            import_summary(1, 2, 0),
+            "Imported 1 timeless jewels, skipped 0 other items (2 already in the list)"
+        );
+    }
+
+    #[test]
+    fn test_group_skips() {
+        let skipped = |name: &str, reason: SkipReason| SkippedItem {
+            id: name.to_string(),
+            name: name.to_string(),
+            reason,
+        };
+        let items = vec![
+            skipped("Ring", SkipReason::NotAJewel),
+            skipped("Lethal Pride", SkipReason::SeedOutOfRange { seed: 9000 }),
+            skipped("Amulet", SkipReason::NotAJewel),
+            skipped("Elegant Hubris", SkipReason::SeedOutOfRange { seed: 2010 }),
+        ];
+
+        let groups: Vec<(&str, Vec<&str>)> = group_skips(&items)
+            .into_iter()
+            .map(|(label, items)| (label, items.iter().map(|i| i.name.as_str()).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("Not a timeless jewel", vec!["Ring", "Amulet"]),
+                ("Seed out of range", vec!["Lethal Pride", "Elegant Hubris"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_error_flags_auth_failures() {
+        let mut state = StashImportState::default();
+
+        state.set_error(&SourceError::ApiError(ApiError::Unauthorized("expired".to_string())));
+        assert!(state.auth_failed);
+
+        state.set_error(&SourceError::FetchFailed("timeout".to_string()));
+        assert!(!state.auth_failed);
+        assert_eq!(state.error.as_deref(), Some("Failed to fetch items: timeout"));
+    }
+}
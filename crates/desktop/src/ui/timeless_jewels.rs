@@ -0,0 +1,363 @@
+//! Timeless jewel analysis tab
+
+use std::path::PathBuf;
+
+use poe_item_analyzer_api::parser::LutData;
+use poe_item_analyzer_core::analyzers::{
+    render_analysis_report, RankedResult, ResultExportV1, TimelessJewelAnalysisResult,
+    TimelessJewelConfig,
+};
+use poe_item_analyzer_core::items::{
+    JewelSummary, JewelType, SocketResult, SocketSortKey, TimelessJewel,
+};
+use serde_json::Value;
+
+use super::timings::render_timings;
+use super::weights::WeightEditor;
+use crate::export::{export_buttons, save_export, ExportFormat, ExportRow};
+
+/// State of the analysis tab
+pub struct AnalysisState {
+    /// Selected jewel type
+    pub jewel_type: JewelType,
+    /// Selected conqueror
+    pub conqueror: String,
+    /// Seed as typed by the user
+    pub seed_text: String,
+    /// Weighted mods to score
+    pub weights: WeightEditor,
+    /// Whether an analysis is running
+    pub running: bool,
+    /// Why the last analysis could not run or failed
+    pub error: Option<String>,
+    /// Result of the last analysis
+    pub result: Option<TimelessJewelAnalysisResult>,
+    /// Socket table sort column
+    pub sort: SocketSortKey,
+    /// Whether the socket table is sorted in descending order
+    pub descending: bool,
+}
+
+impl Default for AnalysisState {
+    fn default() -> Self {
+        let jewel_type = JewelType::LethalPride;
+
+        Self {
+            jewel_type,
+            conqueror: jewel_type.conquerors()[0].to_string(),
+            seed_text: String::new(),
+            weights: WeightEditor::default(),
+            running: false,
+            error: None,
+            result: None,
+            sort: SocketSortKey::Score,
+            descending: true,
+        }
+    }
+}
+
+impl AnalysisState {
+    /// Build the jewel and config to analyze from the form
+    ///
+    /// Returns a message for the user if the input is invalid.
+    pub fn build_request(&self) -> Result<(TimelessJewel, TimelessJewelConfig), String> {
+        let seed = parse_seed(self.jewel_type, &self.seed_text)?;
+
+        let config = self.weights.config();
+        if config.valuable_mods().is_empty() {
+            return Err("Add at least one weighted mod".to_string());
+        }
+
+        let jewel = TimelessJewel::new(
+            format!("{}:{}:{}", self.jewel_type.as_str(), seed, self.conqueror),
+            self.jewel_type,
+            seed,
+            self.conqueror.clone(),
+            Value::Null,
+        );
+
+        Ok((jewel, config))
+    }
+
+    /// Socket results in the selected sort order
+    pub fn sorted_sockets(&self) -> Vec<&SocketResult> {
+        let Some(result) = &self.result else {
+            return Vec::new();
+        };
+
+        let mut sockets = result.metrics.sorted_by(self.sort);
+        if self.descending != self.sort.default_descending() {
+            sockets.reverse();
+        }
+        sockets
+    }
+
+    /// Render the form, returning true when Analyze was clicked
+    pub fn render_form(&mut self, ui: &mut egui::Ui, data: Option<&LutData>) -> bool {
+        egui::Grid::new("analysis_form_grid")
+            .num_columns(2)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                ui.label("Jewel:");
+                let previous_type = self.jewel_type;
+                egui::ComboBox::from_id_source("analysis_jewel_type")
+                    .selected_text(self.jewel_type.as_str())
+                    .show_ui(ui, |ui| {
+                        for jewel_type in JewelType::ALL {
+                            ui.selectable_value(&mut self.jewel_type, jewel_type, jewel_type.as_str());
+                        }
+                    });
+                if self.jewel_type != previous_type {
+                    self.conqueror = self.jewel_type.conquerors()[0].to_string();
+                }
+                ui.end_row();
+
+                ui.label("Conqueror:");
+                egui::ComboBox::from_id_source("analysis_conqueror")
+                    .selected_text(self.conqueror.as_str())
+                    .show_ui(ui, |ui| {
+                        for conqueror in self.jewel_type.conquerors() {
+                            ui.selectable_value(&mut self.conqueror, conqueror.to_string(), *conqueror);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Seed:");
+                let range = self.jewel_type.seed_range();
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.seed_text)
+                        .hint_text(format!("{}-{}", range.start(), range.end()))
+                        .desired_width(120.0),
+                );
+                ui.end_row();
+
+                ui.label("Weights:");
+                self.weights.render_profile_picker(ui);
+                ui.end_row();
+            });
+
+        ui.add_space(5.0);
+        ui.collapsing(format!("⚖ Edit weights ({} mods)", self.weights.rows.len()), |ui| {
+            self.weights.render(ui, data);
+        });
+
+        ui.add_space(5.0);
+
+        let clicked = ui
+            .add_enabled(!self.running, egui::Button::new("🔍 Analyze"))
+            .clicked();
+
+        if clicked && data.is_none() {
+            self.error = Some("No data loaded. Download and parse the PoB data first.".to_string());
+            return false;
+        }
+
+        clicked
+    }
+
+    /// Render the error or the socket results
+    ///
+    /// `lut_version` goes in the footer of HTML reports. Returns the outcome
+    /// of an export, if one was made this frame.
+    pub fn render_results(
+        &mut self,
+        ui: &mut egui::Ui,
+        lut_version: Option<&str>,
+    ) -> Option<Result<PathBuf, String>> {
+        if self.running {
+            ui.label("Analyzing...");
+            return None;
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, format!("❌ {}", error));
+            return None;
+        }
+
+        let result = self.result.as_ref()?;
+
+        let mut exported = None;
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{} {} ({}): best score {:.1}",
+                result.jewel.jewel_type.as_str(),
+                result.jewel.seed,
+                result.jewel.conqueror,
+                result.best_score
+            ));
+
+            if let Some(format) = export_buttons(ui, true) {
+                let ranked = RankedResult { rank: 1, result: result.clone() };
+                let rows = [ExportRow::from_ranked(&ranked)];
+                let json = ResultExportV1::from_result(result, lut_version.map(str::to_string));
+                let html = (format == ExportFormat::Html)
+                    .then(|| render_analysis_report(result, lut_version));
+                exported = save_export(format, "analysis", &rows, &json, html.as_deref());
+            }
+        });
+        if !result.warnings.is_empty() {
+            let lines: Vec<String> = result.warnings.iter().map(ToString::to_string).collect();
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("⚠ {} warning(s)", result.warnings.len()),
+            )
+            .on_hover_text(lines.join("\n"));
+        }
+        render_summary(ui, &result.metrics.summary());
+        if let Some(timings) = &result.timings {
+            render_timings(ui, "analysis_timings", timings);
+        }
+        ui.add_space(5.0);
+
+        let mut clicked_sort = None;
+        let sockets = self.sorted_sockets();
+
+        egui::ScrollArea::vertical()
+            .id_source("analysis_results_scroll")
+            .max_height(400.0)
+            .show(ui, |ui| {
+                egui::Grid::new("analysis_results_grid")
+                    .num_columns(3)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (column, label) in [
+                            (SocketSortKey::Name, "Socket"),
+                            (SocketSortKey::Score, "Score"),
+                            (SocketSortKey::MatchedCount, "Matched mods"),
+                        ] {
+                            let arrow = match (self.sort == column, self.descending) {
+                                (true, true) => " ⏷",
+                                (true, false) => " ⏶",
+                                (false, _) => "",
+                            };
+                            if ui.button(format!("{}{}", label, arrow)).clicked() {
+                                clicked_sort = Some(column);
+                            }
+                        }
+                        ui.end_row();
+
+                        for socket in &sockets {
+                            ui.label(&socket.socket_name);
+                            ui.monospace(format!("{:.1}", socket.score));
+                            let mut matches: Vec<String> = socket
+                                .matched_mods
+                                .iter()
+                                .map(|m| format!("{}× {}", m.count, m.mod_text))
+                                .collect();
+                            if let Some(change) = &socket.keystone_change {
+                                matches.push(format!(
+                                    "{} → {}",
+                                    change.original, change.replacement
+                                ));
+                            }
+                            ui.label(matches.join(", "));
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if let Some(column) = clicked_sort {
+            if self.sort == column {
+                self.descending = !self.descending;
+            } else {
+                self.sort = column;
+                self.descending = column.default_descending();
+            }
+        }
+
+        exported
+    }
+}
+
+/// Parse a seed typed by the user, checking it is valid for `jewel_type`
+pub fn parse_seed(jewel_type: JewelType, text: &str) -> Result<u32, String> {
+    let seed: u32 = text
+        .trim()
+        .parse()
+        .map_err(|_| format!("\"{}\" is not a seed number", text.trim()))?;
+
+    if !jewel_type.is_valid_seed(seed) {
+        let range = jewel_type.seed_range();
+        let mut message = format!(
+            "{} seeds range from {} to {}",
+            jewel_type.as_str(),
+            range.start(),
+            range.end()
+        );
+        if jewel_type == JewelType::ElegantHubris {
+            message.push_str(" in steps of 20");
+        }
+        return Err(message);
+    }
+
+    Ok(seed)
+}
+
+/// Render the best socket for each matched mod as a collapsed table
+fn render_summary(ui: &mut egui::Ui, summary: &JewelSummary) {
+    if summary.mods.is_empty() {
+        return;
+    }
+
+    egui::CollapsingHeader::new("Best socket per mod")
+        .id_source("analysis_summary")
+        .show(ui, |ui| {
+            // A jewel sits in one socket, so the rows are alternatives
+            egui::Grid::new("analysis_summary_grid")
+                .num_columns(3)
+                .spacing([20.0, 2.0])
+                .show(ui, |ui| {
+                    for summary in &summary.mods {
+                        ui.label(format!("{}× {}", summary.count, summary.mod_text));
+                        ui.label(&summary.socket_name);
+                        ui.monospace(format!("{:+.1}", summary.contribution));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(jewel_type: JewelType, seed: &str) -> AnalysisState {
+        let mut state = AnalysisState {
+            jewel_type,
+            seed_text: seed.to_string(),
+            ..AnalysisState::default()
+        };
+        state.weights.add_mod("Double Damage", 5.0);
+        state
+    }
+
+    #[test]
+    fn test_build_request() {
+        let (jewel, config) = state(JewelType::LethalPride, " 14032 ").build_request().unwrap();
+
+        assert_eq!(jewel.seed, 14032);
+        assert_eq!(jewel.conqueror, "Kaom");
+        assert_eq!(config.valuable_mods().get("Double Damage"), Some(&5.0));
+    }
+
+    #[test]
+    fn test_build_request_rejects_bad_seeds() {
+        let error = state(JewelType::LethalPride, "abc").build_request().unwrap_err();
+        assert!(error.contains("not a seed"));
+
+        let error = state(JewelType::LethalPride, "9999").build_request().unwrap_err();
+        assert_eq!(error, "Lethal Pride seeds range from 10000 to 18000");
+
+        let error = state(JewelType::ElegantHubris, "2001").build_request().unwrap_err();
+        assert!(error.ends_with("in steps of 20"));
+    }
+
+    #[test]
+    fn test_build_request_requires_weights() {
+        let mut state = state(JewelType::LethalPride, "14032");
+        state.weights.rows[0].mod_text = "  ".to_string();
+
+        assert!(state.build_request().is_err());
+    }
+}
@@ -0,0 +1,21 @@
+//! Analysis timings, shown when enabled in the settings
+
+use poe_item_analyzer_core::analyzers::AnalysisTimings;
+
+/// Render `timings` as a collapsed table of phases
+pub fn render_timings(ui: &mut egui::Ui, id: &str, timings: &AnalysisTimings) {
+    egui::CollapsingHeader::new(format!("⏱ Timings ({:.1?})", timings.total))
+        .id_source(id)
+        .show(ui, |ui| {
+            egui::Grid::new(id).num_columns(2).spacing([20.0, 2.0]).show(ui, |ui| {
+                for (name, duration) in timings.phases() {
+                    ui.label(name);
+                    ui.label(format!("{:.3?}", duration));
+                    ui.end_row();
+                }
+                ui.strong("Total");
+                ui.strong(format!("{:.3?}", timings.total));
+                ui.end_row();
+            });
+        });
+}
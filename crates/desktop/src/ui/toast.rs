@@ -0,0 +1,66 @@
+//! Short-lived notifications in the corner of the window
+
+use std::time::{Duration, Instant};
+
+/// How long a toast stays visible
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// A notification shown until it expires
+struct Toast {
+    message: String,
+    is_error: bool,
+    shown_at: Instant,
+}
+
+/// Notifications stacked in the bottom-right corner
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    /// Show an informational message
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(message.into(), false);
+    }
+
+    /// Show an error message
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(message.into(), true);
+    }
+
+    fn push(&mut self, message: String, is_error: bool) {
+        self.toasts.push(Toast {
+            message,
+            is_error,
+            shown_at: Instant::now(),
+        });
+    }
+
+    /// Render the visible toasts and drop expired ones
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.toasts.retain(|toast| toast.shown_at.elapsed() < TOAST_DURATION);
+
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .show(ctx, |ui| {
+                for toast in &self.toasts {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        let color = if toast.is_error {
+                            egui::Color32::LIGHT_RED
+                        } else {
+                            egui::Color32::LIGHT_GREEN
+                        };
+                        ui.colored_label(color, &toast.message);
+                    });
+                }
+            });
+
+        // Repaint to hide the toasts when they expire
+        ctx.request_repaint_after(Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,604 @@
+//! Weight profile editor
+
+use std::path::Path;
+
+use poe_item_analyzer_api::parser::LutData;
+use poe_item_analyzer_core::analyzers::TimelessJewelConfig;
+
+use crate::profiles::{ProfileStore, WeightProfile};
+
+/// Most autocomplete suggestions shown under the search box
+const MAX_SUGGESTIONS: usize = 10;
+
+/// A row of the weight list
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightRow {
+    /// Mod text to match (e.g., "Double Damage")
+    pub mod_text: String,
+    /// Score per occurrence (negative to avoid the mod)
+    pub weight: f64,
+    /// Multiply the weight by the number in the mod text, matching every
+    /// roll of the stat (e.g., "+# to Strength")
+    pub per_value: bool,
+}
+
+/// A bundled profile whose name is taken by a different saved profile
+#[derive(Debug, Clone, PartialEq)]
+struct ImportCollision {
+    /// Name in the bundle
+    name: String,
+    /// The bundle's weights
+    config: TimelessJewelConfig,
+    /// Name typed to import it under instead
+    rename: String,
+}
+
+/// Editable weight list with saved profiles
+pub struct WeightEditor {
+    /// Where profiles are saved (None if there is no config directory)
+    store: Option<ProfileStore>,
+    /// Saved profiles
+    profiles: Vec<WeightProfile>,
+    /// Name of the profile being edited, if any
+    active: Option<String>,
+    /// Weighted mods
+    pub rows: Vec<WeightRow>,
+    /// Name typed for "Save as"
+    new_name: String,
+    /// Modifier search box
+    search: String,
+    /// Query the cached suggestions were computed for
+    suggestions_query: String,
+    /// Modifier names matching `search`
+    suggestions: Vec<String>,
+    /// Result of the last profile operation
+    status: Option<Result<String, String>>,
+    /// Imported profiles waiting to be renamed, replace a saved one or be skipped
+    collisions: Vec<ImportCollision>,
+}
+
+impl Default for WeightEditor {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl WeightEditor {
+    /// Create an editor, loading the profiles in `store`
+    pub fn new(store: Option<ProfileStore>) -> Self {
+        let mut editor = Self {
+            store,
+            profiles: Vec::new(),
+            active: None,
+            rows: Vec::new(),
+            new_name: String::new(),
+            search: String::new(),
+            suggestions_query: String::new(),
+            suggestions: Vec::new(),
+            status: None,
+            collisions: Vec::new(),
+        };
+        editor.reload_profiles();
+        editor
+    }
+
+    /// Name of the profile being edited, if any
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Config built from the non-empty rows
+    pub fn config(&self) -> TimelessJewelConfig {
+        let mut config = TimelessJewelConfig::new();
+        for row in &self.rows {
+            let mod_text = row.mod_text.trim();
+            if mod_text.is_empty() {
+                continue;
+            }
+            if row.per_value {
+                config.add_mod_per_value(mod_text.to_string(), row.weight);
+            } else {
+                config.add_mod(mod_text.to_string(), row.weight);
+            }
+        }
+        config
+    }
+
+    /// Edit the profile named `name`, or start an empty list for None
+    ///
+    /// Unknown names fall back to an empty list.
+    pub fn select(&mut self, name: Option<&str>) {
+        let profile = name.and_then(|name| self.profiles.iter().find(|p| p.name == name));
+
+        self.rows = profile.map(|p| rows_from_config(&p.config)).unwrap_or_default();
+        self.active = profile.map(|p| p.name.clone());
+    }
+
+    /// Edit `config`, keeping `profile` active if it is still saved
+    ///
+    /// The rows are `config` even if the saved profile has changed since.
+    pub fn restore(&mut self, profile: Option<&str>, config: &TimelessJewelConfig) {
+        self.rows = rows_from_config(config);
+        self.active = profile
+            .filter(|name| self.profiles.iter().any(|p| p.name == *name))
+            .map(str::to_string);
+    }
+
+    /// Add `mod_text` to the list unless it is already there
+    pub fn add_mod(&mut self, mod_text: &str, weight: f64) {
+        if !self.rows.iter().any(|row| row.mod_text == mod_text) {
+            self.rows.push(WeightRow {
+                mod_text: mod_text.to_string(),
+                weight,
+                per_value: false,
+            });
+        }
+    }
+
+    /// Set the weight of `mod_text`, adding it if it isn't in the list
+    pub fn set_weight(&mut self, mod_text: &str, weight: f64) {
+        match self.rows.iter_mut().find(|row| row.mod_text == mod_text) {
+            Some(row) => row.weight = weight,
+            None => self.add_mod(mod_text, weight),
+        }
+    }
+
+    /// Save the list to the active profile, if there is one
+    pub fn save_active(&mut self) {
+        if let Some(name) = self.active.clone() {
+            self.save(&name);
+        }
+    }
+
+    /// Render the active profile dropdown
+    pub fn render_profile_picker(&mut self, ui: &mut egui::Ui) {
+        let mut selected = self.active.clone();
+
+        egui::ComboBox::from_id_source("weight_profile_picker")
+            .selected_text(selected.as_deref().unwrap_or("(unsaved)"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, None, "(unsaved)");
+                for profile in &self.profiles {
+                    ui.selectable_value(&mut selected, Some(profile.name.clone()), &profile.name);
+                }
+            });
+
+        if selected != self.active {
+            self.select(selected.as_deref());
+        }
+    }
+
+    /// Render the weight list, modifier search and profile buttons
+    pub fn render(&mut self, ui: &mut egui::Ui, data: Option<&LutData>) {
+        let mut remove = None;
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut row.mod_text)
+                        .hint_text("Mod text")
+                        .desired_width(260.0),
+                );
+                ui.add(egui::DragValue::new(&mut row.weight).speed(0.1));
+                ui.checkbox(&mut row.per_value, "per value").on_hover_text(
+                    "Multiply the weight by the number in the mod, \
+                     e.g., \"+# to Strength\" counts +30 as 30 × the weight",
+                );
+                if row.weight < 0.0 {
+                    ui.colored_label(egui::Color32::LIGHT_RED, "avoid");
+                }
+                if ui.small_button("✖").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove {
+            self.rows.remove(index);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.small_button("➕ Add row").clicked() {
+                self.rows.push(WeightRow {
+                    mod_text: String::new(),
+                    weight: 1.0,
+                    per_value: false,
+                });
+            }
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("Search modifiers...")
+                    .desired_width(200.0),
+            );
+        });
+
+        self.refresh_suggestions(data);
+
+        let mut picked = None;
+        for name in &self.suggestions {
+            if ui.small_button(format!("➕ {}", name)).clicked() {
+                picked = Some(name.clone());
+            }
+        }
+        if let Some(name) = picked {
+            self.add_mod(&name, 1.0);
+        }
+        if data.is_none() && !self.search.trim().is_empty() {
+            ui.label("Load data to search modifiers");
+        }
+
+        ui.add_space(5.0);
+        self.render_profile_buttons(ui);
+    }
+
+    /// Recompute suggestions when the search text changed
+    fn refresh_suggestions(&mut self, data: Option<&LutData>) {
+        let query = self.search.trim();
+        if query == self.suggestions_query {
+            return;
+        }
+
+        self.suggestions = match data {
+            Some(data) if !query.is_empty() => data
+                .search_modifiers(query, MAX_SUGGESTIONS)
+                .into_iter()
+                .map(|modifier| modifier.display_name.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.suggestions_query = query.to_string();
+    }
+
+    fn render_profile_buttons(&mut self, ui: &mut egui::Ui) {
+        if self.store.is_none() {
+            ui.label("Profiles can't be saved: no config directory");
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            if let Some(active) = self.active.clone() {
+                if ui.button("💾 Save").clicked() {
+                    self.save(&active);
+                }
+                if ui.button("🗑 Delete").clicked() {
+                    self.delete(&active);
+                }
+            }
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_name)
+                    .hint_text("Profile name")
+                    .desired_width(140.0),
+            );
+            let name = self.new_name.trim().to_string();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("Save as"))
+                .clicked()
+            {
+                self.save(&name);
+                self.new_name.clear();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("📦 Export bundle...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_file_name("weight-profiles.json")
+                    .add_filter("Profile bundle", &["json"])
+                    .save_file()
+                {
+                    self.export_bundle(&path);
+                }
+            }
+            if ui.button("📥 Import bundle...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Profile bundle", &["json"])
+                    .pick_file()
+                {
+                    self.import_bundle(&path);
+                }
+            }
+        });
+
+        let mut resolved = None;
+        for (index, collision) in self.collisions.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("\"{}\" already exists:", collision.name));
+                ui.add(egui::TextEdit::singleline(&mut collision.rename).desired_width(140.0));
+                if ui.button("Import as").clicked() {
+                    resolved = Some((index, Some(false)));
+                }
+                if ui.button("Replace").clicked() {
+                    resolved = Some((index, Some(true)));
+                }
+                if ui.button("Skip").clicked() {
+                    resolved = Some((index, None));
+                }
+            });
+        }
+        match resolved {
+            Some((index, Some(replace))) => self.resolve_collision(index, replace),
+            Some((index, None)) => {
+                self.collisions.remove(index);
+            }
+            None => {}
+        }
+
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.colored_label(egui::Color32::GREEN, message);
+            }
+            Some(Err(message)) => {
+                ui.colored_label(egui::Color32::RED, message);
+            }
+            None => {}
+        }
+    }
+
+    fn save(&mut self, name: &str) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        let profile = WeightProfile {
+            name: name.to_string(),
+            config: self.config(),
+        };
+
+        self.status = Some(match store.save(&profile) {
+            Ok(()) => {
+                self.active = Some(name.to_string());
+                Ok(format!("✓ Saved \"{}\"", name))
+            }
+            Err(e) => Err(format!("✗ Could not save \"{}\": {}", name, e)),
+        });
+        self.reload_profiles();
+    }
+
+    /// Write every saved profile to `path` as a bundle
+    fn export_bundle(&mut self, path: &Path) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        self.status = Some(match store.export_bundle(path) {
+            Ok(count) => Ok(format!("✓ Exported {} profile(s) to {}", count, path.display())),
+            Err(e) => Err(format!("✗ Could not export to {}: {}", path.display(), e)),
+        });
+    }
+
+    /// Import the bundle at `path`, keeping profiles with taken names for
+    /// the user to resolve
+    fn import_bundle(&mut self, path: &Path) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        self.status = Some(match store.import_bundle(path) {
+            Ok(collisions) => {
+                let message = match collisions.len() {
+                    0 => format!("✓ Imported {}", path.display()),
+                    count => format!(
+                        "✓ Imported {}; {} profile(s) need a new name",
+                        path.display(),
+                        count
+                    ),
+                };
+                self.collisions = collisions
+                    .into_iter()
+                    .map(|(name, config)| ImportCollision {
+                        rename: format!("{} (imported)", name),
+                        name,
+                        config,
+                    })
+                    .collect();
+                Ok(message)
+            }
+            Err(e) => Err(format!("✗ Could not import {}: {}", path.display(), e)),
+        });
+        self.reload_profiles();
+    }
+
+    /// Save the collision at `index` under its new name, or over the saved
+    /// profile if `replace`
+    fn resolve_collision(&mut self, index: usize, replace: bool) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let collision = &self.collisions[index];
+        let name = if replace {
+            collision.name.clone()
+        } else {
+            collision.rename.trim().to_string()
+        };
+
+        if name.is_empty() || (!replace && store.get(&name).is_some()) {
+            self.status = Some(Err(format!("✗ \"{}\" is taken too", name)));
+            return;
+        }
+
+        let profile = WeightProfile {
+            name: name.clone(),
+            config: collision.config.clone(),
+        };
+        self.status = Some(match store.save(&profile) {
+            Ok(()) => {
+                self.collisions.remove(index);
+                Ok(format!("✓ Saved \"{}\"", name))
+            }
+            Err(e) => Err(format!("✗ Could not save \"{}\": {}", name, e)),
+        });
+        self.reload_profiles();
+    }
+
+    /// Delete a profile; deleting the active one leaves an empty list
+    fn delete(&mut self, name: &str) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        self.status = Some(match store.delete(name) {
+            Ok(()) => Ok(format!("✓ Deleted \"{}\"", name)),
+            Err(e) => Err(format!("✗ Could not delete \"{}\": {}", name, e)),
+        });
+        self.reload_profiles();
+
+        if self.active.as_deref() == Some(name) {
+            self.select(None);
+        }
+    }
+
+    fn reload_profiles(&mut self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        match store.list() {
+            Ok(profiles) => self.profiles = profiles,
+            Err(e) => {
+                self.status = Some(Err(format!(
+                    "✗ Could not read profiles in {}: {}",
+                    store.dir().display(),
+                    e
+                )));
+            }
+        }
+    }
+}
+
+/// Weight rows for `config`, heaviest first
+fn rows_from_config(config: &TimelessJewelConfig) -> Vec<WeightRow> {
+    let mut rows: Vec<WeightRow> = config
+        .valuable_mods()
+        .iter()
+        .map(|(mod_text, weight)| WeightRow {
+            mod_text: mod_text.clone(),
+            weight: *weight,
+            per_value: config.per_value_mods.contains(mod_text),
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.weight
+            .total_cmp(&a.weight)
+            .then_with(|| a.mod_text.cmp(&b.mod_text))
+    });
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_select_and_delete_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut editor = WeightEditor::new(Some(ProfileStore::new(temp_dir.path())));
+
+        editor.add_mod("Onslaught", -1.0);
+        editor.add_mod("Double Damage", 5.0);
+        editor.add_mod("Double Damage", 2.0);
+        editor.save("Offense");
+
+        // Profiles survive a restart
+        let mut editor = WeightEditor::new(Some(ProfileStore::new(temp_dir.path())));
+        assert_eq!(editor.active(), None);
+        editor.select(Some("Offense"));
+        assert_eq!(editor.active(), Some("Offense"));
+        assert_eq!(
+            editor.rows,
+            vec![
+                WeightRow {
+                    mod_text: "Double Damage".to_string(),
+                    weight: 5.0,
+                    per_value: false,
+                },
+                WeightRow {
+                    mod_text: "Onslaught".to_string(),
+                    weight: -1.0,
+                    per_value: false,
+                },
+            ]
+        );
+
+        // Restored weights keep the profile only while it exists
+        let mut config = TimelessJewelConfig::new();
+        config.add_mod("Onslaught".to_string(), 3.0);
+        editor.restore(Some("Offense"), &config);
+        assert_eq!(editor.active(), Some("Offense"));
+        assert_eq!(editor.config(), config);
+        editor.restore(Some("Defense"), &config);
+        assert_eq!(editor.active(), None);
+        editor.restore(Some("Offense"), &config);
+
+        // Weights per value keep their flag
+        let mut per_value = config.clone();
+        per_value.add_mod_per_value("+# to Strength".to_string(), 0.5);
+        editor.restore(None, &per_value);
+        assert!(editor.rows.iter().any(|row| row.per_value && row.mod_text == "+# to Strength"));
+        assert_eq!(editor.config(), per_value);
+        editor.restore(Some("Offense"), &config);
+
+        // Deleting the profile in use falls back to an empty config
+        editor.delete("Offense");
+        assert_eq!(editor.active(), None);
+        assert!(editor.config().valuable_mods().is_empty());
+    }
+
+    #[test]
+    fn test_import_bundle_renames_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut editor = WeightEditor::new(Some(ProfileStore::new(temp_dir.path().join("p"))));
+        editor.add_mod("Double Damage", 5.0);
+        editor.save("Offense");
+
+        let mut config = TimelessJewelConfig::new();
+        config.add_mod("Onslaught".to_string(), 2.0);
+        let path = temp_dir.path().join("bundle.json");
+        crate::profiles::ProfileBundle::new(vec![WeightProfile {
+            name: "Offense".to_string(),
+            config: config.clone(),
+        }])
+        .save(&path)
+        .unwrap();
+
+        editor.import_bundle(&path);
+        assert_eq!(editor.collisions.len(), 1);
+        assert_eq!(editor.collisions[0].rename, "Offense (imported)");
+
+        // A taken name is refused, a free one is saved
+        editor.collisions[0].rename = "Offense".to_string();
+        editor.resolve_collision(0, false);
+        assert_eq!(editor.collisions.len(), 1);
+        editor.collisions[0].rename = "Offense 2".to_string();
+        editor.resolve_collision(0, false);
+        assert!(editor.collisions.is_empty());
+
+        editor.select(Some("Offense 2"));
+        assert_eq!(editor.config(), config);
+        editor.select(Some("Offense"));
+        assert_eq!(editor.config().valuable_mods().get("Double Damage"), Some(&5.0));
+    }
+
+    #[test]
+    fn test_set_weight_updates_existing_row() {
+        let mut editor = WeightEditor::default();
+        editor.add_mod("Onslaught", 1.0);
+        editor.set_weight("Onslaught", 4.0);
+        editor.set_weight("Double Damage", 2.0);
+
+        assert_eq!(editor.rows.len(), 2);
+        assert_eq!(editor.config().valuable_mods().get("Onslaught"), Some(&4.0));
+        assert_eq!(editor.config().valuable_mods().get("Double Damage"), Some(&2.0));
+    }
+
+    #[test]
+    fn test_config_skips_blank_rows() {
+        let mut editor = WeightEditor::default();
+        editor.add_mod(" Onslaught ", 1.0);
+        editor.add_mod("  ", 3.0);
+
+        let config = editor.config();
+        assert_eq!(config.valuable_mods().len(), 1);
+        assert_eq!(config.valuable_mods().get("Onslaught"), Some(&1.0));
+    }
+}